@@ -19,6 +19,7 @@ use std::sync::Arc;
 use crate::auth::HostingSigningIdentityOwned;
 use crate::client::{ChallengeResponse, Client, RegisterDidRequest, RequestUriResponse};
 use crate::error::ClientError;
+use crate::import::{ImportDidRequest, ImportOffer, ImportedDid, MovedDid};
 use crate::locks::ServerLocks;
 
 /// Authenticated handle over a [`Client`]. Pairs a single
@@ -166,6 +167,43 @@ impl AuthedClient {
         )
         .await
     }
+
+    /// Forward to [`Client::start_import`].
+    pub async fn start_import(
+        &self,
+        req: &ImportDidRequest<'_>,
+    ) -> Result<ImportOffer, ClientError> {
+        self.with_access_token(|token| async move { self.client.start_import(&token, req).await })
+            .await
+    }
+
+    /// Forward to [`Client::get_import`].
+    pub async fn get_import(&self, mnemonic: &str) -> Result<ImportOffer, ClientError> {
+        self.with_access_token(
+            |token| async move { self.client.get_import(&token, mnemonic).await },
+        )
+        .await
+    }
+
+    /// Forward to [`Client::complete_import`].
+    pub async fn complete_import(
+        &self,
+        mnemonic: &str,
+        did_log: &str,
+    ) -> Result<ImportedDid, ClientError> {
+        self.with_access_token(|token| async move {
+            self.client.complete_import(&token, mnemonic, did_log).await
+        })
+        .await
+    }
+
+    /// Forward to [`Client::move_did`].
+    pub async fn move_did(&self, mnemonic: &str, did_log: &str) -> Result<MovedDid, ClientError> {
+        self.with_access_token(|token| async move {
+            self.client.move_did(&token, mnemonic, did_log).await
+        })
+        .await
+    }
 }
 
 impl std::fmt::Debug for AuthedClient {
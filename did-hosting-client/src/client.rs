@@ -17,6 +17,7 @@ use url::Url;
 
 use crate::auth::{HostingSigningIdentity, build_authenticate_body, build_refresh_message};
 use crate::error::ClientError;
+use crate::import::{DidLogBody, ImportDidRequest, ImportOffer, ImportedDid, MovedDid};
 use crate::token_store::{SharedTokenStore, TokenData};
 use crate::transport::enforce_transport_security;
 use crate::trust_tasks::{
@@ -423,6 +424,90 @@ impl Client {
         decode_no_body(resp).await
     }
 
+    // ---- Import (migrate-in) ----
+
+    /// `POST /api/import` — verify a DID hosted elsewhere and reserve
+    /// the slot it will move to. See [`crate::import`] for the flow.
+    pub async fn start_import(
+        &self,
+        access_token: &str,
+        req: &ImportDidRequest<'_>,
+    ) -> Result<ImportOffer, ClientError> {
+        let url = self.url("/api/import")?;
+        let resp = self
+            .http
+            .post(url)
+            .headers(self.trust_task_headers(crate::trust_tasks::TASK_DID_IMPORT_1_0)?)
+            .bearer_auth(access_token)
+            .json(req)
+            .send()
+            .await
+            .map_err(|e| ClientError::Network(e.to_string()))?;
+        decode(resp).await
+    }
+
+    /// `GET /api/import/{mnemonic}` — the offer for an import still
+    /// waiting on its move entry.
+    pub async fn get_import(
+        &self,
+        access_token: &str,
+        mnemonic: &str,
+    ) -> Result<ImportOffer, ClientError> {
+        let url = self.url(&format!("/api/import/{}", mnemonic.trim_start_matches('/')))?;
+        let resp = self
+            .http
+            .get(url)
+            .headers(self.trust_task_headers(crate::trust_tasks::TASK_DID_IMPORT_COMPLETE_1_0)?)
+            .bearer_auth(access_token)
+            .send()
+            .await
+            .map_err(|e| ClientError::Network(e.to_string()))?;
+        decode(resp).await
+    }
+
+    /// `POST /api/import/{mnemonic}` — submit the source log with the
+    /// signed move entry appended.
+    pub async fn complete_import(
+        &self,
+        access_token: &str,
+        mnemonic: &str,
+        did_log: &str,
+    ) -> Result<ImportedDid, ClientError> {
+        let url = self.url(&format!("/api/import/{}", mnemonic.trim_start_matches('/')))?;
+        let resp = self
+            .http
+            .post(url)
+            .headers(self.trust_task_headers(crate::trust_tasks::TASK_DID_IMPORT_COMPLETE_1_0)?)
+            .bearer_auth(access_token)
+            .json(&DidLogBody { did_log })
+            .send()
+            .await
+            .map_err(|e| ClientError::Network(e.to_string()))?;
+        decode(resp).await
+    }
+
+    /// `POST /api/move/{mnemonic}` — move a hosted portable DID to
+    /// another domain or path on this host. `did_log` is the current log
+    /// with the signed move entry appended. See [`crate::import`].
+    pub async fn move_did(
+        &self,
+        access_token: &str,
+        mnemonic: &str,
+        did_log: &str,
+    ) -> Result<MovedDid, ClientError> {
+        let url = self.url(&format!("/api/move/{}", mnemonic.trim_start_matches('/')))?;
+        let resp = self
+            .http
+            .post(url)
+            .headers(self.trust_task_headers(crate::trust_tasks::TASK_DID_MOVE_1_0)?)
+            .bearer_auth(access_token)
+            .json(&DidLogBody { did_log })
+            .send()
+            .await
+            .map_err(|e| ClientError::Network(e.to_string()))?;
+        decode(resp).await
+    }
+
     // ---- internal plumbing ----
 
     fn url(&self, path: &str) -> Result<Url, ClientError> {
@@ -0,0 +1,162 @@
+//! Wire types for importing a `did:webvh` DID from another host.
+//!
+//! ## The flow
+//!
+//! A did:webvh DID changes host by appending a log entry, signed with its
+//! own update keys, whose `state.id` names the new location. The host can
+//! verify that entry but cannot write it, so an import takes two calls:
+//!
+//! 1. [`crate::Client::start_import`] with the current log (or its URL).
+//!    The host verifies the chain, requires `portable: true`, reserves a
+//!    slot and answers with an [`ImportOffer`].
+//! 2. Sign a new entry on top of the source log that sets `state.id` to
+//!    [`ImportOffer::target_did`] and lists [`ImportOffer::also_known_as`]
+//!    in `alsoKnownAs`, then send the whole log to
+//!    [`crate::Client::complete_import`].
+//!
+//! The offer is kept for a week; [`crate::Client::get_import`] reads it
+//! back. A DID created without `portable: true` can never be imported —
+//! the parameter can only be set in its first log entry.
+//!
+//! ## Moving within the host
+//!
+//! A portable DID already hosted here moves to another of the host's
+//! domains (or another path) in one call: sign a move entry onto its
+//! current log, keeping the old DID in `alsoKnownAs`, and send the log to
+//! [`crate::Client::move_did`]. The old location keeps resolving, read-only,
+//! for the host's retention window ([`MovedDid::stub_expires_at`]).
+
+use serde::{Deserialize, Serialize};
+
+/// Request body for `POST /api/import`. Set exactly one of `source_url`
+/// and `did_log`.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportDidRequest<'a> {
+    /// The DID's current `did.jsonl` URL. Must be `https`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<&'a str>,
+    /// The current `did.jsonl`, when the host cannot fetch it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub did_log: Option<&'a str>,
+    /// Path to import into. Omitted → the host mints a mnemonic.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<&'a str>,
+    /// Hosting domain. Omitted → the caller's ACL default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain: Option<&'a str>,
+    /// Replace an existing slot you own (admins: any slot).
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub force: bool,
+}
+
+/// What the move entry has to say, from `POST /api/import` and
+/// `GET /api/import/{mnemonic}`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportOffer {
+    /// The reserved slot.
+    pub mnemonic: String,
+    /// Where the DID will resolve once imported.
+    pub did_url: String,
+    /// The DID as it is today.
+    pub source_did: String,
+    /// The `state.id` the move entry must carry.
+    pub target_did: String,
+    /// `versionId` of the last source entry — the move entry follows it.
+    pub source_version_id: Option<String>,
+    /// Entries in the source log.
+    pub source_version_count: u64,
+    /// Must appear in the move entry's `alsoKnownAs`.
+    pub also_known_as: String,
+    /// Unix seconds after which the offer lapses and the import must be
+    /// started again.
+    pub expires_at: u64,
+}
+
+/// Request body for `POST /api/import/{mnemonic}` and
+/// `POST /api/move/{mnemonic}`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DidLogBody<'a> {
+    pub did_log: &'a str,
+}
+
+/// The imported DID, from `POST /api/import/{mnemonic}`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedDid {
+    /// Slot the DID now lives at.
+    pub mnemonic: String,
+    /// The new identifier (the offer's `target_did`).
+    pub did_id: Option<String>,
+    /// Entries in the stored log: the source entries plus the move.
+    pub version_count: u64,
+}
+
+/// The moved DID, from `POST /api/move/{mnemonic}`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MovedDid {
+    /// Slot the DID now lives at. Same as `previous_mnemonic` when only
+    /// the domain changed.
+    pub mnemonic: String,
+    /// The new identifier (the move entry's `state.id`).
+    pub did_id: String,
+    /// Slot the DID was moved from.
+    pub previous_mnemonic: String,
+    /// The identifier before the move, now served as a forwarding stub.
+    pub previous_did_id: String,
+    /// Entries in the stored log, the move included.
+    pub version_count: u64,
+    /// Unix seconds until which the old location keeps resolving.
+    pub stub_expires_at: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_sends_only_what_is_set() {
+        let body = serde_json::to_value(ImportDidRequest {
+            source_url: Some("https://old.example.com/alice/did.jsonl"),
+            path: Some("alice"),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(body["sourceUrl"], "https://old.example.com/alice/did.jsonl");
+        assert_eq!(body["path"], "alice");
+        let obj = body.as_object().unwrap();
+        assert!(!obj.contains_key("didLog"));
+        assert!(!obj.contains_key("domain"));
+        assert!(!obj.contains_key("force"));
+    }
+
+    #[test]
+    fn offer_deserialises_the_daemon_body() {
+        let offer: ImportOffer = serde_json::from_str(
+            r#"{"mnemonic":"alice","didUrl":"https://new.example.com/alice/did.jsonl",
+                "sourceDid":"did:webvh:Q:old.example.com:alice",
+                "targetDid":"did:webvh:Q:new.example.com:alice",
+                "sourceVersionId":"3-Qm","sourceVersionCount":3,
+                "alsoKnownAs":"did:webvh:Q:old.example.com:alice","expiresAt":1700000000}"#,
+        )
+        .unwrap();
+        assert_eq!(offer.target_did, "did:webvh:Q:new.example.com:alice");
+        assert_eq!(offer.source_version_count, 3);
+    }
+
+    #[test]
+    fn moved_did_deserialises_the_daemon_body() {
+        let moved: MovedDid = serde_json::from_str(
+            r#"{"mnemonic":"alice","didId":"did:webvh:Q:new.example.com:alice",
+                "previousMnemonic":"alice",
+                "previousDidId":"did:webvh:Q:old.example.com:alice",
+                "versionCount":4,"stubExpiresAt":1700000000}"#,
+        )
+        .unwrap();
+        assert_eq!(moved.mnemonic, moved.previous_mnemonic);
+        assert_eq!(moved.version_count, 4);
+    }
+}
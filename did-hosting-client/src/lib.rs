@@ -13,6 +13,9 @@
 //!   Bearer-token Authorization for subsequent REST calls.
 //! - **DID lifecycle**: reserve path / check path / atomic
 //!   register-and-publish / publish update / delete.
+//! - **Import**: bring a portable `did:webvh` over from another host.
+//!   See [`import`] for the two-step move, and for moving a hosted DID
+//!   to another of the host's domains.
 //! - **Agent names**: the `/@alice` shortcut surface — bind, release,
 //!   park, resume, and probe availability. See [`agent_names`] for the
 //!   verb semantics and the `alsoKnownAs` rule they enforce.
@@ -58,6 +61,7 @@ pub mod auth;
 pub mod authed;
 pub mod client;
 pub mod error;
+pub mod import;
 pub mod locks;
 pub mod token_store;
 pub mod transport;
@@ -69,6 +73,7 @@ pub use agent_names::{
 pub use authed::AuthedClient;
 pub use client::{ChallengeResponse, Client, RegisterDidRequest, RequestUriResponse};
 pub use error::ClientError;
+pub use import::{ImportDidRequest, ImportOffer, ImportedDid, MovedDid};
 pub use locks::ServerLocks;
 pub use token_store::{HostingTokenStore, InMemoryTokenStore, SharedTokenStore, TokenData};
 
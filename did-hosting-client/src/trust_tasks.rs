@@ -88,6 +88,25 @@ pub const TASK_AGENT_NAME_UPDATE_0_1: &str =
 pub const TASK_AGENT_NAME_REMOVE_0_1: &str =
     "https://trusttasks.org/spec/did-management/agent-name/remove/0.1";
 
+// ---------------------------------------------------------------------------
+// Import (migrate-in) and move
+// ---------------------------------------------------------------------------
+//
+// did-hosting specific: the `did-management` spec has no host-to-host
+// move, so these live under the bare `did-hosting/` namespace.
+
+/// Start an import — `POST /api/import`. Body
+/// [`crate::ImportDidRequest`], response [`crate::ImportOffer`].
+pub const TASK_DID_IMPORT_1_0: &str = "https://trusttasks.org/did-hosting/did/import/1.0";
+
+/// Read back or finish an import — `GET` / `POST /api/import/{mnemonic}`.
+pub const TASK_DID_IMPORT_COMPLETE_1_0: &str =
+    "https://trusttasks.org/did-hosting/did/import-complete/1.0";
+
+/// Move a hosted portable DID to another domain or path —
+/// `POST /api/move/{mnemonic}`. Response [`crate::MovedDid`].
+pub const TASK_DID_MOVE_1_0: &str = "https://trusttasks.org/did-hosting/did/move/1.0";
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,14 +124,19 @@ mod tests {
             TASK_AGENT_NAME_CHECK_0_1,
             TASK_AGENT_NAME_UPDATE_0_1,
             TASK_AGENT_NAME_REMOVE_0_1,
+            TASK_DID_IMPORT_1_0,
+            TASK_DID_IMPORT_COMPLETE_1_0,
+            TASK_DID_MOVE_1_0,
         ];
         for url in all {
-            // Either a canonical /spec/did-management/* task or the
-            // canonical /spec/auth/* family in trusttasks-tf.
+            // A canonical /spec/did-management/* task, the canonical
+            // /spec/auth/* family in trusttasks-tf, or a did-hosting
+            // specific task with no spec counterpart.
             assert!(
                 url.starts_with("https://trusttasks.org/spec/did-management/")
-                    || url.starts_with("https://trusttasks.org/spec/auth/"),
-                "URL must live under spec/did-management/ or spec/auth/: {url}"
+                    || url.starts_with("https://trusttasks.org/spec/auth/")
+                    || url.starts_with("https://trusttasks.org/did-hosting/"),
+                "URL must live under spec/did-management/, spec/auth/ or did-hosting/: {url}"
             );
             // Trailing `{maj}.{min}` per the canonical Trust-Tasks spec.
             let tail = url.rsplit('/').next().unwrap();
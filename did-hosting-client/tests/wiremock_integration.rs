@@ -25,13 +25,13 @@ use std::sync::Arc;
 use did_hosting_client::auth::HostingSigningIdentityOwned;
 use did_hosting_client::trust_tasks::{
     TASK_AUTH_AUTHENTICATE_0_1, TASK_AUTH_CHALLENGE_0_1, TASK_AUTH_REFRESH_0_1,
-    TASK_DID_REGISTER_0_1,
+    TASK_DID_IMPORT_COMPLETE_1_0, TASK_DID_REGISTER_0_1,
 };
 use did_hosting_client::{
     AuthedClient, Client, ClientError, InMemoryTokenStore, ServerLocks, SharedTokenStore, TokenData,
 };
 use serde_json::json;
-use wiremock::matchers::{header, method, path};
+use wiremock::matchers::{body_json, header, method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 const SERVER_ID: &str = "did:example:server";
@@ -274,6 +274,41 @@ async fn register_forwards_method_and_domain_params() {
     assert_eq!(resp.did_url, "https://example.com/alice/did.json");
 }
 
+/// Import completes against the slot path with the extended log in a
+/// camelCase body and the import-complete Trust-Task header.
+#[tokio::test]
+async fn complete_import_posts_extended_log() {
+    let server = mock_server().await;
+    Mock::given(method("POST"))
+        .and(path("/api/import/alice"))
+        .and(header("trust-task", TASK_DID_IMPORT_COMPLETE_1_0))
+        .and(body_json(json!({ "didLog": "<jsonl>" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "mnemonic": "alice",
+            "createdAt": 1,
+            "updatedAt": 2,
+            "versionCount": 4,
+            "didId": "did:webvh:Q:example.com:alice",
+            "owner": HOLDER_DID,
+            "disabled": false,
+            "log": null,
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let c = client_for(&server);
+    let done = c
+        .complete_import("tok", "alice", "<jsonl>")
+        .await
+        .expect("complete");
+    assert_eq!(done.version_count, 4);
+    assert_eq!(
+        done.did_id.as_deref(),
+        Some("did:webvh:Q:example.com:alice")
+    );
+}
+
 /// Network failure surfaces as `ClientError::Network`. We
 /// simulate by pointing at a TCP port that won't accept (bind a
 /// listener, immediately drop it; the kernel guarantees the port
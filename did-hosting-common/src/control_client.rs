@@ -33,6 +33,23 @@ pub struct DidSyncUpdate {
     pub log_content: String,
     pub witness_content: Option<String>,
     pub version_count: u64,
+    /// The DID's identifier before an in-place domain move, while its old
+    /// host should still resolve the slot. Absent otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_did_id: Option<String>,
+    /// Entries rolled back off the end of `log_content` and still
+    /// restorable, in log order. Passed on to watchers (see
+    /// [`crate::SyncDidRequest::rolled_back`]).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rolled_back: Vec<String>,
+    /// The control plane's signature over `rolled_back` (see
+    /// [`crate::RollbackProof`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollback_proof: Option<crate::RollbackProof>,
+    /// did:webs only: the `did.json` served next to the KEL in
+    /// `log_content`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub did_document: Option<String>,
 }
 
 /// Request body for `POST /api/control/register-service`.
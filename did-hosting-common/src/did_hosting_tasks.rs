@@ -183,6 +183,13 @@ pub static TASK_ADMIN_ACTION_1_0: LazyLock<TrustTask> = LazyLock::new(|| {
     TrustTask::new("https://trusttasks.org/did-hosting/admin-action/1.0").expect("static")
 });
 
+/// `did-hosting/task-consent/status/1.0` — where a stored consent request
+/// stands (`pending` / `granted` / `denied` / `expired`). Polled by the
+/// requester of a gated action before repeating it.
+pub static TASK_CONSENT_STATUS_1_0: LazyLock<TrustTask> = LazyLock::new(|| {
+    TrustTask::new("https://trusttasks.org/did-hosting/task-consent/status/1.0").expect("static")
+});
+
 // -- DID provisioning lifecycle --------------------------------------------
 //
 // Canonical Trust-Task spec URIs only (Phase 3 of the cross-repo
@@ -285,6 +292,29 @@ pub static TASK_DID_ROLLBACK_0_1: LazyLock<TrustTask> = LazyLock::new(|| {
     TrustTask::new("https://trusttasks.org/spec/did-management/did/rollback/0.1").expect("static")
 });
 
+/// Re-apply a log entry a rollback archived. did-hosting specific: the
+/// `did-management` spec has no undo.
+pub static TASK_DID_UNDO_ROLLBACK_1_0: LazyLock<TrustTask> = LazyLock::new(|| {
+    TrustTask::new("https://trusttasks.org/did-hosting/did/undo-rollback/1.0").expect("static")
+});
+
+/// Migrate a `did:webvh` in from another host: verify the source log and
+/// reserve the slot it will move to. did-hosting specific.
+pub static TASK_DID_IMPORT_1_0: LazyLock<TrustTask> = LazyLock::new(|| {
+    TrustTask::new("https://trusttasks.org/did-hosting/did/import/1.0").expect("static")
+});
+
+/// Finish an import with the owner-signed move entry.
+pub static TASK_DID_IMPORT_COMPLETE_1_0: LazyLock<TrustTask> = LazyLock::new(|| {
+    TrustTask::new("https://trusttasks.org/did-hosting/did/import-complete/1.0").expect("static")
+});
+
+/// Move a hosted `did:webvh` to another hosted domain or path with a
+/// tenant-signed move entry. did-hosting specific.
+pub static TASK_DID_MOVE_1_0: LazyLock<TrustTask> = LazyLock::new(|| {
+    TrustTask::new("https://trusttasks.org/did-hosting/did/move/1.0").expect("static")
+});
+
 // Agent names — bind/release/park/resume a human-memorable `/@name` on a
 // hosted DID, on their canonical spec URIs. `update` carries the
 // declarative `state: active | parked` that replaced the retired
@@ -465,6 +495,46 @@ pub static TASK_CONFIG_1_0: LazyLock<TrustTask> = LazyLock::new(|| {
     TrustTask::new("https://trusttasks.org/did-hosting/config/1.0").expect("static")
 });
 
+// Quota usage: current consumption against the configured limits.
+pub static TASK_USAGE_OWNER_1_0: LazyLock<TrustTask> = LazyLock::new(|| {
+    TrustTask::new("https://trusttasks.org/did-hosting/usage/owner/1.0").expect("static")
+});
+pub static TASK_USAGE_DOMAIN_1_0: LazyLock<TrustTask> = LazyLock::new(|| {
+    TrustTask::new("https://trusttasks.org/did-hosting/usage/domain/1.0").expect("static")
+});
+
+// Control-plane audit log: the hash-chained record of every mutation.
+pub static TASK_AUDIT_LIST_1_0: LazyLock<TrustTask> = LazyLock::new(|| {
+    TrustTask::new("https://trusttasks.org/did-hosting/audit/list/1.0").expect("static")
+});
+
+// Outbound webhook subscriptions for DID / domain lifecycle events.
+pub static TASK_WEBHOOK_LIST_1_0: LazyLock<TrustTask> = LazyLock::new(|| {
+    TrustTask::new("https://trusttasks.org/did-hosting/webhook/list/1.0").expect("static")
+});
+pub static TASK_WEBHOOK_UPDATE_1_0: LazyLock<TrustTask> = LazyLock::new(|| {
+    TrustTask::new("https://trusttasks.org/did-hosting/webhook/update/1.0").expect("static")
+});
+
+// Session inventory and revocation (logout, revoke-by-DID).
+pub static TASK_AUTH_SESSION_LIST_1_0: LazyLock<TrustTask> = LazyLock::new(|| {
+    TrustTask::new("https://trusttasks.org/did-hosting/auth/session/list/1.0").expect("static")
+});
+pub static TASK_AUTH_SESSION_REVOKE_1_0: LazyLock<TrustTask> = LazyLock::new(|| {
+    TrustTask::new("https://trusttasks.org/did-hosting/auth/session/revoke/1.0").expect("static")
+});
+
+// Scoped API keys for machine clients: admin-minted, listed, revoked.
+pub static TASK_API_KEY_LIST_1_0: LazyLock<TrustTask> = LazyLock::new(|| {
+    TrustTask::new("https://trusttasks.org/did-hosting/api-key/list/1.0").expect("static")
+});
+pub static TASK_API_KEY_CREATE_1_0: LazyLock<TrustTask> = LazyLock::new(|| {
+    TrustTask::new("https://trusttasks.org/did-hosting/api-key/create/1.0").expect("static")
+});
+pub static TASK_API_KEY_REVOKE_1_0: LazyLock<TrustTask> = LazyLock::new(|| {
+    TrustTask::new("https://trusttasks.org/did-hosting/api-key/revoke/1.0").expect("static")
+});
+
 // Registry admin operations. Distinct from `TASK_SERVER_REGISTER_1_0`,
 // which is the *server's* self-registration; these are the *admin's*
 // CRUD over the registry table.
@@ -563,6 +633,7 @@ mod tests {
             &TASK_CONSENT_DECISION_0_1,
             &TASK_CONSENT_DECISION_RESPONSE_0_1,
             &TASK_ADMIN_ACTION_1_0,
+            &TASK_CONSENT_STATUS_1_0,
             &TASK_DID_CHECK_NAME_0_1,
             &TASK_DID_CHECK_NAME_RESPONSE_0_1,
             &TASK_DID_REGISTER_0_1,
@@ -580,6 +651,10 @@ mod tests {
             &TASK_ME_DOMAINS_RESPONSE_0_1,
             &TASK_DID_SET_STATE_0_1,
             &TASK_DID_ROLLBACK_0_1,
+            &TASK_DID_UNDO_ROLLBACK_1_0,
+            &TASK_DID_IMPORT_1_0,
+            &TASK_DID_IMPORT_COMPLETE_1_0,
+            &TASK_DID_MOVE_1_0,
             &TASK_AGENT_NAME_UPDATE_0_1,
             &TASK_AGENT_NAME_REMOVE_0_1,
             &TASK_AGENT_NAME_CHECK_0_1,
@@ -615,6 +690,16 @@ mod tests {
             &TASK_TIMESERIES_DID_1_0,
             &TASK_SERVICES_OVERVIEW_1_0,
             &TASK_CONFIG_1_0,
+            &TASK_USAGE_OWNER_1_0,
+            &TASK_USAGE_DOMAIN_1_0,
+            &TASK_AUDIT_LIST_1_0,
+            &TASK_WEBHOOK_LIST_1_0,
+            &TASK_WEBHOOK_UPDATE_1_0,
+            &TASK_AUTH_SESSION_LIST_1_0,
+            &TASK_AUTH_SESSION_REVOKE_1_0,
+            &TASK_API_KEY_LIST_1_0,
+            &TASK_API_KEY_CREATE_1_0,
+            &TASK_API_KEY_REVOKE_1_0,
             &TASK_REGISTRY_LIST_1_0,
             &TASK_REGISTRY_ADMIN_REGISTER_1_0,
             &TASK_REGISTRY_GET_1_0,
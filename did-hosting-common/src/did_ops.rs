@@ -49,7 +49,8 @@ pub struct DidRecord {
     // record made `list_dids` pull content bytes on every scan; the
     // split keeps metadata reads cheap.
     /// DID method this record was registered under. Always one of the
-    /// enabled-at-compile-time methods (`webvh`, `web`); the daemon
+    /// enabled-at-compile-time methods (`webvh`, `web`, and `webs` /
+    /// `webplus` when their features are on); the daemon
     /// rejects any other value on the write path. Legacy records
     /// (pre-T13 migration) default to `"webvh"` via the `#[serde(default)]`
     /// fallback in [`Self::default_method`].
@@ -104,6 +105,54 @@ pub struct DidRecord {
     /// the index so the two cannot drift.
     #[serde(default)]
     pub agent_names: Vec<AgentNameEntry>,
+
+    /// When the DID's log was published ending in a `deactivated: true`
+    /// entry. `None` for a live DID.
+    ///
+    /// Deactivation is terminal: no further log is accepted for the slot,
+    /// the DID stops counting against its owner's quota, and the log keeps
+    /// being served so verifiers can see the deactivation. Deleting it is
+    /// refused until `hosting.deactivated_did_retention` has passed (see
+    /// [`Self::deletable_at`]).
+    ///
+    /// `#[serde(default)]`: records written before this field existed read
+    /// as live, and are marked the next time a deactivating log lands.
+    #[serde(default)]
+    pub deactivated_at: Option<u64>,
+
+    /// [`crate::sync_head`] of the content stored at `content_log_key` /
+    /// `content_witness_key`, so the watcher sync listing does not read
+    /// content for every DID.
+    ///
+    /// Every write path that touches either key either recomputes it (see
+    /// [`Self::set_sync_head`]) or clears it. `None` — a record written
+    /// before this field existed, a slot with no log yet, or a path that
+    /// cleared it — makes the listing hash the content itself, so a missing
+    /// head costs a read but never hides an update.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sync_head: Option<String>,
+}
+
+impl DidRecord {
+    /// Record the [`crate::sync_head`] of the log and witness file about to
+    /// be written alongside this record.
+    #[cfg(feature = "server-core")]
+    pub fn set_sync_head(&mut self, log: &[u8], witness: Option<&[u8]>) {
+        self.sync_head = Some(crate::sync_head(log, witness));
+    }
+
+    /// Whether the DID has been deactivated.
+    pub fn is_deactivated(&self) -> bool {
+        self.deactivated_at.is_some()
+    }
+
+    /// Unix seconds from which a deactivated DID may be deleted, given the
+    /// retention window in seconds. `None` for a live DID, which may be
+    /// deleted at any time.
+    pub fn deletable_at(&self, retention_secs: u64) -> Option<u64> {
+        self.deactivated_at
+            .map(|at| at.saturating_add(retention_secs))
+    }
 }
 
 /// One agent name bound to a hosted DID.
@@ -176,6 +225,61 @@ pub fn content_witness_key(mnemonic: &str) -> String {
     format!("content:{mnemonic}:witness")
 }
 
+/// A log entry dropped by a rollback, kept for `undo-rollback` until its
+/// retention window lapses. Zero-padded microseconds sort newest last.
+pub fn archived_entry_key(mnemonic: &str, archived_micros: u128) -> String {
+    format!("archive:{mnemonic}:{archived_micros:020}")
+}
+
+/// Prefix of every [`archived_entry_key`] for one DID. The trailing `:`
+/// keeps `foo` from matching `foo/bar` (`:` is not a path character).
+pub fn archived_entry_prefix(mnemonic: &str) -> String {
+    format!("archive:{mnemonic}:")
+}
+
+/// An import in progress: the verified source log and the DID it must move
+/// to, kept until the owner submits the signed move entry.
+pub fn pending_import_key(mnemonic: &str) -> String {
+    format!("import:{mnemonic}")
+}
+
+/// A DID that moved to another slot or domain, kept (control-side) while
+/// its old location still answers. See `did-hosting-control::did_move`.
+pub fn moved_stub_key(mnemonic: &str) -> String {
+    format!("moved:{mnemonic}")
+}
+
+/// The control plane's running usage counter for a hosting domain (DIDs
+/// and log bytes). See `did-hosting-control::quota`.
+pub fn domain_usage_key(domain: &str) -> String {
+    format!("domain_usage:{domain}")
+}
+
+/// Server-side: the identifier a DID had before an in-place domain move.
+/// Its host keeps resolving the slot until the control plane clears it.
+pub fn previous_did_key(mnemonic: &str) -> String {
+    format!("previous_did:{mnemonic}")
+}
+
+/// Server-side: the entries the control plane rolled back off the end of
+/// the current log, as a JSON array. Sent on to watchers with each push.
+pub fn rolled_back_key(mnemonic: &str) -> String {
+    format!("rolled_back:{mnemonic}")
+}
+
+/// Server-side: the [`crate::RollbackProof`] vouching for the entries under
+/// [`rolled_back_key`]. Kept and cleared together with them.
+pub fn rollback_proof_key(mnemonic: &str) -> String {
+    format!("rollback_proof:{mnemonic}")
+}
+
+/// Sidecar `did.json` for methods whose stored log is not itself the
+/// served document — today `did:webs`, where [`content_log_key`] holds the
+/// `keri.cesr` KEL and the rendered DID document lives here.
+pub fn content_did_doc_key(mnemonic: &str) -> String {
+    format!("content:{mnemonic}:did_doc")
+}
+
 pub fn owner_key(did: &str, mnemonic: &str) -> String {
     format!("owner:{did}:{mnemonic}")
 }
@@ -321,6 +425,10 @@ pub fn validate_did_id_matches_request(
     use didwebvh_rs::url::WebVHURL;
     use url::Url;
 
+    if !did_id.starts_with("did:webvh:") {
+        return validate_method_did_id_matches_request(did_id, request_path, server_base_url);
+    }
+
     let id_parsed = WebVHURL::parse_did_url(did_id)
         .map_err(|e| format!("did_log's DID identifier {did_id} is not a valid did:webvh: {e}"))?;
 
@@ -361,6 +469,43 @@ pub fn validate_did_id_matches_request(
     Ok(())
 }
 
+/// [`validate_did_id_matches_request`] for the methods whose identifier
+/// ends in the slot path itself (`did:webs`, `did:webplus`): the host
+/// (with any port as `%3A`) must be this server's, and the path segments
+/// must be the requested path's, AID or root self-hash included.
+fn validate_method_did_id_matches_request(
+    did_id: &str,
+    request_path: &str,
+    server_base_url: &str,
+) -> Result<(), String> {
+    let method = crate::method::parse_did_method(did_id).map_err(|e| e.to_string())?;
+    let parsed = crate::method::method_by_name(method)
+        .ok_or_else(|| format!("did:{method} is not enabled on this server"))?
+        .parse_identifier(did_id)
+        .map_err(|e| e.to_string())?;
+
+    let base = url::Url::parse(server_base_url)
+        .map_err(|e| format!("internal: server_base_url {server_base_url} is not a URL: {e}"))?;
+    let host = base.host_str().unwrap_or_default();
+    let expected_domain = match base.port() {
+        Some(port) => format!("{host}%3A{port}"),
+        None => host.to_string(),
+    };
+    if !parsed.domain.eq_ignore_ascii_case(&expected_domain) {
+        return Err(format!(
+            "DID host '{}' does not match this server's host '{expected_domain}'",
+            parsed.domain
+        ));
+    }
+    if parsed.path != request_path.replace('/', ":") {
+        return Err(format!(
+            "DID path '{}' does not resolve at the requested path '{request_path}'",
+            parsed.path.replace(':', "/")
+        ));
+    }
+    Ok(())
+}
+
 /// Extract the `did:webvh:...` identifier from the last non-blank line of
 /// JSONL content via the `state.id` field. Trailing blank lines are skipped.
 pub fn extract_did_id(jsonl_content: &str) -> Option<String> {
@@ -413,11 +558,15 @@ pub fn extract_agent_names(jsonl_content: &str, domain: &str) -> Vec<String> {
     let Ok(value) = serde_json::from_str::<serde_json::Value>(last_line) else {
         return Vec::new();
     };
-    let Some(entries) = value
-        .get("state")
-        .and_then(|s| s.get("alsoKnownAs"))
-        .and_then(|a| a.as_array())
-    else {
+    match value.get("state") {
+        Some(state) => agent_names_from_doc(state, domain),
+        None => Vec::new(),
+    }
+}
+
+/// [`extract_agent_names`] over a DID document rather than a webvh log.
+pub fn agent_names_from_doc(doc: &serde_json::Value, domain: &str) -> Vec<String> {
+    let Some(entries) = doc.get("alsoKnownAs").and_then(|a| a.as_array()) else {
         return Vec::new();
     };
 
@@ -437,6 +586,142 @@ pub fn extract_agent_names(jsonl_content: &str, domain: &str) -> Vec<String> {
     names
 }
 
+// ---------------------------------------------------------------------------
+// Method-aware inspection
+// ---------------------------------------------------------------------------
+
+/// What the write paths record about a submitted DID log, read the same
+/// way whatever its method.
+#[derive(Debug, Clone)]
+pub struct LogSummary {
+    /// Read off the log by [`detect_log_method`], never taken from the
+    /// caller.
+    pub method: &'static str,
+    pub did_id: String,
+    /// The DID document the log currently resolves to.
+    pub document: serde_json::Value,
+    pub deactivated: bool,
+}
+
+impl LogSummary {
+    /// Value for [`DidRecord::services`].
+    pub fn services(&self) -> Option<Vec<String>> {
+        Some(crate::did::service_types_from_doc(&self.document))
+    }
+
+    /// Agent names the document claims on `domain`.
+    pub fn agent_names(&self, domain: &str) -> Vec<String> {
+        agent_names_from_doc(&self.document, domain)
+    }
+}
+
+/// File name a `method` DID resolves at, under its slot's URL.
+pub fn resolution_file(method: &str) -> &'static str {
+    match method {
+        "web" | "webs" => "did.json",
+        "webplus" => "did-documents.jsonl",
+        _ => "did.jsonl",
+    }
+}
+
+/// Which method a stored log belongs to, from its shape alone: a KERI
+/// event stream is `did:webs`, a JSONL whose entries carry `state` is
+/// `did:webvh`, and one whose lines are self-hashed documents is
+/// `did:webplus`. `None` for anything else. Says nothing about whether
+/// the method is enabled or the log valid; see [`inspect_did_log`].
+pub fn detect_log_method(log: &str) -> Option<&'static str> {
+    let head = log.trim_start();
+    if head.starts_with(r#"{"v":"KERI"#) {
+        return Some("webs");
+    }
+    let first: serde_json::Value = serde_json::from_str(head.lines().next()?).ok()?;
+    if first.get("versionId").is_some() && first.get("state").is_some() {
+        Some("webvh")
+    } else if first.get("selfHash").is_some() {
+        Some("webplus")
+    } else {
+        None
+    }
+}
+
+/// Verify a submitted log as its own method and summarise it.
+///
+/// `did_doc` is the `did.json` a did:webs KEL is served as; it is required
+/// for webs, checked against the KEL, and refused for every other method.
+/// webvh logs get full proof verification ([`verify_did_log_proofs`]);
+/// webs and webplus get their method's chain checks.
+pub fn inspect_did_log(log: &str, did_doc: Option<&str>) -> Result<LogSummary, String> {
+    let method =
+        detect_log_method(log).ok_or("content is not a did:webvh, did:webs or did:webplus log")?;
+    if crate::method::method_by_name(method).is_none() {
+        return Err(format!("did:{method} is not enabled on this server"));
+    }
+    if did_doc.is_some() && method != "webs" {
+        return Err(format!(
+            "a separate DID document is only accepted for did:webs, not did:{method}"
+        ));
+    }
+
+    match method {
+        "webvh" => {
+            verify_did_log_proofs(log)?;
+            let last_line = log
+                .lines()
+                .rfind(|l| !l.trim().is_empty())
+                .unwrap_or_default();
+            let document = serde_json::from_str::<serde_json::Value>(last_line)
+                .ok()
+                .and_then(|v| v.get("state").cloned())
+                .ok_or("did.jsonl latest entry has no state")?;
+            Ok(LogSummary {
+                method,
+                did_id: extract_did_id(log).ok_or("did.jsonl latest entry has no state.id")?,
+                document,
+                deactivated: extract_log_metadata(log).deactivated,
+            })
+        }
+        #[cfg(feature = "method-webplus")]
+        "webplus" => {
+            let ledger = crate::method::webplus::parse_microledger(log.as_bytes())
+                .map_err(|e| e.to_string())?;
+            let document: serde_json::Value =
+                serde_json::from_str(&ledger.latest().document).map_err(|e| e.to_string())?;
+            // An empty `updateRules` is the method's deactivation.
+            let deactivated = document
+                .get("updateRules")
+                .and_then(|r| r.as_object())
+                .is_some_and(|r| r.is_empty());
+            Ok(LogSummary {
+                method,
+                did_id: ledger.did,
+                document,
+                deactivated,
+            })
+        }
+        #[cfg(feature = "method-webs")]
+        "webs" => {
+            let doc = did_doc.ok_or("did:webs needs its did.json alongside the keri.cesr KEL")?;
+            crate::method::webs::validate_did_document(doc.as_bytes(), log.as_bytes())
+                .map_err(|e| e.to_string())?;
+            let document: serde_json::Value =
+                serde_json::from_str(doc).map_err(|e| e.to_string())?;
+            let did_id = document
+                .get("id")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string();
+            Ok(LogSummary {
+                method,
+                did_id,
+                document,
+                // KERI abandonment is not read here; a webs DID stays live.
+                deactivated: false,
+            })
+        }
+        other => Err(format!("did:{other} is not enabled on this server")),
+    }
+}
+
 /// Parse JSONL content and extract metadata from the log entries.
 pub fn extract_log_metadata(jsonl_content: &str) -> LogMetadata {
     let lines: Vec<&str> = jsonl_content.lines().collect();
@@ -576,6 +861,32 @@ pub fn parse_log_entries(jsonl_content: &str) -> Vec<LogEntryInfo> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn log_method_is_read_off_the_log() {
+        assert_eq!(
+            detect_log_method(r#"{"versionId":"1-x","state":{"id":"did:webvh:a"}}"#),
+            Some("webvh")
+        );
+        assert_eq!(
+            detect_log_method(r#"{"v":"KERI10JSON00012b_","t":"icp"}"#),
+            Some("webs")
+        );
+        assert_eq!(
+            detect_log_method(r#"{"id":"did:webplus:a","selfHash":"uHiB"}"#),
+            Some("webplus")
+        );
+        assert_eq!(detect_log_method(r#"{"id":"did:web:a"}"#), None);
+        assert_eq!(detect_log_method("not json"), None);
+    }
+
+    #[test]
+    fn separate_document_is_refused_outside_webs() {
+        let log = r#"{"versionId":"1-x","state":{"id":"did:webvh:a"}}"#;
+        let err = inspect_did_log(log, Some("{}")).unwrap_err();
+        assert!(err.contains("only accepted for did:webs"), "got: {err}");
+        assert!(inspect_did_log("{}", None).is_err());
+    }
+
     // ---- T12: DidRecord new fields backwards-compat ----
 
     #[test]
@@ -617,6 +928,8 @@ mod tests {
             domain: "tenant-a.example.com".into(),
             services: None,
             agent_names: Vec::new(),
+            deactivated_at: None,
+            sync_head: None,
         };
         let json = serde_json::to_string(&original).unwrap();
         let back: DidRecord = serde_json::from_str(&json).unwrap();
@@ -28,8 +28,8 @@
 //! at compile time via `#[cfg(feature = "method-...")]`. Disabling a
 //! method's feature removes its arm from the dispatcher (and its
 //! resolution route from the router — see T25). The default workspace
-//! build enables `method-webvh` + `method-web`; `method-webs` /
-//! `method-webplus` are scaffolded for future work.
+//! build enables `method-webvh` + `method-web`; `method-webs` (CESR
+//! codec) and `method-webplus` (self-hash hashers) are opt-in.
 //!
 //! ## T10 scope
 //!
@@ -175,12 +175,20 @@ pub fn method_by_name(name: &str) -> Option<&'static dyn DidMethod> {
     static WEBVH: webvh::Webvh = webvh::Webvh;
     #[cfg(feature = "method-web")]
     static WEB: web::Web = web::Web;
+    #[cfg(feature = "method-webs")]
+    static WEBS: webs::Webs = webs::Webs;
+    #[cfg(feature = "method-webplus")]
+    static WEBPLUS: webplus::Webplus = webplus::Webplus;
 
     match name {
         #[cfg(feature = "method-webvh")]
         "webvh" => Some(&WEBVH),
         #[cfg(feature = "method-web")]
         "web" => Some(&WEB),
+        #[cfg(feature = "method-webs")]
+        "webs" => Some(&WEBS),
+        #[cfg(feature = "method-webplus")]
+        "webplus" => Some(&WEBPLUS),
         _ => None,
     }
 }
@@ -200,6 +208,10 @@ pub fn enabled_methods() -> &'static [&'static str] {
         "webvh",
         #[cfg(feature = "method-web")]
         "web",
+        #[cfg(feature = "method-webs")]
+        "webs",
+        #[cfg(feature = "method-webplus")]
+        "webplus",
     ]
 }
 
@@ -301,7 +313,28 @@ mod tests {
         assert!(enabled_methods().contains(&"web"));
     }
 
-    #[cfg(not(any(feature = "method-webvh", feature = "method-web")))]
+    #[cfg(feature = "method-webs")]
+    #[test]
+    fn dispatcher_routes_webs() {
+        let m = method_by_name("webs").expect("method-webs enabled");
+        assert_eq!(m.name(), "webs");
+        assert!(enabled_methods().contains(&"webs"));
+    }
+
+    #[cfg(feature = "method-webplus")]
+    #[test]
+    fn dispatcher_routes_webplus() {
+        let m = method_by_name("webplus").expect("method-webplus enabled");
+        assert_eq!(m.name(), "webplus");
+        assert!(enabled_methods().contains(&"webplus"));
+    }
+
+    #[cfg(not(any(
+        feature = "method-webvh",
+        feature = "method-web",
+        feature = "method-webs",
+        feature = "method-webplus"
+    )))]
     #[test]
     fn enabled_methods_is_empty_when_no_method_feature() {
         assert!(enabled_methods().is_empty());
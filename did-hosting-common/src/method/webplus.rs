@@ -1,19 +1,716 @@
-//! `did:webplus` — **NOT IMPLEMENTED IN THIS RELEASE**.
+//! `did:webplus` implementation of [`DidMethod`].
 //!
-//! Per `docs/multi-method-hosting-spec.md` §1 and §2, `did:webplus` is
-//! a scaffolded compile-time target — the feature flag exists so the
-//! `DidMethod` trait surface can be exercised against a future
-//! method without restructuring, but no impl ships in this release.
+//! Per `docs/multi-method-hosting-spec.md` §6.1. Off by default — enable
+//! with `--features method-webplus`. A did:webplus DID's history is a
+//! microledger: `did-documents.jsonl`, one complete DID document per
+//! line, each self-hashed and chained to its predecessor.
 //!
-//! Enabling `--features method-webplus` produces this compile error
-//! on purpose. A future release will replace this stub with a real
-//! impl following the pattern in `super::webvh`.
+//! ## Identifier shape
+//!
+//! `did:webplus:{host}[:{path-segment}…]:{root-self-hash}`
+//!
+//! - `{host}` may carry a non-default port encoded as `%3A`, same as
+//!   did:web / did:webvh.
+//! - `{root-self-hash}` is always the **last** segment: the self-hash of
+//!   the root (version `0`) document. As with did:webs, it is also a URL
+//!   path segment, so [`ParsedDid::path`] carries it as its final segment
+//!   and it is surfaced on its own as [`ParsedDid::scid`].
+//!
+//! ## Artifacts and storage
+//!
+//! The microledger is stored at [`crate::did_ops::content_log_key`] and
+//! served at `https://{host}/{path…}/did-documents.jsonl`. Individual
+//! versions are served from the same bytes — no sidecar — at
+//! `…/did/selfHash/{selfHash}.json` and `…/did/versionId/{versionId}.json`;
+//! [`Microledger::by_self_hash`] / [`Microledger::by_version_id`] pick
+//! the line.
+//!
+//! ## Self-hashes
+//!
+//! A self-hash is `u` + base64url-no-pad of a multihash — Blake3-256
+//! (`0x1e`) or SHA2-256 (`0x12`). It is computed over the document's RFC
+//! 8785 (JCS) serialisation with every *self-hash slot* set to the
+//! all-zero placeholder of the same hash function. The slots are every
+//! occurrence of the document's own `selfHash` value: the `selfHash`
+//! field itself and, in the root document, the DID's last segment
+//! wherever the DID appears (`id`, verification method ids and
+//! controllers).
+//!
+//! ## Validation scope
+//!
+//! [`parse_microledger`] checks every line's self-hash and the chaining
+//! rules: the root has `versionId` `0`, no `prevDIDDocumentSelfHash`, and
+//! an `id` ending in its own self-hash; every later document keeps the
+//! same `id`, increments `versionId`, names its predecessor's `selfHash`
+//! as `prevDIDDocumentSelfHash`, and moves `validFrom` strictly forward.
+//! A document whose `updateRules` is empty (`{}`) deactivates the DID, so
+//! nothing may follow it. Evaluating `updateRules` against the update's
+//! `proofs` (key signatures) is **not** performed here — same split as
+//! webvh and webs, where the trait method is the cheap transport-layer
+//! gate and full verification belongs to the resolver.
 
 #![cfg(feature = "method-webplus")]
 
-compile_error!(
-    "method-webplus is not implemented in this release. \
-     See docs/multi-method-hosting-spec.md §1 for the planned scope \
-     and §6 for the DidMethod trait the impl will satisfy. To build \
-     without it, drop --features method-webplus."
-);
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, FixedOffset};
+use sha2::Digest;
+
+use super::{DidMethod, MethodError, ParsedDid};
+
+/// Resolution-URL file name of the microledger.
+pub const DID_DOCUMENTS_FILE: &str = "did-documents.jsonl";
+
+/// Multibase prefix of a self-hash (base64url, no padding).
+const MULTIBASE_BASE64URL: char = 'u';
+
+/// Every supported hash function produces a 32-byte digest.
+const DIGEST_LEN: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Webplus;
+
+/// Hash function a self-hash was computed with, from its multihash code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelfHashAlgorithm {
+    Blake3,
+    Sha256,
+}
+
+impl SelfHashAlgorithm {
+    fn multihash_code(self) -> u8 {
+        match self {
+            Self::Blake3 => 0x1e,
+            Self::Sha256 => 0x12,
+        }
+    }
+
+    fn from_multihash_code(code: u8) -> Option<Self> {
+        match code {
+            0x1e => Some(Self::Blake3),
+            0x12 => Some(Self::Sha256),
+            _ => None,
+        }
+    }
+
+    fn digest(self, data: &[u8]) -> [u8; DIGEST_LEN] {
+        match self {
+            Self::Blake3 => *blake3::hash(data).as_bytes(),
+            Self::Sha256 => sha2::Sha256::digest(data).into(),
+        }
+    }
+
+    /// The all-zero self-hash every slot is set to while hashing.
+    pub fn placeholder(self) -> String {
+        encode_self_hash(self, &[0u8; DIGEST_LEN])
+    }
+}
+
+/// One document of a parsed microledger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DidDocumentVersion {
+    pub version_id: u64,
+    pub self_hash: String,
+    pub valid_from: DateTime<FixedOffset>,
+    /// The document exactly as stored (one `did-documents.jsonl` line,
+    /// without its newline) — what the per-version URLs serve.
+    pub document: String,
+}
+
+/// A validated `did-documents.jsonl`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Microledger {
+    /// The DID every document in the ledger carries as `id`.
+    pub did: String,
+    /// Documents in ledger order; `documents[n].version_id == n`.
+    pub documents: Vec<DidDocumentVersion>,
+}
+
+impl Microledger {
+    /// The current document.
+    pub fn latest(&self) -> &DidDocumentVersion {
+        // `parse_microledger` refuses an empty ledger.
+        self.documents
+            .last()
+            .expect("parse_microledger guarantees a root document")
+    }
+
+    pub fn by_self_hash(&self, self_hash: &str) -> Option<&DidDocumentVersion> {
+        self.documents.iter().find(|d| d.self_hash == self_hash)
+    }
+
+    pub fn by_version_id(&self, version_id: u64) -> Option<&DidDocumentVersion> {
+        // Version ids are contiguous from 0, so the id is the index.
+        usize::try_from(version_id)
+            .ok()
+            .and_then(|i| self.documents.get(i))
+    }
+}
+
+impl DidMethod for Webplus {
+    fn name(&self) -> &'static str {
+        "webplus"
+    }
+
+    fn content_type(&self) -> &'static str {
+        "application/jsonl"
+    }
+
+    fn data_ext(&self) -> &'static str {
+        "jsonl"
+    }
+
+    fn parse_identifier(&self, did: &str) -> Result<ParsedDid, MethodError> {
+        let rest = did
+            .strip_prefix("did:webplus:")
+            .ok_or_else(|| MethodError::MethodMismatch {
+                expected: "webplus",
+                found: super::parse_did_method(did)
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|_| "<malformed>".into()),
+            })?;
+
+        // `did:webplus:{host}[:{path...}]:{root-self-hash}` — host first,
+        // root self-hash last, at least those two.
+        let (domain, path) = rest
+            .split_once(':')
+            .ok_or_else(|| MethodError::Malformed(did.to_string()))?;
+        if domain.is_empty() || path.split(':').any(str::is_empty) {
+            return Err(MethodError::Malformed(did.to_string()));
+        }
+        let root = path.rsplit(':').next().unwrap_or(path);
+        validate_self_hash(root).map_err(|reason| {
+            MethodError::Malformed(format!("{did} (root self-hash segment {reason})"))
+        })?;
+
+        Ok(ParsedDid {
+            method: "webplus",
+            scid: Some(root.to_string()),
+            domain: domain.to_string(),
+            path: path.to_string(),
+        })
+    }
+
+    fn resolution_url(&self, domain: &str, mnemonic: &str) -> String {
+        // The root self-hash is always part of the path, so there is no
+        // `.well-known` branch here (unlike web / webvh).
+        let path = mnemonic.replace(':', "/");
+        format!("https://{domain}/{path}/{DID_DOCUMENTS_FILE}")
+    }
+
+    fn validate(&self, data: &[u8]) -> Result<(), MethodError> {
+        parse_microledger(data).map(|_| ())
+    }
+
+    fn apply_update(
+        &self,
+        existing: Option<&[u8]>,
+        new_data: &[u8],
+    ) -> Result<Vec<u8>, MethodError> {
+        // Append-only, like webvh: `new_data` carries the next document(s),
+        // never a replacement ledger.
+        let new_text = std::str::from_utf8(new_data).map_err(|e| {
+            MethodError::Validation(format!("webplus apply_update: not valid UTF-8: {e}"))
+        })?;
+        let new_text = new_text.trim();
+        if new_text.is_empty() {
+            return Err(MethodError::Validation(
+                "webplus apply_update: new_data is empty / whitespace-only".into(),
+            ));
+        }
+        let mut out = existing.map(|b| b.to_vec()).unwrap_or_default();
+        if !out.is_empty() && !out.ends_with(b"\n") {
+            out.push(b'\n');
+        }
+        out.extend_from_slice(new_text.as_bytes());
+        out.push(b'\n');
+
+        // Validate the *combined* ledger: the appended documents must chain
+        // onto the stored ones (`prevDIDDocumentSelfHash`, `versionId`,
+        // `validFrom`), which the appended bytes alone can't show.
+        parse_microledger(&out)?;
+        Ok(out)
+    }
+}
+
+/// Build the per-version URL for the document with `self_hash`.
+pub fn self_hash_url(domain: &str, mnemonic: &str, self_hash: &str) -> String {
+    let path = mnemonic.replace(':', "/");
+    format!("https://{domain}/{path}/did/selfHash/{self_hash}.json")
+}
+
+/// Build the per-version URL for the document with `version_id`.
+pub fn version_id_url(domain: &str, mnemonic: &str, version_id: u64) -> String {
+    let path = mnemonic.replace(':', "/");
+    format!("https://{domain}/{path}/did/versionId/{version_id}.json")
+}
+
+/// Check `s` is a well-formed self-hash of a supported hash function.
+///
+/// Returns the reason as a fragment ("is …") so callers can splice it
+/// into their own error message.
+pub fn validate_self_hash(s: &str) -> Result<(), String> {
+    decode_self_hash(s).map(|_| ())
+}
+
+/// Compute the self-hash of `doc`, whose self-hash slots must already
+/// hold `algorithm`'s [placeholder](SelfHashAlgorithm::placeholder).
+pub fn compute_self_hash(
+    doc: &serde_json::Value,
+    algorithm: SelfHashAlgorithm,
+) -> Result<String, MethodError> {
+    let jcs = serde_json_canonicalizer::to_vec(doc)
+        .map_err(|e| MethodError::Validation(format!("JCS serialisation failed: {e}")))?;
+    Ok(encode_self_hash(algorithm, &algorithm.digest(&jcs)))
+}
+
+/// Parse a `did-documents.jsonl` microledger and check every document's
+/// self-hash and chaining. See the module docs for exactly what is
+/// checked.
+pub fn parse_microledger(data: &[u8]) -> Result<Microledger, MethodError> {
+    let text = std::str::from_utf8(data).map_err(|e| {
+        MethodError::Validation(format!("{DID_DOCUMENTS_FILE} is not valid UTF-8: {e}"))
+    })?;
+    // A single trailing newline terminates the last line; anything else
+    // blank is a malformed ledger.
+    let text = text.strip_suffix('\n').unwrap_or(text);
+
+    let mut did: Option<String> = None;
+    let mut documents: Vec<DidDocumentVersion> = Vec::new();
+    let mut deactivated = false;
+
+    for (idx, line) in text.split('\n').enumerate() {
+        let line_no = idx + 1;
+        if line.trim().is_empty() {
+            return Err(ledger_error(line_no, "blank line"));
+        }
+        let doc: serde_json::Value = serde_json::from_str(line)
+            .map_err(|e| ledger_error(line_no, &format!("not valid JSON: {e}")))?;
+        let version =
+            document_version(&doc, line).map_err(|reason| ledger_error(line_no, &reason))?;
+        verify_self_hash(&doc, &version.self_hash)
+            .map_err(|reason| ledger_error(line_no, &reason))?;
+        let id = str_field(&doc, "id").map_err(|reason| ledger_error(line_no, &reason))?;
+        let prev_self_hash = doc.get("prevDIDDocumentSelfHash");
+
+        match (documents.last(), did.as_deref()) {
+            (None, _) => {
+                if version.version_id != 0 {
+                    return Err(ledger_error(line_no, "root document must have versionId 0"));
+                }
+                if prev_self_hash.is_some() {
+                    return Err(ledger_error(
+                        line_no,
+                        "root document must not carry prevDIDDocumentSelfHash",
+                    ));
+                }
+                let parsed = Webplus
+                    .parse_identifier(id)
+                    .map_err(|e| ledger_error(line_no, &format!("`id` {e}")))?;
+                if parsed.scid.as_deref() != Some(version.self_hash.as_str()) {
+                    return Err(ledger_error(
+                        line_no,
+                        "root document `id` must end in its own selfHash",
+                    ));
+                }
+                did = Some(id.to_string());
+            }
+            (Some(prev), Some(subject)) => {
+                if deactivated {
+                    return Err(ledger_error(
+                        line_no,
+                        "DID was deactivated (empty updateRules) by the previous document",
+                    ));
+                }
+                if id != subject {
+                    return Err(ledger_error(
+                        line_no,
+                        &format!("`id` {id} does not match the root document's {subject}"),
+                    ));
+                }
+                if version.version_id != prev.version_id + 1 {
+                    return Err(ledger_error(
+                        line_no,
+                        &format!(
+                            "versionId {} does not follow {}",
+                            version.version_id, prev.version_id
+                        ),
+                    ));
+                }
+                if prev_self_hash.and_then(|v| v.as_str()) != Some(prev.self_hash.as_str()) {
+                    return Err(ledger_error(
+                        line_no,
+                        &format!(
+                            "prevDIDDocumentSelfHash {prev_self_hash:?} does not match previous document {}",
+                            prev.self_hash
+                        ),
+                    ));
+                }
+                if version.valid_from <= prev.valid_from {
+                    return Err(ledger_error(
+                        line_no,
+                        "validFrom must be later than the previous document's",
+                    ));
+                }
+            }
+            (Some(_), None) => unreachable!("root document records the DID"),
+        }
+
+        deactivated = doc
+            .get("updateRules")
+            .and_then(|r| r.as_object())
+            .is_some_and(|r| r.is_empty());
+        documents.push(version);
+    }
+
+    let did = did.ok_or_else(|| {
+        MethodError::Validation(format!("{DID_DOCUMENTS_FILE} contains no documents"))
+    })?;
+    Ok(Microledger { did, documents })
+}
+
+/// Re-derive `doc`'s self-hash and compare it with the claimed one.
+fn verify_self_hash(doc: &serde_json::Value, claimed: &str) -> Result<(), String> {
+    let algorithm = decode_self_hash(claimed).map_err(|reason| format!("selfHash {reason}"))?;
+    let mut doc = doc.clone();
+    replace_in_strings(&mut doc, claimed, &algorithm.placeholder());
+    let computed = compute_self_hash(&doc, algorithm).map_err(|e| e.to_string())?;
+    if computed != claimed {
+        return Err(format!(
+            "selfHash {claimed} does not match the document (computed {computed})"
+        ));
+    }
+    Ok(())
+}
+
+/// Replace every occurrence of `from` inside every string value (and
+/// object key) of `v`.
+fn replace_in_strings(v: &mut serde_json::Value, from: &str, to: &str) {
+    match v {
+        serde_json::Value::String(s) if s.contains(from) => *s = s.replace(from, to),
+        serde_json::Value::Array(items) => {
+            for item in items {
+                replace_in_strings(item, from, to);
+            }
+        }
+        serde_json::Value::Object(map) => {
+            let entries = std::mem::take(map);
+            for (key, mut value) in entries {
+                replace_in_strings(&mut value, from, to);
+                map.insert(key.replace(from, to), value);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn encode_self_hash(algorithm: SelfHashAlgorithm, digest: &[u8; DIGEST_LEN]) -> String {
+    let mut multihash = Vec::with_capacity(2 + DIGEST_LEN);
+    // Both codes and the length are < 0x80, so each varint is one byte.
+    multihash.push(algorithm.multihash_code());
+    multihash.push(DIGEST_LEN as u8);
+    multihash.extend_from_slice(digest);
+    format!("{MULTIBASE_BASE64URL}{}", URL_SAFE_NO_PAD.encode(multihash))
+}
+
+fn decode_self_hash(s: &str) -> Result<SelfHashAlgorithm, String> {
+    let encoded = s
+        .strip_prefix(MULTIBASE_BASE64URL)
+        .ok_or("is not base64url multibase (expected a `u` prefix)")?;
+    let bytes = URL_SAFE_NO_PAD
+        .decode(encoded)
+        .map_err(|e| format!("is not valid base64url: {e}"))?;
+    let [code, len, digest @ ..] = bytes.as_slice() else {
+        return Err("is too short to be a multihash".into());
+    };
+    let algorithm = SelfHashAlgorithm::from_multihash_code(*code)
+        .ok_or_else(|| format!("uses unsupported multihash code 0x{code:02x}"))?;
+    if usize::from(*len) != DIGEST_LEN || digest.len() != DIGEST_LEN {
+        return Err(format!(
+            "has a {}-byte digest, expected {DIGEST_LEN}",
+            digest.len()
+        ));
+    }
+    Ok(algorithm)
+}
+
+fn document_version(doc: &serde_json::Value, line: &str) -> Result<DidDocumentVersion, String> {
+    let version_id = doc
+        .get("versionId")
+        .and_then(|v| v.as_u64())
+        .ok_or("missing or non-integer `versionId`")?;
+    let valid_from = str_field(doc, "validFrom")?;
+    let valid_from = DateTime::parse_from_rfc3339(valid_from)
+        .map_err(|e| format!("`validFrom` is not RFC 3339: {e}"))?;
+    if !doc.get("updateRules").is_some_and(|r| r.is_object()) {
+        return Err("missing or non-object `updateRules`".into());
+    }
+    Ok(DidDocumentVersion {
+        version_id,
+        self_hash: str_field(doc, "selfHash")?.to_string(),
+        valid_from,
+        document: line.to_string(),
+    })
+}
+
+fn str_field<'a>(doc: &'a serde_json::Value, name: &str) -> Result<&'a str, String> {
+    doc.get(name)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| format!("missing or non-string `{name}`"))
+}
+
+fn ledger_error(line_no: usize, reason: &str) -> MethodError {
+    MethodError::Validation(format!("{DID_DOCUMENTS_FILE} line {line_no}: {reason}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const ALG: SelfHashAlgorithm = SelfHashAlgorithm::Blake3;
+
+    /// Self-hash `doc` in place: every occurrence of the placeholder is a
+    /// slot, exactly as a controller would build it.
+    fn seal(doc: serde_json::Value, algorithm: SelfHashAlgorithm) -> serde_json::Value {
+        let placeholder = algorithm.placeholder();
+        let hash = compute_self_hash(&doc, algorithm).unwrap();
+        serde_json::from_str(&doc.to_string().replace(&placeholder, &hash)).unwrap()
+    }
+
+    /// A root document, with `edit` applied before sealing.
+    fn root_with(
+        algorithm: SelfHashAlgorithm,
+        edit: impl FnOnce(&mut serde_json::Value),
+    ) -> serde_json::Value {
+        let did = format!("did:webplus:example.com:{}", algorithm.placeholder());
+        let mut doc = json!({
+            "id": did,
+            "selfHash": algorithm.placeholder(),
+            "updateRules": {"key": "u7QEJX5oaWFJUFJ5eYlGnT7nMx2TF8ArSZqdX4Fy9cvbWJA"},
+            "validFrom": "2026-01-01T00:00:00Z",
+            "versionId": 0,
+            "verificationMethod": [{
+                "id": format!("{did}#0"),
+                "type": "JsonWebKey2020",
+                "controller": did,
+                "publicKeyJwk": {"kty": "OKP", "crv": "Ed25519", "x": "AAAA"}
+            }]
+        });
+        edit(&mut doc);
+        seal(doc, algorithm)
+    }
+
+    fn root() -> serde_json::Value {
+        root_with(ALG, |_| {})
+    }
+
+    /// The document following `prev`, with `edit` applied before sealing.
+    fn next_with(
+        prev: &serde_json::Value,
+        valid_from: &str,
+        edit: impl FnOnce(&mut serde_json::Value),
+    ) -> serde_json::Value {
+        let mut doc = prev.clone();
+        doc["prevDIDDocumentSelfHash"] = prev["selfHash"].clone();
+        doc["selfHash"] = json!(ALG.placeholder());
+        doc["versionId"] = json!(prev["versionId"].as_u64().unwrap() + 1);
+        doc["validFrom"] = json!(valid_from);
+        edit(&mut doc);
+        seal(doc, ALG)
+    }
+
+    fn next(prev: &serde_json::Value, valid_from: &str) -> serde_json::Value {
+        next_with(prev, valid_from, |_| {})
+    }
+
+    fn ledger(docs: &[&serde_json::Value]) -> Vec<u8> {
+        docs.iter()
+            .map(|d| format!("{d}\n"))
+            .collect::<String>()
+            .into_bytes()
+    }
+
+    fn err(result: Result<Microledger, MethodError>) -> String {
+        result.expect_err("ledger should be rejected").to_string()
+    }
+
+    #[test]
+    fn parses_root_only_ledger() {
+        let root = root();
+        let parsed = parse_microledger(&ledger(&[&root])).expect("valid root");
+        assert_eq!(parsed.did, root["id"].as_str().unwrap());
+        assert_eq!(parsed.latest().version_id, 0);
+        assert!(parsed.did.ends_with(&parsed.latest().self_hash));
+    }
+
+    #[test]
+    fn sha256_self_hash_is_accepted() {
+        let root = root_with(SelfHashAlgorithm::Sha256, |_| {});
+        parse_microledger(&ledger(&[&root])).expect("sha2-256 root");
+    }
+
+    #[test]
+    fn parses_chained_updates_and_indexes_versions() {
+        let v0 = root();
+        let v1 = next(&v0, "2026-02-01T00:00:00Z");
+        let v2 = next(&v1, "2026-03-01T00:00:00Z");
+        let parsed = parse_microledger(&ledger(&[&v0, &v1, &v2])).expect("valid chain");
+        assert_eq!(parsed.documents.len(), 3);
+        let h1 = v1["selfHash"].as_str().unwrap();
+        assert_eq!(parsed.by_self_hash(h1).unwrap().version_id, 1);
+        assert_eq!(parsed.by_version_id(2).unwrap().document, v2.to_string());
+        assert!(parsed.by_version_id(3).is_none());
+        assert!(parsed.by_self_hash(&ALG.placeholder()).is_none());
+    }
+
+    #[test]
+    fn rejects_tampered_document() {
+        let mut root = root();
+        root["validFrom"] = json!("2026-01-02T00:00:00Z");
+        assert!(err(parse_microledger(&ledger(&[&root]))).contains("does not match the document"));
+    }
+
+    #[test]
+    fn rejects_root_whose_id_is_not_its_self_hash() {
+        let other = SelfHashAlgorithm::Sha256.placeholder();
+        let doc = root_with(ALG, |d| {
+            d["id"] = json!(format!("did:webplus:example.com:{other}"))
+        });
+        assert!(err(parse_microledger(&ledger(&[&doc]))).contains("must end in its own selfHash"));
+    }
+
+    #[test]
+    fn rejects_root_with_nonzero_version() {
+        let doc = root_with(ALG, |d| d["versionId"] = json!(1));
+        assert!(err(parse_microledger(&ledger(&[&doc]))).contains("versionId 0"));
+    }
+
+    #[test]
+    fn rejects_broken_prev_self_hash() {
+        let v0 = root();
+        let v1 = next_with(&v0, "2026-02-01T00:00:00Z", |d| {
+            d["prevDIDDocumentSelfHash"] = json!(SelfHashAlgorithm::Sha256.placeholder())
+        });
+        assert!(err(parse_microledger(&ledger(&[&v0, &v1]))).contains("prevDIDDocumentSelfHash"));
+    }
+
+    #[test]
+    fn rejects_skipped_version() {
+        let v0 = root();
+        let v1 = next_with(&v0, "2026-02-01T00:00:00Z", |d| d["versionId"] = json!(2));
+        assert!(err(parse_microledger(&ledger(&[&v0, &v1]))).contains("does not follow"));
+    }
+
+    #[test]
+    fn rejects_valid_from_going_backwards() {
+        let v0 = root();
+        let v1 = next(&v0, "2025-12-31T23:59:59Z");
+        assert!(err(parse_microledger(&ledger(&[&v0, &v1]))).contains("validFrom"));
+    }
+
+    #[test]
+    fn rejects_updates_after_deactivation() {
+        let v0 = root();
+        let v1 = next_with(&v0, "2026-02-01T00:00:00Z", |d| {
+            d["updateRules"] = json!({})
+        });
+        parse_microledger(&ledger(&[&v0, &v1])).expect("deactivation itself is valid");
+        let v2 = next(&v1, "2026-03-01T00:00:00Z");
+        assert!(err(parse_microledger(&ledger(&[&v0, &v1, &v2]))).contains("deactivated"));
+    }
+
+    #[test]
+    fn rejects_blank_and_empty_ledgers() {
+        let root = root();
+        assert!(err(parse_microledger(format!("{root}\n\n").as_bytes())).contains("blank line"));
+        assert!(parse_microledger(b"").is_err());
+    }
+
+    #[test]
+    fn apply_update_appends_and_validates_the_chain() {
+        let v0 = root();
+        let v1 = next(&v0, "2026-02-01T00:00:00Z");
+        let stored = ledger(&[&v0]);
+        let out = Webplus
+            .apply_update(Some(&stored), v1.to_string().as_bytes())
+            .expect("chained update");
+        assert_eq!(out, ledger(&[&v0, &v1]));
+
+        // Re-submitting the same document no longer chains.
+        assert!(
+            Webplus
+                .apply_update(Some(&out), v1.to_string().as_bytes())
+                .is_err()
+        );
+        assert!(Webplus.apply_update(Some(&out), b"  \n").is_err());
+    }
+
+    #[test]
+    fn apply_update_without_existing_requires_a_root() {
+        let v0 = root();
+        let v1 = next(&v0, "2026-02-01T00:00:00Z");
+        assert!(
+            Webplus
+                .apply_update(None, v0.to_string().as_bytes())
+                .is_ok()
+        );
+        assert!(
+            Webplus
+                .apply_update(None, v1.to_string().as_bytes())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn parse_identifier_extracts_root_self_hash() {
+        let hash = root()["selfHash"].as_str().unwrap().to_string();
+        let p = Webplus
+            .parse_identifier(&format!("did:webplus:example.com%3A8443:tenant:{hash}"))
+            .unwrap();
+        assert_eq!(p.method, "webplus");
+        assert_eq!(p.domain, "example.com%3A8443");
+        assert_eq!(p.path, format!("tenant:{hash}"));
+        assert_eq!(p.scid.as_deref(), Some(hash.as_str()));
+    }
+
+    #[test]
+    fn parse_identifier_rejects_bad_shapes() {
+        assert!(matches!(
+            Webplus.parse_identifier("did:web:example.com"),
+            Err(MethodError::MethodMismatch { .. })
+        ));
+        assert!(Webplus.parse_identifier("did:webplus:example.com").is_err());
+        assert!(
+            Webplus
+                .parse_identifier("did:webplus:example.com:not-a-hash")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn resolution_urls_use_slashes() {
+        assert_eq!(
+            Webplus.resolution_url("example.com", "tenant:uHiB"),
+            "https://example.com/tenant/uHiB/did-documents.jsonl"
+        );
+        assert_eq!(
+            self_hash_url("example.com", "uHiB", "uHiC"),
+            "https://example.com/uHiB/did/selfHash/uHiC.json"
+        );
+        assert_eq!(
+            version_id_url("example.com", "uHiB", 3),
+            "https://example.com/uHiB/did/versionId/3.json"
+        );
+    }
+
+    #[test]
+    fn self_hash_validation() {
+        assert!(validate_self_hash(&ALG.placeholder()).is_ok());
+        assert!(validate_self_hash(&SelfHashAlgorithm::Sha256.placeholder()).is_ok());
+        assert!(validate_self_hash("zHiB").is_err());
+        assert!(validate_self_hash("uAAAA").is_err());
+        let short = format!("u{}", URL_SAFE_NO_PAD.encode([0x1e, 0x20, 0, 0]));
+        assert!(validate_self_hash(&short).is_err());
+    }
+}
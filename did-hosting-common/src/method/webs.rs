@@ -1,19 +1,667 @@
-//! `did:webs` — **NOT IMPLEMENTED IN THIS RELEASE**.
+//! `did:webs` implementation of [`DidMethod`].
 //!
-//! Per `docs/multi-method-hosting-spec.md` §1 and §2, `did:webs` is
-//! a scaffolded compile-time target — the feature flag exists so the
-//! `DidMethod` trait surface can be exercised against a future
-//! method without restructuring, but no impl ships in this release.
+//! Per `docs/multi-method-hosting-spec.md` §6.1. Off by default — enable
+//! with `--features method-webs`. A did:webs identifier is a KERI
+//! autonomic identifier (AID) published over HTTPS: the authoritative
+//! state is the AID's key event log (KEL), and the `did.json` served
+//! next to it is a rendering of that state for resolvers that don't
+//! speak KERI.
 //!
-//! Enabling `--features method-webs` produces this compile error on
-//! purpose. A future release will replace this stub with a real
-//! impl following the pattern in `super::webvh`.
+//! ## Identifier shape
+//!
+//! `did:webs:{host}[:{path-segment}…]:{AID}`
+//!
+//! - `{host}` may carry a non-default port encoded as `%3A`, same as
+//!   did:web / did:webvh.
+//! - `{AID}` is always the **last** segment and is a CESR-encoded
+//!   self-certifying prefix (e.g. a 44-char `E…` Blake3 digest).
+//! - Unlike did:webvh, the AID is also a URL path segment — every
+//!   did:webs DID resolves at `https://{host}/{path…}/{AID}/did.json`,
+//!   and there is no no-path `.well-known` form. [`ParsedDid::path`]
+//!   therefore carries the AID as its final segment so the mnemonic
+//!   round-trips to the resolution URL unchanged; the AID is also
+//!   surfaced on its own as [`ParsedDid::scid`].
+//!
+//! ## Artifacts and storage
+//!
+//! Two artifacts are published per DID:
+//!
+//! - `keri.cesr` — the KEL as a CESR text-domain stream: each KERI event
+//!   is a JSON body (framed by its `KERI10JSON…_` version string)
+//!   followed by its attached signature groups. Stored at
+//!   [`crate::did_ops::content_log_key`]; this is the byte stream
+//!   [`DidMethod::validate`] and [`DidMethod::apply_update`] operate
+//!   on, mirroring how webvh treats `did.jsonl`.
+//! - `did.json` — the DID document, stored as a sidecar at
+//!   [`crate::did_ops::content_did_doc_key`] and checked against the KEL
+//!   by [`validate_did_document`]. This is what the resolution URL
+//!   serves, hence [`DidMethod::content_type`] is
+//!   `application/did+json`.
+//!
+//! ## Validation scope
+//!
+//! [`parse_kel`] checks the stream framing and the KEL's chaining: the
+//! first key event is an inception (`icp` / `dip`) at sequence `0`,
+//! every later key event for the AID carries the next sequence number
+//! and a prior-digest (`p`) equal to the previous event's SAID (`d`),
+//! and every key event has attached material. Non-key messages in the
+//! stream (receipts, TEL events, designated-alias ACDCs) are accepted
+//! structurally. Signature and SAID digest verification are **not**
+//! performed here — same split as webvh, where the trait method is the
+//! cheap transport-layer gate and full verification belongs to the
+//! resolver.
 
 #![cfg(feature = "method-webs")]
 
-compile_error!(
-    "method-webs is not implemented in this release. \
-     See docs/multi-method-hosting-spec.md §1 for the planned scope \
-     and §6 for the DidMethod trait the impl will satisfy. To build \
-     without it, drop --features method-webs."
-);
+use affinidi_cesr::{Counter, Matter};
+
+use super::{DidMethod, MethodError, ParsedDid};
+
+/// MIME type for the `keri.cesr` artifact.
+pub const KERI_CESR_CONTENT_TYPE: &str = "application/cesr";
+
+/// Key-event types (`t` field) that participate in the KEL chain.
+const KEY_EVENT_ILKS: &[&str] = &["icp", "rot", "ixn", "dip", "drt"];
+
+/// Inception event types — the only ones allowed at sequence `0`.
+const INCEPTION_ILKS: &[&str] = &["icp", "dip"];
+
+/// Length of a KERI v1 version string (`KERI10JSON0001ad_`).
+const VERSION_STRING_LEN: usize = 17;
+
+/// Byte prefix every v1 JSON-serialised KERI message starts with; the
+/// version string follows immediately.
+const VERSION_FIELD_PREFIX: &[u8] = br#"{"v":""#;
+
+/// Zero-size unit struct — the trait impl carries all the behaviour.
+pub struct Webs;
+
+/// One key event lifted out of a `keri.cesr` stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyEvent {
+    /// Event type (`icp`, `rot`, `ixn`, `dip`, `drt`).
+    pub ilk: String,
+    /// Identifier prefix the event belongs to (`i`).
+    pub prefix: String,
+    /// Sequence number (`s`, hex-encoded on the wire).
+    pub sn: u64,
+    /// Self-addressing identifier of the event (`d`).
+    pub said: String,
+    /// Prior event's SAID (`p`); `None` on inception.
+    pub prior: Option<String>,
+}
+
+/// Summary of a parsed `keri.cesr` stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Kel {
+    /// The AID the KEL establishes (the inception event's `i`).
+    pub aid: String,
+    /// Key events for [`Self::aid`], in stream order.
+    pub events: Vec<KeyEvent>,
+}
+
+impl Kel {
+    /// The most recent key event — the one a resolver's key state
+    /// reflects.
+    pub fn latest(&self) -> &KeyEvent {
+        // `parse_kel` refuses a stream without an inception event, so
+        // `events` is never empty.
+        self.events
+            .last()
+            .expect("parse_kel guarantees an inception")
+    }
+}
+
+impl DidMethod for Webs {
+    fn name(&self) -> &'static str {
+        "webs"
+    }
+
+    fn content_type(&self) -> &'static str {
+        // The resolution URL serves the `did.json` rendering; the KEL has
+        // its own `application/cesr` endpoint (`KERI_CESR_CONTENT_TYPE`).
+        "application/did+json"
+    }
+
+    fn data_ext(&self) -> &'static str {
+        "json"
+    }
+
+    fn parse_identifier(&self, did: &str) -> Result<ParsedDid, MethodError> {
+        let rest = did
+            .strip_prefix("did:webs:")
+            .ok_or_else(|| MethodError::MethodMismatch {
+                expected: "webs",
+                found: super::parse_did_method(did)
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|_| "<malformed>".into()),
+            })?;
+
+        // `did:webs:{host}[:{path...}]:{AID}` — host first, AID last,
+        // at least those two.
+        let (domain, path) = rest
+            .split_once(':')
+            .ok_or_else(|| MethodError::Malformed(did.to_string()))?;
+        if domain.is_empty() || path.split(':').any(str::is_empty) {
+            return Err(MethodError::Malformed(did.to_string()));
+        }
+        let aid = path.rsplit(':').next().unwrap_or(path);
+        validate_aid(aid)
+            .map_err(|reason| MethodError::Malformed(format!("{did} (AID segment {reason})")))?;
+
+        Ok(ParsedDid {
+            method: "webs",
+            scid: Some(aid.to_string()),
+            domain: domain.to_string(),
+            path: path.to_string(),
+        })
+    }
+
+    fn resolution_url(&self, domain: &str, mnemonic: &str) -> String {
+        // The AID is always part of the path, so there is no
+        // `.well-known` branch here (unlike web / webvh).
+        let path = mnemonic.replace(':', "/");
+        format!("https://{domain}/{path}/did.json")
+    }
+
+    fn validate(&self, data: &[u8]) -> Result<(), MethodError> {
+        parse_kel(data).map(|_| ())
+    }
+
+    fn apply_update(
+        &self,
+        existing: Option<&[u8]>,
+        new_data: &[u8],
+    ) -> Result<Vec<u8>, MethodError> {
+        // Append-only, like webvh: `new_data` carries the events to add
+        // (with their attachments), never a replacement KEL.
+        if new_data.iter().all(|b| b.is_ascii_whitespace()) {
+            return Err(MethodError::Validation(
+                "webs apply_update: new_data is empty / whitespace-only".into(),
+            ));
+        }
+        let mut out = existing.map(|b| b.to_vec()).unwrap_or_default();
+        out.extend_from_slice(new_data);
+
+        // Validate the *combined* stream: the appended events must chain
+        // onto the stored ones (next sequence number, prior digest
+        // matching), which the appended bytes alone can't show.
+        parse_kel(&out)?;
+        Ok(out)
+    }
+}
+
+/// Build the `keri.cesr` URL that sits next to the `did.json` for the
+/// same mnemonic.
+pub fn keri_cesr_url(domain: &str, mnemonic: &str) -> String {
+    let path = mnemonic.replace(':', "/");
+    format!("https://{domain}/{path}/keri.cesr")
+}
+
+/// Check an AID segment is a single, complete CESR primitive.
+///
+/// Returns the reason as a fragment ("is …") so callers can splice it
+/// into their own error message.
+pub fn validate_aid(aid: &str) -> Result<(), String> {
+    let matter = Matter::from_qb64(aid).map_err(|e| format!("is not a CESR prefix: {e}"))?;
+    if matter.full_size() != aid.len() {
+        return Err(format!(
+            "is not a single CESR primitive ({} chars, primitive is {})",
+            aid.len(),
+            matter.full_size()
+        ));
+    }
+    Ok(())
+}
+
+/// Parse a `keri.cesr` text-domain stream and check the AID's KEL
+/// chains correctly. See the module docs for exactly what is checked.
+pub fn parse_kel(data: &[u8]) -> Result<Kel, MethodError> {
+    let text = std::str::from_utf8(data)
+        .map_err(|e| MethodError::Validation(format!("keri.cesr is not valid UTF-8: {e}")))?;
+    let bytes = text.as_bytes();
+
+    let mut aid: Option<String> = None;
+    let mut events: Vec<KeyEvent> = Vec::new();
+    let mut pos = 0usize;
+    let mut msg_idx = 0usize;
+
+    while pos < bytes.len() {
+        if bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+            continue;
+        }
+        msg_idx += 1;
+
+        let size = message_size(&bytes[pos..]).map_err(|reason| kel_error(msg_idx, &reason))?;
+        if pos + size > bytes.len() {
+            return Err(kel_error(
+                msg_idx,
+                "version string size runs past the end of the stream",
+            ));
+        }
+        let body: serde_json::Value = serde_json::from_slice(&bytes[pos..pos + size])
+            .map_err(|e| kel_error(msg_idx, &format!("message body is not valid JSON: {e}")))?;
+        pos += size;
+
+        // Attachments run up to the next message (JSON bodies start with
+        // `{`, which is never a Base64url character) or end of stream.
+        let attach_end = bytes[pos..]
+            .iter()
+            .position(|&b| b == b'{')
+            .map_or(bytes.len(), |off| pos + off);
+        let attachments = text[pos..attach_end].trim_end();
+        check_attachments(attachments).map_err(|reason| kel_error(msg_idx, &reason))?;
+        pos = attach_end;
+
+        let Some(ilk) = body.get("t").and_then(|t| t.as_str()) else {
+            // ACDCs (designated aliases) carry no `t`; nothing to chain.
+            continue;
+        };
+        if !KEY_EVENT_ILKS.contains(&ilk) {
+            continue;
+        }
+        let event = key_event(&body, ilk).map_err(|reason| kel_error(msg_idx, &reason))?;
+
+        match aid.as_deref() {
+            None => {
+                if !INCEPTION_ILKS.contains(&ilk) || event.sn != 0 {
+                    return Err(kel_error(
+                        msg_idx,
+                        "first key event must be an inception (icp/dip) at sequence 0",
+                    ));
+                }
+                aid = Some(event.prefix.clone());
+            }
+            // Key events for another AID (e.g. a delegator's) may be
+            // interleaved; only the subject's own KEL is chained.
+            Some(subject) if event.prefix != subject => continue,
+            Some(_) => {
+                let prev = events.last().expect("inception recorded above");
+                if INCEPTION_ILKS.contains(&ilk) {
+                    return Err(kel_error(msg_idx, "duplicate inception event"));
+                }
+                if event.sn != prev.sn + 1 {
+                    return Err(kel_error(
+                        msg_idx,
+                        &format!("sequence {} does not follow {}", event.sn, prev.sn),
+                    ));
+                }
+                if event.prior.as_deref() != Some(prev.said.as_str()) {
+                    return Err(kel_error(
+                        msg_idx,
+                        &format!(
+                            "prior digest {:?} does not match previous event {}",
+                            event.prior, prev.said
+                        ),
+                    ));
+                }
+            }
+        }
+        if attachments.is_empty() {
+            return Err(kel_error(msg_idx, "key event has no attached signatures"));
+        }
+        events.push(event);
+    }
+
+    let aid =
+        aid.ok_or_else(|| MethodError::Validation("keri.cesr contains no inception event".into()))?;
+    Ok(Kel { aid, events })
+}
+
+/// Check a `did.json` document is a did:webs document for the AID the
+/// stored KEL establishes.
+pub fn validate_did_document(doc: &[u8], kel: &[u8]) -> Result<(), MethodError> {
+    let v: serde_json::Value = serde_json::from_slice(doc)
+        .map_err(|e| MethodError::Validation(format!("did.json is not valid JSON: {e}")))?;
+    let id = v
+        .get("id")
+        .and_then(|x| x.as_str())
+        .ok_or_else(|| MethodError::Validation("did.json missing `id` field".into()))?;
+    let parsed = Webs.parse_identifier(id).map_err(|e| match e {
+        MethodError::MethodMismatch { .. } => MethodError::Validation(format!(
+            "did.json `id` is not a did:webs identifier: '{id}'"
+        )),
+        other => other,
+    })?;
+    let kel = parse_kel(kel)?;
+    if parsed.scid.as_deref() != Some(kel.aid.as_str()) {
+        return Err(MethodError::Validation(format!(
+            "did.json `id` names AID {} but keri.cesr establishes {}",
+            parsed.scid.unwrap_or_default(),
+            kel.aid
+        )));
+    }
+    Ok(())
+}
+
+/// Size in bytes of the JSON message at the head of `data`, read from
+/// its KERI v1 version string.
+fn message_size(data: &[u8]) -> Result<usize, String> {
+    let vs = data
+        .strip_prefix(VERSION_FIELD_PREFIX)
+        .ok_or("message does not start with a `v` version string")?;
+    if vs.len() < VERSION_STRING_LEN || !affinidi_cesr::sniff::has_version_string(vs) {
+        return Err("unrecognised version string (expected KERI/ACDC v1)".into());
+    }
+    if &vs[6..10] != b"JSON" {
+        return Err("only JSON-serialised messages are supported".into());
+    }
+    affinidi_cesr::sniff::version_string_size(vs).map_err(|e| e.to_string())
+}
+
+/// Attachments must be CESR text: Base64url characters, opening with a
+/// counter code so the groups are at least framed.
+fn check_attachments(attachments: &str) -> Result<(), String> {
+    if attachments.is_empty() {
+        return Ok(());
+    }
+    if let Some(bad) = attachments
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || *c == '-' || *c == '_'))
+    {
+        return Err(format!("attachments contain non-CESR character {bad:?}"));
+    }
+    Counter::from_qb64(attachments)
+        .map(|_| ())
+        .map_err(|e| format!("attachments do not open with a CESR counter: {e}"))
+}
+
+fn key_event(body: &serde_json::Value, ilk: &str) -> Result<KeyEvent, String> {
+    let field = |name: &str| {
+        body.get(name)
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| format!("{ilk} event missing `{name}`"))
+    };
+    let sn_hex = field("s")?;
+    let sn = u64::from_str_radix(&sn_hex, 16)
+        .map_err(|_| format!("{ilk} event has non-hex sequence number '{sn_hex}'"))?;
+    let prefix = field("i")?;
+    validate_aid(&prefix).map_err(|reason| format!("{ilk} event prefix {reason}"))?;
+    Ok(KeyEvent {
+        ilk: ilk.to_string(),
+        prefix,
+        sn,
+        said: field("d")?,
+        prior: if INCEPTION_ILKS.contains(&ilk) {
+            None
+        } else {
+            Some(field("p")?)
+        },
+    })
+}
+
+fn kel_error(msg_idx: usize, reason: &str) -> MethodError {
+    MethodError::Validation(format!("keri.cesr message {msg_idx}: {reason}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A syntactically valid Blake3-256 (`E`) prefix derived from `seed`.
+    fn digest(seed: u8) -> String {
+        Matter::new("E", vec![seed; 32]).unwrap().qb64().unwrap()
+    }
+
+    /// Frame `fields` as a KERI v1 JSON message with a correct version
+    /// string, followed by a single indexed-signature attachment group.
+    fn event(fields: serde_json::Value) -> String {
+        // `v` must be the first field, so splice it in ahead of the
+        // (key-sorted) serialisation rather than building a map.
+        let rest = fields.to_string();
+        let body = format!(r#"{{"v":"KERI10JSON000000_",{}"#, &rest[1..]);
+        let body = body.replacen("000000", &format!("{:06x}", body.len()), 1);
+        let sig = Matter::new("0B", vec![7u8; 64]).unwrap().qb64().unwrap();
+        format!("{body}-AAB{sig}")
+    }
+
+    fn icp(aid: &str) -> String {
+        event(serde_json::json!({"t": "icp", "d": aid, "i": aid, "s": "0"}))
+    }
+
+    fn next(aid: &str, ilk: &str, sn: u64, said: &str, prior: &str) -> String {
+        event(serde_json::json!({
+            "t": ilk, "d": said, "i": aid, "s": format!("{sn:x}"), "p": prior
+        }))
+    }
+
+    fn did(aid: &str) -> String {
+        format!("did:webs:example.com:people:{aid}")
+    }
+
+    #[test]
+    fn name_and_metadata() {
+        assert_eq!(Webs.name(), "webs");
+        assert_eq!(Webs.content_type(), "application/did+json");
+        assert_eq!(Webs.data_ext(), "json");
+    }
+
+    // ---- parse_identifier ----
+
+    #[test]
+    fn parse_identifier_with_path() {
+        let aid = digest(1);
+        let p = Webs.parse_identifier(&did(&aid)).unwrap();
+        assert_eq!(p.method, "webs");
+        assert_eq!(p.scid.as_deref(), Some(aid.as_str()));
+        assert_eq!(p.domain, "example.com");
+        assert_eq!(p.path, format!("people:{aid}"));
+    }
+
+    #[test]
+    fn parse_identifier_aid_only_path() {
+        let aid = digest(1);
+        let p = Webs
+            .parse_identifier(&format!("did:webs:example.com%3A8443:{aid}"))
+            .unwrap();
+        assert_eq!(p.domain, "example.com%3A8443");
+        assert_eq!(p.path, aid);
+    }
+
+    #[test]
+    fn parse_identifier_rejects_missing_aid() {
+        assert!(Webs.parse_identifier("did:webs:example.com").is_err());
+        assert!(Webs.parse_identifier("did:webs:example.com:").is_err());
+    }
+
+    #[test]
+    fn parse_identifier_rejects_non_cesr_aid() {
+        let err = Webs
+            .parse_identifier("did:webs:example.com:not-an-aid")
+            .expect_err("must reject");
+        assert!(matches!(err, MethodError::Malformed(_)));
+    }
+
+    #[test]
+    fn parse_identifier_rejects_wrong_method() {
+        let err = Webs
+            .parse_identifier("did:web:example.com:user1")
+            .expect_err("did:web must reject");
+        assert!(matches!(
+            err,
+            MethodError::MethodMismatch {
+                expected: "webs",
+                ..
+            }
+        ));
+    }
+
+    // ---- resolution_url ----
+
+    #[test]
+    fn resolution_url_includes_aid_segment() {
+        let aid = digest(1);
+        let mnemonic = format!("people/{aid}");
+        assert_eq!(
+            Webs.resolution_url("example.com", &mnemonic),
+            format!("https://example.com/people/{aid}/did.json")
+        );
+        assert_eq!(
+            keri_cesr_url("example.com", &mnemonic),
+            format!("https://example.com/people/{aid}/keri.cesr")
+        );
+    }
+
+    // ---- validate / parse_kel ----
+
+    #[test]
+    fn validate_accepts_inception_only() {
+        let aid = digest(1);
+        let kel = parse_kel(icp(&aid).as_bytes()).unwrap();
+        assert_eq!(kel.aid, aid);
+        assert_eq!(kel.latest().ilk, "icp");
+        assert!(Webs.validate(icp(&aid).as_bytes()).is_ok());
+    }
+
+    #[test]
+    fn validate_accepts_chained_events() {
+        let aid = digest(1);
+        let rot = digest(2);
+        let stream = format!(
+            "{}{}\n{}",
+            icp(&aid),
+            next(&aid, "rot", 1, &rot, &aid),
+            next(&aid, "ixn", 2, &digest(3), &rot)
+        );
+        let kel = parse_kel(stream.as_bytes()).unwrap();
+        assert_eq!(kel.events.len(), 3);
+        assert_eq!(kel.latest().sn, 2);
+    }
+
+    #[test]
+    fn validate_skips_non_key_messages() {
+        let aid = digest(1);
+        let rct = event(serde_json::json!({"t": "rct", "d": aid, "i": aid, "s": "0"}));
+        let stream = format!("{}{rct}", icp(&aid));
+        assert_eq!(parse_kel(stream.as_bytes()).unwrap().events.len(), 1);
+    }
+
+    #[test]
+    fn validate_rejects_missing_inception() {
+        let aid = digest(1);
+        let err = Webs
+            .validate(next(&aid, "rot", 1, &digest(2), &aid).as_bytes())
+            .expect_err("must reject");
+        assert!(err.to_string().contains("inception"));
+    }
+
+    #[test]
+    fn validate_rejects_sequence_gap() {
+        let aid = digest(1);
+        let stream = format!("{}{}", icp(&aid), next(&aid, "rot", 2, &digest(2), &aid));
+        let err = Webs.validate(stream.as_bytes()).expect_err("must reject");
+        assert!(err.to_string().contains("does not follow"));
+    }
+
+    #[test]
+    fn validate_rejects_broken_prior_digest() {
+        let aid = digest(1);
+        let stream = format!(
+            "{}{}",
+            icp(&aid),
+            next(&aid, "rot", 1, &digest(2), &digest(9))
+        );
+        let err = Webs.validate(stream.as_bytes()).expect_err("must reject");
+        assert!(err.to_string().contains("prior digest"));
+    }
+
+    #[test]
+    fn validate_rejects_unsigned_key_event() {
+        let aid = digest(1);
+        let signed = icp(&aid);
+        let unsigned = &signed[..signed.find("-AAB").unwrap()];
+        let err = Webs.validate(unsigned.as_bytes()).expect_err("must reject");
+        assert!(err.to_string().contains("no attached signatures"));
+    }
+
+    #[test]
+    fn validate_rejects_bad_version_string_size() {
+        let aid = digest(1);
+        let truncated = icp(&aid).replacen("KERI10JSON0", "KERI10JSONf", 1);
+        assert!(Webs.validate(truncated.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn validate_rejects_non_keri_payload() {
+        assert!(Webs.validate(br#"{"id":"did:webs:x"}"#).is_err());
+        assert!(Webs.validate(b"").is_err());
+    }
+
+    // ---- apply_update ----
+
+    #[test]
+    fn apply_update_appends_chained_event() {
+        let aid = digest(1);
+        let existing = icp(&aid);
+        let rot = next(&aid, "rot", 1, &digest(2), &aid);
+        let out = Webs
+            .apply_update(Some(existing.as_bytes()), rot.as_bytes())
+            .unwrap();
+        assert_eq!(out, format!("{existing}{rot}").into_bytes());
+    }
+
+    #[test]
+    fn apply_update_rejects_unchained_event() {
+        let aid = digest(1);
+        let existing = icp(&aid);
+        let rot = next(&aid, "rot", 1, &digest(2), &digest(8));
+        assert!(
+            Webs.apply_update(Some(existing.as_bytes()), rot.as_bytes())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn apply_update_rejects_second_inception() {
+        let existing = icp(&digest(1));
+        assert!(
+            Webs.apply_update(Some(existing.as_bytes()), existing.as_bytes())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn apply_update_rejects_empty_new_data() {
+        assert!(Webs.apply_update(None, b"").is_err());
+        assert!(Webs.apply_update(None, b" \n").is_err());
+    }
+
+    // ---- validate_did_document ----
+
+    #[test]
+    fn did_document_must_name_the_kel_aid() {
+        let aid = digest(1);
+        let kel = icp(&aid);
+        let doc = serde_json::json!({ "id": did(&aid) }).to_string();
+        assert!(validate_did_document(doc.as_bytes(), kel.as_bytes()).is_ok());
+
+        let other = serde_json::json!({ "id": did(&digest(2)) }).to_string();
+        let err = validate_did_document(other.as_bytes(), kel.as_bytes())
+            .expect_err("AID mismatch must reject");
+        assert!(err.to_string().contains("establishes"));
+    }
+
+    #[test]
+    fn inspected_kel_needs_its_matching_document() {
+        let aid = digest(1);
+        let kel = icp(&aid);
+        let doc = serde_json::json!({ "id": did(&aid) }).to_string();
+        let summary = crate::did_ops::inspect_did_log(&kel, Some(&doc)).unwrap();
+        assert_eq!(summary.method, "webs");
+        assert_eq!(summary.did_id, did(&aid));
+
+        assert!(crate::did_ops::inspect_did_log(&kel, None).is_err());
+        let other = serde_json::json!({ "id": did(&digest(2)) }).to_string();
+        assert!(crate::did_ops::inspect_did_log(&kel, Some(&other)).is_err());
+    }
+
+    #[test]
+    fn did_document_rejects_other_methods() {
+        let kel = icp(&digest(1));
+        let err = validate_did_document(br#"{"id":"did:web:example.com"}"#, kel.as_bytes())
+            .expect_err("must reject");
+        assert!(matches!(err, MethodError::Validation(_)));
+    }
+}
@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 use tracing::{debug, warn};
 
 use super::error::AppError;
-use super::store::KeyspaceHandle;
+use super::store::{KeyspaceHandle, WriteBatch};
 
 /// Roles that determine endpoint access permissions.
 ///
@@ -220,6 +220,20 @@ pub async fn delete_acl_entry(acl: &KeyspaceHandle, did: &str) -> Result<(), App
     acl.remove(acl_key(did)).await
 }
 
+/// [`store_acl_entry`] as part of `batch`.
+pub fn stage_acl_entry(
+    batch: &mut WriteBatch,
+    acl: &KeyspaceHandle,
+    entry: &AclEntry,
+) -> Result<(), AppError> {
+    batch.insert(acl, acl_key(&entry.did), entry)
+}
+
+/// [`delete_acl_entry`] as part of `batch`.
+pub fn stage_acl_delete(batch: &mut WriteBatch, acl: &KeyspaceHandle, did: &str) {
+    batch.remove(acl, acl_key(did));
+}
+
 /// List all ACL entries.
 pub async fn list_acl_entries(acl: &KeyspaceHandle) -> Result<Vec<AclEntry>, AppError> {
     let raw = acl.prefix_iter_raw("acl:").await?;
@@ -0,0 +1,458 @@
+//! Scoped API keys for machine clients (CI pipelines, cron jobs) that
+//! cannot hold a DID signing key.
+//!
+//! An admin mints a key bound to an existing ACL entry. The key acts as
+//! that entry's DID, with the entry's *current* role — so revoking or
+//! demoting the entry takes the key's power with it — but only on the
+//! Trust Tasks listed in [`ApiKey::tasks`] and, when
+//! [`ApiKey::domains`] is non-empty, only on those domains.
+//!
+//! The token is `dhk_<id>.<secret>`, sent as `Authorization: Bearer`. Only
+//! the SHA-256 of the secret is stored; the token is shown once, at
+//! minting. The same [`AuthClaims`](super::AuthClaims) extractor accepts
+//! it, and checks the route's task from [`RouteTrustTasks`] against the
+//! key's scope. Exempt routes refuse keys, unless the route carries
+//! [`HandlerChecksTask`] because its handler checks the task itself.
+//!
+//! Keys are always `aal1`: they can never satisfy a step-up gate.
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+
+use crate::server::acl::{Role, check_acl};
+use crate::server::auth::constant_time_eq;
+use crate::server::auth::session::now_epoch;
+use crate::server::error::AppError;
+use crate::server::store::{KeyspaceHandle, WriteBatch};
+use crate::server::trust_task::RouteTrustTasks;
+
+/// Prefix that tells an API key from a JWT on the `Authorization` header.
+pub const TOKEN_PREFIX: &str = "dhk_";
+
+/// `last_used_at` is written at most this often per key, so a busy job
+/// does not turn every request into a store write.
+const LAST_USED_GRANULARITY: u64 = 60;
+
+/// The `amr` an API-key caller carries.
+pub const AMR: &str = "api-key";
+
+fn key_key(id: &str) -> String {
+    format!("key:{id}")
+}
+
+/// Last use lives in its own row so recording it never rewrites the key:
+/// a write-back racing a revocation would otherwise bring the key back.
+fn used_key(id: &str) -> String {
+    format!("used:{id}")
+}
+
+/// A stored API key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub id: String,
+    /// DID of the ACL entry the key acts as.
+    pub did: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Hex SHA-256 of the secret half of the token.
+    pub secret_hash: String,
+    /// Trust-Task type URIs the key may call. Never empty.
+    pub tasks: Vec<String>,
+    /// Domains the key is confined to, within the ACL entry's own scope.
+    /// Empty: the entry's scope.
+    #[serde(default)]
+    pub domains: Vec<String>,
+    pub created_at: u64,
+    /// DID of the admin who minted it.
+    pub created_by: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+    /// Filled in from the `used:<id>` row when listing; never stored on
+    /// the key itself.
+    #[serde(default, skip_serializing)]
+    pub last_used_at: Option<u64>,
+}
+
+impl ApiKey {
+    /// Mint a key. Returns the record to store and the token to hand out
+    /// once; the token is not recoverable from the record.
+    pub fn mint(
+        did: String,
+        label: Option<String>,
+        tasks: Vec<String>,
+        domains: Vec<String>,
+        expires_at: Option<u64>,
+        created_by: String,
+    ) -> (Self, String) {
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        let secret = URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>());
+        let token = format!("{TOKEN_PREFIX}{id}.{secret}");
+        let key = Self {
+            id,
+            did,
+            label,
+            secret_hash: hash_secret(&secret),
+            tasks,
+            domains,
+            created_at: now_epoch(),
+            created_by,
+            expires_at,
+            last_used_at: None,
+        };
+        (key, token)
+    }
+
+    /// Whether the key has expired at `now`.
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|exp| now >= exp)
+    }
+
+    /// The scope carried on [`AuthClaims`](super::AuthClaims).
+    pub fn scope(&self) -> ApiKeyScope {
+        ApiKeyScope {
+            id: self.id.clone(),
+            tasks: self.tasks.clone(),
+            domains: self.domains.clone(),
+        }
+    }
+}
+
+/// What an API-key caller is confined to, on top of its ACL entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiKeyScope {
+    pub id: String,
+    pub tasks: Vec<String>,
+    pub domains: Vec<String>,
+}
+
+impl ApiKeyScope {
+    pub fn allows_task(&self, type_uri: &str) -> bool {
+        self.tasks.iter().any(|t| t == type_uri)
+    }
+
+    pub fn allows_domain(&self, domain: &str) -> bool {
+        self.domains.is_empty() || self.domains.iter().any(|d| d == domain)
+    }
+
+    /// Whether any of the route's tasks is in scope. `None` (an exempt
+    /// route) is out of scope.
+    pub fn allows_route(&self, route: Option<&RouteTrustTasks>) -> bool {
+        route.is_some_and(|r| r.0.iter().any(|t| self.allows_task(t.as_str())))
+    }
+}
+
+/// Route marker: the handler checks an API key's task scope itself,
+/// because the route serves more than one task (`POST /api/trust-tasks`
+/// dispatches on the document's `type`). Add it as an
+/// `axum::Extension` layer. Such a handler also owns the domain check.
+#[derive(Debug, Clone, Copy)]
+pub struct HandlerChecksTask;
+
+/// Route marker: the handler confines a key to its
+/// [`ApiKey::domains`]. A key with a domain restriction is refused on
+/// every route without it, so a route that is not domain-aware cannot
+/// let it act on another domain.
+#[derive(Debug, Clone, Copy)]
+pub struct DomainAware;
+
+/// Where API keys are looked up: the key rows, and the ACL that gives a
+/// key its role.
+#[derive(Clone)]
+pub struct ApiKeyStore {
+    pub keys: KeyspaceHandle,
+    pub acl: KeyspaceHandle,
+}
+
+fn hash_secret(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+/// Split `dhk_<id>.<secret>`.
+fn parse_token(token: &str) -> Option<(&str, &str)> {
+    let (id, secret) = token.strip_prefix(TOKEN_PREFIX)?.split_once('.')?;
+    (!id.is_empty() && !secret.is_empty()).then_some((id, secret))
+}
+
+pub async fn store_key(keys: &KeyspaceHandle, key: &ApiKey) -> Result<(), AppError> {
+    keys.insert(key_key(&key.id), key).await
+}
+
+/// [`store_key`] as part of `batch`.
+pub fn stage_key(
+    batch: &mut WriteBatch,
+    keys: &KeyspaceHandle,
+    key: &ApiKey,
+) -> Result<(), AppError> {
+    batch.insert(keys, key_key(&key.id), key)
+}
+
+pub async fn get_key(keys: &KeyspaceHandle, id: &str) -> Result<Option<ApiKey>, AppError> {
+    keys.get(key_key(id)).await
+}
+
+async fn last_used(keys: &KeyspaceHandle, id: &str) -> Result<Option<u64>, AppError> {
+    keys.get(used_key(id)).await
+}
+
+/// Every key, or only those acting as `did`, oldest first, with
+/// `last_used_at` filled in.
+pub async fn list_keys(keys: &KeyspaceHandle, did: Option<&str>) -> Result<Vec<ApiKey>, AppError> {
+    let mut out = Vec::new();
+    for (_key, value) in keys.prefix_iter_raw("key:").await? {
+        match serde_json::from_slice::<ApiKey>(&value) {
+            Ok(k) if did.is_none_or(|d| k.did == d) => out.push(k),
+            Ok(_) => {}
+            Err(e) => warn!("skipping malformed API key record: {e}"),
+        }
+    }
+    for key in &mut out {
+        key.last_used_at = last_used(keys, &key.id).await?;
+    }
+    out.sort_by_key(|k| k.created_at);
+    Ok(out)
+}
+
+/// Delete a key. Returns it, or `None` if there was none.
+pub async fn delete_key(keys: &KeyspaceHandle, id: &str) -> Result<Option<ApiKey>, AppError> {
+    let key = get_key(keys, id).await?;
+    if key.is_some() {
+        keys.remove(key_key(id)).await?;
+        keys.remove(used_key(id)).await?;
+    }
+    Ok(key)
+}
+
+/// [`delete_key`] as part of `batch`.
+pub async fn stage_key_delete(
+    batch: &mut WriteBatch,
+    keys: &KeyspaceHandle,
+    id: &str,
+) -> Result<Option<ApiKey>, AppError> {
+    let key = get_key(keys, id).await?;
+    if key.is_some() {
+        batch.remove(keys, key_key(id));
+        batch.remove(keys, used_key(id));
+    }
+    Ok(key)
+}
+
+/// Delete every key acting as `did`. Called when its ACL entry is
+/// deleted, so re-creating the entry later does not bring them back.
+pub async fn delete_keys_for_did(keys: &KeyspaceHandle, did: &str) -> Result<usize, AppError> {
+    let doomed = list_keys(keys, Some(did)).await?;
+    for key in &doomed {
+        delete_key(keys, &key.id).await?;
+    }
+    Ok(doomed.len())
+}
+
+/// Check a `dhk_` token and return the key with the role its ACL entry
+/// holds now. Every failure is the same `401`, so a caller cannot tell an
+/// unknown key from a wrong secret.
+pub async fn authenticate(store: &ApiKeyStore, token: &str) -> Result<(ApiKey, Role), AppError> {
+    let rejected = || AppError::Unauthorized("invalid API key".into());
+    let (id, secret) = parse_token(token).ok_or_else(rejected)?;
+    let mut key = get_key(&store.keys, id).await?.ok_or_else(|| {
+        warn!(key_id = %id, "auth rejected: unknown API key");
+        rejected()
+    })?;
+    if !constant_time_eq(hash_secret(secret).as_bytes(), key.secret_hash.as_bytes()) {
+        warn!(key_id = %id, "auth rejected: API key secret mismatch");
+        return Err(rejected());
+    }
+    let now = now_epoch();
+    if key.is_expired(now) {
+        warn!(key_id = %id, "auth rejected: API key expired");
+        return Err(AppError::Unauthorized("API key has expired".into()));
+    }
+    let role = check_acl(&store.acl, &key.did).await.map_err(|_| {
+        warn!(key_id = %id, did = %key.did, "auth rejected: API key's ACL entry is gone");
+        rejected()
+    })?;
+
+    // Best effort, throttled. Only the `used:` row is written, so this
+    // cannot undo a concurrent `delete_key`; at worst it leaves an orphan
+    // row that nothing reads.
+    let previous = last_used(&store.keys, id).await.ok().flatten();
+    if previous.is_none_or(|t| now.saturating_sub(t) >= LAST_USED_GRANULARITY) {
+        if let Err(e) = store.keys.insert(used_key(id), &now).await {
+            debug!(key_id = %id, error = %e, "failed to record API key use");
+        }
+        key.last_used_at = Some(now);
+    } else {
+        key.last_used_at = previous;
+    }
+    Ok((key, role))
+}
+
+#[cfg(all(test, feature = "store-fjall"))]
+mod tests {
+    use super::*;
+    use crate::server::acl::{AclEntry, store_acl_entry};
+    use crate::server::config::StoreConfig;
+    use crate::server::domain::DomainScope;
+    use crate::server::store::{KS_ACL, KS_API_KEYS, Store};
+    use crate::server::trust_task::TrustTask;
+    use std::path::PathBuf;
+
+    async fn make_store() -> (ApiKeyStore, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Store::open(&StoreConfig {
+            data_dir: PathBuf::from(dir.path()),
+            ..StoreConfig::default()
+        })
+        .await
+        .unwrap();
+        let keys = ApiKeyStore {
+            keys: store.keyspace(KS_API_KEYS).unwrap(),
+            acl: store.keyspace(KS_ACL).unwrap(),
+        };
+        store_acl_entry(
+            &keys.acl,
+            &AclEntry {
+                did: "did:example:ci".into(),
+                role: Role::Owner,
+                label: None,
+                created_at: 0,
+                max_total_size: None,
+                max_did_count: None,
+                domains: DomainScope::All,
+            },
+        )
+        .await
+        .unwrap();
+        (keys, dir)
+    }
+
+    fn mint(expires_at: Option<u64>) -> (ApiKey, String) {
+        ApiKey::mint(
+            "did:example:ci".into(),
+            Some("ci".into()),
+            vec!["https://trusttasks.org/did-hosting/did/log/1.0".into()],
+            vec!["a.example".into()],
+            expires_at,
+            "did:example:admin".into(),
+        )
+    }
+
+    #[tokio::test]
+    async fn authenticates_with_the_entry_role_and_hashes_at_rest() {
+        let (store, _dir) = make_store().await;
+        let (key, token) = mint(None);
+        store_key(&store.keys, &key).await.unwrap();
+
+        let secret = token.split_once('.').unwrap().1;
+        let raw = store.keys.get_raw(key_key(&key.id)).await.unwrap().unwrap();
+        assert!(!String::from_utf8_lossy(&raw).contains(secret));
+
+        let (found, role) = authenticate(&store, &token).await.unwrap();
+        assert_eq!(found.id, key.id);
+        assert_eq!(role, Role::Owner);
+        let listed = list_keys(&store.keys, Some("did:example:ci"))
+            .await
+            .unwrap();
+        assert!(listed[0].last_used_at.is_some());
+        // Recording the use did not rewrite the key row.
+        assert_eq!(
+            store.keys.get_raw(key_key(&key.id)).await.unwrap().unwrap(),
+            raw
+        );
+    }
+
+    /// A revocation racing key use must stick: recording the use must not
+    /// write the key back.
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn revocation_survives_concurrent_use() {
+        let (store, _dir) = make_store().await;
+        for _ in 0..20 {
+            let (key, token) = mint(None);
+            store_key(&store.keys, &key).await.unwrap();
+
+            let users: Vec<_> = (0..8)
+                .map(|_| {
+                    let (store, token) = (store.clone(), token.clone());
+                    tokio::spawn(async move { authenticate(&store, &token).await })
+                })
+                .collect();
+            let keys = store.keys.clone();
+            let id = key.id.clone();
+            let revoke = tokio::spawn(async move { delete_key(&keys, &id).await });
+            for user in users {
+                let _ = user.await.unwrap();
+            }
+            revoke.await.unwrap().unwrap();
+
+            assert!(get_key(&store.keys, &key.id).await.unwrap().is_none());
+            assert!(authenticate(&store, &token).await.is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn deleting_a_dids_keys_leaves_others() {
+        let (store, _dir) = make_store().await;
+        let (mine, _) = mint(None);
+        let (other, _) = ApiKey::mint(
+            "did:example:other".into(),
+            None,
+            vec!["https://trusttasks.org/did-hosting/did/log/1.0".into()],
+            Vec::new(),
+            None,
+            "did:example:admin".into(),
+        );
+        store_key(&store.keys, &mine).await.unwrap();
+        store_key(&store.keys, &other).await.unwrap();
+
+        assert_eq!(
+            delete_keys_for_did(&store.keys, "did:example:ci")
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(get_key(&store.keys, &mine.id).await.unwrap().is_none());
+        assert!(get_key(&store.keys, &other.id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn rejects_wrong_secret_expired_key_and_removed_entry() {
+        let (store, _dir) = make_store().await;
+        let (key, token) = mint(None);
+        store_key(&store.keys, &key).await.unwrap();
+
+        let forged = format!("{}.{}", token.split_once('.').unwrap().0, "x".repeat(43));
+        assert!(matches!(
+            authenticate(&store, &forged).await,
+            Err(AppError::Unauthorized(_))
+        ));
+        assert!(authenticate(&store, "dhk_nodot").await.is_err());
+
+        let (expired, expired_token) = mint(Some(1));
+        store_key(&store.keys, &expired).await.unwrap();
+        assert!(authenticate(&store, &expired_token).await.is_err());
+
+        crate::server::acl::delete_acl_entry(&store.acl, "did:example:ci")
+            .await
+            .unwrap();
+        assert!(authenticate(&store, &token).await.is_err());
+    }
+
+    #[test]
+    fn scope_checks_route_tasks_and_domains() {
+        let (key, _) = mint(None);
+        let scope = key.scope();
+        let route = |uri: &str| RouteTrustTasks(vec![TrustTask::new(uri).unwrap()]);
+
+        assert!(scope.allows_route(Some(&route(
+            "https://trusttasks.org/did-hosting/did/log/1.0"
+        ))));
+        assert!(!scope.allows_route(Some(&route(
+            "https://trusttasks.org/did-hosting/acl/list/1.0"
+        ))));
+        assert!(!scope.allows_route(None));
+
+        assert!(scope.allows_domain("a.example"));
+        assert!(!scope.allows_domain("b.example"));
+    }
+}
@@ -8,10 +8,14 @@ use axum_extra::headers::authorization::Bearer;
 use tracing::{debug, warn};
 
 use crate::server::acl::Role;
+use crate::server::auth::api_key::{
+    self, ApiKeyScope, ApiKeyStore, DomainAware, HandlerChecksTask,
+};
 use crate::server::auth::jwt::JwtKeys;
-use crate::server::auth::session::{SessionState, get_session};
+use crate::server::auth::session::{SessionState, get_session, is_token_revoked};
 use crate::server::error::AppError;
 use crate::server::store::KeyspaceHandle;
+use crate::server::trust_task::RouteTrustTasks;
 
 /// Trait that application states must implement to support auth extractors.
 ///
@@ -20,6 +24,12 @@ use crate::server::store::KeyspaceHandle;
 pub trait AuthState: Clone + Send + Sync + 'static {
     fn jwt_keys(&self) -> Option<&Arc<JwtKeys>>;
     fn sessions_ks(&self) -> &KeyspaceHandle;
+
+    /// Where [`api_key`] tokens are checked. `None` (the default): this
+    /// service does not accept API keys.
+    fn api_keys(&self) -> Result<Option<ApiKeyStore>, AppError> {
+        Ok(None)
+    }
 }
 
 /// Extracted from a valid JWT Bearer token on protected routes.
@@ -48,6 +58,10 @@ pub struct AuthClaims {
     /// Assurance level on the session's token (`"aal1"` base, `"aal2"`
     /// after a step-up). Gated by [`StepUpAuth`].
     pub acr: String,
+    /// Set when the caller used an API key rather than a session: the
+    /// tasks and domains the key is confined to. Handlers that act on a
+    /// domain check it with [`ApiKeyScope::allows_domain`].
+    pub api_key: Option<ApiKeyScope>,
 }
 
 impl<S: AuthState> FromRequestParts<S> for AuthClaims {
@@ -65,6 +79,10 @@ impl<S: AuthState> FromRequestParts<S> for AuthClaims {
 
         let token = auth.token();
 
+        if token.starts_with(api_key::TOKEN_PREFIX) {
+            return api_key_claims(parts, state, token).await;
+        }
+
         // Decode and validate JWT
         let jwt_keys = state
             .jwt_keys()
@@ -72,6 +90,14 @@ impl<S: AuthState> FromRequestParts<S> for AuthClaims {
 
         let claims = jwt_keys.decode(token)?;
 
+        // Denylisted by a revocation (logout, an admin kill, or an ACL
+        // change). The session row is deleted too, but the denylist does
+        // not depend on it.
+        if !claims.jti.is_empty() && is_token_revoked(state.sessions_ks(), &claims.jti).await? {
+            warn!(session_id = %claims.session_id, "auth rejected: token on the revocation denylist");
+            return Err(AppError::Unauthorized("token has been revoked".into()));
+        }
+
         // Verify session exists and is authenticated
         let session = get_session(state.sessions_ks(), &claims.session_id)
             .await?
@@ -114,10 +140,54 @@ impl<S: AuthState> FromRequestParts<S> for AuthClaims {
             session_pubkey_b58btc: session.session_pubkey_b58btc,
             amr: claims.amr,
             acr: claims.acr,
+            api_key: None,
         })
     }
 }
 
+/// [`AuthClaims`] for an API-key bearer. The route must serve one of the
+/// key's tasks, or check the task itself ([`HandlerChecksTask`]); a key
+/// confined to domains also needs a [`DomainAware`] route.
+async fn api_key_claims<S: AuthState>(
+    parts: &Parts,
+    state: &S,
+    token: &str,
+) -> Result<AuthClaims, AppError> {
+    let store = state
+        .api_keys()?
+        .ok_or_else(|| AppError::Unauthorized("API keys are not accepted here".into()))?;
+    let (key, role) = api_key::authenticate(&store, token).await?;
+    let scope = key.scope();
+
+    let handler_checks = parts.extensions.get::<HandlerChecksTask>().is_some();
+    if !handler_checks && !scope.allows_route(parts.extensions.get::<RouteTrustTasks>()) {
+        warn!(key_id = %key.id, path = %parts.uri.path(), "auth rejected: route outside the API key's tasks");
+        return Err(AppError::Forbidden(
+            "this API key is not scoped to this operation".into(),
+        ));
+    }
+    if !scope.domains.is_empty()
+        && !handler_checks
+        && parts.extensions.get::<DomainAware>().is_none()
+    {
+        warn!(key_id = %key.id, path = %parts.uri.path(), "auth rejected: domain-restricted API key on a route that is not domain-aware");
+        return Err(AppError::Forbidden(
+            "a domain-restricted API key cannot be used on this route".into(),
+        ));
+    }
+
+    debug!(did = %key.did, role = %role, key_id = %key.id, "request authenticated by API key");
+    Ok(AuthClaims {
+        did: key.did,
+        role,
+        session_id: String::new(),
+        session_pubkey_b58btc: None,
+        amr: vec![api_key::AMR.into()],
+        acr: "aal1".into(),
+        api_key: Some(scope),
+    })
+}
+
 /// Extractor that requires the caller to have Service role.
 ///
 /// Use on endpoints that only service accounts should access (e.g. register-service):
@@ -213,6 +283,7 @@ mod tests {
     struct TestState {
         keys: Arc<JwtKeys>,
         ks: KeyspaceHandle,
+        api_keys: Option<ApiKeyStore>,
     }
 
     impl AuthState for TestState {
@@ -222,6 +293,9 @@ mod tests {
         fn sessions_ks(&self) -> &KeyspaceHandle {
             &self.ks
         }
+        fn api_keys(&self) -> Result<Option<ApiKeyStore>, AppError> {
+            Ok(self.api_keys.clone())
+        }
     }
 
     async fn make_state() -> (TestState, tempfile::TempDir) {
@@ -234,7 +308,8 @@ mod tests {
         .unwrap();
         let ks = store.keyspace(KS_SESSIONS).unwrap();
         let keys = Arc::new(JwtKeys::from_ed25519_bytes(&[9u8; 32]).unwrap());
-        (TestState { keys, ks }, dir)
+        let api_keys = None;
+        (TestState { keys, ks, api_keys }, dir)
     }
 
     fn parts_with_bearer(token: &str) -> axum::http::request::Parts {
@@ -255,8 +330,13 @@ mod tests {
     async fn seed_session(state: &TestState, role: Role, jti: &str) -> String {
         let session_id = uuid::Uuid::new_v4().to_string();
         let _ = role; // role lives on the JWT claims, not the session record
+        seed_session_with_id(state, &session_id, jti).await;
+        session_id
+    }
+
+    async fn seed_session_with_id(state: &TestState, session_id: &str, jti: &str) {
         let session = Session {
-            session_id: session_id.clone(),
+            session_id: session_id.to_string(),
             did: "did:example:caller".into(),
             challenge: String::new(),
             state: SessionState::Authenticated,
@@ -272,7 +352,6 @@ mod tests {
             acr_expires_at: None,
         };
         store_session(&state.ks, &session).await.unwrap();
-        session_id
     }
 
     fn issue(state: &TestState, session_id: &str, role: &str, jti: &str) -> String {
@@ -323,6 +402,123 @@ mod tests {
         assert!(matches!(err, AppError::Unauthorized(_)));
     }
 
+    #[tokio::test]
+    async fn auth_claims_rejects_revoked_token() {
+        use crate::server::auth::session::revoke_session;
+
+        let (state, _dir) = make_state().await;
+        let session_id = seed_session(&state, Role::Owner, "tok-1").await;
+        let token = issue(&state, &session_id, "owner", "tok-1");
+        revoke_session(&state.ks, &session_id).await.unwrap();
+
+        // The denylist holds even if a row for the session reappears.
+        seed_session_with_id(&state, &session_id, "tok-1").await;
+        let mut parts = parts_with_bearer(&token);
+        let err = AuthClaims::from_request_parts(&mut parts, &state)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Unauthorized(_)));
+    }
+
+    #[tokio::test]
+    async fn auth_claims_refuses_api_keys_where_not_accepted() {
+        let (state, _dir) = make_state().await;
+        let mut parts = parts_with_bearer("dhk_0123.secret");
+        let err = AuthClaims::from_request_parts(&mut parts, &state)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Unauthorized(_)));
+    }
+
+    /// A key reaches a route serving one of its tasks; a domain-restricted
+    /// key only one that is also domain-aware.
+    #[tokio::test]
+    async fn auth_claims_scopes_api_keys_to_tasks_and_domain_aware_routes() {
+        use crate::server::acl::{AclEntry, store_acl_entry};
+        use crate::server::auth::api_key::{ApiKey, store_key};
+        use crate::server::domain::DomainScope;
+        use crate::server::store::{KS_ACL, KS_API_KEYS};
+        use crate::server::trust_task::TrustTask;
+
+        let (mut state, _dir) = make_state().await;
+        let api_dir = tempfile::tempdir().unwrap();
+        let store = Store::open(&StoreConfig {
+            data_dir: PathBuf::from(api_dir.path()),
+            ..StoreConfig::default()
+        })
+        .await
+        .unwrap();
+        let api = ApiKeyStore {
+            keys: store.keyspace(KS_API_KEYS).unwrap(),
+            acl: store.keyspace(KS_ACL).unwrap(),
+        };
+        store_acl_entry(
+            &api.acl,
+            &AclEntry {
+                did: "did:example:ci".into(),
+                role: Role::Service,
+                label: None,
+                created_at: 0,
+                max_total_size: None,
+                max_did_count: None,
+                domains: DomainScope::All,
+            },
+        )
+        .await
+        .unwrap();
+        state.api_keys = Some(api.clone());
+
+        let task = "https://trusttasks.org/did-hosting/did/log/1.0";
+        let mint = |domains: Vec<String>| {
+            ApiKey::mint(
+                "did:example:ci".into(),
+                None,
+                vec![task.into()],
+                domains,
+                None,
+                "did:example:ci".into(),
+            )
+        };
+        let parts = |token: &str, route_task: &str, domain_aware: bool| {
+            let mut parts = parts_with_bearer(token);
+            parts
+                .extensions
+                .insert(RouteTrustTasks(vec![TrustTask::new(route_task).unwrap()]));
+            if domain_aware {
+                parts.extensions.insert(DomainAware);
+            }
+            parts
+        };
+
+        let (open, open_token) = mint(Vec::new());
+        store_key(&api.keys, &open).await.unwrap();
+        let auth = AuthClaims::from_request_parts(&mut parts(&open_token, task, false), &state)
+            .await
+            .unwrap();
+        assert_eq!(auth.did, "did:example:ci");
+        assert_eq!(auth.role, Role::Service);
+        assert_eq!(auth.acr, "aal1");
+        assert_eq!(auth.api_key.unwrap().id, open.id);
+
+        let other_task = "https://trusttasks.org/did-hosting/acl/list/1.0";
+        let err = AuthClaims::from_request_parts(&mut parts(&open_token, other_task, true), &state)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Forbidden(_)));
+
+        let (confined, confined_token) = mint(vec!["a.example".into()]);
+        store_key(&api.keys, &confined).await.unwrap();
+        let err = AuthClaims::from_request_parts(&mut parts(&confined_token, task, false), &state)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Forbidden(_)));
+        assert!(
+            AuthClaims::from_request_parts(&mut parts(&confined_token, task, true), &state)
+                .await
+                .is_ok()
+        );
+    }
+
     #[tokio::test]
     async fn auth_claims_rejects_unknown_session() {
         let (state, _dir) = make_state().await;
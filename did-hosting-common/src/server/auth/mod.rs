@@ -1,3 +1,4 @@
+pub mod api_key;
 pub mod backend;
 pub mod extractor;
 pub mod jwt;
@@ -3,8 +3,8 @@ use uuid::Uuid;
 use crate::server::acl::Role;
 use crate::server::auth::jwt::JwtKeys;
 use crate::server::error::AppError;
-use crate::server::store::KeyspaceHandle;
-use serde::Serialize;
+use crate::server::store::{KeyspaceHandle, WriteBatch};
+use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
 use tracing::{debug, warn};
 
@@ -26,8 +26,81 @@ fn refresh_key(token: &str) -> String {
     format!("refresh:{token}")
 }
 
-/// Store a new session in the `sessions` keyspace.
+fn revoked_key(token_id: &str) -> String {
+    format!("revoked:{token_id}")
+}
+
+/// Prefix of the `did → session` index rows of `did`. `#` cannot occur in a
+/// DID, so `did:example:a` never matches the rows of `did:example:a:b`.
+fn did_sessions_prefix(did: &str) -> String {
+    format!("did_session:{did}#")
+}
+
+fn did_session_key(did: &str, session_id: &str) -> String {
+    format!("{}{session_id}", did_sessions_prefix(did))
+}
+
+/// Prefix of the rows recording every access token `session_id` was issued.
+fn session_tokens_prefix(session_id: &str) -> String {
+    format!("session_token:{session_id}#")
+}
+
+fn session_token_key(session_id: &str, token_id: &str) -> String {
+    format!("{}{token_id}", session_tokens_prefix(session_id))
+}
+
+/// Set once every session written before the `did → session` index existed
+/// has been indexed.
+const DID_SESSION_INDEX_MARKER: &str = "did_session_index:v1";
+
+/// How long a revoked access token stays on the denylist when its session
+/// carries no refresh expiry to bound it.
+const REVOKED_TOKEN_TTL: u64 = 86_400;
+
+/// A denylisted access token, kept until it would have expired anyway.
+#[derive(Debug, Serialize, Deserialize)]
+struct RevokedToken {
+    session_id: String,
+    expires_at: u64,
+}
+
+/// An access token issued to a session, kept so revocation can denylist
+/// every token the session still has in circulation, not just its latest.
+#[derive(Debug, Serialize, Deserialize)]
+struct IssuedToken {
+    expires_at: u64,
+}
+
+/// When a token minted for `session` stops mattering: its refresh expiry,
+/// which no access token minted for it outlives.
+fn token_horizon(session: &Session) -> u64 {
+    session
+        .refresh_expires_at
+        .unwrap_or_else(|| now_epoch() + REVOKED_TOKEN_TTL)
+}
+
+/// Store a session in the `sessions` keyspace, indexing it under its DID
+/// and recording its current access token.
+///
+/// Every token issuer — ours and vti-common's refresh handler — persists
+/// through here, so the issued-token rows cover step-up rotations too. The
+/// index rows are written first: a crash in between leaves a dangling row
+/// that lookups skip, never an unindexed session.
 pub async fn store_session(sessions: &KeyspaceHandle, session: &Session) -> Result<(), AppError> {
+    sessions
+        .insert_raw(
+            did_session_key(&session.did, &session.session_id),
+            session.session_id.as_bytes().to_vec(),
+        )
+        .await?;
+    if let Some(ref token_id) = session.token_id {
+        let issued = IssuedToken {
+            expires_at: token_horizon(session),
+        };
+        sessions
+            .insert(session_token_key(&session.session_id, token_id), &issued)
+            .await?;
+    }
     sessions
         .insert(session_key(&session.session_id), session)
         .await?;
@@ -108,19 +181,250 @@ pub fn now_epoch() -> u64 {
         .as_secs()
 }
 
-/// Delete a single session and its refresh index.
+/// Delete a single session with its refresh index, issued-token rows and
+/// `did → session` index row.
 pub async fn delete_session(sessions: &KeyspaceHandle, session_id: &str) -> Result<(), AppError> {
     let session: Option<Session> = sessions.get(session_key(session_id)).await?;
     if let Some(session) = session {
-        if let Some(ref token) = session.refresh_token {
-            sessions.remove(refresh_key(token)).await?;
+        for key in session_rows(sessions, &session).await? {
+            sessions.remove(key).await?;
         }
-        sessions.remove(session_key(session_id)).await?;
         debug!(session_id, "session deleted");
     }
     Ok(())
 }
 
+/// Every row [`delete_session`] removes for `session`.
+async fn session_rows(
+    sessions: &KeyspaceHandle,
+    session: &Session,
+) -> Result<Vec<Vec<u8>>, AppError> {
+    let mut rows = Vec::new();
+    if let Some(ref token) = session.refresh_token {
+        rows.push(refresh_key(token).into_bytes());
+    }
+    for (key, _) in sessions
+        .prefix_iter_raw(session_tokens_prefix(&session.session_id))
+        .await?
+    {
+        rows.push(key);
+    }
+    rows.push(session_key(&session.session_id).into_bytes());
+    rows.push(did_session_key(&session.did, &session.session_id).into_bytes());
+    Ok(rows)
+}
+
+/// Index every session written before the `did → session` index existed.
+/// Runs once per store; the marker row makes later calls a single read.
+async fn backfill_did_session_index(sessions: &KeyspaceHandle) -> Result<(), AppError> {
+    if sessions.contains_key(DID_SESSION_INDEX_MARKER).await? {
+        return Ok(());
+    }
+    let mut indexed = 0u64;
+    for (_key, value) in sessions.prefix_iter_raw("session:").await? {
+        let Ok(session) = serde_json::from_slice::<Session>(&value) else {
+            continue;
+        };
+        sessions
+            .insert_raw(
+                did_session_key(&session.did, &session.session_id),
+                session.session_id.as_bytes().to_vec(),
+            )
+            .await?;
+        indexed += 1;
+    }
+    sessions
+        .insert_raw(
+            DID_SESSION_INDEX_MARKER,
+            now_epoch().to_string().into_bytes(),
+        )
+        .await?;
+    debug!(indexed, "did → session index backfilled");
+    Ok(())
+}
+
+/// Load every session of `did` through the `did → session` index,
+/// challenge-phase ones included. Index rows whose session is gone are
+/// skipped.
+async fn sessions_of_did(sessions: &KeyspaceHandle, did: &str) -> Result<Vec<Session>, AppError> {
+    backfill_did_session_index(sessions).await?;
+    let mut out = Vec::new();
+    for (_key, value) in sessions.prefix_iter_raw(did_sessions_prefix(did)).await? {
+        let Ok(session_id) = String::from_utf8(value) else {
+            continue;
+        };
+        if let Some(session) = get_session(sessions, &session_id).await?
+            && session.did == did
+        {
+            out.push(session);
+        }
+    }
+    Ok(out)
+}
+
+/// List authenticated sessions, optionally only those of `did`.
+///
+/// Sessions still in the challenge phase are left out: they hold no
+/// tokens, and the sweeper reaps them after `challenge_ttl`. With a `did`
+/// the listing reads that DID's index rows instead of every session.
+pub async fn list_sessions(
+    sessions: &KeyspaceHandle,
+    did: Option<&str>,
+) -> Result<Vec<Session>, AppError> {
+    let mut out = match did {
+        Some(did) => sessions_of_did(sessions, did).await?,
+        None => {
+            let mut all = Vec::new();
+            for (_key, value) in sessions.prefix_iter_raw("session:").await? {
+                match serde_json::from_slice::<Session>(&value) {
+                    Ok(s) => all.push(s),
+                    Err(e) => warn!("skipping malformed session record: {e}"),
+                }
+            }
+            all
+        }
+    };
+    out.retain(|s| s.state == SessionState::Authenticated);
+    out.sort_by_key(|s| s.created_at);
+    Ok(out)
+}
+
+/// Revoke a session: delete it and its refresh index, and denylist every
+/// access token it was issued.
+///
+/// Deleting the row already fails the tokens in the extractor; the
+/// denylist makes that independent of the row, so a token stays dead even
+/// if a session with its id is written again. That covers tokens a step-up
+/// rotated out as well as the current one. Each entry lives until the
+/// session's refresh expiry at the time the token was minted, which the
+/// token does not outlive.
+///
+/// Returns the revoked session, or `None` if there was none.
+pub async fn revoke_session(
+    sessions: &KeyspaceHandle,
+    session_id: &str,
+) -> Result<Option<Session>, AppError> {
+    let Some((session, revoked)) = revocation(sessions, session_id).await? else {
+        return Ok(None);
+    };
+    for (token_id, entry) in &revoked {
+        sessions.insert(revoked_key(token_id), entry).await?;
+    }
+    delete_session(sessions, session_id).await?;
+    debug!(session_id, did = %session.did, tokens = revoked.len(), "session revoked");
+    Ok(Some(session))
+}
+
+/// [`revoke_session`] as part of `batch`.
+pub async fn stage_revoke_session(
+    batch: &mut WriteBatch,
+    sessions: &KeyspaceHandle,
+    session_id: &str,
+) -> Result<Option<Session>, AppError> {
+    let Some((session, revoked)) = revocation(sessions, session_id).await? else {
+        return Ok(None);
+    };
+    for (token_id, entry) in &revoked {
+        batch.insert(sessions, revoked_key(token_id), entry)?;
+    }
+    for key in session_rows(sessions, &session).await? {
+        batch.remove(sessions, key);
+    }
+    Ok(Some(session))
+}
+
+/// A session and the denylist entry of every access token it was issued.
+async fn revocation(
+    sessions: &KeyspaceHandle,
+    session_id: &str,
+) -> Result<Option<(Session, Vec<(String, RevokedToken)>)>, AppError> {
+    let Some(session) = get_session(sessions, session_id).await? else {
+        return Ok(None);
+    };
+    let prefix = session_tokens_prefix(session_id);
+    let mut issued: Vec<(String, u64)> = Vec::new();
+    for (key, value) in sessions.prefix_iter_raw(prefix.as_str()).await? {
+        let Some(token_id) = std::str::from_utf8(&key)
+            .ok()
+            .and_then(|k| k.strip_prefix(prefix.as_str()))
+        else {
+            continue;
+        };
+        let expires_at = serde_json::from_slice::<IssuedToken>(&value)
+            .map(|t| t.expires_at)
+            .unwrap_or_else(|_| token_horizon(&session));
+        issued.push((token_id.to_string(), expires_at));
+    }
+    // Sessions stored before issued tokens were recorded only know their
+    // current one.
+    if let Some(ref token_id) = session.token_id
+        && !issued.iter().any(|(id, _)| id == token_id)
+    {
+        issued.push((token_id.clone(), token_horizon(&session)));
+    }
+    let revoked = issued
+        .into_iter()
+        .map(|(token_id, expires_at)| {
+            let entry = RevokedToken {
+                session_id: session_id.to_string(),
+                expires_at,
+            };
+            (token_id, entry)
+        })
+        .collect();
+    Ok(Some((session, revoked)))
+}
+
+/// Revoke every session of `did`, challenge-phase ones included. Returns
+/// how many were revoked.
+pub async fn revoke_sessions_for_did(
+    sessions: &KeyspaceHandle,
+    did: &str,
+) -> Result<usize, AppError> {
+    let mut revoked = 0;
+    for session in sessions_of_did(sessions, did).await? {
+        if revoke_session(sessions, &session.session_id)
+            .await?
+            .is_some()
+        {
+            revoked += 1;
+        }
+    }
+    Ok(revoked)
+}
+
+/// [`revoke_sessions_for_did`] as part of `batch`.
+pub async fn stage_revoke_sessions_for_did(
+    batch: &mut WriteBatch,
+    sessions: &KeyspaceHandle,
+    did: &str,
+) -> Result<usize, AppError> {
+    let mut revoked = 0;
+    for session in sessions_of_did(sessions, did).await? {
+        if stage_revoke_session(batch, sessions, &session.session_id)
+            .await?
+            .is_some()
+        {
+            revoked += 1;
+        }
+    }
+    Ok(revoked)
+}
+
+/// Whether an ACL change from `before` to `after` (`None`: the entry was
+/// removed) must revoke the DID's sessions. Access tokens carry the role
+/// they were minted with, so anything but a promotion to Admin would leave
+/// them claiming a role the DID no longer holds.
+pub fn acl_change_revokes_sessions(before: &Role, after: Option<&Role>) -> bool {
+    after.is_none_or(|after| after != before && *after != Role::Admin)
+}
+
+/// Whether the access token with JWT ID `token_id` has been revoked.
+pub async fn is_token_revoked(sessions: &KeyspaceHandle, token_id: &str) -> Result<bool, AppError> {
+    let revoked: Option<RevokedToken> = sessions.get(revoked_key(token_id)).await?;
+    Ok(revoked.is_some_and(|r| now_epoch() <= r.expires_at))
+}
+
 /// Remove expired sessions from the store.
 ///
 /// - `ChallengeSent` sessions expire after `challenge_ttl` seconds from `created_at`.
@@ -154,25 +458,41 @@ pub async fn cleanup_expired_sessions(
             if let Some(ref token) = session.refresh_token {
                 sessions.remove(refresh_key(token)).await?;
             }
+            sessions
+                .remove(did_session_key(&session.did, &session.session_id))
+                .await?;
             removed += 1;
         }
     }
 
-    // Clean up expired enrollment tokens (have an `expires_at` field).
-    let enrollments = sessions.prefix_iter_raw("enroll:").await?;
-    for (key, value) in enrollments {
-        #[derive(serde::Deserialize)]
-        struct EnrollmentExpiry {
-            expires_at: u64,
-        }
-        if let Ok(e) = serde_json::from_slice::<EnrollmentExpiry>(&value)
-            && now > e.expires_at
-        {
+    // Drop `did → session` index rows whose session is gone.
+    for (key, value) in sessions.prefix_iter_raw("did_session:").await? {
+        let Ok(session_id) = String::from_utf8(value) else {
+            continue;
+        };
+        if !sessions.contains_key(session_key(&session_id)).await? {
             sessions.remove(key).await?;
             removed += 1;
         }
     }
 
+    // Clean up expired enrollment tokens, denylist entries and issued-token
+    // rows (all have an `expires_at` field).
+    for prefix in ["enroll:", "revoked:", "session_token:"] {
+        for (key, value) in sessions.prefix_iter_raw(prefix).await? {
+            #[derive(serde::Deserialize)]
+            struct Expiry {
+                expires_at: u64,
+            }
+            if let Ok(e) = serde_json::from_slice::<Expiry>(&value)
+                && now > e.expires_at
+            {
+                sessions.remove(key).await?;
+                removed += 1;
+            }
+        }
+    }
+
     debug!(removed, "session cleanup complete");
 
     Ok(())
@@ -596,3 +916,234 @@ mod refresh_token_invariant {
         );
     }
 }
+
+#[cfg(all(test, feature = "store-fjall"))]
+mod revocation {
+    use super::*;
+    use crate::server::config::StoreConfig;
+    use crate::server::store::{KS_SESSIONS, Store};
+    use std::path::PathBuf;
+
+    #[test]
+    fn removal_and_demotion_revoke_promotion_does_not() {
+        assert!(acl_change_revokes_sessions(&Role::Owner, None));
+        assert!(acl_change_revokes_sessions(
+            &Role::Admin,
+            Some(&Role::Owner)
+        ));
+        assert!(acl_change_revokes_sessions(
+            &Role::Owner,
+            Some(&Role::Service)
+        ));
+        assert!(!acl_change_revokes_sessions(
+            &Role::Owner,
+            Some(&Role::Admin)
+        ));
+        assert!(!acl_change_revokes_sessions(
+            &Role::Owner,
+            Some(&Role::Owner)
+        ));
+    }
+
+    #[tokio::test]
+    async fn revoking_a_did_kills_only_its_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Store::open(&StoreConfig {
+            data_dir: PathBuf::from(dir.path()),
+            ..StoreConfig::default()
+        })
+        .await
+        .unwrap();
+        let ks = store.keyspace(KS_SESSIONS).unwrap();
+        let keys = JwtKeys::from_ed25519_bytes(&[12u8; 32]).unwrap();
+
+        let mut alice = Vec::new();
+        for _ in 0..2 {
+            let t = create_authenticated_session(
+                &ks,
+                &keys,
+                "did:example:alice",
+                &Role::Owner,
+                60,
+                900,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+            alice.push(t);
+        }
+        let bob = create_authenticated_session(
+            &ks,
+            &keys,
+            "did:example:bob",
+            &Role::Admin,
+            60,
+            900,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(list_sessions(&ks, None).await.unwrap().len(), 3);
+        assert_eq!(
+            list_sessions(&ks, Some("did:example:alice"))
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+
+        assert_eq!(
+            revoke_sessions_for_did(&ks, "did:example:alice")
+                .await
+                .unwrap(),
+            2
+        );
+        for t in &alice {
+            let jti = keys.decode(&t.access_token).unwrap().jti;
+            assert!(is_token_revoked(&ks, &jti).await.unwrap());
+            assert!(get_session(&ks, &t.session_id).await.unwrap().is_none());
+            assert!(
+                get_session_by_refresh(&ks, &t.refresh_token)
+                    .await
+                    .unwrap()
+                    .is_none()
+            );
+        }
+
+        let remaining = list_sessions(&ks, None).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].session_id, bob.session_id);
+        let bob_jti = keys.decode(&bob.access_token).unwrap().jti;
+        assert!(!is_token_revoked(&ks, &bob_jti).await.unwrap());
+
+        assert!(
+            revoke_session(&ks, "no-such-session")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    async fn make_ks() -> (KeyspaceHandle, JwtKeys, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Store::open(&StoreConfig {
+            data_dir: PathBuf::from(dir.path()),
+            ..StoreConfig::default()
+        })
+        .await
+        .unwrap();
+        let ks = store.keyspace(KS_SESSIONS).unwrap();
+        let keys = JwtKeys::from_ed25519_bytes(&[13u8; 32]).unwrap();
+        (ks, keys, dir)
+    }
+
+    /// A step-up rotates the session's `token_id`; revocation must still
+    /// denylist the token minted before it.
+    #[tokio::test]
+    async fn revoking_an_elevated_session_denylists_its_earlier_tokens() {
+        let (ks, keys, _dir) = make_ks().await;
+        let first = create_authenticated_session(
+            &ks,
+            &keys,
+            "did:example:alice",
+            &Role::Owner,
+            60,
+            900,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        let elevated = elevate_session(
+            &ks,
+            &keys,
+            &first.session_id,
+            &Role::Owner,
+            vec!["did".to_string(), "passkey".to_string()],
+            "aal2",
+            60,
+            900,
+        )
+        .await
+        .unwrap();
+
+        revoke_session(&ks, &first.session_id).await.unwrap();
+
+        for t in [&first, &elevated] {
+            let jti = keys.decode(&t.access_token).unwrap().jti;
+            assert!(is_token_revoked(&ks, &jti).await.unwrap());
+        }
+        assert!(
+            ks.prefix_iter_raw(session_tokens_prefix(&first.session_id))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    /// Per-DID lookups go through the index: a DID that extends another
+    /// does not match it, and sessions stored before the index existed are
+    /// picked up by the one-off backfill.
+    #[tokio::test]
+    async fn did_lookups_use_the_session_index() {
+        let (ks, keys, _dir) = make_ks().await;
+        for did in ["did:example:alice", "did:example:alice:b"] {
+            create_authenticated_session(&ks, &keys, did, &Role::Owner, 60, 900, None, None)
+                .await
+                .unwrap();
+        }
+
+        // A session written before the index: the row alone.
+        let legacy = create_authenticated_session(
+            &ks,
+            &keys,
+            "did:example:alice",
+            &Role::Owner,
+            60,
+            900,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        ks.remove(did_session_key("did:example:alice", &legacy.session_id))
+            .await
+            .unwrap();
+        for (key, _) in ks
+            .prefix_iter_raw(session_tokens_prefix(&legacy.session_id))
+            .await
+            .unwrap()
+        {
+            ks.remove(key).await.unwrap();
+        }
+
+        assert_eq!(
+            list_sessions(&ks, Some("did:example:alice"))
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            revoke_sessions_for_did(&ks, "did:example:alice")
+                .await
+                .unwrap(),
+            2
+        );
+        let legacy_jti = keys.decode(&legacy.access_token).unwrap().jti;
+        assert!(is_token_revoked(&ks, &legacy_jti).await.unwrap());
+
+        let remaining = list_sessions(&ks, None).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].did, "did:example:alice:b");
+        assert!(
+            ks.prefix_iter_raw(did_sessions_prefix("did:example:alice"))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,1254 @@
+//! Keyspace backups shared by `did-hosting-server`, `did-hosting-control`
+//! and `webvh-witness`.
+//!
+//! Each binary names its own keyspace set and wires this module to its
+//! `backup` / `restore` / `verify-backup` subcommands; the format, the
+//! `--keyspace` / `--exclude-keyspace` selection, encryption and the
+//! `--dry-run` diff are the same everywhere.
+//!
+//! ## Format (v4)
+//!
+//! A backup is a stream of JSON lines:
+//!
+//! 1. a [`Header`]: service, version, the service's config, the keyspaces
+//!    it covers and, for an incremental backup, `since`;
+//! 2. one line per pair, `{"ks":…,"k":…,"v":…}`, key and value
+//!    base64url-encoded, grouped by keyspace. An incremental backup also
+//!    has `{"ks":…,"k":…,"h":…}` for a key whose value is unchanged (`h`
+//!    is a digest of the value) and `{"ks":…,"k":…,"del":true}` for a key
+//!    deleted since its base;
+//! 3. a trailer with per-keyspace counts and the SHA-256 of every line
+//!    before it. A file that stops short of its trailer is truncated.
+//!
+//! Keyspaces are read a page at a time ([`KeyspaceHandle::scan_page_raw`]),
+//! so neither writing nor restoring holds a whole keyspace in memory.
+//!
+//! With one or more `age1…` recipients the stream is age-encrypted (X25519)
+//! as it is written; reading it back takes a matching identity file.
+//!
+//! ## Incremental backups
+//!
+//! An incremental backup is taken against a base: an earlier full or
+//! incremental v4 backup. Every keyspace is
+//! compared with the base key by key, and only new and changed values are
+//! written in full. Unchanged keys get a digest line, so the next
+//! incremental can use this one as its base, and keys the base has but the
+//! store no longer does get a deletion line. The base's key set is held in
+//! memory while the backup is written, as two 16-byte digests per key.
+//!
+//! Chain them by restoring the full backup, then each incremental in
+//! order.
+//!
+//! ## Restore
+//!
+//! Restore reads the whole file once to check it, then again to write.
+//! Every backed-up pair is written and every recorded deletion applied;
+//! other keys that exist only in the store are left alone. The store is
+//! opened directly, so the service must be stopped (fjall holds an
+//! exclusive lock).
+//!
+//! v1–v3 files (a single JSON document; server only before v3) still
+//! restore. They carry no digest.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::error::AppError;
+use super::store::{KS_SESSIONS, KeyspaceHandle, RawKvPair, Store};
+
+/// Backup-format version.
+///
+/// - **v1**: server only — one JSON document, `{ dids, acl, stats, sessions }`.
+/// - **v2**: server only — adds `{ domains, assignments, pending_purges,
+///   registry, timeseries, meta, witnesses }`.
+/// - **v3**: shared by every service. Adds `service`, renames
+///   `server_version` to `service_version`, `keyspaces` is an open map.
+/// - **v4** (current): the streamed, digest-checked JSON-lines format in
+///   the module docs. Optionally age-encrypted and incremental.
+pub const BACKUP_VERSION: u32 = 4;
+
+/// `format` tag on the header line, telling a v4 stream apart from a
+/// v1–v3 document.
+const FORMAT: &str = "did-hosting-backup";
+
+/// First bytes of an age-encrypted file.
+const AGE_MAGIC: &[u8] = b"age-encryption.org/v1";
+
+/// Pairs read per store round trip.
+const PAGE_SIZE: usize = 500;
+
+/// Pairs per restore write batch.
+const BATCH_SIZE: usize = 1000;
+
+/// Session-keyspace prefixes that survive a backup: passkey credentials
+/// and pending enrolments. Auth sessions, refresh tokens and challenges
+/// are ephemeral and left out.
+pub const DURABLE_SESSION_PREFIXES: &[&str] = &["pk_user:", "pk_cred:", "pk_did:", "enroll:"];
+
+/// First line of a v4 backup.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Header {
+    pub format: String,
+    pub version: u32,
+    /// Which binary wrote it (`server`, `control`, `witness`). Restore
+    /// refuses a backup from a different service.
+    pub service: String,
+    pub created_at: String,
+    /// Unix seconds when the dump started. An incremental backup taken
+    /// `--incremental-from` this one records it as `since`.
+    pub snapshot_at: u64,
+    pub service_version: String,
+    /// The service's `AppConfig`, as JSON.
+    pub config: String,
+    /// Keyspaces covered, in the order they appear.
+    pub keyspaces: Vec<String>,
+    /// Set on incremental backups: the `snapshot_at` of the base backup.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EntryLine {
+    ks: String,
+    k: String,
+    v: String,
+}
+
+/// A key an incremental backup leaves as its base had it.
+#[derive(Serialize, Deserialize)]
+struct UnchangedLine {
+    ks: String,
+    k: String,
+    h: String,
+}
+
+/// A key deleted since the base backup.
+#[derive(Serialize, Deserialize)]
+struct DeletedLine {
+    ks: String,
+    k: String,
+    del: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Trailer {
+    end: bool,
+    counts: BTreeMap<String, u64>,
+    /// Deletion lines per keyspace.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    deleted: BTreeMap<String, u64>,
+    sha256: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Line {
+    Entry(EntryLine),
+    Unchanged(UnchangedLine),
+    Deleted(DeletedLine),
+    Trailer(Trailer),
+}
+
+/// One line of a backup after its header.
+pub enum Item {
+    Pair(RawKvPair),
+    /// An incremental backup's unchanged key, and the digest of its value.
+    Unchanged(Vec<u8>, ValueHash),
+    /// A key deleted since the incremental backup's base.
+    Deleted(Vec<u8>),
+}
+
+/// Truncated SHA-256 of a value, as [`UnchangedLine::h`] carries it.
+pub type ValueHash = [u8; 16];
+
+/// Truncated SHA-256 naming a key within a keyspace.
+type KeyId = [u8; 16];
+
+fn truncated_sha256(parts: &[&[u8]]) -> [u8; 16] {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    let mut out = [0u8; 16];
+    out.copy_from_slice(&hasher.finalize()[..16]);
+    out
+}
+
+fn key_id(ks: &str, key: &[u8]) -> KeyId {
+    truncated_sha256(&[ks.as_bytes(), &[0], key])
+}
+
+fn value_hash(value: &[u8]) -> ValueHash {
+    truncated_sha256(&[value])
+}
+
+fn decode_key(key: &str) -> Result<Vec<u8>, AppError> {
+    BASE64
+        .decode(key)
+        .map_err(|e| AppError::Config(format!("invalid base64url key: {e}")))
+}
+
+/// A v1–v3 backup: one JSON document.
+#[derive(Deserialize)]
+struct LegacyBackup {
+    version: u32,
+    #[serde(default = "legacy_service")]
+    service: String,
+    created_at: String,
+    #[serde(alias = "server_version")]
+    service_version: String,
+    config: String,
+    keyspaces: BTreeMap<String, Vec<LegacyEntry>>,
+}
+
+fn legacy_service() -> String {
+    "server".into()
+}
+
+#[derive(Deserialize)]
+struct LegacyEntry {
+    key: String,
+    value: String,
+}
+
+fn decode_pair(key: &str, value: &str) -> Result<RawKvPair, AppError> {
+    let key = decode_key(key)?;
+    let value = BASE64
+        .decode(value)
+        .map_err(|e| AppError::Config(format!("invalid base64url value: {e}")))?;
+    Ok((key, value))
+}
+
+/// Which keyspaces a backup or restore touches. Empty `only` means all
+/// of the service's keyspaces.
+#[derive(Debug, Clone, Default)]
+pub struct KeyspaceSelection {
+    pub only: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl KeyspaceSelection {
+    /// Narrow `known` to the selection, in `known`'s order. Naming a
+    /// keyspace the service does not have is an error, so a typo can't
+    /// silently back up nothing.
+    pub fn resolve(&self, known: &[&'static str]) -> Result<Vec<&'static str>, AppError> {
+        for name in self.only.iter().chain(&self.exclude) {
+            if !known.contains(&name.as_str()) {
+                return Err(AppError::Config(format!(
+                    "unknown keyspace '{name}' (expected one of: {})",
+                    known.join(", ")
+                )));
+            }
+        }
+        Ok(known
+            .iter()
+            .copied()
+            .filter(|ks| self.only.is_empty() || self.only.iter().any(|o| o == ks))
+            .filter(|ks| !self.exclude.iter().any(|e| e == ks))
+            .collect())
+    }
+}
+
+/// What a `backup` subcommand was asked for.
+#[derive(Debug, Clone, Default)]
+pub struct BackupRequest {
+    /// File path, or `-` for stdout.
+    pub output: String,
+    pub selection: KeyspaceSelection,
+    /// `age1…` X25519 recipients. Empty writes plaintext.
+    pub recipients: Vec<String>,
+    /// Write an incremental backup against this earlier one.
+    pub incremental_from: Option<PathBuf>,
+    /// age identity files, for reading an encrypted `incremental_from`.
+    pub identities: Vec<PathBuf>,
+}
+
+/// The backup an incremental one is taken against.
+struct Base {
+    path: String,
+    snapshot_at: u64,
+    /// Every key the base leaves in the store, with the digest of its
+    /// value. Keys still in the store are taken out as they are compared;
+    /// what remains was deleted since.
+    keys: HashMap<KeyId, ValueHash>,
+}
+
+impl BackupRequest {
+    /// Read `--incremental-from`, if given, checking it end to end.
+    fn base(&self, service: &str) -> Result<Option<Base>, AppError> {
+        let Some(path) = &self.incremental_from else {
+            return Ok(None);
+        };
+        let path = path.to_string_lossy().into_owned();
+        let mut reader = BackupReader::open(&path, &self.identities)?;
+        reader.check(service)?;
+        if reader.legacy {
+            return Err(AppError::Config(format!(
+                "{path} is a pre-v4 backup and cannot be a base; take a full backup first"
+            )));
+        }
+        let mut keys = HashMap::new();
+        while let Some((ks, item)) = reader.next_item()? {
+            match item {
+                Item::Pair((key, value)) => keys.insert(key_id(&ks, &key), value_hash(&value)),
+                Item::Unchanged(key, hash) => keys.insert(key_id(&ks, &key), hash),
+                Item::Deleted(_) => None,
+            };
+        }
+        Ok(Some(Base {
+            path,
+            snapshot_at: reader.header.snapshot_at,
+            keys,
+        }))
+    }
+}
+
+/// What a `restore` subcommand was asked for.
+#[derive(Debug, Clone, Default)]
+pub struct RestoreRequest {
+    pub input: String,
+    pub selection: KeyspaceSelection,
+    /// age identity files, for an encrypted backup.
+    pub identities: Vec<PathBuf>,
+    pub dry_run: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Writing
+// ---------------------------------------------------------------------------
+
+fn parse_recipients(raw: &[String]) -> Result<Vec<age::x25519::Recipient>, AppError> {
+    raw.iter()
+        .map(|r| {
+            r.trim()
+                .parse::<age::x25519::Recipient>()
+                .map_err(|e| AppError::Config(format!("invalid age recipient '{r}': {e}")))
+        })
+        .collect()
+}
+
+/// Create `path` for writing, `0600` on Unix: a backup holds passkey
+/// credentials and, for the witness, witness signing keys.
+fn create_private_file(path: &Path) -> io::Result<File> {
+    let mut opts = std::fs::OpenOptions::new();
+    opts.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        opts.mode(0o600);
+    }
+    opts.open(path)
+}
+
+enum Sink {
+    Plain(BufWriter<Box<dyn Write>>),
+    Encrypted(age::stream::StreamWriter<BufWriter<Box<dyn Write>>>),
+}
+
+impl Sink {
+    fn open(output: &str, recipients: &[age::x25519::Recipient]) -> Result<Self, AppError> {
+        let out: Box<dyn Write> = if output == "-" {
+            Box::new(io::stdout())
+        } else {
+            Box::new(create_private_file(Path::new(output)).map_err(|e| {
+                AppError::Config(format!("failed to create backup file {output}: {e}"))
+            })?)
+        };
+        let out = BufWriter::new(out);
+        if recipients.is_empty() {
+            return Ok(Self::Plain(out));
+        }
+        let encryptor =
+            age::Encryptor::with_recipients(recipients.iter().map(|r| r as &dyn age::Recipient))
+                .map_err(|e| AppError::Config(format!("cannot encrypt backup: {e}")))?;
+        Ok(Self::Encrypted(encryptor.wrap_output(out)?))
+    }
+
+    fn finish(self) -> io::Result<()> {
+        match self {
+            Self::Plain(mut w) => w.flush(),
+            Self::Encrypted(w) => w.finish()?.flush(),
+        }
+    }
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(w) => w.write(buf),
+            Self::Encrypted(w) => w.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Plain(w) => w.flush(),
+            Self::Encrypted(w) => w.flush(),
+        }
+    }
+}
+
+/// Writes lines to a [`Sink`], hashing them and counting pairs and
+/// deletions.
+struct LineWriter {
+    sink: Sink,
+    hasher: Sha256,
+    counts: BTreeMap<String, u64>,
+    deleted: BTreeMap<String, u64>,
+}
+
+impl LineWriter {
+    fn line<T: Serialize>(&mut self, value: &T) -> Result<(), AppError> {
+        let mut bytes = serde_json::to_vec(value)?;
+        bytes.push(b'\n');
+        self.hasher.update(&bytes);
+        self.sink.write_all(&bytes)?;
+        Ok(())
+    }
+
+    fn pair(&mut self, ks: &str, (key, value): &RawKvPair) -> Result<(), AppError> {
+        self.line(&EntryLine {
+            ks: ks.to_string(),
+            k: BASE64.encode(key),
+            v: BASE64.encode(value),
+        })?;
+        *self.counts.entry(ks.to_string()).or_default() += 1;
+        Ok(())
+    }
+
+    fn unchanged(&mut self, ks: &str, key: &[u8], hash: &ValueHash) -> Result<(), AppError> {
+        self.line(&UnchangedLine {
+            ks: ks.to_string(),
+            k: BASE64.encode(key),
+            h: BASE64.encode(hash),
+        })
+    }
+
+    fn deleted(&mut self, ks: &str, key: &[u8]) -> Result<(), AppError> {
+        self.line(&DeletedLine {
+            ks: ks.to_string(),
+            k: BASE64.encode(key),
+            del: true,
+        })?;
+        *self.deleted.entry(ks.to_string()).or_default() += 1;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<BTreeMap<String, u64>, AppError> {
+        let trailer = Trailer {
+            end: true,
+            counts: self.counts.clone(),
+            deleted: self.deleted.clone(),
+            sha256: hex::encode(self.hasher.clone().finalize()),
+        };
+        let mut bytes = serde_json::to_vec(&trailer)?;
+        bytes.push(b'\n');
+        self.sink.write_all(&bytes)?;
+        self.sink.finish()?;
+        Ok(self.counts)
+    }
+}
+
+/// Walks one prefix of a keyspace a page at a time.
+struct Pager<'a> {
+    ks: &'a KeyspaceHandle,
+    prefix: Vec<u8>,
+    after: Option<Vec<u8>>,
+    done: bool,
+}
+
+impl<'a> Pager<'a> {
+    fn new(ks: &'a KeyspaceHandle, prefix: &str) -> Self {
+        Self {
+            ks,
+            prefix: prefix.as_bytes().to_vec(),
+            after: None,
+            done: false,
+        }
+    }
+
+    async fn next_page(&mut self) -> Result<Option<Vec<RawKvPair>>, AppError> {
+        if self.done {
+            return Ok(None);
+        }
+        let page = self
+            .ks
+            .scan_page_raw(self.prefix.clone(), self.after.take(), PAGE_SIZE)
+            .await?;
+        self.done = page.len() < PAGE_SIZE;
+        self.after = page.last().map(|(k, _)| k.clone());
+        Ok(if page.is_empty() { None } else { Some(page) })
+    }
+}
+
+/// Whether `key` in keyspace `ks` belongs in a backup at all.
+fn backed_up(ks: &str, key: &[u8]) -> bool {
+    ks != KS_SESSIONS
+        || DURABLE_SESSION_PREFIXES
+            .iter()
+            .any(|prefix| key.starts_with(prefix.as_bytes()))
+}
+
+/// Stream a backup of `keyspaces` to `req.output`. Returns the pair count
+/// per keyspace, in `keyspaces` order; for an incremental backup, only the
+/// new and changed pairs count.
+pub async fn write_backup(
+    store: &Store,
+    service: &str,
+    service_version: &str,
+    config_json: String,
+    keyspaces: &[&str],
+    req: &BackupRequest,
+) -> Result<Vec<(String, u64)>, AppError> {
+    let recipients = parse_recipients(&req.recipients)?;
+    let mut base = req.base(service)?;
+    let now = chrono::Utc::now();
+
+    let mut out = LineWriter {
+        sink: Sink::open(&req.output, &recipients)?,
+        hasher: Sha256::new(),
+        counts: BTreeMap::new(),
+        deleted: BTreeMap::new(),
+    };
+    out.line(&Header {
+        format: FORMAT.into(),
+        version: BACKUP_VERSION,
+        service: service.into(),
+        created_at: now.to_rfc3339(),
+        snapshot_at: now.timestamp().max(0) as u64,
+        service_version: service_version.into(),
+        config: config_json,
+        keyspaces: keyspaces.iter().map(|ks| ks.to_string()).collect(),
+        since: base.as_ref().map(|b| b.snapshot_at),
+    })?;
+
+    for &name in keyspaces {
+        let ks = store.keyspace(name)?;
+        let mut pager = Pager::new(&ks, "");
+        while let Some(page) = pager.next_page().await? {
+            for pair in page.iter().filter(|(k, _)| backed_up(name, k)) {
+                let Some(base) = &mut base else {
+                    out.pair(name, pair)?;
+                    continue;
+                };
+                let hash = value_hash(&pair.1);
+                if base.keys.remove(&key_id(name, &pair.0)) == Some(hash) {
+                    out.unchanged(name, &pair.0, &hash)?;
+                } else {
+                    out.pair(name, pair)?;
+                }
+            }
+        }
+    }
+
+    if let Some(mut base) = base {
+        // Whatever the walk did not take out of `base.keys` is gone from
+        // the store. The digests cannot be reversed, so the base is read
+        // again for the keys themselves.
+        let mut reader = BackupReader::open(&base.path, &req.identities)?;
+        while let Some((ks, item)) = reader.next_item()? {
+            let key = match item {
+                Item::Pair((key, _)) | Item::Unchanged(key, _) => key,
+                Item::Deleted(_) => continue,
+            };
+            if keyspaces.contains(&ks.as_str()) && base.keys.remove(&key_id(&ks, &key)).is_some() {
+                out.deleted(&ks, &key)?;
+            }
+        }
+    }
+
+    let counts = out.finish()?;
+    Ok(keyspaces
+        .iter()
+        .map(|&ks| (ks.to_string(), counts.get(ks).copied().unwrap_or(0)))
+        .collect())
+}
+
+// ---------------------------------------------------------------------------
+// Reading
+// ---------------------------------------------------------------------------
+
+/// Load every identity in `paths` (age identity files, `AGE-SECRET-KEY-1…`).
+fn load_identities(paths: &[PathBuf]) -> Result<Vec<Box<dyn age::Identity>>, AppError> {
+    let mut identities = Vec::new();
+    for path in paths {
+        let file = age::IdentityFile::from_file(path.to_string_lossy().into_owned())
+            .map_err(|e| AppError::Config(format!("cannot read {}: {e}", path.display())))?;
+        identities.extend(file.into_identities().map_err(|e| {
+            AppError::Config(format!("unusable identity in {}: {e}", path.display()))
+        })?);
+    }
+    Ok(identities)
+}
+
+enum Source {
+    Stream {
+        lines: Box<dyn BufRead>,
+        hasher: Sha256,
+        counts: BTreeMap<String, u64>,
+        deleted: BTreeMap<String, u64>,
+        finished: bool,
+    },
+    Legacy(std::vec::IntoIter<(String, LegacyEntry)>),
+}
+
+/// A backup opened for reading: its header, then its lines one at a time.
+///
+/// For a v4 file the trailer is checked once the last line has been read;
+/// a digest or count mismatch, or a missing trailer, is an error from
+/// [`Self::next_item`].
+pub struct BackupReader {
+    pub header: Header,
+    /// A v1–v3 document, with no digest.
+    pub legacy: bool,
+    source: Source,
+}
+
+impl BackupReader {
+    /// Open `input`, decrypting with `identities` if it is age-encrypted.
+    pub fn open(input: &str, identities: &[PathBuf]) -> Result<Self, AppError> {
+        let file = File::open(input)
+            .map_err(|e| AppError::Config(format!("failed to read backup file {input}: {e}")))?;
+        let mut raw = BufReader::new(file);
+        let encrypted = raw.fill_buf()?.starts_with(AGE_MAGIC);
+        let mut lines: Box<dyn BufRead> = if encrypted {
+            if identities.is_empty() {
+                return Err(AppError::Config(format!(
+                    "{input} is encrypted; pass --identity <age identity file>"
+                )));
+            }
+            let identities = load_identities(identities)?;
+            let reader = age::Decryptor::new_buffered(raw)
+                .and_then(|d| d.decrypt(identities.iter().map(|i| i.as_ref())))
+                .map_err(|e| AppError::Config(format!("cannot decrypt {input}: {e}")))?;
+            Box::new(BufReader::new(reader))
+        } else {
+            Box::new(raw)
+        };
+
+        let mut first = String::new();
+        lines.read_line(&mut first)?;
+        if let Ok(header) = serde_json::from_str::<Header>(&first)
+            && header.format == FORMAT
+        {
+            let mut hasher = Sha256::new();
+            hasher.update(first.as_bytes());
+            return Ok(Self {
+                header,
+                legacy: false,
+                source: Source::Stream {
+                    lines,
+                    hasher,
+                    counts: BTreeMap::new(),
+                    deleted: BTreeMap::new(),
+                    finished: false,
+                },
+            });
+        }
+
+        let mut json = first;
+        lines.read_to_string(&mut json)?;
+        let legacy: LegacyBackup = serde_json::from_str(&json)
+            .map_err(|e| AppError::Config(format!("invalid backup JSON: {e}")))?;
+        let keyspaces = legacy.keyspaces.keys().cloned().collect();
+        let entries: Vec<(String, LegacyEntry)> = legacy
+            .keyspaces
+            .into_iter()
+            .flat_map(|(ks, entries)| entries.into_iter().map(move |e| (ks.clone(), e)))
+            .collect();
+        Ok(Self {
+            header: Header {
+                format: FORMAT.into(),
+                version: legacy.version,
+                service: legacy.service,
+                created_at: legacy.created_at,
+                snapshot_at: 0,
+                service_version: legacy.service_version,
+                config: legacy.config,
+                keyspaces,
+                since: None,
+            },
+            legacy: true,
+            source: Source::Legacy(entries.into_iter()),
+        })
+    }
+
+    /// Refuse a backup this binary cannot restore: a newer version, or
+    /// one written by another service.
+    pub fn check(&self, service: &str) -> Result<(), AppError> {
+        let version = self.header.version;
+        if version > BACKUP_VERSION {
+            return Err(AppError::Config(format!(
+                "backup version {version} is newer than this binary supports (max {BACKUP_VERSION}); \
+                 upgrade the binary or use a matching backup"
+            )));
+        }
+        if version < 1 {
+            return Err(AppError::Config(format!(
+                "unsupported backup version {version} (minimum 1)"
+            )));
+        }
+        if self.header.service != service {
+            return Err(AppError::Config(format!(
+                "this is a {} backup; restore it with that service's binary, not {service}",
+                self.header.service
+            )));
+        }
+        Ok(())
+    }
+
+    /// The next `(keyspace, pair)`, skipping an incremental backup's
+    /// unchanged and deleted keys; `None` once the backup is exhausted and,
+    /// for v4, its trailer has checked out.
+    pub fn next_pair(&mut self) -> Result<Option<(String, RawKvPair)>, AppError> {
+        while let Some((ks, item)) = self.next_item()? {
+            if let Item::Pair(pair) = item {
+                return Ok(Some((ks, pair)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The next `(keyspace, item)`, or `None` once the backup is exhausted
+    /// and, for v4, its trailer has checked out.
+    pub fn next_item(&mut self) -> Result<Option<(String, Item)>, AppError> {
+        let (lines, hasher, counts, deleted, finished) = match &mut self.source {
+            Source::Legacy(entries) => {
+                return match entries.next() {
+                    Some((ks, e)) => Ok(Some((ks, Item::Pair(decode_pair(&e.key, &e.value)?)))),
+                    None => Ok(None),
+                };
+            }
+            Source::Stream {
+                lines,
+                hasher,
+                counts,
+                deleted,
+                finished,
+            } => (lines, hasher, counts, deleted, finished),
+        };
+        if *finished {
+            return Ok(None);
+        }
+
+        let mut line = String::new();
+        if lines.read_line(&mut line)? == 0 {
+            return Err(AppError::Config(
+                "backup is truncated: it ends before its trailer".into(),
+            ));
+        }
+        let parsed: Line = serde_json::from_str(&line)
+            .map_err(|e| AppError::Config(format!("corrupt backup line: {e}")))?;
+        let (ks, item) = match parsed {
+            Line::Entry(entry) => {
+                *counts.entry(entry.ks.clone()).or_default() += 1;
+                (entry.ks, Item::Pair(decode_pair(&entry.k, &entry.v)?))
+            }
+            Line::Unchanged(unchanged) => {
+                let hash = BASE64
+                    .decode(&unchanged.h)
+                    .ok()
+                    .and_then(|h| ValueHash::try_from(h).ok())
+                    .ok_or_else(|| AppError::Config("invalid value digest in backup".into()))?;
+                (
+                    unchanged.ks,
+                    Item::Unchanged(decode_key(&unchanged.k)?, hash),
+                )
+            }
+            Line::Deleted(del) => {
+                *deleted.entry(del.ks.clone()).or_default() += 1;
+                (del.ks, Item::Deleted(decode_key(&del.k)?))
+            }
+            Line::Trailer(trailer) => {
+                let digest = hex::encode(hasher.clone().finalize());
+                if digest != trailer.sha256 {
+                    return Err(AppError::Config(format!(
+                        "backup digest mismatch: trailer says {}, content hashes to {digest}",
+                        trailer.sha256
+                    )));
+                }
+                if *counts != trailer.counts || *deleted != trailer.deleted {
+                    return Err(AppError::Config(
+                        "backup entry counts do not match its trailer".into(),
+                    ));
+                }
+                *finished = true;
+                return Ok(None);
+            }
+        };
+        if !self.header.keyspaces.contains(&ks) {
+            return Err(AppError::Config(format!(
+                "backup holds keyspace '{ks}' its header does not list"
+            )));
+        }
+        hasher.update(line.as_bytes());
+        Ok(Some((ks, item)))
+    }
+}
+
+/// Read a backup end to end without touching any store. Returns its
+/// header and pair count per keyspace.
+pub fn verify(
+    input: &str,
+    identities: &[PathBuf],
+    service: &str,
+) -> Result<(Header, Vec<(String, u64)>), AppError> {
+    let mut reader = BackupReader::open(input, identities)?;
+    reader.check(service)?;
+    let mut counts: BTreeMap<String, u64> = BTreeMap::new();
+    while let Some((ks, _)) = reader.next_pair()? {
+        *counts.entry(ks).or_default() += 1;
+    }
+    let counts = reader
+        .header
+        .keyspaces
+        .iter()
+        .map(|ks| (ks.clone(), counts.get(ks).copied().unwrap_or(0)))
+        .collect();
+    Ok((reader.header, counts))
+}
+
+/// Per-keyspace outcome of [`diff`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyspaceDiff {
+    pub keyspace: String,
+    /// In the backup, not in the store.
+    pub added: u64,
+    /// In both, with a different value — restore overwrites.
+    pub changed: u64,
+    pub unchanged: u64,
+    /// Deleted since an incremental backup's base, and still in the store.
+    pub removed: u64,
+    /// In the store only. Restore leaves these in place.
+    pub store_only: u64,
+}
+
+/// What restoring `keyspaces` from `reader` would change in `store`,
+/// without writing.
+pub async fn diff(
+    reader: &mut BackupReader,
+    store: &Store,
+    keyspaces: &[&str],
+) -> Result<Vec<KeyspaceDiff>, AppError> {
+    let mut handles = HashMap::new();
+    let mut diffs: HashMap<&str, KeyspaceDiff> = HashMap::new();
+    for &name in keyspaces {
+        handles.insert(name, store.keyspace(name)?);
+        diffs.insert(
+            name,
+            KeyspaceDiff {
+                keyspace: name.to_string(),
+                ..KeyspaceDiff::default()
+            },
+        );
+    }
+
+    while let Some((ks, item)) = reader.next_item()? {
+        let (Some(handle), Some(diff)) = (handles.get(ks.as_str()), diffs.get_mut(ks.as_str()))
+        else {
+            continue;
+        };
+        match item {
+            Item::Pair((key, value)) => match handle.get_raw(key).await? {
+                None => diff.added += 1,
+                Some(existing) if existing == value => diff.unchanged += 1,
+                Some(_) => diff.changed += 1,
+            },
+            Item::Deleted(key) => {
+                if handle.contains_key(key).await? {
+                    diff.removed += 1;
+                }
+            }
+            Item::Unchanged(..) => {}
+        }
+    }
+
+    for (name, diff) in diffs.iter_mut() {
+        let mut in_store = 0u64;
+        let mut pager = Pager::new(&handles[name], "");
+        while let Some(page) = pager.next_page().await? {
+            in_store += page.iter().filter(|(k, _)| backed_up(name, k)).count() as u64;
+        }
+        diff.store_only = in_store.saturating_sub(diff.changed + diff.unchanged + diff.removed);
+    }
+    Ok(keyspaces.iter().filter_map(|ks| diffs.remove(ks)).collect())
+}
+
+/// Write every pair in `keyspaces` from `reader` into `store`, and apply
+/// its deletions. Returns the count restored per keyspace, deletions
+/// included, in `keyspaces` order.
+///
+/// Writes as it reads, so only hand this a reader over a file that has
+/// already passed [`verify`].
+pub async fn restore(
+    reader: &mut BackupReader,
+    store: &Store,
+    keyspaces: &[&str],
+) -> Result<Vec<(String, u64)>, AppError> {
+    let mut handles = HashMap::new();
+    for &name in keyspaces {
+        handles.insert(name, store.keyspace(name)?);
+    }
+    let mut counts: BTreeMap<String, u64> = BTreeMap::new();
+    let mut batch = store.batch();
+    let mut pending = 0;
+    while let Some((ks, item)) = reader.next_item()? {
+        let Some(handle) = handles.get(ks.as_str()) else {
+            continue;
+        };
+        match item {
+            Item::Pair((key, value)) => batch.insert_raw(handle, key, value),
+            Item::Deleted(key) => batch.remove(handle, key),
+            Item::Unchanged(..) => continue,
+        }
+        *counts.entry(ks).or_default() += 1;
+        pending += 1;
+        if pending == BATCH_SIZE {
+            std::mem::replace(&mut batch, store.batch())
+                .commit()
+                .await?;
+            pending = 0;
+        }
+    }
+    batch.commit().await?;
+    Ok(keyspaces
+        .iter()
+        .map(|&ks| (ks.to_string(), counts.get(ks).copied().unwrap_or(0)))
+        .collect())
+}
+
+/// Where `restore` writes the backed-up config: `--config`, else the
+/// service's config env var, else `config.toml`.
+pub fn restore_config_path(config_path: Option<PathBuf>, env_var: &str) -> PathBuf {
+    config_path
+        .or_else(|| std::env::var(env_var).ok().map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from("config.toml"))
+}
+
+/// Print a `keyspace: N entries` table with a total, under `title`.
+pub fn print_counts(title: &str, counts: &[(String, u64)]) {
+    eprintln!();
+    eprintln!("  {title}");
+    eprintln!();
+    for (ks, n) in counts {
+        eprintln!("  {:<16}{n} entries", format!("{ks}:"));
+    }
+    let total: u64 = counts.iter().map(|(_, n)| n).sum();
+    eprintln!("  {:<16}{total} entries", "total:");
+    eprintln!();
+}
+
+/// Print a [`diff`] result.
+pub fn print_diff(diffs: &[KeyspaceDiff]) {
+    eprintln!();
+    eprintln!("  Dry run — nothing written. Restore would:");
+    eprintln!();
+    eprintln!(
+        "  {:<16}{:>8}{:>10}{:>11}{:>8}{:>12}",
+        "keyspace", "add", "overwrite", "unchanged", "delete", "store-only"
+    );
+    for d in diffs {
+        eprintln!(
+            "  {:<16}{:>8}{:>10}{:>11}{:>8}{:>12}",
+            d.keyspace, d.added, d.changed, d.unchanged, d.removed, d.store_only
+        );
+    }
+    eprintln!();
+    eprintln!("  Store-only keys are kept; restore deletes only what a backup records as deleted.");
+    eprintln!();
+}
+
+/// Print what [`verify`] found.
+pub fn print_verified(header: &Header, counts: &[(String, u64)]) {
+    let kind = match header.since {
+        Some(since) => format!("incremental since {since}"),
+        None => "full".into(),
+    };
+    print_counts(
+        &format!(
+            "Backup OK — v{} {} backup ({kind}), written {}",
+            header.version, header.service, header.created_at
+        ),
+        counts,
+    );
+    if header.version < BACKUP_VERSION {
+        eprintln!("  Note: pre-v4 backups carry no digest; only their structure was checked.");
+        eprintln!();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A v1 server backup — only the four original keyspaces, and
+    /// `server_version` rather than `service_version`.
+    fn v1_backup_json() -> String {
+        let k = BASE64.encode(b"k");
+        let v = BASE64.encode(b"v");
+        format!(
+            r#"{{
+              "version": 1,
+              "created_at": "2026-05-17T00:00:00Z",
+              "server_version": "0.6.0",
+              "config": "{{}}",
+              "keyspaces": {{
+                "dids":     [{{ "key": "{k}", "value": "{v}" }}],
+                "acl":      [],
+                "stats":    [],
+                "sessions": []
+              }}
+            }}"#
+        )
+    }
+
+    #[test]
+    fn v1_server_backup_still_loads() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("v1.json");
+        std::fs::write(&path, v1_backup_json()).unwrap();
+        let path = path.to_str().unwrap();
+
+        let (header, counts) = verify(path, &[], "server").unwrap();
+        assert_eq!(header.version, 1);
+        assert_eq!(header.service_version, "0.6.0");
+        assert!(counts.contains(&("dids".into(), 1)));
+        assert!(counts.contains(&("acl".into(), 0)));
+
+        let err = verify(path, &[], "control").unwrap_err().to_string();
+        assert!(err.contains("server backup"), "{err}");
+    }
+
+    #[test]
+    fn newer_versions_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("v9.json");
+        std::fs::write(
+            &path,
+            v1_backup_json().replace("\"version\": 1", "\"version\": 9"),
+        )
+        .unwrap();
+        assert!(verify(path.to_str().unwrap(), &[], "server").is_err());
+    }
+
+    #[test]
+    fn selection_filters_and_rejects_unknown_names() {
+        let known = ["dids", "acl", "sessions"];
+        assert_eq!(KeyspaceSelection::default().resolve(&known).unwrap(), known);
+        let sel = KeyspaceSelection {
+            only: vec!["dids".into(), "acl".into()],
+            exclude: vec!["acl".into()],
+        };
+        assert_eq!(sel.resolve(&known).unwrap(), ["dids"]);
+        let typo = KeyspaceSelection {
+            exclude: vec!["sesions".into()],
+            ..Default::default()
+        };
+        assert!(typo.resolve(&known).is_err());
+    }
+
+    #[cfg(feature = "store-fjall")]
+    mod store {
+        use super::super::*;
+        use crate::server::config::StoreConfig;
+        use crate::server::store::KS_ACL;
+
+        async fn temp_store(dir: &Path) -> Store {
+            Store::open(&StoreConfig {
+                data_dir: dir.into(),
+                ..StoreConfig::default()
+            })
+            .await
+            .unwrap()
+        }
+
+        fn request(output: &Path) -> BackupRequest {
+            BackupRequest {
+                output: output.to_string_lossy().into_owned(),
+                ..Default::default()
+            }
+        }
+
+        async fn seed(store: &Store) {
+            let acl = store.keyspace(KS_ACL).unwrap();
+            let sessions = store.keyspace(KS_SESSIONS).unwrap();
+            acl.insert_raw("a", "1").await.unwrap();
+            acl.insert_raw("b", "2").await.unwrap();
+            sessions.insert_raw("session:x", "live").await.unwrap();
+            sessions.insert_raw("pk_user:x", "cred").await.unwrap();
+        }
+
+        #[tokio::test]
+        async fn dry_run_diff_matches_what_restore_writes() {
+            let dir = tempfile::tempdir().unwrap();
+            let store = temp_store(&dir.path().join("store")).await;
+            seed(&store).await;
+            let file = dir.path().join("backup.jsonl");
+            let counts = write_backup(
+                &store,
+                "server",
+                "0.0.0",
+                "{}".into(),
+                &[KS_ACL, KS_SESSIONS],
+                &request(&file),
+            )
+            .await
+            .unwrap();
+            // Only the durable session survives.
+            assert_eq!(counts, vec![(KS_ACL.into(), 2), (KS_SESSIONS.into(), 1)]);
+
+            let acl = store.keyspace(KS_ACL).unwrap();
+            acl.insert_raw("b", "changed").await.unwrap();
+            acl.remove("a").await.unwrap();
+            acl.insert_raw("c", "3").await.unwrap();
+
+            let path = file.to_str().unwrap();
+            let mut reader = BackupReader::open(path, &[]).unwrap();
+            let diffs = diff(&mut reader, &store, &[KS_ACL]).await.unwrap();
+            assert_eq!(
+                diffs,
+                vec![KeyspaceDiff {
+                    keyspace: KS_ACL.into(),
+                    added: 1,
+                    changed: 1,
+                    unchanged: 0,
+                    removed: 0,
+                    store_only: 1,
+                }]
+            );
+            assert_eq!(acl.get_raw("a").await.unwrap(), None);
+
+            let mut reader = BackupReader::open(path, &[]).unwrap();
+            restore(&mut reader, &store, &[KS_ACL]).await.unwrap();
+            assert_eq!(acl.get_raw("a").await.unwrap(), Some(b"1".to_vec()));
+            assert_eq!(acl.get_raw("b").await.unwrap(), Some(b"2".to_vec()));
+            assert_eq!(acl.get_raw("c").await.unwrap(), Some(b"3".to_vec()));
+        }
+
+        #[tokio::test]
+        async fn tampered_or_truncated_backups_fail_verification() {
+            let dir = tempfile::tempdir().unwrap();
+            let store = temp_store(&dir.path().join("store")).await;
+            seed(&store).await;
+            let file = dir.path().join("backup.jsonl");
+            write_backup(
+                &store,
+                "server",
+                "0.0.0",
+                "{}".into(),
+                &[KS_ACL],
+                &request(&file),
+            )
+            .await
+            .unwrap();
+            let path = file.to_str().unwrap();
+            let good = std::fs::read_to_string(&file).unwrap();
+            verify(path, &[], "server").unwrap();
+
+            let tampered = good.replacen(&BASE64.encode(b"1"), &BASE64.encode(b"9"), 1);
+            std::fs::write(&file, tampered).unwrap();
+            let err = verify(path, &[], "server").unwrap_err().to_string();
+            assert!(err.contains("digest mismatch"), "{err}");
+
+            let lines: Vec<&str> = good.lines().collect();
+            std::fs::write(&file, lines[..lines.len() - 1].join("\n") + "\n").unwrap();
+            let err = verify(path, &[], "server").unwrap_err().to_string();
+            assert!(err.contains("truncated"), "{err}");
+        }
+
+        #[tokio::test]
+        async fn encrypted_backups_need_the_identity() {
+            use age::secrecy::ExposeSecret;
+
+            let dir = tempfile::tempdir().unwrap();
+            let store = temp_store(&dir.path().join("store")).await;
+            seed(&store).await;
+
+            let identity = age::x25519::Identity::generate();
+            let identity_file = dir.path().join("operator.key");
+            std::fs::write(&identity_file, identity.to_string().expose_secret()).unwrap();
+
+            let file = dir.path().join("backup.age");
+            let req = BackupRequest {
+                recipients: vec![identity.to_public().to_string()],
+                ..request(&file)
+            };
+            write_backup(&store, "server", "0.0.0", "{}".into(), &[KS_ACL], &req)
+                .await
+                .unwrap();
+
+            assert!(std::fs::read(&file).unwrap().starts_with(AGE_MAGIC));
+            let path = file.to_str().unwrap();
+            assert!(verify(path, &[], "server").is_err());
+            let (_, counts) = verify(path, &[identity_file], "server").unwrap();
+            assert_eq!(counts, vec![(KS_ACL.into(), 2)]);
+        }
+
+        async fn dump(store: &Store) -> Vec<RawKvPair> {
+            let mut all = store.keyspace(KS_ACL).unwrap().iter_all().await.unwrap();
+            all.sort();
+            all
+        }
+
+        #[tokio::test]
+        async fn incrementals_chain_changes_and_deletions() {
+            let dir = tempfile::tempdir().unwrap();
+            let store = temp_store(&dir.path().join("store")).await;
+            seed(&store).await;
+            let acl = store.keyspace(KS_ACL).unwrap();
+            let backup = |name: &str, base: Option<&PathBuf>| {
+                let file = dir.path().join(name);
+                let req = BackupRequest {
+                    incremental_from: base.cloned(),
+                    ..request(&file)
+                };
+                let store = &store;
+                async move {
+                    let counts =
+                        write_backup(store, "server", "0.0.0", "{}".into(), &[KS_ACL], &req)
+                            .await
+                            .unwrap();
+                    (file, counts)
+                }
+            };
+
+            let (full, _) = backup("full.jsonl", None).await;
+            acl.insert_raw("b", "changed").await.unwrap();
+            acl.remove("a").await.unwrap();
+            acl.insert_raw("c", "3").await.unwrap();
+            let (first, counts) = backup("first.jsonl", Some(&full)).await;
+            // Only b and c are written; a is recorded as deleted.
+            assert_eq!(counts, vec![(KS_ACL.into(), 2)]);
+
+            acl.remove("c").await.unwrap();
+            acl.insert_raw("d", "4").await.unwrap();
+            let (second, counts) = backup("second.jsonl", Some(&first)).await;
+            assert_eq!(counts, vec![(KS_ACL.into(), 1)]);
+            let mut reader = BackupReader::open(second.to_str().unwrap(), &[]).unwrap();
+            assert!(reader.header.since.is_some());
+            let mut deleted = Vec::new();
+            while let Some((_, item)) = reader.next_item().unwrap() {
+                if let Item::Deleted(key) = item {
+                    deleted.push(key);
+                }
+            }
+            assert_eq!(deleted, vec![b"c".to_vec()]);
+
+            let restored = temp_store(&dir.path().join("restored")).await;
+            for file in [&full, &first, &second] {
+                let path = file.to_str().unwrap();
+                verify(path, &[], "server").unwrap();
+                let mut reader = BackupReader::open(path, &[]).unwrap();
+                restore(&mut reader, &restored, &[KS_ACL]).await.unwrap();
+            }
+            assert_eq!(dump(&restored).await, dump(&store).await);
+        }
+    }
+}
@@ -0,0 +1,307 @@
+//! Conditional GET for public DID resolution.
+//!
+//! Resolvers poll the same logs over and over; most polls find nothing
+//! new. Every resolution response carries a strong `ETag` and, when the
+//! record's `updated_at` is known, a `Last-Modified`. A request whose
+//! `If-None-Match` (or, failing that, `If-Modified-Since`) still matches
+//! gets `304 Not Modified` with no body.
+//!
+//! ETags come from [`log_etag`] for did:webvh logs — the last entry's
+//! `versionId`, which already commits to the whole log through the entry
+//! hash chain — and from [`content_etag`] for everything else. A
+//! compressed variant ([`super::encoding`]) gets its own tag, so a cache
+//! never answers a gzip request with a stored brotli body.
+
+use std::sync::Arc;
+
+use axum::body::Bytes;
+use axum::http::header::{
+    CACHE_CONTROL, CONTENT_ENCODING, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+    LAST_MODIFIED, VARY,
+};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+use super::encoding::Encoding;
+
+/// `Cache-Control` for public resolution responses. An explicit value
+/// overrides the global `no-store` set by [`super::security_headers`].
+pub const PUBLIC_CACHE_CONTROL: &str = "public, max-age=300";
+
+/// Cache validators for one resolution response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Validators {
+    /// Quoted strong entity tag.
+    pub etag: String,
+    /// Epoch seconds; `None` omits `Last-Modified`.
+    pub last_modified: Option<u64>,
+}
+
+impl Validators {
+    /// Validators for a did:webvh log: the last entry's `versionId`, or a
+    /// content hash if the log has none.
+    pub fn for_log(log: &[u8], last_modified: Option<u64>) -> Self {
+        Self {
+            etag: log_etag(log).unwrap_or_else(|| content_etag(log)),
+            last_modified,
+        }
+    }
+
+    /// Validators from a content hash.
+    pub fn for_content(body: &[u8], last_modified: Option<u64>) -> Self {
+        Self {
+            etag: content_etag(body),
+            last_modified,
+        }
+    }
+
+    /// Validators for the `encoding` variant of the same content: the tag
+    /// gains a `-{token}` suffix (`"2-Qm…"` → `"2-Qm…-br"`).
+    pub fn for_encoding(&self, encoding: Encoding) -> Self {
+        let etag = match encoding {
+            Encoding::Identity => self.etag.clone(),
+            _ => format!("{}-{}\"", self.etag.trim_end_matches('"'), encoding.token()),
+        };
+        Self {
+            etag,
+            last_modified: self.last_modified,
+        }
+    }
+}
+
+/// Strong ETag from the last entry's `versionId` of a did:webvh log.
+pub fn log_etag(log: &[u8]) -> Option<String> {
+    let log = std::str::from_utf8(log).ok()?;
+    let last = log.lines().map(str::trim).rfind(|l| !l.is_empty())?;
+    let entry: serde_json::Value = serde_json::from_str(last).ok()?;
+    let version_id = entry.get("versionId")?.as_str()?;
+    // versionIds are `<n>-<multihash>`; anything that would need escaping
+    // inside a quoted entity tag is not a valid one.
+    if version_id.is_empty() || version_id.contains(['"', '\\']) || !version_id.is_ascii() {
+        return None;
+    }
+    Some(format!("\"{version_id}\""))
+}
+
+/// Strong ETag from a SHA-256 of the body.
+pub fn content_etag(body: &[u8]) -> String {
+    format!("\"sha256-{}\"", hex::encode(Sha256::digest(body)))
+}
+
+/// Format epoch seconds as an IMF-fixdate (`Sun, 06 Nov 1994 08:49:37 GMT`).
+pub fn http_date(epoch: u64) -> String {
+    DateTime::<Utc>::from_timestamp(epoch as i64, 0)
+        .unwrap_or_default()
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string()
+}
+
+fn parse_http_date(value: &str) -> Option<u64> {
+    DateTime::parse_from_rfc2822(value)
+        .ok()
+        .and_then(|d| u64::try_from(d.timestamp()).ok())
+}
+
+/// Whether the request's validators say the client's copy is current.
+///
+/// `If-None-Match` wins when present (RFC 9110 §13.2.2); it uses the weak
+/// comparison, so a `W/` prefix added by an intermediary still matches.
+/// `If-Modified-Since` is consulted only without it.
+pub fn is_not_modified(headers: &HeaderMap, validators: &Validators) -> bool {
+    if let Some(inm) = headers.get(IF_NONE_MATCH) {
+        let Ok(inm) = inm.to_str() else {
+            return false;
+        };
+        return inm
+            .split(',')
+            .map(str::trim)
+            .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == validators.etag);
+    }
+    match (
+        validators.last_modified,
+        headers
+            .get(IF_MODIFIED_SINCE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_http_date),
+    ) {
+        (Some(modified), Some(since)) => modified <= since,
+        _ => false,
+    }
+}
+
+/// A response body over shared bytes, without copying them.
+pub fn shared_body(body: &Arc<Vec<u8>>) -> Bytes {
+    struct Shared(Arc<Vec<u8>>);
+    impl AsRef<[u8]> for Shared {
+        fn as_ref(&self) -> &[u8] {
+            &self.0
+        }
+    }
+    Bytes::from_owner(Shared(body.clone()))
+}
+
+/// Build a cacheable resolution response: `304` when the request's
+/// validators match, otherwise `200` with `body`. Both carry the
+/// validators and [`PUBLIC_CACHE_CONTROL`].
+pub fn respond(
+    request_headers: &HeaderMap,
+    validators: &Validators,
+    content_type: &str,
+    body: impl Into<Bytes>,
+) -> Response {
+    let mut resp = if is_not_modified(request_headers, validators) {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        let mut resp = (StatusCode::OK, body.into()).into_response();
+        if let Ok(v) = HeaderValue::from_str(content_type) {
+            resp.headers_mut().insert(CONTENT_TYPE, v);
+        }
+        resp
+    };
+    let headers = resp.headers_mut();
+    headers.insert(
+        CACHE_CONTROL,
+        HeaderValue::from_static(PUBLIC_CACHE_CONTROL),
+    );
+    if let Ok(v) = HeaderValue::from_str(&validators.etag) {
+        headers.insert(ETAG, v);
+    }
+    if let Some(modified) = validators.last_modified
+        && let Ok(v) = HeaderValue::from_str(&http_date(modified))
+    {
+        headers.insert(LAST_MODIFIED, v);
+    }
+    resp
+}
+
+/// Like [`respond`], for a body the caller chose per request with
+/// [`super::encoding::negotiate`]: `body` is already in `encoding`. Adds
+/// `Content-Encoding` (unless identity), `Vary: Accept-Encoding` and the
+/// variant's ETag. `body` is only built when the answer is not a `304`.
+pub fn respond_encoded(
+    request_headers: &HeaderMap,
+    validators: &Validators,
+    content_type: &str,
+    encoding: Encoding,
+    body: impl FnOnce() -> Bytes,
+) -> Response {
+    let validators = validators.for_encoding(encoding);
+    let not_modified = is_not_modified(request_headers, &validators);
+    let mut resp = respond(
+        request_headers,
+        &validators,
+        content_type,
+        if not_modified { Bytes::new() } else { body() },
+    );
+    let headers = resp.headers_mut();
+    headers.insert(VARY, HeaderValue::from_static("Accept-Encoding"));
+    if encoding != Encoding::Identity && !not_modified {
+        headers.insert(CONTENT_ENCODING, HeaderValue::from_static(encoding.token()));
+    }
+    resp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(axum::http::HeaderName, &str)]) -> HeaderMap {
+        let mut h = HeaderMap::new();
+        for (k, v) in pairs {
+            h.insert(k.clone(), HeaderValue::from_str(v).unwrap());
+        }
+        h
+    }
+
+    #[test]
+    fn log_etag_uses_last_version_id() {
+        let log = b"{\"versionId\":\"1-abc\"}\n{\"versionId\":\"2-def\"}\n\n";
+        assert_eq!(log_etag(log).as_deref(), Some("\"2-def\""));
+        assert_eq!(log_etag(b"not json"), None);
+    }
+
+    #[test]
+    fn if_none_match_matches_list_and_weak_tags() {
+        let v = Validators::for_content(b"doc", Some(100));
+        let tag = v.etag.clone();
+        assert!(is_not_modified(
+            &headers(&[(IF_NONE_MATCH, &format!("\"x\", W/{tag}"))]),
+            &v
+        ));
+        assert!(is_not_modified(&headers(&[(IF_NONE_MATCH, "*")]), &v));
+        assert!(!is_not_modified(&headers(&[(IF_NONE_MATCH, "\"x\"")]), &v));
+    }
+
+    #[test]
+    fn if_none_match_takes_precedence_over_if_modified_since() {
+        let v = Validators::for_content(b"doc", Some(100));
+        let h = headers(&[
+            (IF_NONE_MATCH, "\"stale\""),
+            (IF_MODIFIED_SINCE, &http_date(200)),
+        ]);
+        assert!(!is_not_modified(&h, &v));
+    }
+
+    #[test]
+    fn if_modified_since_compares_seconds() {
+        let v = Validators::for_content(b"doc", Some(1_700_000_000));
+        let at = |t| headers(&[(IF_MODIFIED_SINCE, &http_date(t))]);
+        assert!(is_not_modified(&at(1_700_000_000), &v));
+        assert!(!is_not_modified(&at(1_699_999_999), &v));
+    }
+
+    #[test]
+    fn not_modified_has_no_body_but_keeps_validators() {
+        let v = Validators::for_log(b"{\"versionId\":\"3-x\"}", Some(10));
+        let resp = respond(
+            &headers(&[(IF_NONE_MATCH, "\"3-x\"")]),
+            &v,
+            "application/jsonl+json",
+            b"body".to_vec(),
+        );
+        assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(resp.headers()[ETAG], "\"3-x\"");
+        assert_eq!(resp.headers()[LAST_MODIFIED], http_date(10));
+        assert!(resp.headers().get(CONTENT_TYPE).is_none());
+    }
+
+    #[test]
+    fn encoded_variants_carry_their_own_etag() {
+        let v = Validators::for_log(b"{\"versionId\":\"3-x\"}", None);
+        assert_eq!(v.for_encoding(Encoding::Brotli).etag, "\"3-x-br\"");
+        assert_eq!(v.for_encoding(Encoding::Identity), v);
+
+        let resp = respond_encoded(
+            &HeaderMap::new(),
+            &v,
+            "application/jsonl+json",
+            Encoding::Gzip,
+            || Bytes::from_static(b"gz"),
+        );
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers()[ETAG], "\"3-x-gzip\"");
+        assert_eq!(resp.headers()[CONTENT_ENCODING], "gzip");
+        assert_eq!(resp.headers()[VARY], "Accept-Encoding");
+
+        // The identity tag does not validate the gzip variant.
+        let resp = respond_encoded(
+            &headers(&[(IF_NONE_MATCH, "\"3-x\"")]),
+            &v,
+            "application/jsonl+json",
+            Encoding::Gzip,
+            || Bytes::from_static(b"gz"),
+        );
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp = respond_encoded(
+            &headers(&[(IF_NONE_MATCH, "\"3-x-gzip\"")]),
+            &v,
+            "application/jsonl+json",
+            Encoding::Gzip,
+            || panic!("body built for a 304"),
+        );
+        assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+        assert!(resp.headers().get(CONTENT_ENCODING).is_none());
+    }
+}
@@ -162,8 +162,70 @@ pub struct LogConfig {
     pub format: LogFormat,
 }
 
+/// A storage backend, as named in `[store] backend`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StoreBackend {
+    Fjall,
+    Redis,
+    Dynamodb,
+    Firestore,
+    Cosmosdb,
+}
+
+impl StoreBackend {
+    pub const ALL: [StoreBackend; 5] = [
+        Self::Fjall,
+        Self::Redis,
+        Self::Dynamodb,
+        Self::Firestore,
+        Self::Cosmosdb,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Fjall => "fjall",
+            Self::Redis => "redis",
+            Self::Dynamodb => "dynamodb",
+            Self::Firestore => "firestore",
+            Self::Cosmosdb => "cosmosdb",
+        }
+    }
+
+    /// Whether this binary was built with the backend's `store-*` feature.
+    pub fn compiled(self) -> bool {
+        match self {
+            Self::Fjall => cfg!(feature = "store-fjall"),
+            Self::Redis => cfg!(feature = "store-redis"),
+            Self::Dynamodb => cfg!(feature = "store-dynamodb"),
+            Self::Firestore => cfg!(feature = "store-firestore"),
+            Self::Cosmosdb => cfg!(feature = "store-cosmosdb"),
+        }
+    }
+}
+
+impl std::str::FromStr for StoreBackend {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|b| b.name().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                AppError::Config(format!(
+                    "unknown store backend '{s}' (expected fjall, redis, dynamodb, firestore or cosmosdb)"
+                ))
+            })
+    }
+}
+
 #[derive(Clone, Deserialize, Serialize)]
 pub struct StoreConfig {
+    /// Which backend to open. Required when the binary is built with more
+    /// than one `store-*` feature (as for `store-migrate`); otherwise the
+    /// one compiled in is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<StoreBackend>,
     #[serde(default = "default_data_dir")]
     pub data_dir: PathBuf,
     /// Redis connection URL (e.g. `redis://localhost:6379`). Used by `store-redis` backend.
@@ -193,6 +255,7 @@ pub struct StoreConfig {
 impl std::fmt::Debug for StoreConfig {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("StoreConfig")
+            .field("backend", &self.backend)
             .field("data_dir", &self.data_dir)
             .field("redis_url", &self.redis_url.as_ref().map(|_| "<redacted>"))
             .field("dynamodb_table_prefix", &self.dynamodb_table_prefix)
@@ -363,6 +426,27 @@ pub struct HostingConfig {
     /// short enough that abandoned domains don't accumulate forever.
     #[serde(default = "default_disable_purge_grace")]
     pub disable_purge_grace: String,
+
+    /// How long a log entry dropped by a rollback (and the witness proofs
+    /// it carried) stays archived under its DID, recoverable with
+    /// `undo-rollback`. Format matches `unassigned_purge_grace`. Default:
+    /// `"30d"`, the same window as an accidental domain disable.
+    #[serde(default = "default_rollback_archive_retention")]
+    pub rollback_archive_retention: String,
+
+    /// How long the old location of a DID moved between hosted domains
+    /// (`did/move`) keeps serving its log, so resolvers holding the old
+    /// identifier can follow `alsoKnownAs` to the new one. Format matches
+    /// `unassigned_purge_grace`. Default: `"90d"`.
+    #[serde(default = "default_moved_did_retention")]
+    pub moved_did_retention: String,
+
+    /// How long a deactivated DID's log must be kept before `delete` may
+    /// remove it. Until then the slot keeps serving the log, so verifiers
+    /// see the deactivation rather than a missing DID. Format matches
+    /// `unassigned_purge_grace`. Default: `"365d"`.
+    #[serde(default = "default_deactivated_did_retention")]
+    pub deactivated_did_retention: String,
 }
 
 fn default_unassigned_purge_grace() -> String {
@@ -373,12 +457,27 @@ fn default_disable_purge_grace() -> String {
     "30d".to_string()
 }
 
+fn default_rollback_archive_retention() -> String {
+    "30d".to_string()
+}
+
+fn default_moved_did_retention() -> String {
+    "90d".to_string()
+}
+
+fn default_deactivated_did_retention() -> String {
+    "365d".to_string()
+}
+
 impl Default for HostingConfig {
     fn default() -> Self {
         Self {
             bootstrap_domains: Vec::new(),
             unassigned_purge_grace: default_unassigned_purge_grace(),
             disable_purge_grace: default_disable_purge_grace(),
+            rollback_archive_retention: default_rollback_archive_retention(),
+            moved_did_retention: default_moved_did_retention(),
+            deactivated_did_retention: default_deactivated_did_retention(),
         }
     }
 }
@@ -395,6 +494,7 @@ impl Default for LogConfig {
 impl Default for StoreConfig {
     fn default() -> Self {
         Self {
+            backend: None,
             data_dir: default_data_dir(),
             redis_url: None,
             dynamodb_table_prefix: None,
@@ -408,6 +508,74 @@ impl Default for StoreConfig {
     }
 }
 
+impl StoreConfig {
+    /// The backend [`super::store::Store::open`] will use.
+    ///
+    /// `backend` when set, otherwise the only compiled backend. A binary
+    /// built with several must name one: guessing from whichever
+    /// connection field happens to be filled in could open the wrong store.
+    pub fn resolved_backend(&self) -> Result<StoreBackend, AppError> {
+        let backend = match self.backend {
+            Some(backend) => backend,
+            None => {
+                let compiled: Vec<_> = StoreBackend::ALL
+                    .into_iter()
+                    .filter(|b| b.compiled())
+                    .collect();
+                match compiled.as_slice() {
+                    [only] => *only,
+                    [] => {
+                        return Err(AppError::Config(
+                            "no store backend is compiled into this binary".into(),
+                        ));
+                    }
+                    several => {
+                        let names: Vec<_> = several.iter().map(|b| b.name()).collect();
+                        return Err(AppError::Config(format!(
+                            "this binary is built with several store backends ({}); \
+                             set [store] backend to one of them",
+                            names.join(", ")
+                        )));
+                    }
+                }
+            }
+        };
+        if !backend.compiled() {
+            return Err(AppError::Config(format!(
+                "store backend '{}' is not compiled into this binary; rebuild with --features store-{}",
+                backend.name(),
+                backend.name()
+            )));
+        }
+        Ok(backend)
+    }
+
+    /// Read the `[table]` section of a service config file on its own, then
+    /// apply the `{env_prefix}_STORE_*` overrides when a prefix is given.
+    /// No other section is parsed, so two files (and two prefixes) can name
+    /// two different stores in one process.
+    pub fn from_config_file(
+        path: &std::path::Path,
+        table: &str,
+        env_prefix: Option<&str>,
+    ) -> Result<Self, AppError> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| AppError::Config(format!("failed to read {}: {e}", path.display())))?;
+        let mut doc: toml::Table = toml::from_str(&raw)
+            .map_err(|e| AppError::Config(format!("failed to parse {}: {e}", path.display())))?;
+        let mut store: Self = match doc.remove(table) {
+            Some(section) => section.try_into().map_err(|e| {
+                AppError::Config(format!("invalid [{table}] in {}: {e}", path.display()))
+            })?,
+            None => Self::default(),
+        };
+        if let Some(prefix) = env_prefix {
+            apply_store_env_overrides(prefix, &mut store)?;
+        }
+        Ok(store)
+    }
+}
+
 #[derive(Clone, Deserialize, Serialize)]
 pub struct SecretsConfig {
     pub aws_secret_name: Option<String>,
@@ -810,40 +978,7 @@ pub fn apply_env_overrides(
         };
     }
 
-    // Store
-    let store_data_dir_var = format!("{prefix}_STORE_DATA_DIR");
-    if let Ok(data_dir) = std::env::var(&store_data_dir_var) {
-        store.data_dir = PathBuf::from(data_dir);
-    }
-    env_opt!(&format!("{prefix}_STORE_REDIS_URL"), store.redis_url);
-    env_opt!(
-        &format!("{prefix}_STORE_DYNAMODB_TABLE_PREFIX"),
-        store.dynamodb_table_prefix
-    );
-    env_opt!(
-        &format!("{prefix}_STORE_DYNAMODB_REGION"),
-        store.dynamodb_region
-    );
-    env_opt!(
-        &format!("{prefix}_STORE_FIRESTORE_PROJECT"),
-        store.firestore_project
-    );
-    env_opt!(
-        &format!("{prefix}_STORE_FIRESTORE_DATABASE"),
-        store.firestore_database
-    );
-    env_opt!(
-        &format!("{prefix}_STORE_COSMOSDB_CONNECTION_STRING"),
-        store.cosmosdb_connection_string
-    );
-    env_opt!(
-        &format!("{prefix}_STORE_COSMOSDB_DATABASE"),
-        store.cosmosdb_database
-    );
-    env_opt!(
-        &format!("{prefix}_STORE_COSMOSDB_REGION"),
-        store.cosmosdb_region
-    );
+    apply_store_env_overrides(prefix, store)?;
 
     // Auth
     env_parse!(
@@ -967,6 +1102,57 @@ pub fn apply_env_overrides(
     Ok(())
 }
 
+/// Apply the `{prefix}_STORE_*` overrides to one store section. Part of
+/// [`apply_env_overrides`]; `store-migrate` also calls it on its own for
+/// each store it opens.
+pub fn apply_store_env_overrides(prefix: &str, store: &mut StoreConfig) -> Result<(), AppError> {
+    macro_rules! env_opt {
+        ($var:expr, $field:expr) => {
+            if let Ok(v) = std::env::var($var) {
+                $field = Some(v);
+            }
+        };
+    }
+
+    if let Ok(backend) = std::env::var(format!("{prefix}_STORE_BACKEND")) {
+        store.backend = Some(backend.parse()?);
+    }
+    let store_data_dir_var = format!("{prefix}_STORE_DATA_DIR");
+    if let Ok(data_dir) = std::env::var(&store_data_dir_var) {
+        store.data_dir = PathBuf::from(data_dir);
+    }
+    env_opt!(&format!("{prefix}_STORE_REDIS_URL"), store.redis_url);
+    env_opt!(
+        &format!("{prefix}_STORE_DYNAMODB_TABLE_PREFIX"),
+        store.dynamodb_table_prefix
+    );
+    env_opt!(
+        &format!("{prefix}_STORE_DYNAMODB_REGION"),
+        store.dynamodb_region
+    );
+    env_opt!(
+        &format!("{prefix}_STORE_FIRESTORE_PROJECT"),
+        store.firestore_project
+    );
+    env_opt!(
+        &format!("{prefix}_STORE_FIRESTORE_DATABASE"),
+        store.firestore_database
+    );
+    env_opt!(
+        &format!("{prefix}_STORE_COSMOSDB_CONNECTION_STRING"),
+        store.cosmosdb_connection_string
+    );
+    env_opt!(
+        &format!("{prefix}_STORE_COSMOSDB_DATABASE"),
+        store.cosmosdb_database
+    );
+    env_opt!(
+        &format!("{prefix}_STORE_COSMOSDB_REGION"),
+        store.cosmosdb_region
+    );
+    Ok(())
+}
+
 /// Initialize the global tracing subscriber based on config.
 ///
 /// Uses `try_init` so that callers embedded inside a host process that has
@@ -1169,4 +1355,38 @@ mod tests {
             assert_eq!(TransportSelection::parse(sel.as_str()).unwrap(), sel);
         }
     }
+
+    #[test]
+    fn store_section_reads_on_its_own() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "[server]\nport = 1\n\n[store]\nbackend = \"redis\"\nredis_url = \"redis://h:6379\"\n",
+        )
+        .unwrap();
+        let store = StoreConfig::from_config_file(&path, "store", None).unwrap();
+        assert_eq!(store.backend, Some(StoreBackend::Redis));
+        assert_eq!(store.redis_url.as_deref(), Some("redis://h:6379"));
+        // A missing section is the default store.
+        let witness = StoreConfig::from_config_file(&path, "witness_store", None).unwrap();
+        assert_eq!(witness.backend, None);
+
+        // Env overrides apply under the given prefix only. The prefix is
+        // unique to this test, so parallel tests cannot see it.
+        // SAFETY: no other test reads or writes this variable.
+        unsafe { std::env::set_var("SECTION_TEST_STORE_REDIS_URL", "redis://env:6379") };
+        let store = StoreConfig::from_config_file(&path, "store", Some("SECTION_TEST")).unwrap();
+        assert_eq!(store.redis_url.as_deref(), Some("redis://env:6379"));
+        let store = StoreConfig::from_config_file(&path, "store", Some("OTHER_TEST")).unwrap();
+        assert_eq!(store.redis_url.as_deref(), Some("redis://h:6379"));
+    }
+
+    #[test]
+    fn backends_parse_by_name() {
+        for backend in StoreBackend::ALL {
+            assert_eq!(backend.name().parse::<StoreBackend>().unwrap(), backend);
+        }
+        assert!("postgres".parse::<StoreBackend>().is_err());
+    }
 }
@@ -0,0 +1,34 @@
+//! Deactivated DIDs, shared by the control plane and hosting servers.
+//!
+//! A log whose last entry sets `deactivated: true` is final: the slot
+//! takes no further log ([`ensure_not_deactivated`]) and keeps serving the
+//! deactivation until `hosting.deactivated_did_retention` has passed
+//! ([`deactivation_retention_secs`]). See
+//! [`crate::did_ops::DidRecord::deactivated_at`].
+
+use super::config::HostingConfig;
+use super::error::AppError;
+use super::pending_purge::parse_grace_string;
+use crate::did_ops::DidRecord;
+
+/// Retention window from `hosting.deactivated_did_retention`, in seconds.
+pub fn deactivation_retention_secs(hosting: &HostingConfig) -> Result<u64, AppError> {
+    parse_grace_string(&hosting.deactivated_did_retention).map_err(|e| {
+        AppError::Internal(format!(
+            "config [hosting] deactivated_did_retention='{}' is invalid: {e}",
+            hosting.deactivated_did_retention
+        ))
+    })
+}
+
+/// Refuse to change the log of a deactivated DID. Its last entry is the
+/// deactivation, and it stays that way.
+pub fn ensure_not_deactivated(record: &DidRecord) -> Result<(), AppError> {
+    if record.is_deactivated() {
+        return Err(AppError::Conflict(format!(
+            "DID '{}' is deactivated; its log is final",
+            record.mnemonic
+        )));
+    }
+    Ok(())
+}
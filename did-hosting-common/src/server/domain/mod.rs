@@ -42,6 +42,8 @@ pub use scope::{DomainResolveError, DomainScope, resolve_request_domain};
 pub use seed::{SeedOutcome, SeedTier, seed_domains_first_boot};
 pub use store::{
     DISABLE_PURGE_REASON, create_domain, delete_domain_record, disable_domain, enable_domain,
-    get_default_domain, get_domain, list_domains, set_default_domain, update_domain,
+    get_default_domain, get_domain, list_domains, set_default_domain, stage_create_domain,
+    stage_default_pointer, stage_delete_domain_record, stage_disable_domain, stage_enable_domain,
+    stage_set_default_domain, stage_update_domain, update_domain,
 };
 pub use types::{DomainBranding, DomainEntry, DomainQuota, DomainStatus, DomainUrlScheme};
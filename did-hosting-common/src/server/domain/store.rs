@@ -21,7 +21,7 @@ use super::normalize::normalize_domain_name;
 use super::types::{DomainEntry, DomainStatus};
 use crate::server::error::AppError;
 use crate::server::pending_purge;
-use crate::server::store::{KS_DOMAINS, KS_META, KeyspaceHandle, Store};
+use crate::server::store::{KS_DOMAINS, KS_META, KeyspaceHandle, Store, WriteBatch};
 
 /// `pending_purge::PendingPurge::reason` value used for soft-deleted
 /// (disabled, awaiting purge) domains. Distinct from
@@ -73,6 +73,17 @@ fn meta_ks(store: &Store) -> Result<KeyspaceHandle, AppError> {
 ///   names are immutable per spec §3 — the only path to "rename" is
 ///   add-new + offboard-old.
 pub async fn create_domain(store: &Store, entry: &DomainEntry) -> Result<(), AppError> {
+    let mut batch = store.batch();
+    stage_create_domain(store, &mut batch, entry).await?;
+    batch.commit().await
+}
+
+/// [`create_domain`] as part of `batch`.
+pub async fn stage_create_domain(
+    store: &Store,
+    batch: &mut WriteBatch,
+    entry: &DomainEntry,
+) -> Result<(), AppError> {
     let canonical = normalize_domain_name(&entry.name)?;
     if canonical != entry.name {
         // This branch is unreachable because `normalize_domain_name`
@@ -88,8 +99,7 @@ pub async fn create_domain(store: &Store, entry: &DomainEntry) -> Result<(), App
             "domain '{canonical}' already exists"
         )));
     }
-    ks.insert(canonical.as_bytes().to_vec(), entry).await?;
-    Ok(())
+    batch.insert(&ks, canonical.as_bytes().to_vec(), entry)
 }
 
 /// Fetch one `DomainEntry` by name. `name` is normalised before lookup.
@@ -126,6 +136,18 @@ pub async fn update_domain(
     store: &Store,
     name: &str,
     new_entry: &DomainEntry,
+) -> Result<(), AppError> {
+    let mut batch = store.batch();
+    stage_update_domain(store, &mut batch, name, new_entry).await?;
+    batch.commit().await
+}
+
+/// [`update_domain`] as part of `batch`.
+pub async fn stage_update_domain(
+    store: &Store,
+    batch: &mut WriteBatch,
+    name: &str,
+    new_entry: &DomainEntry,
 ) -> Result<(), AppError> {
     let canonical = normalize_domain_name(name)?;
     if new_entry.name != canonical {
@@ -138,8 +160,7 @@ pub async fn update_domain(
     if !ks.contains_key(canonical.as_bytes().to_vec()).await? {
         return Err(AppError::NotFound(format!("domain '{canonical}'")));
     }
-    ks.insert(canonical.as_bytes().to_vec(), new_entry).await?;
-    Ok(())
+    batch.insert(&ks, canonical.as_bytes().to_vec(), new_entry)
 }
 
 /// Disable a domain (soft-delete). Refuses if the domain is currently
@@ -159,6 +180,29 @@ pub async fn disable_domain(
     grace_seconds: u64,
     scheduled_by: &str,
 ) -> Result<(), AppError> {
+    let mut batch = store.batch();
+    stage_disable_domain(
+        store,
+        &mut batch,
+        name,
+        now_epoch,
+        grace_seconds,
+        scheduled_by,
+    )
+    .await?;
+    batch.commit().await
+}
+
+/// [`disable_domain`] as part of `batch`. Returns the entry as the batch
+/// leaves it.
+pub async fn stage_disable_domain(
+    store: &Store,
+    batch: &mut WriteBatch,
+    name: &str,
+    now_epoch: u64,
+    grace_seconds: u64,
+    scheduled_by: &str,
+) -> Result<DomainEntry, AppError> {
     let canonical = normalize_domain_name(name)?;
     if let Some(current_default) = get_default_domain(store).await?
         && current_default == canonical
@@ -174,10 +218,11 @@ pub async fn disable_domain(
     entry.disabled_at = Some(now_epoch);
     entry.purge_at = Some(now_epoch.saturating_add(grace_seconds));
     let ks = domains_ks(store)?;
-    ks.insert(canonical.as_bytes().to_vec(), &entry).await?;
+    batch.insert(&ks, canonical.as_bytes().to_vec(), &entry)?;
 
-    pending_purge::schedule(
+    pending_purge::stage_schedule(
         store,
+        batch,
         &canonical,
         now_epoch,
         grace_seconds,
@@ -185,7 +230,7 @@ pub async fn disable_domain(
         scheduled_by,
     )
     .await?;
-    Ok(())
+    Ok(entry)
 }
 
 /// Enable a previously-disabled domain. Cancels the pending purge if
@@ -193,6 +238,18 @@ pub async fn disable_domain(
 /// schedules; missing pending row is a quiet no-op for old records
 /// disabled before this feature shipped).
 pub async fn enable_domain(store: &Store, name: &str) -> Result<(), AppError> {
+    let mut batch = store.batch();
+    stage_enable_domain(store, &mut batch, name).await?;
+    batch.commit().await
+}
+
+/// [`enable_domain`] as part of `batch`. Returns the entry as the batch
+/// leaves it.
+pub async fn stage_enable_domain(
+    store: &Store,
+    batch: &mut WriteBatch,
+    name: &str,
+) -> Result<DomainEntry, AppError> {
     let canonical = normalize_domain_name(name)?;
     let mut entry = get_domain(store, &canonical)
         .await?
@@ -201,21 +258,32 @@ pub async fn enable_domain(store: &Store, name: &str) -> Result<(), AppError> {
     entry.disabled_at = None;
     entry.purge_at = None;
     let ks = domains_ks(store)?;
-    ks.insert(canonical.as_bytes().to_vec(), &entry).await?;
+    batch.insert(&ks, canonical.as_bytes().to_vec(), &entry)?;
 
     // Best-effort cancel. A `Missing` outcome is fine — it means this
     // domain was disabled before the soft-delete feature shipped and
     // never had a pending row, or the sweep already cleared it (the
     // sweep would only do that AFTER deleting the domain record, in
     // which case `get_domain` above would have returned NotFound).
-    let _ = pending_purge::cancel(store, &canonical).await?;
-    Ok(())
+    let _ = pending_purge::stage_cancel(store, batch, &canonical).await?;
+    Ok(entry)
 }
 
 /// Hard-delete a domain record. Does **not** purge DIDs hosted under
 /// the domain — that's the `domain.purge` admin Trust Task in T30.
 /// Refuses if the domain is the current default.
 pub async fn delete_domain_record(store: &Store, name: &str) -> Result<(), AppError> {
+    let mut batch = store.batch();
+    stage_delete_domain_record(store, &mut batch, name).await?;
+    batch.commit().await
+}
+
+/// [`delete_domain_record`] as part of `batch`.
+pub async fn stage_delete_domain_record(
+    store: &Store,
+    batch: &mut WriteBatch,
+    name: &str,
+) -> Result<(), AppError> {
     let canonical = normalize_domain_name(name)?;
     if let Some(current_default) = get_default_domain(store).await?
         && current_default == canonical
@@ -228,7 +296,7 @@ pub async fn delete_domain_record(store: &Store, name: &str) -> Result<(), AppEr
     if !ks.contains_key(canonical.as_bytes().to_vec()).await? {
         return Err(AppError::NotFound(format!("domain '{canonical}'")));
     }
-    ks.remove(canonical.as_bytes().to_vec()).await?;
+    batch.remove(&ks, canonical.as_bytes().to_vec());
     Ok(())
 }
 
@@ -254,8 +322,20 @@ pub async fn get_default_domain(store: &Store) -> Result<Option<String>, AppErro
 /// - `Conflict` if the target domain is `Disabled` (per spec §3
 ///   "Default domain ... must point to an active domain").
 pub async fn set_default_domain(store: &Store, name: &str) -> Result<(), AppError> {
+    let mut batch = store.batch();
+    stage_set_default_domain(store, &mut batch, name).await?;
+    batch.commit().await
+}
+
+/// [`set_default_domain`] as part of `batch`. Returns the new default's
+/// entry as the batch leaves it.
+pub async fn stage_set_default_domain(
+    store: &Store,
+    batch: &mut WriteBatch,
+    name: &str,
+) -> Result<DomainEntry, AppError> {
     let canonical = normalize_domain_name(name)?;
-    let entry = get_domain(store, &canonical)
+    let mut entry = get_domain(store, &canonical)
         .await?
         .ok_or_else(|| AppError::NotFound(format!("domain '{canonical}'")))?;
     if !entry.status.is_active() {
@@ -263,27 +343,40 @@ pub async fn set_default_domain(store: &Store, name: &str) -> Result<(), AppErro
             "cannot set default to '{canonical}' — domain is disabled"
         )));
     }
-    let ks = meta_ks(store)?;
+    if !entry.default_domain {
+        entry.default_domain = true;
+        batch.insert(&domains_ks(store)?, canonical.as_bytes().to_vec(), &entry)?;
+    }
+    stage_default_pointer(store, batch, &canonical).await?;
+    Ok(entry)
+}
+
+/// Point the default at `canonical` and clear the `default_domain` flag
+/// on every other entry, as part of `batch`. The caller sets the flag on
+/// `canonical`'s own entry — for a domain created in the same batch,
+/// before [`stage_create_domain`]. The pointer in `meta` is the truth;
+/// the denormalised flag is what we serve in API responses, so keep them
+/// in sync.
+pub async fn stage_default_pointer(
+    store: &Store,
+    batch: &mut WriteBatch,
+    canonical: &str,
+) -> Result<(), AppError> {
     let pointer = DefaultDomainPointer {
-        domain: canonical.clone(),
+        domain: canonical.to_string(),
     };
-    ks.insert(META_DEFAULT_DOMAIN_KEY.as_bytes().to_vec(), &pointer)
-        .await?;
-
-    // Refresh the `default_domain` boolean on every entry: set it on
-    // the new default, clear it everywhere else. The pointer in
-    // `meta` is the truth; this denormalised flag is what we serve
-    // in API responses, so keep them in sync.
+    batch.insert(
+        &meta_ks(store)?,
+        META_DEFAULT_DOMAIN_KEY.as_bytes().to_vec(),
+        &pointer,
+    )?;
     let domains_ks = domains_ks(store)?;
-    let all = list_domains(store).await?;
-    for mut e in all {
-        let is_default = e.name == canonical;
-        if e.default_domain != is_default {
-            e.default_domain = is_default;
-            domains_ks.insert(e.name.as_bytes().to_vec(), &e).await?;
+    for mut e in list_domains(store).await? {
+        if e.name != canonical && e.default_domain {
+            e.default_domain = false;
+            batch.insert(&domains_ks, e.name.as_bytes().to_vec(), &e)?;
         }
     }
-
     Ok(())
 }
 
@@ -296,6 +389,7 @@ mod tests {
     async fn fjall_store() -> Store {
         let dir = tempfile::tempdir().expect("tempdir");
         let cfg = StoreConfig {
+            backend: None,
             data_dir: dir.path().to_path_buf(),
             redis_url: None,
             dynamodb_table_prefix: None,
@@ -29,6 +29,9 @@
 //! - `owner:<did>:<mnemonic>` — the owner index entry.
 //! - `watcher_sync:<mnemonic>` — the watcher-sync cursor (if any).
 //!
+//! The domain's `domain_usage:<domain>` counter goes once the walk is
+//! done, so a control plane sharing the store recounts it.
+//!
 //! All deletes for one DID are batched into a single atomic write
 //! per the existing `Store` API. Cross-DID atomicity is not
 //! guaranteed (a crash mid-purge leaves the keyspace partially
@@ -39,7 +42,8 @@ use tracing::{info, warn};
 use super::error::AppError;
 use super::store::{KS_DIDS, Store};
 use crate::did_ops::{
-    DidRecord, content_log_key, content_witness_key, did_key, owner_key, watcher_sync_key,
+    DidRecord, content_log_key, content_witness_key, did_key, domain_usage_key, owner_key,
+    watcher_sync_key,
 };
 
 /// Summary of a [`purge_domain_dids`] run, returned for audit-log
@@ -126,6 +130,7 @@ pub async fn purge_domain_dids(
             }
         }
     }
+    ks.remove(domain_usage_key(target_domain)).await?;
 
     info!(
         domain = %target_domain,
@@ -173,6 +178,8 @@ mod tests {
             domain: domain.into(),
             services: None,
             agent_names: Vec::new(),
+            deactivated_at: None,
+            sync_head: None,
         }
     }
 
@@ -0,0 +1,217 @@
+//! `Accept-Encoding` negotiation for public DID artifacts.
+//!
+//! did:webvh logs are append-only JSON lines that repeat the same keys,
+//! proofs and key material entry after entry; long-lived logs compress
+//! around 10x. Rather than compress on every request, callers keep each
+//! variant next to the raw bytes — the hosting server in its content cache,
+//! the watcher in its store at sync time — and pick one per request with
+//! [`negotiate`]. [`super::conditional::respond_encoded`] then sets
+//! `Content-Encoding`, `Vary: Accept-Encoding` and a per-variant ETag.
+
+use std::io::Write;
+
+use axum::http::HeaderMap;
+use axum::http::header::ACCEPT_ENCODING;
+
+/// Bodies smaller than this are always sent as-is: the saving would not
+/// cover the framing, and small witness files are the common case.
+pub const MIN_COMPRESS_SIZE: usize = 1024;
+
+/// Variants are built once per content version, so favour ratio over
+/// speed, short of the levels that take seconds on a large log.
+const GZIP_LEVEL: u32 = 9;
+const BROTLI_QUALITY: u32 = 9;
+const BROTLI_WINDOW: u32 = 22;
+const ZSTD_LEVEL: i32 = 12;
+
+/// A `Content-Encoding` the resolution routes can serve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    Identity,
+    Gzip,
+    Brotli,
+    Zstd,
+}
+
+impl Encoding {
+    /// Compressed encodings, in the order the server prefers them when a
+    /// client rates several equally.
+    pub const COMPRESSED: [Encoding; 3] = [Encoding::Brotli, Encoding::Zstd, Encoding::Gzip];
+
+    /// The `Content-Encoding` token.
+    pub fn token(self) -> &'static str {
+        match self {
+            Encoding::Identity => "identity",
+            Encoding::Gzip => "gzip",
+            Encoding::Brotli => "br",
+            Encoding::Zstd => "zstd",
+        }
+    }
+
+    fn from_token(token: &str) -> Option<Self> {
+        match token.to_ascii_lowercase().as_str() {
+            "identity" => Some(Encoding::Identity),
+            "gzip" | "x-gzip" => Some(Encoding::Gzip),
+            "br" => Some(Encoding::Brotli),
+            "zstd" => Some(Encoding::Zstd),
+            _ => None,
+        }
+    }
+}
+
+/// The encoding to answer a request with, out of the compressed variants
+/// the caller holds (`available`; identity is always possible).
+///
+/// The highest `q` wins, ties going to [`Encoding::COMPRESSED`] order.
+/// `*` rates anything not named. Identity wins only if the client names it
+/// with a higher `q` than every compressed option it accepts.
+pub fn negotiate(headers: &HeaderMap, available: &[Encoding]) -> Encoding {
+    let Some(accept) = headers.get(ACCEPT_ENCODING).and_then(|v| v.to_str().ok()) else {
+        return Encoding::Identity;
+    };
+
+    let mut star = None;
+    let mut rated: Vec<(Encoding, f32)> = Vec::new();
+    for item in accept.split(',') {
+        let mut parts = item.split(';');
+        let name = parts.next().unwrap_or_default().trim();
+        let q = parts
+            .find_map(|p| {
+                let p = p.trim();
+                p.strip_prefix("q=").or_else(|| p.strip_prefix("Q="))
+            })
+            .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())
+            .unwrap_or(0.0);
+        if name == "*" {
+            star = Some(q);
+        } else if let Some(encoding) = Encoding::from_token(name) {
+            rated.push((encoding, q));
+        }
+    }
+    let rating = |encoding: Encoding| rated.iter().find(|(e, _)| *e == encoding).map(|(_, q)| *q);
+
+    let mut best: Option<(Encoding, f32)> = None;
+    for encoding in Encoding::COMPRESSED {
+        let q = rating(encoding).or(star).unwrap_or(0.0);
+        if q > 0.0 && available.contains(&encoding) && best.is_none_or(|(_, b)| q > b) {
+            best = Some((encoding, q));
+        }
+    }
+    match (best, rating(Encoding::Identity)) {
+        (Some((_, q)), Some(identity)) if identity > q => Encoding::Identity,
+        (Some((encoding, _)), _) => encoding,
+        (None, _) => Encoding::Identity,
+    }
+}
+
+/// Compress `body` with `encoding`.
+pub fn compress(encoding: Encoding, body: &[u8]) -> std::io::Result<Vec<u8>> {
+    match encoding {
+        Encoding::Identity => Ok(body.to_vec()),
+        Encoding::Gzip => {
+            let mut w =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::new(GZIP_LEVEL));
+            w.write_all(body)?;
+            w.finish()
+        }
+        Encoding::Brotli => {
+            let mut w =
+                brotli::CompressorWriter::new(Vec::new(), 4096, BROTLI_QUALITY, BROTLI_WINDOW);
+            w.write_all(body)?;
+            Ok(w.into_inner())
+        }
+        Encoding::Zstd => zstd::bulk::compress(body, ZSTD_LEVEL),
+    }
+}
+
+/// Whether `body` is worth keeping compressed variants of.
+pub fn worth_compressing(body: &[u8]) -> bool {
+    body.len() >= MIN_COMPRESS_SIZE
+}
+
+/// Every compressed variant of `body` worth serving: none for a body
+/// below [`MIN_COMPRESS_SIZE`], and only variants that came out smaller.
+pub fn precompress(body: &[u8]) -> std::io::Result<Vec<(Encoding, Vec<u8>)>> {
+    if !worth_compressing(body) {
+        return Ok(Vec::new());
+    }
+    let mut variants = Vec::new();
+    for encoding in Encoding::COMPRESSED {
+        let compressed = compress(encoding, body)?;
+        if compressed.len() < body.len() {
+            variants.push((encoding, compressed));
+        }
+    }
+    Ok(variants)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use std::io::Read;
+
+    fn accept(value: &str) -> HeaderMap {
+        let mut h = HeaderMap::new();
+        h.insert(ACCEPT_ENCODING, HeaderValue::from_str(value).unwrap());
+        h
+    }
+
+    const ALL: &[Encoding] = &Encoding::COMPRESSED;
+
+    #[test]
+    fn negotiates_by_quality_then_preference() {
+        assert_eq!(negotiate(&HeaderMap::new(), ALL), Encoding::Identity);
+        assert_eq!(
+            negotiate(&accept("gzip, deflate, br"), ALL),
+            Encoding::Brotli
+        );
+        assert_eq!(negotiate(&accept("br;q=0.5, gzip"), ALL), Encoding::Gzip);
+        assert_eq!(negotiate(&accept("zstd, br;q=0"), ALL), Encoding::Zstd);
+        assert_eq!(negotiate(&accept("*"), ALL), Encoding::Brotli);
+        assert_eq!(negotiate(&accept("*;q=0, gzip"), ALL), Encoding::Gzip);
+        assert_eq!(
+            negotiate(&accept("gzip;q=0.2, identity"), ALL),
+            Encoding::Identity
+        );
+        // Only what the caller holds.
+        assert_eq!(
+            negotiate(&accept("br, gzip"), &[Encoding::Gzip]),
+            Encoding::Gzip
+        );
+        assert_eq!(negotiate(&accept("br"), &[]), Encoding::Identity);
+    }
+
+    #[test]
+    fn variants_round_trip() {
+        let entry = r#"{"versionId":"1-Qm","parameters":{"updateKeys":["z6Mk"]},"proof":[]}"#;
+        let log = format!("{entry}\n").repeat(100);
+        let variants = precompress(log.as_bytes()).unwrap();
+        assert_eq!(variants.len(), 3);
+
+        for (encoding, bytes) in variants {
+            assert!(
+                bytes.len() * 5 < log.len(),
+                "{encoding:?} barely compressed"
+            );
+            let mut out = Vec::new();
+            match encoding {
+                Encoding::Gzip => {
+                    flate2::read::GzDecoder::new(&bytes[..])
+                        .read_to_end(&mut out)
+                        .unwrap();
+                }
+                Encoding::Brotli => {
+                    brotli::Decompressor::new(&bytes[..], 4096)
+                        .read_to_end(&mut out)
+                        .unwrap();
+                }
+                Encoding::Zstd => out = zstd::decode_all(&bytes[..]).unwrap(),
+                Encoding::Identity => unreachable!(),
+            }
+            assert_eq!(out, log.as_bytes());
+        }
+
+        assert!(precompress(b"{}").unwrap().is_empty());
+    }
+}
@@ -98,6 +98,13 @@ pub enum AppError {
     /// sniffing message prose — the same discipline `ValidationKind` follows.
     #[error("agent name error: {0}")]
     AgentName(#[from] AgentNameError),
+
+    /// A gated action is waiting for its approvers (the control plane's
+    /// multi-party consent). Renders to **202 Accepted** with body
+    /// `{ "status": "pending", "approval_id": "<id>" }`; the caller repeats
+    /// the request once the approval is granted.
+    #[error("approval pending: {approval_id}")]
+    ApprovalPending { approval_id: String },
 }
 
 /// Failure modes specific to the agent-name operations. Kept as a typed enum,
@@ -273,6 +280,7 @@ impl AppError {
     /// - `Validation(InvalidPath)` → `e.p.did.path-invalid`
     /// - `Validation(InvalidWitness)` → `e.p.did.witness-invalid`
     /// - `Validation(Other)` → `e.p.did.validation-error`
+    /// - `ApprovalPending` → `e.p.did.approval-pending`
     /// - everything else (5xx-shaped) → `e.p.did.internal-error`
     pub fn didcomm_code(&self) -> &'static str {
         match self {
@@ -301,6 +309,7 @@ impl AppError {
                 | AgentNameError::AlreadyDisabled
                 | AgentNameError::AlsoKnownAsMismatch => "e.p.did.validation-error",
             },
+            AppError::ApprovalPending { .. } => "e.p.did.approval-pending",
             _ => "e.p.did.internal-error",
         }
     }
@@ -359,6 +368,7 @@ impl IntoResponse for AppError {
             AppError::TrustTaskMismatch { .. } => StatusCode::UNSUPPORTED_MEDIA_TYPE,
             AppError::DomainDisabled { .. } => StatusCode::SERVICE_UNAVAILABLE,
             AppError::AgentName(e) => e.http_status(),
+            AppError::ApprovalPending { .. } => StatusCode::ACCEPTED,
         };
 
         // DomainDisabled is a 5xx but the body is part of the public
@@ -396,6 +406,10 @@ impl IntoResponse for AppError {
                 "error": "step_up_required",
                 "required_acr": "aal2",
             }),
+            AppError::ApprovalPending { approval_id } => serde_json::json!({
+                "status": "pending",
+                "approval_id": approval_id,
+            }),
             _ => serde_json::json!({ "error": self.user_message() }),
         };
         (status, axum::Json(body)).into_response()
@@ -442,6 +456,23 @@ mod response_tests {
         assert_eq!(err.user_message(), "path 'foo' is already taken");
     }
 
+    #[tokio::test]
+    async fn approval_pending_is_accepted_with_its_id() {
+        let resp = AppError::ApprovalPending {
+            approval_id: "abc".into(),
+        }
+        .into_response();
+        assert_eq!(resp.status(), StatusCode::ACCEPTED);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({ "status": "pending", "approval_id": "abc" })
+        );
+    }
+
     #[test]
     fn not_found_does_not_leak_mnemonic() {
         let err = AppError::NotFound("DID not found: super-secret-mnemonic".into());
@@ -149,7 +149,9 @@ pub async fn check_secrets(secrets_config: &SecretsConfig, config_path: &Path) {
 pub async fn check_store(
     store_config: &crate::server::config::StoreConfig,
 ) -> Option<crate::server::store::Store> {
-    let backend_name = active_store_backend();
+    let backend_name = store_config
+        .resolved_backend()
+        .map_or_else(|_| active_store_backend(), |b| b.name());
     match crate::server::store::Store::open(store_config).await {
         Ok(store) => {
             pass(&format!(
@@ -46,7 +46,7 @@ use tracing::{debug, error, info, warn};
 use super::auth::session::now_epoch;
 use super::error::AppError;
 use super::secret_store::{RetiredKeys, SecretStore, ServerSecrets};
-use super::store::{KS_IDENTITY, KeyspaceHandle, Store};
+use super::store::{KS_IDENTITY, KeyspaceHandle, Store, WriteBatch};
 
 /// Store key holding the current generation's id.
 const KEY_CURRENT: &str = "identity:current";
@@ -569,11 +569,38 @@ pub async fn save_current_generation(
     generation: &IdentityGeneration,
 ) -> Result<(), AppError> {
     let mut batch = store.batch();
-    batch.insert(identity_ks, gen_key(generation.id), generation)?;
-    batch.insert(identity_ks, KEY_CURRENT.as_bytes().to_vec(), &generation.id)?;
+    stage_current_generation(&mut batch, identity_ks, generation)?;
     batch.commit().await
 }
 
+fn stage_current_generation(
+    batch: &mut WriteBatch,
+    identity_ks: &KeyspaceHandle,
+    generation: &IdentityGeneration,
+) -> Result<(), AppError> {
+    batch.insert(identity_ks, gen_key(generation.id), generation)?;
+    batch.insert(identity_ks, KEY_CURRENT.as_bytes().to_vec(), &generation.id)
+}
+
+/// Writes a caller adds to the batch that stores a rotation — its audit
+/// record, say — so they commit with it. Called with the new generation
+/// and the one it retires (`None` when the rotation could keep no
+/// overlap).
+pub type StageRotation<'a> = &'a (
+        dyn Fn(
+    &mut WriteBatch,
+    &IdentityGeneration,
+    Option<&IdentityGeneration>,
+) -> Result<(), AppError>
+            + Send
+            + Sync
+    );
+
+/// Writes a caller adds to the batch that removes a retired generation
+/// (see [`retire_generation_now`]).
+pub type StageRetire<'a> =
+    &'a (dyn Fn(&mut WriteBatch, &IdentityGeneration) -> Result<(), AppError> + Send + Sync);
+
 // ---------------------------------------------------------------------------
 // Loading
 // ---------------------------------------------------------------------------
@@ -959,7 +986,8 @@ pub enum ReloadOutcome {
 ///
 /// The caller is responsible for rebuilding the listener afterwards (the
 /// profile's secrets vector changed); see
-/// `didcomm_profile::build_tdk_profile_for_identity`.
+/// `didcomm_profile::build_tdk_profile_for_identity`. `stage` adds the
+/// caller's own writes to the batch that stores a rotation.
 pub async fn reload_service_identity(
     identity: &ServiceIdentity,
     store: &Store,
@@ -967,6 +995,7 @@ pub async fn reload_service_identity(
     mediator_did: Option<&str>,
     protocols: ProtocolSet,
     grace_secs: u64,
+    stage: Option<StageRotation<'_>>,
 ) -> Result<ReloadOutcome, AppError> {
     // Serialise rotations. A burst of publishes coalesces here; whoever loses
     // the race re-resolves below and finds nothing left to do.
@@ -1018,7 +1047,15 @@ pub async fn reload_service_identity(
     if persisted.is_none() {
         // First real resolve. Replace the placeholder in place — no retirement,
         // no expiry, no old key to preserve, because there was no old identity.
-        establish_generation(identity, store, &identity_ks, &candidate, secret_store).await?;
+        establish_generation(
+            identity,
+            store,
+            &identity_ks,
+            &candidate,
+            secret_store,
+            None,
+        )
+        .await?;
         info!(
             generation = candidate.id,
             signing_kid = %candidate.signing_kid,
@@ -1102,7 +1139,15 @@ pub async fn reload_service_identity(
     // old one heal as their caches expire — and say plainly what could not be
     // done. Rotating onto a *fresh* fragment is what makes the grace window work.
     if current.is_same_kid_rotation(&candidate) {
-        establish_generation(identity, store, &identity_ks, &candidate, secret_store).await?;
+        establish_generation(
+            identity,
+            store,
+            &identity_ks,
+            &candidate,
+            secret_store,
+            stage,
+        )
+        .await?;
         warn!(
             ka_kid = %candidate.ka_kid,
             "the DID document rotated its key-agreement key but kept the same \
@@ -1147,8 +1192,10 @@ pub async fn reload_service_identity(
     let identity_ks = store.keyspace(KS_IDENTITY)?;
     let mut batch = store.batch();
     batch.insert(&identity_ks, gen_key(retiring.id), &retiring)?;
-    batch.insert(&identity_ks, gen_key(candidate.id), &candidate)?;
-    batch.insert(&identity_ks, KEY_CURRENT.as_bytes().to_vec(), &candidate.id)?;
+    stage_current_generation(&mut batch, &identity_ks, &candidate)?;
+    if let Some(stage) = stage {
+        stage(&mut batch, &candidate, Some(&retiring))?;
+    }
     batch.commit().await?;
 
     // Swap the live set. The new generation's secrets go into the *same*
@@ -1236,6 +1283,7 @@ async fn establish_generation(
     identity_ks: &KeyspaceHandle,
     generation: &IdentityGeneration,
     secret_store: &dyn SecretStore,
+    stage: Option<StageRotation<'_>>,
 ) -> Result<(), AppError> {
     let Some(secrets) = secret_store.get().await? else {
         return Err(AppError::Config(
@@ -1243,7 +1291,12 @@ async fn establish_generation(
         ));
     };
 
-    save_current_generation(store, identity_ks, generation).await?;
+    let mut batch = store.batch();
+    stage_current_generation(&mut batch, identity_ks, generation)?;
+    if let Some(stage) = stage {
+        stage(&mut batch, generation, None)?;
+    }
+    batch.commit().await?;
 
     // Drop the placeholder's key material before inserting the real thing.
     let placeholder = identity.current();
@@ -1428,7 +1481,7 @@ pub async fn run_sweep_once(
 
     let mut reaped = 0;
     for generation in &to_expire {
-        if let Err(e) = expire_generation(identity, store, secret_store, generation).await {
+        if let Err(e) = expire_generation(identity, store, secret_store, generation, None).await {
             warn!(id = generation.id, "failed to expire generation: {e}");
             continue;
         }
@@ -1449,12 +1502,14 @@ pub async fn run_sweep_once(
 /// This is the compromise response. Inbound messages still addressed to the old
 /// key-agreement key stop decrypting the moment this returns, and that is the
 /// point: a compromised key must stop being honoured immediately, breakage
-/// accepted.
+/// accepted. `stage` adds the caller's own writes to the batch that removes
+/// the generation's record.
 pub async fn retire_generation_now(
     identity: &ServiceIdentity,
     store: &Store,
     secret_store: &dyn SecretStore,
     generation_id: u64,
+    stage: Option<StageRetire<'_>>,
 ) -> Result<(), AppError> {
     let _guard = identity.rotation.lock().await;
 
@@ -1472,7 +1527,7 @@ pub async fn retire_generation_now(
         ));
     }
 
-    expire_generation(identity, store, secret_store, generation).await
+    expire_generation(identity, store, secret_store, generation, stage).await
 }
 
 /// Drop a generation: from the secrets resolver, the live set, the store, and
@@ -1482,6 +1537,7 @@ async fn expire_generation(
     store: &Store,
     secret_store: &dyn SecretStore,
     generation: &IdentityGeneration,
+    stage: Option<StageRetire<'_>>,
 ) -> Result<(), AppError> {
     // Key material first. If a later step fails we have still stopped honouring
     // the key, which is the direction to fail in.
@@ -1496,9 +1552,12 @@ async fn expire_generation(
     }
 
     let identity_ks = store.keyspace(KS_IDENTITY)?;
-    identity_ks.remove(gen_key(generation.id)).await?;
-
-    Ok(())
+    let mut batch = store.batch();
+    batch.remove(&identity_ks, gen_key(generation.id));
+    if let Some(stage) = stage {
+        stage(&mut batch, generation)?;
+    }
+    batch.commit().await
 }
 
 #[cfg(test)]
@@ -2055,7 +2114,7 @@ mod tests {
         ];
         let identity = identity_with(vec![placeholder.clone()], placeholder_secrets).await;
 
-        establish_generation(&identity, &store, &ks, &real, &secret_store)
+        establish_generation(&identity, &store, &ks, &real, &secret_store, None)
             .await
             .expect("establish");
 
@@ -2229,7 +2288,7 @@ mod tests {
         )
         .await;
 
-        retire_generation_now(&identity, &store, &secret_store, old_gen.id)
+        retire_generation_now(&identity, &store, &secret_store, old_gen.id, None)
             .await
             .expect("retire now");
 
@@ -2253,7 +2312,7 @@ mod tests {
         let secret_store = MockSecretStore::new(server_secrets(&signing, &ka, Vec::new()));
         let identity = identity_with(vec![current.clone()], vec![signing, ka.clone()]).await;
 
-        let err = retire_generation_now(&identity, &store, &secret_store, current.id)
+        let err = retire_generation_now(&identity, &store, &secret_store, current.id, None)
             .await
             .expect_err("must refuse");
         assert!(
@@ -38,7 +38,7 @@ use didwebvh_rs::{
 };
 use serde_json::{Value, json};
 
-use crate::did_ops::content_log_key;
+use crate::did_ops::{DidRecord, content_log_key, content_witness_key, did_key};
 use crate::server::auth::session::now_epoch;
 use crate::server::error::AppError;
 use crate::server::identity::{IdentityGeneration, load_generations, mnemonic_from_did};
@@ -532,9 +532,14 @@ pub async fn rotate_keys(
     batch.commit().await?;
 
     // 3. The DID log. The document now advertises the new key.
-    dids_ks
-        .insert_raw(content_log_key(&mnemonic), new_log.into_bytes())
-        .await?;
+    let mut batch = store.batch();
+    if let Some(mut record) = dids_ks.get::<DidRecord>(did_key(&mnemonic)).await? {
+        let witness = dids_ks.get_raw(content_witness_key(&mnemonic)).await?;
+        record.set_sync_head(new_log.as_bytes(), witness.as_deref());
+        batch.insert(&dids_ks, did_key(&mnemonic), &record)?;
+    }
+    batch.insert_raw(&dids_ks, content_log_key(&mnemonic), new_log.into_bytes());
+    batch.commit().await?;
     store.persist().await?;
 
     Ok(RotationReport {
@@ -20,6 +20,23 @@ pub fn decode_multibase_ed25519_key(multibase_key: &str) -> Result<[u8; 32], App
         .map_err(|_| AppError::Config("key must be exactly 32 bytes".into()))
 }
 
+/// Ed25519 multikey: `z` + base58btc(0xed01 || key).
+pub fn encode_public_key(key: &ed25519_dalek::VerifyingKey) -> String {
+    let mut bytes = vec![0xed, 0x01];
+    bytes.extend_from_slice(key.as_bytes());
+    multibase::encode(multibase::Base::Base58Btc, bytes)
+}
+
+/// Inverse of [`encode_public_key`]; also accepts a bare 32-byte key.
+pub fn decode_public_key(mb: &str) -> Option<ed25519_dalek::VerifyingKey> {
+    let (_, raw) = multibase::decode(mb).ok()?;
+    let bytes: &[u8] = match raw.as_slice() {
+        [0xed, 0x01, rest @ ..] if rest.len() == 32 => rest,
+        other => other,
+    };
+    ed25519_dalek::VerifyingKey::from_bytes(bytes.try_into().ok()?).ok()
+}
+
 /// Load JWT signing keys from the server secrets.
 ///
 /// Returns `None` (with a warning) if the key cannot be decoded or constructed.
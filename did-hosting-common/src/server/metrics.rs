@@ -4,7 +4,7 @@
 //! for DID operations, auth events, cache performance, and stats sync.
 //! Access via `GET /metrics` (unauthenticated).
 
-use prometheus::{Encoder, IntCounter, Registry, TextEncoder};
+use prometheus::{Encoder, IntCounter, IntGauge, Registry, TextEncoder};
 use std::sync::LazyLock;
 
 static REGISTRY: LazyLock<Registry> = LazyLock::new(Registry::new);
@@ -59,6 +59,48 @@ static CACHE_MISSES: LazyLock<IntCounter> = LazyLock::new(|| {
     c
 });
 
+static CACHE_NEGATIVE_HITS: LazyLock<IntCounter> = LazyLock::new(|| {
+    let c = IntCounter::new(
+        "webvh_cache_negative_hits_total",
+        "Total cache hits on a remembered not-found",
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(c.clone())).unwrap();
+    c
+});
+
+static CACHE_EVICTIONS: LazyLock<IntCounter> = LazyLock::new(|| {
+    let c = IntCounter::new(
+        "webvh_cache_evictions_total",
+        "Total cache entries evicted to make room",
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(c.clone())).unwrap();
+    c
+});
+
+static CACHE_REJECTIONS: LazyLock<IntCounter> = LazyLock::new(|| {
+    let c = IntCounter::new(
+        "webvh_cache_rejections_total",
+        "Total cache inserts refused by admission",
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(c.clone())).unwrap();
+    c
+});
+
+static CACHE_BYTES: LazyLock<IntGauge> = LazyLock::new(|| {
+    let g = IntGauge::new("webvh_cache_bytes", "Estimated bytes held by the cache").unwrap();
+    REGISTRY.register(Box::new(g.clone())).unwrap();
+    g
+});
+
+static CACHE_ENTRIES: LazyLock<IntGauge> = LazyLock::new(|| {
+    let g = IntGauge::new("webvh_cache_entries", "Entries held by the cache").unwrap();
+    REGISTRY.register(Box::new(g.clone())).unwrap();
+    g
+});
+
 static STATS_SYNCS: LazyLock<IntCounter> = LazyLock::new(|| {
     let c = IntCounter::new("webvh_stats_syncs_total", "Total stats sync operations").unwrap();
     REGISTRY.register(Box::new(c.clone())).unwrap();
@@ -100,6 +142,27 @@ pub fn inc_cache_miss() {
     CACHE_MISSES.inc();
 }
 
+/// Increment the cached not-found counter.
+pub fn inc_cache_negative_hit() {
+    CACHE_NEGATIVE_HITS.inc();
+}
+
+/// Add to the cache eviction counter.
+pub fn inc_cache_evictions(n: u64) {
+    CACHE_EVICTIONS.inc_by(n);
+}
+
+/// Increment the cache admission rejection counter.
+pub fn inc_cache_rejection() {
+    CACHE_REJECTIONS.inc();
+}
+
+/// Set the cache size gauges.
+pub fn set_cache_usage(bytes: i64, entries: i64) {
+    CACHE_BYTES.set(bytes);
+    CACHE_ENTRIES.set(entries);
+}
+
 /// Increment the stats sync counter.
 pub fn inc_stats_sync() {
     STATS_SYNCS.inc();
@@ -206,6 +206,8 @@ mod tests {
             domain: String::new(), // legacy state
             services: None,
             agent_names: Vec::new(),
+            deactivated_at: None,
+            sync_head: None,
         }
     }
 
@@ -224,6 +226,8 @@ mod tests {
             domain: domain.into(),
             services: None,
             agent_names: Vec::new(),
+            deactivated_at: None,
+            sync_head: None,
         }
     }
 
@@ -194,6 +194,8 @@ mod tests {
             domain: "host.example".into(),
             services,
             agent_names: Vec::new(),
+            deactivated_at: None,
+            sync_head: None,
         }
     }
 
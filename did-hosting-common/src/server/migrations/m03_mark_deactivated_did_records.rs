@@ -0,0 +1,221 @@
+//! `m03_mark_deactivated_did_records` — set `DidRecord.deactivated_at` on
+//! every record whose stored log already ends in a deactivation.
+//!
+//! ## Why
+//!
+//! Deactivation became terminal when `deactivated_at` was added: a
+//! deactivated slot refuses further logs, leaves its owner's quota and
+//! cannot be deleted before `hosting.deactivated_did_retention`. Records
+//! written before the field existed read as live (`#[serde(default)]`),
+//! so a DID deactivated back then could still be overwritten. This sweep
+//! marks them from their logs.
+//!
+//! ## What gets touched
+//!
+//! Every `did:{mnemonic}` entry in `KS_DIDS` with `deactivated_at: None`
+//! whose log at `content:{mnemonic}:log` is deactivated: a did:webvh log
+//! whose latest parameters set `deactivated: true`, or a did:webplus
+//! ledger whose latest document has empty `updateRules`. did:webs logs are
+//! never read as deactivated (see [`crate::did_ops::inspect_did_log`]).
+//!
+//! `deactivated_at` is set to the record's `updated_at`: the deactivating
+//! entry was the record's last write, and using a stored value keeps two
+//! replicas running the sweep in agreement.
+//!
+//! ## Quota
+//!
+//! A hosting server keeps a per-owner usage counter at `quota:{owner}`,
+//! which a deactivating publish decrements. The sweep does the same for
+//! each record it marks, in the same batch, so the counter matches what a
+//! publish would have left. The control plane keeps no such counter; the
+//! key is only touched when it exists. Marked records are skipped on a
+//! re-run, so the counter is adjusted once.
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use super::super::store::{KS_DIDS, Store};
+use super::{Migration, MigrationFuture};
+use crate::did_ops::{DidRecord, content_log_key, detect_log_method, did_key};
+
+/// Public migration ID. Stable wire identifier — never rename.
+pub const ID: &str = "m03_mark_deactivated_did_records";
+
+/// The hosting server's `quota:{owner}` row, as far as this sweep needs it.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct OwnerQuota {
+    did_count: u64,
+    total_size: u64,
+}
+
+/// Whether a stored log ends in its method's deactivation.
+fn log_is_deactivated(content: &str) -> bool {
+    match detect_log_method(content) {
+        Some("webvh") => crate::did_ops::extract_log_metadata(content).deactivated,
+        Some("webplus") => {
+            crate::did_ops::inspect_did_log(content, None).is_ok_and(|summary| summary.deactivated)
+        }
+        _ => false,
+    }
+}
+
+pub struct M03MarkDeactivatedDidRecords;
+
+impl Migration for M03MarkDeactivatedDidRecords {
+    fn id(&self) -> &'static str {
+        ID
+    }
+
+    fn description(&self) -> &'static str {
+        "set DidRecord.deactivated_at on records whose log ends in a deactivation"
+    }
+
+    fn run<'a>(&'a self, store: &'a Store) -> MigrationFuture<'a> {
+        Box::pin(async move {
+            let dids = store.keyspace(KS_DIDS)?;
+            let (mut marked, mut live) = (0u64, 0u64);
+
+            for (key, value) in dids.prefix_iter_raw(b"did:".to_vec()).await? {
+                let Ok(key) = std::str::from_utf8(&key) else {
+                    warn!(migration_id = ID, "skipping non-UTF-8 key in dids keyspace");
+                    continue;
+                };
+                let mnemonic = key.strip_prefix("did:").unwrap_or(key).to_string();
+                let mut record: DidRecord = match serde_json::from_slice(&value) {
+                    Ok(r) => r,
+                    Err(e) => {
+                        warn!(
+                            migration_id = ID,
+                            mnemonic = %mnemonic,
+                            error = %e,
+                            "skipping unparseable DidRecord"
+                        );
+                        continue;
+                    }
+                };
+                if record.is_deactivated() {
+                    continue;
+                }
+                let deactivated = dids
+                    .get_raw(content_log_key(&mnemonic))
+                    .await?
+                    .and_then(|bytes| String::from_utf8(bytes).ok())
+                    .is_some_and(|content| log_is_deactivated(&content));
+                if !deactivated {
+                    live += 1;
+                    continue;
+                }
+
+                record.deactivated_at = Some(record.updated_at);
+                let mut batch = store.batch();
+                batch.insert(&dids, did_key(&mnemonic), &record)?;
+                let quota_key = format!("quota:{}", record.owner);
+                if let Some(mut quota) = dids.get::<OwnerQuota>(quota_key.clone()).await? {
+                    quota.did_count = quota.did_count.saturating_sub(1);
+                    quota.total_size = quota.total_size.saturating_sub(record.content_size);
+                    batch.insert(&dids, quota_key, &quota)?;
+                }
+                batch.commit().await?;
+                marked += 1;
+            }
+
+            info!(migration_id = ID, marked, live, "M-03 complete");
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::MigrationRunner;
+    use super::*;
+    use crate::server::config::StoreConfig;
+
+    async fn fjall_store() -> Store {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let cfg = StoreConfig {
+            data_dir: dir.path().to_path_buf(),
+            ..StoreConfig::default()
+        };
+        std::mem::forget(dir);
+        Store::open(&cfg).await.expect("open fjall")
+    }
+
+    fn record(mnemonic: &str) -> DidRecord {
+        DidRecord {
+            owner: "did:example:owner".into(),
+            mnemonic: mnemonic.into(),
+            created_at: 0,
+            updated_at: 42,
+            version_count: 2,
+            did_id: Some(format!("did:webvh:Q1:host.example:{mnemonic}")),
+            content_size: 100,
+            disabled: false,
+            deleted_at: None,
+            method: "webvh".into(),
+            domain: "host.example".into(),
+            services: None,
+            agent_names: Vec::new(),
+            deactivated_at: None,
+            sync_head: None,
+        }
+    }
+
+    fn log(deactivated: bool) -> String {
+        let v1 = r#"{"versionId":"1-a","parameters":{"method":"did:webvh:1.0"},"state":{"id":"did:webvh:Q1:host.example:x"}}"#;
+        let v2 = format!(
+            r#"{{"versionId":"2-b","parameters":{{"deactivated":{deactivated}}},"state":{{"id":"did:webvh:Q1:host.example:x"}}}}"#
+        );
+        format!("{v1}\n{v2}")
+    }
+
+    async fn seed(store: &Store, rec: &DidRecord, log: &str) {
+        let ks = store.keyspace(KS_DIDS).unwrap();
+        ks.insert(did_key(&rec.mnemonic), rec).await.unwrap();
+        ks.insert_raw(content_log_key(&rec.mnemonic), log.as_bytes().to_vec())
+            .await
+            .unwrap();
+    }
+
+    async fn load(store: &Store, mnemonic: &str) -> DidRecord {
+        let ks = store.keyspace(KS_DIDS).unwrap();
+        ks.get::<DidRecord>(did_key(mnemonic))
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    /// A deactivated log marks its record at its last write and leaves
+    /// the server's quota counter; a live log is left alone.
+    #[tokio::test]
+    async fn marks_records_whose_log_is_deactivated() {
+        let store = fjall_store().await;
+        seed(&store, &record("gone"), &log(true)).await;
+        seed(&store, &record("live"), &log(false)).await;
+        let ks = store.keyspace(KS_DIDS).unwrap();
+        ks.insert(
+            "quota:did:example:owner",
+            &OwnerQuota {
+                did_count: 2,
+                total_size: 200,
+            },
+        )
+        .await
+        .unwrap();
+
+        MigrationRunner::new(vec![std::sync::Arc::new(M03MarkDeactivatedDidRecords)])
+            .run_pending(&store)
+            .await
+            .expect("m03 runs");
+        // The body is idempotent: a second pass finds nothing to mark.
+        M03MarkDeactivatedDidRecords
+            .run(&store)
+            .await
+            .expect("second body run");
+
+        assert_eq!(load(&store, "gone").await.deactivated_at, Some(42));
+        assert_eq!(load(&store, "live").await.deactivated_at, None);
+        let quota: OwnerQuota = ks.get("quota:did:example:owner").await.unwrap().unwrap();
+        assert_eq!((quota.did_count, quota.total_size), (1, 100));
+    }
+}
@@ -43,10 +43,12 @@ use super::store::{KS_META, KeyspaceHandle, Store};
 
 pub mod m01_tag_did_records_with_domain;
 pub mod m02_cache_did_record_services;
+pub mod m03_mark_deactivated_did_records;
 pub mod runner;
 
 pub use m01_tag_did_records_with_domain::M01TagDidRecordsWithDomain;
 pub use m02_cache_did_record_services::M02CacheDidRecordServices;
+pub use m03_mark_deactivated_did_records::M03MarkDeactivatedDidRecords;
 pub use runner::{MigrationRunner, RunSummary};
 
 /// Boxed future used by [`Migration::run`] so the trait stays object-safe
@@ -138,5 +140,6 @@ pub fn registry() -> Vec<Arc<dyn Migration>> {
     vec![
         Arc::new(M01TagDidRecordsWithDomain),
         Arc::new(M02CacheDidRecordServices),
+        Arc::new(M03MarkDeactivatedDidRecords),
     ]
 }
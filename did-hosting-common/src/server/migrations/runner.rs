@@ -92,6 +92,7 @@ mod tests {
         // feature is compiled in and no cloud-backend fields are set.
         let dir = tempfile::tempdir().expect("tempdir");
         let cfg = StoreConfig {
+            backend: None,
             data_dir: dir.path().to_path_buf(),
             redis_url: None,
             dynamodb_table_prefix: None,
@@ -91,13 +91,81 @@ pub fn validate_custom_path(path: &str) -> Result<(), AppError> {
 
 /// Validate a mnemonic extracted from a URL path parameter.
 ///
-/// Accepts either `.well-known` (the root DID) or any path that passes
-/// [`validate_custom_path`].
+/// Accepts `.well-known` (the root DID), any path that passes
+/// [`validate_custom_path`], and, when their methods are compiled in, the
+/// mixed-case did:webs and did:webplus slots ([`validate_webs_mnemonic`],
+/// [`validate_webplus_mnemonic`]). The error reported is the custom-path
+/// one.
 pub fn validate_mnemonic(mnemonic: &str) -> Result<(), AppError> {
     if mnemonic == ".well-known" {
         return Ok(());
     }
-    validate_custom_path(mnemonic)
+    let err = match validate_custom_path(mnemonic) {
+        Ok(()) => return Ok(()),
+        Err(e) => e,
+    };
+    #[cfg(feature = "method-webs")]
+    if validate_webs_mnemonic(mnemonic).is_ok() {
+        return Ok(());
+    }
+    #[cfg(feature = "method-webplus")]
+    if validate_webplus_mnemonic(mnemonic).is_ok() {
+        return Ok(());
+    }
+    Err(err)
+}
+
+/// [`validate_mnemonic`] for a slot about to hold a `method` DID: webs and
+/// webplus slots must end in their AID or root self-hash, every other
+/// method uses the plain path grammar.
+pub fn validate_mnemonic_for(method: &str, mnemonic: &str) -> Result<(), AppError> {
+    match method {
+        #[cfg(feature = "method-webs")]
+        "webs" => validate_webs_mnemonic(mnemonic),
+        #[cfg(feature = "method-webplus")]
+        "webplus" => validate_webplus_mnemonic(mnemonic),
+        _ if mnemonic == ".well-known" => Ok(()),
+        _ => validate_custom_path(mnemonic),
+    }
+}
+
+/// Validate a did:webs mnemonic: an optional [`validate_custom_path`]
+/// prefix followed by the AID as the final segment.
+///
+/// The AID is a case-sensitive CESR primitive (`E…`, 44 chars), which the
+/// lowercase segment grammar would reject, so it is checked as CESR
+/// instead. Every did:webs DID resolves under its AID, so a bare prefix
+/// with no AID is never a webs slot.
+#[cfg(feature = "method-webs")]
+pub fn validate_webs_mnemonic(mnemonic: &str) -> Result<(), AppError> {
+    let (prefix, aid) = match mnemonic.rsplit_once('/') {
+        Some((prefix, aid)) => (Some(prefix), aid),
+        None => (None, mnemonic),
+    };
+    if let Some(prefix) = prefix {
+        validate_custom_path(prefix)?;
+    }
+    crate::method::webs::validate_aid(aid)
+        .map_err(|reason| path_err(format!("did:webs AID segment {reason}")))
+}
+
+/// Validate a did:webplus mnemonic: an optional [`validate_custom_path`]
+/// prefix followed by the root self-hash as the final segment.
+///
+/// The self-hash is case-sensitive base64url (`uHiB…`, 47 chars), so like
+/// the did:webs AID it is checked against its own encoding rather than the
+/// lowercase segment grammar.
+#[cfg(feature = "method-webplus")]
+pub fn validate_webplus_mnemonic(mnemonic: &str) -> Result<(), AppError> {
+    let (prefix, root) = match mnemonic.rsplit_once('/') {
+        Some((prefix, root)) => (Some(prefix), root),
+        None => (None, mnemonic),
+    };
+    if let Some(prefix) = prefix {
+        validate_custom_path(prefix)?;
+    }
+    crate::method::webplus::validate_self_hash(root)
+        .map_err(|reason| path_err(format!("did:webplus root self-hash segment {reason}")))
 }
 
 /// Agent names nobody may claim.
@@ -2,13 +2,17 @@ pub mod acl;
 pub mod assignment;
 pub mod assignment_seed;
 pub mod auth;
+pub mod backup;
 pub mod cli_acl;
 pub mod cli_identity;
+pub mod conditional;
 pub mod config;
+pub mod deactivation;
 pub mod didcomm_profile;
 pub mod didcomm_unpack;
 pub mod domain;
 pub mod domain_purge;
+pub mod encoding;
 pub mod error;
 pub mod health;
 pub mod identity;
@@ -25,12 +29,14 @@ pub mod passkey;
 pub mod path_locks;
 pub mod pending_purge;
 pub mod problem_report;
+pub mod rollback_proof;
 pub mod secret_store;
 #[cfg(feature = "setup-wizard")]
 pub mod setup_prompts;
 pub mod setup_recipe;
 pub mod stats_collector;
 pub mod store;
+pub mod store_migrate;
 pub mod trust_task;
 /// New trust-tasks framework integration (SPEC.md 0.1). Gated behind
 /// `server-core` because the dispatcher only runs on the server side;
@@ -132,4 +138,6 @@ pub fn public_resolution_cors() -> tower_http::cors::CorsLayer {
         .allow_origin(tower_http::cors::Any)
         .allow_methods([Method::GET, Method::HEAD, Method::OPTIONS])
         .allow_headers(tower_http::cors::Any)
+        // Let browser resolvers read the validator for conditional polls.
+        .expose_headers([axum::http::header::ETAG])
 }
@@ -33,7 +33,7 @@
 use serde::{Deserialize, Serialize};
 
 use super::error::AppError;
-use super::store::{KS_PENDING_PURGES, Store};
+use super::store::{KS_PENDING_PURGES, Store, WriteBatch};
 
 /// One row in the per-server pending-purge queue.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -92,6 +92,31 @@ pub async fn schedule(
     grace_seconds: u64,
     reason: &str,
     scheduled_by: &str,
+) -> Result<ScheduleOutcome, AppError> {
+    let mut batch = store.batch();
+    let outcome = stage_schedule(
+        store,
+        &mut batch,
+        domain,
+        scheduled_at,
+        grace_seconds,
+        reason,
+        scheduled_by,
+    )
+    .await?;
+    batch.commit().await?;
+    Ok(outcome)
+}
+
+/// [`schedule`] as part of `batch`.
+pub async fn stage_schedule(
+    store: &Store,
+    batch: &mut WriteBatch,
+    domain: &str,
+    scheduled_at: u64,
+    grace_seconds: u64,
+    reason: &str,
+    scheduled_by: &str,
 ) -> Result<ScheduleOutcome, AppError> {
     let ks = store.keyspace(KS_PENDING_PURGES)?;
     let key = pending_key(domain);
@@ -111,20 +136,32 @@ pub async fn schedule(
         },
         None => ScheduleOutcome::Created(new.clone()),
     };
-    ks.insert(key, &new).await?;
+    batch.insert(&ks, key, &new)?;
     Ok(outcome)
 }
 
 /// Cancel a pending purge — typically called when a domain is re-
 /// assigned within the grace window.
 pub async fn cancel(store: &Store, domain: &str) -> Result<CancelOutcome, AppError> {
+    let mut batch = store.batch();
+    let outcome = stage_cancel(store, &mut batch, domain).await?;
+    batch.commit().await?;
+    Ok(outcome)
+}
+
+/// [`cancel`] as part of `batch`.
+pub async fn stage_cancel(
+    store: &Store,
+    batch: &mut WriteBatch,
+    domain: &str,
+) -> Result<CancelOutcome, AppError> {
     let ks = store.keyspace(KS_PENDING_PURGES)?;
     let key = pending_key(domain);
 
     let Some(existing) = ks.get::<PendingPurge>(key.clone()).await? else {
         return Ok(CancelOutcome::Missing);
     };
-    ks.remove(key).await?;
+    batch.remove(&ks, key);
     Ok(CancelOutcome::Removed(existing))
 }
 
@@ -0,0 +1,128 @@
+//! Signed rollbacks for watchers.
+//!
+//! A watcher serves a DID's log append-only: a shorter log is a rewrite
+//! unless the entries that came off the end were rolled back on purpose.
+//! The `rolled_back` list on a sync body only says which entries those were;
+//! anyone holding a push token could send it. A [`RollbackProof`] makes it
+//! checkable: the service that rolled the log back signs the DID, a hash of
+//! each dropped entry and a hash of the new head, and the watcher accepts
+//! the truncation only under a signature from a key in its
+//! `sync.rollback_keys`.
+//!
+//! The control plane signs with its assertion key when it pushes a rolled
+//! back log to its servers; a standalone server signs its own rollbacks
+//! with its signing key. Servers pass the proof on unchanged.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use super::init::{decode_public_key, encode_public_key};
+use crate::RollbackProof;
+
+/// Separates these signatures from anything else the same key signs.
+const CONTEXT: &str = "did-hosting/rollback/1";
+
+/// The bytes a [`RollbackProof`] signs.
+#[derive(Serialize)]
+struct Statement<'a> {
+    context: &'a str,
+    did: &'a str,
+    dropped: &'a [String],
+    head: &'a str,
+}
+
+fn entry_hash(entry: &str) -> String {
+    hex::encode(Sha256::digest(entry.trim().as_bytes()))
+}
+
+fn last_entry(log: &str) -> Option<&str> {
+    log.lines().map(str::trim).rfind(|l| !l.is_empty())
+}
+
+fn statement_bytes(did: &str, dropped: &[String], head: &str) -> Vec<u8> {
+    serde_json::to_vec(&Statement {
+        context: CONTEXT,
+        did,
+        dropped,
+        head,
+    })
+    .expect("statement serialises")
+}
+
+/// Sign the rollback of `did`'s log to `log`, with `rolled_back` the
+/// entries taken off its end in log order. `None` when `log` is empty.
+pub fn sign(
+    did: &str,
+    log: &str,
+    rolled_back: &[String],
+    key: &SigningKey,
+) -> Option<RollbackProof> {
+    let head = entry_hash(last_entry(log)?);
+    let dropped: Vec<String> = rolled_back.iter().map(|e| entry_hash(e)).collect();
+    let signature = key.sign(&statement_bytes(did, &dropped, &head));
+    Some(RollbackProof {
+        did: did.to_string(),
+        dropped,
+        head,
+        signer: encode_public_key(&key.verifying_key()),
+        signature: multibase::encode(multibase::Base::Base58Btc, signature.to_bytes()),
+    })
+}
+
+/// Check that `proof` vouches for rolling `did`'s log back to `log` by
+/// exactly `rolled_back`, under one of the `trusted` keys.
+pub fn verify(
+    proof: &RollbackProof,
+    did: &str,
+    log: &str,
+    rolled_back: &[String],
+    trusted: &[VerifyingKey],
+) -> Result<(), String> {
+    if proof.did != did {
+        return Err(format!("proof is for {}, not {did}", proof.did));
+    }
+    let head = last_entry(log).map(entry_hash).ok_or("log is empty")?;
+    if proof.head != head {
+        return Err("proof names a different head".into());
+    }
+    let dropped: Vec<String> = rolled_back.iter().map(|e| entry_hash(e)).collect();
+    if proof.dropped != dropped {
+        return Err("proof names different dropped entries".into());
+    }
+    let signer = decode_public_key(&proof.signer).ok_or("malformed signer key")?;
+    if !trusted.contains(&signer) {
+        return Err(format!("signer {} is not trusted", proof.signer));
+    }
+    let (_, raw) = multibase::decode(&proof.signature).map_err(|e| e.to_string())?;
+    let signature = Signature::from_slice(&raw).map_err(|e| e.to_string())?;
+    signer
+        .verify(&statement_bytes(did, &dropped, &head), &signature)
+        .map_err(|_| "bad signature".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proof_binds_did_entries_head_and_signer() {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let trusted = [key.verifying_key()];
+        let dropped = vec!["{\"c\":3}".to_string()];
+        let log = "{\"a\":1}\n{\"b\":2}\n";
+        let proof = sign("did:webvh:x", log, &dropped, &key).unwrap();
+
+        assert!(verify(&proof, "did:webvh:x", log, &dropped, &trusted).is_ok());
+        assert!(verify(&proof, "did:webvh:y", log, &dropped, &trusted).is_err());
+        assert!(verify(&proof, "did:webvh:x", "{\"a\":1}\n", &dropped, &trusted).is_err());
+        assert!(verify(&proof, "did:webvh:x", log, &["{\"d\":4}".into()], &trusted).is_err());
+
+        let stranger = SigningKey::from_bytes(&[8u8; 32]).verifying_key();
+        assert!(verify(&proof, "did:webvh:x", log, &dropped, &[stranger]).is_err());
+
+        let mut forged = proof.clone();
+        forged.dropped = vec![entry_hash("{\"d\":4}")];
+        assert!(verify(&forged, "did:webvh:x", log, &["{\"d\":4}".into()], &trusted).is_err());
+    }
+}
@@ -151,6 +151,13 @@ fn encode_doc_id(key: &[u8]) -> String {
     BASE64.encode(key)
 }
 
+/// The longest encoded string every ID under `prefix` starts with. Base64
+/// only fixes a character once its whole 3-byte group is known, so a
+/// trailing partial group is left for the byte check after decoding.
+fn encode_id_prefix(prefix: &[u8]) -> String {
+    encode_doc_id(&prefix[..prefix.len() / 3 * 3])
+}
+
 impl CosmosDbKeyspace {
     async fn container(&self) -> Result<azure_data_cosmos::clients::ContainerClient, AppError> {
         self.client
@@ -244,16 +251,35 @@ impl KeyspaceOps for CosmosDbKeyspace {
         })
     }
 
+    fn insert_raw_if_absent(
+        &self,
+        key: Vec<u8>,
+        value: Vec<u8>,
+    ) -> BoxFuture<'_, Result<bool, AppError>> {
+        // Serialised with the same per-keyspace mutex as
+        // `take_raw_atomic`, so it carries the same **single-replica**
+        // caveat: two replicas can both see the key absent and both
+        // write it.
+        Box::pin(async move {
+            let _guard = self.take_lock.lock().await;
+            if self.contains_key(key.clone()).await? {
+                return Ok(false);
+            }
+            self.insert_raw(key, value).await?;
+            Ok(true)
+        })
+    }
+
     fn prefix_iter_raw(&self, prefix: Vec<u8>) -> BoxFuture<'_, Result<Vec<RawKvPair>, AppError>> {
         Box::pin(async move {
             let container = self.container().await?;
 
-            let query = if prefix.is_empty() {
+            let prefix_encoded = encode_id_prefix(&prefix);
+            let query = if prefix_encoded.is_empty() {
                 azure_data_cosmos::Query::from("SELECT * FROM c WHERE c.pk = @pk")
                     .with_parameter("@pk", PARTITION_VALUE)
                     .map_err(|e| AppError::Store(format!("cosmosdb query param: {e}")))?
             } else {
-                let prefix_encoded = encode_doc_id(&prefix);
                 azure_data_cosmos::Query::from(
                     "SELECT * FROM c WHERE c.pk = @pk AND STARTSWITH(c.id, @prefix)",
                 )
@@ -287,6 +313,64 @@ impl KeyspaceOps for CosmosDbKeyspace {
             Ok(results)
         })
     }
+
+    fn scan_page_raw(
+        &self,
+        prefix: Vec<u8>,
+        after: Option<Vec<u8>>,
+        limit: usize,
+    ) -> BoxFuture<'_, Result<Vec<RawKvPair>, AppError>> {
+        Box::pin(async move {
+            let container = self.container().await?;
+
+            // Pages follow the encoded ID, so `after` becomes an `id >`
+            // bound. Base64url does not keep byte order; the prefix is
+            // narrowed on the encoded ID and checked on the decoded key.
+            let prefix_encoded = encode_id_prefix(&prefix);
+            let mut cursor = after.map(|after| encode_doc_id(&after)).unwrap_or_default();
+            let mut results = Vec::new();
+            while results.len() < limit {
+                let want = limit - results.len();
+                let query = azure_data_cosmos::Query::from(format!(
+                    "SELECT TOP {want} * FROM c WHERE c.pk = @pk AND c.id > @after \
+                     AND STARTSWITH(c.id, @prefix) ORDER BY c.id"
+                ))
+                .with_parameter("@pk", PARTITION_VALUE)
+                .map_err(|e| AppError::Store(format!("cosmosdb query param: {e}")))?
+                .with_parameter("@after", &cursor)
+                .map_err(|e| AppError::Store(format!("cosmosdb query param: {e}")))?
+                .with_parameter("@prefix", &prefix_encoded)
+                .map_err(|e| AppError::Store(format!("cosmosdb query param: {e}")))?;
+
+                let mut pager = container
+                    .query_items::<KvDoc>(query, FeedScope::partition(PARTITION_VALUE), None)
+                    .await
+                    .map_err(|e| AppError::Store(format!("cosmosdb query: {e}")))?;
+
+                let mut seen = 0;
+                while let Some(item_result) = pager.next().await {
+                    let doc: KvDoc = item_result
+                        .map_err(|e| AppError::Store(format!("cosmosdb query item: {e}")))?;
+                    seen += 1;
+                    let key_bytes = BASE64
+                        .decode(&doc.id)
+                        .map_err(|e| AppError::Store(format!("cosmosdb decode key: {e}")))?;
+                    if key_bytes.starts_with(&prefix) {
+                        let val_bytes = BASE64
+                            .decode(&doc.data)
+                            .map_err(|e| AppError::Store(format!("cosmosdb decode val: {e}")))?;
+                        results.push((key_bytes, val_bytes));
+                    }
+                    cursor = doc.id;
+                }
+                if seen < want {
+                    break;
+                }
+            }
+
+            Ok(results)
+        })
+    }
 }
 
 /// Check if a Cosmos DB error is a 404 Not Found.
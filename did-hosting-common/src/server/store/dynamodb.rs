@@ -240,6 +240,39 @@ impl KeyspaceOps for DynamoDbKeyspace {
         })
     }
 
+    fn insert_raw_if_absent(
+        &self,
+        key: Vec<u8>,
+        value: Vec<u8>,
+    ) -> BoxFuture<'_, Result<bool, AppError>> {
+        Box::pin(async move {
+            ensure_table(&self.client, &self.table, &self.verified).await?;
+            // A conditional PutItem is evaluated atomically per partition
+            // key: exactly one concurrent writer passes
+            // `attribute_not_exists`, the rest fail the condition.
+            let result = self
+                .client
+                .put_item()
+                .table_name(&self.table)
+                .item(PK_ATTR, AttributeValue::B(Blob::new(key)))
+                .item(VAL_ATTR, AttributeValue::B(Blob::new(value)))
+                .condition_expression("attribute_not_exists(#pk)")
+                .expression_attribute_names("#pk", PK_ATTR)
+                .send()
+                .await;
+            match result {
+                Ok(_) => Ok(true),
+                Err(e)
+                    if e.as_service_error()
+                        .is_some_and(|se| se.is_conditional_check_failed_exception()) =>
+                {
+                    Ok(false)
+                }
+                Err(e) => Err(AppError::Store(format!("dynamodb put (if absent): {e}"))),
+            }
+        })
+    }
+
     fn prefix_iter_raw(&self, prefix: Vec<u8>) -> BoxFuture<'_, Result<Vec<RawKvPair>, AppError>> {
         Box::pin(async move {
             ensure_table(&self.client, &self.table, &self.verified).await?;
@@ -276,6 +309,49 @@ impl KeyspaceOps for DynamoDbKeyspace {
             Ok(results)
         })
     }
+
+    fn scan_page_raw(
+        &self,
+        prefix: Vec<u8>,
+        after: Option<Vec<u8>>,
+        limit: usize,
+    ) -> BoxFuture<'_, Result<Vec<RawKvPair>, AppError>> {
+        Box::pin(async move {
+            ensure_table(&self.client, &self.table, &self.verified).await?;
+
+            // The table is hash-keyed, so pages come in the Scan's own
+            // order. A Scan resumes after any item key given as its
+            // exclusive start key, which makes `after` the cursor; prefix
+            // filtering stays client-side as in `prefix_iter_raw`.
+            let mut results = Vec::new();
+            let mut start = after.map(|after| {
+                HashMap::from([(PK_ATTR.to_string(), AttributeValue::B(Blob::new(after)))])
+            });
+            while results.len() < limit {
+                let want = i32::try_from(limit - results.len()).unwrap_or(i32::MAX);
+                let resp = self
+                    .client
+                    .scan()
+                    .table_name(&self.table)
+                    .set_exclusive_start_key(start.take())
+                    .limit(want)
+                    .send()
+                    .await
+                    .map_err(|e| AppError::Store(format!("dynamodb scan: {e}")))?;
+
+                if let Some(items) = resp.items {
+                    filter_items_by_prefix(items, &prefix, &mut results);
+                }
+
+                start = resp.last_evaluated_key;
+                if start.is_none() {
+                    break;
+                }
+            }
+
+            Ok(results)
+        })
+    }
 }
 
 /// Reduce one page of scanned items to the `(pk, val)` byte pairs whose key
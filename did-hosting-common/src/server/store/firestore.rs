@@ -177,6 +177,25 @@ impl KeyspaceOps for FirestoreKeyspace {
         })
     }
 
+    fn insert_raw_if_absent(
+        &self,
+        key: Vec<u8>,
+        value: Vec<u8>,
+    ) -> BoxFuture<'_, Result<bool, AppError>> {
+        // Serialised with the same per-keyspace mutex as
+        // `take_raw_atomic`, so it carries the same **single-replica**
+        // caveat: two replicas can both see the key absent and both
+        // write it.
+        Box::pin(async move {
+            let _guard = self.take_lock.lock().await;
+            if self.contains_key(key.clone()).await? {
+                return Ok(false);
+            }
+            self.insert_raw(key, value).await?;
+            Ok(true)
+        })
+    }
+
     fn prefix_iter_raw(&self, prefix: Vec<u8>) -> BoxFuture<'_, Result<Vec<RawKvPair>, AppError>> {
         Box::pin(async move {
             let params =
@@ -205,6 +224,60 @@ impl KeyspaceOps for FirestoreKeyspace {
             Ok(results)
         })
     }
+
+    fn scan_page_raw(
+        &self,
+        prefix: Vec<u8>,
+        after: Option<Vec<u8>>,
+        limit: usize,
+    ) -> BoxFuture<'_, Result<Vec<RawKvPair>, AppError>> {
+        Box::pin(async move {
+            // Pages follow the `key` field, the encoded document ID, so
+            // `after` becomes a query cursor. Base64url does not keep byte
+            // order, so the prefix is matched on the decoded key.
+            let mut results = Vec::new();
+            let mut cursor = after.map(|after| encode_doc_id(&after));
+            while results.len() < limit {
+                let want = limit - results.len();
+                let mut query = self
+                    .db
+                    .fluent()
+                    .select()
+                    .from(self.collection.as_str())
+                    .order_by([(path!(KvDoc::key), FirestoreQueryDirection::Ascending)]);
+                if let Some(cursor) = &cursor {
+                    query = query.start_at(FirestoreQueryCursor::AfterValue(vec![
+                        (&cursor.as_str()).into(),
+                    ]));
+                }
+                let docs: Vec<KvDoc> = query
+                    .limit(u32::try_from(want).unwrap_or(u32::MAX))
+                    .obj()
+                    .query()
+                    .await
+                    .map_err(|e| AppError::Store(format!("firestore query: {e}")))?;
+
+                let exhausted = docs.len() < want;
+                for doc in docs {
+                    let key_bytes = BASE64
+                        .decode(&doc.key)
+                        .map_err(|e| AppError::Store(format!("firestore decode key: {e}")))?;
+                    if key_bytes.starts_with(&prefix) {
+                        let val_bytes = BASE64
+                            .decode(&doc.data)
+                            .map_err(|e| AppError::Store(format!("firestore decode val: {e}")))?;
+                        results.push((key_bytes, val_bytes));
+                    }
+                    cursor = Some(doc.key);
+                }
+                if exhausted {
+                    break;
+                }
+            }
+
+            Ok(results)
+        })
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -1,3 +1,4 @@
+use std::ops::Bound;
 use std::sync::Arc;
 
 use fjall::{KeyspaceCreateOptions, PersistMode};
@@ -140,6 +141,36 @@ impl KeyspaceOps for FjallKeyspace {
         })
     }
 
+    fn scan_page_raw(
+        &self,
+        prefix: Vec<u8>,
+        after: Option<Vec<u8>>,
+        limit: usize,
+    ) -> BoxFuture<'_, Result<Vec<RawKvPair>, AppError>> {
+        let ks = self.keyspace.clone();
+        Box::pin(async move {
+            tokio::task::spawn_blocking(move || -> Result<Vec<RawKvPair>, AppError> {
+                let iter: Box<dyn Iterator<Item = _>> = match after {
+                    Some(after) => Box::new(ks.range((Bound::Excluded(after), Bound::Unbounded))),
+                    None => Box::new(ks.prefix(&prefix)),
+                };
+                let mut results = Vec::with_capacity(limit.min(1024));
+                for guard in iter {
+                    let (key, value) = guard
+                        .into_inner()
+                        .map_err(|e| AppError::Store(e.to_string()))?;
+                    if !key.starts_with(&prefix) || results.len() == limit {
+                        break;
+                    }
+                    results.push((key.to_vec(), value.to_vec()));
+                }
+                Ok(results)
+            })
+            .await
+            .map_err(|e| AppError::Internal(format!("blocking task panicked: {e}")))?
+        })
+    }
+
     fn take_raw_atomic(&self, key: Vec<u8>) -> BoxFuture<'_, Result<Option<Vec<u8>>, AppError>> {
         Box::pin(async move {
             // Per-keyspace mutex serialises the get-then-remove so two
@@ -164,6 +195,23 @@ impl KeyspaceOps for FjallKeyspace {
             Ok(value)
         })
     }
+
+    fn insert_raw_if_absent(
+        &self,
+        key: Vec<u8>,
+        value: Vec<u8>,
+    ) -> BoxFuture<'_, Result<bool, AppError>> {
+        Box::pin(async move {
+            // Same mutex as `take_raw_atomic`: fjall is single-process, so
+            // serialising the check-then-insert here is enough.
+            let _guard = self.take_lock.lock().await;
+            if self.contains_key(key.clone()).await? {
+                return Ok(false);
+            }
+            self.insert_raw(key, value).await?;
+            Ok(true)
+        })
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -258,6 +306,27 @@ mod tests {
         assert!(ks.get_raw(b"refresh:abc".to_vec()).await.unwrap().is_none());
     }
 
+    /// Concurrent `insert_if_absent` calls for one key: exactly one wins,
+    /// and its value is the one left stored.
+    #[tokio::test]
+    async fn insert_if_absent_never_overwrites() {
+        let (store, _dir) = temp_store().await;
+        let ks = store.keyspace("test").unwrap();
+
+        let ks_a = ks.clone();
+        let ks_b = ks.clone();
+        let (a, b) = tokio::join!(
+            tokio::spawn(async move { ks_a.insert_if_absent("rec:1", &"a").await.unwrap() }),
+            tokio::spawn(async move { ks_b.insert_if_absent("rec:1", &"b").await.unwrap() }),
+        );
+        let (a, b) = (a.unwrap(), b.unwrap());
+        assert!(a ^ b, "exactly one concurrent insert_if_absent must win");
+
+        let stored: String = ks.get("rec:1").await.unwrap().unwrap();
+        assert_eq!(stored, if a { "a" } else { "b" });
+        assert!(!ks.insert_if_absent("rec:1", &"c").await.unwrap());
+    }
+
     #[tokio::test]
     async fn get_missing_returns_none() {
         let (store, _dir) = temp_store().await;
@@ -310,4 +379,23 @@ mod tests {
         assert!(keys.contains(&"prefix:a".to_string()));
         assert!(keys.contains(&"prefix:b".to_string()));
     }
+
+    #[tokio::test]
+    async fn scan_page_raw_walks_a_prefix_in_pages() {
+        let (store, _dir) = temp_store().await;
+        let ks = store.keyspace("test").unwrap();
+        for k in ["p:a", "p:b", "p:c", "q:d"] {
+            ks.insert_raw(k, b"v".to_vec()).await.unwrap();
+        }
+        let keys = |page: &[RawKvPair]| {
+            page.iter()
+                .map(|(k, _)| String::from_utf8(k.clone()).unwrap())
+                .collect::<Vec<_>>()
+        };
+        let first = ks.scan_page_raw("p:", None, 2).await.unwrap();
+        assert_eq!(keys(&first), ["p:a", "p:b"]);
+        let after = first.last().map(|(k, _)| k.clone());
+        let second = ks.scan_page_raw("p:", after, 2).await.unwrap();
+        assert_eq!(keys(&second), ["p:c"]);
+    }
 }
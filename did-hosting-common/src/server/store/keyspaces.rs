@@ -13,7 +13,8 @@
 //! - a workspace lint (`rg 'keyspace\("'` returns no matches outside this
 //!   file plus the storage-backend unit tests) is the CI invariant.
 //!
-//! Adding a new keyspace = add a `KS_*` const here, document it, done.
+//! Adding a new keyspace = add a `KS_*` const here, document it, list it in
+//! [`ALL_KEYSPACES`], done.
 
 // ---------------------------------------------------------------------------
 // Existing keyspaces (pre-rollout)
@@ -41,10 +42,15 @@ pub const KS_TIMESERIES: &str = "timeseries";
 /// `did-hosting-server` instances and `webvh-witness` services.
 pub const KS_REGISTRY: &str = "registry";
 
-/// `witnesses:<mnemonic>` — witness proofs the witness service has
-/// signed for hosted DIDs.
+/// `witness:<witness_id>` — witness identities (`WitnessRecord`), and
+/// `proof:<witness_id>:<did>:<version>` — the ledger of proofs each has
+/// signed (`ProofRecord`). Both owned by `webvh-witness`.
 pub const KS_WITNESSES: &str = "witnesses";
 
+/// `event:<id>` — append-only tamper events (`WatcherEvent`) recorded by
+/// `webvh-watcher` when a source offers rewritten history. Never pruned.
+pub const KS_WATCHER_EVENTS: &str = "watcher_events";
+
 /// `meta:<key>` — runner-internal state (e.g. `migration:applied:{id}`
 /// markers from the migration runner in `super::super::migrations`).
 /// Reserved for workspace-internal bookkeeping; not part of the wire
@@ -99,3 +105,49 @@ pub const KS_IDENTITY: &str = "identity";
 /// flight work. Receivers must remain idempotent because the
 /// delivery guarantee is at-least-once.
 pub const KS_OUTBOUND_QUEUE: &str = "outbox";
+
+/// `rec:<seq>` + `head` — the control plane's tamper-evident audit log.
+/// Every mutation appends one hash-chained, service-signed record; the
+/// `head` pointer names the latest so a truncated tail is detectable.
+/// Append-only: nothing in the workspace rewrites or prunes it.
+pub const KS_AUDIT: &str = "audit";
+
+/// `sub:<id>` + `queue:<sub_id>:<micros>:<uuid>` — outbound webhook
+/// subscriptions and their pending deliveries. The queue rows follow the
+/// `KS_OUTBOUND_QUEUE` layout (per-subscription FIFO, removed once the
+/// endpoint answers 2xx), so delivery is at-least-once here too.
+pub const KS_WEBHOOKS: &str = "webhooks";
+
+/// `key:<id>` — scoped API keys for machine clients of the control plane.
+/// Each row holds the SHA-256 of the key's secret, never the secret.
+pub const KS_API_KEYS: &str = "api_keys";
+
+/// `req:<id>` + `dec:<id>:<approver>` + `gate:<digest>` — multi-party
+/// approvals of gated admin actions. The request row and one row per
+/// approver's decision, so decisions recorded by different replicas never
+/// overwrite each other; the gate row maps an action to its open approval.
+/// Rows are swept once the approval can no longer be redeemed.
+pub const KS_APPROVALS: &str = "approvals";
+
+/// Every keyspace above. For tools that walk a whole store, such as the
+/// daemon's `store-migrate`, whose main store holds all of them.
+pub const ALL_KEYSPACES: &[&str] = &[
+    KS_DIDS,
+    KS_ACL,
+    KS_SESSIONS,
+    KS_STATS,
+    KS_TIMESERIES,
+    KS_REGISTRY,
+    KS_WITNESSES,
+    KS_WATCHER_EVENTS,
+    KS_META,
+    KS_DOMAINS,
+    KS_ASSIGNMENTS,
+    KS_PENDING_PURGES,
+    KS_IDENTITY,
+    KS_OUTBOUND_QUEUE,
+    KS_AUDIT,
+    KS_WEBHOOKS,
+    KS_API_KEYS,
+    KS_APPROVALS,
+];
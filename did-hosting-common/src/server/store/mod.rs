@@ -11,8 +11,9 @@ pub mod keyspaces;
 mod redis;
 
 pub use keyspaces::{
-    KS_ACL, KS_ASSIGNMENTS, KS_DIDS, KS_DOMAINS, KS_IDENTITY, KS_META, KS_OUTBOUND_QUEUE,
-    KS_PENDING_PURGES, KS_REGISTRY, KS_SESSIONS, KS_STATS, KS_TIMESERIES, KS_WITNESSES,
+    ALL_KEYSPACES, KS_ACL, KS_API_KEYS, KS_APPROVALS, KS_ASSIGNMENTS, KS_AUDIT, KS_DIDS,
+    KS_DOMAINS, KS_IDENTITY, KS_META, KS_OUTBOUND_QUEUE, KS_PENDING_PURGES, KS_REGISTRY,
+    KS_SESSIONS, KS_STATS, KS_TIMESERIES, KS_WATCHER_EVENTS, KS_WEBHOOKS, KS_WITNESSES,
 };
 
 use std::future::Future;
@@ -22,6 +23,14 @@ use std::sync::Arc;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 
+#[cfg(any(
+    feature = "store-fjall",
+    feature = "store-redis",
+    feature = "store-dynamodb",
+    feature = "store-firestore",
+    feature = "store-cosmosdb"
+))]
+use super::config::StoreBackend;
 use super::config::StoreConfig;
 use super::error::AppError;
 
@@ -74,6 +83,23 @@ pub trait KeyspaceOps: Send + Sync {
     fn contains_key(&self, key: Vec<u8>) -> BoxFuture<'_, Result<bool, AppError>>;
     fn prefix_iter_raw(&self, prefix: Vec<u8>) -> BoxFuture<'_, Result<Vec<RawKvPair>, AppError>>;
 
+    /// One page of a prefix scan: at most `limit` pairs whose key starts
+    /// with `prefix` and comes after `after`, the last key of the previous
+    /// page.
+    ///
+    /// Lets a caller walk a large keyspace without holding all of it. fjall
+    /// and Redis page in key order; DynamoDB, Firestore and Cosmos DB page
+    /// in their own cursor order, so callers must not rely on keys being
+    /// sorted. A page shorter than `limit` means the scan is done: backends
+    /// keep fetching until the page is full or the data runs out. Each
+    /// backend seeks to `after` rather than re-reading the earlier pages.
+    fn scan_page_raw(
+        &self,
+        prefix: Vec<u8>,
+        after: Option<Vec<u8>>,
+        limit: usize,
+    ) -> BoxFuture<'_, Result<Vec<RawKvPair>, AppError>>;
+
     /// Atomically read a key's value and remove it in one operation.
     ///
     /// Returns `Some(value)` if the key existed and was removed; `None` if
@@ -90,6 +116,26 @@ pub trait KeyspaceOps: Send + Sync {
     /// SQL transaction, etc.). Single-replica backends (fjall) wrap the
     /// non-atomic `get + remove` in a process-local mutex.
     fn take_raw_atomic(&self, key: Vec<u8>) -> BoxFuture<'_, Result<Option<Vec<u8>>, AppError>>;
+
+    /// Write `value` under `key` only if the key is absent, in one
+    /// operation.
+    ///
+    /// Returns `true` if this call wrote the value; `false` if the key
+    /// already existed, in which case it is left untouched. Among
+    /// concurrent callers for the same absent key exactly one observes
+    /// `true`. A plain `insert_raw` is not excluded and may still
+    /// overwrite.
+    ///
+    /// Backends are expected to use a native conditional write (Redis
+    /// `SET NX`, DynamoDB `PutItem` + `attribute_not_exists`). The
+    /// others serialise `contains + insert` behind the same process-local
+    /// mutex as [`Self::take_raw_atomic`], with the same single-replica
+    /// caveat.
+    fn insert_raw_if_absent(
+        &self,
+        key: Vec<u8>,
+        value: Vec<u8>,
+    ) -> BoxFuture<'_, Result<bool, AppError>>;
 }
 
 /// Atomic multi-key write batch identified by keyspace name.
@@ -245,6 +291,21 @@ impl KeyspaceHandle {
         self.inner.take_raw_atomic(key.into()).await
     }
 
+    /// Serialize `value` and write it under `key` unless the key already
+    /// exists. Returns whether this call wrote it.
+    ///
+    /// Backed by `KeyspaceOps::insert_raw_if_absent` — exactly one
+    /// concurrent caller wins an absent key, and an existing value is
+    /// never overwritten.
+    pub async fn insert_if_absent<V: Serialize>(
+        &self,
+        key: impl Into<Vec<u8>>,
+        value: &V,
+    ) -> Result<bool, AppError> {
+        let bytes = serde_json::to_vec(value)?;
+        self.inner.insert_raw_if_absent(key.into(), bytes).await
+    }
+
     pub async fn remove(&self, key: impl Into<Vec<u8>>) -> Result<(), AppError> {
         self.inner.remove(key.into()).await
     }
@@ -278,6 +339,17 @@ impl KeyspaceHandle {
         self.inner.prefix_iter_raw(prefix.into()).await
     }
 
+    /// One page of a prefix scan; see [`KeyspaceOps::scan_page_raw`]. Pass the last key of the previous
+    /// page as `after`.
+    pub async fn scan_page_raw(
+        &self,
+        prefix: impl Into<Vec<u8>>,
+        after: Option<Vec<u8>>,
+        limit: usize,
+    ) -> Result<Vec<RawKvPair>, AppError> {
+        self.inner.scan_page_raw(prefix.into(), after, limit).await
+    }
+
     /// Returns the approximate number of items in the keyspace.
     pub async fn approximate_len(&self) -> Result<usize, AppError> {
         Ok(self.prefix_iter_raw(b"").await?.len())
@@ -314,36 +386,25 @@ impl KeyspaceHandle {
 // Backend factory
 // ---------------------------------------------------------------------------
 
-#[allow(unused_variables)]
+/// Open the backend `config` resolves to ([`StoreConfig::resolved_backend`]).
+/// Several may be compiled in; `store-migrate` opens two at once.
 async fn create_backend(config: &StoreConfig) -> Result<Box<dyn StorageBackend>, AppError> {
-    #[cfg(feature = "store-fjall")]
-    {
-        return fjall::FjallBackend::open(config);
-    }
-
-    #[cfg(feature = "store-redis")]
-    {
-        return redis::RedisBackend::open(config).await;
+    match config.resolved_backend()? {
+        #[cfg(feature = "store-fjall")]
+        StoreBackend::Fjall => fjall::FjallBackend::open(config),
+        #[cfg(feature = "store-redis")]
+        StoreBackend::Redis => redis::RedisBackend::open(config).await,
+        #[cfg(feature = "store-dynamodb")]
+        StoreBackend::Dynamodb => dynamodb::DynamoDbBackend::open(config).await,
+        #[cfg(feature = "store-firestore")]
+        StoreBackend::Firestore => firestore::FirestoreBackend::open(config).await,
+        #[cfg(feature = "store-cosmosdb")]
+        StoreBackend::Cosmosdb => cosmosdb::CosmosDbBackend::open(config).await,
+        // `resolved_backend` only returns compiled backends.
+        #[allow(unreachable_patterns)]
+        other => Err(AppError::Config(format!(
+            "store backend '{}' is not compiled into this binary",
+            other.name()
+        ))),
     }
-
-    #[cfg(feature = "store-dynamodb")]
-    {
-        return dynamodb::DynamoDbBackend::open(config).await;
-    }
-
-    #[cfg(feature = "store-firestore")]
-    {
-        return firestore::FirestoreBackend::open(config).await;
-    }
-
-    #[cfg(feature = "store-cosmosdb")]
-    {
-        return cosmosdb::CosmosDbBackend::open(config).await;
-    }
-
-    // build.rs enforces exactly one feature, so this is unreachable
-    #[allow(unreachable_code)]
-    Err(AppError::Config(
-        "no storage backend compiled — enable a store-* feature".into(),
-    ))
 }
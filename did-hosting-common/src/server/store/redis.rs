@@ -1,6 +1,8 @@
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use redis::AsyncCommands;
+use tokio::sync::RwLock;
 use tracing::info;
 
 use crate::server::config::StoreConfig;
@@ -10,8 +12,26 @@ use super::{BatchOps, BoxFuture, KeyspaceOps, RawKvPair, StorageBackend};
 
 pub struct RedisBackend {
     conn: redis::aio::MultiplexedConnection,
+    indexed: Arc<RwLock<HashSet<String>>>,
 }
 
+/// Sorted set holding every key of a keyspace, all at score 0 so members
+/// order by their bytes. [`KeyspaceOps::scan_page_raw`] seeks in it with
+/// `ZRANGE … BYLEX` rather than `SCAN`ning the whole database. It lives
+/// outside the keyspace's `{name}:` namespace, so prefix scans never see
+/// it. Every write keeps it current in the same `MULTI` as the data.
+fn index_key(keyspace: &str) -> String {
+    format!("__keys:{keyspace}")
+}
+
+/// Set once a keyspace's index has been built from its existing keys.
+fn indexed_marker(keyspace: &str) -> String {
+    format!("__keys_indexed:{keyspace}")
+}
+
+/// Keys added to an index per `ZADD` while backfilling it.
+const BACKFILL_CHUNK: usize = 1000;
+
 impl RedisBackend {
     pub async fn open(config: &StoreConfig) -> Result<Box<dyn StorageBackend>, AppError> {
         let url = config
@@ -34,7 +54,10 @@ impl RedisBackend {
             .await
             .map_err(|e| AppError::Store(format!("redis ping: {e}")))?;
 
-        Ok(Box::new(Self { conn }))
+        Ok(Box::new(Self {
+            conn,
+            indexed: Arc::new(RwLock::new(HashSet::new())),
+        }))
     }
 }
 
@@ -45,6 +68,9 @@ impl StorageBackend for RedisBackend {
             Arc::new(RedisKeyspace {
                 conn: self.conn.clone(),
                 prefix: format!("{name}:"),
+                index: index_key(name),
+                marker: indexed_marker(name),
+                indexed: self.indexed.clone(),
             }),
         ))
     }
@@ -64,6 +90,9 @@ impl StorageBackend for RedisBackend {
 struct RedisKeyspace {
     conn: redis::aio::MultiplexedConnection,
     prefix: String,
+    index: String,
+    marker: String,
+    indexed: Arc<RwLock<HashSet<String>>>,
 }
 
 impl RedisKeyspace {
@@ -72,6 +101,58 @@ impl RedisKeyspace {
         fk.extend_from_slice(key);
         fk
     }
+
+    /// Build the key index from the keyspace's existing keys, once per
+    /// store. Keys written meanwhile are indexed by their own writes; a key
+    /// deleted meanwhile leaves a stale member, which
+    /// [`KeyspaceOps::scan_page_raw`] drops when it finds no value.
+    async fn ensure_indexed(&self) -> Result<(), AppError> {
+        if self.indexed.read().await.contains(&self.index) {
+            return Ok(());
+        }
+        let mut conn = self.conn.clone();
+        let built: bool = conn
+            .exists(&self.marker)
+            .await
+            .map_err(|e| AppError::Store(format!("redis EXISTS: {e}")))?;
+        if !built {
+            let mut pattern = self.prefix.as_bytes().to_vec();
+            pattern.extend_from_slice(b"*");
+            let keys: Vec<Vec<u8>> = {
+                let mut collected = Vec::new();
+                let mut iter: redis::AsyncIter<Vec<u8>> = conn
+                    .scan_match(&pattern)
+                    .await
+                    .map_err(|e| AppError::Store(format!("redis SCAN: {e}")))?;
+                while let Some(key) = iter.next_item().await {
+                    collected
+                        .push(key.map_err(|e| AppError::Store(format!("redis SCAN iter: {e}")))?);
+                }
+                collected
+            };
+            let prefix_len = self.prefix.len();
+            for chunk in keys.chunks(BACKFILL_CHUNK) {
+                let members: Vec<(i64, &[u8])> =
+                    chunk.iter().map(|k| (0, &k[prefix_len..])).collect();
+                conn.zadd_multiple::<_, _, _, ()>(&self.index, &members)
+                    .await
+                    .map_err(|e| AppError::Store(format!("redis ZADD: {e}")))?;
+            }
+            conn.set::<_, _, ()>(&self.marker, 1)
+                .await
+                .map_err(|e| AppError::Store(format!("redis SET: {e}")))?;
+            info!(index = %self.index, keys = keys.len(), "built redis key index");
+        }
+        self.indexed.write().await.insert(self.index.clone());
+        Ok(())
+    }
+}
+
+/// A `ZRANGE … BYLEX` bound: `[` includes `key`, `(` excludes it.
+fn lex_bound(inclusive: bool, key: &[u8]) -> Vec<u8> {
+    let mut bound = vec![if inclusive { b'[' } else { b'(' }];
+    bound.extend_from_slice(key);
+    bound
 }
 
 impl KeyspaceOps for RedisKeyspace {
@@ -79,7 +160,11 @@ impl KeyspaceOps for RedisKeyspace {
         Box::pin(async move {
             let fk = self.full_key(&key);
             let mut conn = self.conn.clone();
-            conn.set::<_, _, ()>(fk, value)
+            redis::pipe()
+                .atomic()
+                .set(fk, value)
+                .zadd(&self.index, key, 0)
+                .query_async::<()>(&mut conn)
                 .await
                 .map_err(|e| AppError::Store(format!("redis SET: {e}")))?;
             Ok(())
@@ -102,7 +187,11 @@ impl KeyspaceOps for RedisKeyspace {
         Box::pin(async move {
             let fk = self.full_key(&key);
             let mut conn = self.conn.clone();
-            conn.del::<_, ()>(fk)
+            redis::pipe()
+                .atomic()
+                .del(fk)
+                .zrem(&self.index, key)
+                .query_async::<()>(&mut conn)
                 .await
                 .map_err(|e| AppError::Store(format!("redis DEL: {e}")))?;
             Ok(())
@@ -129,8 +218,11 @@ impl KeyspaceOps for RedisKeyspace {
             // a non-nil result for any given key.
             let fk = self.full_key(&key);
             let mut conn = self.conn.clone();
-            let result: Option<Vec<u8>> = redis::cmd("GETDEL")
+            let (result, _): (Option<Vec<u8>>, i64) = redis::pipe()
+                .atomic()
+                .cmd("GETDEL")
                 .arg(fk)
+                .zrem(&self.index, key)
                 .query_async(&mut conn)
                 .await
                 .map_err(|e| AppError::Store(format!("redis GETDEL: {e}")))?;
@@ -138,6 +230,31 @@ impl KeyspaceOps for RedisKeyspace {
         })
     }
 
+    fn insert_raw_if_absent(
+        &self,
+        key: Vec<u8>,
+        value: Vec<u8>,
+    ) -> BoxFuture<'_, Result<bool, AppError>> {
+        Box::pin(async move {
+            // SET NX writes only when the key is absent and returns nil
+            // otherwise — cross-replica safe. The index ZADD is idempotent,
+            // so it can run either way.
+            let fk = self.full_key(&key);
+            let mut conn = self.conn.clone();
+            let (written, _): (Option<String>, i64) = redis::pipe()
+                .atomic()
+                .cmd("SET")
+                .arg(fk)
+                .arg(value)
+                .arg("NX")
+                .zadd(&self.index, key, 0)
+                .query_async(&mut conn)
+                .await
+                .map_err(|e| AppError::Store(format!("redis SET NX: {e}")))?;
+            Ok(written.is_some())
+        })
+    }
+
     fn prefix_iter_raw(&self, prefix: Vec<u8>) -> BoxFuture<'_, Result<Vec<RawKvPair>, AppError>> {
         Box::pin(async move {
             let mut pattern = self.full_key(&prefix);
@@ -183,11 +300,84 @@ impl KeyspaceOps for RedisKeyspace {
             Ok(results)
         })
     }
+
+    fn scan_page_raw(
+        &self,
+        prefix: Vec<u8>,
+        after: Option<Vec<u8>>,
+        limit: usize,
+    ) -> BoxFuture<'_, Result<Vec<RawKvPair>, AppError>> {
+        Box::pin(async move {
+            self.ensure_indexed().await?;
+            let mut conn = self.conn.clone();
+            let mut results = Vec::with_capacity(limit.min(1024));
+            let mut after = after;
+            while results.len() < limit {
+                let min = match &after {
+                    Some(after) => lex_bound(false, after),
+                    None if prefix.is_empty() => b"-".to_vec(),
+                    None => lex_bound(true, &prefix),
+                };
+                let want = limit - results.len();
+                let members: Vec<Vec<u8>> = redis::cmd("ZRANGE")
+                    .arg(&self.index)
+                    .arg(min)
+                    .arg("+")
+                    .arg("BYLEX")
+                    .arg("LIMIT")
+                    .arg(0)
+                    .arg(want)
+                    .query_async(&mut conn)
+                    .await
+                    .map_err(|e| AppError::Store(format!("redis ZRANGE: {e}")))?;
+                let exhausted = members.len() < want;
+                let in_prefix: Vec<Vec<u8>> = members
+                    .into_iter()
+                    .take_while(|m| m.starts_with(&prefix))
+                    .collect();
+                let past_prefix = in_prefix.len() < want && !exhausted;
+                let Some(last) = in_prefix.last().cloned() else {
+                    break;
+                };
+                let full_keys: Vec<Vec<u8>> = in_prefix.iter().map(|k| self.full_key(k)).collect();
+                let values: Vec<Option<Vec<u8>>> = conn
+                    .mget(&full_keys)
+                    .await
+                    .map_err(|e| AppError::Store(format!("redis MGET: {e}")))?;
+                let mut stale = Vec::new();
+                for (key, value) in in_prefix.into_iter().zip(values) {
+                    match value {
+                        Some(value) => results.push((key, value)),
+                        None => stale.push(key),
+                    }
+                }
+                if !stale.is_empty() {
+                    conn.zrem::<_, _, ()>(&self.index, &stale)
+                        .await
+                        .map_err(|e| AppError::Store(format!("redis ZREM: {e}")))?;
+                }
+                if exhausted || past_prefix {
+                    break;
+                }
+                after = Some(last);
+            }
+            Ok(results)
+        })
+    }
 }
 
 enum RedisBatchOp {
-    Insert { full_key: Vec<u8>, value: Vec<u8> },
-    Remove { full_key: Vec<u8> },
+    Insert {
+        full_key: Vec<u8>,
+        index: String,
+        key: Vec<u8>,
+        value: Vec<u8>,
+    },
+    Remove {
+        full_key: Vec<u8>,
+        index: String,
+        key: Vec<u8>,
+    },
 }
 
 struct RedisBatch {
@@ -199,13 +389,22 @@ impl BatchOps for RedisBatch {
     fn insert_raw(&mut self, keyspace: &str, key: Vec<u8>, value: Vec<u8>) {
         let mut full_key = format!("{keyspace}:").into_bytes();
         full_key.extend_from_slice(&key);
-        self.ops.push(RedisBatchOp::Insert { full_key, value });
+        self.ops.push(RedisBatchOp::Insert {
+            full_key,
+            index: index_key(keyspace),
+            key,
+            value,
+        });
     }
 
     fn remove(&mut self, keyspace: &str, key: Vec<u8>) {
         let mut full_key = format!("{keyspace}:").into_bytes();
         full_key.extend_from_slice(&key);
-        self.ops.push(RedisBatchOp::Remove { full_key });
+        self.ops.push(RedisBatchOp::Remove {
+            full_key,
+            index: index_key(keyspace),
+            key,
+        });
     }
 
     fn commit(self: Box<Self>) -> BoxFuture<'static, Result<(), AppError>> {
@@ -217,11 +416,22 @@ impl BatchOps for RedisBatch {
 
             for op in &self.ops {
                 match op {
-                    RedisBatchOp::Insert { full_key, value } => {
+                    RedisBatchOp::Insert {
+                        full_key,
+                        index,
+                        key,
+                        value,
+                    } => {
                         pipe.set(full_key.as_slice(), value.as_slice());
+                        pipe.zadd(index, key.as_slice(), 0);
                     }
-                    RedisBatchOp::Remove { full_key } => {
+                    RedisBatchOp::Remove {
+                        full_key,
+                        index,
+                        key,
+                    } => {
                         pipe.del(full_key.as_slice());
+                        pipe.zrem(index, key.as_slice());
                     }
                 }
             }
@@ -97,6 +97,16 @@ impl std::fmt::Display for TrustTask {
     }
 }
 
+/// The Trust Task(s) the route serving a request is registered under.
+///
+/// [`TrustTaskRouter`] puts this in the request's extensions on every
+/// registered route, header or not, so an authenticator can scope a
+/// credential to tasks without knowing the route table (see
+/// `auth::api_key`). Absent on exempt routes. A did-hosting addition: the
+/// VTI copy of this module does not have it.
+#[derive(Debug, Clone)]
+pub struct RouteTrustTasks(pub Vec<TrustTask>);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -28,7 +28,7 @@ use std::sync::Arc;
 use axum::Router;
 use axum::routing::MethodRouter;
 
-use super::TrustTask;
+use super::{RouteTrustTasks, TrustTask};
 
 /// Builder that wraps an Axum [`Router`] and enforces Trust-Task
 /// header validation on each registered route.
@@ -74,8 +74,9 @@ where
         // closure must be `Clone + Send + Sync + 'static` per Axum's
         // `from_fn` bound, which `Arc<TrustTask>` satisfies.
         let task = Arc::new(task);
-        let layered = method_router.layer(axum::middleware::from_fn(move |request, next| {
+        let layered = method_router.layer(axum::middleware::from_fn(move |mut request, next| {
             let task = task.clone();
+            tag_route(&mut request, std::slice::from_ref(&*task));
             async move { super::extractor::validate_header(&task, request, next).await }
         }));
         self.inner = self.inner.route(path, layered);
@@ -104,8 +105,9 @@ where
         task: TrustTask,
     ) -> Self {
         let task = Arc::new(task);
-        let layered = method_router.layer(axum::middleware::from_fn(move |request, next| {
+        let layered = method_router.layer(axum::middleware::from_fn(move |mut request, next| {
             let task = task.clone();
+            tag_route(&mut request, std::slice::from_ref(&*task));
             async move { super::extractor::validate_header_permissive(&task, request, next).await }
         }));
         self.inner = self.inner.route(path, layered);
@@ -137,8 +139,9 @@ where
         accepted.push(primary);
         accepted.extend(deprecated);
         let accepted = Arc::new(accepted);
-        let layered = method_router.layer(axum::middleware::from_fn(move |request, next| {
+        let layered = method_router.layer(axum::middleware::from_fn(move |mut request, next| {
             let accepted = accepted.clone();
+            tag_route(&mut request, &accepted);
             async move {
                 super::extractor::validate_header_permissive_any(&accepted, request, next).await
             }
@@ -165,6 +168,13 @@ where
     }
 }
 
+/// Record the route's task(s) on the request; see [`RouteTrustTasks`].
+fn tag_route(request: &mut axum::extract::Request, tasks: &[TrustTask]) {
+    request
+        .extensions_mut()
+        .insert(RouteTrustTasks(tasks.to_vec()));
+}
+
 impl<S> From<TrustTaskRouter<S>> for Router<S>
 where
     S: Clone + Send + Sync + 'static,
@@ -305,6 +315,44 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::OK);
     }
 
+    /// Registered routes tag the request with their task, header or not;
+    /// exempt routes do not.
+    #[tokio::test]
+    async fn registered_routes_tag_the_request_with_their_task() {
+        async fn tasks(route: Option<axum::Extension<RouteTrustTasks>>) -> String {
+            route
+                .map(|r| {
+                    r.0.0
+                        .iter()
+                        .map(|t| t.as_str())
+                        .collect::<Vec<_>>()
+                        .join(",")
+                })
+                .unwrap_or_default()
+        }
+        let claim = TrustTask::new("https://trusttasks.org/openvtc/vtc/install/claim/1.0").unwrap();
+        let app = TrustTaskRouter::new()
+            .route_with_task_permissive("/claim", get(tasks), claim)
+            .route_exempt("/health", get(tasks))
+            .into_router();
+
+        for (uri, expected) in [
+            (
+                "/claim",
+                "https://trusttasks.org/openvtc/vtc/install/claim/1.0",
+            ),
+            ("/health", ""),
+        ] {
+            let resp = app
+                .clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let body = resp.into_body().collect().await.unwrap().to_bytes();
+            assert_eq!(body, expected.as_bytes(), "{uri}");
+        }
+    }
+
     // ---- permissive variant (T8b) ----
 
     fn make_permissive_router() -> Router {
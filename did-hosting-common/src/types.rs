@@ -258,6 +258,14 @@ pub struct DidRegisterRequest {
     pub did_data: Option<serde_json::Value>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub domain: Option<String>,
+    /// did:webs only: the `did.json` served next to the KEL in
+    /// `did_data`. Checked against the KEL before anything is stored.
+    #[serde(
+        default,
+        alias = "didDocument",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub did_document: Option<serde_json::Value>,
 
     /// Legacy pre-T26 field — webvh-only. Use `did_data` + `method`
     /// for new clients; this field is accepted unchanged for v0.7
@@ -282,8 +290,9 @@ impl DidRegisterRequest {
     ///   the declared `method` mismatches the method derived from
     ///   `did_data.id`.
     /// - `Ok((method, payload))` where `method` is one of the known
-    ///   strings (`"webvh"`, `"web"`) and `payload` is the bytes the
-    ///   storage layer will persist (the jsonl text for webvh, the
+    ///   strings (`"webvh"`, `"web"`, `"webs"`, `"webplus"`) and
+    ///   `payload` is the bytes the storage layer will persist (the
+    ///   jsonl text for webvh and webplus, the KEL for webs, the
     ///   did.json bytes for web).
     pub fn resolve(&self) -> Result<(String, Vec<u8>), String> {
         if self.did_data.is_some() && self.did_log.is_some() {
@@ -331,10 +340,11 @@ impl DidRegisterRequest {
         };
 
         let bytes = match method.as_str() {
-            "webvh" => {
-                // webvh payload is jsonl text. Accept either a JSON
-                // string (preferred) or an array of objects (one per
-                // log entry — serialise to jsonl).
+            "webvh" | "webplus" => {
+                // webvh and webplus payloads are jsonl text (the log or
+                // the microledger). Accept either a JSON string
+                // (preferred) or an array of objects (one per line —
+                // serialise to jsonl).
                 if let Some(s) = did_data.as_str() {
                     s.as_bytes().to_vec()
                 } else if let Some(arr) = did_data.as_array() {
@@ -349,11 +359,16 @@ impl DidRegisterRequest {
                     }
                     buf.into_bytes()
                 } else {
-                    return Err(
-                        "webvh `did_data` must be a jsonl string or an array of log entries".into(),
-                    );
+                    return Err(format!(
+                        "{method} `did_data` must be a jsonl string or an array of log entries"
+                    ));
                 }
             }
+            "webs" => match did_data.as_str() {
+                // The KERI event log, as CESR text.
+                Some(s) => s.as_bytes().to_vec(),
+                None => return Err("webs `did_data` must be the KEL as a CESR string".into()),
+            },
             "web" => {
                 if did_data.is_object() {
                     serde_json::to_vec(did_data).map_err(|e| e.to_string())?
@@ -363,7 +378,7 @@ impl DidRegisterRequest {
             }
             other => {
                 return Err(format!(
-                    "unknown or unsupported method `{other}`; compiled-in methods: webvh, web",
+                    "unknown or unsupported method `{other}`; known methods: webvh, web, webs, webplus",
                 ));
             }
         };
@@ -501,6 +516,38 @@ mod did_register_request_tests {
         assert_eq!(text, "{\"v\":1}\n{\"v\":2}");
     }
 
+    #[test]
+    fn did_data_webplus_array_serialises_to_jsonl() {
+        let r = DidRegisterRequest {
+            method: Some("webplus".into()),
+            did_data: Some(json!([{"selfHash": "a"}, {"selfHash": "b"}])),
+            ..req()
+        };
+        let (method, payload) = r.resolve().unwrap();
+        assert_eq!(method, "webplus");
+        assert_eq!(
+            String::from_utf8(payload).unwrap(),
+            "{\"selfHash\":\"a\"}\n{\"selfHash\":\"b\"}"
+        );
+    }
+
+    #[test]
+    fn did_data_webs_must_be_a_cesr_string() {
+        let r = DidRegisterRequest {
+            method: Some("webs".into()),
+            did_data: Some(json!("{\"v\":\"KERI10JSON\"}")),
+            ..req()
+        };
+        let (method, _) = r.resolve().unwrap();
+        assert_eq!(method, "webs");
+        let r = DidRegisterRequest {
+            method: Some("webs".into()),
+            did_data: Some(json!([{"v": "KERI10JSON"}])),
+            ..req()
+        };
+        assert!(r.resolve().is_err());
+    }
+
     #[test]
     fn did_data_web_object_serialises() {
         let r = DidRegisterRequest {
@@ -608,6 +655,10 @@ pub struct DidListEntry {
     /// is every slot on a deployment that has never used the feature.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub agent_names: Vec<crate::did_ops::AgentNameEntry>,
+    /// When the DID was deactivated, straight off
+    /// `DidRecord::deactivated_at`. Omitted for live DIDs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deactivated_at: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -679,12 +730,21 @@ pub struct WitnessListResponse {
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SignProofRequest {
-    pub version_id: String,
+    /// The log entry to witness — one did.jsonl line.
+    pub log_entry: String,
+    /// Every entry before `log_entry`, from genesis. `None` for genesis.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_log: Option<String>,
+    /// Optional cross-check against `log_entry`'s `versionId`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SignProofResponse {
+    // The witness answers in snake_case.
+    #[serde(alias = "version_id")]
     pub version_id: String,
     pub proof: serde_json::Value,
 }
@@ -708,6 +768,73 @@ pub struct SyncDidRequest {
     pub source_url: String,
     pub updated_at: u64,
     pub disabled: bool,
+    /// Entries the control plane rolled back off the end of `log_content`,
+    /// in log order. A watcher holding `log_content` plus exactly these
+    /// accepts the shorter log as a rollback instead of a fork.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rolled_back: Vec<String>,
+    /// Signature vouching for `rolled_back`. Watchers accept the shorter
+    /// log only with a proof from a key they trust; without one it is a
+    /// fork.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollback_proof: Option<RollbackProof>,
+}
+
+/// A signed statement that a DID's log was rolled back: which entries
+/// came off the end, and which entry is the head now. Made by the service
+/// that rolled the log back (see `server::rollback_proof`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RollbackProof {
+    /// DID whose log was rolled back.
+    pub did: String,
+    /// SHA-256 (hex) of each dropped entry, in log order.
+    pub dropped: Vec<String>,
+    /// SHA-256 (hex) of the last entry left.
+    pub head: String,
+    /// Ed25519 multikey (`z6Mk…`) of the signer.
+    pub signer: String,
+    /// Ed25519 signature, base58btc multibase.
+    pub signature: String,
+}
+
+/// One DID in a server's sync listing (`GET /api/sync/dids`). Pulled by
+/// webvh-watcher reconciliation to find DIDs it is missing or behind on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncDidSummary {
+    pub mnemonic: String,
+    pub did_id: Option<String>,
+    pub updated_at: u64,
+    pub disabled: bool,
+    /// [`sync_head`] of the log and witness file a fetch would return.
+    /// `None` from servers that predate it; watchers then fall back to
+    /// `updated_at`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head: Option<String>,
+}
+
+/// Fingerprint of a DID's synced content: a SHA-256 over the log and the
+/// witness file. Unlike `updated_at` it changes with every write, however
+/// close together, and with witness-only updates.
+#[cfg(feature = "server-core")]
+pub fn sync_head(log: &[u8], witness: Option<&[u8]>) -> String {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    // Length-prefix the log so no split of the same bytes collides.
+    hasher.update((log.len() as u64).to_be_bytes());
+    hasher.update(log);
+    if let Some(witness) = witness {
+        hasher.update(witness);
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Response of `GET /api/sync/dids` on did-hosting-server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncDidListResponse {
+    /// The server's public base URL — the `source_url` its pushes carry.
+    pub source_url: String,
+    pub dids: Vec<SyncDidSummary>,
 }
 
 /// Pushed from did-hosting-server to webvh-watcher when a DID is deleted.
@@ -755,6 +882,7 @@ mod tests {
             domain: None,
             services: None,
             agent_names: Vec::new(),
+            deactivated_at: None,
         };
         let json = serde_json::to_string(&entry).unwrap();
         assert!(json.contains("\"createdAt\""));
@@ -767,6 +895,8 @@ mod tests {
         assert!(!json.contains("\"version_count\""));
         assert!(!json.contains("\"did_id\""));
         assert!(!json.contains("\"total_resolves\""));
+        // Live DIDs don't carry the field at all.
+        assert!(!json.contains("deactivatedAt"));
     }
 
     #[test]
@@ -784,6 +914,7 @@ mod tests {
             domain: None,
             services: None,
             agent_names: Vec::new(),
+            deactivated_at: None,
         };
         let json = serde_json::to_string(&entry).unwrap();
         assert!(json.contains("\"didId\":null"));
@@ -812,10 +943,13 @@ mod tests {
                 enabled: true,
                 created_at: 5,
             }],
+            deactivated_at: Some(3000),
         };
         let json = serde_json::to_string(&entry).unwrap();
+        assert!(json.contains("\"deactivatedAt\":3000"));
         let back: DidListEntry = serde_json::from_str(&json).unwrap();
         assert_eq!(back.mnemonic, "test");
+        assert_eq!(back.deactivated_at, Some(3000));
         assert_eq!(back.version_count, 3);
         assert_eq!(back.did_id, Some("did:webvh:abc:host:path".to_string()));
         assert_eq!(back.total_resolves, 99);
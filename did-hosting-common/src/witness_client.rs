@@ -102,16 +102,26 @@ impl WitnessClient {
     // Witness API
     // -------------------------------------------------------------------
 
-    /// Request a witness proof for a version ID.
+    /// Request a witness proof for the last entry of `did_log`.
+    ///
+    /// The witness verifies the whole log before signing, so `did_log`
+    /// must run from genesis up to and including the entry to witness.
     pub async fn request_proof(
         &self,
         witness_id: &str,
-        version_id: &str,
+        did_log: &str,
     ) -> Result<SignProofResponse> {
+        let did_log = did_log.trim_end();
+        let (previous_log, log_entry) = match did_log.rsplit_once('\n') {
+            Some((previous, entry)) => (Some(previous.to_string()), entry),
+            None => (None, did_log),
+        };
         let resp = self
             .auth_post(&format!("/api/proof/{witness_id}"))?
             .json(&SignProofRequest {
-                version_id: version_id.to_string(),
+                log_entry: log_entry.to_string(),
+                previous_log,
+                version_id: None,
             })
             .send()
             .await?;
@@ -16,11 +16,8 @@ fn main() {
             "cargo:warning=No storage backend feature enabled! Enable one of: store-fjall, store-redis, store-dynamodb, store-firestore, store-cosmosdb"
         );
     }
-    if enabled_count > 1 {
-        println!(
-            "cargo:warning=Multiple storage backend features enabled — only one will be used at runtime."
-        );
-    }
+    // Several backends may be compiled in (for `store-migrate`);
+    // `[store] backend` picks one at runtime.
 
     // ---- Secret store feature-gate validation ----
     let secret_features = [
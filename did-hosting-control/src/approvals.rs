@@ -0,0 +1,334 @@
+//! Multi-party approvals, kept in the store.
+//!
+//! A consent request is an [`Approval`] row plus one row per approver's
+//! decision in [`KS_APPROVALS`]. Nothing is held in process memory, so an
+//! approval survives a restart and a dropped client connection, and a
+//! decision counts whichever replica's DIDComm listener received it.
+//!
+//! Gated actions do not wait for their approvers: [`crate::consent::require`]
+//! opens an approval, answers `202` with its id, and redeems it when the
+//! requester repeats the action after the threshold is met. A granted
+//! approval can be redeemed once, for [`REDEEM_WINDOW_SECS`] after its
+//! request window closes; [`sweep_expired`] removes it after that.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::consent::ConsentOutcome;
+use crate::error::AppError;
+use crate::store::{KeyspaceHandle, Store};
+use did_hosting_common::server::store::KS_APPROVALS;
+
+/// How long a granted approval stays redeemable after its request window
+/// closes.
+pub const REDEEM_WINDOW_SECS: u64 = 600;
+
+/// A consent request sent to one or more approvers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Approval {
+    /// The `challenge` every request document carries.
+    pub id: String,
+    /// The gated action this approval is for ([`crate::consent`]'s gate
+    /// digest). `None` for a prompt sent with `POST /task-consent/request`,
+    /// which authorizes nothing here.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gate: Option<String>,
+    pub requester: String,
+    /// The text the approvers were shown.
+    pub note: String,
+    /// The DIDs the request was addressed to. Only their decisions count.
+    pub approvers: Vec<String>,
+    /// The salted `payloadDigest` every decision must echo.
+    pub expected_digest: String,
+    pub min_approvals: usize,
+    pub created_at: u64,
+    /// Decisions arriving after this are refused.
+    pub expires_at: u64,
+}
+
+impl Approval {
+    /// Whether a granted approval may still be redeemed, and how long its
+    /// rows are kept.
+    pub fn redeemable(&self, now: u64) -> bool {
+        now <= self.expires_at.saturating_add(REDEEM_WINDOW_SECS)
+    }
+}
+
+/// One approver's answer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Decision {
+    approved: bool,
+    /// Microseconds, so the granted decisions keep their arrival order.
+    at_micros: u64,
+    /// The signed `task-consent/decision/0.1` document.
+    document: Value,
+}
+
+fn request_key(id: &str) -> String {
+    format!("req:{id}")
+}
+
+fn decision_prefix(id: &str) -> String {
+    format!("dec:{id}:")
+}
+
+fn gate_key(gate: &str) -> String {
+    format!("gate:{gate}")
+}
+
+pub fn keyspace(store: &Store) -> Result<KeyspaceHandle, AppError> {
+    store.keyspace(KS_APPROVALS)
+}
+
+/// Store a new approval, and point its gate at it.
+pub async fn open(store: &Store, approval: &Approval) -> Result<(), AppError> {
+    let ks = &keyspace(store)?;
+    let mut batch = store.batch();
+    batch.insert(ks, request_key(&approval.id), approval)?;
+    if let Some(gate) = &approval.gate {
+        batch.insert(ks, gate_key(gate), &approval.id)?;
+    }
+    batch.commit().await
+}
+
+pub async fn get(store: &Store, id: &str) -> Result<Option<Approval>, AppError> {
+    let ks = &keyspace(store)?;
+    ks.get(request_key(id)).await
+}
+
+/// The approval currently open for a gated action.
+pub async fn for_gate(store: &Store, gate: &str) -> Result<Option<Approval>, AppError> {
+    let ks = &keyspace(store)?;
+    match ks.get::<String>(gate_key(gate)).await? {
+        Some(id) => get(store, &id).await,
+        None => Ok(None),
+    }
+}
+
+/// Record `approver`'s decision. A repeat from the same approver replaces
+/// their earlier one, so it counts once.
+pub async fn record_decision(
+    store: &Store,
+    id: &str,
+    approver: &str,
+    approved: bool,
+    document: Value,
+) -> Result<(), AppError> {
+    let ks = &keyspace(store)?;
+    let decision = Decision {
+        approved,
+        at_micros: u64::try_from(chrono::Utc::now().timestamp_micros()).unwrap_or_default(),
+        document,
+    };
+    ks.insert(format!("{}{approver}", decision_prefix(id)), &decision)
+        .await
+}
+
+/// Where `approval` stands at `now`. A deny ends it; otherwise it is
+/// granted once `min_approvals` approvers have approved, with their signed
+/// decisions in arrival order.
+pub async fn status(
+    store: &Store,
+    approval: &Approval,
+    now: u64,
+) -> Result<ConsentOutcome, AppError> {
+    let ks = &keyspace(store)?;
+    let mut decisions = Vec::new();
+    for (key, value) in ks.prefix_iter_raw(decision_prefix(&approval.id)).await? {
+        let approver = String::from_utf8_lossy(&key[decision_prefix(&approval.id).len()..]);
+        // Only addressed approvers are ever recorded; re-checked so a stray
+        // row cannot count.
+        if !approval.approvers.iter().any(|a| *a == approver) {
+            continue;
+        }
+        let decision: Decision = serde_json::from_slice(&value)?;
+        if !decision.approved {
+            return Ok(ConsentOutcome::Denied {
+                by: approver.into_owned(),
+            });
+        }
+        decisions.push(decision);
+    }
+    let approvals = decisions.len();
+    if approvals >= approval.min_approvals {
+        decisions.sort_by_key(|d| d.at_micros);
+        return Ok(ConsentOutcome::Granted(
+            decisions.into_iter().map(|d| d.document).collect(),
+        ));
+    }
+    if now > approval.expires_at {
+        Ok(ConsentOutcome::Expired { approvals })
+    } else {
+        Ok(ConsentOutcome::Pending { approvals })
+    }
+}
+
+/// Spend a granted approval. `false` when another request already did.
+pub async fn redeem(store: &Store, approval: &Approval) -> Result<bool, AppError> {
+    let ks = &keyspace(store)?;
+    if ks.take_raw(request_key(&approval.id)).await?.is_none() {
+        return Ok(false);
+    }
+    remove(store, approval).await?;
+    Ok(true)
+}
+
+/// Delete an approval, its decisions, and its gate pointer if it still
+/// names this approval.
+pub async fn remove(store: &Store, approval: &Approval) -> Result<(), AppError> {
+    let ks = &keyspace(store)?;
+    let mut batch = store.batch();
+    batch.remove(ks, request_key(&approval.id));
+    for (key, _) in ks.prefix_iter_raw(decision_prefix(&approval.id)).await? {
+        batch.remove(ks, key);
+    }
+    if let Some(gate) = &approval.gate
+        && ks.get::<String>(gate_key(gate)).await?.as_deref() == Some(approval.id.as_str())
+    {
+        batch.remove(ks, gate_key(gate));
+    }
+    batch.commit().await
+}
+
+/// Delete approvals that can no longer be redeemed, and decisions left
+/// behind by an approval removed while they were being recorded. Returns
+/// the number of approvals removed.
+pub async fn sweep_expired(store: &Store, now: u64) -> Result<u64, AppError> {
+    let ks = keyspace(store)?;
+    let mut removed = 0;
+    for (_, value) in ks.prefix_iter_raw("req:").await? {
+        let approval: Approval = serde_json::from_slice(&value)?;
+        if !approval.redeemable(now) {
+            remove(store, &approval).await?;
+            removed += 1;
+        }
+    }
+    for (key, _) in ks.prefix_iter_raw("dec:").await? {
+        let id = String::from_utf8_lossy(&key["dec:".len()..])
+            .split(':')
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        if !ks.contains_key(request_key(&id)).await? {
+            ks.remove(key).await?;
+        }
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use did_hosting_common::server::config::StoreConfig;
+    use serde_json::json;
+
+    async fn temp_store() -> (Store, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Store::open(&StoreConfig {
+            data_dir: dir.path().to_path_buf(),
+            ..StoreConfig::default()
+        })
+        .await
+        .unwrap();
+        (store, dir)
+    }
+
+    fn approval(gate: Option<&str>) -> Approval {
+        Approval {
+            id: "c1".into(),
+            gate: gate.map(str::to_string),
+            requester: "did:example:admin".into(),
+            note: "note".into(),
+            approvers: vec!["did:example:a".into(), "did:example:b".into()],
+            expected_digest: "zDigest".into(),
+            min_approvals: 2,
+            created_at: 100,
+            expires_at: 400,
+        }
+    }
+
+    #[tokio::test]
+    async fn status_follows_the_recorded_decisions() {
+        let (store, _dir) = temp_store().await;
+        let approval = approval(Some("g1"));
+        open(&store, &approval).await.unwrap();
+        assert_eq!(for_gate(&store, "g1").await.unwrap().unwrap().id, "c1");
+
+        let pending = status(&store, &approval, 200).await.unwrap();
+        assert!(matches!(pending, ConsentOutcome::Pending { approvals: 0 }));
+        assert!(matches!(
+            status(&store, &approval, 401).await.unwrap(),
+            ConsentOutcome::Expired { approvals: 0 }
+        ));
+
+        record_decision(&store, "c1", "did:example:b", true, json!({ "n": "b" }))
+            .await
+            .unwrap();
+        record_decision(&store, "c1", "did:example:b", true, json!({ "n": "b" }))
+            .await
+            .unwrap();
+        assert!(matches!(
+            status(&store, &approval, 200).await.unwrap(),
+            ConsentOutcome::Pending { approvals: 1 }
+        ));
+        // A decision from outside the approver set never counts.
+        record_decision(&store, "c1", "did:example:x", true, json!({}))
+            .await
+            .unwrap();
+        record_decision(&store, "c1", "did:example:a", true, json!({ "n": "a" }))
+            .await
+            .unwrap();
+        match status(&store, &approval, 200).await.unwrap() {
+            ConsentOutcome::Granted(docs) => {
+                assert_eq!(docs, vec![json!({ "n": "b" }), json!({ "n": "a" })]);
+            }
+            other => panic!("expected a grant, got {other:?}"),
+        }
+
+        record_decision(&store, "c1", "did:example:a", false, json!({}))
+            .await
+            .unwrap();
+        assert!(matches!(
+            status(&store, &approval, 200).await.unwrap(),
+            ConsentOutcome::Denied { by } if by == "did:example:a"
+        ));
+    }
+
+    #[tokio::test]
+    async fn an_approval_is_redeemed_once_and_swept_after_its_window() {
+        let (store, _dir) = temp_store().await;
+        let approval = approval(Some("g1"));
+        open(&store, &approval).await.unwrap();
+        record_decision(&store, "c1", "did:example:a", true, json!({}))
+            .await
+            .unwrap();
+
+        assert!(redeem(&store, &approval).await.unwrap());
+        assert!(!redeem(&store, &approval).await.unwrap(), "spent");
+        assert!(for_gate(&store, "g1").await.unwrap().is_none());
+        assert!(
+            keyspace(&store)
+                .unwrap()
+                .prefix_iter_raw("dec:")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        open(&store, &approval).await.unwrap();
+        assert_eq!(
+            sweep_expired(&store, 400 + REDEEM_WINDOW_SECS)
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            sweep_expired(&store, 401 + REDEEM_WINDOW_SECS)
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(get(&store, "c1").await.unwrap().is_none());
+        assert!(for_gate(&store, "g1").await.unwrap().is_none());
+    }
+}
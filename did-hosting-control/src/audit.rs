@@ -0,0 +1,1167 @@
+//! Tamper-evident audit log of control-plane mutations.
+//!
+//! Every mutation — ACL changes, DID create / register / publish / delete /
+//! rollback / owner change / disable, agent-name ops, domain lifecycle,
+//! identity rotation and retirement, webhook subscriptions — appends one
+//! [`AuditRecord`] to the [`KS_AUDIT`] keyspace. A record names who acted
+//! (DID, role, `acr` / `amr`), under which Trust Task, on what, and digests
+//! of the subject's state before and after. A mutation a consent policy
+//! gated also carries the approvers' signed decisions (see
+//! [`crate::consent`]), inside the hash like every other field.
+//!
+//! ## Chain
+//!
+//! Records are numbered from 0. Each carries the previous record's `hash`
+//! in `prev_hash` (64 zeros for the first), and its own `hash` is SHA-256
+//! over a domain tag plus the JCS form of every other field except
+//! `signature`. Editing, dropping or reordering any record breaks every
+//! hash after it. The `head` key names the latest record, so truncating
+//! the tail is caught too when verifying against the store.
+//!
+//! ## Signatures
+//!
+//! With a service identity loaded, `hash` is signed Ed25519 with the
+//! control DID's current assertion key (see
+//! [`crate::signing::control_assertion_secret`]); the record carries the
+//! kid and public key. Verification pins the accepted keys out of band —
+//! `did-hosting-control verify-audit --public-key` — so a forger who can
+//! rewrite the store still cannot re-sign it. Without an identity (first
+//! boot, tests) records are appended unsigned rather than lost.
+//!
+//! ## Appending
+//!
+//! A mutation does not write its record directly: [`stage`] adds it to the
+//! mutation's own write batch as a `staged:` row, so a committed mutation
+//! always has its record and a failed one never does. [`flush`] — called
+//! once the batch commits, and by [`run_audit_loop`] for anything it
+//! missed — gives staged rows their place in the chain.
+//!
+//! The store has no compare-and-swap, so the chain is serialised with a
+//! baton: the `next` row holds the next `seq` and the hash it links to,
+//! and a writer must [`KeyspaceHandle::take`] it before appending. Only one
+//! caller gets it — across replicas on Redis and DynamoDB, within one
+//! process on the other backends, whose `take` is a local mutex. A baton
+//! lost with a writer that died holding it is laid down again from the
+//! head once the head has not moved for [`BATON_GRACE`].
+//!
+//! That re-lay, like the first baton a fresh chain lays, can duplicate a
+//! baton still held, so two writers may hold the same `seq`. The record is
+//! therefore written with [`KeyspaceHandle::insert_if_absent`] before the
+//! new head and the returned baton commit: the first writer keeps the
+//! `seq`, and the second lays the baton after the records it finds
+//! instead of overwriting one.
+//!
+//! The one exception is an ACL change made through a Trust Task: the
+//! framework handler stores it before the task returns, so its record is
+//! staged on its own right after, with [`record`].
+
+use std::time::{Duration, Instant};
+
+use axum::extract::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use did_hosting_common::did_ops::{DidRecord, content_log_key, did_key};
+use did_hosting_common::server::acl::{AclEntry, Role, get_acl_entry};
+pub use did_hosting_common::server::init::{decode_public_key, encode_public_key};
+use did_hosting_common::server::store::{KS_AUDIT, KeyspaceHandle, Store, WriteBatch};
+use did_hosting_common::server::trust_task::HEADER_NAME as TRUST_TASK_HEADER;
+use did_hosting_common::server::trust_tasks::DispatchOutcome;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tokio::sync::{Mutex, watch};
+use tracing::{debug, error, info, warn};
+use trust_tasks_rs::TrustTask;
+
+use crate::auth::AuthClaims;
+use crate::auth::session::now_epoch;
+use crate::error::AppError;
+use crate::server::AppState;
+
+/// Domain tag mixed into every record hash, so an audit hash can never
+/// collide with a digest computed for another purpose over the same JSON.
+const HASH_DOMAIN: &[u8] = b"did-hosting-control/audit/v1\0";
+
+/// `prev_hash` of record 0.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+const HEAD_KEY: &str = "head";
+
+/// Where the [`Baton`] rests while no writer holds it.
+const BATON_KEY: &str = "next";
+
+/// How long the baton may stay missing, with the head not moving, before
+/// it is taken for lost with a writer that died holding it.
+pub const BATON_GRACE: Duration = Duration::from_secs(60);
+
+/// Worker tick: chains whatever a request's own [`flush`] did not.
+pub const DEFAULT_AUDIT_TICK: Duration = Duration::from_secs(30);
+
+/// Default and maximum page size for [`list`].
+pub const DEFAULT_LIMIT: usize = 100;
+pub const MAX_LIMIT: usize = 1000;
+
+/// Records [`list`] reads for one page before it hands back a cursor, so a
+/// filter that matches little does not walk the whole chain in one call.
+pub const MAX_SCAN: u64 = 10_000;
+
+/// One writer per process; the baton serialises writers across replicas.
+static APPEND_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// The head seen when the baton was first found missing, and when.
+static BATON_MISSING: std::sync::Mutex<Option<(Option<Head>, Instant)>> =
+    std::sync::Mutex::new(None);
+
+tokio::task_local! {
+    static TRUST_TASK: Option<TrustTaskRef>;
+    static APPROVALS: Vec<Value>;
+}
+
+/// The Trust Task a mutation ran under.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrustTaskRef {
+    /// Task type URI.
+    #[serde(rename = "type")]
+    pub type_uri: String,
+    /// Document `id`, when the task arrived as a Trust Task document.
+    /// REST calls carry only the `Trust-Task` header, so have none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+}
+
+/// Who performed a mutation. `None` on a record means the service itself
+/// (e.g. an identity rotation triggered by its own DID being published).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Actor {
+    pub did: String,
+    /// ACL role at the time, when the caller authenticated with a session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    /// Assurance level (`aal1` / `aal2`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acr: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub amr: Vec<String>,
+    /// Whether the session had been stepped up (`acr == aal2`).
+    pub step_up: bool,
+    /// The API key the caller used instead of a session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+}
+
+impl From<&AuthClaims> for Actor {
+    fn from(auth: &AuthClaims) -> Self {
+        Self {
+            did: auth.did.clone(),
+            role: Some(auth.role.to_string()),
+            acr: Some(auth.acr.clone()),
+            amr: auth.amr.clone(),
+            step_up: auth.acr == "aal2",
+            api_key: auth.api_key.as_ref().map(|k| k.id.clone()),
+        }
+    }
+}
+
+impl Actor {
+    /// A transport-authenticated peer with no session (Trust Task documents
+    /// over DIDComm / TSP).
+    pub fn peer(did: &str) -> Self {
+        Self {
+            did: did.to_string(),
+            role: None,
+            acr: None,
+            amr: Vec::new(),
+            step_up: false,
+            api_key: None,
+        }
+    }
+}
+
+/// One entry in the audit chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub seq: u64,
+    /// Epoch seconds.
+    pub at: u64,
+    /// Dotted verb, e.g. `did.publish`, `acl.update`, `domain.disable`.
+    pub action: String,
+    /// What was acted on: a mnemonic, DID, domain or generation id.
+    pub subject: String,
+    pub actor: Option<Actor>,
+    pub trust_task: Option<TrustTaskRef>,
+    /// The signed `task-consent/decision/0.1` documents that approved the
+    /// mutation, when a multi-party policy gated it (see [`crate::consent`]).
+    /// Omitted when empty, so records without it hash as before.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub approvals: Vec<Value>,
+    /// `sha256:<hex>` of the subject's state before; `None` if it did not exist.
+    pub before: Option<String>,
+    /// `sha256:<hex>` of the subject's state after; `None` if it no longer exists.
+    pub after: Option<String>,
+    pub prev_hash: String,
+    pub hash: String,
+    /// Kid of the signing key.
+    pub signer: Option<String>,
+    /// Signing public key, multibase Ed25519 multikey.
+    pub public_key: Option<String>,
+    /// Ed25519 over the raw `hash` bytes, multibase base58btc.
+    pub signature: Option<String>,
+}
+
+impl AuditRecord {
+    /// Recompute `hash` from the record's other fields.
+    pub fn compute_hash(&self) -> Result<String, AppError> {
+        let mut body = serde_json::to_value(self)
+            .map_err(|e| AppError::Internal(format!("audit record serialisation: {e}")))?;
+        if let Some(obj) = body.as_object_mut() {
+            obj.remove("hash");
+            obj.remove("signature");
+        }
+        let canonical = serde_json_canonicalizer::to_string(&body)
+            .map_err(|e| AppError::Internal(format!("audit record JCS failed: {e}")))?;
+        let mut h = Sha256::new();
+        h.update(HASH_DOMAIN);
+        h.update(canonical.as_bytes());
+        Ok(to_hex(&h.finalize()))
+    }
+}
+
+/// The latest record, as stored under `head`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Head {
+    pub seq: u64,
+    pub hash: String,
+}
+
+/// The right to append: the `seq` the next record takes and the hash it
+/// links to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Baton {
+    seq: u64,
+    prev_hash: String,
+}
+
+impl Baton {
+    fn after(head: Option<&Head>) -> Self {
+        match head {
+            Some(h) => Self {
+                seq: h.seq + 1,
+                prev_hash: h.hash.clone(),
+            },
+            None => Self {
+                seq: 0,
+                prev_hash: GENESIS_HASH.to_string(),
+            },
+        }
+    }
+}
+
+/// A record waiting for its place in the chain. Everything but the chain
+/// fields is fixed when the mutation is staged.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Staged {
+    at: u64,
+    action: String,
+    subject: String,
+    actor: Option<Actor>,
+    trust_task: Option<TrustTaskRef>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    approvals: Vec<Value>,
+    before: Option<String>,
+    after: Option<String>,
+}
+
+impl From<&AuditRecord> for Staged {
+    fn from(rec: &AuditRecord) -> Self {
+        Self {
+            at: rec.at,
+            action: rec.action.clone(),
+            subject: rec.subject.clone(),
+            actor: rec.actor.clone(),
+            trust_task: rec.trust_task.clone(),
+            approvals: rec.approvals.clone(),
+            before: rec.before.clone(),
+            after: rec.after.clone(),
+        }
+    }
+}
+
+/// A mutation to record.
+#[derive(Debug, Clone)]
+pub struct Entry<'a> {
+    pub action: &'a str,
+    pub subject: &'a str,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+fn audit_ks(store: &Store) -> Result<KeyspaceHandle, AppError> {
+    store.keyspace(KS_AUDIT)
+}
+
+fn record_key(seq: u64) -> String {
+    format!("rec:{seq:020}")
+}
+
+fn staged_key(micros: u128, uuid_short: &str) -> String {
+    format!("staged:{micros:020}:{uuid_short}")
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// `sha256:<hex>` over the JCS form of `value`.
+pub fn digest<T: Serialize>(value: &T) -> Option<String> {
+    let canonical = serde_json_canonicalizer::to_string(value).ok()?;
+    Some(format!(
+        "sha256:{}",
+        to_hex(&Sha256::digest(canonical.as_bytes()))
+    ))
+}
+
+/// Digest of a DID slot: its record plus the `did.jsonl` it serves.
+/// `None` when the slot does not exist.
+pub async fn did_snapshot(state: &AppState, mnemonic: &str) -> Result<Option<String>, AppError> {
+    let Some(record) = state.dids_ks.get::<DidRecord>(did_key(mnemonic)).await? else {
+        return Ok(None);
+    };
+    did_snapshot_of(state, &record, None).await
+}
+
+/// [`did_snapshot`] of a slot about to be written: `record`, serving `log`,
+/// or the stored log when the write leaves it alone.
+pub async fn did_snapshot_of(
+    state: &AppState,
+    record: &DidRecord,
+    log: Option<&str>,
+) -> Result<Option<String>, AppError> {
+    let log = match log {
+        Some(log) => Some(log.as_bytes().to_vec()),
+        None => {
+            state
+                .dids_ks
+                .get_raw(content_log_key(&record.mnemonic))
+                .await?
+        }
+    };
+    Ok(digest(&serde_json::json!({
+        "record": record,
+        "log": log.map(|l| to_hex(&Sha256::digest(&l))),
+    })))
+}
+
+/// Run `fut` with `task` as the Trust Task any mutation inside it is
+/// recorded under.
+pub async fn with_trust_task<F: Future>(task: Option<TrustTaskRef>, fut: F) -> F::Output {
+    TRUST_TASK.scope(task, fut).await
+}
+
+fn current_trust_task() -> Option<TrustTaskRef> {
+    TRUST_TASK.try_with(Clone::clone).ok().flatten()
+}
+
+/// Run `fut` with `approvals` attached to every record appended inside it.
+pub async fn with_approvals<F: Future>(approvals: Vec<Value>, fut: F) -> F::Output {
+    APPROVALS.scope(approvals, fut).await
+}
+
+fn current_approvals() -> Vec<Value> {
+    APPROVALS.try_with(Clone::clone).unwrap_or_default()
+}
+
+/// REST middleware: scope the request's `Trust-Task` header, when present,
+/// as the task its mutations are recorded under.
+pub async fn trust_task_header_scope(req: Request, next: Next) -> Response {
+    let task = req
+        .headers()
+        .get(TRUST_TASK_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(|uri| TrustTaskRef {
+            type_uri: uri.to_string(),
+            id: None,
+        });
+    with_trust_task(task, next.run(req)).await
+}
+
+/// Stage a record for a mutation by `actor` in `batch`, the write that
+/// makes the mutation, so the record commits — or fails — with it. Call
+/// [`flush`] once the batch has committed.
+pub fn stage(
+    store: &Store,
+    batch: &mut WriteBatch,
+    actor: Option<Actor>,
+    entry: Entry<'_>,
+) -> Result<(), AppError> {
+    let staged = Staged {
+        at: now_epoch(),
+        action: entry.action.to_string(),
+        subject: entry.subject.to_string(),
+        actor,
+        trust_task: current_trust_task(),
+        approvals: current_approvals(),
+        before: entry.before,
+        after: entry.after,
+    };
+    let uuid_short = uuid::Uuid::new_v4().simple().to_string();
+    let key = staged_key(crate::outbox::now_micros(), &uuid_short[..12]);
+    batch.insert(&audit_ks(store)?, key, &staged)?;
+    debug!(action = %staged.action, subject = %staged.subject, "audit record staged");
+    Ok(())
+}
+
+/// [`stage`] a DID-slot mutation by `auth`. `after` is the slot as the
+/// batch leaves it — see [`did_snapshot_of`].
+pub fn stage_did(
+    store: &Store,
+    batch: &mut WriteBatch,
+    auth: &AuthClaims,
+    action: &str,
+    mnemonic: &str,
+    before: Option<String>,
+    after: Option<String>,
+) -> Result<(), AppError> {
+    stage(
+        store,
+        batch,
+        Some(Actor::from(auth)),
+        Entry {
+            action,
+            subject: mnemonic,
+            before,
+            after,
+        },
+    )
+}
+
+/// Record a mutation stored by code outside this crate, which gives no
+/// batch to [`stage`] into: stage it on its own, then [`flush`]. Call it
+/// right after the mutation; an error means the mutation is stored but
+/// unaudited, and is the caller's. Anything written here stages instead.
+pub async fn record(
+    state: &AppState,
+    actor: Option<Actor>,
+    entry: Entry<'_>,
+) -> Result<(), AppError> {
+    let mut batch = state.store.batch();
+    stage(&state.store, &mut batch, actor, entry)?;
+    batch.commit().await?;
+    flush(state).await;
+    Ok(())
+}
+
+/// Chain the records staged by a committed batch. A failure is logged and
+/// left to [`run_audit_loop`]: the records are already stored.
+pub async fn flush(state: &AppState) {
+    if let Err(e) = chain_staged(state).await {
+        error!(error = %e, "failed to chain staged audit records; the worker will retry");
+    }
+}
+
+/// Append every staged record to the chain, oldest first. Returns how many
+/// were appended; fewer than were staged when another writer holds the
+/// baton, in which case that writer appends them.
+pub async fn chain_staged(state: &AppState) -> Result<usize, AppError> {
+    let ks = audit_ks(&state.store)?;
+    let _guard = APPEND_LOCK.lock().await;
+    let mut staged = ks.prefix_iter_raw("staged:").await?;
+    staged.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let signing = signing_key(state);
+    let mut appended = 0;
+    for (key, value) in staged {
+        let Some(next) = take_baton(&ks).await? else {
+            break;
+        };
+        let mut batch = state.store.batch();
+        // Another writer may have appended it while this one waited for
+        // the baton.
+        let pass = if !ks.contains_key(key.clone()).await? {
+            next
+        } else {
+            match serde_json::from_slice::<Staged>(&value) {
+                Ok(entry) => {
+                    let rec = chain_record(entry.clone(), &next, signing.as_ref())?;
+                    // A re-laid baton can turn out to duplicate a live one,
+                    // so another writer may hold this `seq` too. Whoever
+                    // writes the record first keeps it; the other must not
+                    // overwrite it.
+                    if !ks.insert_if_absent(record_key(rec.seq), &rec).await? {
+                        resync_baton(&ks, batch, &key, &entry, &next).await?;
+                        break;
+                    }
+                    let head = Head {
+                        seq: rec.seq,
+                        hash: rec.hash.clone(),
+                    };
+                    batch.insert(&ks, HEAD_KEY, &head)?;
+                    appended += 1;
+                    Baton::after(Some(&head))
+                }
+                Err(e) => {
+                    warn!(error = %e, "audit: dropping malformed staged record");
+                    next
+                }
+            }
+        };
+        batch.remove(&ks, key);
+        batch.insert(&ks, BATON_KEY, &pass)?;
+        batch.commit().await?;
+    }
+    Ok(appended)
+}
+
+/// Recover from losing `next.seq` to another writer: lay the baton after
+/// the last record actually stored, move the head there if it is behind,
+/// and drop the staged row `key` only if one of those records is already
+/// its own (a writer that stored it and died before removing the row).
+async fn resync_baton(
+    ks: &KeyspaceHandle,
+    mut batch: WriteBatch,
+    key: &[u8],
+    entry: &Staged,
+    next: &Baton,
+) -> Result<(), AppError> {
+    let mut seq = next.seq;
+    let mut tip: Option<AuditRecord> = None;
+    let mut chained = false;
+    while let Some(rec) = ks.get::<AuditRecord>(record_key(seq)).await? {
+        chained |= Staged::from(&rec) == *entry;
+        tip = Some(rec);
+        seq += 1;
+    }
+    let tip = tip.map(|rec| Head {
+        seq: rec.seq,
+        hash: rec.hash,
+    });
+    warn!(
+        seq = next.seq,
+        tip = ?tip,
+        "audit: another writer appended at this seq; taking the baton past it"
+    );
+    let head: Option<Head> = ks.get(HEAD_KEY).await?;
+    if let Some(tip) = &tip
+        && head.as_ref().is_none_or(|h| h.seq < tip.seq)
+    {
+        batch.insert(ks, HEAD_KEY, tip)?;
+    }
+    if chained {
+        batch.remove(ks, key.to_vec());
+    }
+    batch.insert(ks, BATON_KEY, &Baton::after(tip.as_ref().or(head.as_ref())))?;
+    batch.commit().await
+}
+
+/// Take the baton. `None` while another writer holds it.
+async fn take_baton(ks: &KeyspaceHandle) -> Result<Option<Baton>, AppError> {
+    if let Some(next) = ks.take::<Baton>(BATON_KEY).await? {
+        *BATON_MISSING.lock().expect("baton lock") = None;
+        return Ok(Some(next));
+    }
+    let head: Option<Head> = ks.get(HEAD_KEY).await?;
+    if head.is_none() && ks.prefix_iter_raw("rec:").await?.is_empty() {
+        // Nothing chained yet, so there was never a baton to hold.
+        return Ok(Some(Baton::after(None)));
+    }
+    // Held by another writer, lost with one that died, or never laid (a
+    // chain written before the baton existed). A holder moves the head
+    // within moments; one that has not moved for `BATON_GRACE` is lost.
+    let mut missing = BATON_MISSING.lock().expect("baton lock");
+    match &*missing {
+        Some((seen, since)) if *seen == head && since.elapsed() >= BATON_GRACE => {
+            warn!(head = ?head, "audit: baton lost; laying it down again from the head");
+            *missing = None;
+            Ok(Some(Baton::after(head.as_ref())))
+        }
+        Some((seen, _)) if *seen == head => Ok(None),
+        _ => {
+            *missing = Some((head, Instant::now()));
+            Ok(None)
+        }
+    }
+}
+
+/// Build, hash and sign the record `entry` becomes at `next`.
+fn chain_record(
+    entry: Staged,
+    next: &Baton,
+    signing: Option<&(String, SigningKey)>,
+) -> Result<AuditRecord, AppError> {
+    let mut rec = AuditRecord {
+        seq: next.seq,
+        at: entry.at,
+        action: entry.action,
+        subject: entry.subject,
+        actor: entry.actor,
+        trust_task: entry.trust_task,
+        approvals: entry.approvals,
+        before: entry.before,
+        after: entry.after,
+        prev_hash: next.prev_hash.clone(),
+        hash: String::new(),
+        signer: signing.map(|(kid, _)| kid.clone()),
+        public_key: signing.map(|(_, sk)| encode_public_key(&sk.verifying_key())),
+        signature: None,
+    };
+    rec.hash = rec.compute_hash()?;
+    if let Some((_, sk)) = signing {
+        let hash_bytes = from_hex(&rec.hash).expect("hash is hex");
+        let sig = sk.sign(&hash_bytes);
+        rec.signature = Some(multibase::encode(
+            multibase::Base::Base58Btc,
+            sig.to_bytes(),
+        ));
+    }
+    Ok(rec)
+}
+
+/// Long-running worker: chains records whose request did not (it failed
+/// after committing, or another replica held the baton), and recovers a
+/// lost baton.
+pub async fn run_audit_loop(state: AppState, mut shutdown: watch::Receiver<bool>) {
+    let mut ticker = tokio::time::interval(DEFAULT_AUDIT_TICK);
+    ticker.tick().await; // skip the immediate first tick
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => match chain_staged(&state).await {
+                Ok(0) => {}
+                Ok(appended) => info!(appended, "audit records chained by the worker"),
+                Err(e) => warn!(error = %e, "audit worker failed to chain staged records"),
+            },
+            _ = shutdown.changed() => {
+                info!("audit worker shutting down");
+                return;
+            }
+        }
+    }
+}
+
+/// The control DID's current assertion key, or `None` when no identity
+/// is loaded (records are then appended unsigned).
+pub(crate) fn signing_key(state: &AppState) -> Option<(String, SigningKey)> {
+    let control_did = state.config.server_did.as_deref()?;
+    let secret = crate::signing::control_assertion_secret(state, control_did)
+        .inspect_err(|e| debug!("audit records unsigned: {e}"))
+        .ok()?;
+    let seed: [u8; 32] = secret.get_private_bytes().try_into().ok()?;
+    Some((secret.id.clone(), SigningKey::from_bytes(&seed)))
+}
+
+// ---------------------------------------------------------------------------
+// Reading
+// ---------------------------------------------------------------------------
+
+/// Filters for [`list`]. All are conjunctive; unset means "any".
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct Filter {
+    pub actor: Option<String>,
+    /// Exact action, or a prefix ending in `.` (`did.` matches every DID verb).
+    pub action: Option<String>,
+    pub subject: Option<String>,
+    /// Epoch seconds, inclusive.
+    pub since: Option<u64>,
+    /// Epoch seconds, inclusive.
+    pub until: Option<u64>,
+    /// Only records with `seq` greater than this — the paging cursor.
+    pub after_seq: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+impl Filter {
+    fn matches(&self, rec: &AuditRecord) -> bool {
+        if self
+            .actor
+            .as_deref()
+            .is_some_and(|a| rec.actor.as_ref().map(|x| x.did.as_str()) != Some(a))
+        {
+            return false;
+        }
+        if let Some(action) = self.action.as_deref() {
+            let hit = if action.ends_with('.') {
+                rec.action.starts_with(action)
+            } else {
+                rec.action == action
+            };
+            if !hit {
+                return false;
+            }
+        }
+        if self.subject.as_deref().is_some_and(|s| rec.subject != s) {
+            return false;
+        }
+        if self.since.is_some_and(|t| rec.at < t) || self.until.is_some_and(|t| rec.at > t) {
+            return false;
+        }
+        self.after_seq.is_none_or(|s| rec.seq > s)
+    }
+}
+
+/// A page of records, oldest first, with the chain head.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditPage {
+    pub records: Vec<AuditRecord>,
+    pub head: Option<Head>,
+    /// Set when the page stopped before the head: pass it back as
+    /// `after_seq` for the rest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_after_seq: Option<u64>,
+}
+
+/// The chain head, if any record has been written.
+pub async fn head(store: &Store) -> Result<Option<Head>, AppError> {
+    audit_ks(store)?.get(HEAD_KEY).await
+}
+
+/// Every record in `seq` order.
+pub async fn all_records(store: &Store) -> Result<Vec<AuditRecord>, AppError> {
+    let mut out = Vec::new();
+    for (_key, value) in audit_ks(store)?.prefix_iter_raw("rec:").await? {
+        let rec: AuditRecord = serde_json::from_slice(&value)
+            .map_err(|e| AppError::Internal(format!("corrupt audit record: {e}")))?;
+        out.push(rec);
+    }
+    // Keys are zero-padded, so store order is already `seq` order; sort
+    // anyway rather than depend on every backend iterating sorted.
+    out.sort_by_key(|r| r.seq);
+    Ok(out)
+}
+
+/// Records matching `filter`, oldest first.
+///
+/// Reads records by `seq` from the cursor towards the head, stopping at
+/// `limit` matches or after [`MAX_SCAN`] records; `next_after_seq` then
+/// says where to resume.
+pub async fn list(store: &Store, filter: &Filter) -> Result<AuditPage, AppError> {
+    let limit = filter.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let ks = audit_ks(store)?;
+    let head = head(store).await?;
+    let mut records = Vec::new();
+    let mut next_after_seq = None;
+    if let Some(last) = head.as_ref().map(|h| h.seq) {
+        let start = filter.after_seq.map_or(0, |s| s.saturating_add(1));
+        for seq in start..=last {
+            if records.len() == limit || seq - start == MAX_SCAN {
+                next_after_seq = Some(seq - 1);
+                break;
+            }
+            let rec: Option<AuditRecord> = ks.get(record_key(seq)).await?;
+            if let Some(rec) = rec.filter(|r| filter.matches(r)) {
+                records.push(rec);
+            }
+        }
+    }
+    Ok(AuditPage {
+        records,
+        head,
+        next_after_seq,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Verification
+// ---------------------------------------------------------------------------
+
+/// Outcome of [`verify_chain`]. `problems` empty means the chain verified.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct VerifyReport {
+    pub records: usize,
+    pub signed: usize,
+    pub first_seq: Option<u64>,
+    pub last_seq: Option<u64>,
+    pub last_hash: Option<String>,
+    pub problems: Vec<String>,
+}
+
+impl VerifyReport {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Check hashes, links and signatures over `records` (in `seq` order).
+///
+/// `pinned` are the public keys trusted to have signed; empty accepts any
+/// key a record carries (integrity only, no provenance). With
+/// `require_signatures`, an unsigned record is a problem. `head`, when
+/// given, must match the last record — a missing tail fails here.
+///
+/// A chain that starts after `seq` 0 (an export from a later point) is
+/// checked from its first record's `prev_hash` onward.
+pub fn verify_chain(
+    records: &[AuditRecord],
+    pinned: &[VerifyingKey],
+    require_signatures: bool,
+    head: Option<&Head>,
+) -> VerifyReport {
+    let mut report = VerifyReport {
+        records: records.len(),
+        first_seq: records.first().map(|r| r.seq),
+        last_seq: records.last().map(|r| r.seq),
+        last_hash: records.last().map(|r| r.hash.clone()),
+        ..Default::default()
+    };
+    let mut problems = Vec::new();
+
+    if let Some(first) = records.first()
+        && first.seq == 0
+        && first.prev_hash != GENESIS_HASH
+    {
+        problems.push("record 0 does not link to the genesis hash".to_string());
+    }
+
+    for (i, rec) in records.iter().enumerate() {
+        if let Some(prev) = i.checked_sub(1).map(|p| &records[p]) {
+            if rec.seq != prev.seq + 1 {
+                problems.push(format!(
+                    "gap: record {} follows record {}",
+                    rec.seq, prev.seq
+                ));
+            }
+            if rec.prev_hash != prev.hash {
+                problems.push(format!(
+                    "record {} does not link to record {}",
+                    rec.seq, prev.seq
+                ));
+            }
+        }
+        match rec.compute_hash() {
+            Ok(h) if h == rec.hash => {}
+            Ok(_) => problems.push(format!("record {}: hash mismatch", rec.seq)),
+            Err(e) => problems.push(format!("record {}: {e}", rec.seq)),
+        }
+        match verify_signature(rec, pinned) {
+            Ok(true) => report.signed += 1,
+            Ok(false) if require_signatures => {
+                problems.push(format!("record {}: unsigned", rec.seq))
+            }
+            Ok(false) => {}
+            Err(reason) => problems.push(format!("record {}: {reason}", rec.seq)),
+        }
+    }
+
+    if let Some(head) = head {
+        match records.last() {
+            Some(last) if last.seq == head.seq && last.hash == head.hash => {}
+            Some(last) => problems.push(format!(
+                "head names record {} ({}), but the chain ends at record {}",
+                head.seq, head.hash, last.seq
+            )),
+            None => problems.push(format!("head names record {} but no records", head.seq)),
+        }
+    }
+
+    report.problems = problems;
+    report
+}
+
+/// `Ok(true)` signed and valid, `Ok(false)` unsigned, `Err` bad signature.
+fn verify_signature(rec: &AuditRecord, pinned: &[VerifyingKey]) -> Result<bool, String> {
+    let Some(sig) = rec.signature.as_deref() else {
+        return Ok(false);
+    };
+    let key = rec
+        .public_key
+        .as_deref()
+        .and_then(decode_public_key)
+        .ok_or("signed but carries no usable public key")?;
+    if !pinned.is_empty() && !pinned.contains(&key) {
+        return Err("signed by a key that is not pinned".into());
+    }
+    let sig_bytes = multibase::decode(sig)
+        .ok()
+        .and_then(|(_, b)| <[u8; 64]>::try_from(b).ok())
+        .ok_or("malformed signature")?;
+    let hash = from_hex(&rec.hash).ok_or("malformed hash")?;
+    key.verify(&hash, &Signature::from_bytes(&sig_bytes))
+        .map(|()| true)
+        .map_err(|_| "signature does not verify".into())
+}
+
+/// Parse an export: either a JSON [`AuditPage`] (the
+/// `GET /api/control/audit` response) or JSON Lines of records.
+pub fn parse_export(text: &str) -> Result<(Vec<AuditRecord>, Option<Head>), String> {
+    if let Ok(page) = serde_json::from_str::<AuditPage>(text) {
+        return Ok((page.records, page.head));
+    }
+    let mut records = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let rec: AuditRecord =
+            serde_json::from_str(line).map_err(|e| format!("line {}: {e}", n + 1))?;
+        records.push(rec);
+    }
+    records.sort_by_key(|r| r.seq);
+    Ok((records, None))
+}
+
+/// Subject and action for a framework ACL Trust Task (`acl/grant`,
+/// `acl/revoke`, `acl/change-role`); `None` for anything that does not
+/// mutate the ACL.
+pub fn acl_task(type_uri: &str, payload: &Value) -> Option<(&'static str, String)> {
+    use trust_tasks_rs::Payload;
+    use trust_tasks_rs::specs::acl::{change_role, grant, revoke};
+
+    let action = if type_uri == grant::v0_1::Payload::TYPE_URI {
+        "acl.grant"
+    } else if type_uri == revoke::v0_1::Payload::TYPE_URI {
+        "acl.revoke"
+    } else if type_uri == change_role::v0_1::Payload::TYPE_URI {
+        "acl.change-role"
+    } else {
+        return None;
+    };
+    let subject = payload
+        .get("subject")
+        .or_else(|| payload.get("entry").and_then(|e| e.get("subject")))
+        .and_then(Value::as_str)?;
+    Some((action, subject.to_string()))
+}
+
+/// A framework ACL Trust Task in flight: the ACL handlers live in
+/// `did-hosting-common` and know nothing of this log, so the transports
+/// capture the subject's entry before dispatch and record after it.
+///
+/// The same before/after view drives session revocation: a subject whose
+/// entry was revoked or whose role changed loses its sessions (see
+/// [`crate::routes::sessions::revoke_on_acl_change`]), and a revoked one
+/// its API keys too.
+pub struct AclTaskWatch {
+    action: &'static str,
+    subject: String,
+    before: Option<String>,
+    before_role: Option<Role>,
+    task: TrustTaskRef,
+}
+
+impl AclTaskWatch {
+    /// `None` unless `doc` is an ACL mutation.
+    pub async fn begin(state: &AppState, doc: &TrustTask<Value>) -> Option<Self> {
+        let (action, subject) = acl_task(&doc.type_uri.to_string(), &doc.payload)?;
+        let entry = acl_entry(state, &subject).await;
+        Some(Self {
+            action,
+            subject,
+            before: entry.as_ref().and_then(digest),
+            before_role: entry.map(|e| e.role),
+            task: TrustTaskRef::from(doc),
+        })
+    }
+
+    /// Record the mutation if the dispatcher applied it.
+    pub async fn finish(self, state: &AppState, actor: Actor, outcome: &DispatchOutcome) {
+        if !matches!(outcome, DispatchOutcome::Handled(_)) {
+            return;
+        }
+        let entry = acl_entry(state, &self.subject).await;
+        // The framework handler has already stored the change.
+        if let Err(e) = with_trust_task(
+            Some(self.task),
+            record(
+                state,
+                Some(actor),
+                Entry {
+                    action: self.action,
+                    subject: &self.subject,
+                    before: self.before,
+                    after: entry.as_ref().and_then(digest),
+                },
+            ),
+        )
+        .await
+        {
+            error!(action = self.action, subject = %self.subject, error = %e, "failed to record ACL Trust Task in the audit log");
+        }
+        if let Some(before) = &self.before_role {
+            crate::routes::sessions::revoke_on_acl_change(
+                state,
+                &self.subject,
+                before,
+                entry.as_ref().map(|e| &e.role),
+            )
+            .await;
+            if entry.is_none() {
+                crate::routes::api_keys::revoke_on_acl_delete(state, &self.subject).await;
+            }
+        }
+    }
+}
+
+async fn acl_entry(state: &AppState, did: &str) -> Option<AclEntry> {
+    get_acl_entry(&state.acl_ks, did).await.ok().flatten()
+}
+
+impl From<&TrustTask<Value>> for TrustTaskRef {
+    fn from(doc: &TrustTask<Value>) -> Self {
+        Self {
+            type_uri: doc.type_uri.to_string(),
+            id: Some(doc.id.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key() -> SigningKey {
+        SigningKey::from_bytes(&[7u8; 32])
+    }
+
+    /// Build a chain the way [`chain_staged`] does, without a store.
+    fn chain(n: u64, signer: Option<&SigningKey>) -> Vec<AuditRecord> {
+        let mut out: Vec<AuditRecord> = Vec::new();
+        for seq in 0..n {
+            let mut rec = AuditRecord {
+                seq,
+                at: 1_700_000_000 + seq,
+                action: "did.publish".into(),
+                subject: format!("slot-{seq}"),
+                actor: Some(Actor::peer("did:example:alice")),
+                trust_task: None,
+                approvals: Vec::new(),
+                before: None,
+                after: digest(&seq),
+                prev_hash: out
+                    .last()
+                    .map(|r| r.hash.clone())
+                    .unwrap_or_else(|| GENESIS_HASH.into()),
+                hash: String::new(),
+                signer: signer.map(|_| "did:example:svc#key-0".into()),
+                public_key: signer.map(|k| encode_public_key(&k.verifying_key())),
+                signature: None,
+            };
+            rec.hash = rec.compute_hash().unwrap();
+            if let Some(k) = signer {
+                let sig = k.sign(&from_hex(&rec.hash).unwrap());
+                rec.signature = Some(multibase::encode(
+                    multibase::Base::Base58Btc,
+                    sig.to_bytes(),
+                ));
+            }
+            out.push(rec);
+        }
+        out
+    }
+
+    #[test]
+    fn intact_signed_chain_verifies_against_pinned_key() {
+        let k = key();
+        let recs = chain(3, Some(&k));
+        let head = Head {
+            seq: 2,
+            hash: recs[2].hash.clone(),
+        };
+        let report = verify_chain(&recs, &[k.verifying_key()], true, Some(&head));
+        assert!(report.is_ok(), "{:?}", report.problems);
+        assert_eq!(report.signed, 3);
+    }
+
+    #[test]
+    fn edited_record_breaks_hash_and_link() {
+        let k = key();
+        let mut recs = chain(3, Some(&k));
+        recs[1].subject = "someone-else".into();
+        let report = verify_chain(&recs, &[], false, None);
+        assert!(
+            report
+                .problems
+                .iter()
+                .any(|p| p.contains("record 1: hash mismatch"))
+        );
+
+        // Re-hashing the edit to hide it breaks the signature and the link
+        // from the next record instead.
+        recs[1].hash = recs[1].compute_hash().unwrap();
+        let report = verify_chain(&recs, &[], false, None);
+        assert!(report.problems.iter().any(|p| p.contains("signature")));
+        assert!(
+            report
+                .problems
+                .iter()
+                .any(|p| p.contains("record 2 does not link"))
+        );
+    }
+
+    #[test]
+    fn dropped_record_and_truncated_tail_are_detected() {
+        let recs = chain(4, None);
+        let gap = [recs[0].clone(), recs[2].clone(), recs[3].clone()];
+        let report = verify_chain(&gap, &[], false, None);
+        assert!(report.problems.iter().any(|p| p.starts_with("gap")));
+
+        let head = Head {
+            seq: 3,
+            hash: recs[3].hash.clone(),
+        };
+        let report = verify_chain(&recs[..3], &[], false, Some(&head));
+        assert!(report.problems.iter().any(|p| p.starts_with("head names")));
+    }
+
+    #[test]
+    fn unpinned_or_missing_signatures_fail_when_required() {
+        let recs = chain(2, Some(&key()));
+        let other = SigningKey::from_bytes(&[9u8; 32]).verifying_key();
+        let report = verify_chain(&recs, &[other], false, None);
+        assert!(report.problems.iter().any(|p| p.contains("not pinned")));
+
+        let unsigned = chain(2, None);
+        assert!(verify_chain(&unsigned, &[], false, None).is_ok());
+        assert!(!verify_chain(&unsigned, &[], true, None).is_ok());
+    }
+
+    #[test]
+    fn export_parses_as_page_or_jsonl() {
+        let recs = chain(2, None);
+        let jsonl = recs
+            .iter()
+            .map(|r| serde_json::to_string(r).unwrap())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let (parsed, head) = parse_export(&jsonl).unwrap();
+        assert_eq!(parsed, recs);
+        assert!(head.is_none());
+
+        let page = AuditPage {
+            records: recs.clone(),
+            head: Some(Head {
+                seq: 1,
+                hash: recs[1].hash.clone(),
+            }),
+            next_after_seq: None,
+        };
+        let (parsed, head) = parse_export(&serde_json::to_string(&page).unwrap()).unwrap();
+        assert_eq!(parsed, recs);
+        assert_eq!(head.unwrap().seq, 1);
+    }
+
+    #[test]
+    fn acl_task_subjects() {
+        use trust_tasks_rs::Payload;
+        use trust_tasks_rs::specs::acl::{grant, list, revoke};
+
+        let entry = serde_json::json!({ "entry": { "subject": "did:example:bob" } });
+        assert_eq!(
+            acl_task(grant::v0_1::Payload::TYPE_URI, &entry),
+            Some(("acl.grant", "did:example:bob".into()))
+        );
+        let bare = serde_json::json!({ "subject": "did:example:bob" });
+        assert_eq!(
+            acl_task(revoke::v0_1::Payload::TYPE_URI, &bare).map(|a| a.0),
+            Some("acl.revoke")
+        );
+        assert!(acl_task(list::v0_1::Payload::TYPE_URI, &bare).is_none());
+    }
+}
@@ -4,6 +4,10 @@ pub mod jwt {
     pub use did_hosting_common::server::auth::jwt::*;
 }
 
+pub mod api_key {
+    pub use did_hosting_common::server::auth::api_key::*;
+}
+
 pub mod session {
     pub use did_hosting_common::server::auth::session::*;
 }
@@ -0,0 +1,113 @@
+use crate::config::AppConfig;
+use crate::error::AppError;
+use crate::store::Store;
+use did_hosting_common::server::backup::{self, BackupReader, BackupRequest, RestoreRequest};
+use did_hosting_common::server::store::{
+    KS_ACL, KS_API_KEYS, KS_APPROVALS, KS_AUDIT, KS_DIDS, KS_DOMAINS, KS_IDENTITY, KS_META,
+    KS_OUTBOUND_QUEUE, KS_REGISTRY, KS_SESSIONS, KS_STATS, KS_TIMESERIES, KS_WEBHOOKS,
+};
+use std::path::PathBuf;
+
+/// `service` tag written into, and required of, control-plane backups.
+const SERVICE: &str = "control";
+
+/// Every keyspace the control plane owns. `sessions` is cut down to its
+/// durable prefixes (passkeys, enrolments) by the shared dump. The outbox
+/// is included so undelivered server pushes survive a restore.
+pub const KEYSPACES: &[&str] = &[
+    KS_DIDS,
+    KS_ACL,
+    KS_SESSIONS,
+    KS_REGISTRY,
+    KS_DOMAINS,
+    KS_OUTBOUND_QUEUE,
+    KS_IDENTITY,
+    KS_AUDIT,
+    KS_WEBHOOKS,
+    KS_API_KEYS,
+    KS_APPROVALS,
+    KS_STATS,
+    KS_TIMESERIES,
+    KS_META,
+];
+
+pub async fn run_backup(config_path: Option<PathBuf>, req: BackupRequest) -> Result<(), AppError> {
+    let keyspaces = req.selection.resolve(KEYSPACES)?;
+    let config = AppConfig::load(config_path)?;
+
+    let config_json = serde_json::to_string_pretty(&config)
+        .map_err(|e| AppError::Config(format!("failed to serialize config: {e}")))?;
+
+    let store = Store::open(&config.store).await?;
+    let counts = backup::write_backup(
+        &store,
+        SERVICE,
+        env!("CARGO_PKG_VERSION"),
+        config_json,
+        &keyspaces,
+        &req,
+    )
+    .await?;
+
+    backup::print_counts("Backup complete!", &counts);
+    if req.output != "-" {
+        eprintln!("  Output: {}", req.output);
+        eprintln!();
+    }
+
+    Ok(())
+}
+
+pub async fn run_restore(
+    config_path: Option<PathBuf>,
+    req: RestoreRequest,
+) -> Result<(), AppError> {
+    let keyspaces = req.selection.resolve(KEYSPACES)?;
+    // Read the whole file once before writing anything: a corrupt or
+    // truncated backup must not leave a half-restored store.
+    let (header, _) = backup::verify(&req.input, &req.identities, SERVICE)?;
+
+    // Deserialize the embedded AppConfig from the backup
+    let backup_config: AppConfig = serde_json::from_str(&header.config)
+        .map_err(|e| AppError::Config(format!("invalid config in backup: {e}")))?;
+    let mut reader = BackupReader::open(&req.input, &req.identities)?;
+
+    if req.dry_run {
+        // Diff against the store the current config points at, or the
+        // backed-up one when there is no config yet.
+        let store_config = AppConfig::load(config_path)
+            .map(|c| c.store)
+            .unwrap_or(backup_config.store);
+        let store = Store::open(&store_config).await?;
+        backup::print_diff(&backup::diff(&mut reader, &store, &keyspaces).await?);
+        return Ok(());
+    }
+
+    // Write config.toml from the backup
+    let config_file_path = backup::restore_config_path(config_path.clone(), "CONTROL_CONFIG_PATH");
+
+    let config_toml = toml::to_string_pretty(&backup_config)
+        .map_err(|e| AppError::Config(format!("failed to serialize config as TOML: {e}")))?;
+    std::fs::write(&config_file_path, &config_toml).map_err(|e| {
+        AppError::Config(format!(
+            "failed to write config to {}: {e}",
+            config_file_path.display()
+        ))
+    })?;
+    eprintln!("  Config restored to: {}", config_file_path.display());
+
+    let config = AppConfig::load(config_path)?;
+    let store = Store::open(&config.store).await?;
+
+    let counts = backup::restore(&mut reader, &store, &keyspaces).await?;
+    backup::print_counts("Restore complete!", &counts);
+
+    Ok(())
+}
+
+/// Check a backup's digest, trailer and entries without restoring it.
+pub fn run_verify_backup(input: String, identities: Vec<PathBuf>) -> Result<(), AppError> {
+    let (header, counts) = backup::verify(&input, &identities, SERVICE)?;
+    backup::print_verified(&header, &counts);
+    Ok(())
+}
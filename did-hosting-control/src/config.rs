@@ -1,4 +1,5 @@
 use crate::error::AppError;
+use did_hosting_common::server::acl::Role;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -32,7 +33,10 @@ pub struct AppConfig {
     pub registry: RegistryConfig,
     /// Multi-domain hosting knobs. Today the control plane reads
     /// `hosting.disable_purge_grace` to schedule the soft-delete
-    /// timer; `bootstrap_domains` + `unassigned_purge_grace` are
+    /// timer, `hosting.rollback_archive_retention` to expire
+    /// rolled-back log entries, `hosting.moved_did_retention` to
+    /// retire moved-DID stubs and `hosting.deactivated_did_retention` to
+    /// hold deactivated DIDs; `bootstrap_domains` + `unassigned_purge_grace` are
     /// server-side concerns (replicated here for shared-store
     /// deployments where one fjall directory backs both processes).
     #[serde(default)]
@@ -45,6 +49,10 @@ pub struct AppConfig {
     /// (`identity.rotation_grace_period`).
     #[serde(default)]
     pub identity: did_hosting_common::server::config::IdentityConfig,
+    /// Multi-party approval policies for sensitive admin actions
+    /// (`[[consent.policies]]`, see [`crate::consent`]).
+    #[serde(default)]
+    pub consent: ConsentConfig,
     #[serde(skip)]
     pub config_path: PathBuf,
 }
@@ -90,6 +98,88 @@ impl Default for TrustTasksConfig {
     }
 }
 
+/// Which admin actions need approval from other parties before they run.
+///
+/// Each policy names one gated action ([`crate::consent::TASKS`]), the DIDs
+/// that may approve it, and how many of them must. An action no policy
+/// names runs on the caller's own authority, as it always has.
+///
+/// ```toml
+/// [[consent.policies]]
+/// task = "acl/grant"
+/// role = "admin"
+/// approvers = ["did:webvh:…:alice", "did:webvh:…:bob", "did:webvh:…:carol"]
+/// threshold = 2
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ConsentConfig {
+    #[serde(default)]
+    pub policies: Vec<ConsentPolicy>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ConsentPolicy {
+    /// The gated action: `acl/grant`, `domain/purge` or `identity/rotate`.
+    pub task: String,
+    /// For `acl/grant` only: the granted role this policy covers. Unset
+    /// covers every role.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<Role>,
+    /// DIDs whose wallets are asked to approve.
+    pub approvers: Vec<String>,
+    /// How many distinct approvers must approve. One deny refuses.
+    pub threshold: usize,
+    /// When `true` (default), the admin requesting the action is not asked
+    /// to approve it even if listed, and does not count towards `threshold`.
+    #[serde(default = "default_exclude_requester")]
+    pub exclude_requester: bool,
+    /// How long approvers have before the request lapses, in seconds.
+    #[serde(default = "default_consent_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_exclude_requester() -> bool {
+    true
+}
+
+fn default_consent_timeout_secs() -> u64 {
+    300
+}
+
+impl ConsentConfig {
+    /// Reject a policy that could never be met or names no known action,
+    /// so a typo fails at startup rather than silently ungating the action.
+    pub fn validate(&self) -> Result<(), AppError> {
+        for (i, policy) in self.policies.iter().enumerate() {
+            let at = format!("consent.policies[{i}] ({})", policy.task);
+            if !crate::consent::TASKS.contains(&policy.task.as_str()) {
+                return Err(AppError::Config(format!(
+                    "{at}: unknown task; expected one of {}",
+                    crate::consent::TASKS.join(", ")
+                )));
+            }
+            if policy.role.is_some() && policy.task != crate::consent::ACL_GRANT {
+                return Err(AppError::Config(format!(
+                    "{at}: `role` only applies to {}",
+                    crate::consent::ACL_GRANT
+                )));
+            }
+            if policy.threshold == 0 || policy.threshold > policy.approvers.len() {
+                return Err(AppError::Config(format!(
+                    "{at}: threshold must be between 1 and the number of approvers ({})",
+                    policy.approvers.len()
+                )));
+            }
+            if policy.timeout_secs == 0 {
+                return Err(AppError::Config(format!(
+                    "{at}: timeout_secs must be positive"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
 fn default_server() -> ServerConfig {
     ServerConfig {
         host: "0.0.0.0".to_string(),
@@ -207,6 +297,8 @@ impl AppConfig {
             config.registry.health_check_interval
         );
 
+        config.consent.validate()?;
+
         // Normalize: strip trailing slashes from public_url and did_hosting_url
         if let Some(ref mut url) = config.public_url {
             let trimmed = url.trim_end_matches('/').to_string();
@@ -0,0 +1,411 @@
+//! Multi-party (M-of-N) approval of sensitive admin actions.
+//!
+//! One admin session is enough to grant the Admin role, purge a domain, or
+//! publish a new version of the service's own DID — and with it rotate the
+//! keys the service signs and decrypts with. The `[[consent.policies]]`
+//! table ([`crate::config::ConsentConfig`]) puts any of these behind a
+//! quorum: the first time the action is requested, every approver the
+//! policy names is sent a signed `task-consent/request/0.1` over DIDComm
+//! ([`crate::routes::task_consent::solicit`]) and the request is answered
+//! `202` with the approval's id ([`AppError::ApprovalPending`]). The
+//! requester polls `GET /api/task-consent/{id}` and repeats the action once
+//! `threshold` distinct approvers have returned signed approvals; that
+//! repeat runs it and spends the approval. One deny refuses the action with
+//! `403`. When the window lapses first, the next repeat asks again.
+//!
+//! Approvals are kept in the store ([`crate::approvals`]), so they survive a
+//! restart or a dropped connection and any replica can record a decision.
+//! An approval is bound to the requester, the policy task, the subject, the
+//! role and the text the approvers were shown; a repeat that differs in
+//! any of them is a new request.
+//!
+//! The signed decisions are the evidence. [`require`] returns them, and the
+//! caller appends the action's audit record inside
+//! [`audit::with_approvals`], so the record carries them under its hash and
+//! signature.
+//!
+//! Gated actions, by policy `task`:
+//!
+//! - [`ACL_GRANT`] — creating an ACL entry with, or changing one to, the
+//!   policy's `role` (any role when unset): `POST` / `PUT /api/acl` and the
+//!   `acl/grant` / `acl/change-role` Trust Tasks on every transport.
+//! - [`DOMAIN_PURGE`] — `DELETE /api/domains/{name}`, which removes a domain
+//!   without the disable grace period.
+//! - [`IDENTITY_ROTATE`] — publishing the service's own DID, including the
+//!   agent-name verbs that republish it.
+//!
+//! The identity kill switch (`POST /api/identity/generations/{id}/retire`)
+//! is deliberately not gated: it only ever narrows what is honoured, and it
+//! has to work while the approvers cannot be reached.
+//!
+//! The first policy matching an action applies. Requests are only fanned
+//! out for a caller who could perform the action anyway, so a gate cannot
+//! be used to spam the approvers.
+
+use std::time::Duration;
+
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+use trust_tasks_rs::{RejectReason, TrustTask};
+
+use crate::acl::{Role, check_acl};
+use crate::approvals;
+use crate::audit;
+use crate::auth::session::now_epoch;
+use crate::config::{ConsentConfig, ConsentPolicy};
+use crate::error::AppError;
+use crate::routes::task_consent::{ApprovalRule, solicit, validate_action};
+use crate::server::AppState;
+
+/// Granting a role: a new ACL entry, or a role change.
+pub const ACL_GRANT: &str = "acl/grant";
+/// Deleting a domain outright.
+pub const DOMAIN_PURGE: &str = "domain/purge";
+/// Publishing the service's own DID.
+pub const IDENTITY_ROTATE: &str = "identity/rotate";
+
+/// Every `task` a policy may name.
+pub const TASKS: &[&str] = &[ACL_GRANT, DOMAIN_PURGE, IDENTITY_ROTATE];
+
+/// Where a solicited consent stands.
+#[derive(Debug)]
+pub enum ConsentOutcome {
+    /// Still collecting: `approvals` approvals so far.
+    Pending { approvals: usize },
+    /// The threshold was met. One signed decision document per approver.
+    Granted(Vec<Value>),
+    /// An approver refused.
+    Denied { by: String },
+    /// The window lapsed with `approvals` approvals in.
+    Expired { approvals: usize },
+}
+
+/// A gated action about to run.
+#[derive(Debug, Clone)]
+pub struct Gate<'a> {
+    /// One of [`TASKS`].
+    pub task: &'static str,
+    /// For [`ACL_GRANT`], the role being granted.
+    pub role: Option<Role>,
+    /// What is acted on: a DID, domain or mnemonic.
+    pub subject: &'a str,
+    /// What the approvers are shown, as the request's `note`.
+    pub note: String,
+}
+
+fn policy_for<'c>(config: &'c ConsentConfig, gate: &Gate<'_>) -> Option<&'c ConsentPolicy> {
+    config.policies.iter().find(|p| {
+        p.task == gate.task
+            && p.role
+                .as_ref()
+                .is_none_or(|role| gate.role.as_ref() == Some(role))
+    })
+}
+
+/// What binds an approval to one action: hex SHA-256 over the requester and
+/// every field of the gate.
+pub(crate) fn gate_digest(requester: &str, gate: &Gate<'_>) -> Result<String, AppError> {
+    let canonical = serde_json_canonicalizer::to_string(&json!([
+        requester,
+        gate.task,
+        gate.subject,
+        gate.role,
+        gate.note,
+    ]))
+    .map_err(|e| AppError::Internal(format!("gate canonicalization failed: {e}")))?;
+    Ok(Sha256::digest(canonical.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect())
+}
+
+/// Obtain the approvals the policy covering `gate` requires.
+///
+/// Returns the approvers' signed decisions, or nothing when no policy
+/// covers the action. Until a granted approval exists for exactly this
+/// requester and gate, the approvers are asked (once per approval window)
+/// and the result is [`AppError::ApprovalPending`]; a granted approval is
+/// spent by the call that returns it. A deny, or a policy the requester's
+/// exclusion leaves unmeetable, is [`AppError::Forbidden`].
+pub async fn require(
+    state: &AppState,
+    requester: &str,
+    gate: Gate<'_>,
+) -> Result<Vec<Value>, AppError> {
+    let Some(policy) = policy_for(&state.config.consent, &gate) else {
+        return Ok(Vec::new());
+    };
+    let approvers: Vec<String> = policy
+        .approvers
+        .iter()
+        .filter(|a| !(policy.exclude_requester && a.as_str() == requester))
+        .cloned()
+        .collect();
+    if approvers.len() < policy.threshold {
+        return Err(AppError::Forbidden(format!(
+            "{} needs {} approvals, but only {} approvers other than the requester are configured",
+            gate.task,
+            policy.threshold,
+            approvers.len()
+        )));
+    }
+    validate_action(&gate.note)?;
+
+    let gate_key = gate_digest(requester, &gate)?;
+    // Serializes a requester's repeats of the same action on this replica;
+    // `approvals::redeem` makes spending the approval safe across replicas.
+    let _guard = state
+        .path_locks
+        .guard(&format!("approval:{gate_key}"))
+        .await;
+    if let Some(approval) = approvals::for_gate(&state.store, &gate_key).await? {
+        match approvals::status(&state.store, &approval, now_epoch()).await? {
+            ConsentOutcome::Granted(decisions) if approval.redeemable(now_epoch()) => {
+                if approvals::redeem(&state.store, &approval).await? {
+                    info!(
+                        task = gate.task,
+                        subject = gate.subject,
+                        approval = %approval.id,
+                        approvals = decisions.len(),
+                        "multi-party consent granted"
+                    );
+                    return Ok(decisions);
+                }
+                // Spent by a concurrent repeat; this one asks again.
+            }
+            ConsentOutcome::Pending { .. } => {
+                return Err(AppError::ApprovalPending {
+                    approval_id: approval.id,
+                });
+            }
+            ConsentOutcome::Denied { by } => {
+                approvals::remove(&state.store, &approval).await?;
+                warn!(task = gate.task, subject = gate.subject, denied_by = %by, "multi-party consent denied");
+                return Err(AppError::Forbidden(format!(
+                    "{} of {} was denied by {by}",
+                    gate.task, gate.subject
+                )));
+            }
+            ConsentOutcome::Granted(_) | ConsentOutcome::Expired { .. } => {
+                approvals::remove(&state.store, &approval).await?;
+            }
+        }
+    }
+
+    let approver_set = format!("policy:{}", policy.task);
+    let rule = ApprovalRule {
+        approver_set: &approver_set,
+        min_approvals: policy.threshold,
+        exclude_requester: policy.exclude_requester,
+    };
+    let payload = json!({
+        "action": gate.note,
+        "task": gate.task,
+        "subject": gate.subject,
+        "role": gate.role,
+    });
+    let approval_id = solicit(
+        state,
+        requester,
+        &approvers,
+        rule,
+        &gate.note,
+        &payload,
+        Some(gate_key),
+        Duration::from_secs(policy.timeout_secs),
+    )
+    .await?;
+    info!(
+        task = gate.task,
+        subject = gate.subject,
+        requester,
+        approval = %approval_id,
+        approvers = approvers.len(),
+        threshold = policy.threshold,
+        "action needs multi-party consent; approvers asked"
+    );
+    Err(AppError::ApprovalPending { approval_id })
+}
+
+/// Whether `mnemonic` is the slot the service's own DID lives in.
+pub fn is_own_did(state: &AppState, mnemonic: &str) -> bool {
+    state
+        .config
+        .server_did
+        .as_deref()
+        .and_then(did_hosting_common::server::identity::mnemonic_from_did)
+        .is_some_and(|ours| ours == mnemonic)
+}
+
+/// The subject and role an ACL Trust Task grants: `payload.entry.role` of
+/// an `acl/grant`, `payload.toRole` of an `acl/change-role`. `None` for
+/// anything else, or a role this service does not know (the dispatcher
+/// rejects those).
+pub fn acl_task_grant(doc: &TrustTask<Value>) -> Option<(String, Role)> {
+    let (action, subject) = audit::acl_task(&doc.type_uri.to_string(), &doc.payload)?;
+    let role = match action {
+        "acl.grant" => doc.payload.get("entry")?.get("role")?,
+        "acl.change-role" => doc.payload.get("toRole")?,
+        _ => return None,
+    };
+    let role = serde_json::from_value(json!(role.as_str()?.to_ascii_lowercase())).ok()?;
+    Some((subject, role))
+}
+
+/// [`require`] for an inbound ACL Trust Task. Only a role grant from an
+/// Admin is gated — anyone else is refused by the dispatcher without the
+/// approvers being asked.
+pub async fn require_for_acl_task(
+    state: &AppState,
+    requester: &str,
+    doc: &TrustTask<Value>,
+) -> Result<Vec<Value>, AppError> {
+    let Some((subject, role)) = acl_task_grant(doc) else {
+        return Ok(Vec::new());
+    };
+    if !matches!(check_acl(&state.acl_ks, requester).await, Ok(Role::Admin)) {
+        return Ok(Vec::new());
+    }
+    require(
+        state,
+        requester,
+        Gate {
+            task: ACL_GRANT,
+            note: format!("Grant the {role} role to {subject}"),
+            role: Some(role),
+            subject: &subject,
+        },
+    )
+    .await
+}
+
+/// The routed rejection for a Trust Task whose consent was not obtained.
+/// A pending approval is a `task_failed` whose details carry its id, so the
+/// caller can poll it and resubmit the document.
+pub fn reject(doc: &TrustTask<Value>, err: &AppError) -> trust_tasks_rs::ErrorResponse {
+    let reason = match err {
+        AppError::Forbidden(msg) => RejectReason::PermissionDenied {
+            reason: msg.clone(),
+        },
+        AppError::ApprovalPending { approval_id } => RejectReason::TaskFailed {
+            reason: "awaiting multi-party approval".into(),
+            details: Some(json!({ "status": "pending", "approvalId": approval_id })),
+        },
+        other => RejectReason::InternalError {
+            reason: other.to_string(),
+        },
+    };
+    doc.reject_with(format!("urn:uuid:{}", uuid::Uuid::new_v4()), reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(task: &str, role: Option<Role>) -> ConsentPolicy {
+        ConsentPolicy {
+            task: task.into(),
+            role,
+            approvers: vec!["did:example:a".into(), "did:example:b".into()],
+            threshold: 2,
+            exclude_requester: true,
+            timeout_secs: 300,
+        }
+    }
+
+    fn gate(task: &'static str, role: Option<Role>) -> Gate<'static> {
+        Gate {
+            task,
+            role,
+            subject: "did:example:subject",
+            note: "note".into(),
+        }
+    }
+
+    #[test]
+    fn policy_matches_task_and_role() {
+        let config = ConsentConfig {
+            policies: vec![
+                policy(ACL_GRANT, Some(Role::Admin)),
+                policy(DOMAIN_PURGE, None),
+            ],
+        };
+        assert!(policy_for(&config, &gate(ACL_GRANT, Some(Role::Admin))).is_some());
+        assert!(policy_for(&config, &gate(ACL_GRANT, Some(Role::Owner))).is_none());
+        assert!(policy_for(&config, &gate(DOMAIN_PURGE, None)).is_some());
+        assert!(policy_for(&config, &gate(IDENTITY_ROTATE, None)).is_none());
+
+        // A role-less ACL policy covers every grant.
+        let config = ConsentConfig {
+            policies: vec![policy(ACL_GRANT, None)],
+        };
+        assert!(policy_for(&config, &gate(ACL_GRANT, Some(Role::Owner))).is_some());
+    }
+
+    #[test]
+    fn validate_rejects_unmeetable_or_unknown_policies() {
+        let ok = ConsentConfig {
+            policies: vec![policy(ACL_GRANT, Some(Role::Admin))],
+        };
+        ok.validate().unwrap();
+
+        for bad in [
+            policy("acl/revoke", None),
+            policy(DOMAIN_PURGE, Some(Role::Admin)),
+            ConsentPolicy {
+                threshold: 3,
+                ..policy(DOMAIN_PURGE, None)
+            },
+            ConsentPolicy {
+                threshold: 0,
+                ..policy(DOMAIN_PURGE, None)
+            },
+        ] {
+            let config = ConsentConfig {
+                policies: vec![bad.clone()],
+            };
+            assert!(config.validate().is_err(), "{bad:?} must be rejected");
+        }
+    }
+
+    #[test]
+    fn acl_task_grant_reads_grant_and_change_role() {
+        use trust_tasks_rs::Payload;
+        use trust_tasks_rs::specs::acl::{change_role, grant, revoke};
+
+        let doc = |type_uri: &str, payload: Value| -> TrustTask<Value> {
+            serde_json::from_value(json!({
+                "id": "urn:uuid:1",
+                "type": type_uri,
+                "issuedAt": "2026-07-29T00:00:00Z",
+                "payload": payload,
+            }))
+            .unwrap()
+        };
+
+        let granted = doc(
+            grant::v0_1::Payload::TYPE_URI,
+            json!({ "entry": { "subject": "did:example:s", "role": "admin" } }),
+        );
+        assert_eq!(
+            acl_task_grant(&granted),
+            Some(("did:example:s".into(), Role::Admin))
+        );
+
+        let changed = doc(
+            change_role::v0_1::Payload::TYPE_URI,
+            json!({ "subject": "did:example:s", "fromRole": "owner", "toRole": "admin" }),
+        );
+        assert_eq!(
+            acl_task_grant(&changed),
+            Some(("did:example:s".into(), Role::Admin))
+        );
+
+        let revoked = doc(
+            revoke::v0_1::Payload::TYPE_URI,
+            json!({ "subject": "did:example:s" }),
+        );
+        assert_eq!(acl_task_grant(&revoked), None);
+    }
+}
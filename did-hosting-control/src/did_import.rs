@@ -0,0 +1,347 @@
+//! Migrating an existing `did:webvh` DID in from another host.
+//!
+//! A did:webvh DID can only change host by appending a log entry, signed
+//! by the DID's own update keys, whose `state.id` names the new location.
+//! The control plane cannot sign that entry, so import is two steps:
+//!
+//! 1. **Start** ([`crate::did_ops::start_import`]) — the caller supplies
+//!    the source `did.jsonl` (uploaded, or fetched from its current URL).
+//!    The whole chain is verified and must declare `portable: true`. A
+//!    slot is reserved here, and the reply is an [`ImportOffer`]: the
+//!    exact DID the move entry must switch to, and the source DID its
+//!    `alsoKnownAs` must keep.
+//! 2. **Complete** ([`crate::did_ops::complete_import`]) — the caller
+//!    submits the source log with the signed move entry appended. It must
+//!    extend the source log unchanged, verify end to end, and land on the
+//!    offered DID. It is then registered like any other DID, with
+//!    `version_count`, `services` and agent names taken from the log.
+//!
+//! Between the two, the offer is kept under the slot
+//! ([`pending_import_key`]) for [`PENDING_IMPORT_TTL_SECS`]. After that
+//! [`expire_pending`] drops it and releases the slot if nothing was
+//! published there.
+
+use std::time::Duration;
+
+use did_hosting_common::did_ops::{DidRecord, did_key, owner_key, pending_import_key};
+use did_hosting_common::server::store::KeyspaceHandle;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::audit;
+use crate::error::AppError;
+use crate::outbound;
+use crate::quota;
+use crate::server::AppState;
+
+/// How long a started import waits for its move entry.
+pub const PENDING_IMPORT_TTL_SECS: u64 = 7 * 24 * 3600;
+
+/// Largest source log accepted, fetched or uploaded. Matches the body
+/// ceiling on the upload routes.
+pub const MAX_SOURCE_LOG_BYTES: usize = 10 * 1024 * 1024;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Where the source log comes from. Exactly one of the two.
+#[derive(Debug, Clone, Copy)]
+pub enum ImportSource<'a> {
+    /// The DID's current `did.jsonl` URL.
+    Url(&'a str),
+    /// The log itself.
+    Log(&'a str),
+}
+
+impl<'a> ImportSource<'a> {
+    /// Pick the source from a request's two optional fields.
+    pub fn from_parts(url: Option<&'a str>, log: Option<&'a str>) -> Result<Self, AppError> {
+        match (url, log) {
+            (Some(url), None) => Ok(Self::Url(url)),
+            (None, Some(log)) => Ok(Self::Log(log)),
+            _ => Err(AppError::Validation(
+                "import needs exactly one of `sourceUrl` or `didLog`".into(),
+            )),
+        }
+    }
+}
+
+/// A started import, as stored.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PendingImport {
+    pub source_did: String,
+    /// The verified source log, trailing whitespace trimmed.
+    pub source_log: String,
+    pub target_did: String,
+    /// Where the DID will resolve once imported.
+    pub did_url: String,
+    pub created_at: u64,
+    pub expires_at: u64,
+}
+
+/// What the owner needs to build the move entry. Returned by start, and
+/// again by `GET /api/import/{mnemonic}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportOffer {
+    pub mnemonic: String,
+    pub did_url: String,
+    pub source_did: String,
+    /// The `state.id` the move entry must carry.
+    pub target_did: String,
+    /// `versionId` of the last source entry — the move entry follows it.
+    pub source_version_id: Option<String>,
+    pub source_version_count: u64,
+    /// Must appear in the move entry's `alsoKnownAs`, so resolvers of the
+    /// old identifier can follow it here.
+    pub also_known_as: String,
+    pub expires_at: u64,
+}
+
+impl ImportOffer {
+    pub fn new(mnemonic: &str, pending: &PendingImport) -> Self {
+        let meta = did_hosting_common::did_ops::extract_log_metadata(&pending.source_log);
+        Self {
+            mnemonic: mnemonic.to_string(),
+            did_url: pending.did_url.clone(),
+            source_did: pending.source_did.clone(),
+            target_did: pending.target_did.clone(),
+            source_version_id: meta.latest_version_id,
+            source_version_count: meta.log_entry_count,
+            also_known_as: pending.source_did.clone(),
+            expires_at: pending.expires_at,
+        }
+    }
+}
+
+/// Load the source log. URLs are checked with
+/// [`outbound::validate_url`] and, for non-admins, fetched through the
+/// client that refuses internal addresses at connect time. Redirects are
+/// not followed, and the body is read up to [`MAX_SOURCE_LOG_BYTES`].
+pub async fn load_source(
+    state: &AppState,
+    source: ImportSource<'_>,
+    admin: bool,
+) -> Result<String, AppError> {
+    let raw = match source {
+        ImportSource::Log(log) => log.to_string(),
+        ImportSource::Url(url) => {
+            let url = outbound::validate_url(url, admin, "source URL")?;
+            fetch(outbound::client(state, admin), &url).await?
+        }
+    };
+    if raw.len() > MAX_SOURCE_LOG_BYTES {
+        return Err(AppError::Validation(format!(
+            "source log exceeds {MAX_SOURCE_LOG_BYTES} bytes"
+        )));
+    }
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return Err(AppError::Validation("source log is empty".into()));
+    }
+    Ok(trimmed.to_string())
+}
+
+async fn fetch(client: reqwest::Client, url: &url::Url) -> Result<String, AppError> {
+    let unreachable = |e: &dyn std::fmt::Display| {
+        AppError::Validation(format!("could not fetch source log from {url}: {e}"))
+    };
+    let resp = client
+        .get(url.clone())
+        .timeout(FETCH_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| unreachable(&e))?;
+    if !resp.status().is_success() {
+        return Err(unreachable(&resp.status()));
+    }
+    let bytes = outbound::read_capped(resp, MAX_SOURCE_LOG_BYTES)
+        .await
+        .map_err(|e| unreachable(&e))?;
+    String::from_utf8(bytes)
+        .map_err(|e| AppError::Validation(format!("source log is not valid UTF-8: {e}")))
+}
+
+/// The DID a slot resolves as: `did:webvh:{scid}:{host}[:{path}]`, where
+/// `host` is already percent-encoded (see `did::encode_host`). The root
+/// slot has no path segment.
+pub fn target_did(source_did: &str, host: &str, mnemonic: &str) -> Result<String, AppError> {
+    let scid = source_did
+        .strip_prefix("did:webvh:")
+        .and_then(|rest| rest.split(':').next())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| {
+            AppError::Validation(format!("source DID '{source_did}' is not a did:webvh"))
+        })?;
+    Ok(if mnemonic == ".well-known" {
+        format!("did:webvh:{scid}:{host}")
+    } else {
+        format!("did:webvh:{scid}:{host}:{}", mnemonic.replace('/', ":"))
+    })
+}
+
+/// Whether the latest entry's `alsoKnownAs` lists `did`.
+pub fn claims_also_known_as(did_log: &str, did: &str) -> bool {
+    did_log
+        .lines()
+        .rfind(|l| !l.trim().is_empty())
+        .and_then(|l| serde_json::from_str::<serde_json::Value>(l).ok())
+        .and_then(|v| v.pointer("/state/alsoKnownAs").cloned())
+        .and_then(|a| a.as_array().cloned())
+        .is_some_and(|a| a.iter().any(|e| e.as_str() == Some(did)))
+}
+
+/// The entries `submitted` adds on top of `source`, or `None` if it does
+/// not start with every source entry unchanged.
+pub fn appended_entries<'a>(source: &str, submitted: &'a str) -> Option<Vec<&'a str>> {
+    let mut submitted = submitted.lines().filter(|l| !l.trim().is_empty());
+    for expected in source.lines().filter(|l| !l.trim().is_empty()) {
+        if submitted.next()?.trim() != expected.trim() {
+            return None;
+        }
+    }
+    Some(submitted.collect())
+}
+
+pub async fn get(
+    dids_ks: &KeyspaceHandle,
+    mnemonic: &str,
+) -> Result<Option<PendingImport>, AppError> {
+    dids_ks.get(pending_import_key(mnemonic)).await
+}
+
+/// Abandon every import past its `expires_at`. Returns how many went.
+///
+/// The slot an import reserved is released too, but only while it is
+/// still the empty slot `start` created: once anything has been published
+/// there it is a DID in its own right and only the offer is dropped. Each
+/// expiry is audited.
+pub async fn expire_pending(state: &AppState, now: u64) -> Result<u64, AppError> {
+    let dids_ks = &state.dids_ks;
+    let mut expired = 0;
+    for (k, v) in dids_ks.prefix_iter_raw("import:").await? {
+        let key = String::from_utf8_lossy(&k).into_owned();
+        let Some(mnemonic) = key.strip_prefix("import:").map(str::to_string) else {
+            continue;
+        };
+        let pending = match serde_json::from_slice::<PendingImport>(&v) {
+            Ok(pending) if pending.expires_at > now => continue,
+            Ok(pending) => pending,
+            Err(e) => {
+                warn!(key = %key, error = %e, "dropping malformed pending import");
+                dids_ks.remove(k).await?;
+                continue;
+            }
+        };
+
+        let _guard = state.path_locks.guard(&mnemonic).await;
+        // Completed or abandoned while waiting for the lock.
+        if !dids_ks.contains_key(k.clone()).await? {
+            continue;
+        }
+        let before = audit::did_snapshot(state, &mnemonic).await?;
+        let empty_slot = dids_ks
+            .get::<DidRecord>(did_key(&mnemonic))
+            .await?
+            .filter(|r| r.version_count == 0 && r.did_id.is_none());
+        let mut usage = quota::lock(
+            state,
+            [],
+            [empty_slot.as_ref().and_then(quota::record_domain)],
+        )
+        .await;
+        let mut batch = state.store.batch();
+        if let Some(record) = &empty_slot {
+            batch.remove(dids_ks, did_key(&mnemonic));
+            batch.remove(dids_ks, owner_key(&record.owner, &mnemonic));
+            usage.record(Some(record), None)?;
+        }
+        batch.remove(dids_ks, k);
+        audit::stage(
+            &state.store,
+            &mut batch,
+            None,
+            audit::Entry {
+                action: "did.import.expire",
+                subject: &mnemonic,
+                // Only the pending row goes when the slot is not empty.
+                after: if empty_slot.is_some() {
+                    None
+                } else {
+                    before.clone()
+                },
+                before,
+            },
+        )?;
+        usage.stage(state, &mut batch).await?;
+        batch.commit().await?;
+        drop(usage);
+        audit::flush(state).await;
+        info!(
+            mnemonic = %mnemonic,
+            source = %pending.source_did,
+            released = empty_slot.is_some(),
+            "pending DID import expired"
+        );
+        expired += 1;
+    }
+    Ok(expired)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn target_did_keeps_scid_and_swaps_location() {
+        let src = "did:webvh:QmSCID:old.example.com:people:alice";
+        assert_eq!(
+            target_did(src, "new.example.com", "alice").unwrap(),
+            "did:webvh:QmSCID:new.example.com:alice"
+        );
+        assert_eq!(
+            target_did(src, "localhost%3A8085", "team/alice").unwrap(),
+            "did:webvh:QmSCID:localhost%3A8085:team:alice"
+        );
+        assert_eq!(
+            target_did(src, "new.example.com", ".well-known").unwrap(),
+            "did:webvh:QmSCID:new.example.com"
+        );
+        assert!(target_did("did:web:old.example.com", "new.example.com", "a").is_err());
+    }
+
+    #[test]
+    fn appended_entries_requires_unchanged_prefix() {
+        let source = "{\"a\":1}\n{\"a\":2}";
+        assert_eq!(
+            appended_entries(source, "{\"a\":1}\n{\"a\":2}\n{\"a\":3}\n"),
+            Some(vec!["{\"a\":3}"])
+        );
+        assert_eq!(appended_entries(source, source), Some(vec![]));
+        assert_eq!(
+            appended_entries(source, "{\"a\":1}\n{\"a\":9}\n{\"a\":3}"),
+            None
+        );
+        assert_eq!(appended_entries(source, "{\"a\":1}"), None);
+    }
+
+    #[test]
+    fn also_known_as_is_read_from_the_latest_entry() {
+        let log = concat!(
+            "{\"state\":{\"alsoKnownAs\":[\"did:webvh:Q:old.example\"]}}\n",
+            "{\"state\":{\"alsoKnownAs\":[\"https://x.example/@a\"]}}\n",
+        );
+        assert!(!claims_also_known_as(log, "did:webvh:Q:old.example"));
+        assert!(claims_also_known_as(log, "https://x.example/@a"));
+    }
+
+    #[test]
+    fn source_urls_are_https_and_external() {
+        let check = |url, admin| outbound::validate_url(url, admin, "source URL");
+        assert!(check("https://old.example.com/alice/did.jsonl", false).is_ok());
+        assert!(check("http://old.example.com/alice/did.jsonl", false).is_err());
+        assert!(check("https://127.0.0.1/did.jsonl", false).is_err());
+        assert!(check("https://127.0.0.1/did.jsonl", true).is_ok());
+        assert!(ImportSource::from_parts(Some("u"), Some("l")).is_err());
+        assert!(ImportSource::from_parts(None, None).is_err());
+    }
+}
@@ -0,0 +1,275 @@
+//! Moving a hosted `did:webvh` to another domain or path hosted here.
+//!
+//! A did:webvh DID changes location by appending a log entry, signed by
+//! its own update keys, whose `state.id` names the new place; the log must
+//! have been created with `portable: true`. [`crate::did_ops::move_did`]
+//! takes that signed log and re-keys the record under the slot the new
+//! identifier maps to (`did:webvh:{scid}:{domain}:{path}` → `{path}`).
+//!
+//! The old location keeps answering for `hosting.moved_did_retention`, so
+//! a resolver holding the old identifier fetches the full log, sees the
+//! move entry and follows it — the new document lists the old DID in
+//! `alsoKnownAs`. How that works depends on whether the path changed:
+//!
+//! - **New path.** The old slot stays as a read-only stub: its record
+//!   keeps the old `did_id` and serves the moved log. Writes to it are
+//!   refused ([`ensure_not_moved_away`]).
+//! - **Same path, new domain.** Mnemonics are global, so there is only one
+//!   slot. Servers are told the old identifier alongside the sync
+//!   (`previous_did_id`) and keep resolving the old host for it.
+//!
+//! Either way a [`MovedStub`] sits under the old mnemonic
+//! ([`moved_stub_key`]) until it lapses; the control purge sweep then
+//! drops the stub slot and frees its agent names, or re-syncs the moved
+//! slot without its old identifier ([`expire_stubs`]).
+
+use did_hosting_common::did_ops::{
+    DidRecord, agent_name_key, content_did_doc_key, content_log_key, content_witness_key, did_key,
+    moved_stub_key, owner_key,
+};
+use did_hosting_common::server::identity::mnemonic_from_did;
+use did_hosting_common::server::pending_purge::parse_grace_string;
+use did_hosting_common::server::store::{KS_REGISTRY, KeyspaceHandle};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::audit;
+use crate::auth::session::now_epoch;
+use crate::config::AppConfig;
+use crate::error::AppError;
+use crate::quota;
+use crate::server::AppState;
+use crate::webhooks;
+
+/// A move's old location, as stored under the old mnemonic.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MovedStub {
+    /// The identifier before the move.
+    pub old_did: String,
+    /// Where the DID lives now. Equal to the key's mnemonic for an
+    /// in-place (domain-only) move.
+    pub new_mnemonic: String,
+    pub new_did: String,
+    pub moved_at: u64,
+    pub expires_at: u64,
+    /// DID of the caller that moved it.
+    pub moved_by: String,
+}
+
+impl MovedStub {
+    /// Whether the DID left this slot, rather than changing domain in it.
+    pub fn moved_away(&self, mnemonic: &str) -> bool {
+        self.new_mnemonic != mnemonic
+    }
+}
+
+/// Retention window from `hosting.moved_did_retention`, in seconds.
+pub fn retention_secs(config: &AppConfig) -> Result<u64, AppError> {
+    parse_grace_string(&config.hosting.moved_did_retention).map_err(|e| {
+        AppError::Internal(format!(
+            "config [hosting] moved_did_retention='{}' is invalid: {e}",
+            config.hosting.moved_did_retention
+        ))
+    })
+}
+
+/// The slot a `did:webvh` identifier lives at.
+pub fn mnemonic_for(did: &str) -> Result<String, AppError> {
+    mnemonic_from_did(did)
+        .ok_or_else(|| AppError::Validation(format!("'{did}' is not a did:webvh identifier")))
+}
+
+pub async fn get(dids_ks: &KeyspaceHandle, mnemonic: &str) -> Result<Option<MovedStub>, AppError> {
+    dids_ks.get(moved_stub_key(mnemonic)).await
+}
+
+/// The identifier `mnemonic` answered to before an in-place move, while
+/// its stub is live. Read errors are logged and treated as "none": the
+/// caller is a sync push, which must not fail over the stub.
+pub async fn previous_did_id(dids_ks: &KeyspaceHandle, mnemonic: &str) -> Option<String> {
+    match get(dids_ks, mnemonic).await {
+        Ok(Some(stub)) if !stub.moved_away(mnemonic) && stub.expires_at > now_epoch() => {
+            Some(stub.old_did)
+        }
+        Ok(_) => None,
+        Err(e) => {
+            warn!(mnemonic, error = %e, "failed to read moved-DID stub");
+            None
+        }
+    }
+}
+
+/// Refuse writes to a slot the DID has moved away from.
+pub async fn ensure_not_moved_away(
+    dids_ks: &KeyspaceHandle,
+    mnemonic: &str,
+) -> Result<(), AppError> {
+    match get(dids_ks, mnemonic).await? {
+        Some(stub) if stub.moved_away(mnemonic) => Err(AppError::Conflict(format!(
+            "this DID has moved to '{}'; the old slot is read-only",
+            stub.new_mnemonic
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Retire every stub past its `expires_at`. Returns how many went.
+///
+/// A stub slot is deleted outright and servers are told to drop it, but
+/// only while it still holds the old identifier: anything else under the
+/// old mnemonic is no longer the stub and is left alone. An in-place move
+/// is re-synced so servers stop resolving the old host. Each retirement
+/// is audited and raised as a webhook; outbox rows are picked up on the
+/// worker's next tick.
+pub async fn expire_stubs(state: &AppState, now: u64) -> Result<u64, AppError> {
+    let store = &state.store;
+    let dids_ks = &state.dids_ks;
+    let registry_ks = store.keyspace(KS_REGISTRY)?;
+    let mut expired = 0;
+    for (k, v) in dids_ks.prefix_iter_raw("moved:").await? {
+        let key = String::from_utf8_lossy(&k).into_owned();
+        let Some(mnemonic) = key.strip_prefix("moved:").map(str::to_string) else {
+            continue;
+        };
+        let stub = match serde_json::from_slice::<MovedStub>(&v) {
+            Ok(stub) if stub.expires_at > now => continue,
+            Ok(stub) => stub,
+            Err(e) => {
+                warn!(key = %key, error = %e, "dropping malformed moved-DID stub");
+                dids_ks.remove(k).await?;
+                continue;
+            }
+        };
+
+        let _guard = state.path_locks.guard(&mnemonic).await;
+        let before = audit::did_snapshot(state, &mnemonic).await?;
+        let record = dids_ks.get::<DidRecord>(did_key(&mnemonic)).await?;
+        let stub_slot = record
+            .filter(|r| stub.moved_away(&mnemonic) && r.did_id.as_deref() == Some(&stub.old_did));
+        let mut usage = quota::lock(
+            state,
+            [],
+            [stub_slot.as_ref().and_then(quota::record_domain)],
+        )
+        .await;
+        let mut batch = store.batch();
+        if let Some(record) = &stub_slot {
+            usage.record(Some(record), None)?;
+            batch.remove(dids_ks, did_key(&mnemonic));
+            batch.remove(dids_ks, content_log_key(&mnemonic));
+            batch.remove(dids_ks, content_witness_key(&mnemonic));
+            batch.remove(dids_ks, content_did_doc_key(&mnemonic));
+            batch.remove(dids_ks, owner_key(&record.owner, &mnemonic));
+            if let Some(domain) = quota::record_domain(record) {
+                for entry in &record.agent_names {
+                    let name_key = agent_name_key(&domain, &entry.name);
+                    if dids_ks
+                        .get_raw(name_key.clone())
+                        .await?
+                        .is_some_and(|bytes| bytes == mnemonic.as_bytes())
+                    {
+                        batch.remove(dids_ks, name_key);
+                    }
+                }
+            }
+            for key in crate::log_archive::all_keys(dids_ks, &mnemonic).await? {
+                batch.remove(dids_ks, key);
+            }
+        }
+        batch.remove(dids_ks, k);
+        if let Some(record) = &stub_slot {
+            webhooks::emit_did(
+                store,
+                &mut batch,
+                webhooks::DID_DELETED,
+                record,
+                serde_json::json!({ "reason": "moved", "new_did": stub.new_did }),
+            )?;
+        } else if !stub.moved_away(&mnemonic)
+            && let Some(record) = dids_ks.get::<DidRecord>(did_key(&mnemonic)).await?
+        {
+            webhooks::emit_did(
+                store,
+                &mut batch,
+                webhooks::DID_UPDATED,
+                &record,
+                serde_json::json!({ "reason": "moved-did-expired", "previous_did": stub.old_did }),
+            )?;
+        }
+        audit::stage(
+            store,
+            &mut batch,
+            None,
+            audit::Entry {
+                action: "did.move.expire",
+                subject: &mnemonic,
+                // Only the `moved:` row goes when the slot is not the stub.
+                after: if stub_slot.is_some() {
+                    None
+                } else {
+                    before.clone()
+                },
+                before,
+            },
+        )?;
+        usage.stage(state, &mut batch).await?;
+        batch.commit().await?;
+        drop(usage);
+        audit::flush(state).await;
+        webhooks::wake();
+
+        if stub_slot.is_some() {
+            crate::server_push::queue_did_delete(store, &registry_ks, &mnemonic).await;
+        } else if !stub.moved_away(&mnemonic) {
+            let signer = crate::audit::signing_key(state).map(|(_, key)| key);
+            crate::server_push::queue_did_update(
+                store,
+                dids_ks,
+                &registry_ks,
+                &mnemonic,
+                signer.as_ref(),
+            )
+            .await;
+        }
+        info!(
+            mnemonic = %mnemonic,
+            old_did = %stub.old_did,
+            new_did = %stub.new_did,
+            "moved-DID stub expired"
+        );
+        expired += 1;
+    }
+    Ok(expired)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mnemonic_follows_the_identifier_path() {
+        assert_eq!(
+            mnemonic_for("did:webvh:QmSCID:new.example.com:team:alice").unwrap(),
+            "team/alice"
+        );
+        assert_eq!(
+            mnemonic_for("did:webvh:QmSCID:new.example.com").unwrap(),
+            ".well-known"
+        );
+        assert!(mnemonic_for("did:web:new.example.com:alice").is_err());
+    }
+
+    #[test]
+    fn in_place_moves_keep_their_slot() {
+        let stub = MovedStub {
+            old_did: "did:webvh:Q:old.example.com:alice".into(),
+            new_mnemonic: "alice".into(),
+            new_did: "did:webvh:Q:new.example.com:alice".into(),
+            moved_at: 1,
+            expires_at: 2,
+            moved_by: "did:example:alice".into(),
+        };
+        assert!(!stub.moved_away("alice"));
+        assert!(stub.moved_away("old-alice"));
+    }
+}
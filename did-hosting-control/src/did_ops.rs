@@ -6,23 +6,35 @@
 
 use bip39::Language;
 use did_hosting_common::did_ops::{
-    self, AgentNameEntry, DidRecord, LogEntryInfo, LogMetadata, agent_name_key, content_log_key,
-    content_witness_key, did_key, extract_agent_names, owner_key,
+    self, AgentNameEntry, DidRecord, LogEntryInfo, LogMetadata, LogSummary, agent_name_key,
+    content_did_doc_key, content_log_key, content_witness_key, did_key, extract_agent_names,
+    owner_key,
 };
 use did_hosting_common::server::acl::validate_did_format;
+use did_hosting_common::server::deactivation::{
+    deactivation_retention_secs, ensure_not_deactivated,
+};
 use did_hosting_common::server::error::AgentNameError;
 use did_hosting_common::server::identity::mnemonic_from_did;
 use did_hosting_common::server::mnemonic::{
     validate_agent_name, validate_agent_name_binding, validate_custom_path, validate_mnemonic,
+    validate_mnemonic_for,
 };
 use did_hosting_common::{CheckNameResponse, DidListEntry, RequestUriResponse};
 use rand::random_range;
 use tracing::{debug, info, warn};
 
+use crate::audit;
 use crate::auth::AuthClaims;
+use crate::consent;
+use crate::did_import::{self, ImportOffer, ImportSource, PendingImport};
+use crate::did_move::{self, MovedStub};
 use crate::error::AppError;
+use crate::log_archive::{self, ArchivedEntry, ArchivedEntryInfo};
+use crate::quota;
 use crate::server::AppState;
 use crate::store::KeyspaceHandle;
+use crate::webhooks;
 
 /// Run the T20 safety check before any storage write on an inbound
 /// create / publish.
@@ -43,6 +55,11 @@ async fn check_did_host_safety(
     use did_hosting_common::server::acl::{AclEntry, get_acl_entry};
     use did_hosting_common::server::domain::DomainScope;
 
+    if auth.api_key.is_some() {
+        let host = did_hosting_common::server::domain::extract_did_host(did_id)?;
+        check_api_key_domain(auth, Some(&host))?;
+    }
+
     // Look up the caller's ACL entry. In production an authenticated
     // caller always has one — the auth extractor enforces it.
     // In unit-test paths that call did_ops directly without seeding
@@ -96,6 +113,61 @@ fn verify_did_log_proofs(content: &str) -> Result<(), AppError> {
         .map_err(|m| AppError::validation(ValidationKind::InvalidLog, m))
 }
 
+/// [`did_ops::inspect_did_log`] with the same `InvalidLog` tag: derives the
+/// method from the log and verifies it as that method. webvh logs get the
+/// full [`verify_did_log_proofs`] pipeline.
+fn inspect_did_log(content: &str, did_doc: Option<&str>) -> Result<LogSummary, AppError> {
+    use did_hosting_common::server::error::ValidationKind;
+    did_ops::inspect_did_log(content, did_doc)
+        .map_err(|m| AppError::validation(ValidationKind::InvalidLog, m))
+}
+
+/// Refuse an operation that only makes sense on a did:webvh log (it edits
+/// the log line by line, or relies on webvh portability).
+fn ensure_webvh(record: &DidRecord, what: &str) -> Result<(), AppError> {
+    if record.method != "webvh" {
+        return Err(AppError::Validation(format!(
+            "{what} is only supported for did:webvh, not did:{}",
+            record.method
+        )));
+    }
+    Ok(())
+}
+
+/// The `(log, did.json)` pair a [`DidRegisterRequest`] carries, for
+/// [`register_did_atomic`]. The declared method must match the log.
+///
+/// [`DidRegisterRequest`]: did_hosting_common::DidRegisterRequest
+pub fn register_payload(
+    req: &did_hosting_common::DidRegisterRequest,
+) -> Result<(String, Option<String>), AppError> {
+    let (method, payload) = req.resolve().map_err(AppError::Validation)?;
+    if method == "web" {
+        // A did:web document has no log to check the claim against; it
+        // is published through `PUT /api/dids/{mnemonic}`.
+        return Err(AppError::Validation(
+            "did:web is not registered atomically; publish its did.json to the slot instead".into(),
+        ));
+    }
+    let did_log = String::from_utf8(payload).map_err(|e| {
+        AppError::Validation(format!("{method} `did_data` is not valid UTF-8: {e}"))
+    })?;
+    let detected = did_ops::detect_log_method(&did_log);
+    if detected != Some(method.as_str()) {
+        return Err(AppError::Validation(format!(
+            "`did_data` is not a did:{method} log (looks like {})",
+            detected.map_or("an unknown format".to_string(), |m| format!("did:{m}"))
+        )));
+    }
+    let did_doc = req
+        .did_document
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| AppError::Validation(format!("invalid `did_document`: {e}")))?;
+    Ok((did_log, did_doc))
+}
+
 // ---------------------------------------------------------------------------
 // Mnemonic generation (same logic as did-hosting-server/src/mnemonic.rs)
 // ---------------------------------------------------------------------------
@@ -149,9 +221,53 @@ async fn get_authorized_record(
         );
         return Err(AppError::Forbidden("not the owner of this DID".into()));
     }
+    check_api_key_domain(auth, record_domain(&record).as_deref())?;
     Ok(record)
 }
 
+/// The domain a record is served under: its `domain`, or for a record
+/// stored without one, its DID's host.
+fn record_domain(record: &DidRecord) -> Option<String> {
+    if !record.domain.is_empty() {
+        return Some(record.domain.clone());
+    }
+    record
+        .did_id
+        .as_deref()
+        .and_then(|id| did_hosting_common::server::domain::extract_did_host(id).ok())
+}
+
+/// An API key confined to domains acts only on DIDs under them. `domain`
+/// is `None` when it is not known (a create without one),
+/// which only an unconfined key gets past.
+fn check_api_key_domain(auth: &AuthClaims, domain: Option<&str>) -> Result<(), AppError> {
+    let Some(scope) = auth.api_key.as_ref() else {
+        return Ok(());
+    };
+    if !api_key_sees(auth, domain) {
+        warn!(
+            caller = %auth.did,
+            key_id = %scope.id,
+            domain = domain.unwrap_or("<unknown>"),
+            "access denied: domain outside the API key's scope"
+        );
+        return Err(AppError::Forbidden(
+            "this API key is not scoped to this domain".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Whether the caller's API-key domain scope covers `domain` (`None`:
+/// unknown, which only an unrestricted key gets past). Always true for
+/// sessions.
+fn api_key_sees(auth: &AuthClaims, domain: Option<&str>) -> bool {
+    auth.api_key.as_ref().is_none_or(|scope| match domain {
+        Some(domain) => scope.allows_domain(domain),
+        None => scope.domains.is_empty(),
+    })
+}
+
 /// Resolve a custom path during create, applying force-replace semantics.
 ///
 /// If the path is free, returns it unchanged. If taken and `force` is false,
@@ -197,6 +313,7 @@ async fn resolve_path_for_create(
     let mut batch = state.store.batch();
     batch.remove(&state.dids_ks, content_log_key(custom_path));
     batch.remove(&state.dids_ks, content_witness_key(custom_path));
+    batch.remove(&state.dids_ks, content_did_doc_key(custom_path));
     if existing.owner != auth.did {
         batch.remove(&state.dids_ks, owner_key(&existing.owner, custom_path));
     }
@@ -237,6 +354,52 @@ pub async fn create_did(
     use crate::acl::Role;
     use crate::auth::session::now_epoch;
 
+    check_api_key_domain(auth, domain)?;
+
+    // A forced replace reads the slot it overwrites to move its usage out
+    // of its domain counter; hold the path so that read stays current.
+    let _path_guard = match path {
+        Some(p) => Some(state.path_locks.guard(p).await),
+        None => None,
+    };
+
+    // A fresh slot counts against the caller's DID quota. A forced
+    // replace of the caller's own slot keeps the count unchanged (and an
+    // admin takeover is quota-exempt), so only charge when the path is
+    // free or server-assigned.
+    let fresh = match path {
+        Some(p) => is_path_available(&state.dids_ks, p).await?,
+        None => true,
+    };
+    let replaced = match path.filter(|_| !fresh) {
+        Some(p) => state.dids_ks.get::<DidRecord>(did_key(p)).await?,
+        None => None,
+    };
+    let mut usage = quota::lock(
+        state,
+        [auth.did.as_str()],
+        [
+            domain.map(str::to_string),
+            replaced.as_ref().and_then(quota::record_domain),
+        ],
+    )
+    .await;
+    if fresh {
+        quota::check(
+            state,
+            auth,
+            &quota::Charge {
+                owner: &auth.did,
+                domain,
+                new_dids: 1,
+                old_size: 0,
+                new_size: 0,
+            },
+            &usage,
+        )
+        .await?;
+    }
+
     let mnemonic = match path {
         Some(custom_path) if custom_path == ".well-known" => {
             if auth.role != Role::Admin {
@@ -254,12 +417,16 @@ pub async fn create_did(
             .await?
         }
         Some(custom_path) => {
-            validate_custom_path(custom_path)?;
+            // `.well-known` is handled above; this also admits did:webs and
+            // did:webplus slots, whose last segment is mixed-case.
+            validate_mnemonic(custom_path)?;
             let conflict_msg = format!("path '{custom_path}' is already taken");
             resolve_path_for_create(state, custom_path, auth, force, &conflict_msg).await?
         }
         None => generate_unique_mnemonic(&state.dids_ks).await?,
     };
+    // Non-empty only on a forced replace of an existing slot.
+    let before = audit::did_snapshot(state, &mnemonic).await?;
 
     let now = now_epoch();
     let record = DidRecord {
@@ -273,6 +440,8 @@ pub async fn create_did(
         disabled: false,
         deleted_at: None,
 
+        // Placeholder: an empty slot has no log to read a method from. The
+        // first publish sets it (see `prepare_republish`).
         method: "webvh".to_string(),
         // Persist the resolved domain so the per-domain UI filters and
         // the dashboard's per-domain stat cards see the new DID on the
@@ -286,6 +455,8 @@ pub async fn create_did(
         // `publish_did` fills this on first upload.
         services: None,
         agent_names: Vec::new(),
+        deactivated_at: None,
+        sync_head: None,
     };
 
     let mut batch = state.store.batch();
@@ -295,7 +466,21 @@ pub async fn create_did(
         owner_key(&auth.did, &mnemonic),
         mnemonic.as_bytes().to_vec(),
     );
+    usage.record(replaced.as_ref(), Some(&record))?;
+    usage.stage(state, &mut batch).await?;
+    let after = audit::did_snapshot_of(state, &record, None).await?;
+    audit::stage_did(
+        &state.store,
+        &mut batch,
+        auth,
+        "did.create",
+        &mnemonic,
+        before,
+        after,
+    )?;
     batch.commit().await?;
+    drop(usage);
+    audit::flush(state).await;
 
     // Build the DID URL using the did_hosting_url if configured, else public_url
     let base_url = state
@@ -332,9 +517,10 @@ pub async fn create_did(
 /// - Slot exists, any other case → `Forbidden`.
 ///
 /// Content validation:
-/// - `did_log` must parse as valid did.jsonl whose latest entry
-///   resolves to a `did:webvh:` identifier.
-/// - That identifier's host (and optional port) must match this
+/// - `did_log` must be a valid log of an enabled method, which is read off
+///   the log itself ([`did_ops::detect_log_method`]); `did_doc` is the
+///   `did.json` of a did:webs KEL and is refused for other methods.
+/// - The DID's host (and optional port) must match this
 ///   server's hosting URL, AND its path component must match the
 ///   requested `path`. This stops an admin from uploading
 ///   arbitrary `did.jsonl` content under a path they happen to own
@@ -344,6 +530,7 @@ pub async fn register_did_atomic(
     state: &AppState,
     path: &str,
     did_log: &str,
+    did_doc: Option<&str>,
     force: bool,
 ) -> Result<RequestUriResponse, AppError> {
     use crate::acl::Role;
@@ -366,8 +553,8 @@ pub async fn register_did_atomic(
             "only admins can register the root DID".into(),
         ));
     }
-    validate_mnemonic(path)?;
-    verify_did_log_proofs(did_log)?;
+    let summary = inspect_did_log(did_log, did_doc)?;
+    validate_mnemonic_for(summary.method, path)?;
 
     let server_base_url = state
         .config
@@ -381,9 +568,7 @@ pub async fn register_did_atomic(
             )
         })?;
 
-    let did_id = extract_did_id(did_log).ok_or_else(|| {
-        AppError::Validation("did_log's latest entry has no resolvable did:webvh state.id".into())
-    })?;
+    let did_id = summary.did_id.clone();
 
     // T20b: multi-domain safety check. Runs before the legacy
     // `validate_did_id_matches_request` host equality check. In
@@ -408,8 +593,16 @@ pub async fn register_did_atomic(
     // exit too.
     let _path_guard = state.path_locks.guard(path).await;
 
+    let before = audit::did_snapshot(state, path).await?;
     let existing: Option<DidRecord> = state.dids_ks.get(did_key(path)).await?;
 
+    // Re-registering replaces the log, which would erase a deactivation
+    // or overwrite a moved DID's read-only stub.
+    if let Some(rec) = &existing {
+        ensure_not_deactivated(rec)?;
+    }
+    did_move::ensure_not_moved_away(&state.dids_ks, path).await?;
+
     let owner_changed = match &existing {
         Some(rec) if rec.owner == auth.did => false,
         Some(rec) => {
@@ -441,6 +634,36 @@ pub async fn register_did_atomic(
         None => false,
     };
 
+    let reg_domain =
+        did_hosting_common::server::domain::extract_did_host(&did_id).unwrap_or_default();
+
+    let (new_dids, old_size) = match &existing {
+        Some(rec) if !owner_changed => (0, rec.content_size),
+        _ => (1, 0),
+    };
+    let mut usage = quota::lock(
+        state,
+        [auth.did.as_str()],
+        [
+            Some(reg_domain.clone()),
+            existing.as_ref().and_then(quota::record_domain),
+        ],
+    )
+    .await;
+    quota::check(
+        state,
+        auth,
+        &quota::Charge {
+            owner: &auth.did,
+            domain: Some(&reg_domain),
+            new_dids,
+            old_size,
+            new_size: did_log.len() as u64,
+        },
+        &usage,
+    )
+    .await?;
+
     // Preserve created_at when the same owner is re-publishing; reset
     // on takeover or fresh allocation.
     let now = now_epoch();
@@ -460,11 +683,11 @@ pub async fn register_did_atomic(
         disabled: false,
         deleted_at: None,
 
+        method: summary.method.to_string(),
         // T12: legacy construction site; T13 migration fills `domain`.
-        method: "webvh".to_string(),
         domain: String::new(),
 
-        services: extract_service_types(did_log),
+        services: summary.services(),
 
         // Start from the existing registry (this path also re-registers an
         // EXISTING slot, where `Vec::new()` would silently drop every name),
@@ -475,6 +698,8 @@ pub async fn register_did_atomic(
             .as_ref()
             .map(|r| r.agent_names.clone())
             .unwrap_or_default(),
+        deactivated_at: summary.deactivated.then_some(now),
+        sync_head: None,
     };
 
     // Reconcile the authoritative registry against what the document claims —
@@ -487,21 +712,49 @@ pub async fn register_did_atomic(
     // collision check needs. A fresh slot is the *easiest* place to attempt a
     // name capture — nothing else about registering constrains what the
     // submitted document may claim.
-    let reg_domain =
-        did_hosting_common::server::domain::extract_did_host(&did_id).unwrap_or_default();
-    let (claimed, released) =
-        reconcile_agent_names(state, &mut new_record, path, did_log, &reg_domain, now).await?;
+    let (claimed, released) = reconcile_agent_names(
+        state,
+        &mut new_record,
+        path,
+        summary.agent_names(&reg_domain),
+        &reg_domain,
+        now,
+        None,
+    )
+    .await?;
 
     // 4. Single-batch atomic write: record, log content, owner index, agent-name
     //    index; plus old-owner cleanup on takeover. From a resolver's
     //    perspective there is no point at which the slot is half-updated —
     //    either old-content/old-record or new-content/new-record.
+    // On takeover the prior owner's rolled-back entries go too: they
+    // belong to a different chain, and are not the new owner's to read.
+    let stale_archive = if owner_changed {
+        log_archive::all_keys(&state.dids_ks, path).await?
+    } else {
+        Vec::new()
+    };
+    if owner_changed {
+        // The prior owner's witness file is removed below.
+        new_record.set_sync_head(did_log.as_bytes(), None);
+    } else {
+        refresh_sync_head(state, &mut new_record, path, did_log).await?;
+    }
+
     let mut batch = state.store.batch();
     batch.insert_raw(
         &state.dids_ks,
         content_log_key(path),
         did_log.as_bytes().to_vec(),
     );
+    match did_doc {
+        Some(doc) => batch.insert_raw(
+            &state.dids_ks,
+            content_did_doc_key(path),
+            doc.as_bytes().to_vec(),
+        ),
+        None => batch.remove(&state.dids_ks, content_did_doc_key(path)),
+    }
     if owner_changed {
         let prev = existing
             .as_ref()
@@ -510,6 +763,9 @@ pub async fn register_did_atomic(
         batch.remove(&state.dids_ks, content_witness_key(path));
         batch.remove(&state.dids_ks, owner_key(&prev.owner, path));
     }
+    for key in stale_archive {
+        batch.remove(&state.dids_ks, key);
+    }
     batch.insert(&state.dids_ks, did_key(path), &new_record)?;
     batch.insert_raw(
         &state.dids_ks,
@@ -526,18 +782,39 @@ pub async fn register_did_atomic(
     for name in &released {
         batch.remove(&state.dids_ks, agent_name_key(&reg_domain, name));
     }
+    webhooks::emit_did_log(&state.store, &mut batch, &new_record, did_log)?;
+    usage.record(existing.as_ref(), Some(&new_record))?;
+    usage.stage(state, &mut batch).await?;
+    let after = audit::did_snapshot_of(state, &new_record, Some(did_log)).await?;
+    audit::stage_did(
+        &state.store,
+        &mut batch,
+        auth,
+        "did.register",
+        path,
+        before,
+        after,
+    )?;
     batch.commit().await?;
+    drop(usage);
+    audit::flush(state).await;
+    webhooks::wake();
 
     // Same rationale as `publish_did`: the atomic register path commits
     // a new log entry, so it must advance the update counters when the
     // control plane is authoritative for stats.
     state.stats_collector.record_update(path);
 
-    let did_url = format!("{}/{path}/did.jsonl", server_base_url.trim_end_matches('/'));
+    let did_url = format!(
+        "{}/{path}/{}",
+        server_base_url.trim_end_matches('/'),
+        did_ops::resolution_file(summary.method)
+    );
 
     info!(
         did = %auth.did,
         path = %path,
+        method = summary.method,
         version = version_count,
         owner_changed,
         "DID atomically registered on control plane"
@@ -549,37 +826,80 @@ pub async fn register_did_atomic(
     })
 }
 
+/// Set `record`'s sync head for `log` stored alongside the witness file
+/// already held for `mnemonic`.
+async fn refresh_sync_head(
+    state: &AppState,
+    record: &mut DidRecord,
+    mnemonic: &str,
+    log: &str,
+) -> Result<(), AppError> {
+    let witness = state.dids_ks.get_raw(content_witness_key(mnemonic)).await?;
+    record.set_sync_head(log.as_bytes(), witness.as_deref());
+    Ok(())
+}
+
 /// Shared front-half of a new-version publish: authorize the caller, verify
 /// the submitted log's cryptographic proofs, run the host/domain safety
 /// checks, and advance the loaded record's version/size/did_id/services/domain
 /// fields — the work `publish_did` and every agent-name operation do
 /// identically before they commit.
 ///
-/// Returns the prepared, **uncommitted** record and the DID's resolved hosting
-/// domain (the authority an agent name is scoped to). The caller commits it,
-/// optionally alongside extra batch operations, so a single implementation of
-/// the authorize/verify/safety pipeline backs both the plain publish and the
+/// Returns the prepared, **uncommitted** record, the DID's resolved hosting
+/// domain (the authority an agent name is scoped to), the agent names the
+/// submitted document claims on that domain, and the domain's usage lock
+/// with the record's change already counted. The caller commits it,
+/// optionally alongside extra batch operations, staging the usage and
+/// holding its lock until the commit, so a single implementation of the
+/// authorize/verify/safety pipeline backs both the plain publish and the
 /// name-binding ops.
 async fn prepare_republish(
     auth: &AuthClaims,
     state: &AppState,
     mnemonic: &str,
     did_log: &str,
+    did_doc: Option<&str>,
     request_domain: Option<&str>,
-) -> Result<(DidRecord, String), AppError> {
+) -> Result<(DidRecord, String, Vec<String>, quota::UsageLock), AppError> {
     use crate::auth::session::now_epoch;
 
     validate_mnemonic(mnemonic)?;
     let mut record = get_authorized_record(&state.dids_ks, mnemonic, auth).await?;
+    did_move::ensure_not_moved_away(&state.dids_ks, mnemonic).await?;
+    ensure_not_deactivated(&record)?;
+
+    // A did:webs KEL is checked against the `did.json` submitted with it,
+    // or else the one already stored for it; a rotation keeps the AID, so
+    // the document still names it.
+    let stored_doc = match (did_doc, did_ops::detect_log_method(did_log)) {
+        (Some(doc), _) => Some(doc.to_string()),
+        (None, Some("webs")) => state
+            .dids_ks
+            .get_raw(content_did_doc_key(mnemonic))
+            .await?
+            .and_then(|bytes| String::from_utf8(bytes).ok()),
+        _ => None,
+    };
 
-    // Proof verification subsumes the structural check. The
+    // Proof verification subsumes the structural check. For webvh the
     // didwebvh-rs verifier walks the chain, validates each entry's
     // signature against `parameters.updateKeys`, and rejects any
     // tampered or post-deactivation entries.
-    verify_did_log_proofs(did_log)?;
+    let summary = inspect_did_log(did_log, stored_doc.as_deref())?;
+    validate_mnemonic_for(summary.method, mnemonic)?;
+
+    // The method is fixed by the first log. A reserved slot has none yet.
+    if record.version_count > 0 && record.method != summary.method {
+        return Err(AppError::Validation(format!(
+            "slot '{mnemonic}' holds a did:{} log; cannot publish a did:{} log over it",
+            record.method, summary.method
+        )));
+    }
+    record.method = summary.method.to_string();
 
     let new_size = did_log.len() as u64;
-    let did_id_val = extract_did_id(did_log);
+    let did_id_val = Some(summary.did_id.clone());
+    let deactivating = summary.deactivated;
 
     // T20b: same safety check as register_did_atomic — the embedded
     // DID's host must be a configured active domain on this server
@@ -609,17 +929,53 @@ async fn prepare_republish(
         }
     }
 
+    // Charge the growth to the slot's owner (not the caller — an admin
+    // publishing on a tenant's behalf is exempt anyway) and to the domain
+    // the DID is hosted in. A deactivating entry is charged like any other:
+    // it adds no DID, and the DID only leaves the owner's usage once the
+    // entry has landed.
+    let quota_domain = (!record.domain.is_empty())
+        .then(|| record.domain.clone())
+        .or_else(|| {
+            did_id_val
+                .as_deref()
+                .and_then(|d| did_hosting_common::server::domain::extract_did_host(d).ok())
+        });
+    let mut usage = quota::lock(
+        state,
+        [record.owner.as_str()],
+        [quota_domain.clone(), quota::record_domain(&record)],
+    )
+    .await;
+    quota::check(
+        state,
+        auth,
+        &quota::Charge {
+            owner: &record.owner,
+            domain: quota_domain.as_deref(),
+            new_dids: 0,
+            old_size: record.content_size,
+            new_size,
+        },
+        &usage,
+    )
+    .await?;
+    let stored = record.clone();
+
     record.updated_at = now_epoch();
     record.version_count += 1;
     record.did_id = did_id_val.clone();
     record.content_size = new_size;
+    if deactivating {
+        record.deactivated_at = Some(record.updated_at);
+    }
 
     // Recompute the badge cache from the document we're about to store.
     // Unconditional, not a fill-if-empty: a publish can add or drop a
     // service (e.g. a node that stops advertising DIDComm), so a stale
     // non-empty cache is just as wrong as a missing one. Also self-heals a
     // legacy `None` if the M-02 boot sweep hasn't reached this record.
-    record.services = extract_service_types(did_log);
+    record.services = summary.services();
 
     // Backfill `record.domain` from the embedded DID's host on first
     // publish for records that pre-date the `request_uri` resolver fix
@@ -645,8 +1001,10 @@ async fn prepare_republish(
             .and_then(|d| did_hosting_common::server::domain::extract_did_host(d).ok())
             .unwrap_or_default()
     };
+    let claims = summary.agent_names(&domain);
+    usage.record(Some(&stored), Some(&record))?;
 
-    Ok((record, domain))
+    Ok((record, domain, claims, usage))
 }
 
 /// Publish (upload) a did.jsonl log for an existing DID slot.
@@ -705,16 +1063,20 @@ async fn prepare_republish(
 /// Callers must hold `state.path_locks.guard(mnemonic)`: the collision check
 /// below and the index write it authorises have to be one critical section, or
 /// two concurrent publishes each see a free name and both claim it.
+///
+/// `previous` is the slot a moving DID is leaving: names indexed to it are the
+/// DID's own, not another's, so claiming them again is not a collision.
 async fn reconcile_agent_names(
     state: &AppState,
     record: &mut DidRecord,
     mnemonic: &str,
-    did_log: &str,
+    claims: Vec<String>,
     domain: &str,
     now: u64,
+    previous: Option<&str>,
 ) -> Result<(Vec<String>, Vec<String>), AppError> {
     let mut claimed = Vec::new();
-    for name in extract_agent_names(did_log, domain) {
+    for name in claims {
         match validate_agent_name_binding(&name, mnemonic) {
             Ok(()) => {}
             // Reserved is a refusal, not a skip — see above. This also covers a
@@ -745,6 +1107,7 @@ async fn reconcile_agent_names(
         // state, so this cannot be used to wedge someone's key rotation.
         if let Some(bytes) = state.dids_ks.get_raw(agent_name_key(domain, &name)).await?
             && bytes != mnemonic.as_bytes()
+            && previous.is_none_or(|p| bytes != p.as_bytes())
         {
             warn!(
                 mnemonic = %mnemonic,
@@ -801,12 +1164,94 @@ async fn reconcile_agent_names(
     Ok((claimed, released))
 }
 
+/// Publish a new version of a DID's log.
+///
+/// A publish of the service's own DID can rotate its identity, so it first
+/// waits on any `identity/rotate` consent policy (see
+/// [`approve_own_did_publish`]).
 pub async fn publish_did(
     auth: &AuthClaims,
     state: &AppState,
     mnemonic: &str,
     did_log: &str,
     request_domain: Option<&str>,
+) -> Result<(), AppError> {
+    let approvals = approve_own_did_publish(auth, state, mnemonic).await?;
+    audit::with_approvals(
+        approvals,
+        publish_approved(auth, state, mnemonic, did_log, None, request_domain),
+    )
+    .await
+}
+
+/// The approvals an `identity/rotate` consent policy requires before the
+/// service's own DID is republished; none for any other DID. The caller is
+/// authorized first, so only a party that could publish asks the
+/// approvers.
+async fn approve_own_did_publish(
+    auth: &AuthClaims,
+    state: &AppState,
+    mnemonic: &str,
+) -> Result<Vec<serde_json::Value>, AppError> {
+    if !consent::is_own_did(state, mnemonic) {
+        return Ok(Vec::new());
+    }
+    get_authorized_record(&state.dids_ks, mnemonic, auth).await?;
+    consent::require(
+        state,
+        &auth.did,
+        consent::Gate {
+            task: consent::IDENTITY_ROTATE,
+            role: None,
+            subject: mnemonic,
+            note: format!(
+                "Publish a new version of this service's own DID ({}), which may rotate its keys",
+                state.config.server_did.as_deref().unwrap_or(mnemonic)
+            ),
+        },
+    )
+    .await
+}
+
+/// Replace the `did.json` served next to a did:webs KEL. The document is
+/// checked against the stored KEL and committed as a new version of it,
+/// through the same pipeline as [`publish_did`]. A key rotation that needs
+/// a new KEL and document at once goes through [`register_did_atomic`].
+pub async fn publish_did_document(
+    auth: &AuthClaims,
+    state: &AppState,
+    mnemonic: &str,
+    did_doc: &str,
+) -> Result<(), AppError> {
+    validate_mnemonic(mnemonic)?;
+    let record = get_authorized_record(&state.dids_ks, mnemonic, auth).await?;
+    if record.method != "webs" {
+        return Err(AppError::Validation(format!(
+            "a separate DID document is only accepted for did:webs, not did:{}",
+            record.method
+        )));
+    }
+    let kel = state
+        .dids_ks
+        .get_raw(content_log_key(mnemonic))
+        .await?
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| AppError::NotFound(format!("no KEL published for {mnemonic}")))?;
+    let approvals = approve_own_did_publish(auth, state, mnemonic).await?;
+    audit::with_approvals(
+        approvals,
+        publish_approved(auth, state, mnemonic, &kel, Some(did_doc), None),
+    )
+    .await
+}
+
+async fn publish_approved(
+    auth: &AuthClaims,
+    state: &AppState,
+    mnemonic: &str,
+    did_log: &str,
+    did_doc: Option<&str>,
+    request_domain: Option<&str>,
 ) -> Result<(), AppError> {
     // Serialise the read-modify-write on this slot. `reconcile_agent_names`
     // checks the name index and then writes it, and the agent-name verbs take
@@ -815,8 +1260,9 @@ pub async fn publish_did(
     // commit.
     let _guard = state.path_locks.guard(mnemonic).await;
 
-    let (mut record, domain) =
-        prepare_republish(auth, state, mnemonic, did_log, request_domain).await?;
+    let before = audit::did_snapshot(state, mnemonic).await?;
+    let (mut record, domain, claims, usage) =
+        prepare_republish(auth, state, mnemonic, did_log, did_doc, request_domain).await?;
     let new_size = record.content_size;
     let now = record.updated_at;
 
@@ -824,7 +1270,8 @@ pub async fn publish_did(
     // applying the same preconditions `set` does, so this path cannot be used
     // to capture a reserved name or take one from another DID.
     let (claimed, released) =
-        reconcile_agent_names(state, &mut record, mnemonic, did_log, &domain, now).await?;
+        reconcile_agent_names(state, &mut record, mnemonic, claims, &domain, now, None).await?;
+    refresh_sync_head(state, &mut record, mnemonic, did_log).await?;
 
     let mut batch = state.store.batch();
     batch.insert_raw(
@@ -832,6 +1279,13 @@ pub async fn publish_did(
         content_log_key(mnemonic),
         did_log.as_bytes().to_vec(),
     );
+    if let Some(doc) = did_doc {
+        batch.insert_raw(
+            &state.dids_ks,
+            content_did_doc_key(mnemonic),
+            doc.as_bytes().to_vec(),
+        );
+    }
     batch.insert(&state.dids_ks, did_key(mnemonic), &record)?;
     for name in &claimed {
         batch.insert_raw(
@@ -843,7 +1297,22 @@ pub async fn publish_did(
     for name in &released {
         batch.remove(&state.dids_ks, agent_name_key(&domain, name));
     }
+    webhooks::emit_did_log(&state.store, &mut batch, &record, did_log)?;
+    usage.stage(state, &mut batch).await?;
+    let after = audit::did_snapshot_of(state, &record, Some(did_log)).await?;
+    audit::stage_did(
+        &state.store,
+        &mut batch,
+        auth,
+        "did.publish",
+        mnemonic,
+        before,
+        after,
+    )?;
     batch.commit().await?;
+    drop(usage);
+    audit::flush(state).await;
+    webhooks::wake();
 
     // Mirror did-hosting-server's `record_update` call so total_updates /
     // last_updated_at advance when the control plane is the authoritative
@@ -916,6 +1385,14 @@ impl AgentNameOp {
     fn requires_claim(self) -> bool {
         matches!(self, AgentNameOp::Activate)
     }
+
+    fn audit_action(self) -> &'static str {
+        match self {
+            AgentNameOp::Activate => "agent-name.activate",
+            AgentNameOp::Remove => "agent-name.remove",
+            AgentNameOp::Park => "agent-name.park",
+        }
+    }
 }
 
 /// Set the binding state of an agent name per
@@ -995,6 +1472,25 @@ async fn apply_agent_name_op(
     did_log: &str,
     request_domain: Option<&str>,
     op: AgentNameOp,
+) -> Result<DidRecord, AppError> {
+    // These verbs republish the document too — same gate as `publish_did`.
+    let approvals = approve_own_did_publish(auth, state, mnemonic).await?;
+    audit::with_approvals(
+        approvals,
+        apply_agent_name_op_approved(auth, state, mnemonic, name, did_log, request_domain, op),
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn apply_agent_name_op_approved(
+    auth: &AuthClaims,
+    state: &AppState,
+    mnemonic: &str,
+    name: &str,
+    did_log: &str,
+    request_domain: Option<&str>,
+    op: AgentNameOp,
 ) -> Result<DidRecord, AppError> {
     use crate::auth::session::now_epoch;
 
@@ -1010,18 +1506,18 @@ async fn apply_agent_name_op(
     // op on the same DID.
     let _guard = state.path_locks.guard(mnemonic).await;
 
+    let before = audit::did_snapshot(state, mnemonic).await?;
+
     // Authorize + verify the submitted document + advance the record. This
     // yields not_owner / invalid_did_data / unknown_domain exactly as a plain
     // publish would.
-    let (mut record, domain) =
-        prepare_republish(auth, state, mnemonic, did_log, request_domain).await?;
+    let (mut record, domain, claims, usage) =
+        prepare_republish(auth, state, mnemonic, did_log, None, request_domain).await?;
 
     // The gate: does the submitted document claim the name on this domain?
-    // `extract_agent_names` canonicalises through the `agent-names` crate, so
+    // `agent_names_from_doc` canonicalises through the `agent-names` crate, so
     // the comparison is byte-identical to what a resolver will later do.
-    let claimed = extract_agent_names(did_log, &domain)
-        .iter()
-        .any(|n| n == &name);
+    let claimed = claims.iter().any(|n| n == &name);
     if claimed != op.requires_claim() {
         return Err(AgentNameError::AlsoKnownAsMismatch.into());
     }
@@ -1068,6 +1564,8 @@ async fn apply_agent_name_op(
         }
     };
 
+    refresh_sync_head(state, &mut record, mnemonic, did_log).await?;
+
     // Commit the new document version, the updated record, and the name-index
     // change in one atomic batch — the guarantee the specification requires.
     let mut batch = state.store.batch();
@@ -1084,7 +1582,22 @@ async fn apply_agent_name_op(
         IndexWrite::Remove => batch.remove(&state.dids_ks, index_key),
         IndexWrite::Keep => {}
     }
+    webhooks::emit_did_log(&state.store, &mut batch, &record, did_log)?;
+    usage.stage(state, &mut batch).await?;
+    let after = audit::did_snapshot_of(state, &record, Some(did_log)).await?;
+    audit::stage_did(
+        &state.store,
+        &mut batch,
+        auth,
+        op.audit_action(),
+        mnemonic,
+        before,
+        after,
+    )?;
     batch.commit().await?;
+    drop(usage);
+    audit::flush(state).await;
+    webhooks::wake();
 
     state.stats_collector.record_update(mnemonic);
 
@@ -1125,7 +1638,13 @@ pub async fn upload_witness(
     witness_content: &str,
 ) -> Result<(), AppError> {
     validate_mnemonic(mnemonic)?;
-    get_authorized_record(&state.dids_ks, mnemonic, auth).await?;
+    let mut record = get_authorized_record(&state.dids_ks, mnemonic, auth).await?;
+    did_move::ensure_not_moved_away(&state.dids_ks, mnemonic).await?;
+    let before = state
+        .dids_ks
+        .get_raw(content_witness_key(mnemonic))
+        .await?
+        .and_then(|w| audit::digest(&String::from_utf8_lossy(&w)));
 
     use did_hosting_common::server::error::ValidationKind;
     if witness_content.is_empty() {
@@ -1142,13 +1661,31 @@ pub async fn upload_witness(
         )
     })?;
 
-    state
-        .dids_ks
-        .insert_raw(
-            content_witness_key(mnemonic),
-            witness_content.as_bytes().to_vec(),
-        )
-        .await?;
+    match state.dids_ks.get_raw(content_log_key(mnemonic)).await? {
+        Some(log) => record.set_sync_head(&log, Some(witness_content.as_bytes())),
+        None => record.sync_head = None,
+    }
+
+    let mut batch = state.store.batch();
+    batch.insert_raw(
+        &state.dids_ks,
+        content_witness_key(mnemonic),
+        witness_content.as_bytes().to_vec(),
+    );
+    batch.insert(&state.dids_ks, did_key(mnemonic), &record)?;
+    audit::stage(
+        &state.store,
+        &mut batch,
+        Some(audit::Actor::from(auth)),
+        audit::Entry {
+            action: "did.witness",
+            subject: mnemonic,
+            before,
+            after: audit::digest(&witness_content),
+        },
+    )?;
+    batch.commit().await?;
+    audit::flush(state).await;
 
     info!(did = %auth.did, mnemonic = %mnemonic, "did-witness.json uploaded on control plane");
 
@@ -1228,7 +1765,7 @@ pub async fn list_dids(
     use crate::acl::Role;
 
     if auth.role == Role::Admin && requested_owner.is_none() {
-        return list_all_dids(state).await;
+        return list_all_dids(auth, state).await;
     }
 
     let target_owner = if auth.role == Role::Admin {
@@ -1256,6 +1793,10 @@ pub async fn list_dids(
             if record.owner != target_owner {
                 continue;
             }
+            // A domain-restricted API key lists only its domains' DIDs.
+            if !api_key_sees(auth, record_domain(&record).as_deref()) {
+                continue;
+            }
             let stats_key = format!("stats:{mnemonic}");
             let did_stats: did_hosting_common::DidStats =
                 state.stats_ks.get(stats_key).await?.unwrap_or_default();
@@ -1273,6 +1814,7 @@ pub async fn list_dids(
                 domain: (!record.domain.is_empty()).then(|| record.domain.clone()),
                 agent_names,
                 services: record.services,
+                deactivated_at: record.deactivated_at,
             });
         }
     }
@@ -1289,7 +1831,7 @@ pub async fn list_dids(
 }
 
 /// List all DIDs in the store (admin only).
-async fn list_all_dids(state: &AppState) -> Result<Vec<DidListEntry>, AppError> {
+async fn list_all_dids(auth: &AuthClaims, state: &AppState) -> Result<Vec<DidListEntry>, AppError> {
     let raw = state.dids_ks.prefix_iter_raw("did:").await?;
 
     let mut entries = Vec::with_capacity(raw.len());
@@ -1298,6 +1840,9 @@ async fn list_all_dids(state: &AppState) -> Result<Vec<DidListEntry>, AppError>
             Ok(r) => r,
             Err(_) => continue,
         };
+        if !api_key_sees(auth, record_domain(&record).as_deref()) {
+            continue;
+        }
         let stats_key = format!("stats:{}", record.mnemonic);
         let did_stats: did_hosting_common::DidStats =
             state.stats_ks.get(stats_key).await?.unwrap_or_default();
@@ -1315,6 +1860,7 @@ async fn list_all_dids(state: &AppState) -> Result<Vec<DidListEntry>, AppError>
             domain: (!record.domain.is_empty()).then(|| record.domain.clone()),
             agent_names,
             services: record.services,
+            deactivated_at: record.deactivated_at,
         });
     }
 
@@ -1366,19 +1912,63 @@ pub async fn delete_did(
     mnemonic: &str,
     request_domain: Option<&str>,
 ) -> Result<Option<String>, AppError> {
+    use crate::auth::session::now_epoch;
+
     validate_mnemonic(mnemonic)?;
+    // The deleted record's size comes off its domain counter, so it must
+    // not change underneath us.
+    let _path_guard = state.path_locks.guard(mnemonic).await;
     let record = get_authorized_record(&state.dids_ks, mnemonic, auth).await?;
 
     let did_id = record.did_id.clone();
 
     ensure_slot_domain_matches(&record, request_domain)?;
+    // A deactivated DID's log is the evidence verifiers rely on; keep it
+    // for the retention window.
+    if let Some(at) = record.deletable_at(deactivation_retention_secs(&state.config.hosting)?)
+        && at > now_epoch()
+    {
+        return Err(AppError::Conflict(format!(
+            "DID is deactivated and cannot be deleted before {at} (unix seconds)"
+        )));
+    }
+    let before = audit::did_snapshot(state, mnemonic).await?;
+    let archived = log_archive::all_keys(&state.dids_ks, mnemonic).await?;
+    let mut usage = quota::lock(state, [], [quota::record_domain(&record)]).await;
+    usage.record(Some(&record), None)?;
 
     let mut batch = state.store.batch();
     batch.remove(&state.dids_ks, did_key(mnemonic));
     batch.remove(&state.dids_ks, content_log_key(mnemonic));
     batch.remove(&state.dids_ks, content_witness_key(mnemonic));
+    batch.remove(&state.dids_ks, content_did_doc_key(mnemonic));
     batch.remove(&state.dids_ks, owner_key(&record.owner, mnemonic));
+    for key in archived {
+        batch.remove(&state.dids_ks, key);
+    }
+    batch.remove(&state.dids_ks, did_ops::pending_import_key(mnemonic));
+    batch.remove(&state.dids_ks, did_ops::moved_stub_key(mnemonic));
+    webhooks::emit_did(
+        &state.store,
+        &mut batch,
+        webhooks::DID_DELETED,
+        &record,
+        serde_json::Value::Null,
+    )?;
+    usage.stage(state, &mut batch).await?;
+    audit::stage_did(
+        &state.store,
+        &mut batch,
+        auth,
+        "did.delete",
+        mnemonic,
+        before,
+        None,
+    )?;
     batch.commit().await?;
+    drop(usage);
+    audit::flush(state).await;
+    webhooks::wake();
 
     info!(did = %auth.did, mnemonic = %mnemonic, "DID deleted on control plane");
 
@@ -1436,6 +2026,25 @@ pub async fn change_did_owner(
         )));
     }
 
+    // The DID moves onto the new owner's quota; the domain is unchanged.
+    // Held until the transfer commits, so two transfers to one owner
+    // cannot both pass.
+    let usage = quota::lock(state, [new_owner.as_str()], [quota::record_domain(&record)]).await;
+    quota::check(
+        state,
+        auth,
+        &quota::Charge {
+            owner: &new_owner,
+            domain: None,
+            new_dids: 1,
+            old_size: 0,
+            new_size: record.content_size,
+        },
+        &usage,
+    )
+    .await?;
+
+    let before = audit::did_snapshot(state, mnemonic).await?;
     let prev_owner = std::mem::replace(&mut record.owner, new_owner.clone());
     record.updated_at = now_epoch();
 
@@ -1447,7 +2056,26 @@ pub async fn change_did_owner(
         owner_key(&new_owner, mnemonic),
         mnemonic.as_bytes().to_vec(),
     );
+    webhooks::emit_did(
+        &state.store,
+        &mut batch,
+        webhooks::DID_OWNER_CHANGED,
+        &record,
+        serde_json::json!({ "previous_owner": prev_owner }),
+    )?;
+    let after = audit::did_snapshot_of(state, &record, None).await?;
+    audit::stage_did(
+        &state.store,
+        &mut batch,
+        auth,
+        "did.change-owner",
+        mnemonic,
+        before,
+        after,
+    )?;
     batch.commit().await?;
+    audit::flush(state).await;
+    webhooks::wake();
 
     info!(
         caller = %auth.did,
@@ -1469,8 +2097,35 @@ pub async fn set_did_disabled(
 ) -> Result<(), AppError> {
     validate_mnemonic(mnemonic)?;
     let mut record = get_authorized_record(&state.dids_ks, mnemonic, auth).await?;
+    let before = audit::did_snapshot(state, mnemonic).await?;
     record.disabled = disabled;
-    state.dids_ks.insert(did_key(mnemonic), &record).await?;
+    let (action, event) = if disabled {
+        ("did.disable", webhooks::DID_DISABLED)
+    } else {
+        ("did.enable", webhooks::DID_ENABLED)
+    };
+    let mut batch = state.store.batch();
+    batch.insert(&state.dids_ks, did_key(mnemonic), &record)?;
+    webhooks::emit_did(
+        &state.store,
+        &mut batch,
+        event,
+        &record,
+        serde_json::Value::Null,
+    )?;
+    let after = audit::did_snapshot_of(state, &record, None).await?;
+    audit::stage_did(
+        &state.store,
+        &mut batch,
+        auth,
+        action,
+        mnemonic,
+        before,
+        after,
+    )?;
+    batch.commit().await?;
+    audit::flush(state).await;
+    webhooks::wake();
     info!(
         did = %auth.did,
         mnemonic = %mnemonic,
@@ -1481,6 +2136,10 @@ pub async fn set_did_disabled(
 }
 
 /// Roll back (remove) the last log entry from a DID's JSONL content.
+///
+/// The dropped entry and the witness file are archived under the DID for
+/// `hosting.rollback_archive_retention` (see [`crate::log_archive`]), so
+/// [`undo_rollback_did`] can put them back.
 pub async fn rollback_did(
     auth: &AuthClaims,
     state: &AppState,
@@ -1489,14 +2148,25 @@ pub async fn rollback_did(
     use crate::auth::session::now_epoch;
 
     validate_mnemonic(mnemonic)?;
+    let retention = log_archive::retention_secs(&state.config)?;
+    let _guard = state.path_locks.guard(mnemonic).await;
     let mut record = get_authorized_record(&state.dids_ks, mnemonic, auth).await?;
+    did_move::ensure_not_moved_away(&state.dids_ks, mnemonic).await?;
+    ensure_webvh(&record, "rollback")?;
+    ensure_not_deactivated(&record)?;
 
     let bytes = state
         .dids_ks
         .get_raw(content_log_key(mnemonic))
         .await?
         .ok_or_else(|| AppError::NotFound("no log content for this DID".into()))?;
+    let witness = state
+        .dids_ks
+        .get_raw(content_witness_key(mnemonic))
+        .await?
+        .map(|w| String::from_utf8_lossy(&w).into_owned());
 
+    let before = audit::did_snapshot(state, mnemonic).await?;
     let content = String::from_utf8(bytes)
         .map_err(|e| AppError::Internal(format!("invalid log bytes: {e}")))?;
 
@@ -1510,16 +2180,28 @@ pub async fn rollback_did(
     let truncated_lines = &lines[..lines.len() - 1];
     let truncated = truncated_lines.join("\n");
 
+    let now = now_epoch();
+    let archived = ArchivedEntry {
+        entry: lines[lines.len() - 1].to_string(),
+        witness,
+        base_log_hash: log_archive::log_hash(&truncated),
+        archived_at: now,
+        expires_at: now.saturating_add(retention),
+        archived_by: auth.did.clone(),
+    };
+
     let new_did_id = extract_did_id(&truncated);
     let new_size = truncated.len() as u64;
+    let stored = record.clone();
 
     record.version_count = truncated_lines.len() as u64;
     record.did_id = new_did_id;
     record.content_size = new_size;
-    record.updated_at = now_epoch();
+    record.updated_at = now;
     // Rolling back can retract a service — if the dropped entry was the
     // one that added `TSPTransport`, the badge must go with it.
     record.services = extract_service_types(&truncated);
+    record.set_sync_head(truncated.as_bytes(), None);
 
     let mut batch = state.store.batch();
     batch.insert_raw(
@@ -1529,11 +2211,44 @@ pub async fn rollback_did(
     );
     batch.insert(&state.dids_ks, did_key(mnemonic), &record)?;
     batch.remove(&state.dids_ks, content_witness_key(mnemonic));
-    batch.commit().await?;
-
-    let log_metadata = Some(extract_log_metadata(&truncated));
-
-    info!(
+    batch.insert(
+        &state.dids_ks,
+        did_ops::archived_entry_key(mnemonic, crate::outbox::now_micros()),
+        &archived,
+    )?;
+    webhooks::emit_did(
+        &state.store,
+        &mut batch,
+        webhooks::DID_UPDATED,
+        &record,
+        serde_json::json!({ "rolled_back": true }),
+    )?;
+    let mut usage = quota::lock(
+        state,
+        [],
+        [quota::record_domain(&stored), quota::record_domain(&record)],
+    )
+    .await;
+    usage.record(Some(&stored), Some(&record))?;
+    usage.stage(state, &mut batch).await?;
+    let after = audit::did_snapshot_of(state, &record, Some(truncated.as_str())).await?;
+    audit::stage_did(
+        &state.store,
+        &mut batch,
+        auth,
+        "did.rollback",
+        mnemonic,
+        before,
+        after,
+    )?;
+    batch.commit().await?;
+    drop(usage);
+    audit::flush(state).await;
+    webhooks::wake();
+
+    let log_metadata = Some(extract_log_metadata(&truncated));
+
+    info!(
         did = %auth.did,
         mnemonic = %mnemonic,
         remaining = truncated_lines.len(),
@@ -1543,1278 +2258,3271 @@ pub async fn rollback_did(
     Ok((record, log_metadata))
 }
 
-/// Check if a custom path is available.
-pub async fn check_name(state: &AppState, path: &str) -> Result<CheckNameResponse, AppError> {
-    validate_custom_path(path)?;
-    let available = is_path_available(&state.dids_ks, path).await?;
-    Ok(CheckNameResponse {
-        available,
-        path: path.to_string(),
-    })
+/// Re-apply a log entry set aside by [`rollback_did`].
+///
+/// `id` picks an archived entry (see [`list_archived_entries`]); `None`
+/// takes the most recent. The entry is appended to the *current* log and
+/// the result must verify, so an entry whose chain has since moved on (a
+/// newer version was published, or an earlier rollback is still
+/// outstanding) is refused with `Conflict`. The archived witness file is
+/// restored only when the log is byte-for-byte what the rollback left.
+pub async fn undo_rollback_did(
+    auth: &AuthClaims,
+    state: &AppState,
+    mnemonic: &str,
+    id: Option<&str>,
+) -> Result<(DidRecord, Option<LogMetadata>), AppError> {
+    use crate::auth::session::now_epoch;
+
+    validate_mnemonic(mnemonic)?;
+    let _guard = state.path_locks.guard(mnemonic).await;
+    let mut record = get_authorized_record(&state.dids_ks, mnemonic, auth).await?;
+    did_move::ensure_not_moved_away(&state.dids_ks, mnemonic).await?;
+    ensure_webvh(&record, "rollback")?;
+    ensure_not_deactivated(&record)?;
+
+    let now = now_epoch();
+    let archived = log_archive::list(&state.dids_ks, mnemonic, now).await?;
+    let (archive_id, archived) = match id {
+        Some(id) => archived.into_iter().find(|(candidate, _)| candidate == id),
+        None => archived.into_iter().next_back(),
+    }
+    .ok_or_else(|| AppError::NotFound("no archived log entry to restore".into()))?;
+
+    let bytes = state
+        .dids_ks
+        .get_raw(content_log_key(mnemonic))
+        .await?
+        .ok_or_else(|| AppError::NotFound("no log content for this DID".into()))?;
+    let current = String::from_utf8(bytes)
+        .map_err(|e| AppError::Internal(format!("invalid log bytes: {e}")))?;
+
+    let before = audit::did_snapshot(state, mnemonic).await?;
+    let restored = format!("{}\n{}", current.trim_end(), archived.entry);
+    verify_did_log_proofs(&restored).map_err(|e| {
+        AppError::Conflict(format!(
+            "archived entry no longer extends the current log: {e}"
+        ))
+    })?;
+
+    let did_id = extract_did_id(&restored);
+    if let Some(did_id) = did_id.as_deref() {
+        check_did_host_safety(state, auth, did_id).await?;
+    }
+    let new_size = restored.len() as u64;
+    let mut usage = quota::lock(
+        state,
+        [record.owner.as_str()],
+        [
+            quota::record_domain(&record),
+            did_id
+                .as_deref()
+                .and_then(|d| did_hosting_common::server::domain::extract_did_host(d).ok()),
+        ],
+    )
+    .await;
+    quota::check(
+        state,
+        auth,
+        &quota::Charge {
+            owner: &record.owner,
+            domain: (!record.domain.is_empty()).then_some(record.domain.as_str()),
+            new_dids: 0,
+            old_size: record.content_size,
+            new_size,
+        },
+        &usage,
+    )
+    .await?;
+    let deactivating = extract_log_metadata(&restored).deactivated;
+    let stored = record.clone();
+
+    let witness = archived
+        .witness
+        .filter(|_| log_archive::log_hash(&current) == archived.base_log_hash);
+
+    record.version_count = restored.lines().filter(|l| !l.trim().is_empty()).count() as u64;
+    record.did_id = did_id;
+    record.content_size = new_size;
+    record.updated_at = now;
+    record.services = extract_service_types(&restored);
+    if deactivating {
+        record.deactivated_at = Some(now);
+    }
+    match &witness {
+        Some(witness) => record.set_sync_head(restored.as_bytes(), Some(witness.as_bytes())),
+        None => refresh_sync_head(state, &mut record, mnemonic, &restored).await?,
+    }
+
+    let mut batch = state.store.batch();
+    batch.insert_raw(
+        &state.dids_ks,
+        content_log_key(mnemonic),
+        restored.as_bytes().to_vec(),
+    );
+    batch.insert(&state.dids_ks, did_key(mnemonic), &record)?;
+    if let Some(witness) = &witness {
+        batch.insert_raw(
+            &state.dids_ks,
+            content_witness_key(mnemonic),
+            witness.as_bytes().to_vec(),
+        );
+    }
+    batch.remove(&state.dids_ks, log_archive::key_for(mnemonic, &archive_id)?);
+    webhooks::emit_did_log(&state.store, &mut batch, &record, &restored)?;
+    usage.record(Some(&stored), Some(&record))?;
+    usage.stage(state, &mut batch).await?;
+    let after = audit::did_snapshot_of(state, &record, Some(restored.as_str())).await?;
+    audit::stage_did(
+        &state.store,
+        &mut batch,
+        auth,
+        "did.undo-rollback",
+        mnemonic,
+        before,
+        after,
+    )?;
+    batch.commit().await?;
+    drop(usage);
+    audit::flush(state).await;
+    webhooks::wake();
+
+    info!(
+        did = %auth.did,
+        mnemonic = %mnemonic,
+        archive_id = %archive_id,
+        witness_restored = witness.is_some(),
+        "rolled-back DID log entry restored on control plane"
+    );
+
+    Ok((record, Some(extract_log_metadata(&restored))))
 }
 
-/// Availability of an agent name on a hosting domain.
-#[derive(Debug, Clone, serde::Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct AgentNameAvailability {
-    pub name: String,
-    pub domain: String,
-    /// Free to claim: neither reserved nor already bound on this domain.
-    pub available: bool,
-    /// On the host's reserved list (`@admin`, `@support`, …) — unavailable but
-    /// a well-formed name, distinct from a grammar error (which is a 400).
-    pub reserved: bool,
+/// Log entries [`rollback_did`] has archived for this DID and that are
+/// still within their retention window, oldest first.
+pub async fn list_archived_entries(
+    auth: &AuthClaims,
+    state: &AppState,
+    mnemonic: &str,
+) -> Result<Vec<ArchivedEntryInfo>, AppError> {
+    use crate::auth::session::now_epoch;
+
+    validate_mnemonic(mnemonic)?;
+    get_authorized_record(&state.dids_ks, mnemonic, auth).await?;
+    Ok(log_archive::list(&state.dids_ks, mnemonic, now_epoch())
+        .await?
+        .into_iter()
+        .map(|(id, entry)| ArchivedEntryInfo::new(id, &entry))
+        .collect())
 }
 
-/// Check whether an agent name can be claimed on `domain`.
-///
-/// A reserved name is reported as `available: false, reserved: true` rather
-/// than an error, so a UI can explain *why*; a grammatically invalid name is a
-/// client error (`AppError::Validation`). Availability is domain-scoped: the
-/// same name may be free on one domain and taken on another.
+// ---------------------------------------------------------------------------
+// Import (migrate-in)
+// ---------------------------------------------------------------------------
+
+/// Begin importing a `did:webvh` DID hosted elsewhere (see
+/// [`crate::did_import`]).
 ///
-/// The community name (`{domain}/@`) reports as reserved. This probe carries no
-/// mnemonic, so it cannot know whether the asker is the root DID — and the
-/// honest answer to "can I claim this" is no for every caller but one, whose
-/// binding happens at provisioning rather than through this surface. Answering
-/// `available` here would invite a claim that
-/// [`validate_agent_name_binding`] then refuses.
-pub async fn check_agent_name(
+/// The source log must verify end to end, declare `portable: true`, and
+/// not be deactivated. On success a slot is reserved exactly as
+/// [`create_did`] would (same `path` / `force` / `domain` rules and DID
+/// quota), and the returned offer names the DID the owner's move entry
+/// must switch to.
+pub async fn start_import(
+    auth: &AuthClaims,
     state: &AppState,
-    domain: &str,
-    name: &str,
-) -> Result<AgentNameAvailability, AppError> {
-    let bare = name.strip_prefix('@').unwrap_or(name).to_string();
-    let reserved = if bare.is_empty() {
-        true
-    } else {
-        match validate_agent_name(&bare) {
-            Ok(()) => false,
-            Err(AppError::AgentName(AgentNameError::Reserved)) => true,
-            // A malformed name (bad grammar) is a client error, not "unavailable".
-            Err(e) => return Err(e),
+    source: ImportSource<'_>,
+    path: Option<&str>,
+    force: bool,
+    domain: Option<&str>,
+) -> Result<ImportOffer, AppError> {
+    use crate::acl::Role;
+    use crate::auth::session::now_epoch;
+
+    let source_log = did_import::load_source(state, source, auth.role == Role::Admin).await?;
+    verify_did_log_proofs(&source_log)?;
+    let meta = extract_log_metadata(&source_log);
+    if meta.deactivated {
+        return Err(AppError::Validation(
+            "source DID is deactivated and cannot be moved".into(),
+        ));
+    }
+    if !meta.portable {
+        return Err(AppError::Validation(
+            "source DID is not portable: its log must set `portable: true` to change host".into(),
+        ));
+    }
+    let source_did = extract_did_id(&source_log).ok_or_else(|| {
+        AppError::Validation("source log's latest entry has no did:webvh state.id".into())
+    })?;
+
+    // The DID's host is its domain; without an explicit one, fall back to
+    // the hosting URL (which keeps a dev port, percent-encoded).
+    let host = match domain.filter(|d| !d.is_empty()) {
+        Some(d) => d.replace(':', "%3A"),
+        None => {
+            let base = state
+                .config
+                .did_hosting_url
+                .as_deref()
+                .or(state.config.public_url.as_deref())
+                .ok_or_else(|| {
+                    AppError::Internal(
+                        "server has neither did_hosting_url nor public_url configured".into(),
+                    )
+                })?;
+            did_hosting_common::did::encode_host(base)
+                .map_err(|e| AppError::Internal(format!("invalid hosting URL: {e}")))?
         }
     };
-    let taken = state
+    // Refuse before reserving anything: only the host is inspected here.
+    let probe = did_import::target_did(&source_did, &host, path.unwrap_or("import"))?;
+    check_did_host_safety(state, auth, &probe).await?;
+    if probe == source_did {
+        return Err(AppError::Validation(
+            "source DID is already hosted at this location".into(),
+        ));
+    }
+
+    let slot = create_did(auth, state, path, force, domain).await?;
+    let now = now_epoch();
+    let pending = PendingImport {
+        target_did: did_import::target_did(&source_did, &host, &slot.mnemonic)?,
+        source_did,
+        source_log,
+        did_url: slot.did_url,
+        created_at: now,
+        expires_at: now + did_import::PENDING_IMPORT_TTL_SECS,
+    };
+    state
         .dids_ks
-        .get_raw(agent_name_key(domain, &bare))
-        .await?
-        .is_some();
-    Ok(AgentNameAvailability {
-        available: !reserved && !taken,
-        reserved,
-        name: bare,
-        domain: domain.to_string(),
-    })
+        .insert(did_ops::pending_import_key(&slot.mnemonic), &pending)
+        .await?;
+
+    info!(
+        did = %auth.did,
+        mnemonic = %slot.mnemonic,
+        source = %pending.source_did,
+        target = %pending.target_did,
+        "DID import started on control plane"
+    );
+
+    Ok(ImportOffer::new(&slot.mnemonic, &pending))
 }
 
-/// Read a hosted DID's agent-name registry — the authoritative forward view,
-/// including *parked* entries.
-///
-/// The registry is the only place a parked name is visible: disabling drops it
-/// from the document's `alsoKnownAs` by design, so a client reading the DID
-/// document alone cannot tell a parked name from one that was never bound, and
-/// so cannot offer to resume it.
-///
-/// Returns the slot's domain alongside the entries because a bare local part
-/// (`alice`) only means something within a domain (`example.com/@alice`); the
-/// domain is empty for un-domained legacy slots.
-pub async fn list_agent_names(
+/// The offer for an import still waiting on its move entry.
+pub async fn get_import(
     auth: &AuthClaims,
     state: &AppState,
     mnemonic: &str,
-    request_domain: Option<&str>,
-) -> Result<(String, Vec<AgentNameEntry>), AppError> {
+) -> Result<ImportOffer, AppError> {
     validate_mnemonic(mnemonic)?;
-    let record = get_authorized_record(&state.dids_ks, mnemonic, auth).await?;
-    ensure_slot_domain_matches(&record, request_domain)?;
-
-    let domain = if !record.domain.is_empty() {
-        record.domain.clone()
-    } else {
-        record
-            .did_id
-            .as_deref()
-            .and_then(|d| did_hosting_common::server::domain::extract_did_host(d).ok())
-            .unwrap_or_default()
-    };
-    debug!(
-        did = %auth.did,
-        mnemonic = %mnemonic,
-        count = record.agent_names.len(),
-        "agent names listed"
-    );
-    Ok((domain, record.agent_names))
+    get_authorized_record(&state.dids_ks, mnemonic, auth).await?;
+    let pending = did_import::get(&state.dids_ks, mnemonic)
+        .await?
+        .ok_or_else(|| AppError::NotFound("no import in progress for this DID".into()))?;
+    Ok(ImportOffer::new(mnemonic, &pending))
 }
 
-/// The most DIDs one resolve call will answer for.
+/// Finish an import: `did_log` is the source log with the owner's signed
+/// move entry (or entries) appended.
 ///
-/// Sized for the display surfaces that drive it — a settings page holds three
-/// DIDs, a servers list a handful — not for bulk export. Each entry is a store
-/// read, so an uncapped list would turn one request into unbounded I/O.
-pub const MAX_RESOLVE_DIDS: usize = 64;
+/// The source entries must be unchanged, the whole chain must verify, the
+/// latest `state.id` must be the offered DID and its `alsoKnownAs` must
+/// list the source DID. The record is then filled in from the log —
+/// `version_count` is the full entry count, not 1 — and agent names the
+/// document claims on the new domain are registered under the same rules
+/// as a publish.
+pub async fn complete_import(
+    auth: &AuthClaims,
+    state: &AppState,
+    mnemonic: &str,
+    did_log: &str,
+) -> Result<(DidRecord, LogMetadata), AppError> {
+    use crate::auth::session::now_epoch;
 
-/// Turn what an operator typed into the DID an authorization record is keyed on.
-///
-/// Shared by the ACL routes and `change_did_owner`.
-///
-/// Accepts either a DID, or an **agent name** (`webvh.storm.ws/@alice`) which
-/// is resolved to the DID that currently holds it. Names are far easier to type
-/// correctly than a `did:webvh:Qm…` string, and a mistyped ACL subject is a
-/// silent failure — the entry simply never matches anyone.
-///
-/// # Resolution happens here, and *only* here
-///
-/// The value stored is always the DID. Nothing downstream ever resolves a name
-/// again, and that is a security property rather than an optimisation:
-///
-/// - A name can be released by its holder and claimed by someone else. If
-///   authorization resolved names at **check** time, the new holder would
-///   inherit the old holder's role the moment they claimed it — no write to the
-///   ACL, no audit trail, nothing to notice.
-/// - `check_acl` is an exact lookup on the authenticated DID, and the principal
-///   arriving at every authorization decision is a DID. A name stored as a key
-///   could never match one, so late resolution is not merely unsafe, it does
-///   not work.
-///
-/// So an agent name here is *input convenience*. The binding it creates is to
-/// the DID the name pointed at when the operator pressed the button, and it
-/// stays pointed there. The UI shows the DID's current name by looking it up
-/// (`/api/agent-names/resolve`), so the display stays live without the stored
-/// authorization data depending on a mutable label.
-///
-/// Only names this deployment actually serves resolve — the same gates the
-/// public `/@name` redirect applies. A name that resolves to nothing is a
-/// validation error naming the name, never a silently-created entry keyed on
-/// something that cannot authenticate.
-pub async fn resolve_did_or_agent_name(state: &AppState, input: &str) -> Result<String, AppError> {
-    let trimmed = input.trim();
-    if !agent_names::AgentName::looks_like_agent_name(trimmed) {
-        return validate_did_format(trimmed);
+    validate_mnemonic(mnemonic)?;
+    let _guard = state.path_locks.guard(mnemonic).await;
+    let mut record = get_authorized_record(&state.dids_ks, mnemonic, auth).await?;
+    let pending = did_import::get(&state.dids_ks, mnemonic)
+        .await?
+        .ok_or_else(|| AppError::NotFound("no import in progress for this DID".into()))?;
+
+    let now = now_epoch();
+    if pending.expires_at <= now {
+        return Err(AppError::Conflict(
+            "import has expired; start it again".into(),
+        ));
     }
-    // Canonicalise through the shared parser so the authority and local part are
-    // split exactly as the resolver and the registry would split them.
-    let name = agent_names::AgentName::parse(trimmed)
-        .map_err(|e| AppError::Validation(format!("'{trimmed}' is not a valid agent name: {e}")))?;
-    match resolve_agent_name_to_did(state, name.authority(), name.local_name()).await? {
-        Some(did) => Ok(did),
-        None => Err(AppError::Validation(format!(
-            "agent name '{}' does not resolve to a DID hosted here — bind it first, \
-             or use the DID directly",
-            name.without_scheme()
-        ))),
+    if record.version_count > 0 {
+        return Err(AppError::Conflict(
+            "a log has already been published to this slot".into(),
+        ));
     }
-}
 
-/// The DID an agent name currently resolves to, or `None` if it resolves to
-/// nothing here.
-///
-/// The `/@name` redirect the edge serves, done in-process against the
-/// authoritative registry. Applies the same gates the edge does — the name must
-/// be `enabled`, and the slot must be neither disabled nor deleted — so this
-/// can never hand back a DID that the public redirect would refuse to serve.
-///
-/// # This is a *write-time* helper, and that is the whole point
-///
-/// Resolving a name to a DID is only safe at the moment an operator types it.
-/// A name is not a stable identifier: its holder may release it (`remove`) and
-/// somebody else may then claim it. Anything that resolves a name **at decision
-/// time** therefore hands the new holder whatever the old holder had — silently,
-/// with no write to the thing being protected and nothing in an audit log to
-/// show for it. Resolve once, store the DID, and a later re-claim changes
-/// nothing.
-pub async fn resolve_agent_name_to_did(
-    state: &AppState,
-    domain: &str,
-    name: &str,
-) -> Result<Option<String>, AppError> {
-    if !state.config.features.agent_names {
-        return Ok(None);
+    let did_log = did_log.trim();
+    match did_import::appended_entries(&pending.source_log, did_log) {
+        None => {
+            return Err(AppError::Validation(
+                "submitted log must start with the source log, unchanged".into(),
+            ));
+        }
+        Some(added) if added.is_empty() => {
+            return Err(AppError::Validation(format!(
+                "submitted log has no move entry: append one setting state.id to {}",
+                pending.target_did
+            )));
+        }
+        Some(_) => {}
     }
-    let bare = name.strip_prefix('@').unwrap_or(name);
-    let Some(mnemonic) = state
-        .dids_ks
-        .get_raw(agent_name_key(domain, bare))
-        .await?
-        .and_then(|b| String::from_utf8(b).ok())
-    else {
-        return Ok(None);
-    };
-    let Some(record) = state.dids_ks.get::<DidRecord>(did_key(&mnemonic)).await? else {
-        return Ok(None);
-    };
-    if record.disabled || record.deleted_at.is_some() {
-        return Ok(None);
+    verify_did_log_proofs(did_log)?;
+
+    let did_id = extract_did_id(did_log).ok_or_else(|| {
+        AppError::Validation("did_log's latest entry has no resolvable did:webvh state.id".into())
+    })?;
+    if did_id != pending.target_did {
+        return Err(AppError::Validation(format!(
+            "move entry must set state.id to {}, found {did_id}",
+            pending.target_did
+        )));
     }
-    if !record
-        .agent_names
-        .iter()
-        .any(|e| e.name == bare && e.enabled)
-    {
-        return Ok(None);
+    if !did_import::claims_also_known_as(did_log, &pending.source_did) {
+        return Err(AppError::Validation(format!(
+            "move entry must list the source DID {} in alsoKnownAs",
+            pending.source_did
+        )));
     }
-    Ok(record.did_id)
+    check_did_host_safety(state, auth, &did_id).await?;
+    let domain = did_hosting_common::server::domain::extract_did_host(&did_id)?;
+
+    let new_size = did_log.len() as u64;
+    let mut usage = quota::lock(
+        state,
+        [record.owner.as_str()],
+        [Some(domain.clone()), quota::record_domain(&record)],
+    )
+    .await;
+    quota::check(
+        state,
+        auth,
+        &quota::Charge {
+            owner: &record.owner,
+            domain: Some(&domain),
+            new_dids: 0,
+            old_size: record.content_size,
+            new_size,
+        },
+        &usage,
+    )
+    .await?;
+
+    let before = audit::did_snapshot(state, mnemonic).await?;
+    let stored = record.clone();
+    record.version_count = did_log.lines().filter(|l| !l.trim().is_empty()).count() as u64;
+    record.did_id = Some(did_id);
+    record.content_size = new_size;
+    record.updated_at = now;
+    record.services = extract_service_types(did_log);
+    record.domain = domain.clone();
+    record.deactivated_at = extract_log_metadata(did_log).deactivated.then_some(now);
+    refresh_sync_head(state, &mut record, mnemonic, did_log).await?;
+    let (claimed, released) = reconcile_agent_names(
+        state,
+        &mut record,
+        mnemonic,
+        extract_agent_names(did_log, &domain),
+        &domain,
+        now,
+        None,
+    )
+    .await?;
+
+    let mut batch = state.store.batch();
+    batch.insert_raw(
+        &state.dids_ks,
+        content_log_key(mnemonic),
+        did_log.as_bytes().to_vec(),
+    );
+    batch.insert(&state.dids_ks, did_key(mnemonic), &record)?;
+    for name in &claimed {
+        batch.insert_raw(
+            &state.dids_ks,
+            agent_name_key(&domain, name),
+            mnemonic.as_bytes().to_vec(),
+        );
+    }
+    for name in &released {
+        batch.remove(&state.dids_ks, agent_name_key(&domain, name));
+    }
+    batch.remove(&state.dids_ks, did_ops::pending_import_key(mnemonic));
+    let meta = extract_log_metadata(did_log);
+    webhooks::emit_did(
+        &state.store,
+        &mut batch,
+        webhooks::DID_REGISTERED,
+        &record,
+        serde_json::json!({
+            "version_id": meta.latest_version_id,
+            "version_time": meta.latest_version_time,
+            "imported_from": pending.source_did,
+        }),
+    )?;
+    usage.record(Some(&stored), Some(&record))?;
+    usage.stage(state, &mut batch).await?;
+    let after = audit::did_snapshot_of(state, &record, Some(did_log)).await?;
+    audit::stage_did(
+        &state.store,
+        &mut batch,
+        auth,
+        "did.import",
+        mnemonic,
+        before,
+        after,
+    )?;
+    batch.commit().await?;
+    drop(usage);
+    audit::flush(state).await;
+    webhooks::wake();
+    state.stats_collector.record_update(mnemonic);
+
+    info!(
+        did = %auth.did,
+        mnemonic = %mnemonic,
+        source = %pending.source_did,
+        version = record.version_count,
+        "DID import completed on control plane"
+    );
+
+    Ok((record, meta))
 }
 
-/// DID → its served agent names, for a batch of DIDs. The reverse of the
-/// `/@name` redirect.
-///
-/// # Why this is not an index lookup
-///
-/// The registry indexes `name → mnemonic`; nothing maps a DID identifier back
-/// to its record. It does not need to: for `did:webvh` the mnemonic *is*
-/// derivable from the identifier ([`mnemonic_from_did`]), so this is a direct
-/// read per DID rather than a scan. That is also why the result is not
-/// authoritative for foreign DIDs — a DID hosted elsewhere simply has no local
-/// record, and is reported as having no names rather than resolved over the
-/// network. Callers rendering a foreign DID get the DID, which is correct: this
-/// service can only vouch for names it serves.
-///
-/// # What is returned
-///
-/// Only **served** (`enabled`) names, and only from a record whose `did_id` is
-/// the DID asked about. Parked names are registry-private — they resolve to
-/// nothing, so publishing them here would advertise a handle that 404s and
-/// would leak a reservation its holder deliberately took out of service.
-///
-/// DIDs with no names are omitted from the map entirely; a caller reads a
-/// missing key and an empty list the same way, and the common case is that most
-/// DIDs have none.
+// ---------------------------------------------------------------------------
+// Move between hosted domains
+// ---------------------------------------------------------------------------
+
+/// A completed move, for the REST and Trust Task responses.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MovedDid {
+    pub mnemonic: String,
+    pub did_id: String,
+    pub previous_mnemonic: String,
+    pub previous_did_id: String,
+    pub version_count: u64,
+    /// Until when the old location keeps serving the log.
+    pub stub_expires_at: u64,
+}
+
+/// Move a hosted DID to another hosted domain or path (see
+/// [`crate::did_move`]).
 ///
-/// # Disclosure
+/// `did_log` is the DID's current log with the owner's signed move entry
+/// (or entries) appended. The log must be portable, the stored entries
+/// unchanged, and the new `state.id` must differ from the old one and keep
+/// it in `alsoKnownAs`. The destination slot is the one the new identifier
+/// names; it must be free unless it is this slot (a domain-only move).
 ///
-/// Every name returned is already in that DID's `alsoKnownAs` and already
-/// served as a public redirect by the edge, so this reveals nothing new about a
-/// DID the caller already holds. It is authenticated regardless — it answers
-/// questions about arbitrary DIDs, and an unauthenticated batch endpoint is an
-/// enumeration surface even when each individual answer is public.
-pub async fn resolve_agent_names(
+/// Agent names on the old domain are released; names the move entry
+/// claims on the new domain are registered under the publish rules. A
+/// move to a new path is charged like a new DID at the destination; an
+/// in-place move is a new DID only for the new domain.
+pub async fn move_did(
+    auth: &AuthClaims,
     state: &AppState,
-    dids: &[String],
-) -> Result<std::collections::HashMap<String, Vec<String>>, AppError> {
-    use std::collections::HashMap;
+    mnemonic: &str,
+    did_log: &str,
+) -> Result<MovedDid, AppError> {
+    use crate::acl::Role;
+    use crate::auth::session::now_epoch;
 
-    if !state.config.features.agent_names {
-        return Ok(HashMap::new());
+    validate_mnemonic(mnemonic)?;
+    let retention = did_move::retention_secs(&state.config)?;
+    let did_log = did_log.trim();
+    let new_did = extract_did_id(did_log).ok_or_else(|| {
+        AppError::Validation("did_log's latest entry has no resolvable did:webvh state.id".into())
+    })?;
+    let new_mnemonic = did_move::mnemonic_for(&new_did)?;
+    let in_place = new_mnemonic == mnemonic;
+    if !in_place {
+        if new_mnemonic == ".well-known" {
+            if auth.role != Role::Admin {
+                return Err(AppError::Forbidden(
+                    "only admins can move a DID to the root".into(),
+                ));
+            }
+        } else {
+            validate_custom_path(&new_mnemonic)?;
+        }
     }
-    if dids.len() > MAX_RESOLVE_DIDS {
+
+    // Both slots are read-modify-written; lock them in a fixed order so two
+    // opposing moves cannot deadlock.
+    let (first, second) = if new_mnemonic.as_str() < mnemonic {
+        (new_mnemonic.as_str(), mnemonic)
+    } else {
+        (mnemonic, new_mnemonic.as_str())
+    };
+    let _guard = state.path_locks.guard(first).await;
+    let _second_guard = if in_place {
+        None
+    } else {
+        Some(state.path_locks.guard(second).await)
+    };
+
+    let record = get_authorized_record(&state.dids_ks, mnemonic, auth).await?;
+    did_move::ensure_not_moved_away(&state.dids_ks, mnemonic).await?;
+    ensure_webvh(&record, "moving a DID")?;
+    let old_did = record
+        .did_id
+        .clone()
+        .filter(|_| record.version_count > 0)
+        .ok_or_else(|| AppError::Conflict("DID has no published log to move".into()))?;
+    if new_did == old_did {
         return Err(AppError::Validation(format!(
-            "at most {MAX_RESOLVE_DIDS} DIDs per resolve request, got {}",
-            dids.len()
+            "move entry must change state.id; it still names {old_did}"
+        )));
+    }
+    if !in_place && !is_path_available(&state.dids_ks, &new_mnemonic).await? {
+        return Err(AppError::Conflict(format!(
+            "path '{new_mnemonic}' is already taken"
         )));
     }
 
-    let mut out: HashMap<String, Vec<String>> = HashMap::new();
-    for did in dids {
-        // Already answered — a caller may repeat a DID (the same instance
-        // appearing twice in a list), and one read is enough.
-        if out.contains_key(did) {
-            continue;
-        }
-        let Some(mnemonic) = mnemonic_from_did(did) else {
-            continue;
-        };
-        let Some(record) = state
-            .dids_ks
-            .get::<DidRecord>(did_key(&mnemonic))
-            .await
-            .ok()
-            .flatten()
-        else {
-            continue;
-        };
-        // The slot must hold *this* DID. `mnemonic_from_did` yields the slot a
-        // DID *would* occupy, and for a pathless DID that is the single global
-        // `.well-known` — so without this, asking about any foreign root DID
-        // would return the local root DID's names.
-        if record.did_id.as_deref() != Some(did.as_str())
-            || record.disabled
-            || record.deleted_at.is_some()
-        {
-            continue;
+    let current = state
+        .dids_ks
+        .get_raw(content_log_key(mnemonic))
+        .await?
+        .map(|b| String::from_utf8_lossy(&b).into_owned())
+        .ok_or_else(|| AppError::NotFound("no log content for this DID".into()))?;
+    let current_meta = extract_log_metadata(&current);
+    if current_meta.deactivated {
+        return Err(AppError::Conflict(
+            "DID is deactivated and cannot be moved".into(),
+        ));
+    }
+    if !current_meta.portable {
+        return Err(AppError::Validation(
+            "DID is not portable: its log must set `portable: true` to change location".into(),
+        ));
+    }
+    match did_import::appended_entries(&current, did_log) {
+        None => {
+            return Err(AppError::Validation(
+                "submitted log must start with the stored log, unchanged".into(),
+            ));
         }
-        let names: Vec<String> = record
-            .agent_names
-            .iter()
-            .filter(|e| e.enabled)
-            .map(|e| e.name.clone())
-            .collect();
-        if !names.is_empty() {
-            out.insert(did.clone(), names);
+        Some(added) if added.is_empty() => {
+            return Err(AppError::Validation(
+                "submitted log has no move entry".into(),
+            ));
         }
+        Some(_) => {}
     }
-    Ok(out)
-}
+    verify_did_log_proofs(did_log)?;
+    if !did_import::claims_also_known_as(did_log, &old_did) {
+        return Err(AppError::Validation(format!(
+            "move entry must list the old DID {old_did} in alsoKnownAs"
+        )));
+    }
+    check_did_host_safety(state, auth, &new_did).await?;
+    let new_domain = did_hosting_common::server::domain::extract_did_host(&new_did)?;
+    let old_domain = quota::record_domain(&record).unwrap_or_default();
 
-// ---------------------------------------------------------------------------
-// Tests for register_did_atomic, publish_did stats counters, etc.
-// ---------------------------------------------------------------------------
+    // A move to a new path leaves the stub counting against the owner until
+    // it expires, so it is charged like a new DID. An in-place move keeps
+    // the owner's slot and only arrives as a new DID in the new domain.
+    let new_size = did_log.len() as u64;
+    let mut usage = quota::lock(
+        state,
+        [record.owner.as_str()],
+        [Some(old_domain.clone()), Some(new_domain.clone())],
+    )
+    .await;
+    if in_place {
+        quota::check(
+            state,
+            auth,
+            &quota::Charge {
+                owner: &record.owner,
+                domain: None,
+                new_dids: 0,
+                old_size: record.content_size,
+                new_size,
+            },
+            &usage,
+        )
+        .await?;
+        quota::check_domain(
+            state,
+            auth,
+            &new_domain,
+            &quota::Charge {
+                owner: &record.owner,
+                domain: Some(&new_domain),
+                new_dids: u64::from(!old_domain.eq_ignore_ascii_case(&new_domain)),
+                old_size: 0,
+                new_size,
+            },
+            &usage,
+        )
+        .await?;
+    } else {
+        quota::check(
+            state,
+            auth,
+            &quota::Charge {
+                owner: &record.owner,
+                domain: Some(&new_domain),
+                new_dids: 1,
+                old_size: 0,
+                new_size,
+            },
+            &usage,
+        )
+        .await?;
+    }
 
-#[cfg(test)]
-mod tests_atomic {
-    use super::*;
-    use did_hosting_common::server::store::{
-        KS_ACL, KS_DIDS, KS_REGISTRY, KS_SESSIONS, KS_STATS, KS_TIMESERIES,
+    let before = audit::did_snapshot(state, mnemonic).await?;
+    let now = now_epoch();
+    let version_count = did_log.lines().filter(|l| !l.trim().is_empty()).count() as u64;
+    let mut moved = DidRecord {
+        mnemonic: new_mnemonic.clone(),
+        updated_at: now,
+        version_count,
+        did_id: Some(new_did.clone()),
+        content_size: new_size,
+        domain: new_domain.clone(),
+        services: extract_service_types(did_log),
+        agent_names: Vec::new(),
+        deactivated_at: extract_log_metadata(did_log).deactivated.then_some(now),
+        ..record.clone()
     };
-    use std::path::PathBuf;
-    use std::sync::{Arc, OnceLock};
+    // Both slots serve the moved log with the old slot's witness file.
+    let witness = state.dids_ks.get_raw(content_witness_key(mnemonic)).await?;
+    moved.set_sync_head(did_log.as_bytes(), witness.as_deref());
+    let (claimed, _) = reconcile_agent_names(
+        state,
+        &mut moved,
+        &new_mnemonic,
+        extract_agent_names(did_log, &new_domain),
+        &new_domain,
+        now,
+        Some(mnemonic),
+    )
+    .await?;
+    let stub = MovedStub {
+        old_did: old_did.clone(),
+        new_mnemonic: new_mnemonic.clone(),
+        new_did: new_did.clone(),
+        moved_at: now,
+        expires_at: now.saturating_add(retention),
+        moved_by: auth.did.clone(),
+    };
+
+    let mut batch = state.store.batch();
+    for entry in &record.agent_names {
+        let follows = old_domain.eq_ignore_ascii_case(&new_domain) && claimed.contains(&entry.name);
+        let ours = state
+            .dids_ks
+            .get_raw(agent_name_key(&old_domain, &entry.name))
+            .await?
+            .is_some_and(|bytes| bytes == mnemonic.as_bytes());
+        if ours && !follows {
+            batch.remove(&state.dids_ks, agent_name_key(&old_domain, &entry.name));
+        }
+    }
+    for name in &claimed {
+        batch.insert_raw(
+            &state.dids_ks,
+            agent_name_key(&new_domain, name),
+            new_mnemonic.as_bytes().to_vec(),
+        );
+    }
+    let after;
+    if in_place {
+        usage.record(Some(&record), Some(&moved))?;
+        after = audit::did_snapshot_of(state, &moved, Some(did_log)).await?;
+    } else {
+        // The old slot becomes the stub: old identifier, moved log, no names.
+        let stub_record = DidRecord {
+            updated_at: now,
+            version_count,
+            content_size: new_size,
+            services: moved.services.clone(),
+            agent_names: Vec::new(),
+            deactivated_at: moved.deactivated_at,
+            sync_head: moved.sync_head.clone(),
+            ..record.clone()
+        };
+        usage.record(Some(&record), Some(&stub_record))?;
+        usage.record(None, Some(&moved))?;
+        after = audit::did_snapshot_of(state, &stub_record, Some(did_log)).await?;
+        batch.insert(&state.dids_ks, did_key(mnemonic), &stub_record)?;
+        batch.insert_raw(
+            &state.dids_ks,
+            owner_key(&record.owner, &new_mnemonic),
+            new_mnemonic.as_bytes().to_vec(),
+        );
+        if let Some(witness) = witness {
+            batch.insert_raw(&state.dids_ks, content_witness_key(&new_mnemonic), witness);
+        }
+    }
+    batch.insert_raw(
+        &state.dids_ks,
+        content_log_key(mnemonic),
+        did_log.as_bytes().to_vec(),
+    );
+    batch.insert_raw(
+        &state.dids_ks,
+        content_log_key(&new_mnemonic),
+        did_log.as_bytes().to_vec(),
+    );
+    batch.insert(&state.dids_ks, did_key(&new_mnemonic), &moved)?;
+    batch.insert(&state.dids_ks, did_ops::moved_stub_key(mnemonic), &stub)?;
+    let meta = extract_log_metadata(did_log);
+    webhooks::emit_did(
+        &state.store,
+        &mut batch,
+        webhooks::DID_MOVED,
+        &moved,
+        serde_json::json!({
+            "version_id": meta.latest_version_id,
+            "version_time": meta.latest_version_time,
+            "previous_did": old_did,
+            "previous_mnemonic": mnemonic,
+            "previous_domain": old_domain,
+        }),
+    )?;
+    usage.stage(state, &mut batch).await?;
+    audit::stage_did(
+        &state.store,
+        &mut batch,
+        auth,
+        "did.move",
+        mnemonic,
+        before,
+        after,
+    )?;
+    batch.commit().await?;
+    drop(usage);
+    audit::flush(state).await;
+    webhooks::wake();
+    state.stats_collector.record_update(&new_mnemonic);
+
+    info!(
+        did = %auth.did,
+        from = %mnemonic,
+        to = %new_mnemonic,
+        old_did = %old_did,
+        new_did = %new_did,
+        "DID moved on control plane"
+    );
+
+    Ok(MovedDid {
+        mnemonic: new_mnemonic,
+        did_id: new_did,
+        previous_mnemonic: mnemonic.to_string(),
+        previous_did_id: old_did,
+        version_count,
+        stub_expires_at: stub.expires_at,
+    })
+}
+
+/// Check if a custom path is available.
+pub async fn check_name(state: &AppState, path: &str) -> Result<CheckNameResponse, AppError> {
+    validate_custom_path(path)?;
+    let available = is_path_available(&state.dids_ks, path).await?;
+    Ok(CheckNameResponse {
+        available,
+        path: path.to_string(),
+    })
+}
+
+/// Availability of an agent name on a hosting domain.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentNameAvailability {
+    pub name: String,
+    pub domain: String,
+    /// Free to claim: neither reserved nor already bound on this domain.
+    pub available: bool,
+    /// On the host's reserved list (`@admin`, `@support`, …) — unavailable but
+    /// a well-formed name, distinct from a grammar error (which is a 400).
+    pub reserved: bool,
+}
+
+/// Check whether an agent name can be claimed on `domain`.
+///
+/// A reserved name is reported as `available: false, reserved: true` rather
+/// than an error, so a UI can explain *why*; a grammatically invalid name is a
+/// client error (`AppError::Validation`). Availability is domain-scoped: the
+/// same name may be free on one domain and taken on another.
+///
+/// The community name (`{domain}/@`) reports as reserved. This probe carries no
+/// mnemonic, so it cannot know whether the asker is the root DID — and the
+/// honest answer to "can I claim this" is no for every caller but one, whose
+/// binding happens at provisioning rather than through this surface. Answering
+/// `available` here would invite a claim that
+/// [`validate_agent_name_binding`] then refuses.
+pub async fn check_agent_name(
+    state: &AppState,
+    domain: &str,
+    name: &str,
+) -> Result<AgentNameAvailability, AppError> {
+    let bare = name.strip_prefix('@').unwrap_or(name).to_string();
+    let reserved = if bare.is_empty() {
+        true
+    } else {
+        match validate_agent_name(&bare) {
+            Ok(()) => false,
+            Err(AppError::AgentName(AgentNameError::Reserved)) => true,
+            // A malformed name (bad grammar) is a client error, not "unavailable".
+            Err(e) => return Err(e),
+        }
+    };
+    let taken = state
+        .dids_ks
+        .get_raw(agent_name_key(domain, &bare))
+        .await?
+        .is_some();
+    Ok(AgentNameAvailability {
+        available: !reserved && !taken,
+        reserved,
+        name: bare,
+        domain: domain.to_string(),
+    })
+}
+
+/// Read a hosted DID's agent-name registry — the authoritative forward view,
+/// including *parked* entries.
+///
+/// The registry is the only place a parked name is visible: disabling drops it
+/// from the document's `alsoKnownAs` by design, so a client reading the DID
+/// document alone cannot tell a parked name from one that was never bound, and
+/// so cannot offer to resume it.
+///
+/// Returns the slot's domain alongside the entries because a bare local part
+/// (`alice`) only means something within a domain (`example.com/@alice`); the
+/// domain is empty for un-domained legacy slots.
+pub async fn list_agent_names(
+    auth: &AuthClaims,
+    state: &AppState,
+    mnemonic: &str,
+    request_domain: Option<&str>,
+) -> Result<(String, Vec<AgentNameEntry>), AppError> {
+    validate_mnemonic(mnemonic)?;
+    let record = get_authorized_record(&state.dids_ks, mnemonic, auth).await?;
+    ensure_slot_domain_matches(&record, request_domain)?;
+
+    let domain = if !record.domain.is_empty() {
+        record.domain.clone()
+    } else {
+        record
+            .did_id
+            .as_deref()
+            .and_then(|d| did_hosting_common::server::domain::extract_did_host(d).ok())
+            .unwrap_or_default()
+    };
+    debug!(
+        did = %auth.did,
+        mnemonic = %mnemonic,
+        count = record.agent_names.len(),
+        "agent names listed"
+    );
+    Ok((domain, record.agent_names))
+}
+
+/// The most DIDs one resolve call will answer for.
+///
+/// Sized for the display surfaces that drive it — a settings page holds three
+/// DIDs, a servers list a handful — not for bulk export. Each entry is a store
+/// read, so an uncapped list would turn one request into unbounded I/O.
+pub const MAX_RESOLVE_DIDS: usize = 64;
+
+/// Turn what an operator typed into the DID an authorization record is keyed on.
+///
+/// Shared by the ACL routes and `change_did_owner`.
+///
+/// Accepts either a DID, or an **agent name** (`webvh.storm.ws/@alice`) which
+/// is resolved to the DID that currently holds it. Names are far easier to type
+/// correctly than a `did:webvh:Qm…` string, and a mistyped ACL subject is a
+/// silent failure — the entry simply never matches anyone.
+///
+/// # Resolution happens here, and *only* here
+///
+/// The value stored is always the DID. Nothing downstream ever resolves a name
+/// again, and that is a security property rather than an optimisation:
+///
+/// - A name can be released by its holder and claimed by someone else. If
+///   authorization resolved names at **check** time, the new holder would
+///   inherit the old holder's role the moment they claimed it — no write to the
+///   ACL, no audit trail, nothing to notice.
+/// - `check_acl` is an exact lookup on the authenticated DID, and the principal
+///   arriving at every authorization decision is a DID. A name stored as a key
+///   could never match one, so late resolution is not merely unsafe, it does
+///   not work.
+///
+/// So an agent name here is *input convenience*. The binding it creates is to
+/// the DID the name pointed at when the operator pressed the button, and it
+/// stays pointed there. The UI shows the DID's current name by looking it up
+/// (`/api/agent-names/resolve`), so the display stays live without the stored
+/// authorization data depending on a mutable label.
+///
+/// Only names this deployment actually serves resolve — the same gates the
+/// public `/@name` redirect applies. A name that resolves to nothing is a
+/// validation error naming the name, never a silently-created entry keyed on
+/// something that cannot authenticate.
+pub async fn resolve_did_or_agent_name(state: &AppState, input: &str) -> Result<String, AppError> {
+    let trimmed = input.trim();
+    if !agent_names::AgentName::looks_like_agent_name(trimmed) {
+        return validate_did_format(trimmed);
+    }
+    // Canonicalise through the shared parser so the authority and local part are
+    // split exactly as the resolver and the registry would split them.
+    let name = agent_names::AgentName::parse(trimmed)
+        .map_err(|e| AppError::Validation(format!("'{trimmed}' is not a valid agent name: {e}")))?;
+    match resolve_agent_name_to_did(state, name.authority(), name.local_name()).await? {
+        Some(did) => Ok(did),
+        None => Err(AppError::Validation(format!(
+            "agent name '{}' does not resolve to a DID hosted here — bind it first, \
+             or use the DID directly",
+            name.without_scheme()
+        ))),
+    }
+}
+
+/// The DID an agent name currently resolves to, or `None` if it resolves to
+/// nothing here.
+///
+/// The `/@name` redirect the edge serves, done in-process against the
+/// authoritative registry. Applies the same gates the edge does — the name must
+/// be `enabled`, and the slot must be neither disabled nor deleted — so this
+/// can never hand back a DID that the public redirect would refuse to serve.
+///
+/// # This is a *write-time* helper, and that is the whole point
+///
+/// Resolving a name to a DID is only safe at the moment an operator types it.
+/// A name is not a stable identifier: its holder may release it (`remove`) and
+/// somebody else may then claim it. Anything that resolves a name **at decision
+/// time** therefore hands the new holder whatever the old holder had — silently,
+/// with no write to the thing being protected and nothing in an audit log to
+/// show for it. Resolve once, store the DID, and a later re-claim changes
+/// nothing.
+pub async fn resolve_agent_name_to_did(
+    state: &AppState,
+    domain: &str,
+    name: &str,
+) -> Result<Option<String>, AppError> {
+    if !state.config.features.agent_names {
+        return Ok(None);
+    }
+    let bare = name.strip_prefix('@').unwrap_or(name);
+    let Some(mnemonic) = state
+        .dids_ks
+        .get_raw(agent_name_key(domain, bare))
+        .await?
+        .and_then(|b| String::from_utf8(b).ok())
+    else {
+        return Ok(None);
+    };
+    let Some(record) = state.dids_ks.get::<DidRecord>(did_key(&mnemonic)).await? else {
+        return Ok(None);
+    };
+    if record.disabled || record.deleted_at.is_some() {
+        return Ok(None);
+    }
+    if !record
+        .agent_names
+        .iter()
+        .any(|e| e.name == bare && e.enabled)
+    {
+        return Ok(None);
+    }
+    Ok(record.did_id)
+}
+
+/// DID → its served agent names, for a batch of DIDs. The reverse of the
+/// `/@name` redirect.
+///
+/// # Why this is not an index lookup
+///
+/// The registry indexes `name → mnemonic`; nothing maps a DID identifier back
+/// to its record. It does not need to: for `did:webvh` the mnemonic *is*
+/// derivable from the identifier ([`mnemonic_from_did`]), so this is a direct
+/// read per DID rather than a scan. That is also why the result is not
+/// authoritative for foreign DIDs — a DID hosted elsewhere simply has no local
+/// record, and is reported as having no names rather than resolved over the
+/// network. Callers rendering a foreign DID get the DID, which is correct: this
+/// service can only vouch for names it serves.
+///
+/// # What is returned
+///
+/// Only **served** (`enabled`) names, and only from a record whose `did_id` is
+/// the DID asked about. Parked names are registry-private — they resolve to
+/// nothing, so publishing them here would advertise a handle that 404s and
+/// would leak a reservation its holder deliberately took out of service.
+///
+/// DIDs with no names are omitted from the map entirely; a caller reads a
+/// missing key and an empty list the same way, and the common case is that most
+/// DIDs have none.
+///
+/// # Disclosure
+///
+/// Every name returned is already in that DID's `alsoKnownAs` and already
+/// served as a public redirect by the edge, so this reveals nothing new about a
+/// DID the caller already holds. It is authenticated regardless — it answers
+/// questions about arbitrary DIDs, and an unauthenticated batch endpoint is an
+/// enumeration surface even when each individual answer is public.
+pub async fn resolve_agent_names(
+    state: &AppState,
+    dids: &[String],
+) -> Result<std::collections::HashMap<String, Vec<String>>, AppError> {
+    use std::collections::HashMap;
+
+    if !state.config.features.agent_names {
+        return Ok(HashMap::new());
+    }
+    if dids.len() > MAX_RESOLVE_DIDS {
+        return Err(AppError::Validation(format!(
+            "at most {MAX_RESOLVE_DIDS} DIDs per resolve request, got {}",
+            dids.len()
+        )));
+    }
+
+    let mut out: HashMap<String, Vec<String>> = HashMap::new();
+    for did in dids {
+        // Already answered — a caller may repeat a DID (the same instance
+        // appearing twice in a list), and one read is enough.
+        if out.contains_key(did) {
+            continue;
+        }
+        let Some(mnemonic) = mnemonic_from_did(did) else {
+            continue;
+        };
+        let Some(record) = state
+            .dids_ks
+            .get::<DidRecord>(did_key(&mnemonic))
+            .await
+            .ok()
+            .flatten()
+        else {
+            continue;
+        };
+        // The slot must hold *this* DID. `mnemonic_from_did` yields the slot a
+        // DID *would* occupy, and for a pathless DID that is the single global
+        // `.well-known` — so without this, asking about any foreign root DID
+        // would return the local root DID's names.
+        if record.did_id.as_deref() != Some(did.as_str())
+            || record.disabled
+            || record.deleted_at.is_some()
+        {
+            continue;
+        }
+        let names: Vec<String> = record
+            .agent_names
+            .iter()
+            .filter(|e| e.enabled)
+            .map(|e| e.name.clone())
+            .collect();
+        if !names.is_empty() {
+            out.insert(did.clone(), names);
+        }
+    }
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// Tests for register_did_atomic, publish_did stats counters, etc.
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests_atomic {
+    use super::*;
+    use did_hosting_common::server::store::{
+        KS_ACL, KS_DIDS, KS_REGISTRY, KS_SESSIONS, KS_STATS, KS_TIMESERIES,
+    };
+    use std::path::PathBuf;
+    use std::sync::{Arc, OnceLock};
+
+    use affinidi_tdk::secrets_resolver::secrets::Secret;
+    use did_hosting_common::DidRegisterRequest;
+    use did_hosting_common::did::{DidDocumentOptions, build_did_document, create_log_entry};
+    use did_hosting_common::server::config::{
+        AuthConfig, FeaturesConfig, LogConfig, SecretsConfig, ServerConfig, StoreConfig, VtaConfig,
+    };
+    use did_hosting_common::server::stats_collector::StatsCollector;
+    use did_hosting_common::server::store::Store;
+
+    use crate::acl::Role;
+    use crate::config::{AppConfig, RegistryConfig};
+    use crate::server::AppState;
+
+    /// Build a real signed did:webvh log entry for testing.
+    ///
+    /// Generates a fresh Ed25519 signing key per call, builds the DID
+    /// document via the same `build_did_document` helper production
+    /// uses, and signs the log entry via `create_log_entry` — the
+    /// resulting jsonl passes the cryptographic-proof verifier in
+    /// `verify_did_log_proofs`. Replaces an earlier static fixture
+    /// that string-replaced `state.id` and so produced an entry whose
+    /// proof was correctly-formed but signed for a different DID.
+    ///
+    /// `scid` parameter is ignored — the SCID is derived from the
+    /// signed log entry. Kept in the signature for call-site
+    /// compatibility with the prior helper; tests that asserted a
+    /// specific scid have been updated to read it from the resulting
+    /// record's `did_id`.
+    async fn build_test_did_log(_scid: &str, host_encoded: &str, path: &str) -> String {
+        let signing = Secret::generate_ed25519(None, None);
+        let signing_pub_mb = signing
+            .get_public_keymultibase()
+            .expect("signing public key multibase");
+        let doc = build_did_document(
+            host_encoded,
+            path,
+            &signing_pub_mb,
+            &DidDocumentOptions::default(),
+        );
+        let (_scid, jsonl) = create_log_entry(&doc, &signing)
+            .await
+            .expect("create_log_entry");
+        jsonl
+    }
+
+    /// Same as [`build_test_did_log`] but the document advertises the
+    /// requested transports, so the `services` cache has something to read.
+    async fn build_test_did_log_with_transports(
+        host_encoded: &str,
+        path: &str,
+        tsp: bool,
+        didcomm: bool,
+    ) -> String {
+        const MED: &str = "did:webvh:QmMED:mediator.example.com";
+        let signing = Secret::generate_ed25519(None, None);
+        let signing_pub_mb = signing
+            .get_public_keymultibase()
+            .expect("signing public key multibase");
+        let doc = build_did_document(
+            host_encoded,
+            path,
+            &signing_pub_mb,
+            &DidDocumentOptions {
+                key_agreement_multibase: None,
+                mediator_endpoint: didcomm.then_some(MED),
+                tsp_endpoint: tsp.then_some(MED),
+            },
+        );
+        let (_scid, jsonl) = create_log_entry(&doc, &signing)
+            .await
+            .expect("create_log_entry");
+        jsonl
+    }
+
+    async fn stored_services(state: &AppState, path: &str) -> Option<Vec<String>> {
+        let record: DidRecord = state
+            .dids_ks
+            .get(did_key(path))
+            .await
+            .unwrap()
+            .expect("record");
+        record.services
+    }
+
+    async fn test_state() -> (AppState, tempfile::TempDir) {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let store_config = StoreConfig {
+            data_dir: PathBuf::from(dir.path()),
+            ..StoreConfig::default()
+        };
+        let store = Store::open(&store_config).await.expect("open store");
+        let sessions_ks = store.keyspace(KS_SESSIONS).expect("sessions ks");
+        let acl_ks = store.keyspace(KS_ACL).expect("acl ks");
+        let registry_ks = store.keyspace(KS_REGISTRY).expect("registry ks");
+        let dids_ks = store.keyspace(KS_DIDS).expect("dids ks");
+        let stats_ks = store.keyspace(KS_STATS).expect("stats ks");
+
+        let config = AppConfig {
+            features: FeaturesConfig::default(),
+            server_did: Some("did:webvh:test:control.example.com".into()),
+            mediator_did: None,
+            public_url: Some("http://control.test".into()),
+            did_hosting_url: Some("http://control.test".into()),
+            server: ServerConfig::default(),
+            log: LogConfig::default(),
+            store: store_config,
+            auth: AuthConfig::default(),
+            secrets: SecretsConfig::default(),
+            vta: VtaConfig::default(),
+            registry: RegistryConfig::default(),
+            trust_tasks: Default::default(),
+            hosting: Default::default(),
+            identity: Default::default(),
+            consent: Default::default(),
+            config_path: PathBuf::new(),
+        };
+
+        let state = AppState {
+            store: store.clone(),
+            sessions_ks,
+            acl_ks,
+            registry_ks,
+            dids_ks,
+            config: Arc::new(config),
+            did_resolver: None,
+            secrets_resolver: None,
+            identity: None,
+            trust_tasks_verifier: None,
+            jwt_keys: None,
+            webauthn: None,
+            http_client: reqwest::Client::new(),
+            didcomm_service: Arc::new(OnceLock::new()),
+            stats_collector: Arc::new(StatsCollector::new()),
+            stats_ks: stats_ks.clone(),
+            timeseries_ks: store.keyspace(KS_TIMESERIES).expect("timeseries ks"),
+            signing_key_bytes: None,
+            replay_cache: Arc::new(crate::replay::ReplayCache::new()),
+            path_locks: crate::path_locks::PathLocks::new(),
+            acl_locks: did_hosting_common::server::path_locks::PathLocks::new(),
+            pending_challenges: Arc::new(crate::pending_challenges::PendingChallengeTracker::new()),
+            ip_rate_limiter: Arc::new(crate::rate_limit::IpRateLimiter::new()),
+            outbox_notify: Arc::new(tokio::sync::Notify::new()),
+        };
+
+        (state, dir)
+    }
+
+    fn owner_auth(did: &str) -> AuthClaims {
+        AuthClaims {
+            did: did.to_string(),
+            role: Role::Owner,
+            session_pubkey_b58btc: None,
+            session_id: String::new(),
+            amr: vec!["did".to_string()],
+            acr: "aal1".to_string(),
+            api_key: None,
+        }
+    }
+
+    fn admin_auth(did: &str) -> AuthClaims {
+        AuthClaims {
+            did: did.to_string(),
+            role: Role::Admin,
+            session_pubkey_b58btc: None,
+            session_id: String::new(),
+            amr: vec!["did".to_string()],
+            acr: "aal1".to_string(),
+            api_key: None,
+        }
+    }
+
+    #[test]
+    fn api_key_domain_scope_confines_did_ops() {
+        use did_hosting_common::server::auth::api_key::ApiKeyScope;
+
+        let session = owner_auth("did:example:ci");
+        assert!(check_api_key_domain(&session, None).is_ok());
+
+        let mut key = owner_auth("did:example:ci");
+        key.api_key = Some(ApiKeyScope {
+            id: "k1".into(),
+            tasks: Vec::new(),
+            domains: vec!["a.example".into()],
+        });
+        assert!(check_api_key_domain(&key, Some("a.example")).is_ok());
+        assert!(matches!(
+            check_api_key_domain(&key, Some("b.example")),
+            Err(AppError::Forbidden(_))
+        ));
+        assert!(check_api_key_domain(&key, None).is_err());
+
+        key.api_key.as_mut().unwrap().domains.clear();
+        assert!(check_api_key_domain(&key, None).is_ok());
+    }
+
+    /// Listings drop DIDs outside a domain-restricted key's domains,
+    /// for the owner listing and the admin-wide one alike.
+    #[tokio::test]
+    async fn api_key_domain_scope_filters_listings() {
+        use did_hosting_common::server::auth::api_key::ApiKeyScope;
+
+        let (state, _dir) = test_state().await;
+        let owner = "did:example:owner";
+        let did_log = build_test_did_log("scid-list", "control.test", "listed").await;
+        register_did_atomic(&owner_auth(owner), &state, "listed", &did_log, None, false)
+            .await
+            .unwrap();
+
+        let scoped = |mut auth: AuthClaims, domain: &str| {
+            auth.api_key = Some(ApiKeyScope {
+                id: "k1".into(),
+                tasks: Vec::new(),
+                domains: vec![domain.into()],
+            });
+            auth
+        };
+        for auth in [owner_auth(owner), admin_auth("did:example:admin")] {
+            let seen = list_dids(
+                &scoped(auth.clone(), "control.test"),
+                &state,
+                Some(owner),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+            assert_eq!(seen.len(), 1);
+            let hidden = list_dids(
+                &scoped(auth.clone(), "other.example"),
+                &state,
+                Some(owner),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+            assert!(hidden.is_empty());
+            let all = list_dids(&scoped(auth, "other.example"), &state, None, None, None)
+                .await
+                .unwrap();
+            assert!(all.is_empty());
+        }
+    }
+
+    /// Fresh slot, well-formed did.jsonl, caller becomes owner.
+    #[tokio::test]
+    async fn fresh_slot_succeeds_and_writes_atomically() {
+        let (state, _dir) = test_state().await;
+        let owner = "did:example:owner";
+        let path = "alpha";
+        let did_log = build_test_did_log("scid-alpha", "control.test", path).await;
+
+        let result = register_did_atomic(&owner_auth(owner), &state, path, &did_log, None, false)
+            .await
+            .expect("fresh-slot register should succeed");
+        assert_eq!(result.mnemonic, path);
+        assert_eq!(result.did_url, "http://control.test/alpha/did.jsonl");
+
+        // Record + log + owner-index all present after the single batch commit.
+        let record: DidRecord = state
+            .dids_ks
+            .get(did_key(path))
+            .await
+            .unwrap()
+            .expect("record");
+        assert_eq!(record.owner, owner);
+        assert_eq!(record.version_count, 1);
+        // SCID is derived from the signed log entry, so its exact
+        // value depends on the freshly-generated signing key. Pin
+        // the prefix and the host:path suffix instead.
+        let did_id = record.did_id.as_deref().expect("did_id present");
+        assert!(
+            did_id.starts_with("did:webvh:") && did_id.ends_with(":control.test:alpha"),
+            "did_id must be did:webvh:<scid>:control.test:alpha; got {did_id}"
+        );
+
+        let log = state
+            .dids_ks
+            .get_raw(content_log_key(path))
+            .await
+            .unwrap()
+            .expect("log");
+        assert_eq!(log, did_log.as_bytes());
+
+        let owner_idx = state.dids_ks.get_raw(owner_key(owner, path)).await.unwrap();
+        assert!(owner_idx.is_some(), "owner index must be written");
+    }
+
+    /// Same owner re-registering: idempotent path. Slot stays owned by the
+    /// same DID; version_count bumps; created_at preserved; new content
+    /// replaces old without ever leaving the slot empty.
+    #[tokio::test]
+    async fn owner_re_register_is_idempotent() {
+        let (state, _dir) = test_state().await;
+        let owner = "did:example:owner";
+        let path = "beta";
+
+        let log_v1 = build_test_did_log("scid-beta", "control.test", path).await;
+        let r1 = register_did_atomic(&owner_auth(owner), &state, path, &log_v1, None, false)
+            .await
+            .unwrap();
+        let rec_v1: DidRecord = state.dids_ks.get(did_key(path)).await.unwrap().unwrap();
+
+        // Same owner re-registers (potentially with new log content). No
+        // intermediate empty state — old content is replaced in-batch.
+        let log_v2 = build_test_did_log("scid-beta", "control.test", path).await;
+        let r2 = register_did_atomic(&owner_auth(owner), &state, path, &log_v2, None, false)
+            .await
+            .expect("idempotent re-register should succeed without force");
+        assert_eq!(r1.mnemonic, r2.mnemonic);
+
+        let rec_v2: DidRecord = state.dids_ks.get(did_key(path)).await.unwrap().unwrap();
+        assert_eq!(rec_v2.owner, owner);
+        assert_eq!(rec_v2.version_count, rec_v1.version_count + 1);
+        assert_eq!(
+            rec_v2.created_at, rec_v1.created_at,
+            "owner re-register must preserve created_at"
+        );
+    }
+
+    /// A different non-admin caller hitting an existing slot is forbidden,
+    /// regardless of force.
+    #[tokio::test]
+    async fn other_owner_without_admin_forbidden() {
+        let (state, _dir) = test_state().await;
+        let path = "gamma";
+        let owner_a = "did:example:owner-a";
+        let owner_b = "did:example:owner-b";
+        let log = build_test_did_log("scid-gamma", "control.test", path).await;
+
+        register_did_atomic(&owner_auth(owner_a), &state, path, &log, None, false)
+            .await
+            .unwrap();
+
+        // Without force.
+        let err = register_did_atomic(&owner_auth(owner_b), &state, path, &log, None, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Forbidden(_)));
+
+        // With force — still 403, since caller is not admin.
+        let err = register_did_atomic(&owner_auth(owner_b), &state, path, &log, None, true)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Forbidden(_)));
+    }
+
+    /// Admin without force on a slot owned by someone else is forbidden —
+    /// `force` is the explicit "yes really take this from the previous
+    /// owner" gate.
+    #[tokio::test]
+    async fn admin_takeover_requires_force() {
+        let (state, _dir) = test_state().await;
+        let path = "delta";
+        let log = build_test_did_log("scid-delta", "control.test", path).await;
+
+        register_did_atomic(
+            &owner_auth("did:example:owner-a"),
+            &state,
+            path,
+            &log,
+            None,
+            false,
+        )
+        .await
+        .unwrap();
+
+        let err = register_did_atomic(
+            &admin_auth("did:example:admin"),
+            &state,
+            path,
+            &log,
+            None,
+            false,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::Forbidden(ref m) if m.contains("force")));
+    }
+
+    /// Admin with force succeeds; caller becomes the new owner; old
+    /// owner-index entry is removed; old witness is cleared (was signed
+    /// for the prior DID identifier).
+    #[tokio::test]
+    async fn admin_takeover_with_force_succeeds() {
+        let (state, _dir) = test_state().await;
+        let path = "epsilon";
+        let owner_a = "did:example:owner-a";
+        let admin = "did:example:admin";
+        let log = build_test_did_log("scid-epsilon", "control.test", path).await;
+
+        register_did_atomic(&owner_auth(owner_a), &state, path, &log, None, false)
+            .await
+            .unwrap();
+        // Seed a witness file as if the original owner had uploaded one.
+        state
+            .dids_ks
+            .insert_raw(content_witness_key(path), b"prior-witness".to_vec())
+            .await
+            .unwrap();
+
+        register_did_atomic(&admin_auth(admin), &state, path, &log, None, true)
+            .await
+            .expect("admin force takeover should succeed");
+
+        let rec: DidRecord = state.dids_ks.get(did_key(path)).await.unwrap().unwrap();
+        assert_eq!(rec.owner, admin);
+        assert_eq!(rec.version_count, 1, "takeover resets version_count");
+
+        // Old owner-index entry removed; new one present.
+        assert!(
+            state
+                .dids_ks
+                .get_raw(owner_key(owner_a, path))
+                .await
+                .unwrap()
+                .is_none(),
+            "old owner-index entry must be removed on takeover"
+        );
+        assert!(
+            state
+                .dids_ks
+                .get_raw(owner_key(admin, path))
+                .await
+                .unwrap()
+                .is_some(),
+            "new owner-index entry must be present after takeover"
+        );
+
+        // Stale witness cleared.
+        assert!(
+            state
+                .dids_ks
+                .get_raw(content_witness_key(path))
+                .await
+                .unwrap()
+                .is_none(),
+            "prior witness must be cleared on takeover (signed for prior DID)"
+        );
+    }
+
+    /// did_log's path component doesn't match the requested slot — rejected
+    /// before any storage write.
+    #[tokio::test]
+    async fn mismatched_did_path_rejected() {
+        let (state, _dir) = test_state().await;
+        // Log claims path "wrong-path" but request is for path "right-path".
+        let log = build_test_did_log("scid-x", "control.test", "wrong-path").await;
+        let err = register_did_atomic(
+            &owner_auth("did:example:owner"),
+            &state,
+            "right-path",
+            &log,
+            None,
+            false,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::Validation(ref m) if m.contains("path")));
+        assert!(
+            state
+                .dids_ks
+                .get_raw(did_key("right-path"))
+                .await
+                .unwrap()
+                .is_none(),
+            "no record must be written when validation fails"
+        );
+    }
+
+    /// did_log claims a different host — claim-jumping prevention.
+    #[tokio::test]
+    async fn mismatched_did_host_rejected() {
+        let (state, _dir) = test_state().await;
+        let log = build_test_did_log("scid-x", "other-host.example", "valid-path").await;
+        let err = register_did_atomic(
+            &owner_auth("did:example:owner"),
+            &state,
+            "valid-path",
+            &log,
+            None,
+            false,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::Validation(ref m) if m.contains("host")));
+    }
+
+    // ---- T20b: multi-domain safety check ----
+
+    /// Seed an active domain for tests that exercise the new
+    /// multi-domain safety check. Returns the canonical name.
+    async fn seed_active_domain(state: &AppState, name: &str) -> String {
+        use did_hosting_common::server::domain::{
+            create_domain,
+            types::{DomainEntry, DomainStatus, DomainUrlScheme},
+        };
+        let entry = DomainEntry {
+            name: name.into(),
+            label: None,
+            scheme: DomainUrlScheme::Https,
+            status: DomainStatus::Active,
+            created_at: 0,
+            default_domain: true,
+            branding: None,
+            witnesses: None,
+            watchers: None,
+            quota: None,
+            well_known_enabled: false,
+            disabled_at: None,
+            purge_at: None,
+        };
+        create_domain(&state.store, &entry).await.unwrap();
+        name.into()
+    }
 
-    use affinidi_tdk::secrets_resolver::secrets::Secret;
-    use did_hosting_common::DidRegisterRequest;
-    use did_hosting_common::did::{DidDocumentOptions, build_did_document, create_log_entry};
-    use did_hosting_common::server::config::{
-        AuthConfig, FeaturesConfig, LogConfig, SecretsConfig, ServerConfig, StoreConfig, VtaConfig,
-    };
-    use did_hosting_common::server::stats_collector::StatsCollector;
-    use did_hosting_common::server::store::Store;
+    /// Seed an ACL entry for a caller with an explicit DomainScope.
+    async fn seed_caller_acl(
+        state: &AppState,
+        did: &str,
+        role: Role,
+        scope: did_hosting_common::server::domain::DomainScope,
+    ) {
+        use crate::acl::store_acl_entry;
+        use did_hosting_common::server::acl::AclEntry;
+        let entry = AclEntry {
+            did: did.into(),
+            role,
+            label: None,
+            created_at: 0,
+            max_total_size: None,
+            max_did_count: None,
+            domains: scope,
+        };
+        store_acl_entry(&state.acl_ks, &entry).await.unwrap();
+    }
 
-    use crate::acl::Role;
-    use crate::config::{AppConfig, RegistryConfig};
-    use crate::server::AppState;
+    /// With a domain configured and the caller's ACL scoped to it,
+    /// a DID on that domain registers normally.
+    #[tokio::test]
+    async fn register_with_domain_seeded_owner_scoped_to_matching_host() {
+        use did_hosting_common::server::domain::DomainScope;
+        let (state, _dir) = test_state().await;
+        let owner = "did:example:scoped";
+        seed_active_domain(&state, "control.test").await;
+        seed_caller_acl(
+            &state,
+            owner,
+            Role::Owner,
+            DomainScope::Allowed {
+                domains: vec!["control.test".into()],
+            },
+        )
+        .await;
 
-    /// Build a real signed did:webvh log entry for testing.
-    ///
-    /// Generates a fresh Ed25519 signing key per call, builds the DID
-    /// document via the same `build_did_document` helper production
-    /// uses, and signs the log entry via `create_log_entry` — the
-    /// resulting jsonl passes the cryptographic-proof verifier in
-    /// `verify_did_log_proofs`. Replaces an earlier static fixture
-    /// that string-replaced `state.id` and so produced an entry whose
-    /// proof was correctly-formed but signed for a different DID.
-    ///
-    /// `scid` parameter is ignored — the SCID is derived from the
-    /// signed log entry. Kept in the signature for call-site
-    /// compatibility with the prior helper; tests that asserted a
-    /// specific scid have been updated to read it from the resulting
-    /// record's `did_id`.
-    async fn build_test_did_log(_scid: &str, host_encoded: &str, path: &str) -> String {
-        let signing = Secret::generate_ed25519(None, None);
-        let signing_pub_mb = signing
-            .get_public_keymultibase()
-            .expect("signing public key multibase");
-        let doc = build_did_document(
-            host_encoded,
-            path,
-            &signing_pub_mb,
-            &DidDocumentOptions::default(),
-        );
-        let (_scid, jsonl) = create_log_entry(&doc, &signing)
-            .await
-            .expect("create_log_entry");
-        jsonl
+        let did_log = build_test_did_log("scid-ok", "control.test", "alpha").await;
+        let result =
+            register_did_atomic(&owner_auth(owner), &state, "alpha", &did_log, None, false)
+                .await
+                .expect("scoped owner on matching host must succeed");
+        assert_eq!(result.mnemonic, "alpha");
     }
 
-    /// Same as [`build_test_did_log`] but the document advertises the
-    /// requested transports, so the `services` cache has something to read.
-    async fn build_test_did_log_with_transports(
-        host_encoded: &str,
-        path: &str,
-        tsp: bool,
-        didcomm: bool,
-    ) -> String {
-        const MED: &str = "did:webvh:QmMED:mediator.example.com";
-        let signing = Secret::generate_ed25519(None, None);
-        let signing_pub_mb = signing
-            .get_public_keymultibase()
-            .expect("signing public key multibase");
-        let doc = build_did_document(
-            host_encoded,
-            path,
-            &signing_pub_mb,
-            &DidDocumentOptions {
-                key_agreement_multibase: None,
-                mediator_endpoint: didcomm.then_some(MED),
-                tsp_endpoint: tsp.then_some(MED),
+    /// Same domain configured, but the caller's ACL allows only
+    /// `domain-a` while the DID is on `domain-b`. The legacy host
+    /// validator would already reject this (host != public_url),
+    /// but the safety check fires first and returns Forbidden (403)
+    /// rather than Validation (400) — distinct error codes are how
+    /// the UI tells "we don't serve that domain" from "you can't
+    /// post there".
+    #[tokio::test]
+    async fn register_acl_rejects_host_outside_allowed_scope() {
+        use did_hosting_common::server::domain::DomainScope;
+        let (state, _dir) = test_state().await;
+        let owner = "did:example:scoped";
+        // Two active domains: control.test AND domain-b.example.
+        seed_active_domain(&state, "control.test").await;
+        {
+            use did_hosting_common::server::domain::{
+                create_domain,
+                types::{DomainEntry, DomainStatus, DomainUrlScheme},
+            };
+            create_domain(
+                &state.store,
+                &DomainEntry {
+                    name: "domain-b.example".into(),
+                    label: None,
+                    scheme: DomainUrlScheme::Https,
+                    status: DomainStatus::Active,
+                    created_at: 0,
+                    default_domain: false,
+                    branding: None,
+                    witnesses: None,
+                    watchers: None,
+                    quota: None,
+                    well_known_enabled: false,
+                    disabled_at: None,
+                    purge_at: None,
+                },
+            )
+            .await
+            .unwrap();
+        }
+        // Caller's ACL only allows control.test.
+        seed_caller_acl(
+            &state,
+            owner,
+            Role::Owner,
+            DomainScope::Allowed {
+                domains: vec!["control.test".into()],
             },
+        )
+        .await;
+
+        let did_log = build_test_did_log("scid-evil", "domain-b.example", "alpha").await;
+        let err = register_did_atomic(&owner_auth(owner), &state, "alpha", &did_log, None, false)
+            .await
+            .expect_err("ACL must reject host outside scope");
+        assert!(
+            matches!(err, AppError::Forbidden(_)),
+            "expected Forbidden, got {err:?}"
         );
-        let (_scid, jsonl) = create_log_entry(&doc, &signing)
+        // No record written.
+        assert!(
+            state
+                .dids_ks
+                .get_raw(did_key("alpha"))
+                .await
+                .unwrap()
+                .is_none(),
+            "no record may be written when ACL rejects the host"
+        );
+    }
+
+    /// `Admin` role short-circuits the ACL scope check — admins can
+    /// write to any **active** domain regardless of their ACL
+    /// `domains` field.
+    #[tokio::test]
+    async fn register_admin_can_write_any_active_domain() {
+        use did_hosting_common::server::domain::DomainScope;
+        let (state, _dir) = test_state().await;
+        let admin = "did:example:admin";
+        seed_active_domain(&state, "control.test").await;
+        seed_caller_acl(
+            &state,
+            admin,
+            Role::Admin,
+            DomainScope::Allowed {
+                domains: vec!["irrelevant.example".into()],
+            },
+        )
+        .await;
+
+        let did_log = build_test_did_log("scid-admin", "control.test", "alpha").await;
+        register_did_atomic(&admin_auth(admin), &state, "alpha", &did_log, None, false)
             .await
-            .expect("create_log_entry");
-        jsonl
+            .expect("admin role overrides ACL domain scope");
     }
 
-    async fn stored_services(state: &AppState, path: &str) -> Option<Vec<String>> {
+    /// Malformed did.jsonl is rejected by `validate_did_jsonl`.
+    #[tokio::test]
+    async fn invalid_did_log_rejected() {
+        let (state, _dir) = test_state().await;
+        let err = register_did_atomic(
+            &owner_auth("did:example:owner"),
+            &state,
+            "any-path",
+            "not valid jsonl",
+            None,
+            false,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+    }
+
+    /// A root did:webvh — `did:webvh:{SCID}:<host>`, no path segments,
+    /// resolving at `https://<host>/.well-known/did.jsonl` — registers at
+    /// the reserved `.well-known` slot.
+    ///
+    /// Regression: `register_did_atomic` used to validate `path` with
+    /// `validate_custom_path`, which rejects `.well-known` by design (the
+    /// leading dot is not in the `[a-z0-9-]` segment charset). An operator
+    /// self-hosting their domain therefore could not publish the domain's
+    /// own DID through register at all — every attempt came back
+    /// `e.p.did.path-invalid`, and no other `path` value works either
+    /// (empty is rejected by the dispatcher, and any legal mnemonic would
+    /// fail the DID↔path equality check below).
+    #[tokio::test]
+    async fn register_root_did_at_well_known_as_admin() {
+        let (state, _dir) = test_state().await;
+        // Empty mnemonic ⇒ root DID for the host.
+        let did_log = build_test_did_log("scid-root", "control.test", "").await;
+
+        register_did_atomic(
+            &admin_auth("did:example:admin"),
+            &state,
+            ".well-known",
+            &did_log,
+            None,
+            false,
+        )
+        .await
+        .expect("admin may register the root DID at the .well-known slot");
+
         let record: DidRecord = state
             .dids_ks
-            .get(did_key(path))
+            .get(did_key(".well-known"))
             .await
             .unwrap()
-            .expect("record");
-        record.services
+            .expect("root slot record written");
+        assert_eq!(record.mnemonic, ".well-known");
+        assert_eq!(
+            record.did_id.as_deref(),
+            extract_did_id(&did_log).as_deref(),
+            "the root slot must hold the registered root DID"
+        );
     }
 
-    async fn test_state() -> (AppState, tempfile::TempDir) {
-        let dir = tempfile::tempdir().expect("temp dir");
-        let store_config = StoreConfig {
-            data_dir: PathBuf::from(dir.path()),
-            ..StoreConfig::default()
-        };
-        let store = Store::open(&store_config).await.expect("open store");
-        let sessions_ks = store.keyspace(KS_SESSIONS).expect("sessions ks");
-        let acl_ks = store.keyspace(KS_ACL).expect("acl ks");
-        let registry_ks = store.keyspace(KS_REGISTRY).expect("registry ks");
-        let dids_ks = store.keyspace(KS_DIDS).expect("dids ks");
-        let stats_ks = store.keyspace(KS_STATS).expect("stats ks");
+    /// Root is admin-only, mirroring `create_did`'s carve-out — a domain's
+    /// root slot can belong to only one tenant, so an owner-role caller
+    /// must not be able to claim it.
+    #[tokio::test]
+    async fn register_root_did_forbidden_for_non_admin() {
+        let (state, _dir) = test_state().await;
+        let did_log = build_test_did_log("scid-root", "control.test", "").await;
 
-        let config = AppConfig {
-            features: FeaturesConfig::default(),
-            server_did: Some("did:webvh:test:control.example.com".into()),
-            mediator_did: None,
-            public_url: Some("http://control.test".into()),
-            did_hosting_url: Some("http://control.test".into()),
-            server: ServerConfig::default(),
-            log: LogConfig::default(),
-            store: store_config,
-            auth: AuthConfig::default(),
-            secrets: SecretsConfig::default(),
-            vta: VtaConfig::default(),
-            registry: RegistryConfig::default(),
-            trust_tasks: Default::default(),
-            hosting: Default::default(),
-            identity: Default::default(),
-            config_path: PathBuf::new(),
-        };
+        let err = register_did_atomic(
+            &owner_auth("did:example:owner"),
+            &state,
+            ".well-known",
+            &did_log,
+            None,
+            false,
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            matches!(err, AppError::Forbidden(_)),
+            "non-admin root register must be Forbidden, got {err:?}"
+        );
+    }
 
-        let state = AppState {
-            store: store.clone(),
-            sessions_ks,
-            acl_ks,
-            registry_ks,
-            dids_ks,
-            config: Arc::new(config),
-            did_resolver: None,
-            secrets_resolver: None,
-            identity: None,
-            trust_tasks_verifier: None,
-            jwt_keys: None,
-            webauthn: None,
-            http_client: reqwest::Client::new(),
-            didcomm_service: Arc::new(OnceLock::new()),
-            stats_collector: Arc::new(StatsCollector::new()),
-            stats_ks: stats_ks.clone(),
-            timeseries_ks: store.keyspace(KS_TIMESERIES).expect("timeseries ks"),
-            signing_key_bytes: None,
-            replay_cache: Arc::new(crate::replay::ReplayCache::new()),
-            path_locks: crate::path_locks::PathLocks::new(),
-            acl_locks: did_hosting_common::server::path_locks::PathLocks::new(),
-            pending_challenges: Arc::new(crate::pending_challenges::PendingChallengeTracker::new()),
-            ip_rate_limiter: Arc::new(crate::rate_limit::IpRateLimiter::new()),
-            pending_confirms: Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
-            outbox_notify: Arc::new(tokio::sync::Notify::new()),
-        };
+    /// The root carve-out is exactly `.well-known` and nothing else — any
+    /// other dot-prefixed path still fails the segment charset rules, even
+    /// for an admin. Guards against widening `validate_mnemonic` into a
+    /// general escape hatch.
+    #[tokio::test]
+    async fn register_rejects_other_dotted_paths_even_for_admin() {
+        let (state, _dir) = test_state().await;
+        let did_log = build_test_did_log("scid-dot", "control.test", "hidden").await;
 
-        (state, dir)
+        let err = register_did_atomic(
+            &admin_auth("did:example:admin"),
+            &state,
+            ".hidden",
+            &did_log,
+            None,
+            false,
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            matches!(err, AppError::Validation(_)),
+            "dotted non-root path must still be rejected, got {err:?}"
+        );
     }
 
-    fn owner_auth(did: &str) -> AuthClaims {
-        AuthClaims {
-            did: did.to_string(),
-            role: Role::Owner,
-            session_pubkey_b58btc: None,
-            session_id: String::new(),
-            amr: vec!["did".to_string()],
-            acr: "aal1".to_string(),
-        }
+    /// Smoke test: the wire request type round-trips with default `force`
+    /// and the legacy `did_log` field still parses (T26 backwards-compat).
+    #[test]
+    fn did_register_request_round_trip_defaults_force_false() {
+        let raw = r#"{"path":"alpha","did_log":"line"}"#;
+        let req: DidRegisterRequest = serde_json::from_str(raw).unwrap();
+        assert_eq!(req.path, "alpha");
+        assert_eq!(req.did_log.as_deref(), Some("line"));
+        assert!(!req.force, "force must default to false");
+        let (method, payload) = req.resolve().unwrap();
+        assert_eq!(method, "webvh");
+        assert_eq!(payload, b"line");
     }
 
-    fn admin_auth(did: &str) -> AuthClaims {
-        AuthClaims {
-            did: did.to_string(),
-            role: Role::Admin,
-            session_pubkey_b58btc: None,
-            session_id: String::new(),
-            amr: vec!["did".to_string()],
-            acr: "aal1".to_string(),
-        }
+    /// Pin the stats-counter behaviour: every successful
+    /// `register_did_atomic` advances the aggregate `total_updates` and the
+    /// per-DID `last_updated_at`. Without this the dashboards / `MSG_INFO`
+    /// response will report zero updates in self-hosted (control-plane-as-
+    /// authoritative) deployments. Mirrors did-hosting-server::publish_did's
+    /// `record_update` call.
+    #[tokio::test]
+    async fn register_did_atomic_records_update_stats() {
+        let (state, _dir) = test_state().await;
+        let owner = "did:example:owner";
+        let path = "stats-fresh";
+        let did_log = build_test_did_log("scid-stats", "control.test", path).await;
+
+        let before = state.stats_collector.get_aggregate().total_updates;
+        register_did_atomic(&owner_auth(owner), &state, path, &did_log, None, false)
+            .await
+            .unwrap();
+        let after = state.stats_collector.get_aggregate().total_updates;
+        assert_eq!(
+            after,
+            before + 1,
+            "fresh atomic register must advance total_updates by 1"
+        );
+
+        // A second register by the same owner advances again — the counter
+        // tracks log-write operations, not unique DIDs.
+        register_did_atomic(&owner_auth(owner), &state, path, &did_log, None, false)
+            .await
+            .unwrap();
+        let after_two = state.stats_collector.get_aggregate().total_updates;
+        assert_eq!(
+            after_two,
+            after + 1,
+            "idempotent re-register must also advance total_updates"
+        );
     }
 
-    /// Fresh slot, well-formed did.jsonl, caller becomes owner.
+    /// Mirror coverage for the `publish_did` path. Pre-create a slot via
+    /// `create_did` (which does NOT bump update stats — it only reserves
+    /// the mnemonic), then publish a log against it and assert the counter
+    /// only moves on the publish.
     #[tokio::test]
-    async fn fresh_slot_succeeds_and_writes_atomically() {
+    async fn publish_did_records_update_stats() {
         let (state, _dir) = test_state().await;
         let owner = "did:example:owner";
-        let path = "alpha";
-        let did_log = build_test_did_log("scid-alpha", "control.test", path).await;
+        let path = "stats-publish";
+        let did_log = build_test_did_log("scid-publish", "control.test", path).await;
 
-        let result = register_did_atomic(&owner_auth(owner), &state, path, &did_log, false)
+        // Reserve the slot. create_did is the "request URI" step — it
+        // doesn't write log content and shouldn't bump update counters.
+        let baseline = state.stats_collector.get_aggregate().total_updates;
+        create_did(&owner_auth(owner), &state, Some(path), false, None)
             .await
-            .expect("fresh-slot register should succeed");
-        assert_eq!(result.mnemonic, path);
-        assert_eq!(result.did_url, "http://control.test/alpha/did.jsonl");
+            .unwrap();
+        assert_eq!(
+            state.stats_collector.get_aggregate().total_updates,
+            baseline,
+            "create_did is a slot reservation; must NOT count as an update"
+        );
 
-        // Record + log + owner-index all present after the single batch commit.
-        let record: DidRecord = state
-            .dids_ks
-            .get(did_key(path))
+        // Publish flips the counter.
+        publish_did(&owner_auth(owner), &state, path, &did_log, None)
             .await
-            .unwrap()
-            .expect("record");
-        assert_eq!(record.owner, owner);
-        assert_eq!(record.version_count, 1);
-        // SCID is derived from the signed log entry, so its exact
-        // value depends on the freshly-generated signing key. Pin
-        // the prefix and the host:path suffix instead.
-        let did_id = record.did_id.as_deref().expect("did_id present");
-        assert!(
-            did_id.starts_with("did:webvh:") && did_id.ends_with(":control.test:alpha"),
-            "did_id must be did:webvh:<scid>:control.test:alpha; got {did_id}"
+            .unwrap();
+        assert_eq!(
+            state.stats_collector.get_aggregate().total_updates,
+            baseline + 1,
+            "publish_did must record an update on success"
         );
 
-        let log = state
-            .dids_ks
-            .get_raw(content_log_key(path))
+        // Republishing advances again.
+        publish_did(&owner_auth(owner), &state, path, &did_log, None)
             .await
-            .unwrap()
-            .expect("log");
-        assert_eq!(log, did_log.as_bytes());
-
-        let owner_idx = state.dids_ks.get_raw(owner_key(owner, path)).await.unwrap();
-        assert!(owner_idx.is_some(), "owner index must be written");
+            .unwrap();
+        assert_eq!(
+            state.stats_collector.get_aggregate().total_updates,
+            baseline + 2,
+            "subsequent publishes must keep advancing total_updates"
+        );
     }
 
-    /// Same owner re-registering: idempotent path. Slot stays owned by the
-    /// same DID; version_count bumps; created_at preserved; new content
-    /// replaces old without ever leaving the slot empty.
+    /// F5: the caller's explicit `?domain=` is cross-checked against the DID's
+    /// host (a DID's host IS its domain). A mismatch is rejected as
+    /// `did-management:unknown_domain`; a match or an omitted domain publishes.
     #[tokio::test]
-    async fn owner_re_register_is_idempotent() {
+    async fn publish_cross_checks_explicit_domain_against_did_host() {
         let (state, _dir) = test_state().await;
         let owner = "did:example:owner";
-        let path = "beta";
-
-        let log_v1 = build_test_did_log("scid-beta", "control.test", path).await;
-        let r1 = register_did_atomic(&owner_auth(owner), &state, path, &log_v1, false)
+        let path = "domain-xcheck";
+        // The DID's host — i.e. its domain — is `control.test`.
+        let did_log = build_test_did_log("scid-domain", "control.test", path).await;
+        create_did(&owner_auth(owner), &state, Some(path), false, None)
             .await
             .unwrap();
-        let rec_v1: DidRecord = state.dids_ks.get(did_key(path)).await.unwrap().unwrap();
 
-        // Same owner re-registers (potentially with new log content). No
-        // intermediate empty state — old content is replaced in-batch.
-        let log_v2 = build_test_did_log("scid-beta", "control.test", path).await;
-        let r2 = register_did_atomic(&owner_auth(owner), &state, path, &log_v2, false)
+        // No domain stated → unchanged behaviour: publishes.
+        publish_did(&owner_auth(owner), &state, path, &did_log, None)
             .await
-            .expect("idempotent re-register should succeed without force");
-        assert_eq!(r1.mnemonic, r2.mnemonic);
+            .expect("omitted domain still publishes");
+
+        // Matching domain → publishes.
+        publish_did(
+            &owner_auth(owner),
+            &state,
+            path,
+            &did_log,
+            Some("control.test"),
+        )
+        .await
+        .expect("matching domain publishes");
 
-        let rec_v2: DidRecord = state.dids_ks.get(did_key(path)).await.unwrap().unwrap();
-        assert_eq!(rec_v2.owner, owner);
-        assert_eq!(rec_v2.version_count, rec_v1.version_count + 1);
-        assert_eq!(
-            rec_v2.created_at, rec_v1.created_at,
-            "owner re-register must preserve created_at"
+        // Mismatched domain → rejected before the log lands.
+        let err = publish_did(
+            &owner_auth(owner),
+            &state,
+            path,
+            &did_log,
+            Some("evil.example"),
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            matches!(err, AppError::Validation(ref m) if m.contains("unknown_domain")),
+            "mismatched domain must be rejected as unknown_domain, got {err:?}"
         );
     }
 
-    /// A different non-admin caller hitting an existing slot is forbidden,
-    /// regardless of force.
+    /// Failed publishes (auth denied, validation error, missing slot) must
+    /// NOT bump the counter — `record_update` runs only after the storage
+    /// commit returns Ok. Pinning this prevents drift if someone moves the
+    /// call earlier in the function.
     #[tokio::test]
-    async fn other_owner_without_admin_forbidden() {
+    async fn failed_publish_does_not_record_update() {
         let (state, _dir) = test_state().await;
-        let path = "gamma";
         let owner_a = "did:example:owner-a";
         let owner_b = "did:example:owner-b";
-        let log = build_test_did_log("scid-gamma", "control.test", path).await;
+        let path = "stats-fail";
+        let did_log = build_test_did_log("scid-fail", "control.test", path).await;
 
-        register_did_atomic(&owner_auth(owner_a), &state, path, &log, false)
+        // Reserve as owner-a, then have owner-b try to publish.
+        create_did(&owner_auth(owner_a), &state, Some(path), false, None)
             .await
             .unwrap();
 
-        // Without force.
-        let err = register_did_atomic(&owner_auth(owner_b), &state, path, &log, false)
-            .await
-            .unwrap_err();
-        assert!(matches!(err, AppError::Forbidden(_)));
-
-        // With force — still 403, since caller is not admin.
-        let err = register_did_atomic(&owner_auth(owner_b), &state, path, &log, true)
+        let baseline = state.stats_collector.get_aggregate().total_updates;
+        let err = publish_did(&owner_auth(owner_b), &state, path, &did_log, None)
             .await
             .unwrap_err();
         assert!(matches!(err, AppError::Forbidden(_)));
-    }
-
-    /// Admin without force on a slot owned by someone else is forbidden —
-    /// `force` is the explicit "yes really take this from the previous
-    /// owner" gate.
-    #[tokio::test]
-    async fn admin_takeover_requires_force() {
-        let (state, _dir) = test_state().await;
-        let path = "delta";
-        let log = build_test_did_log("scid-delta", "control.test", path).await;
-
-        register_did_atomic(
-            &owner_auth("did:example:owner-a"),
-            &state,
-            path,
-            &log,
-            false,
-        )
-        .await
-        .unwrap();
+        assert_eq!(
+            state.stats_collector.get_aggregate().total_updates,
+            baseline,
+            "auth-denied publish must NOT record an update"
+        );
 
-        let err = register_did_atomic(&admin_auth("did:example:admin"), &state, path, &log, false)
+        // Validation failure on the JSONL body — still no counter movement.
+        let err = publish_did(&owner_auth(owner_a), &state, path, "not-jsonl", None)
             .await
             .unwrap_err();
-        assert!(matches!(err, AppError::Forbidden(ref m) if m.contains("force")));
+        assert!(matches!(err, AppError::Validation(_)));
+        assert_eq!(
+            state.stats_collector.get_aggregate().total_updates,
+            baseline,
+            "validation-failed publish must NOT record an update"
+        );
     }
 
-    /// Admin with force succeeds; caller becomes the new owner; old
-    /// owner-index entry is removed; old witness is cleared (was signed
-    /// for the prior DID identifier).
+    /// Concurrency: two parallel `register_did_atomic` calls on the same
+    /// fresh path serialise via the per-path write lock — exactly one
+    /// wins, the other races the lock. Without the lock both could
+    /// observe `existing == None`, both build version_count=1 records,
+    /// and both commit; the second silently overwrites the first.
+    ///
+    /// We can't deterministically pick which caller wins (the loser
+    /// could be either), but we CAN assert (a) both calls succeed
+    /// without panicking, (b) the on-disk record reflects exactly one
+    /// of the two callers (not a mash-up), (c) the version_count is 2
+    /// (the second call's idempotent re-register bumps it from the
+    /// first call's 1).
     #[tokio::test]
-    async fn admin_takeover_with_force_succeeds() {
+    async fn concurrent_register_serialises() {
         let (state, _dir) = test_state().await;
-        let path = "epsilon";
-        let owner_a = "did:example:owner-a";
-        let admin = "did:example:admin";
-        let log = build_test_did_log("scid-epsilon", "control.test", path).await;
+        let owner_a = "did:example:owner-a".to_string();
+        let path = "race-path";
+        let log_a = build_test_did_log("scid-race", "control.test", path).await;
+        let log_b = log_a.clone();
 
-        register_did_atomic(&owner_auth(owner_a), &state, path, &log, false)
-            .await
-            .unwrap();
-        // Seed a witness file as if the original owner had uploaded one.
-        state
-            .dids_ks
-            .insert_raw(content_witness_key(path), b"prior-witness".to_vec())
-            .await
-            .unwrap();
+        let auth_a = owner_auth(&owner_a);
+        let auth_b = owner_auth(&owner_a); // same owner, different concurrent attempts
 
-        register_did_atomic(&admin_auth(admin), &state, path, &log, true)
-            .await
-            .expect("admin force takeover should succeed");
+        let state_a = state.clone();
+        let state_b = state.clone();
+        let path_a = path.to_string();
+        let path_b = path.to_string();
 
-        let rec: DidRecord = state.dids_ks.get(did_key(path)).await.unwrap().unwrap();
-        assert_eq!(rec.owner, admin);
-        assert_eq!(rec.version_count, 1, "takeover resets version_count");
+        let task_a = tokio::spawn(async move {
+            register_did_atomic(&auth_a, &state_a, &path_a, &log_a, None, false).await
+        });
+        let task_b = tokio::spawn(async move {
+            register_did_atomic(&auth_b, &state_b, &path_b, &log_b, None, false).await
+        });
 
-        // Old owner-index entry removed; new one present.
-        assert!(
-            state
-                .dids_ks
-                .get_raw(owner_key(owner_a, path))
-                .await
-                .unwrap()
-                .is_none(),
-            "old owner-index entry must be removed on takeover"
-        );
+        let r_a = task_a.await.unwrap();
+        let r_b = task_b.await.unwrap();
+        // Both calls succeed because they're from the same owner — the
+        // second is treated as an idempotent re-publish and bumps
+        // version_count.
         assert!(
-            state
-                .dids_ks
-                .get_raw(owner_key(admin, path))
-                .await
-                .unwrap()
-                .is_some(),
-            "new owner-index entry must be present after takeover"
+            r_a.is_ok() && r_b.is_ok(),
+            "both same-owner registers should succeed; got a={r_a:?}, b={r_b:?}"
         );
 
-        // Stale witness cleared.
-        assert!(
-            state
-                .dids_ks
-                .get_raw(content_witness_key(path))
-                .await
-                .unwrap()
-                .is_none(),
-            "prior witness must be cleared on takeover (signed for prior DID)"
+        let record: DidRecord = state
+            .dids_ks
+            .get(did_key(path))
+            .await
+            .unwrap()
+            .expect("record present after race");
+        assert_eq!(record.owner, owner_a);
+        // Two sequential commits via the lock => version_count == 2.
+        // Without the lock, the race could land on 1 (both observe
+        // None and both write version_count=1).
+        assert_eq!(
+            record.version_count, 2,
+            "both register calls must have committed in sequence; got version_count={}",
+            record.version_count
         );
     }
 
-    /// did_log's path component doesn't match the requested slot — rejected
-    /// before any storage write.
+    /// `create_did(..., Some("tenant.example"))` MUST persist the
+    /// caller-supplied domain on the DidRecord. This is the bug
+    /// b0e2fb11 fixed: previously `RequestUriRequest` had no
+    /// `domain` field, every record landed with `domain: ""`, and the
+    /// per-domain UI filters / dashboard stats hid the freshly-
+    /// created DID until the M-01 backfill sweep next ran. Pinning
+    /// the persisted value guards against the next regression in
+    /// `request_uri` / `dispatch_did_op` forgetting to thread the
+    /// resolver output through.
     #[tokio::test]
-    async fn mismatched_did_path_rejected() {
+    async fn create_did_persists_caller_supplied_domain() {
         let (state, _dir) = test_state().await;
-        // Log claims path "wrong-path" but request is for path "right-path".
-        let log = build_test_did_log("scid-x", "control.test", "wrong-path").await;
-        let err = register_did_atomic(
-            &owner_auth("did:example:owner"),
-            &state,
-            "right-path",
-            &log,
-            false,
-        )
-        .await
-        .unwrap_err();
-        assert!(matches!(err, AppError::Validation(ref m) if m.contains("path")));
-        assert!(
-            state
-                .dids_ks
-                .get_raw(did_key("right-path"))
-                .await
-                .unwrap()
-                .is_none(),
-            "no record must be written when validation fails"
-        );
-    }
+        let owner = "did:example:owner";
+        let path = "alpha-domain";
 
-    /// did_log claims a different host — claim-jumping prevention.
-    #[tokio::test]
-    async fn mismatched_did_host_rejected() {
-        let (state, _dir) = test_state().await;
-        let log = build_test_did_log("scid-x", "other-host.example", "valid-path").await;
-        let err = register_did_atomic(
-            &owner_auth("did:example:owner"),
+        create_did(
+            &owner_auth(owner),
             &state,
-            "valid-path",
-            &log,
+            Some(path),
             false,
+            Some("tenant.example"),
         )
         .await
-        .unwrap_err();
-        assert!(matches!(err, AppError::Validation(ref m) if m.contains("host")));
-    }
-
-    // ---- T20b: multi-domain safety check ----
-
-    /// Seed an active domain for tests that exercise the new
-    /// multi-domain safety check. Returns the canonical name.
-    async fn seed_active_domain(state: &AppState, name: &str) -> String {
-        use did_hosting_common::server::domain::{
-            create_domain,
-            types::{DomainEntry, DomainStatus, DomainUrlScheme},
-        };
-        let entry = DomainEntry {
-            name: name.into(),
-            label: None,
-            scheme: DomainUrlScheme::Https,
-            status: DomainStatus::Active,
-            created_at: 0,
-            default_domain: true,
-            branding: None,
-            witnesses: None,
-            watchers: None,
-            quota: None,
-            well_known_enabled: false,
-            disabled_at: None,
-            purge_at: None,
-        };
-        create_domain(&state.store, &entry).await.unwrap();
-        name.into()
+        .expect("create_did with domain should succeed");
+
+        let record: DidRecord = state
+            .dids_ks
+            .get(did_key(path))
+            .await
+            .unwrap()
+            .expect("record");
+        assert_eq!(
+            record.domain, "tenant.example",
+            "domain on the DidRecord MUST equal the caller-supplied value"
+        );
     }
 
-    /// Seed an ACL entry for a caller with an explicit DomainScope.
-    async fn seed_caller_acl(
+    // ---- Quotas ----
+
+    async fn seed_owner_limits(
         state: &AppState,
         did: &str,
-        role: Role,
-        scope: did_hosting_common::server::domain::DomainScope,
+        max_did_count: Option<u64>,
+        max_total_size: Option<u64>,
     ) {
         use crate::acl::store_acl_entry;
         use did_hosting_common::server::acl::AclEntry;
         let entry = AclEntry {
             did: did.into(),
-            role,
+            role: Role::Owner,
             label: None,
             created_at: 0,
-            max_total_size: None,
-            max_did_count: None,
-            domains: scope,
+            max_total_size,
+            max_did_count,
+            domains: Default::default(),
         };
         store_acl_entry(&state.acl_ks, &entry).await.unwrap();
     }
 
-    /// With a domain configured and the caller's ACL scoped to it,
-    /// a DID on that domain registers normally.
     #[tokio::test]
-    async fn register_with_domain_seeded_owner_scoped_to_matching_host() {
-        use did_hosting_common::server::domain::DomainScope;
+    async fn owner_did_count_quota_refuses_new_slots() {
         let (state, _dir) = test_state().await;
-        let owner = "did:example:scoped";
-        seed_active_domain(&state, "control.test").await;
-        seed_caller_acl(
+        let owner = "did:example:capped";
+        seed_owner_limits(&state, owner, Some(1), None).await;
+
+        create_did(&owner_auth(owner), &state, Some("one"), false, None)
+            .await
+            .expect("first slot is within quota");
+        let err = create_did(&owner_auth(owner), &state, Some("two"), false, None)
+            .await
+            .expect_err("second slot exceeds quota");
+        assert!(matches!(err, AppError::QuotaExceeded(_)), "got {err:?}");
+        assert_eq!(err.didcomm_code(), "e.p.did.quota-exceeded");
+
+        let did_log = build_test_did_log("s", "control.test", "three").await;
+        let err = register_did_atomic(&owner_auth(owner), &state, "three", &did_log, None, false)
+            .await
+            .expect_err("register also counts");
+        assert!(matches!(err, AppError::QuotaExceeded(_)));
+
+        // Admins are exempt.
+        create_did(
+            &admin_auth("did:example:admin"),
             &state,
-            owner,
-            Role::Owner,
-            DomainScope::Allowed {
-                domains: vec!["control.test".into()],
+            Some("four"),
+            false,
+            None,
+        )
+        .await
+        .expect("admin is quota-exempt");
+
+        let report = quota::owner_report(&state, owner).await.unwrap();
+        assert_eq!(report.usage.did_count, 1);
+        assert_eq!(report.limits.max_did_count, Some(1));
+    }
+
+    /// Two transfers onto one capped owner run their checks under that
+    /// owner's lock, so only one of them fits.
+    #[tokio::test]
+    async fn concurrent_owner_transfers_respect_the_new_owner_quota() {
+        let (state, _dir) = test_state().await;
+        let capped = "did:example:capped";
+        seed_owner_limits(&state, capped, Some(1), None).await;
+        // Different domains, so only the owner lock can serialise them.
+        for (owner, slot, host) in [
+            ("did:example:a", "slot-a", "a.example.com"),
+            ("did:example:b", "slot-b", "b.example.com"),
+        ] {
+            seed_owner_limits(&state, owner, None, None).await;
+            let record = DidRecord {
+                owner: owner.to_string(),
+                mnemonic: slot.to_string(),
+                created_at: 0,
+                updated_at: 0,
+                version_count: 1,
+                did_id: Some(format!("did:webvh:seed:{host}:{slot}")),
+                content_size: 0,
+                disabled: false,
+                deleted_at: None,
+                method: "webvh".to_string(),
+                domain: host.to_string(),
+                services: None,
+                agent_names: Vec::new(),
+                deactivated_at: None,
+                sync_head: None,
+            };
+            state.dids_ks.insert(did_key(slot), &record).await.unwrap();
+        }
+
+        let (auth_a, auth_b) = (owner_auth("did:example:a"), owner_auth("did:example:b"));
+        let (a, b) = tokio::join!(
+            change_did_owner(&auth_a, &state, "slot-a", capped),
+            change_did_owner(&auth_b, &state, "slot-b", capped),
+        );
+        assert_eq!(
+            u8::from(a.is_ok()) + u8::from(b.is_ok()),
+            1,
+            "exactly one transfer fits: {a:?} / {b:?}"
+        );
+        let err = a.err().or(b.err()).unwrap();
+        assert!(matches!(err, AppError::QuotaExceeded(_)), "got {err:?}");
+        assert_eq!(
+            quota::owner_usage(&state, capped).await.unwrap().did_count,
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn mutations_append_a_verifiable_audit_chain() {
+        let (state, _dir) = test_state().await;
+        let owner = "did:example:audited";
+        let did_log = build_test_did_log("s", "control.test", "audited").await;
+
+        register_did_atomic(&owner_auth(owner), &state, "audited", &did_log, None, false)
+            .await
+            .unwrap();
+        set_did_disabled(&owner_auth(owner), &state, "audited", true)
+            .await
+            .unwrap();
+        delete_did(&owner_auth(owner), &state, "audited", None)
+            .await
+            .unwrap();
+
+        let records = audit::all_records(&state.store).await.unwrap();
+        let actions: Vec<_> = records.iter().map(|r| r.action.as_str()).collect();
+        assert_eq!(actions, ["did.register", "did.disable", "did.delete"]);
+        assert!(records[0].before.is_none() && records[0].after.is_some());
+        assert_eq!(records[1].before, records[0].after);
+        assert!(records[2].after.is_none());
+        let actor = records[0].actor.as_ref().unwrap();
+        assert_eq!(actor.did, owner);
+        assert!(!actor.step_up);
+
+        let head = audit::head(&state.store).await.unwrap();
+        let report = audit::verify_chain(&records, &[], false, head.as_ref());
+        assert!(report.is_ok(), "{:?}", report.problems);
+
+        let page = audit::list(
+            &state.store,
+            &audit::Filter {
+                action: Some("did.d".into()),
+                ..Default::default()
             },
         )
-        .await;
+        .await
+        .unwrap();
+        assert!(
+            page.records.is_empty(),
+            "exact match unless the prefix ends in '.'"
+        );
 
-        let did_log = build_test_did_log("scid-ok", "control.test", "alpha").await;
-        let result = register_did_atomic(&owner_auth(owner), &state, "alpha", &did_log, false)
+        let page = audit::list(
+            &state.store,
+            &audit::Filter {
+                limit: Some(2),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(page.records.len(), 2);
+        assert_eq!(page.next_after_seq, Some(1));
+        let rest = audit::list(
+            &state.store,
+            &audit::Filter {
+                after_seq: page.next_after_seq,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(rest.records.len(), 1);
+        assert_eq!(rest.next_after_seq, None);
+    }
+
+    #[tokio::test]
+    async fn audit_records_wait_for_the_baton() {
+        use did_hosting_common::server::store::KS_AUDIT;
+        let (state, _dir) = test_state().await;
+        let owner = "did:example:audited";
+        let ks = state.store.keyspace(KS_AUDIT).unwrap();
+
+        create_did(&owner_auth(owner), &state, Some("one"), false, None)
             .await
-            .expect("scoped owner on matching host must succeed");
-        assert_eq!(result.mnemonic, "alpha");
+            .unwrap();
+        // Another writer holds the baton: the record stays staged.
+        let baton = ks.take_raw("next").await.unwrap().expect("baton laid");
+        create_did(&owner_auth(owner), &state, Some("two"), false, None)
+            .await
+            .unwrap();
+        assert_eq!(audit::all_records(&state.store).await.unwrap().len(), 1);
+        assert_eq!(ks.prefix_iter_raw("staged:").await.unwrap().len(), 1);
+
+        ks.insert_raw("next", baton).await.unwrap();
+        assert_eq!(audit::chain_staged(&state).await.unwrap(), 1);
+        let records = audit::all_records(&state.store).await.unwrap();
+        assert_eq!(records[1].subject, "two");
+        let head = audit::head(&state.store).await.unwrap();
+        assert!(audit::verify_chain(&records, &[], false, head.as_ref()).is_ok());
+        assert!(ks.prefix_iter_raw("staged:").await.unwrap().is_empty());
     }
 
-    /// Same domain configured, but the caller's ACL allows only
-    /// `domain-a` while the DID is on `domain-b`. The legacy host
-    /// validator would already reject this (host != public_url),
-    /// but the safety check fires first and returns Forbidden (403)
-    /// rather than Validation (400) — distinct error codes are how
-    /// the UI tells "we don't serve that domain" from "you can't
-    /// post there".
+    /// A baton laid twice for one `seq` (a re-lay while the holder was only
+    /// slow) must not let the second writer overwrite the first's record.
     #[tokio::test]
-    async fn register_acl_rejects_host_outside_allowed_scope() {
-        use did_hosting_common::server::domain::DomainScope;
+    async fn duplicate_baton_never_overwrites_a_record() {
+        use did_hosting_common::server::store::KS_AUDIT;
         let (state, _dir) = test_state().await;
-        let owner = "did:example:scoped";
-        // Two active domains: control.test AND domain-b.example.
-        seed_active_domain(&state, "control.test").await;
-        {
-            use did_hosting_common::server::domain::{
-                create_domain,
-                types::{DomainEntry, DomainStatus, DomainUrlScheme},
-            };
-            create_domain(
-                &state.store,
-                &DomainEntry {
-                    name: "domain-b.example".into(),
-                    label: None,
-                    scheme: DomainUrlScheme::Https,
-                    status: DomainStatus::Active,
-                    created_at: 0,
-                    default_domain: false,
-                    branding: None,
-                    witnesses: None,
-                    watchers: None,
-                    quota: None,
-                    well_known_enabled: false,
-                    disabled_at: None,
-                    purge_at: None,
-                },
-            )
+        let owner = "did:example:audited";
+        let ks = state.store.keyspace(KS_AUDIT).unwrap();
+
+        create_did(&owner_auth(owner), &state, Some("one"), false, None)
             .await
             .unwrap();
-        }
-        // Caller's ACL only allows control.test.
-        seed_caller_acl(
+        let stale = ks.get_raw("next").await.unwrap().expect("baton laid");
+        create_did(&owner_auth(owner), &state, Some("two"), false, None)
+            .await
+            .unwrap();
+        // The same `seq` is handed out again.
+        ks.insert_raw("next", stale).await.unwrap();
+        create_did(&owner_auth(owner), &state, Some("three"), false, None)
+            .await
+            .unwrap();
+        assert_eq!(ks.prefix_iter_raw("staged:").await.unwrap().len(), 1);
+
+        assert_eq!(audit::chain_staged(&state).await.unwrap(), 1);
+        let records = audit::all_records(&state.store).await.unwrap();
+        let subjects: Vec<&str> = records.iter().map(|r| r.subject.as_str()).collect();
+        assert_eq!(subjects, ["one", "two", "three"]);
+        let head = audit::head(&state.store).await.unwrap();
+        assert!(audit::verify_chain(&records, &[], false, head.as_ref()).is_ok());
+        assert!(ks.prefix_iter_raw("staged:").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn lifecycle_mutations_queue_webhook_events() {
+        let (state, _dir) = test_state().await;
+        let owner = "did:example:hooked";
+        let sub = webhooks::Subscription {
+            id: "hooked".into(),
+            created_by: owner.into(),
+            scope: webhooks::Scope::Owner { did: owner.into() },
+            url: "https://hooks.example.com/in".into(),
+            events: Vec::new(),
+            signing: webhooks::Signing::Hmac,
+            secret: webhooks::generate_secret(),
+            enabled: true,
+            created_at: 0,
+            updated_at: 0,
+        };
+        webhooks::put(&state.store, &sub).await.unwrap();
+        let did_log = build_test_did_log("s", "control.test", "hooked").await;
+
+        register_did_atomic(&owner_auth(owner), &state, "hooked", &did_log, None, false)
+            .await
+            .unwrap();
+        set_did_disabled(&owner_auth(owner), &state, "hooked", true)
+            .await
+            .unwrap();
+        delete_did(&owner_auth(owner), &state, "hooked", None)
+            .await
+            .unwrap();
+        // Someone else's DID is not this subscription's business.
+        let other_log = build_test_did_log("s", "control.test", "other").await;
+        register_did_atomic(
+            &owner_auth("did:example:other"),
             &state,
-            owner,
-            Role::Owner,
-            DomainScope::Allowed {
-                domains: vec!["control.test".into()],
-            },
+            "other",
+            &other_log,
+            None,
+            false,
         )
-        .await;
+        .await
+        .unwrap();
 
-        let did_log = build_test_did_log("scid-evil", "domain-b.example", "alpha").await;
-        let err = register_did_atomic(&owner_auth(owner), &state, "alpha", &did_log, false)
+        // Events are staged with each mutation and fanned out by the worker.
+        assert!(
+            webhooks::list_pending(&state.store, "hooked")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(webhooks::fan_out_staged(&state.store).await.unwrap(), 3);
+        assert_eq!(webhooks::fan_out_staged(&state.store).await.unwrap(), 0);
+        let pending = webhooks::list_pending(&state.store, "hooked")
             .await
-            .expect_err("ACL must reject host outside scope");
+            .unwrap();
+        let types: Vec<_> = pending
+            .iter()
+            .map(|(_, d)| d.event.event_type.as_str())
+            .collect();
+        assert_eq!(
+            types,
+            [
+                webhooks::DID_REGISTERED,
+                webhooks::DID_DISABLED,
+                webhooks::DID_DELETED
+            ]
+        );
+        let registered = &pending[0].1.event;
+        assert_eq!(registered.mnemonic.as_deref(), Some("hooked"));
+        assert_eq!(registered.owner.as_deref(), Some(owner));
+        assert!(registered.did.is_some());
         assert!(
-            matches!(err, AppError::Forbidden(_)),
-            "expected Forbidden, got {err:?}"
+            registered.domain.is_some(),
+            "falls back to the DID's host when the record carries no domain"
         );
-        // No record written.
+    }
+
+    /// Rollback keeps the dropped line (and witness) under the DID rather
+    /// than discarding it. The second line here is a copy of the first, so
+    /// it can never re-verify: undo must refuse it with `Conflict` and
+    /// leave the archive row where it is.
+    #[tokio::test]
+    async fn rollback_archives_dropped_entry() {
+        let (state, _dir) = test_state().await;
+        let owner = "did:example:roller";
+        let auth = owner_auth(owner);
+        let did_log = build_test_did_log("s", "control.test", "roll").await;
+        register_did_atomic(&auth, &state, "roll", &did_log, None, false)
+            .await
+            .unwrap();
+        let line = did_log.trim_end();
+        state
+            .dids_ks
+            .insert_raw(
+                content_log_key("roll"),
+                format!("{line}\n{line}").into_bytes(),
+            )
+            .await
+            .unwrap();
+        state
+            .dids_ks
+            .insert_raw(content_witness_key("roll"), b"{\"proofs\":[]}".to_vec())
+            .await
+            .unwrap();
+
+        let err = undo_rollback_did(&auth, &state, "roll", None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)), "got {err:?}");
+
+        let (record, _) = rollback_did(&auth, &state, "roll").await.unwrap();
+        assert_eq!(record.version_count, 1);
         assert!(
             state
                 .dids_ks
-                .get_raw(did_key("alpha"))
+                .get_raw(content_witness_key("roll"))
                 .await
                 .unwrap()
-                .is_none(),
-            "no record may be written when ACL rejects the host"
+                .is_none()
+        );
+
+        let archived = list_archived_entries(&auth, &state, "roll").await.unwrap();
+        assert_eq!(archived.len(), 1);
+        assert!(archived[0].has_witness);
+        assert_eq!(archived[0].archived_by, owner);
+        assert!(archived[0].entry.is_some(), "archived line is parsed");
+        let err = list_archived_entries(&owner_auth("did:example:nosy"), &state, "roll")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Forbidden(_)), "got {err:?}");
+
+        let err = undo_rollback_did(&auth, &state, "roll", Some(&archived[0].id))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)), "got {err:?}");
+        assert_eq!(
+            list_archived_entries(&auth, &state, "roll")
+                .await
+                .unwrap()
+                .len(),
+            1,
+            "a refused undo keeps the entry"
+        );
+
+        delete_did(&auth, &state, "roll", None).await.unwrap();
+        assert!(
+            log_archive::all_keys(&state.dids_ks, "roll")
+                .await
+                .unwrap()
+                .is_empty(),
+            "deleting the DID drops its archive"
         );
     }
 
-    /// `Admin` role short-circuits the ACL scope check — admins can
-    /// write to any **active** domain regardless of their ACL
-    /// `domains` field.
+    /// A genesis log at `host/path`, and the same log with a second entry,
+    /// signed by its update key, that only adds an `alsoKnownAs`.
+    async fn build_two_version_log(host: &str, path: &str) -> (String, String) {
+        use didwebvh_rs::DIDWebVHState;
+        use didwebvh_rs::parameters::Parameters;
+
+        let mut signing = Secret::generate_ed25519(None, None);
+        let signing_pub_mb = signing
+            .get_public_keymultibase()
+            .expect("signing public key multibase");
+        signing.id = format!("did:key:{signing_pub_mb}#{signing_pub_mb}");
+        let doc = build_did_document(host, path, &signing_pub_mb, &DidDocumentOptions::default());
+        let params = Parameters {
+            update_keys: Some(Arc::new(vec![signing_pub_mb.clone().into()])),
+            ..Default::default()
+        };
+        let mut webvh = DIDWebVHState::default();
+        webvh
+            .create_log_entry(
+                Some((chrono::Utc::now() - chrono::Duration::minutes(1)).fixed_offset()),
+                &doc,
+                &params,
+                &signing,
+            )
+            .await
+            .expect("genesis entry");
+        let jsonl = |webvh: &DIDWebVHState| {
+            webvh
+                .log_entries()
+                .iter()
+                .map(|e| serde_json::to_string(&e.log_entry).unwrap())
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        let genesis = jsonl(&webvh);
+
+        let last = webvh.log_entries().last().unwrap();
+        let mut updated = last.get_state().clone();
+        updated["alsoKnownAs"] = serde_json::json!(["https://example.com/alias"]);
+        let mut params = last.validated_parameters.clone();
+        params.update_keys = Some(Arc::new(vec![signing_pub_mb.into()]));
+        webvh
+            .create_log_entry(None, &updated, &params, &signing)
+            .await
+            .expect("update entry");
+        (genesis, jsonl(&webvh))
+    }
+
+    /// Rollback then undo puts the log, witness and record back as they
+    /// were, and spends the archived entry.
     #[tokio::test]
-    async fn register_admin_can_write_any_active_domain() {
-        use did_hosting_common::server::domain::DomainScope;
+    async fn undo_rollback_restores_the_dropped_entry() {
         let (state, _dir) = test_state().await;
-        let admin = "did:example:admin";
-        seed_active_domain(&state, "control.test").await;
-        seed_caller_acl(
-            &state,
-            admin,
-            Role::Admin,
-            DomainScope::Allowed {
-                domains: vec!["irrelevant.example".into()],
-            },
-        )
-        .await;
+        let owner = "did:example:roller";
+        let auth = owner_auth(owner);
+        let (genesis, updated) = build_two_version_log("control.test", "undo").await;
+        register_did_atomic(&auth, &state, "undo", &genesis, None, false)
+            .await
+            .unwrap();
+        publish_did(&auth, &state, "undo", &updated, None)
+            .await
+            .unwrap();
+        let witness = b"{\"proofs\":[]}".to_vec();
+        state
+            .dids_ks
+            .insert_raw(content_witness_key("undo"), witness.clone())
+            .await
+            .unwrap();
+        let published = get_record(&state, "undo").await;
+        assert_eq!(published.version_count, 2);
 
-        let did_log = build_test_did_log("scid-admin", "control.test", "alpha").await;
-        register_did_atomic(&admin_auth(admin), &state, "alpha", &did_log, false)
+        let (rolled, _) = rollback_did(&auth, &state, "undo").await.unwrap();
+        assert_eq!(rolled.version_count, 1);
+        assert_eq!(rolled.content_size, genesis.len() as u64);
+
+        let (restored, meta) = undo_rollback_did(&auth, &state, "undo", None)
+            .await
+            .unwrap();
+        assert_eq!(restored.version_count, 2);
+        assert_eq!(restored.content_size, published.content_size);
+        assert_eq!(restored.did_id, published.did_id);
+        assert_eq!(
+            meta.unwrap().latest_version_id,
+            extract_log_metadata(&updated).latest_version_id
+        );
+        let log = state
+            .dids_ks
+            .get_raw(content_log_key("undo"))
             .await
-            .expect("admin role overrides ACL domain scope");
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(log).unwrap().trim_end(),
+            updated.trim_end()
+        );
+        assert_eq!(
+            state
+                .dids_ks
+                .get_raw(content_witness_key("undo"))
+                .await
+                .unwrap(),
+            Some(witness),
+            "the log is what the rollback left, so the witness comes back"
+        );
+        assert!(
+            list_archived_entries(&auth, &state, "undo")
+                .await
+                .unwrap()
+                .is_empty(),
+            "the archived entry is spent"
+        );
+        let usage: quota::Usage = state
+            .dids_ks
+            .get(did_ops::domain_usage_key("control.test"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(usage.total_size, published.content_size);
+        let records = audit::all_records(&state.store).await.unwrap();
+        assert_eq!(records.last().unwrap().action, "did.undo-rollback");
     }
 
-    /// Malformed did.jsonl is rejected by `validate_did_jsonl`.
+    /// `build_test_did_log` never sets `portable`, so it cannot move host.
     #[tokio::test]
-    async fn invalid_did_log_rejected() {
+    async fn import_refuses_non_portable_source() {
         let (state, _dir) = test_state().await;
-        let err = register_did_atomic(
-            &owner_auth("did:example:owner"),
+        let auth = owner_auth("did:example:mover");
+        let source = build_test_did_log("s", "old.example.com", "alice").await;
+
+        let err = start_import(
+            &auth,
             &state,
-            "any-path",
-            "not valid jsonl",
+            ImportSource::Log(&source),
+            Some("alice"),
             false,
+            None,
         )
         .await
         .unwrap_err();
-        assert!(matches!(err, AppError::Validation(_)));
+        assert!(
+            matches!(err, AppError::Validation(ref m) if m.contains("portable")),
+            "got {err:?}"
+        );
+        assert!(is_path_available(&state.dids_ks, "alice").await.unwrap());
     }
 
-    /// A root did:webvh — `did:webvh:{SCID}:<host>`, no path segments,
-    /// resolving at `https://<host>/.well-known/did.jsonl` — registers at
-    /// the reserved `.well-known` slot.
-    ///
-    /// Regression: `register_did_atomic` used to validate `path` with
-    /// `validate_custom_path`, which rejects `.well-known` by design (the
-    /// leading dot is not in the `[a-z0-9-]` segment charset). An operator
-    /// self-hosting their domain therefore could not publish the domain's
-    /// own DID through register at all — every attempt came back
-    /// `e.p.did.path-invalid`, and no other `path` value works either
-    /// (empty is rejected by the dispatcher, and any legal mnemonic would
-    /// fail the DID↔path equality check below).
     #[tokio::test]
-    async fn register_root_did_at_well_known_as_admin() {
+    async fn complete_import_checks_submission_against_offer() {
         let (state, _dir) = test_state().await;
-        // Empty mnemonic ⇒ root DID for the host.
-        let did_log = build_test_did_log("scid-root", "control.test", "").await;
+        let auth = owner_auth("did:example:mover");
+        let source = build_test_did_log("s", "old.example.com", "alice").await;
+        create_did(&auth, &state, Some("alice"), false, None)
+            .await
+            .unwrap();
 
-        register_did_atomic(
-            &admin_auth("did:example:admin"),
-            &state,
-            ".well-known",
-            &did_log,
-            false,
-        )
-        .await
-        .expect("admin may register the root DID at the .well-known slot");
+        let err = complete_import(&auth, &state, "alice", &source)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)), "got {err:?}");
 
-        let record: DidRecord = state
+        let mut pending = PendingImport {
+            source_did: extract_did_id(&source).unwrap(),
+            source_log: source.trim().to_string(),
+            target_did: "did:webvh:Q:control.test:alice".into(),
+            did_url: "http://control.test/alice/did.jsonl".into(),
+            created_at: 0,
+            expires_at: u64::MAX,
+        };
+        state
             .dids_ks
-            .get(did_key(".well-known"))
+            .insert(did_ops::pending_import_key("alice"), &pending)
             .await
-            .unwrap()
-            .expect("root slot record written");
-        assert_eq!(record.mnemonic, ".well-known");
-        assert_eq!(
-            record.did_id.as_deref(),
-            extract_did_id(&did_log).as_deref(),
-            "the root slot must hold the registered root DID"
+            .unwrap();
+
+        let offer = get_import(&auth, &state, "alice").await.unwrap();
+        assert_eq!(offer.also_known_as, pending.source_did);
+        assert_eq!(offer.source_version_count, 1);
+        let err = get_import(&owner_auth("did:example:nosy"), &state, "alice")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Forbidden(_)), "got {err:?}");
+
+        let err = complete_import(&auth, &state, "alice", &source)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, AppError::Validation(ref m) if m.contains("no move entry")),
+            "got {err:?}"
+        );
+        let other = build_test_did_log("s", "old.example.com", "bob").await;
+        let err = complete_import(&auth, &state, "alice", &format!("{other}\n{source}"))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, AppError::Validation(ref m) if m.contains("unchanged")),
+            "got {err:?}"
+        );
+
+        pending.expires_at = 1;
+        state
+            .dids_ks
+            .insert(did_ops::pending_import_key("alice"), &pending)
+            .await
+            .unwrap();
+        let err = complete_import(&auth, &state, "alice", &source)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)), "got {err:?}");
+
+        delete_did(&auth, &state, "alice", None).await.unwrap();
+        assert!(
+            did_import::get(&state.dids_ks, "alice")
+                .await
+                .unwrap()
+                .is_none(),
+            "deleting the slot abandons the import"
         );
     }
 
-    /// Root is admin-only, mirroring `create_did`'s carve-out — a domain's
-    /// root slot can belong to only one tenant, so an owner-role caller
-    /// must not be able to claim it.
     #[tokio::test]
-    async fn register_root_did_forbidden_for_non_admin() {
+    async fn import_moves_a_portable_did_in() {
         let (state, _dir) = test_state().await;
-        let did_log = build_test_did_log("scid-root", "control.test", "").await;
+        let auth = owner_auth("did:example:mover");
+        let (source, moved) =
+            build_portable_move("old.example.com", "alice", "control.test", "alice").await;
+        let source_did = extract_did_id(&source).unwrap();
 
-        let err = register_did_atomic(
-            &owner_auth("did:example:owner"),
+        let offer = start_import(
+            &auth,
             &state,
-            ".well-known",
-            &did_log,
+            ImportSource::Log(&source),
+            Some("alice"),
             false,
+            Some("control.test"),
         )
         .await
-        .unwrap_err();
+        .unwrap();
+        assert_eq!(offer.mnemonic, "alice");
+        assert_eq!(offer.source_did, source_did);
+        assert_eq!(
+            offer.target_did,
+            source_did.replace(":old.example.com:", ":control.test:")
+        );
+        assert_eq!(offer.source_version_count, 1);
+
+        let (record, meta) = complete_import(&auth, &state, "alice", &moved)
+            .await
+            .unwrap();
+        assert_eq!(record.did_id.as_deref(), Some(offer.target_did.as_str()));
+        assert_eq!(record.version_count, 2);
+        assert_eq!(meta.log_entry_count, 2);
         assert!(
-            matches!(err, AppError::Forbidden(_)),
-            "non-admin root register must be Forbidden, got {err:?}"
+            did_import::get(&state.dids_ks, "alice")
+                .await
+                .unwrap()
+                .is_none(),
+            "the offer is spent"
         );
+        let served = get_raw_log(&auth, &state, "alice").await.unwrap();
+        verify_did_log_proofs(&served).unwrap();
+        assert!(did_import::claims_also_known_as(&served, &source_did));
     }
 
-    /// The root carve-out is exactly `.well-known` and nothing else — any
-    /// other dot-prefixed path still fails the segment charset rules, even
-    /// for an admin. Guards against widening `validate_mnemonic` into a
-    /// general escape hatch.
     #[tokio::test]
-    async fn register_rejects_other_dotted_paths_even_for_admin() {
+    async fn expired_imports_release_their_empty_slot() {
         let (state, _dir) = test_state().await;
-        let did_log = build_test_did_log("scid-dot", "control.test", "hidden").await;
+        let auth = owner_auth("did:example:mover");
+        let (source, _) =
+            build_portable_move("old.example.com", "alice", "control.test", "alice").await;
+        for path in ["alice", "bob"] {
+            start_import(
+                &auth,
+                &state,
+                ImportSource::Log(&source),
+                Some(path),
+                false,
+                Some("control.test"),
+            )
+            .await
+            .unwrap();
+        }
+        // `bob` got a log some other way; only its offer should go.
+        let mut bob = get_record(&state, "bob").await;
+        bob.version_count = 1;
+        state
+            .dids_ks
+            .insert(did_ops::did_key("bob"), &bob)
+            .await
+            .unwrap();
 
-        let err = register_did_atomic(
-            &admin_auth("did:example:admin"),
+        assert_eq!(did_import::expire_pending(&state, 1_000).await.unwrap(), 0);
+        let later = crate::auth::session::now_epoch() + did_import::PENDING_IMPORT_TTL_SECS;
+        assert_eq!(did_import::expire_pending(&state, later).await.unwrap(), 2);
+        assert!(is_path_available(&state.dids_ks, "alice").await.unwrap());
+        assert!(
+            state
+                .dids_ks
+                .get_raw(did_ops::owner_key(&auth.did, "alice"))
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(get_record(&state, "bob").await.version_count, 1);
+        assert!(
+            did_import::get(&state.dids_ks, "bob")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    /// `build_test_did_log` never sets `portable`, so a hosted DID built
+    /// with it cannot move either.
+    #[tokio::test]
+    async fn move_refuses_non_portable_log() {
+        let (state, _dir) = test_state().await;
+        let auth = owner_auth("did:example:mover");
+        let did_log = build_test_did_log("s", "control.test", "alice").await;
+        register_did_atomic(&auth, &state, "alice", &did_log, None, false)
+            .await
+            .unwrap();
+        let elsewhere = build_test_did_log("s", "control.test", "alice2").await;
+
+        let err = move_did(
+            &auth,
             &state,
-            ".hidden",
-            &did_log,
-            false,
+            "alice",
+            &format!("{}\n{elsewhere}", did_log.trim_end()),
         )
         .await
         .unwrap_err();
         assert!(
-            matches!(err, AppError::Validation(_)),
-            "dotted non-root path must still be rejected, got {err:?}"
+            matches!(err, AppError::Validation(ref m) if m.contains("portable")),
+            "got {err:?}"
+        );
+        assert!(is_path_available(&state.dids_ks, "alice2").await.unwrap());
+        assert!(
+            did_move::get(&state.dids_ks, "alice")
+                .await
+                .unwrap()
+                .is_none()
         );
     }
 
-    /// Smoke test: the wire request type round-trips with default `force`
-    /// and the legacy `did_log` field still parses (T26 backwards-compat).
-    #[test]
-    fn did_register_request_round_trip_defaults_force_false() {
-        let raw = r#"{"path":"alpha","did_log":"line"}"#;
-        let req: DidRegisterRequest = serde_json::from_str(raw).unwrap();
-        assert_eq!(req.path, "alpha");
-        assert_eq!(req.did_log.as_deref(), Some("line"));
-        assert!(!req.force, "force must default to false");
-        let (method, payload) = req.resolve().unwrap();
-        assert_eq!(method, "webvh");
-        assert_eq!(payload, b"line");
+    /// A portable log at `host/path`, and the same log with an entry,
+    /// signed by its update key, moving it to `to_host/to_path`.
+    async fn build_portable_move(
+        host: &str,
+        path: &str,
+        to_host: &str,
+        to_path: &str,
+    ) -> (String, String) {
+        use didwebvh_rs::DIDWebVHState;
+        use didwebvh_rs::parameters::Parameters;
+
+        let mut signing = Secret::generate_ed25519(None, None);
+        let signing_pub_mb = signing
+            .get_public_keymultibase()
+            .expect("signing public key multibase");
+        signing.id = format!("did:key:{signing_pub_mb}#{signing_pub_mb}");
+        let doc = build_did_document(host, path, &signing_pub_mb, &DidDocumentOptions::default());
+        let params = Parameters {
+            update_keys: Some(Arc::new(vec![signing_pub_mb.clone().into()])),
+            portable: Some(true),
+            ..Default::default()
+        };
+        let mut webvh = DIDWebVHState::default();
+        webvh
+            .create_log_entry(
+                Some((chrono::Utc::now() - chrono::Duration::minutes(1)).fixed_offset()),
+                &doc,
+                &params,
+                &signing,
+            )
+            .await
+            .expect("genesis entry");
+        let jsonl = |webvh: &DIDWebVHState| {
+            webvh
+                .log_entries()
+                .iter()
+                .map(|e| serde_json::to_string(&e.log_entry).unwrap())
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        let genesis = jsonl(&webvh);
+
+        let last = webvh.log_entries().last().unwrap();
+        let old_did = last.get_state()["id"].as_str().unwrap().to_string();
+        let moved = serde_json::to_string(last.get_state())
+            .unwrap()
+            .replace(&format!(":{host}:{path}"), &format!(":{to_host}:{to_path}"));
+        let mut moved: serde_json::Value = serde_json::from_str(&moved).unwrap();
+        moved["alsoKnownAs"] = serde_json::json!([old_did]);
+        let mut params = last.validated_parameters.clone();
+        params.update_keys = Some(Arc::new(vec![signing_pub_mb.into()]));
+        webvh
+            .create_log_entry(None, &moved, &params, &signing)
+            .await
+            .expect("move entry");
+        (genesis, jsonl(&webvh))
     }
 
-    /// Pin the stats-counter behaviour: every successful
-    /// `register_did_atomic` advances the aggregate `total_updates` and the
-    /// per-DID `last_updated_at`. Without this the dashboards / `MSG_INFO`
-    /// response will report zero updates in self-hosted (control-plane-as-
-    /// authoritative) deployments. Mirrors did-hosting-server::publish_did's
-    /// `record_update` call.
     #[tokio::test]
-    async fn register_did_atomic_records_update_stats() {
+    async fn moved_did_resolves_at_both_locations_until_the_stub_expires() {
         let (state, _dir) = test_state().await;
-        let owner = "did:example:owner";
-        let path = "stats-fresh";
-        let did_log = build_test_did_log("scid-stats", "control.test", path).await;
+        let auth = owner_auth("did:example:mover");
+        let (genesis, moved_log) =
+            build_portable_move("control.test", "alice", "control.test", "alice2").await;
+        register_did_atomic(&auth, &state, "alice", &genesis, None, false)
+            .await
+            .unwrap();
+        let old_did = get_record(&state, "alice").await.did_id.unwrap();
+
+        let moved = move_did(&auth, &state, "alice", &moved_log).await.unwrap();
+        assert_eq!(moved.mnemonic, "alice2");
+        assert_eq!(moved.previous_did_id, old_did);
+        assert_eq!(moved.version_count, 2);
+
+        // The new slot resolves to the new identifier with the full log…
+        let (record, meta) = get_did_info(&auth, &state, "alice2").await.unwrap();
+        assert_eq!(record.did_id.as_deref(), Some(moved.did_id.as_str()));
+        assert_eq!(meta.unwrap().log_entry_count, 2);
+        let served = get_raw_log(&auth, &state, "alice2").await.unwrap();
+        verify_did_log_proofs(&served).unwrap();
+        assert!(did_import::claims_also_known_as(&served, &old_did));
+        // …and the old slot still serves it under the old identifier, so a
+        // resolver holding that can follow the move.
+        assert_eq!(get_raw_log(&auth, &state, "alice").await.unwrap(), served);
+        assert_eq!(get_record(&state, "alice").await.did_id, Some(old_did));
+        let err = register_did_atomic(&auth, &state, "alice", &genesis, None, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)), "got {err:?}");
 
-        let before = state.stats_collector.get_aggregate().total_updates;
-        register_did_atomic(&owner_auth(owner), &state, path, &did_log, false)
+        let mut stub = did_move::get(&state.dids_ks, "alice")
             .await
+            .unwrap()
             .unwrap();
-        let after = state.stats_collector.get_aggregate().total_updates;
-        assert_eq!(
-            after,
-            before + 1,
-            "fresh atomic register must advance total_updates by 1"
-        );
+        stub.expires_at = 1;
+        state
+            .dids_ks
+            .insert(did_ops::moved_stub_key("alice"), &stub)
+            .await
+            .unwrap();
+        assert_eq!(did_move::expire_stubs(&state, 1_000).await.unwrap(), 1);
+        assert!(is_path_available(&state.dids_ks, "alice").await.unwrap());
+        assert_eq!(get_raw_log(&auth, &state, "alice2").await.unwrap(), served);
+    }
 
-        // A second register by the same owner advances again — the counter
-        // tracks log-write operations, not unique DIDs.
-        register_did_atomic(&owner_auth(owner), &state, path, &did_log, false)
+    #[tokio::test]
+    async fn moved_stubs_are_read_only_and_expire() {
+        let (state, _dir) = test_state().await;
+        let auth = owner_auth("did:example:mover");
+        for path in ["old", "same", "other"] {
+            let did_log = build_test_did_log("s", "control.test", path).await;
+            register_did_atomic(&auth, &state, path, &did_log, None, false)
+                .await
+                .unwrap();
+        }
+        let old_did = get_record(&state, "old").await.did_id.unwrap();
+        let stub = |old_did: &str, new_mnemonic: &str| MovedStub {
+            old_did: old_did.into(),
+            new_mnemonic: new_mnemonic.into(),
+            new_did: "did:webvh:Q:new.test:x".into(),
+            moved_at: 1,
+            expires_at: u64::MAX,
+            moved_by: auth.did.clone(),
+        };
+        state
+            .dids_ks
+            .insert(did_ops::moved_stub_key("old"), &stub(&old_did, "new"))
             .await
             .unwrap();
-        let after_two = state.stats_collector.get_aggregate().total_updates;
+        state
+            .dids_ks
+            .insert(
+                did_ops::moved_stub_key("same"),
+                &stub("did:webvh:Q:control.test:old", "same"),
+            )
+            .await
+            .unwrap();
+        // A stub naming an identifier its slot no longer holds.
+        state
+            .dids_ks
+            .insert(
+                did_ops::moved_stub_key("other"),
+                &stub("did:webvh:Q:control.test:gone", "new"),
+            )
+            .await
+            .unwrap();
+
+        // Moved away: the old slot takes no more writes.
+        let log = build_test_did_log("s", "control.test", "old").await;
+        let err = publish_did(&auth, &state, "old", &log, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)), "got {err:?}");
+        let err = rollback_did(&auth, &state, "old").await.unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)), "got {err:?}");
+        assert_eq!(did_move::previous_did_id(&state.dids_ks, "old").await, None);
+
+        // Moved in place: still writable, and servers keep the old host.
+        assert_eq!(
+            did_move::previous_did_id(&state.dids_ks, "same")
+                .await
+                .as_deref(),
+            Some("did:webvh:Q:control.test:old")
+        );
+
+        assert_eq!(did_move::expire_stubs(&state, 1_000).await.unwrap(), 0);
+        for key in ["old", "same", "other"] {
+            let mut expired = did_move::get(&state.dids_ks, key).await.unwrap().unwrap();
+            expired.expires_at = 1;
+            state
+                .dids_ks
+                .insert(did_ops::moved_stub_key(key), &expired)
+                .await
+                .unwrap();
+        }
+        assert_eq!(did_move::expire_stubs(&state, 1_000).await.unwrap(), 3);
+        assert!(is_path_available(&state.dids_ks, "old").await.unwrap());
+        assert!(!is_path_available(&state.dids_ks, "other").await.unwrap());
+        assert!(
+            did_move::get(&state.dids_ks, "other")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            state
+                .dids_ks
+                .get_raw(content_log_key("old"))
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(!is_path_available(&state.dids_ks, "same").await.unwrap());
         assert_eq!(
-            after_two,
-            after + 1,
-            "idempotent re-register must also advance total_updates"
+            did_move::previous_did_id(&state.dids_ks, "same").await,
+            None
         );
     }
 
-    /// Mirror coverage for the `publish_did` path. Pre-create a slot via
-    /// `create_did` (which does NOT bump update stats — it only reserves
-    /// the mnemonic), then publish a log against it and assert the counter
-    /// only moves on the publish.
+    /// A real chain whose last entry sets `deactivated: true`, from the
+    /// proof-verifier fuzz corpus.
+    const DEACTIVATED_LOG: &str =
+        include_str!("../../fuzz/corpus/verify_did_log_proofs/chain_deactivated.jsonl");
+
     #[tokio::test]
-    async fn publish_did_records_update_stats() {
+    async fn publishing_a_deactivation_makes_the_did_terminal() {
         let (state, _dir) = test_state().await;
         let owner = "did:example:owner";
-        let path = "stats-publish";
-        let did_log = build_test_did_log("scid-publish", "control.test", path).await;
-
-        // Reserve the slot. create_did is the "request URI" step — it
-        // doesn't write log content and shouldn't bump update counters.
-        let baseline = state.stats_collector.get_aggregate().total_updates;
-        create_did(&owner_auth(owner), &state, Some(path), false, None)
+        let auth = owner_auth(owner);
+        seed_record_on_host(&state, owner, "fuzz", "fuzz.example.com").await;
+        state
+            .dids_ks
+            .insert_raw(owner_key(owner, "fuzz"), b"fuzz".to_vec())
             .await
             .unwrap();
         assert_eq!(
-            state.stats_collector.get_aggregate().total_updates,
-            baseline,
-            "create_did is a slot reservation; must NOT count as an update"
+            quota::owner_usage(&state, owner).await.unwrap().did_count,
+            1
         );
 
-        // Publish flips the counter.
-        publish_did(&owner_auth(owner), &state, path, &did_log, None)
+        publish_did(&auth, &state, "fuzz", DEACTIVATED_LOG, None)
             .await
-            .unwrap();
+            .expect("a deactivating entry is accepted");
+        let record: DidRecord = state.dids_ks.get(did_key("fuzz")).await.unwrap().unwrap();
+        assert!(record.is_deactivated());
+        let listed = list_dids(&auth, &state, None, None, None).await.unwrap();
+        assert_eq!(listed[0].deactivated_at, record.deactivated_at);
         assert_eq!(
-            state.stats_collector.get_aggregate().total_updates,
-            baseline + 1,
-            "publish_did must record an update on success"
+            quota::owner_usage(&state, owner).await.unwrap().did_count,
+            0
         );
 
-        // Republishing advances again.
-        publish_did(&owner_auth(owner), &state, path, &did_log, None)
+        // The log is final.
+        let err = publish_did(&auth, &state, "fuzz", DEACTIVATED_LOG, None)
             .await
-            .unwrap();
-        assert_eq!(
-            state.stats_collector.get_aggregate().total_updates,
-            baseline + 2,
-            "subsequent publishes must keep advancing total_updates"
-        );
-    }
-
-    /// F5: the caller's explicit `?domain=` is cross-checked against the DID's
-    /// host (a DID's host IS its domain). A mismatch is rejected as
-    /// `did-management:unknown_domain`; a match or an omitted domain publishes.
-    #[tokio::test]
-    async fn publish_cross_checks_explicit_domain_against_did_host() {
-        let (state, _dir) = test_state().await;
-        let owner = "did:example:owner";
-        let path = "domain-xcheck";
-        // The DID's host — i.e. its domain — is `control.test`.
-        let did_log = build_test_did_log("scid-domain", "control.test", path).await;
-        create_did(&owner_auth(owner), &state, Some(path), false, None)
+            .unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)), "got {err:?}");
+        let err = rollback_did(&auth, &state, "fuzz").await.unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)), "got {err:?}");
+
+        // And kept until the retention window has passed.
+        let err = delete_did(&auth, &state, "fuzz", None).await.unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)), "got {err:?}");
+        let long_ago = DidRecord {
+            deactivated_at: Some(1),
+            ..record
+        };
+        state
+            .dids_ks
+            .insert(did_key("fuzz"), &long_ago)
             .await
             .unwrap();
-
-        // No domain stated → unchanged behaviour: publishes.
-        publish_did(&owner_auth(owner), &state, path, &did_log, None)
+        delete_did(&auth, &state, "fuzz", None)
             .await
-            .expect("omitted domain still publishes");
-
-        // Matching domain → publishes.
-        publish_did(
-            &owner_auth(owner),
-            &state,
-            path,
-            &did_log,
-            Some("control.test"),
-        )
-        .await
-        .expect("matching domain publishes");
-
-        // Mismatched domain → rejected before the log lands.
-        let err = publish_did(
-            &owner_auth(owner),
-            &state,
-            path,
-            &did_log,
-            Some("evil.example"),
-        )
-        .await
-        .unwrap_err();
-        assert!(
-            matches!(err, AppError::Validation(ref m) if m.contains("unknown_domain")),
-            "mismatched domain must be rejected as unknown_domain, got {err:?}"
-        );
+            .expect("deletable once retention has passed");
     }
 
-    /// Failed publishes (auth denied, validation error, missing slot) must
-    /// NOT bump the counter — `record_update` runs only after the storage
-    /// commit returns Ok. Pinning this prevents drift if someone moves the
-    /// call earlier in the function.
     #[tokio::test]
-    async fn failed_publish_does_not_record_update() {
+    async fn expired_archive_entries_are_hidden_and_pruned() {
         let (state, _dir) = test_state().await;
-        let owner_a = "did:example:owner-a";
-        let owner_b = "did:example:owner-b";
-        let path = "stats-fail";
-        let did_log = build_test_did_log("scid-fail", "control.test", path).await;
-
-        // Reserve as owner-a, then have owner-b try to publish.
-        create_did(&owner_auth(owner_a), &state, Some(path), false, None)
+        let entry = ArchivedEntry {
+            entry: "{}".into(),
+            witness: None,
+            base_log_hash: String::new(),
+            archived_at: 100,
+            expires_at: 200,
+            archived_by: "did:example:someone".into(),
+        };
+        state
+            .dids_ks
+            .insert(did_ops::archived_entry_key("old", 1), &entry)
             .await
             .unwrap();
 
-        let baseline = state.stats_collector.get_aggregate().total_updates;
-        let err = publish_did(&owner_auth(owner_b), &state, path, &did_log, None)
-            .await
-            .unwrap_err();
-        assert!(matches!(err, AppError::Forbidden(_)));
         assert_eq!(
-            state.stats_collector.get_aggregate().total_updates,
-            baseline,
-            "auth-denied publish must NOT record an update"
+            log_archive::list(&state.dids_ks, "old", 199)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(
+            log_archive::list(&state.dids_ks, "old", 200)
+                .await
+                .unwrap()
+                .is_empty()
         );
-
-        // Validation failure on the JSONL body — still no counter movement.
-        let err = publish_did(&owner_auth(owner_a), &state, path, "not-jsonl", None)
-            .await
-            .unwrap_err();
-        assert!(matches!(err, AppError::Validation(_)));
         assert_eq!(
-            state.stats_collector.get_aggregate().total_updates,
-            baseline,
-            "validation-failed publish must NOT record an update"
+            log_archive::prune_expired(&state.store, 150).await.unwrap(),
+            0
+        );
+        assert_eq!(
+            log_archive::prune_expired(&state.store, 200).await.unwrap(),
+            1
+        );
+        assert!(
+            log_archive::all_keys(&state.dids_ks, "old")
+                .await
+                .unwrap()
+                .is_empty()
         );
     }
 
-    /// Concurrency: two parallel `register_did_atomic` calls on the same
-    /// fresh path serialise via the per-path write lock — exactly one
-    /// wins, the other races the lock. Without the lock both could
-    /// observe `existing == None`, both build version_count=1 records,
-    /// and both commit; the second silently overwrites the first.
-    ///
-    /// We can't deterministically pick which caller wins (the loser
-    /// could be either), but we CAN assert (a) both calls succeed
-    /// without panicking, (b) the on-disk record reflects exactly one
-    /// of the two callers (not a mash-up), (c) the version_count is 2
-    /// (the second call's idempotent re-register bumps it from the
-    /// first call's 1).
     #[tokio::test]
-    async fn concurrent_register_serialises() {
+    async fn owner_storage_quota_refuses_growing_publish() {
         let (state, _dir) = test_state().await;
-        let owner_a = "did:example:owner-a".to_string();
-        let path = "race-path";
-        let log_a = build_test_did_log("scid-race", "control.test", path).await;
-        let log_b = log_a.clone();
-
-        let auth_a = owner_auth(&owner_a);
-        let auth_b = owner_auth(&owner_a); // same owner, different concurrent attempts
-
-        let state_a = state.clone();
-        let state_b = state.clone();
-        let path_a = path.to_string();
-        let path_b = path.to_string();
-
-        let task_a = tokio::spawn(async move {
-            register_did_atomic(&auth_a, &state_a, &path_a, &log_a, false).await
-        });
-        let task_b = tokio::spawn(async move {
-            register_did_atomic(&auth_b, &state_b, &path_b, &log_b, false).await
-        });
-
-        let r_a = task_a.await.unwrap();
-        let r_b = task_b.await.unwrap();
-        // Both calls succeed because they're from the same owner — the
-        // second is treated as an idempotent re-publish and bumps
-        // version_count.
-        assert!(
-            r_a.is_ok() && r_b.is_ok(),
-            "both same-owner registers should succeed; got a={r_a:?}, b={r_b:?}"
-        );
+        let owner = "did:example:small";
+        let did_log = build_test_did_log("s", "control.test", "alpha").await;
+        seed_owner_limits(&state, owner, None, Some(did_log.len() as u64)).await;
 
-        let record: DidRecord = state
-            .dids_ks
-            .get(did_key(path))
+        register_did_atomic(&owner_auth(owner), &state, "alpha", &did_log, None, false)
             .await
-            .unwrap()
-            .expect("record present after race");
-        assert_eq!(record.owner, owner_a);
-        // Two sequential commits via the lock => version_count == 2.
-        // Without the lock, the race could land on 1 (both observe
-        // None and both write version_count=1).
-        assert_eq!(
-            record.version_count, 2,
-            "both register calls must have committed in sequence; got version_count={}",
-            record.version_count
-        );
+            .expect("exactly at the limit");
+        let bigger = format!("{did_log}\n");
+        let err = publish_did(&owner_auth(owner), &state, "alpha", &bigger, None)
+            .await
+            .expect_err("growth past the limit is refused");
+        assert!(matches!(err, AppError::QuotaExceeded(_)), "got {err:?}");
     }
 
-    /// `create_did(..., Some("tenant.example"))` MUST persist the
-    /// caller-supplied domain on the DidRecord. This is the bug
-    /// b0e2fb11 fixed: previously `RequestUriRequest` had no
-    /// `domain` field, every record landed with `domain: ""`, and the
-    /// per-domain UI filters / dashboard stats hid the freshly-
-    /// created DID until the M-01 backfill sweep next ran. Pinning
-    /// the persisted value guards against the next regression in
-    /// `request_uri` / `dispatch_did_op` forgetting to thread the
-    /// resolver output through.
     #[tokio::test]
-    async fn create_did_persists_caller_supplied_domain() {
+    async fn domain_did_count_quota_applies_across_owners() {
+        use did_hosting_common::server::domain::{DomainQuota, update_domain};
         let (state, _dir) = test_state().await;
-        let owner = "did:example:owner";
-        let path = "alpha-domain";
+        seed_active_domain(&state, "control.test").await;
+        let mut entry =
+            did_hosting_common::server::domain::get_domain(&state.store, "control.test")
+                .await
+                .unwrap()
+                .unwrap();
+        entry.quota = Some(DomainQuota {
+            max_dids_in_domain: Some(1),
+            max_total_size_in_domain: None,
+        });
+        update_domain(&state.store, "control.test", &entry)
+            .await
+            .unwrap();
 
         create_did(
-            &owner_auth(owner),
+            &owner_auth("did:example:a"),
             &state,
-            Some(path),
+            Some("alpha"),
             false,
-            Some("tenant.example"),
+            Some("control.test"),
         )
         .await
-        .expect("create_did with domain should succeed");
+        .expect("first DID in domain");
+        let err = create_did(
+            &owner_auth("did:example:b"),
+            &state,
+            Some("beta"),
+            false,
+            Some("control.test"),
+        )
+        .await
+        .expect_err("domain is full");
+        assert!(matches!(err, AppError::QuotaExceeded(_)));
 
-        let record: DidRecord = state
-            .dids_ks
-            .get(did_key(path))
+        let report = quota::domain_report(&state, "control.test").await.unwrap();
+        assert_eq!(report.usage.did_count, 1);
+    }
+
+    #[tokio::test]
+    async fn domain_usage_counter_follows_writes() {
+        let (state, _dir) = test_state().await;
+        let owner = "did:example:owner";
+        let counter = async |state: &AppState| {
+            state
+                .dids_ks
+                .get::<quota::Usage>(did_ops::domain_usage_key("control.test"))
+                .await
+                .unwrap()
+        };
+        let did_log = build_test_did_log("s", "control.test", "alpha").await;
+        register_did_atomic(&owner_auth(owner), &state, "alpha", &did_log, None, false)
             .await
-            .unwrap()
-            .expect("record");
+            .unwrap();
         assert_eq!(
-            record.domain, "tenant.example",
-            "domain on the DidRecord MUST equal the caller-supplied value"
+            counter(&state).await,
+            Some(quota::Usage {
+                did_count: 1,
+                total_size: did_log.len() as u64,
+            })
+        );
+
+        let bigger = format!("{did_log}\n");
+        publish_did(&owner_auth(owner), &state, "alpha", &bigger, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            counter(&state).await.map(|u| u.total_size),
+            Some(bigger.len() as u64)
         );
+
+        delete_did(&owner_auth(owner), &state, "alpha", None)
+            .await
+            .unwrap();
+        assert_eq!(counter(&state).await, Some(quota::Usage::default()));
     }
 
     /// `create_did(..., None)` MUST leave `domain` empty — the field
@@ -3119,7 +5827,7 @@ mod tests_atomic {
     /// Register a fresh DID at `path` on host `control.test`, owned by `owner`.
     async fn register_owned(state: &AppState, owner: &str, path: &str) {
         let log = build_test_did_log("scid", "control.test", path).await;
-        register_did_atomic(&owner_auth(owner), state, path, &log, false)
+        register_did_atomic(&owner_auth(owner), state, path, &log, None, false)
             .await
             .expect("register");
     }
@@ -3346,6 +6054,8 @@ mod tests_atomic {
             domain: host.to_string(),
             services: None,
             agent_names: Vec::new(),
+            deactivated_at: None,
+            sync_head: None,
         };
         state
             .dids_ks
@@ -3784,7 +6494,7 @@ mod tests_atomic {
         let (state, _dir) = test_state().await;
         let admin = "did:example:admin";
         let log = build_test_did_log("scid", "control.test", ".well-known").await;
-        register_did_atomic(&admin_auth(admin), &state, ".well-known", &log, false)
+        register_did_atomic(&admin_auth(admin), &state, ".well-known", &log, None, false)
             .await
             .expect("admin registers the root DID");
 
@@ -3875,7 +6585,7 @@ mod tests_atomic {
         // provisioned by the operator, not by a tenant.
         let admin = "did:example:admin";
         let log = build_test_did_log("scid", "control.test", ".well-known").await;
-        register_did_atomic(&admin_auth(admin), &state, ".well-known", &log, false)
+        register_did_atomic(&admin_auth(admin), &state, ".well-known", &log, None, false)
             .await
             .expect("admin registers the root DID");
 
@@ -4002,9 +6712,16 @@ mod tests_atomic {
 
         // Register a brand-new slot whose first document claims A's name.
         let log_b = build_test_did_log_with_names("control.test", "slot-new", &["alice"]).await;
-        let err = register_did_atomic(&owner_auth(owner_b), &state, "slot-new", &log_b, false)
-            .await
-            .unwrap_err();
+        let err = register_did_atomic(
+            &owner_auth(owner_b),
+            &state,
+            "slot-new",
+            &log_b,
+            None,
+            false,
+        )
+        .await
+        .unwrap_err();
         assert!(
             matches!(err, AppError::AgentName(AgentNameError::Taken)),
             "expected Taken, got {err:?}"
@@ -4018,9 +6735,16 @@ mod tests_atomic {
 
         // …and the same for a reserved name on a fresh slot.
         let log_r = build_test_did_log_with_names("control.test", "slot-res", &["support"]).await;
-        let err = register_did_atomic(&owner_auth(owner_b), &state, "slot-res", &log_r, false)
-            .await
-            .unwrap_err();
+        let err = register_did_atomic(
+            &owner_auth(owner_b),
+            &state,
+            "slot-res",
+            &log_r,
+            None,
+            false,
+        )
+        .await
+        .unwrap_err();
         assert!(
             matches!(err, AppError::AgentName(AgentNameError::Reserved)),
             "expected Reserved, got {err:?}"
@@ -24,9 +24,10 @@ use affinidi_messaging_didcomm_service::{
 use did_hosting_common::server::didcomm_profile::build_tdk_profile_for_identity;
 use did_hosting_common::server::identity::{
     self, DEFAULT_RELOAD_INTERVAL, DEFAULT_SWEEP_INTERVAL, IdentityGeneration, ReloadOutcome,
-    mnemonic_from_did,
+    StageRetire, mnemonic_from_did,
 };
 use did_hosting_common::server::identity_drain;
+use did_hosting_common::server::store::WriteBatch;
 use tokio::sync::watch;
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
@@ -79,6 +80,23 @@ pub async fn reload_now(state: &AppState) -> Result<(), AppError> {
     // one here is free next to the DID resolution it is about to do.
     let secret_store = create_secret_store(&state.config)?;
 
+    // The rotation's audit record commits with it. No actor: the service
+    // rotates itself, in response to its own DID being published (that
+    // publish is recorded with its caller).
+    let stage_audit =
+        |batch: &mut WriteBatch, new: &IdentityGeneration, retired: Option<&IdentityGeneration>| {
+            crate::audit::stage(
+                &state.store,
+                batch,
+                None,
+                crate::audit::Entry {
+                    action: "identity.rotate",
+                    subject: &new.id.to_string(),
+                    before: retired.and_then(crate::audit::digest),
+                    after: crate::audit::digest(new),
+                },
+            )
+        };
     let outcome = identity::reload_service_identity(
         identity,
         &state.store,
@@ -89,6 +107,7 @@ pub async fn reload_now(state: &AppState) -> Result<(), AppError> {
             tsp: state.config.features.tsp,
         },
         state.config.identity.rotation_grace_secs(),
+        Some(&stage_audit),
     )
     .await?;
 
@@ -126,6 +145,7 @@ pub async fn reload_now(state: &AppState) -> Result<(), AppError> {
                  DID document cannot reach this service until their cache expires. Rotate onto a \
                  NEW fragment for a seamless cutover."
             );
+            crate::audit::flush(state).await;
             rebuild_listener(state).await?;
         }
         ReloadOutcome::Unresolvable => {
@@ -150,6 +170,7 @@ pub async fn reload_now(state: &AppState) -> Result<(), AppError> {
                 new_generation,
                 retired_generation, expires_at, "service identity rotated — rebuilding listener"
             );
+            crate::audit::flush(state).await;
             // Order matters: rebuild first, drain second.
             //
             // `rebuild_listener` re-points the main listener at the *new*
@@ -396,15 +417,27 @@ pub async fn expire_due(state: &AppState) {
 ///
 /// The compromise response. Messages still addressed to the old key stop
 /// decrypting at once — that is the point, and the caller is expected to have
-/// meant it.
-pub async fn retire_generation_now(state: &AppState, generation_id: u64) -> Result<(), AppError> {
+/// meant it. `stage` adds the caller's writes (its audit record) to the
+/// batch that removes the generation.
+pub async fn retire_generation_now(
+    state: &AppState,
+    generation_id: u64,
+    stage: StageRetire<'_>,
+) -> Result<(), AppError> {
     let Some(identity) = state.identity.as_ref() else {
         return Err(AppError::Config("no service identity loaded".into()));
     };
     let secret_store = create_secret_store(&state.config)?;
 
-    identity::retire_generation_now(identity, &state.store, secret_store.as_ref(), generation_id)
-        .await?;
+    identity::retire_generation_now(
+        identity,
+        &state.store,
+        secret_store.as_ref(),
+        generation_id,
+        Some(stage),
+    )
+    .await?;
+    crate::audit::flush(state).await;
 
     rebuild_listener(state).await?;
 
@@ -10,19 +10,28 @@
 //! auth primitives) live in `did-hosting-common`.
 
 pub mod acl;
+pub mod approvals;
+pub mod audit;
 pub mod auth;
+pub mod backup;
 pub mod config;
+pub mod consent;
+pub mod did_import;
+pub mod did_move;
 pub mod did_ops;
 pub mod error;
 #[cfg(feature = "ui")]
 pub mod frontend;
 pub mod health;
 pub mod identity_rotation;
+pub mod log_archive;
 pub mod messaging;
+pub mod outbound;
 pub mod outbox;
 pub mod path_locks;
 pub mod pending_challenges;
 pub mod purge_sweep;
+pub mod quota;
 pub mod rate_limit;
 pub mod registry;
 pub mod replay;
@@ -39,3 +48,4 @@ pub mod test_support;
 pub mod trust_tasks_did;
 pub mod trust_tasks_infra;
 pub mod tsp;
+pub mod webhooks;
@@ -0,0 +1,239 @@
+//! Log entries set aside by a rollback, recoverable with `undo-rollback`.
+//!
+//! [`crate::did_ops::rollback_did`] drops the last line of a DID's log and
+//! the witness file that covered it. Both are kept here, under the DID in
+//! `KS_DIDS` ([`archived_entry_key`]), until `hosting.rollback_archive_retention`
+//! lapses. [`crate::did_ops::undo_rollback_did`] re-applies an entry if the
+//! chain still verifies with it on the end; the control purge sweep drops
+//! entries past their `expires_at`.
+//!
+//! Rows are control-local. Hosting servers see the current log, pushed by
+//! the usual `notify_servers_did` after a rollback or undo, plus the
+//! entries rolled back off its end ([`rolled_back`]) and a
+//! [`rollback_proof`] signed by the control DID, so their watchers can tell
+//! a rollback from a rewritten history.
+
+use did_hosting_common::RollbackProof;
+use did_hosting_common::did_ops::{
+    LogEntryInfo, archived_entry_key, archived_entry_prefix, parse_log_entries,
+};
+use did_hosting_common::server::pending_purge::parse_grace_string;
+use did_hosting_common::server::store::{KS_DIDS, KeyspaceHandle, Store};
+use ed25519_dalek::SigningKey;
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::auth::session::now_epoch;
+use crate::config::AppConfig;
+use crate::error::AppError;
+
+/// One rolled-back log entry, as stored.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ArchivedEntry {
+    /// The dropped JSONL line, verbatim.
+    pub entry: String,
+    /// The witness file as it was at rollback time, if there was one.
+    #[serde(default)]
+    pub witness: Option<String>,
+    /// [`log_hash`] of the log the entry was dropped from, minus the
+    /// entry. The witness is only restored onto that exact log.
+    pub base_log_hash: String,
+    pub archived_at: u64,
+    pub expires_at: u64,
+    /// DID of the caller that rolled back.
+    pub archived_by: String,
+}
+
+/// Wire shape for `GET /api/log/{mnemonic}?archived=true`: the parsed
+/// entry plus its archive metadata. `id` is what `undo-rollback` takes.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivedEntryInfo {
+    pub id: String,
+    pub archived_at: u64,
+    pub expires_at: u64,
+    pub archived_by: String,
+    pub has_witness: bool,
+    #[serde(flatten)]
+    pub entry: Option<LogEntryInfo>,
+}
+
+impl ArchivedEntryInfo {
+    pub fn new(id: String, archived: &ArchivedEntry) -> Self {
+        Self {
+            id,
+            archived_at: archived.archived_at,
+            expires_at: archived.expires_at,
+            archived_by: archived.archived_by.clone(),
+            has_witness: archived.witness.is_some(),
+            entry: parse_log_entries(&archived.entry).into_iter().next(),
+        }
+    }
+}
+
+/// Retention window from `hosting.rollback_archive_retention`, in seconds.
+pub fn retention_secs(config: &AppConfig) -> Result<u64, AppError> {
+    parse_grace_string(&config.hosting.rollback_archive_retention).map_err(|e| {
+        AppError::Internal(format!(
+            "config [hosting] rollback_archive_retention='{}' is invalid: {e}",
+            config.hosting.rollback_archive_retention
+        ))
+    })
+}
+
+/// Hex SHA-256 of a log's content, ignoring trailing whitespace so a
+/// log that was republished with a final newline still matches.
+pub fn log_hash(content: &str) -> String {
+    Sha256::digest(content.trim_end().as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Storage key for an archive `id` (the zero-padded microsecond stamp).
+pub fn key_for(mnemonic: &str, id: &str) -> Result<String, AppError> {
+    let micros: u128 = id
+        .parse()
+        .map_err(|_| AppError::Validation(format!("invalid archived entry id '{id}'")))?;
+    Ok(archived_entry_key(mnemonic, micros))
+}
+
+/// Unexpired archived entries for one DID, oldest first, as `(id, entry)`.
+pub async fn list(
+    dids_ks: &KeyspaceHandle,
+    mnemonic: &str,
+    now: u64,
+) -> Result<Vec<(String, ArchivedEntry)>, AppError> {
+    let prefix = archived_entry_prefix(mnemonic);
+    let raw = dids_ks.prefix_iter_raw(prefix.clone()).await?;
+    let mut out = Vec::with_capacity(raw.len());
+    for (k, v) in raw {
+        let key = String::from_utf8_lossy(&k);
+        let Some(id) = key.strip_prefix(&prefix) else {
+            continue;
+        };
+        match serde_json::from_slice::<ArchivedEntry>(&v) {
+            Ok(entry) if entry.expires_at > now => out.push((id.to_string(), entry)),
+            Ok(_) => {}
+            Err(e) => {
+                warn!(mnemonic, key = %key, error = %e, "skipping malformed archived log entry")
+            }
+        }
+    }
+    out.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(out)
+}
+
+/// The archived entries that rolled back off the end of `current`, in log
+/// order: the entry archived against `current` itself, then the one
+/// archived against `current` plus that entry, and so on. Empty when the
+/// current log is not what a rollback left.
+pub fn rolled_back_chain(current: &str, archived: &[ArchivedEntry]) -> Vec<String> {
+    let mut by_base: HashMap<&str, &ArchivedEntry> = archived
+        .iter()
+        .map(|e| (e.base_log_hash.as_str(), e))
+        .collect();
+    let mut log = current.trim_end().to_string();
+    let mut chain = Vec::new();
+    while let Some(entry) = by_base.remove(log_hash(&log).as_str()) {
+        chain.push(entry.entry.clone());
+        log = format!("{log}\n{}", entry.entry);
+    }
+    chain
+}
+
+/// [`rolled_back_chain`] over this DID's unexpired archive, for the sync
+/// pushes that tell hosting servers (and through them, watchers) which
+/// entries a rollback removed.
+pub async fn rolled_back(
+    dids_ks: &KeyspaceHandle,
+    mnemonic: &str,
+    current: &str,
+) -> Result<Vec<String>, AppError> {
+    let archived: Vec<ArchivedEntry> = list(dids_ks, mnemonic, now_epoch())
+        .await?
+        .into_iter()
+        .map(|(_, entry)| entry)
+        .collect();
+    Ok(rolled_back_chain(current, &archived))
+}
+
+/// A [`RollbackProof`] for pushing `log` with `rolled_back` taken off its
+/// end, signed with the control DID's assertion key. `None` (logged) with
+/// no key: watchers then refuse the shorter log as a fork.
+pub fn rollback_proof(
+    signer: Option<&SigningKey>,
+    did: &str,
+    log: &str,
+    rolled_back: &[String],
+) -> Option<RollbackProof> {
+    let Some(key) = signer else {
+        warn!(
+            did,
+            "no control signing key; rollback pushed without a proof"
+        );
+        return None;
+    };
+    did_hosting_common::server::rollback_proof::sign(did, log, rolled_back, key)
+}
+
+/// Every archive key for one DID, expired or not — for removal alongside
+/// the DID itself.
+pub async fn all_keys(dids_ks: &KeyspaceHandle, mnemonic: &str) -> Result<Vec<Vec<u8>>, AppError> {
+    Ok(dids_ks
+        .prefix_iter_raw(archived_entry_prefix(mnemonic))
+        .await?
+        .into_iter()
+        .map(|(k, _)| k)
+        .collect())
+}
+
+/// Drop every archived entry past its `expires_at`. Returns how many went.
+pub async fn prune_expired(store: &Store, now: u64) -> Result<u64, AppError> {
+    let ks = store.keyspace(KS_DIDS)?;
+    let mut pruned = 0;
+    for (k, v) in ks.prefix_iter_raw("archive:").await? {
+        let expired = serde_json::from_slice::<ArchivedEntry>(&v)
+            .map(|e| e.expires_at <= now)
+            // Unreadable rows can never be restored; don't keep them.
+            .unwrap_or(true);
+        if expired {
+            ks.remove(k).await?;
+            pruned += 1;
+        }
+    }
+    Ok(pruned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn archived(base: &str, entry: &str) -> ArchivedEntry {
+        ArchivedEntry {
+            entry: entry.into(),
+            witness: None,
+            base_log_hash: log_hash(base),
+            archived_at: 0,
+            expires_at: u64::MAX,
+            archived_by: "did:example:owner".into(),
+        }
+    }
+
+    #[test]
+    fn rolled_back_chain_follows_successive_rollbacks() {
+        // Two rollbacks took `c` then `b` off `a\nb\nc`; an unrelated
+        // archive (a different base) is not part of the chain.
+        let archive = [
+            archived("a\nb", "c"),
+            archived("a", "b"),
+            archived("x", "y"),
+        ];
+        assert_eq!(rolled_back_chain("a\n", &archive), vec!["b", "c"]);
+        assert_eq!(rolled_back_chain("a\nb", &archive), vec!["c"]);
+        assert!(rolled_back_chain("a\nb\nc", &archive).is_empty());
+    }
+}
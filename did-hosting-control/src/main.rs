@@ -1,7 +1,11 @@
 use clap::{Parser, Subcommand};
+use did_hosting_common::server::backup::{BackupRequest, KeyspaceSelection, RestoreRequest};
 use did_hosting_common::server::store::KS_SESSIONS;
+use did_hosting_common::server::store_migrate::{self, MigrateRequest};
 use did_hosting_control::config::AppConfig;
-use did_hosting_control::{health, secret_store, server, setup, setup_recipe, store};
+use did_hosting_control::{
+    audit, backup, health, secret_store, server, setup, setup_recipe, store,
+};
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -124,12 +128,113 @@ enum Command {
         #[arg(long)]
         generation: u64,
     },
+    /// Verify the tamper-evident audit log offline.
+    ///
+    /// Recomputes every record hash, checks the chain links and the head
+    /// pointer, and verifies signatures. Reads the store directly (the
+    /// service must be STOPPED) unless `--file` names an export — either a
+    /// `GET /api/control/audit` response or JSON Lines of records.
+    ///
+    /// Exits non-zero if anything fails to verify.
+    VerifyAudit {
+        /// Verify this export instead of the store.
+        #[arg(long)]
+        file: Option<PathBuf>,
+        /// Ed25519 public key (multibase) trusted to have signed records.
+        /// Repeat for keys from earlier identity generations. Without any,
+        /// signatures are checked against the key each record carries —
+        /// integrity only, not provenance.
+        #[arg(long = "public-key")]
+        public_keys: Vec<String>,
+        /// Treat unsigned records as failures.
+        #[arg(long)]
+        require_signatures: bool,
+    },
     /// Remove an ACL entry
     RemoveAcl {
         /// DID to remove from the ACL
         #[arg(long)]
         did: String,
     },
+    /// Export control plane data to a backup file.
+    ///
+    /// The service must be stopped (the store is exclusively locked).
+    Backup {
+        /// Output file path (use "-" for stdout)
+        #[arg(short, long, default_value = "control-backup.jsonl")]
+        output: String,
+        /// Back up only these keyspaces (repeatable or comma-separated).
+        #[arg(long = "keyspace", value_delimiter = ',')]
+        keyspaces: Vec<String>,
+        /// Leave these keyspaces out.
+        #[arg(long = "exclude-keyspace", value_delimiter = ',')]
+        exclude_keyspaces: Vec<String>,
+        /// Encrypt to this age X25519 recipient (`age1…`; repeatable). The
+        /// backup can then only be read with the matching identity file.
+        #[arg(long = "recipient")]
+        recipients: Vec<String>,
+        /// Incremental: write only what changed or was deleted since this
+        /// earlier backup (full or incremental).
+        #[arg(long)]
+        incremental_from: Option<PathBuf>,
+        /// age identity file, to read an encrypted `--incremental-from`.
+        #[arg(long = "identity")]
+        identities: Vec<PathBuf>,
+    },
+    /// Restore control plane data and config.toml from a backup file.
+    ///
+    /// The service must be stopped (the store is exclusively locked).
+    Restore {
+        /// Input backup file path
+        #[arg(short, long)]
+        input: String,
+        /// Restore only these keyspaces (repeatable or comma-separated).
+        #[arg(long = "keyspace", value_delimiter = ',')]
+        keyspaces: Vec<String>,
+        /// Leave these keyspaces out.
+        #[arg(long = "exclude-keyspace", value_delimiter = ',')]
+        exclude_keyspaces: Vec<String>,
+        /// age identity file, for an encrypted backup (repeatable).
+        #[arg(long = "identity")]
+        identities: Vec<PathBuf>,
+        /// Report what would be added, overwritten and kept, per keyspace,
+        /// without writing the config or the store.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Check a backup file's digest and entries without restoring it
+    VerifyBackup {
+        /// Input backup file path
+        #[arg(short, long)]
+        input: String,
+        /// age identity file, for an encrypted backup (repeatable).
+        #[arg(long = "identity")]
+        identities: Vec<PathBuf>,
+    },
+    /// Copy every keyspace into another storage backend.
+    ///
+    /// Stop the service first. Both backends' `store-*` features must be
+    /// compiled into this binary.
+    StoreMigrate {
+        /// Config file whose [store] is the source
+        #[arg(long)]
+        from: PathBuf,
+        /// Config file whose [store] is the target
+        #[arg(long)]
+        to: PathBuf,
+        /// Progress file; an interrupted run resumes from it
+        #[arg(long, default_value = "store-migrate.checkpoint")]
+        checkpoint: PathBuf,
+        /// Migrate only these keyspaces (repeatable or comma-separated).
+        #[arg(long = "keyspace", value_delimiter = ',')]
+        keyspaces: Vec<String>,
+        /// Leave these keyspaces out.
+        #[arg(long = "exclude-keyspace", value_delimiter = ',')]
+        exclude_keyspaces: Vec<String>,
+        /// Compare the two stores without copying anything
+        #[arg(long)]
+        verify_only: bool,
+    },
     /// Create a passkey enrollment invite
     Invite {
         /// DID to invite
@@ -373,12 +478,100 @@ async fn main() {
                 std::process::exit(1);
             }
         }
+        Some(Command::VerifyAudit {
+            file,
+            public_keys,
+            require_signatures,
+        }) => {
+            if let Err(e) =
+                run_verify_audit(cli.config, file, public_keys, require_signatures).await
+            {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+        }
         Some(Command::RemoveAcl { did }) => {
             if let Err(e) = run_remove_acl(cli.config, did).await {
                 eprintln!("Error: {e}");
                 std::process::exit(1);
             }
         }
+        Some(Command::Backup {
+            output,
+            keyspaces,
+            exclude_keyspaces,
+            recipients,
+            incremental_from,
+            identities,
+        }) => {
+            let req = BackupRequest {
+                output,
+                selection: KeyspaceSelection {
+                    only: keyspaces,
+                    exclude: exclude_keyspaces,
+                },
+                recipients,
+                incremental_from,
+                identities,
+            };
+            if let Err(e) = backup::run_backup(cli.config, req).await {
+                eprintln!("Backup error: {e}");
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Restore {
+            input,
+            keyspaces,
+            exclude_keyspaces,
+            identities,
+            dry_run,
+        }) => {
+            let req = RestoreRequest {
+                input,
+                selection: KeyspaceSelection {
+                    only: keyspaces,
+                    exclude: exclude_keyspaces,
+                },
+                identities,
+                dry_run,
+            };
+            if let Err(e) = backup::run_restore(cli.config, req).await {
+                eprintln!("Restore error: {e}");
+                std::process::exit(1);
+            }
+        }
+        Some(Command::VerifyBackup { input, identities }) => {
+            if let Err(e) = backup::run_verify_backup(input, identities) {
+                eprintln!("Backup verification failed: {e}");
+                std::process::exit(1);
+            }
+        }
+        Some(Command::StoreMigrate {
+            from,
+            to,
+            checkpoint,
+            keyspaces,
+            exclude_keyspaces,
+            verify_only,
+        }) => {
+            let req = MigrateRequest {
+                from,
+                to,
+                checkpoint,
+                selection: KeyspaceSelection {
+                    only: keyspaces,
+                    exclude: exclude_keyspaces,
+                },
+                verify_only,
+            };
+            if let Err(e) =
+                store_migrate::run_store_migrate(&req, "store", backup::KEYSPACES, Some("CONTROL"))
+                    .await
+            {
+                eprintln!("Store migration error: {e}");
+                std::process::exit(1);
+            }
+        }
         Some(Command::Invite {
             did,
             role,
@@ -852,6 +1045,68 @@ async fn run_identity_retire_now(
     .await
 }
 
+/// `verify-audit` — check the audit chain from the store or an export.
+async fn run_verify_audit(
+    config_path: Option<PathBuf>,
+    file: Option<PathBuf>,
+    public_keys: Vec<String>,
+    require_signatures: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let pinned = public_keys
+        .iter()
+        .map(|k| {
+            audit::decode_public_key(k).ok_or_else(|| format!("invalid Ed25519 public key: {k}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (records, head) = match file {
+        Some(path) => {
+            let text = std::fs::read_to_string(&path)
+                .map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+            audit::parse_export(&text)?
+        }
+        None => {
+            let config = AppConfig::load(config_path)?;
+            let store = store::Store::open(&config.store).await?;
+            (
+                audit::all_records(&store).await?,
+                audit::head(&store).await?,
+            )
+        }
+    };
+
+    let report = audit::verify_chain(&records, &pinned, require_signatures, head.as_ref());
+
+    eprintln!();
+    eprintln!("  Records:  {}", report.records);
+    if let (Some(first), Some(last)) = (report.first_seq, report.last_seq) {
+        eprintln!("  Range:    {first}..={last}");
+    }
+    eprintln!("  Signed:   {}", report.signed);
+    if let Some(hash) = &report.last_hash {
+        eprintln!("  Head:     {hash}");
+    }
+    if head.is_none() {
+        eprintln!("  (no head pointer — a truncated tail cannot be detected)");
+    }
+    eprintln!();
+
+    if report.is_ok() {
+        eprintln!("  Audit chain verified.");
+        eprintln!();
+        return Ok(());
+    }
+    for problem in &report.problems {
+        eprintln!("  FAIL: {problem}");
+    }
+    eprintln!();
+    Err(format!(
+        "audit chain failed verification ({} problems)",
+        report.problems.len()
+    )
+    .into())
+}
+
 /// `identity-rotate-keys` — rotate the service's own keys, offline.
 ///
 /// Writes the new DID log entry, the new key material (carrying the outgoing key
@@ -22,7 +22,9 @@ use tracing::{debug, info, warn};
 
 use crate::acl::check_acl;
 use crate::auth::AuthClaims;
+use crate::auth::api_key::{self, ApiKeyScope};
 use crate::auth::session::create_authenticated_session;
+use crate::consent::ConsentOutcome;
 use crate::did_ops;
 use crate::error::AppError;
 use crate::server::AppState;
@@ -223,7 +225,7 @@ async fn run_authenticate(
 /// anything that is not literally `approve` is *not* an approval, and
 /// anything that is not literally `deny` is not a signed refusal either
 /// — an unknown third value is a decision this executor cannot read, so
-/// it resolves nothing and the parked request times out.
+/// it resolves nothing and the request lapses.
 fn parse_decision_payload(payload: &Value) -> Option<(&str, &str, bool)> {
     let challenge = payload.get("challenge").and_then(Value::as_str)?;
     let digest = payload.get("payloadDigest").and_then(Value::as_str)?;
@@ -241,14 +243,15 @@ fn parse_decision_payload(payload: &Value) -> Option<(&str, &str, bool)> {
 /// the authorization (per the task-consent spec), so it is **required**
 /// and verified before anything else. The authcrypt envelope binds the
 /// sender on top of that: the DIDComm service layer has already
-/// authenticated `ctx.sender_did` as the holder DID, and the decision is
-/// only honoured when the proof's `verificationMethod` DID, the in-band
-/// `issuer` (when present) and the authcrypt sender all name the holder
-/// the request was addressed to. We correlate by `challenge`, require
-/// the echoed `payloadDigest` to match the one we sent (the binding
-/// between what the human approved and what the parked admin call
-/// proceeds with), and resolve the parked REST request with the user's
-/// decision. A `#response` acknowledgement is returned per the spec.
+/// authenticated `ctx.sender_did`, and the decision is only honoured
+/// when the proof's `verificationMethod` DID, the in-band `issuer` (when
+/// present) and the authcrypt sender all name one of the approvers the
+/// request was addressed to. We correlate by `challenge`, require the
+/// echoed `payloadDigest` to match the one we sent (the binding between
+/// what the human approved and what the gated admin action proceeds
+/// with), and record the decision against the stored approval
+/// ([`crate::approvals`]), which any replica then reads. A `#response`
+/// acknowledgement is returned per the spec.
 async fn handle_consent_decision(
     ctx: HandlerContext,
     message: Message,
@@ -310,8 +313,8 @@ async fn run_consent_decision(
         );
         return Ok(None);
     };
-    // The proven signer must be the authcrypt sender (and so, below, the
-    // addressed holder). `TransportBoundVerifier` additionally enforces
+    // The proven signer must be the authcrypt sender (and so, below, an
+    // addressed approver). `TransportBoundVerifier` additionally enforces
     // `verificationMethod` DID == `issuer` whenever `issuer` is present.
     let proof_did = proof
         .verification_method
@@ -373,34 +376,38 @@ async fn run_consent_decision(
         return Ok(None);
     };
 
-    // ── 4. Match the pending entry and remove it under ONE lock, so two
-    //       decisions for the same challenge can't both fire the sender —
-    //       and so a decision that fails the holder/digest checks leaves
-    //       the legitimate pending entry in place rather than consuming
-    //       it. The proven sender must equal the holder DID the request
+    // ── 4. Match the stored approval and record the decision against it.
+    //       The proven sender must be one of the approvers the request
     //       was addressed to, and the echoed digest must be the one we
     //       sent (the binding between what the human approved and what
-    //       the parked admin call proceeds with).
-    let pending = {
-        let mut map = state.pending_confirms.lock().await;
-        match map.get(challenge) {
+    //       the gated action proceeds with). A decision that fails either
+    //       check, or arrives once the approval is resolved or lapsed,
+    //       is ignored and leaves the approval as it was. Each approver's
+    //       decision is its own row, so decisions recorded concurrently —
+    //       here or on another replica — never overwrite each other.
+    let (status, approvals) = {
+        let store_err = |e: AppError| DIDCommServiceError::Internal(e.to_string());
+        let approval = match crate::approvals::get(&state.store, challenge)
+            .await
+            .map_err(store_err)?
+        {
             None => {
-                // Stale, duplicate, lapsed, or already-resolved challenge.
+                // Stale, lapsed-and-swept, or never-issued challenge.
                 warn!(
                     sender = sender,
                     "task-consent decision for unknown challenge — ignoring"
                 );
                 return Ok(None);
             }
-            Some(p) if p.holder_did != sender => {
+            Some(a) if !a.approvers.iter().any(|d| d == sender) => {
                 warn!(
                     sender = sender,
-                    expected = %p.holder_did,
-                    "task-consent decision sender does not match addressed holder DID — rejecting"
+                    expected = ?a.approvers,
+                    "task-consent decision sender is not an addressed approver — rejecting"
                 );
                 return Ok(None);
             }
-            Some(p) if p.expected_digest != digest => {
+            Some(a) if a.expected_digest != digest => {
                 warn!(
                     sender = sender,
                     "task-consent decision payloadDigest does not match the pending request \
@@ -408,22 +415,53 @@ async fn run_consent_decision(
                 );
                 return Ok(None);
             }
-            Some(_) => map
-                .remove(challenge)
-                .expect("checked present under the same lock"),
+            Some(a) => a,
+        };
+        let now = crate::auth::session::now_epoch();
+        if !matches!(
+            crate::approvals::status(&state.store, &approval, now)
+                .await
+                .map_err(store_err)?,
+            ConsentOutcome::Pending { .. }
+        ) {
+            warn!(
+                sender = sender,
+                "task-consent decision for a resolved or lapsed request — ignoring"
+            );
+            return Ok(None);
+        }
+        crate::approvals::record_decision(
+            &state.store,
+            challenge,
+            sender,
+            approved,
+            message.body.clone(),
+        )
+        .await
+        .map_err(store_err)?;
+        match crate::approvals::status(&state.store, &approval, now)
+            .await
+            .map_err(store_err)?
+        {
+            ConsentOutcome::Granted(decisions) => ("granted", decisions.len()),
+            ConsentOutcome::Denied { .. } => ("denied", 0),
+            ConsentOutcome::Pending { approvals } | ConsentOutcome::Expired { approvals } => {
+                ("pending", approvals)
+            }
         }
     };
 
-    info!(sender = sender, approved, "task-consent decision received");
-    // Receiver may have already timed out and dropped — ignore the error.
-    let _ = pending.tx.send(approved);
+    info!(
+        sender = sender,
+        approved, status, approvals, "task-consent decision received"
+    );
 
-    // Spec `#response` acknowledgement: minApprovals is 1, so an approve
-    // is immediately `granted` and a deny is `denied`.
+    // Spec `#response` acknowledgement: `granted` once the threshold is
+    // met, `denied` on a deny, `pending` while more approvals are needed.
     let ack = json!({
-        "status": if approved { "granted" } else { "denied" },
+        "status": status,
         "payloadDigest": digest,
-        "approvals": if approved { 1 } else { 0 },
+        "approvals": approvals,
     });
     Ok(Some((
         TASK_CONSENT_DECISION_RESPONSE_0_1.as_str().to_string(),
@@ -472,6 +510,7 @@ async fn run_webvh_dispatch(state: &AppState, sender: &str, message: &Message) -
                 // DIDComm authcrypt-sender auth is a base (did) factor.
                 amr: vec!["did".to_string()],
                 acr: "aal1".to_string(),
+                api_key: None,
             };
             match dispatch_did_op(&auth, state, message).await {
                 Ok(result) => result,
@@ -673,18 +712,17 @@ pub async fn dispatch_did_op(
             if req.path.is_empty() {
                 return Err(AppError::Validation("missing 'path' in body".into()));
             }
-            let (method, payload) = req.resolve().map_err(AppError::Validation)?;
-            if method != "webvh" {
-                return Err(AppError::Validation(format!(
-                    "DIDComm register is currently webvh-only; received method = '{method}'.",
-                )));
-            }
-            let did_log = std::str::from_utf8(&payload).map_err(|e| {
-                AppError::Validation(format!("webvh did_data is not valid UTF-8: {e}"))
-            })?;
+            let (did_log, did_doc) = did_ops::register_payload(&req)?;
 
-            let result =
-                did_ops::register_did_atomic(auth, state, &req.path, did_log, req.force).await?;
+            let result = did_ops::register_did_atomic(
+                auth,
+                state,
+                &req.path,
+                &did_log,
+                did_doc.as_deref(),
+                req.force,
+            )
+            .await?;
             server_push::notify_servers_did(state, result.mnemonic.clone());
 
             let server_did = state.config.server_did.as_deref().unwrap_or_default();
@@ -1895,6 +1933,26 @@ pub(crate) async fn dispatch_trust_task_doc(
     sender: &str,
     transport: &(impl trust_tasks_rs::TransportHandler + Sync),
     doc: trust_tasks_rs::TrustTask<Value>,
+) -> Result<Option<Value>, DIDCommServiceError> {
+    // Every mutation the document causes is audited under its task.
+    let task = crate::audit::TrustTaskRef::from(&doc);
+    // Boxed: the scoped dispatch nests every DID operation's future, and
+    // inlined into each transport's it overflows the compiler's layout
+    // depth limit.
+    crate::audit::with_trust_task(
+        Some(task),
+        Box::pin(dispatch_trust_task_doc_scoped(
+            state, sender, transport, doc,
+        )),
+    )
+    .await
+}
+
+async fn dispatch_trust_task_doc_scoped(
+    state: &AppState,
+    sender: &str,
+    transport: &(impl trust_tasks_rs::TransportHandler + Sync),
+    doc: trust_tasks_rs::TrustTask<Value>,
 ) -> Result<Option<Value>, DIDCommServiceError> {
     use did_hosting_common::server::trust_tasks::{
         DispatchOutcome, TransportBoundVerifier, TrustTaskContext, build_dispatcher,
@@ -1955,7 +2013,7 @@ pub(crate) async fn dispatch_trust_task_doc(
         .contains(&type_uri.as_str());
 
     if !framework_owns {
-        return bridge_did_management(state, sender, my_vid, &doc)
+        return bridge_did_management(state, sender, my_vid, &doc, None)
             .await
             .map(Some);
     }
@@ -1973,7 +2031,26 @@ pub(crate) async fn dispatch_trust_task_doc(
         _ => trust_tasks_rs::ProofPolicy::RejectIfPresent,
     };
 
+    // A role grant under an `acl/grant` consent policy waits for the
+    // approvers; their decisions go on the audit record.
+    let approvals = match crate::consent::require_for_acl_task(state, sender, &doc).await {
+        Ok(approvals) => approvals,
+        Err(e) => {
+            let err = crate::consent::reject(&doc, &e);
+            return Ok(Some(
+                serde_json::to_value(&err).expect("error document serialises"),
+            ));
+        }
+    };
+    let watch = crate::audit::AclTaskWatch::begin(state, &doc).await;
     let outcome = dispatch_inbound::<TransportBoundVerifier>(&ctx, transport, policy, doc).await;
+    if let Some(watch) = watch {
+        crate::audit::with_approvals(
+            approvals,
+            watch.finish(state, crate::audit::Actor::peer(sender), &outcome),
+        )
+        .await;
+    }
     let value = match outcome {
         DispatchOutcome::Handled(resp) => {
             serde_json::to_value(&resp).expect("response document serialises")
@@ -1986,6 +2063,22 @@ pub(crate) async fn dispatch_trust_task_doc(
     Ok(Some(value))
 }
 
+/// DID-management ops whose `did_ops` handlers check each record's domain
+/// against the caller's API-key scope. A domain-restricted key is refused
+/// every other op: their answers are not per-domain.
+const DOMAIN_AWARE_OPS: &[&str] = &[
+    MSG_DID_REQUEST,
+    MSG_DID_REGISTER,
+    MSG_WITNESS_PUBLISH,
+    MSG_INFO_REQUEST,
+    MSG_LIST_REQUEST,
+    MSG_DELETE,
+    MSG_DID_CHANGE_OWNER,
+    MSG_AGENT_NAME_UPDATE,
+    MSG_AGENT_NAME_REMOVE,
+    MSG_AGENT_NAME_LIST,
+];
+
 /// Bridge a legacy DID-management Trust Task document to the shared
 /// [`dispatch_did_op`] table.
 ///
@@ -2002,6 +2095,7 @@ pub(crate) async fn bridge_did_management(
     sender: &str,
     my_vid: &str,
     doc: &trust_tasks_rs::TrustTask<Value>,
+    api_key: Option<ApiKeyScope>,
 ) -> Result<Value, DIDCommServiceError> {
     let role = match check_acl(&state.acl_ks, sender).await {
         Ok(r) => r,
@@ -2019,9 +2113,25 @@ pub(crate) async fn bridge_did_management(
         role,
         session_id: String::new(),
         session_pubkey_b58btc: None,
-        amr: vec!["did".to_string()],
+        amr: vec![
+            if api_key.is_some() {
+                api_key::AMR
+            } else {
+                "did"
+            }
+            .to_string(),
+        ],
         acr: "aal1".to_string(),
+        api_key,
     };
+    if auth.api_key.as_ref().is_some_and(|k| !k.domains.is_empty())
+        && !DOMAIN_AWARE_OPS.contains(&doc.type_uri.to_string().as_str())
+    {
+        let e =
+            AppError::Forbidden("a domain-restricted API key cannot be used for this task".into());
+        warn!(sender, type_uri = %doc.type_uri, "trust-task DID-management: API key domain scope denied");
+        return tt_reply(doc, my_vid, sender, MSG_PROBLEM_REPORT, problem_body(&e));
+    }
 
     // `dispatch_did_op` reads only `typ` and `body`; `id`/`from` are set for
     // completeness / logging.
@@ -2191,6 +2301,7 @@ mod tests {
             trust_tasks: Default::default(),
             hosting: Default::default(),
             identity: Default::default(),
+            consent: Default::default(),
             config_path: PathBuf::new(),
         };
 
@@ -2218,7 +2329,6 @@ mod tests {
             acl_locks: did_hosting_common::server::path_locks::PathLocks::new(),
             pending_challenges: Arc::new(crate::pending_challenges::PendingChallengeTracker::new()),
             ip_rate_limiter: Arc::new(crate::rate_limit::IpRateLimiter::new()),
-            pending_confirms: Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
             outbox_notify: Arc::new(tokio::sync::Notify::new()),
         };
 
@@ -2233,6 +2343,7 @@ mod tests {
             session_id: String::new(),
             amr: vec!["did".to_string()],
             acr: "aal1".to_string(),
+            api_key: None,
         }
     }
 
@@ -2244,6 +2355,7 @@ mod tests {
             session_id: String::new(),
             amr: vec!["did".to_string()],
             acr: "aal1".to_string(),
+            api_key: None,
         }
     }
 
@@ -2270,6 +2382,8 @@ mod tests {
             method: "webvh".to_string(),
             domain: String::new(),
             agent_names: Vec::new(),
+            deactivated_at: None,
+            sync_head: None,
         };
         state
             .dids_ks
@@ -3881,6 +3995,7 @@ mod tests {
             trust_tasks: state.config.trust_tasks.clone(),
             hosting: state.config.hosting.clone(),
             identity: Default::default(),
+            consent: Default::default(),
             config_path: state.config.config_path.clone(),
         };
         state.config = Arc::new(cfg);
@@ -3911,8 +4026,8 @@ mod tests {
     };
     use did_hosting_common::server::trust_tasks::TransportBoundVerifier;
 
+    use crate::approvals::Approval;
     use crate::routes::task_consent::{build_signed_request_document, wire_digest};
-    use crate::server::PendingConfirm;
     use crate::signing::test_util::did_key_signer;
 
     /// The action text used across the round-trip tests.
@@ -3950,6 +4065,7 @@ mod tests {
             control_signer,
             holder_did,
             "did:example:admin",
+            crate::routes::task_consent::ApprovalRule::HOLDER,
             CONSENT_ACTION,
             task_type,
             &challenge,
@@ -4006,36 +4122,58 @@ mod tests {
         .expect("sign decision")
     }
 
-    /// Park a pending consent exactly as the REST route does; the
-    /// returned receiver resolves with the wallet's decision.
+    /// Store a pending approval exactly as `solicit` does.
     async fn park_pending(
         state: &AppState,
         challenge: &str,
         holder_did: &str,
         digest: &str,
-    ) -> tokio::sync::oneshot::Receiver<bool> {
-        let (tx, rx) = tokio::sync::oneshot::channel();
-        state.pending_confirms.lock().await.insert(
-            challenge.to_string(),
-            PendingConfirm {
-                holder_did: holder_did.to_string(),
-                expected_digest: digest.to_string(),
-                tx,
-            },
-        );
-        rx
+    ) -> Approval {
+        park_quorum(state, challenge, &[holder_did], 1, digest).await
+    }
+
+    /// [`park_pending`] for `min_approvals` of several approvers, as the
+    /// `crate::consent` gates store it.
+    async fn park_quorum(
+        state: &AppState,
+        challenge: &str,
+        approvers: &[&str],
+        min_approvals: usize,
+        digest: &str,
+    ) -> Approval {
+        let now = crate::auth::session::now_epoch();
+        let approval = Approval {
+            id: challenge.to_string(),
+            gate: None,
+            requester: "did:example:admin".into(),
+            note: CONSENT_ACTION.into(),
+            approvers: approvers.iter().map(|a| a.to_string()).collect(),
+            expected_digest: digest.to_string(),
+            min_approvals,
+            created_at: now,
+            expires_at: now + 60,
+        };
+        crate::approvals::open(&state.store, &approval)
+            .await
+            .expect("store approval");
+        approval
+    }
+
+    /// Where `approval` stands now.
+    async fn outcome(state: &AppState, approval: &Approval) -> ConsentOutcome {
+        crate::approvals::status(&state.store, approval, crate::auth::session::now_epoch())
+            .await
+            .expect("approval status")
     }
 
     /// Drive a decision document through the handler and assert it is
-    /// refused *silently and completely*: no acknowledgement, the parked
-    /// entry survives for the legitimate wallet, and the channel never
-    /// fires.
+    /// refused *silently and completely*: no acknowledgement, and the
+    /// stored approval is left pending with nothing counted.
     async fn assert_decision_refused(
         state: &AppState,
         sender: &str,
         decision: Value,
-        rx: &mut tokio::sync::oneshot::Receiver<bool>,
-        challenge: &str,
+        approval: &Approval,
         why: &str,
     ) {
         let msg = build_msg(TASK_CONSENT_DECISION_0_1.as_str(), decision);
@@ -4043,16 +4181,12 @@ mod tests {
             .await
             .expect("refusals are silent, not transport errors");
         assert!(resp.is_none(), "{why}: must not be acknowledged");
-        assert!(
-            state.pending_confirms.lock().await.contains_key(challenge),
-            "{why}: the pending entry must survive"
-        );
         assert!(
             matches!(
-                rx.try_recv(),
-                Err(tokio::sync::oneshot::error::TryRecvError::Empty)
+                outcome(state, approval).await,
+                ConsentOutcome::Pending { approvals: 0 }
             ),
-            "{why}: the parked request must not resolve"
+            "{why}: the approval must stay pending with nothing counted"
         );
     }
 
@@ -4095,11 +4229,11 @@ mod tests {
         assert_eq!(echo_challenge, challenge);
         assert_eq!(echo_digest, digest);
 
-        // ── The REST route parks the pending entry keyed by challenge.
-        let mut rx = park_pending(&state, &challenge, &holder_did, &digest).await;
+        // ── The REST route stores the approval keyed by challenge.
+        let approval = park_pending(&state, &challenge, &holder_did, &digest).await;
 
         // ── Decision leg: holder signs an approve; the handler verifies
-        // and resolves the parked request.
+        // it and records it against the approval.
         let decision = signed_decision(
             &holder_signer,
             &holder_did,
@@ -4119,10 +4253,22 @@ mod tests {
         assert_eq!(ack["status"], "granted");
         assert_eq!(ack["approvals"], 1);
         assert_eq!(ack["payloadDigest"], digest.as_str());
-        assert!(rx.try_recv().expect("decision delivered"), "approved");
+        match outcome(&state, &approval).await {
+            ConsentOutcome::Granted(decisions) => {
+                assert_eq!(decisions.len(), 1);
+                assert!(
+                    decisions[0]["proof"].is_object(),
+                    "the signed decision is kept"
+                );
+            }
+            other => panic!("expected approval, got {other:?}"),
+        }
         assert!(
-            state.pending_confirms.lock().await.is_empty(),
-            "pending entry consumed"
+            super::run_consent_decision(&state, &holder_did, &msg)
+                .await
+                .expect("handler ok")
+                .is_none(),
+            "a resolved approval takes no further decisions"
         );
     }
 
@@ -4135,7 +4281,7 @@ mod tests {
         let (state, _dir) = consent_state(&control_did).await;
         let (_request, challenge, digest) =
             minted_request(&control_did, &control_signer, &holder_did).await;
-        let mut rx = park_pending(&state, &challenge, &holder_did, &digest).await;
+        let approval = park_pending(&state, &challenge, &holder_did, &digest).await;
 
         let decision = signed_decision(
             &holder_signer,
@@ -4154,7 +4300,172 @@ mod tests {
 
         assert_eq!(ack["status"], "denied");
         assert_eq!(ack["approvals"], 0);
-        assert!(!rx.try_recv().expect("decision delivered"), "denied");
+        assert!(
+            matches!(
+                outcome(&state, &approval).await,
+                ConsentOutcome::Denied { by } if by == holder_did
+            ),
+            "denied"
+        );
+    }
+
+    /// Two-of-three: the first approval is acknowledged `pending` and
+    /// leaves the request parked; a repeat from the same approver does
+    /// not count twice; the second distinct approver grants it, and the
+    /// outcome carries both signed decisions.
+    #[tokio::test]
+    async fn task_consent_quorum_grants_at_threshold() {
+        let (control_did, control_signer) = did_key_signer(&[39u8; 32]);
+        let (alice, alice_signer) = did_key_signer(&[40u8; 32]);
+        let (bob, bob_signer) = did_key_signer(&[41u8; 32]);
+        let (carol, _) = did_key_signer(&[42u8; 32]);
+        let (state, _dir) = consent_state(&control_did).await;
+        let (_request, challenge, digest) =
+            minted_request(&control_did, &control_signer, &alice).await;
+        let approval = park_quorum(&state, &challenge, &[&alice, &bob, &carol], 2, &digest).await;
+
+        let decide = async |signer: &Secret, did: &str| {
+            let decision =
+                signed_decision(signer, did, &control_did, &challenge, &digest, "approve").await;
+            let msg = build_msg(TASK_CONSENT_DECISION_0_1.as_str(), decision);
+            super::run_consent_decision(&state, did, &msg)
+                .await
+                .expect("handler ok")
+                .expect("an acknowledgement is returned")
+                .1
+        };
+
+        let ack = decide(&alice_signer, &alice).await;
+        assert_eq!(ack["status"], "pending");
+        assert_eq!(ack["approvals"], 1);
+        let ack = decide(&alice_signer, &alice).await;
+        assert_eq!(ack["status"], "pending", "a repeat approval counts once");
+        assert_eq!(ack["approvals"], 1);
+        assert!(
+            matches!(
+                outcome(&state, &approval).await,
+                ConsentOutcome::Pending { approvals: 1 }
+            ),
+            "one approval of two must not resolve the request"
+        );
+
+        let ack = decide(&bob_signer, &bob).await;
+        assert_eq!(ack["status"], "granted");
+        assert_eq!(ack["approvals"], 2);
+        match outcome(&state, &approval).await {
+            ConsentOutcome::Granted(decisions) => {
+                let issuers: Vec<_> = decisions.iter().map(|d| d["issuer"].clone()).collect();
+                assert_eq!(issuers, vec![json!(alice), json!(bob)]);
+            }
+            other => panic!("expected approval, got {other:?}"),
+        }
+    }
+
+    /// A deny from any approver ends the request, even after approvals.
+    #[tokio::test]
+    async fn task_consent_quorum_deny_overrides_approvals() {
+        let (control_did, control_signer) = did_key_signer(&[43u8; 32]);
+        let (alice, alice_signer) = did_key_signer(&[44u8; 32]);
+        let (bob, bob_signer) = did_key_signer(&[45u8; 32]);
+        let (state, _dir) = consent_state(&control_did).await;
+        let (_request, challenge, digest) =
+            minted_request(&control_did, &control_signer, &alice).await;
+        let approval = park_quorum(&state, &challenge, &[&alice, &bob], 2, &digest).await;
+
+        for (signer, did, decision) in [
+            (&alice_signer, &alice, "approve"),
+            (&bob_signer, &bob, "deny"),
+        ] {
+            let doc =
+                signed_decision(signer, did, &control_did, &challenge, &digest, decision).await;
+            let msg = build_msg(TASK_CONSENT_DECISION_0_1.as_str(), doc);
+            super::run_consent_decision(&state, did, &msg)
+                .await
+                .expect("handler ok")
+                .expect("an acknowledgement is returned");
+        }
+
+        assert!(matches!(
+            outcome(&state, &approval).await,
+            ConsentOutcome::Denied { by } if by == bob
+        ));
+    }
+
+    /// A gated action answers `202` with the approval's id until the quorum
+    /// is in; the repeat after that runs with both signed decisions and
+    /// spends the approval, so a third call has to ask again.
+    #[tokio::test]
+    async fn consent_gate_redeems_a_granted_approval_once() {
+        use crate::consent::{self, DOMAIN_PURGE, Gate};
+
+        let (control_did, _) = did_key_signer(&[46u8; 32]);
+        let (alice, alice_signer) = did_key_signer(&[47u8; 32]);
+        let (bob, bob_signer) = did_key_signer(&[48u8; 32]);
+        let (mut state, _dir) = consent_state(&control_did).await;
+        let mut config = (*state.config).clone();
+        config.consent.policies = vec![crate::config::ConsentPolicy {
+            task: DOMAIN_PURGE.into(),
+            role: None,
+            approvers: vec![alice.clone(), bob.clone()],
+            threshold: 2,
+            exclude_requester: true,
+            timeout_secs: 60,
+        }];
+        state.config = Arc::new(config);
+        let gate = || Gate {
+            task: DOMAIN_PURGE,
+            role: None,
+            subject: "a.example",
+            note: "Purge a.example".into(),
+        };
+
+        // What `solicit` stores for the first request.
+        let challenge = "aabbccddeeff00112233445566778899";
+        let digest = "zDigest";
+        let mut approval = park_quorum(&state, challenge, &[&alice, &bob], 2, digest).await;
+        approval.gate = Some(consent::gate_digest("did:example:admin", &gate()).unwrap());
+        crate::approvals::open(&state.store, &approval)
+            .await
+            .unwrap();
+
+        for (signer, did) in [(&alice_signer, &alice), (&bob_signer, &bob)] {
+            match consent::require(&state, "did:example:admin", gate()).await {
+                Err(AppError::ApprovalPending { approval_id }) => {
+                    assert_eq!(approval_id, challenge)
+                }
+                other => panic!("expected a pending approval, got {other:?}"),
+            }
+            let doc =
+                signed_decision(signer, did, &control_did, challenge, digest, "approve").await;
+            let msg = build_msg(TASK_CONSENT_DECISION_0_1.as_str(), doc);
+            super::run_consent_decision(&state, did, &msg)
+                .await
+                .expect("handler ok")
+                .expect("an acknowledgement is returned");
+        }
+
+        // Someone else repeating the same action does not spend it.
+        assert!(
+            consent::require(&state, "did:example:other", gate())
+                .await
+                .is_err()
+        );
+        let decisions = consent::require(&state, "did:example:admin", gate())
+            .await
+            .expect("granted");
+        assert_eq!(decisions.len(), 2);
+        assert!(
+            crate::approvals::get(&state.store, challenge)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        // Spent: the next repeat asks the approvers again, which needs the
+        // DIDComm service this test does not start.
+        assert!(!matches!(
+            consent::require(&state, "did:example:admin", gate()).await,
+            Ok(_) | Err(AppError::ApprovalPending { .. })
+        ));
     }
 
     /// An unsigned decision is refused outright — the proof, not the
@@ -4166,7 +4477,7 @@ mod tests {
         let (state, _dir) = consent_state(&control_did).await;
         let (_request, challenge, digest) =
             minted_request(&control_did, &control_signer, &holder_did).await;
-        let mut rx = park_pending(&state, &challenge, &holder_did, &digest).await;
+        let approval = park_pending(&state, &challenge, &holder_did, &digest).await;
 
         let decision = unsigned_decision(
             Some(&holder_did),
@@ -4179,8 +4490,7 @@ mod tests {
             &state,
             &holder_did,
             decision,
-            &mut rx,
-            &challenge,
+            &approval,
             "unsigned decision",
         )
         .await;
@@ -4196,7 +4506,7 @@ mod tests {
         let (state, _dir) = consent_state(&control_did).await;
         let (_request, challenge, digest) =
             minted_request(&control_did, &control_signer, &holder_did).await;
-        let mut rx = park_pending(&state, &challenge, &holder_did, &digest).await;
+        let approval = park_pending(&state, &challenge, &holder_did, &digest).await;
 
         let mut decision = signed_decision(
             &holder_signer,
@@ -4212,8 +4522,7 @@ mod tests {
             &state,
             &holder_did,
             decision,
-            &mut rx,
-            &challenge,
+            &approval,
             "tampered decision",
         )
         .await;
@@ -4230,7 +4539,7 @@ mod tests {
         let (state, _dir) = consent_state(&control_did).await;
         let (_request, challenge, digest) =
             minted_request(&control_did, &control_signer, &holder_did).await;
-        let mut rx = park_pending(&state, &challenge, &holder_did, &digest).await;
+        let approval = park_pending(&state, &challenge, &holder_did, &digest).await;
 
         // Well-formed and properly signed — just by the wrong DID.
         let decision = signed_decision(
@@ -4242,15 +4551,7 @@ mod tests {
             "approve",
         )
         .await;
-        assert_decision_refused(
-            &state,
-            &attacker_did,
-            decision,
-            &mut rx,
-            &challenge,
-            "wrong holder",
-        )
-        .await;
+        assert_decision_refused(&state, &attacker_did, decision, &approval, "wrong holder").await;
     }
 
     /// The proof's `verificationMethod` DID must equal the authcrypt
@@ -4265,7 +4566,7 @@ mod tests {
         let (state, _dir) = consent_state(&control_did).await;
         let (_request, challenge, digest) =
             minted_request(&control_did, &control_signer, &holder_did).await;
-        let mut rx = park_pending(&state, &challenge, &holder_did, &digest).await;
+        let approval = park_pending(&state, &challenge, &holder_did, &digest).await;
 
         // Signed by the attacker's key, but arriving on a transport
         // session the service attributes to the holder.
@@ -4282,8 +4583,7 @@ mod tests {
             &state,
             &holder_did,
             decision,
-            &mut rx,
-            &challenge,
+            &approval,
             "proof key != sender",
         )
         .await;
@@ -4299,7 +4599,7 @@ mod tests {
         let (state, _dir) = consent_state(&control_did).await;
         let (_request, challenge, digest) =
             minted_request(&control_did, &control_signer, &holder_did).await;
-        let mut rx = park_pending(&state, &challenge, &holder_did, &digest).await;
+        let approval = park_pending(&state, &challenge, &holder_did, &digest).await;
 
         // A digest over a *different* action than the one we asked about.
         let other_digest = wire_digest(
@@ -4317,15 +4617,7 @@ mod tests {
             "approve",
         )
         .await;
-        assert_decision_refused(
-            &state,
-            &holder_did,
-            decision,
-            &mut rx,
-            &challenge,
-            "digest mismatch",
-        )
-        .await;
+        assert_decision_refused(&state, &holder_did, decision, &approval, "digest mismatch").await;
     }
 
     /// Audience binding: a decision addressed to another executor must
@@ -4339,7 +4631,7 @@ mod tests {
         let (state, _dir) = consent_state(&control_did).await;
         let (_request, challenge, digest) =
             minted_request(&control_did, &control_signer, &holder_did).await;
-        let mut rx = park_pending(&state, &challenge, &holder_did, &digest).await;
+        let approval = park_pending(&state, &challenge, &holder_did, &digest).await;
 
         let decision = signed_decision(
             &holder_signer,
@@ -4350,15 +4642,7 @@ mod tests {
             "approve",
         )
         .await;
-        assert_decision_refused(
-            &state,
-            &holder_did,
-            decision,
-            &mut rx,
-            &challenge,
-            "wrong recipient",
-        )
-        .await;
+        assert_decision_refused(&state, &holder_did, decision, &approval, "wrong recipient").await;
     }
 
     /// A decision for a challenge nothing is parked on (stale, lapsed,
@@ -0,0 +1,197 @@
+//! Outbound requests to caller-supplied URLs: webhook endpoints and
+//! import source logs.
+//!
+//! The control plane runs inside the operator's network, so a tenant must
+//! not be able to aim it at internal services. [`validate_url`] refuses
+//! loopback names and internal address literals up front, but a public
+//! hostname can resolve anywhere — and can resolve differently by the time
+//! the request is made. Tenant requests therefore go through [`client`],
+//! whose resolver drops internal addresses at connect time and fails when
+//! none are left. Admin requests keep the shared `state.http_client`, which
+//! may reach internal hosts.
+//!
+//! Neither client follows redirects.
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+
+use crate::error::AppError;
+use crate::server::AppState;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Check a caller-supplied URL. `what` names it in errors ("webhook URL",
+/// "source URL").
+///
+/// HTTPS only, no embedded credentials. Non-admin callers additionally may
+/// not name `localhost` or an internal address literal.
+pub fn validate_url(raw: &str, admin: bool, what: &str) -> Result<url::Url, AppError> {
+    let url = url::Url::parse(raw.trim())
+        .map_err(|e| AppError::Validation(format!("invalid {what}: {e}")))?;
+    if url.scheme() != "https" {
+        return Err(AppError::Validation(format!("{what} must use https")));
+    }
+    if !url.username().is_empty() || url.password().is_some() {
+        return Err(AppError::Validation(format!(
+            "{what} must not embed credentials"
+        )));
+    }
+    let internal = match url.host() {
+        None => return Err(AppError::Validation(format!("{what} has no host"))),
+        Some(url::Host::Domain(d)) => {
+            let d = d.to_ascii_lowercase();
+            d == "localhost" || d.ends_with(".localhost")
+        }
+        Some(url::Host::Ipv4(ip)) => is_internal(IpAddr::V4(ip)),
+        Some(url::Host::Ipv6(ip)) => is_internal(IpAddr::V6(ip)),
+    };
+    if internal && !admin {
+        return Err(AppError::Validation(format!(
+            "{what} must not target a loopback, private or link-local address"
+        )));
+    }
+    Ok(url)
+}
+
+/// Whether a tenant may not reach `ip`: anything that is not a globally
+/// routable unicast address. IPv6 forms that embed an IPv4 address
+/// (mapped, NAT64, 6to4) are judged by that address.
+pub(crate) fn is_internal(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, c, _] = v4.octets();
+            v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_multicast()
+                || v4.is_documentation()
+                || a == 0 // "this network"
+                || (a == 100 && (b & 0xc0) == 64) // shared address space (CGNAT)
+                || (a == 192 && b == 0 && c == 0) // IETF protocol assignments
+                || (a == 198 && (b & 0xfe) == 18) // benchmarking
+                || a >= 240 // reserved
+        }
+        IpAddr::V6(v6) => {
+            let segments = v6.segments();
+            let first = segments[0];
+            let embedded_v4 = |hi: u16, lo: u16| {
+                let [a, b] = hi.to_be_bytes();
+                let [c, d] = lo.to_be_bytes();
+                is_internal(IpAddr::V4(std::net::Ipv4Addr::new(a, b, c, d)))
+            };
+            v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                || (first & 0xfe00) == 0xfc00 // unique local
+                || (first & 0xffc0) == 0xfe80 // link local
+                || (first & 0xffc0) == 0xfec0 // site local (deprecated)
+                || (first == 0x2001 && segments[1] == 0x0db8) // documentation
+                || (first == 0x0100 && segments[1..4] == [0, 0, 0]) // discard
+                || v6.to_ipv4_mapped().is_some_and(|v4| is_internal(IpAddr::V4(v4)))
+                || (segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] // NAT64
+                    && embedded_v4(segments[6], segments[7]))
+                || (first == 0x2002 && embedded_v4(segments[1], segments[2])) // 6to4
+        }
+    }
+}
+
+/// Resolves through the system resolver and keeps only the addresses a
+/// tenant may reach.
+struct PublicOnly;
+
+impl Resolve for PublicOnly {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| !is_internal(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{host} resolves only to internal addresses").into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+static PUBLIC_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .dns_resolver(PublicOnly)
+        .build()
+        .expect("reqwest client construction must succeed")
+});
+
+/// The client to reach a URL supplied by a caller who is (`admin`) or is
+/// not an admin.
+pub fn client(state: &AppState, admin: bool) -> reqwest::Client {
+    if admin {
+        state.http_client.clone()
+    } else {
+        PUBLIC_CLIENT.clone()
+    }
+}
+
+/// Read a response body, failing as soon as it passes `limit` bytes.
+pub async fn read_capped(mut resp: reqwest::Response, limit: usize) -> Result<Vec<u8>, String> {
+    if resp.content_length().is_some_and(|len| len > limit as u64) {
+        return Err(format!("body exceeds {limit} bytes"));
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = resp.chunk().await.map_err(|e| e.to_string())? {
+        if body.len() + chunk.len() > limit {
+            return Err(format!("body exceeds {limit} bytes"));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_global_unicast_addresses_are_external() {
+        for internal in [
+            "100.64.0.1",
+            "100.127.255.254",
+            "192.0.0.8",
+            "198.18.0.1",
+            "203.0.113.7",
+            "224.0.0.251",
+            "240.0.0.1",
+            "255.255.255.255",
+            "0.1.2.3",
+            "ff02::1",
+            "fec0::1",
+            "2001:db8::1",
+            "64:ff9b::a9fe:a9fe",
+            "2002:0a00:0001::1",
+        ] {
+            assert!(is_internal(internal.parse().unwrap()), "{internal}");
+        }
+        for external in [
+            "93.184.216.34",
+            "100.128.0.1",
+            "2606:4700::1111",
+            "64:ff9b::808:808",
+        ] {
+            assert!(!is_internal(external.parse().unwrap()), "{external}");
+        }
+    }
+
+    #[tokio::test]
+    async fn tenant_lookups_drop_internal_addresses() {
+        let name: Name = "localhost".parse().unwrap();
+        let err = PublicOnly.resolve(name).await.err().expect("refused");
+        assert!(err.to_string().contains("internal"), "got {err}");
+    }
+}
@@ -111,14 +111,14 @@ fn target_prefix(target_did: &str) -> Vec<u8> {
     format!("outbox:{target_did}:").into_bytes()
 }
 
-fn now_micros() -> u128 {
+pub(crate) fn now_micros() -> u128 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .map(|d| d.as_micros())
         .unwrap_or(0)
 }
 
-fn truncated(err: &str) -> String {
+pub(crate) fn truncated(err: &str) -> String {
     const MAX: usize = 200;
     if err.len() <= MAX {
         err.to_string()
@@ -127,7 +127,7 @@ fn truncated(err: &str) -> String {
     }
 }
 
-fn compute_backoff(attempts: u32) -> u64 {
+pub(crate) fn compute_backoff(attempts: u32) -> u64 {
     let secs = 1u64
         .checked_shl(attempts.min(10))
         .unwrap_or(MAX_BACKOFF_SECS);
@@ -18,6 +18,14 @@
 //!
 //! Eventually-consistent: a few seconds of skew between control's
 //! delete and the servers' deletes is acceptable.
+//!
+//! The same tick also drops rolled-back log entries whose
+//! `hosting.rollback_archive_retention` window has lapsed (see
+//! [`crate::log_archive`]), and retires the old locations of moved DIDs
+//! once `hosting.moved_did_retention` has passed (see
+//! [`crate::did_move`]), abandons imports whose move entry never arrived
+//! (see [`crate::did_import`]), and removes consent requests that can no
+//! longer be redeemed (see [`crate::approvals`]).
 
 use std::time::Duration;
 
@@ -28,6 +36,7 @@ use tokio::sync::watch;
 use tracing::{debug, info, warn};
 
 use crate::auth::session::now_epoch;
+use crate::server::AppState;
 
 /// 60-second tick — matches the server sweep cadence. Operators
 /// expecting prompt cleanup tune the grace value (`hosting
@@ -68,6 +77,19 @@ pub async fn run_sweep_once(store: &Store) -> u64 {
                     domain = %entry.domain,
                     "control purge sweep: disabled-domain record deleted"
                 );
+                if let Err(e) = crate::webhooks::emit_domain(
+                    store,
+                    crate::webhooks::DOMAIN_DELETED,
+                    &entry.domain,
+                )
+                .await
+                {
+                    warn!(
+                        domain = %entry.domain,
+                        error = %e,
+                        "control purge sweep: failed to stage domain.deleted webhook"
+                    );
+                }
             }
             Err(e) => {
                 // Retain the pending row — next tick will retry.
@@ -94,7 +116,8 @@ pub async fn run_sweep_once(store: &Store) -> u64 {
 /// Long-running background driver for [`run_sweep_once`]. Spawn one
 /// of these per control-plane process; standalone control binaries
 /// call it from their startup chain.
-pub async fn run_purge_sweep_loop(store: Store, mut shutdown: watch::Receiver<bool>) {
+pub async fn run_purge_sweep_loop(state: AppState, mut shutdown: watch::Receiver<bool>) {
+    let store = state.store.clone();
     let mut ticker = tokio::time::interval(DEFAULT_SWEEP_INTERVAL);
     // First-tick: skip the immediate fire so startup logs aren't
     // crowded by an empty-sweep line.
@@ -107,6 +130,26 @@ pub async fn run_purge_sweep_loop(store: Store, mut shutdown: watch::Receiver<bo
                 if purged > 0 {
                     info!(count = purged, "control purge sweep tick completed");
                 }
+                match crate::log_archive::prune_expired(&store, now_epoch()).await {
+                    Ok(0) => {}
+                    Ok(n) => info!(count = n, "pruned expired archived log entries"),
+                    Err(e) => warn!(error = %e, "failed to prune archived log entries"),
+                }
+                match crate::did_move::expire_stubs(&state, now_epoch()).await {
+                    Ok(0) => {}
+                    Ok(n) => info!(count = n, "retired expired moved-DID stubs"),
+                    Err(e) => warn!(error = %e, "failed to retire moved-DID stubs"),
+                }
+                match crate::did_import::expire_pending(&state, now_epoch()).await {
+                    Ok(0) => {}
+                    Ok(n) => info!(count = n, "abandoned expired DID imports"),
+                    Err(e) => warn!(error = %e, "failed to abandon expired DID imports"),
+                }
+                match crate::approvals::sweep_expired(&store, now_epoch()).await {
+                    Ok(0) => {}
+                    Ok(n) => info!(count = n, "removed lapsed consent requests"),
+                    Err(e) => warn!(error = %e, "failed to remove lapsed consent requests"),
+                }
             }
             _ = shutdown.changed() => {
                 info!("control purge sweep loop shutting down");
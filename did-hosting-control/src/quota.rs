@@ -0,0 +1,472 @@
+//! Per-owner and per-domain hosting quotas.
+//!
+//! Two sets of limits apply to every DID write on the control plane:
+//!
+//! - the owner's [`AclEntry`] — `max_did_count` / `max_total_size`;
+//! - the hosting domain's [`DomainQuota`] — `max_dids_in_domain` /
+//!   `max_total_size_in_domain`.
+//!
+//! `None` means unlimited; the control plane has no global default the
+//! way `did-hosting-server`'s `[limits]` does. Admin callers are exempt,
+//! matching the server-side checks.
+//!
+//! Owner usage walks the owner index, and is skipped when the owner has no
+//! limit. Domain usage is a counter at [`domain_usage_key`], changed in the
+//! same write batch as the records it counts ([`UsageLock::record`] /
+//! [`UsageLock::stage`]); a domain with no counter yet is counted from its
+//! records once. Deactivated DIDs are left out of owner usage — they are
+//! kept as evidence, not hosted on the owner's behalf — but still take up
+//! room in their domain.
+//!
+//! A write holds a [`UsageLock`] on the owner it charges and every domain
+//! it touches from its quota check until its batch commits, so two writes
+//! for one owner or to one domain cannot both pass a check that only one
+//! of them fits under, and counter updates do not overwrite each other.
+//! [`check`] refuses to run without the charged owner's lock. The lock is
+//! per process, like
+//! [`crate::path_locks`]. Writes that bypass the control plane (a hosting
+//! server sharing the store, a restore) are not counted; a domain purge
+//! drops the counter, and deleting it by hand forces a recount.
+//!
+//! A refused write returns [`AppError::QuotaExceeded`], which renders as
+//! `403` on REST, `e.p.did.quota-exceeded` on DIDComm and the
+//! `quota_exceeded` extended code on Trust Tasks. The messages avoid the
+//! word "size" so [`AppError::quota_kind`] keeps them on the quota code
+//! rather than the per-upload `e.p.did.size-exceeded`.
+//!
+//! [`AclEntry`]: did_hosting_common::server::acl::AclEntry
+//! [`DomainQuota`]: did_hosting_common::server::domain::DomainQuota
+
+use std::collections::BTreeMap;
+
+use did_hosting_common::did_ops::{DidRecord, did_key, domain_usage_key};
+use did_hosting_common::server::acl::get_acl_entry;
+use did_hosting_common::server::domain::{self, extract_did_host};
+use serde::{Deserialize, Serialize};
+use tokio::sync::OwnedMutexGuard;
+use tracing::{debug, warn};
+
+use crate::acl::Role;
+use crate::auth::AuthClaims;
+use crate::error::AppError;
+use crate::server::AppState;
+use crate::store::WriteBatch;
+
+/// What a subject currently hosts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Usage {
+    pub did_count: u64,
+    /// Bytes across all stored `did.jsonl` logs.
+    pub total_size: u64,
+}
+
+/// Configured limits for a subject. `None` = unlimited.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Limits {
+    pub max_did_count: Option<u64>,
+    pub max_total_size: Option<u64>,
+}
+
+impl Limits {
+    fn is_unlimited(&self) -> bool {
+        self.max_did_count.is_none() && self.max_total_size.is_none()
+    }
+}
+
+/// Usage against limits for one owner DID or one domain.
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageReport {
+    /// The owner DID or domain name.
+    pub subject: String,
+    pub usage: Usage,
+    pub limits: Limits,
+}
+
+/// The effect a write would have on quota usage.
+#[derive(Debug, Clone, Copy)]
+pub struct Charge<'a> {
+    /// Owner DID the DID is (or will be) hosted under.
+    pub owner: &'a str,
+    /// Hosting domain, when known. `None` skips the domain check.
+    pub domain: Option<&'a str>,
+    /// DIDs this write adds for `owner` (1 for a new slot, else 0).
+    pub new_dids: u64,
+    /// Current stored log size of the DID being written (0 when new).
+    pub old_size: u64,
+    /// Log size after the write.
+    pub new_size: u64,
+}
+
+/// The hosting domain a record counts against: its tagged domain, or the
+/// DID's own host for legacy records that never had one resolved.
+pub fn record_domain(record: &DidRecord) -> Option<String> {
+    if !record.domain.is_empty() {
+        return Some(record.domain.to_ascii_lowercase());
+    }
+    record
+        .did_id
+        .as_deref()
+        .and_then(|d| extract_did_host(d).ok())
+}
+
+/// Limits from the owner's ACL entry.
+pub async fn owner_limits(state: &AppState, owner: &str) -> Result<Limits, AppError> {
+    Ok(get_acl_entry(&state.acl_ks, owner)
+        .await?
+        .map(|e| Limits {
+            max_did_count: e.max_did_count,
+            max_total_size: e.max_total_size,
+        })
+        .unwrap_or_default())
+}
+
+/// Limits from the domain's `quota`.
+pub async fn domain_limits(state: &AppState, name: &str) -> Result<Limits, AppError> {
+    Ok(domain::get_domain(&state.store, name)
+        .await?
+        .and_then(|e| e.quota)
+        .map(|q| Limits {
+            max_did_count: q.max_dids_in_domain,
+            max_total_size: q.max_total_size_in_domain,
+        })
+        .unwrap_or_default())
+}
+
+/// DIDs and bytes currently hosted for `owner`, deactivated DIDs aside.
+pub async fn owner_usage(state: &AppState, owner: &str) -> Result<Usage, AppError> {
+    let mut usage = Usage::default();
+    for (_key, value) in state
+        .dids_ks
+        .prefix_iter_raw(format!("owner:{owner}:"))
+        .await?
+    {
+        let Ok(mnemonic) = String::from_utf8(value) else {
+            continue;
+        };
+        // Same prefix-collision guard as `did_ops::list_dids`: a DID that
+        // is a string-prefix of another shares its index prefix.
+        if let Some(record) = state.dids_ks.get::<DidRecord>(did_key(&mnemonic)).await?
+            && record.owner == owner
+            && !record.is_deactivated()
+        {
+            usage.did_count += 1;
+            usage.total_size += record.content_size;
+        }
+    }
+    Ok(usage)
+}
+
+/// DIDs and bytes currently hosted in domain `name`.
+pub async fn domain_usage(state: &AppState, name: &str) -> Result<Usage, AppError> {
+    let lock = lock(state, [], [Some(name.to_string())]).await;
+    domain_counter(state, &lock.domains[0]).await
+}
+
+/// Read domain `name`'s counter, counting its records when there is none
+/// yet. Callers hold its [`UsageLock`].
+async fn domain_counter(state: &AppState, name: &str) -> Result<Usage, AppError> {
+    if let Some(usage) = state.dids_ks.get(domain_usage_key(name)).await? {
+        return Ok(usage);
+    }
+    let mut usage = Usage::default();
+    for (_key, value) in state.dids_ks.prefix_iter_raw("did:").await? {
+        let Ok(record) = serde_json::from_slice::<DidRecord>(&value) else {
+            continue;
+        };
+        if record_domain(&record).as_deref() == Some(name) {
+            usage.did_count += 1;
+            usage.total_size += record.content_size;
+        }
+    }
+    state.dids_ks.insert(domain_usage_key(name), &usage).await?;
+    debug!(domain = name, ?usage, "domain usage counted");
+    Ok(usage)
+}
+
+/// Exclusive hold on the usage of a set of owners and domains, from a
+/// quota check until the write it allowed has committed. Drop it after the
+/// commit.
+pub struct UsageLock {
+    owners: Vec<String>,
+    domains: Vec<String>,
+    changes: BTreeMap<String, (i64, i64)>,
+    _guards: Vec<OwnedMutexGuard<()>>,
+}
+
+/// Path-lock key serialising quota checks for one owner.
+fn owner_lock_key(owner: &str) -> String {
+    format!("owner_usage:{owner}")
+}
+
+/// Lock the usage of `owners`, then the counters of `domains` (`None`s and
+/// repeats are skipped). Each set is taken in name order, owners before
+/// domains, after any path lock the caller holds.
+pub async fn lock<'a>(
+    state: &AppState,
+    owners: impl IntoIterator<Item = &'a str>,
+    domains: impl IntoIterator<Item = Option<String>>,
+) -> UsageLock {
+    let mut owners: Vec<String> = owners.into_iter().map(str::to_string).collect();
+    owners.sort();
+    owners.dedup();
+    let mut domains: Vec<String> = domains
+        .into_iter()
+        .flatten()
+        .filter(|d| !d.is_empty())
+        .map(|d| d.to_ascii_lowercase())
+        .collect();
+    domains.sort();
+    domains.dedup();
+    let mut guards = Vec::with_capacity(owners.len() + domains.len());
+    for owner in &owners {
+        guards.push(state.path_locks.guard(&owner_lock_key(owner)).await);
+    }
+    for name in &domains {
+        guards.push(state.path_locks.guard(&domain_usage_key(name)).await);
+    }
+    UsageLock {
+        owners,
+        domains,
+        changes: BTreeMap::new(),
+        _guards: guards,
+    }
+}
+
+impl UsageLock {
+    fn holds_owner(&self, owner: &str) -> Result<(), AppError> {
+        if self.owners.iter().any(|o| o == owner) {
+            Ok(())
+        } else {
+            Err(AppError::Internal(format!(
+                "usage of owner {owner} used without its lock"
+            )))
+        }
+    }
+
+    fn holds(&self, name: &str) -> Result<(), AppError> {
+        if self.domains.iter().any(|d| d == name) {
+            Ok(())
+        } else {
+            Err(AppError::Internal(format!(
+                "usage of domain {name} used without its lock"
+            )))
+        }
+    }
+
+    /// Count a record write: `before` is the stored record it replaces or
+    /// removes, `after` the one it stores.
+    pub fn record(
+        &mut self,
+        before: Option<&DidRecord>,
+        after: Option<&DidRecord>,
+    ) -> Result<(), AppError> {
+        for (record, sign) in [(before, -1), (after, 1)] {
+            let Some(record) = record else { continue };
+            let Some(name) = record_domain(record) else {
+                continue;
+            };
+            self.holds(&name)?;
+            let (dids, bytes) = self.changes.entry(name).or_default();
+            *dids += sign;
+            *bytes += sign * i64::try_from(record.content_size).unwrap_or(i64::MAX);
+        }
+        Ok(())
+    }
+
+    /// Add the counters changed by the [`record`](Self::record)ed writes
+    /// to `batch`.
+    pub async fn stage(&self, state: &AppState, batch: &mut WriteBatch) -> Result<(), AppError> {
+        for (name, &(dids, bytes)) in &self.changes {
+            if dids == 0 && bytes == 0 {
+                continue;
+            }
+            let mut usage = domain_counter(state, name).await?;
+            usage.did_count = usage.did_count.saturating_add_signed(dids);
+            usage.total_size = usage.total_size.saturating_add_signed(bytes);
+            batch.insert(&state.dids_ks, domain_usage_key(name), &usage)?;
+        }
+        Ok(())
+    }
+}
+
+/// Usage and limits for `owner`.
+pub async fn owner_report(state: &AppState, owner: &str) -> Result<UsageReport, AppError> {
+    Ok(UsageReport {
+        subject: owner.to_string(),
+        usage: owner_usage(state, owner).await?,
+        limits: owner_limits(state, owner).await?,
+    })
+}
+
+/// Usage and limits for domain `name`.
+pub async fn domain_report(state: &AppState, name: &str) -> Result<UsageReport, AppError> {
+    Ok(UsageReport {
+        subject: name.to_string(),
+        usage: domain_usage(state, name).await?,
+        limits: domain_limits(state, name).await?,
+    })
+}
+
+/// Refuse `charge` if it would take its owner or domain over a limit.
+/// `usage` must hold the charged owner and domain.
+///
+/// Only growth is checked: a write that adds no DIDs and does not grow
+/// the log always passes, so a subject already over a lowered limit can
+/// still shrink or rotate keys.
+pub async fn check(
+    state: &AppState,
+    auth: &AuthClaims,
+    charge: &Charge<'_>,
+    usage: &UsageLock,
+) -> Result<(), AppError> {
+    usage.holds_owner(charge.owner)?;
+    if auth.role == Role::Admin {
+        return Ok(());
+    }
+    if charge.new_dids == 0 && charge.new_size <= charge.old_size {
+        return Ok(());
+    }
+
+    let limits = owner_limits(state, charge.owner).await?;
+    if !limits.is_unlimited() {
+        let usage = owner_usage(state, charge.owner).await?;
+        enforce("owner", charge.owner, usage, limits, charge)?;
+    }
+
+    if let Some(name) = charge.domain.filter(|d| !d.is_empty()) {
+        enforce_domain(state, name, charge, usage).await?;
+    }
+
+    debug!(owner = %charge.owner, domain = ?charge.domain, "quota check passed");
+    Ok(())
+}
+
+/// [`check`] against domain `name` alone, for a write whose effect on the
+/// domain differs from its effect on the owner (an in-place move).
+pub async fn check_domain(
+    state: &AppState,
+    auth: &AuthClaims,
+    name: &str,
+    charge: &Charge<'_>,
+    usage: &UsageLock,
+) -> Result<(), AppError> {
+    if auth.role == Role::Admin || (charge.new_dids == 0 && charge.new_size <= charge.old_size) {
+        return Ok(());
+    }
+    enforce_domain(state, name, charge, usage).await
+}
+
+async fn enforce_domain(
+    state: &AppState,
+    name: &str,
+    charge: &Charge<'_>,
+    usage: &UsageLock,
+) -> Result<(), AppError> {
+    let name = name.to_ascii_lowercase();
+    let limits = domain_limits(state, &name).await?;
+    if !limits.is_unlimited() {
+        usage.holds(&name)?;
+        let current = domain_counter(state, &name).await?;
+        enforce("domain", &name, current, limits, charge)?;
+    }
+    Ok(())
+}
+
+/// `scope` ("owner" / "domain") goes into the client-facing message; the
+/// subject itself is only logged, since a DID or domain name could carry
+/// text that changes how [`AppError::quota_kind`] classifies the error.
+fn enforce(
+    scope: &str,
+    subject: &str,
+    usage: Usage,
+    limits: Limits,
+    charge: &Charge<'_>,
+) -> Result<(), AppError> {
+    if let Some(max) = limits.max_did_count
+        && charge.new_dids > 0
+        && usage.did_count.saturating_add(charge.new_dids) > max
+    {
+        warn!(
+            subject,
+            count = usage.did_count,
+            max,
+            "DID count quota exceeded"
+        );
+        return Err(AppError::QuotaExceeded(format!(
+            "{scope} DID count quota reached ({max})"
+        )));
+    }
+    let proposed = usage
+        .total_size
+        .saturating_sub(charge.old_size)
+        .saturating_add(charge.new_size);
+    if let Some(max) = limits.max_total_size
+        && charge.new_size > charge.old_size
+        && proposed > max
+    {
+        warn!(
+            subject,
+            current = usage.total_size,
+            proposed,
+            max,
+            "storage quota exceeded"
+        );
+        return Err(AppError::QuotaExceeded(format!(
+            "{scope} storage quota reached ({max} bytes)"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn charge(new_dids: u64, old_size: u64, new_size: u64) -> Charge<'static> {
+        Charge {
+            owner: "did:example:owner",
+            domain: None,
+            new_dids,
+            old_size,
+            new_size,
+        }
+    }
+
+    #[test]
+    fn count_limit_only_applies_to_new_dids() {
+        let limits = Limits {
+            max_did_count: Some(2),
+            max_total_size: None,
+        };
+        let at_cap = Usage {
+            did_count: 2,
+            total_size: 0,
+        };
+        let err = enforce("owner", "x", at_cap, limits, &charge(1, 0, 10)).unwrap_err();
+        assert!(matches!(err, AppError::QuotaExceeded(_)));
+        assert_eq!(err.didcomm_code(), "e.p.did.quota-exceeded");
+        assert!(enforce("owner", "x", at_cap, limits, &charge(0, 10, 20)).is_ok());
+    }
+
+    #[test]
+    fn storage_limit_counts_the_replaced_log_once() {
+        let limits = Limits {
+            max_did_count: None,
+            max_total_size: Some(100),
+        };
+        let usage = Usage {
+            did_count: 1,
+            total_size: 80,
+        };
+        // 80 - 30 + 50 = 100: at the limit, allowed.
+        assert!(enforce("owner", "x", usage, limits, &charge(0, 30, 50)).is_ok());
+        let err = enforce("owner", "x", usage, limits, &charge(0, 30, 51)).unwrap_err();
+        assert_eq!(err.didcomm_code(), "e.p.did.quota-exceeded");
+        // Shrinking is always allowed, even when already over.
+        let over = Usage {
+            did_count: 1,
+            total_size: 150,
+        };
+        assert!(enforce("owner", "x", over, limits, &charge(0, 60, 40)).is_ok());
+    }
+}
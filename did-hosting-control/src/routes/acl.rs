@@ -22,10 +22,13 @@ use serde::Serialize;
 use tracing::{info, warn};
 
 use crate::acl::{self, AclEntry};
+use crate::audit;
 use crate::auth::AdminAuth;
 use crate::auth::session::now_epoch;
+use crate::consent;
 use crate::did_ops;
 use crate::error::AppError;
+use crate::routes::{api_keys, sessions};
 use crate::server::AppState;
 use did_hosting_common::server::acl::{
     AclEntryResponse, AclListResponse, CreateAclRequest, UpdateAclRequest,
@@ -138,6 +141,25 @@ pub async fn create_acl(
         },
         None => DomainScope::All,
     };
+    // An `acl/grant` consent policy for this role holds the create until
+    // enough other approvers sign off; their decisions go on the audit record.
+    let approvals = consent::require(
+        &state,
+        &auth.0.did,
+        consent::Gate {
+            task: consent::ACL_GRANT,
+            note: format!("Grant the {} role to {did}", req.role),
+            role: Some(req.role.clone()),
+            subject: &did,
+        },
+    )
+    .await?;
+    // Approval can take minutes; someone may have created the entry since.
+    if !approvals.is_empty() && acl::get_acl_entry(&state.acl_ks, &did).await?.is_some() {
+        return Err(AppError::Conflict(format!(
+            "ACL entry already exists for {did}"
+        )));
+    }
     let entry = AclEntry {
         did,
         role: req.role,
@@ -147,7 +169,24 @@ pub async fn create_acl(
         max_did_count: req.max_did_count,
         domains,
     };
-    acl::store_acl_entry(&state.acl_ks, &entry).await?;
+    let mut batch = state.store.batch();
+    acl::stage_acl_entry(&mut batch, &state.acl_ks, &entry)?;
+    audit::with_approvals(approvals, async {
+        audit::stage(
+            &state.store,
+            &mut batch,
+            Some(audit::Actor::from(&auth.0)),
+            audit::Entry {
+                action: "acl.create",
+                subject: &entry.did,
+                before: None,
+                after: audit::digest(&entry),
+            },
+        )
+    })
+    .await?;
+    batch.commit().await?;
+    audit::flush(&state).await;
     info!(caller = %auth.0.did, did = %entry.did, role = %entry.role, "ACL entry created");
     Ok(deprecated(
         StatusCode::CREATED,
@@ -169,6 +208,32 @@ pub async fn update_acl(
         .await?
         .ok_or_else(|| AppError::NotFound(format!("ACL entry not found: {did}")))?;
 
+    // A role change is a grant of the new role — same policy as create.
+    let approvals = match &updates.role {
+        Some(role) if *role != entry.role => {
+            let approvals = consent::require(
+                &state,
+                &auth.0.did,
+                consent::Gate {
+                    task: consent::ACL_GRANT,
+                    note: format!("Change the role of {did} from {} to {role}", entry.role),
+                    role: Some(role.clone()),
+                    subject: &did,
+                },
+            )
+            .await?;
+            // Approval can take minutes; apply the update to the entry as
+            // it is now, not as it was when the request came in.
+            entry = acl::get_acl_entry(&state.acl_ks, &did)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("ACL entry not found: {did}")))?;
+            approvals
+        }
+        _ => Vec::new(),
+    };
+    let before = audit::digest(&entry);
+    let before_role = entry.role.clone();
+
     if let Some(role) = updates.role {
         entry.role = role;
     }
@@ -185,7 +250,27 @@ pub async fn update_acl(
         entry.domains = domains;
     }
 
-    acl::store_acl_entry(&state.acl_ks, &entry).await?;
+    let mut batch = state.store.batch();
+    acl::stage_acl_entry(&mut batch, &state.acl_ks, &entry)?;
+    audit::with_approvals(approvals, async {
+        audit::stage(
+            &state.store,
+            &mut batch,
+            Some(audit::Actor::from(&auth.0)),
+            audit::Entry {
+                action: "acl.update",
+                subject: &entry.did,
+                before,
+                after: audit::digest(&entry),
+            },
+        )
+    })
+    .await?;
+    batch.commit().await?;
+    audit::flush(&state).await;
+    // Tokens carry the role they were minted with; a demotion must not
+    // wait for them to expire.
+    sessions::revoke_on_acl_change(&state, &entry.did, &before_role, Some(&entry.role)).await;
     info!(caller = %auth.0.did, did = %entry.did, role = %entry.role, "ACL entry updated");
     Ok(deprecated(StatusCode::OK, AclEntryResponse::from(entry)))
 }
@@ -209,11 +294,27 @@ pub async fn delete_acl(
     }
 
     // Verify entry exists
-    acl::get_acl_entry(&state.acl_ks, &did)
+    let existing = acl::get_acl_entry(&state.acl_ks, &did)
         .await?
         .ok_or_else(|| AppError::NotFound(format!("ACL entry not found: {did}")))?;
 
-    acl::delete_acl_entry(&state.acl_ks, &did).await?;
+    let mut batch = state.store.batch();
+    acl::stage_acl_delete(&mut batch, &state.acl_ks, &did);
+    audit::stage(
+        &state.store,
+        &mut batch,
+        Some(audit::Actor::from(&auth.0)),
+        audit::Entry {
+            action: "acl.delete",
+            subject: &did,
+            before: audit::digest(&existing),
+            after: None,
+        },
+    )?;
+    batch.commit().await?;
+    audit::flush(&state).await;
+    sessions::revoke_on_acl_change(&state, &did, &existing.role, None).await;
+    api_keys::revoke_on_acl_delete(&state, &did).await;
     info!(caller = %auth.0.did, did = %did, "ACL entry deleted");
     // 204 No Content with no body; still attach deprecation headers.
     let mut resp = StatusCode::NO_CONTENT.into_response();
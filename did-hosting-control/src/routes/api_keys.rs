@@ -0,0 +1,352 @@
+//! Scoped API keys for machine clients (see
+//! [`did_hosting_common::server::auth::api_key`]).
+//!
+//! - `GET /api/control/api-keys` — every key, or one DID's with `?did=`.
+//! - `POST /api/control/api-keys` — mint a key bound to an existing ACL
+//!   entry. The response carries the token; it is never shown again.
+//! - `DELETE /api/control/api-keys/{id}` — revoke a key.
+//!
+//! Admin only. A key cannot mint another key, whatever its tasks: minting
+//! needs a session. An admin mints keys for their own entry or for a
+//! Service entry, never for another person's; a key on an Admin entry
+//! goes through the `acl/grant` consent policy for the Admin role.
+//! Deleting an ACL entry deletes its keys
+//! ([`revoke_on_acl_delete`]).
+
+use axum::Json;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use did_hosting_common::server::domain::normalize_domain_name;
+use did_hosting_common::server::store::KS_API_KEYS;
+use did_hosting_common::server::trust_task::TrustTask;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::acl;
+use crate::audit;
+use crate::auth::AdminAuth;
+use crate::auth::api_key::{self, ApiKey};
+use crate::auth::session::now_epoch;
+use crate::consent;
+use crate::error::AppError;
+use crate::server::AppState;
+
+/// At most this many tasks or domains on one key.
+const MAX_SCOPE_ENTRIES: usize = 64;
+
+#[derive(Debug, Deserialize)]
+pub struct ApiKeyQuery {
+    #[serde(default)]
+    pub did: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyRequest {
+    /// DID of the ACL entry the key acts as.
+    pub did: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Trust-Task type URIs the key may call.
+    pub tasks: Vec<String>,
+    /// Empty (the default): the ACL entry's domain scope.
+    #[serde(default)]
+    pub domains: Vec<String>,
+    /// Lifetime in seconds. Unset: the key lives until revoked.
+    #[serde(default)]
+    pub expires_in: Option<u64>,
+}
+
+/// A key as listed. Never carries the secret hash.
+#[derive(Debug, Serialize)]
+pub struct ApiKeyInfo {
+    pub id: String,
+    pub did: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub tasks: Vec<String>,
+    pub domains: Vec<String>,
+    pub created_at: u64,
+    pub created_by: String,
+    pub expires_at: Option<u64>,
+    pub last_used_at: Option<u64>,
+}
+
+impl From<ApiKey> for ApiKeyInfo {
+    fn from(k: ApiKey) -> Self {
+        Self {
+            id: k.id,
+            did: k.did,
+            label: k.label,
+            tasks: k.tasks,
+            domains: k.domains,
+            created_at: k.created_at,
+            created_by: k.created_by,
+            expires_at: k.expires_at,
+            last_used_at: k.last_used_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiKeyListResponse {
+    pub keys: Vec<ApiKeyInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateApiKeyResponse {
+    #[serde(flatten)]
+    pub key: ApiKeyInfo,
+    /// `Authorization: Bearer` value. Shown once.
+    pub token: String,
+}
+
+/// Canonical task URIs, deduplicated in request order.
+fn validate_tasks(tasks: Vec<String>) -> Result<Vec<String>, AppError> {
+    if tasks.is_empty() {
+        return Err(AppError::Validation(
+            "an API key needs at least one task".into(),
+        ));
+    }
+    if tasks.len() > MAX_SCOPE_ENTRIES {
+        return Err(AppError::Validation(format!(
+            "an API key may name at most {MAX_SCOPE_ENTRIES} tasks"
+        )));
+    }
+    let mut out: Vec<String> = Vec::new();
+    for task in tasks {
+        let task = TrustTask::new(task)?.as_str().to_string();
+        if !out.contains(&task) {
+            out.push(task);
+        }
+    }
+    Ok(out)
+}
+
+/// Normalized domains, each inside the ACL entry's own scope.
+fn validate_domains(domains: Vec<String>, entry: &acl::AclEntry) -> Result<Vec<String>, AppError> {
+    if domains.len() > MAX_SCOPE_ENTRIES {
+        return Err(AppError::Validation(format!(
+            "an API key may name at most {MAX_SCOPE_ENTRIES} domains"
+        )));
+    }
+    let mut out: Vec<String> = Vec::new();
+    for domain in domains {
+        let domain = normalize_domain_name(&domain)?;
+        if !entry.domains.allows(&domain) {
+            return Err(AppError::Validation(format!(
+                "{} is not authorised for domain {domain}",
+                entry.did
+            )));
+        }
+        if !out.contains(&domain) {
+            out.push(domain);
+        }
+    }
+    Ok(out)
+}
+
+/// `GET /api/control/api-keys`
+pub async fn list(
+    auth: AdminAuth,
+    State(state): State<AppState>,
+    Query(query): Query<ApiKeyQuery>,
+) -> Result<Json<ApiKeyListResponse>, AppError> {
+    let keys_ks = state.store.keyspace(KS_API_KEYS)?;
+    let keys = api_key::list_keys(&keys_ks, query.did.as_deref())
+        .await?
+        .into_iter()
+        .map(ApiKeyInfo::from)
+        .collect::<Vec<_>>();
+    info!(caller = %auth.0.did, returned = keys.len(), "API keys listed");
+    Ok(Json(ApiKeyListResponse { keys }))
+}
+
+/// Whom `caller` may mint a key for: itself, or a dedicated Service
+/// entry. A key acts as its entry's DID, so a key on another person's
+/// entry would act (and be audited, and approve consents) as them.
+fn check_mintable(caller: &str, entry: &acl::AclEntry) -> Result<(), AppError> {
+    if entry.did == caller || entry.role == acl::Role::Service {
+        return Ok(());
+    }
+    warn!(caller, target = %entry.did, role = %entry.role, "API key mint rejected: another person's entry");
+    Err(AppError::Forbidden(
+        "API keys can only be minted for your own entry or a service entry".into(),
+    ))
+}
+
+/// `POST /api/control/api-keys`
+pub async fn create(
+    auth: AdminAuth,
+    State(state): State<AppState>,
+    Json(req): Json<CreateApiKeyRequest>,
+) -> Result<(StatusCode, Json<CreateApiKeyResponse>), AppError> {
+    if auth.0.api_key.is_some() {
+        warn!(caller = %auth.0.did, "API key mint rejected: caller is itself an API key");
+        return Err(AppError::Forbidden("API keys cannot mint API keys".into()));
+    }
+    let entry = acl::get_acl_entry(&state.acl_ks, &req.did)
+        .await?
+        .ok_or_else(|| AppError::Validation(format!("no ACL entry for {}", req.did)))?;
+    check_mintable(&auth.0.did, &entry)?;
+    let tasks = validate_tasks(req.tasks)?;
+    let domains = validate_domains(req.domains, &entry)?;
+    let expires_at = match req.expires_in {
+        Some(0) => {
+            return Err(AppError::Validation("expires_in must be positive".into()));
+        }
+        Some(secs) => Some(now_epoch().saturating_add(secs)),
+        None => None,
+    };
+
+    // A key on an Admin entry hands out Admin power without a session, so
+    // it is a role grant for the `acl/grant` consent policy.
+    let approvals = if entry.role == acl::Role::Admin {
+        consent::require(
+            &state,
+            &auth.0.did,
+            consent::Gate {
+                task: consent::ACL_GRANT,
+                note: format!("Mint an API key acting as admin {}", entry.did),
+                role: Some(acl::Role::Admin),
+                subject: &entry.did,
+            },
+        )
+        .await?
+    } else {
+        Vec::new()
+    };
+
+    let (key, token) = ApiKey::mint(
+        entry.did,
+        req.label,
+        tasks,
+        domains,
+        expires_at,
+        auth.0.did.clone(),
+    );
+    let mut batch = state.store.batch();
+    api_key::stage_key(&mut batch, &state.store.keyspace(KS_API_KEYS)?, &key)?;
+    let info = ApiKeyInfo::from(key);
+    audit::with_approvals(approvals, async {
+        audit::stage(
+            &state.store,
+            &mut batch,
+            Some(audit::Actor::from(&auth.0)),
+            audit::Entry {
+                action: "api-key.create",
+                subject: &info.id,
+                before: None,
+                after: audit::digest(&info),
+            },
+        )
+    })
+    .await?;
+    batch.commit().await?;
+    audit::flush(&state).await;
+    info!(caller = %auth.0.did, key_id = %info.id, did = %info.did, "API key minted");
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateApiKeyResponse { key: info, token }),
+    ))
+}
+
+/// `DELETE /api/control/api-keys/{id}`
+pub async fn revoke(
+    auth: AdminAuth,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let mut batch = state.store.batch();
+    let key = api_key::stage_key_delete(&mut batch, &state.store.keyspace(KS_API_KEYS)?, &id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("API key not found: {id}")))?;
+    let info = ApiKeyInfo::from(key);
+    audit::stage(
+        &state.store,
+        &mut batch,
+        Some(audit::Actor::from(&auth.0)),
+        audit::Entry {
+            action: "api-key.revoke",
+            subject: &id,
+            before: audit::digest(&info),
+            after: None,
+        },
+    )?;
+    batch.commit().await?;
+    audit::flush(&state).await;
+    info!(caller = %auth.0.did, key_id = %id, did = %info.did, "API key revoked");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Delete the keys acting as `did` after its ACL entry was deleted, so a
+/// later entry for the same DID does not inherit them.
+///
+/// Runs after the deletion is stored, so a failure is logged rather than
+/// returned; the keys are unusable anyway while the entry is gone.
+pub async fn revoke_on_acl_delete(state: &AppState, did: &str) {
+    let deleted = match state.store.keyspace(KS_API_KEYS) {
+        Ok(keys) => api_key::delete_keys_for_did(&keys, did).await,
+        Err(e) => Err(e),
+    };
+    match deleted {
+        Ok(0) => {}
+        Ok(revoked) => info!(did, revoked, "API keys revoked after ACL delete"),
+        Err(e) => warn!(did, error = %e, "failed to revoke API keys after ACL delete"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use did_hosting_common::server::domain::DomainScope;
+
+    fn entry(domains: DomainScope) -> acl::AclEntry {
+        acl::AclEntry {
+            did: "did:example:ci".into(),
+            role: acl::Role::Owner,
+            label: None,
+            created_at: 0,
+            max_total_size: None,
+            max_did_count: None,
+            domains,
+        }
+    }
+
+    #[test]
+    fn keys_are_minted_for_yourself_or_a_service_entry() {
+        let mut target = entry(DomainScope::All);
+        assert!(check_mintable("did:example:ci", &target).is_ok());
+        assert!(matches!(
+            check_mintable("did:example:admin", &target),
+            Err(AppError::Forbidden(_))
+        ));
+        target.role = acl::Role::Admin;
+        assert!(check_mintable("did:example:admin", &target).is_err());
+        target.role = acl::Role::Service;
+        assert!(check_mintable("did:example:admin", &target).is_ok());
+    }
+
+    #[test]
+    fn tasks_must_be_present_and_well_formed() {
+        assert!(validate_tasks(Vec::new()).is_err());
+        assert!(validate_tasks(vec!["not a uri".into()]).is_err());
+        let log = "https://trusttasks.org/did-hosting/did/log/1.0".to_string();
+        assert_eq!(
+            validate_tasks(vec![log.clone(), log.clone()]).unwrap(),
+            vec![log]
+        );
+    }
+
+    #[test]
+    fn domains_stay_inside_the_entry_scope() {
+        let scoped = entry(DomainScope::Allowed {
+            domains: vec!["a.example".into()],
+        });
+        assert_eq!(
+            validate_domains(vec!["a.example".into()], &scoped).unwrap(),
+            vec!["a.example".to_string()]
+        );
+        assert!(validate_domains(vec!["b.example".into()], &scoped).is_err());
+        assert!(validate_domains(vec!["b.example".into()], &entry(DomainScope::All)).is_ok());
+    }
+}
@@ -0,0 +1,35 @@
+//! `GET /api/control/audit` — Admin only. Reads the tamper-evident audit
+//! log (see [`crate::audit`]), oldest first.
+//!
+//! Query parameters, all optional and conjunctive: `actor` (DID),
+//! `action` (exact, or a prefix ending in `.` such as `did.`), `subject`,
+//! `since` / `until` (epoch seconds, inclusive), `after_seq` (paging
+//! cursor — pass the last `seq` you saw) and `limit` (default 100, max
+//! 1000).
+//!
+//! A page stops at `limit` matches or after [`audit::MAX_SCAN`] records,
+//! and then carries `next_after_seq`, the cursor for the next page.
+//!
+//! The response carries the chain `head`, so a client that pages through
+//! everything can feed the result straight to `did-hosting-control
+//! verify-audit --file`.
+
+use axum::Json;
+use axum::extract::{Query, State};
+use tracing::info;
+
+use crate::audit::{self, AuditPage, Filter};
+use crate::auth::AdminAuth;
+use crate::error::AppError;
+use crate::server::AppState;
+
+/// `GET /api/control/audit`
+pub async fn list(
+    auth: AdminAuth,
+    State(state): State<AppState>,
+    Query(filter): Query<Filter>,
+) -> Result<Json<AuditPage>, AppError> {
+    let page = audit::list(&state.store, &filter).await?;
+    info!(caller = %auth.0.did, returned = page.records.len(), "audit log read");
+    Ok(Json(page))
+}
@@ -3,6 +3,7 @@
 //! These routes match what the UI expects (from `did-hosting-ui/lib/api.ts`).
 
 use crate::auth::{AdminAuth, AuthClaims};
+use crate::did_import::{ImportOffer, ImportSource};
 use crate::did_ops;
 use crate::error::AppError;
 use crate::server::AppState;
@@ -10,7 +11,9 @@ use crate::server_push;
 use axum::Json;
 use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
 use did_hosting_common::did_ops::LogMetadata;
+use did_hosting_common::server::deactivation::deactivation_retention_secs;
 use did_hosting_common::{
     CheckNameResponse, DidListEntry, DidRegisterRequest, DidRegisterResponse, RequestUriResponse,
 };
@@ -339,20 +342,7 @@ pub async fn register_did(
 ) -> Result<Json<DidRegisterResponse>, AppError> {
     // T26: resolve multi-shape body (legacy `did_log` OR new
     // `did_data` + `method`) into `(method, payload_bytes)`.
-    let (method, payload) = req.resolve().map_err(AppError::Validation)?;
-    if method != "webvh" {
-        // Method-aware register paths (did:web etc.) land with T26's
-        // follow-up plumbing through the `DidMethod` trait. For now,
-        // refuse explicitly so callers don't see a webvh-validation
-        // error against a did:web payload.
-        return Err(AppError::Validation(format!(
-            "registration via REST is currently webvh-only; received method = '{method}'. \
-             Use PUT /api/dids/{{mnemonic}} with the appropriate Content-Type once \
-             T26 follow-up wires per-method storage.",
-        )));
-    }
-    let did_log = std::str::from_utf8(&payload)
-        .map_err(|e| AppError::Validation(format!("webvh `did_data` is not valid UTF-8: {e}")))?;
+    let (did_log, did_doc) = did_ops::register_payload(&req)?;
 
     // T34: domain resolution — explicit request → ACL default →
     // system default → reject. The resolved domain is recorded for
@@ -383,7 +373,15 @@ pub async fn register_did(
         "domain resolved for atomic register"
     );
 
-    let result = did_ops::register_did_atomic(&auth, &state, &req.path, did_log, req.force).await?;
+    let result = did_ops::register_did_atomic(
+        &auth,
+        &state,
+        &req.path,
+        &did_log,
+        did_doc.as_deref(),
+        req.force,
+    )
+    .await?;
 
     // Push the (potentially replaced) log to downstream servers so their
     // resolvers see the new content right away. Same as `upload_did`.
@@ -425,6 +423,12 @@ pub struct DidDetailResponse {
     /// (e.g. to offer Resume).
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub agent_names: Vec<did_hosting_common::did_ops::AgentNameEntry>,
+    /// Set once the log ends in a `deactivated: true` entry. The DID
+    /// accepts no further log and cannot be deleted before `deletableAt`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deactivated_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deletable_at: Option<u64>,
 }
 
 pub async fn get_did(
@@ -435,31 +439,33 @@ pub async fn get_did(
     let mnemonic = clean_mnemonic(&mnemonic);
     let (record, log_metadata) = did_ops::get_did_info(&auth, &state, mnemonic).await?;
 
-    Ok(Json(DidDetailResponse {
-        mnemonic: record.mnemonic,
-        created_at: record.created_at,
-        updated_at: record.updated_at,
-        version_count: record.version_count,
-        did_id: record.did_id,
-        owner: record.owner,
-        disabled: record.disabled,
-        log: log_metadata,
-        method: (!record.method.is_empty()).then(|| record.method.clone()),
-        domain: (!record.domain.is_empty()).then(|| record.domain.clone()),
-        agent_names: record.agent_names,
-    }))
+    Ok(Json(detail_response(&state, record, log_metadata)))
 }
 
 // ---------- GET /api/log/{mnemonic} ----------
 
+#[derive(Debug, Default, Deserialize)]
+pub struct LogQuery {
+    /// `?archived=true` lists the entries rollbacks have set aside
+    /// (restorable via `POST /api/undo-rollback/{mnemonic}`) instead of
+    /// the live chain.
+    #[serde(default)]
+    pub archived: bool,
+}
+
 pub async fn get_did_log(
     auth: AuthClaims,
     State(state): State<AppState>,
     Path(mnemonic): Path<String>,
-) -> Result<Json<Vec<did_hosting_common::did_ops::LogEntryInfo>>, AppError> {
+    Query(q): Query<LogQuery>,
+) -> Result<Response, AppError> {
     let mnemonic = clean_mnemonic(&mnemonic);
+    if q.archived {
+        let entries = did_ops::list_archived_entries(&auth, &state, mnemonic).await?;
+        return Ok(Json(entries).into_response());
+    }
     let entries = did_ops::get_did_log(&auth, &state, mnemonic).await?;
-    Ok(Json(entries))
+    Ok(Json(entries).into_response())
 }
 
 // ---------- PUT /api/dids/{mnemonic} ----------
@@ -467,12 +473,13 @@ pub async fn get_did_log(
 /// `PUT /api/dids/{mnemonic}` — publish a new version of a hosted DID.
 ///
 /// T26 content-type discriminator:
-/// - `application/jsonl` (or absent / `application/jsonl+json`) →
-///   webvh (the historical default; body is the did.jsonl text).
+/// - `application/jsonl` (or absent / `application/jsonl+json`) → a
+///   did:webvh log or did:webplus microledger; `application/cesr` → a
+///   did:webs KEL. `publish_did` reads the method off the body and
+///   refuses a change of method once the DID has been published.
 /// - `application/did+json` → did:web (single document upload). Not
-///   yet wired through `publish_did`; returns 501 with a clear
-///   message until the per-method publish path lands.
-/// - Any other content-type → 415 with the same enumeration.
+///   wired through `publish_did`; rejected with a clear message.
+/// - Any other content-type → rejected with the same enumeration.
 pub async fn upload_did(
     auth: AuthClaims,
     State(state): State<AppState>,
@@ -487,25 +494,28 @@ pub async fn upload_did(
         .and_then(|v| v.to_str().ok())
         .map(|s| s.split(';').next().unwrap_or(s).trim().to_ascii_lowercase());
 
-    let method = match content_type.as_deref() {
-        // Default + explicit webvh content types.
-        None | Some("application/jsonl") | Some("application/jsonl+json") | Some("text/plain") => {
-            "webvh"
+    match content_type.as_deref() {
+        // A log of any enabled method: the did:webvh log or the did:webplus
+        // microledger (JSON lines), or the did:webs KEL (CESR). The method
+        // is read off the body.
+        None
+        | Some("application/jsonl")
+        | Some("application/jsonl+json")
+        | Some("application/cesr")
+        | Some("text/plain") => {}
+        Some("application/did+json") | Some("application/json") => {
+            return Err(AppError::Validation(
+                "did:web publish via PUT is not supported; upload a log \
+                 ('application/jsonl' or 'application/cesr')"
+                    .into(),
+            ));
         }
-        Some("application/did+json") | Some("application/json") => "web",
         Some(other) => {
             return Err(AppError::Validation(format!(
                 "unsupported Content-Type '{other}' on PUT /api/dids/{{mnemonic}}; \
-                 use 'application/jsonl' (webvh) or 'application/did+json' (web)",
+                 use 'application/jsonl' (webvh, webplus) or 'application/cesr' (webs)",
             )));
         }
-    };
-
-    if method != "webvh" {
-        return Err(AppError::Validation(format!(
-            "method '{method}' publish via PUT not yet wired; T26 follow-up will \
-             route per-method through `DidMethod::apply_update`",
-        )));
     }
 
     did_ops::publish_did(&auth, &state, mnemonic, &body, dq.domain.as_deref()).await?;
@@ -527,6 +537,22 @@ pub async fn upload_witness(
     Ok(StatusCode::NO_CONTENT)
 }
 
+// ---------- PUT /api/did-document/{mnemonic} ----------
+
+/// `PUT /api/did-document/{mnemonic}` — replace the `did.json` of a
+/// did:webs DID. See [`did_ops::publish_did_document`].
+pub async fn upload_did_document(
+    auth: AuthClaims,
+    State(state): State<AppState>,
+    Path(mnemonic): Path<String>,
+    body: String,
+) -> Result<StatusCode, AppError> {
+    let mnemonic = clean_mnemonic(&mnemonic);
+    did_ops::publish_did_document(&auth, &state, mnemonic, &body).await?;
+    server_push::notify_servers_did(&state, mnemonic.to_string());
+    Ok(StatusCode::NO_CONTENT)
+}
+
 // ---------- DELETE /api/dids/{mnemonic} ----------
 
 pub async fn delete_did(
@@ -609,7 +635,20 @@ pub async fn rollback_did(
 
     server_push::notify_servers_did(&state, mnemonic.to_string());
 
-    Ok(Json(DidDetailResponse {
+    Ok(Json(detail_response(&state, record, log_metadata)))
+}
+
+fn detail_response(
+    state: &AppState,
+    record: did_hosting_common::did_ops::DidRecord,
+    log_metadata: Option<LogMetadata>,
+) -> DidDetailResponse {
+    // An unparseable retention is reported by `delete` itself; here it
+    // just leaves `deletableAt` off.
+    let deletable_at = deactivation_retention_secs(&state.config.hosting)
+        .ok()
+        .and_then(|secs| record.deletable_at(secs));
+    DidDetailResponse {
         mnemonic: record.mnemonic,
         created_at: record.created_at,
         updated_at: record.updated_at,
@@ -621,7 +660,152 @@ pub async fn rollback_did(
         method: (!record.method.is_empty()).then(|| record.method.clone()),
         domain: (!record.domain.is_empty()).then(|| record.domain.clone()),
         agent_names: record.agent_names,
-    }))
+        deactivated_at: record.deactivated_at,
+        deletable_at,
+    }
+}
+
+// ---------- POST /api/undo-rollback/{mnemonic} ----------
+
+#[derive(Debug, Default, Deserialize)]
+pub struct UndoRollbackQuery {
+    /// Archived entry to restore (from `GET /api/log/{mnemonic}?archived=true`).
+    /// Defaults to the most recent.
+    #[serde(default)]
+    pub id: Option<String>,
+}
+
+pub async fn undo_rollback_did(
+    auth: AuthClaims,
+    State(state): State<AppState>,
+    Path(mnemonic): Path<String>,
+    Query(q): Query<UndoRollbackQuery>,
+) -> Result<Json<DidDetailResponse>, AppError> {
+    let mnemonic = clean_mnemonic(&mnemonic);
+    let (record, log_metadata) =
+        did_ops::undo_rollback_did(&auth, &state, mnemonic, q.id.as_deref()).await?;
+
+    server_push::notify_servers_did(&state, mnemonic.to_string());
+
+    Ok(Json(detail_response(&state, record, log_metadata)))
+}
+
+// ---------- POST /api/import ----------
+
+/// Body of `POST /api/import`: exactly one of `sourceUrl` / `didLog`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartImportRequest {
+    /// The DID's current `did.jsonl` URL (https).
+    #[serde(default)]
+    pub source_url: Option<String>,
+    /// The source `did.jsonl`, uploaded directly.
+    #[serde(default)]
+    pub did_log: Option<String>,
+    /// Path to import into; omitted → a fresh mnemonic.
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub force: bool,
+    /// Same T34 resolution chain as `POST /api/dids`.
+    #[serde(default)]
+    pub domain: Option<String>,
+}
+
+/// Verify a DID hosted elsewhere and reserve the slot it will move to.
+/// See [`crate::did_import`] for the two-step flow.
+pub async fn start_import(
+    auth: AuthClaims,
+    State(state): State<AppState>,
+    Json(req): Json<StartImportRequest>,
+) -> Result<(StatusCode, Json<ImportOffer>), AppError> {
+    let source = ImportSource::from_parts(req.source_url.as_deref(), req.did_log.as_deref())?;
+    let acl_scope =
+        match did_hosting_common::server::acl::get_acl_entry(&state.acl_ks, &auth.did).await? {
+            Some(e) => e.domains,
+            None => did_hosting_common::server::domain::DomainScope::All,
+        };
+    let system_default = did_hosting_common::server::domain::get_default_domain(&state.store)
+        .await
+        .ok()
+        .flatten();
+    let resolved_domain = did_hosting_common::server::domain::resolve_request_domain(
+        req.domain.as_deref(),
+        &acl_scope,
+        system_default.as_deref(),
+    )
+    .ok();
+    let offer = did_ops::start_import(
+        &auth,
+        &state,
+        source,
+        req.path.as_deref(),
+        req.force,
+        resolved_domain.as_deref(),
+    )
+    .await?;
+    Ok((StatusCode::CREATED, Json(offer)))
+}
+
+// ---------- GET/POST /api/import/{mnemonic} ----------
+
+pub async fn get_import(
+    auth: AuthClaims,
+    State(state): State<AppState>,
+    Path(mnemonic): Path<String>,
+) -> Result<Json<ImportOffer>, AppError> {
+    let mnemonic = clean_mnemonic(&mnemonic);
+    Ok(Json(did_ops::get_import(&auth, &state, mnemonic).await?))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompleteImportRequest {
+    /// The source log with the signed move entry appended.
+    pub did_log: String,
+}
+
+pub async fn complete_import(
+    auth: AuthClaims,
+    State(state): State<AppState>,
+    Path(mnemonic): Path<String>,
+    Json(req): Json<CompleteImportRequest>,
+) -> Result<Json<DidDetailResponse>, AppError> {
+    let mnemonic = clean_mnemonic(&mnemonic);
+    let (record, log_metadata) =
+        did_ops::complete_import(&auth, &state, mnemonic, &req.did_log).await?;
+
+    server_push::notify_servers_did(&state, mnemonic.to_string());
+
+    Ok(Json(detail_response(&state, record, Some(log_metadata))))
+}
+
+// ---------- POST /api/move/{mnemonic} ----------
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveDidRequest {
+    /// The current log with the signed move entry appended.
+    pub did_log: String,
+}
+
+/// Move a hosted DID to another hosted domain or path. See
+/// [`crate::did_move`].
+pub async fn move_did(
+    auth: AuthClaims,
+    State(state): State<AppState>,
+    Path(mnemonic): Path<String>,
+    Json(req): Json<MoveDidRequest>,
+) -> Result<Json<did_ops::MovedDid>, AppError> {
+    let mnemonic = clean_mnemonic(&mnemonic);
+    let moved = did_ops::move_did(&auth, &state, mnemonic, &req.did_log).await?;
+
+    server_push::notify_servers_did(&state, moved.mnemonic.clone());
+    if moved.mnemonic != mnemonic {
+        server_push::notify_servers_did(&state, mnemonic.to_string());
+    }
+
+    Ok(Json(moved))
 }
 
 // ---------- GET /api/raw/{mnemonic} ----------
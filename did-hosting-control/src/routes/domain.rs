@@ -22,9 +22,12 @@ use axum::http::StatusCode;
 use serde::{Deserialize, Serialize};
 use tracing::{info, warn};
 
+use crate::audit;
 use crate::auth::{AdminAuth, AuthClaims, StepUpAuth};
+use crate::consent;
 use crate::error::AppError;
 use crate::server::AppState;
+use crate::webhooks;
 use did_hosting_common::server::acl;
 use did_hosting_common::server::auth::session::now_epoch;
 use did_hosting_common::server::domain::{
@@ -32,6 +35,7 @@ use did_hosting_common::server::domain::{
     normalize_domain_name,
 };
 use did_hosting_common::server::pending_purge;
+use did_hosting_common::server::store::WriteBatch;
 
 /// Body for both list endpoints. `default` carries the current
 /// default-domain pointer so the UI can highlight it without a second
@@ -181,7 +185,7 @@ pub async fn create_domain_route(
         scheme: req.scheme.unwrap_or(DomainUrlScheme::Https),
         status: DomainStatus::Active,
         created_at: now_epoch(),
-        default_domain: false,
+        default_domain: req.set_as_default,
         branding: req.branding,
         witnesses: req.witnesses,
         watchers: req.watchers,
@@ -190,14 +194,15 @@ pub async fn create_domain_route(
         disabled_at: None,
         purge_at: None,
     };
-    domain::create_domain(&state.store, &entry).await?;
+    let mut batch = state.store.batch();
+    domain::stage_create_domain(&state.store, &mut batch, &entry).await?;
     if req.set_as_default {
-        domain::set_default_domain(&state.store, &canonical).await?;
+        domain::stage_default_pointer(&state.store, &mut batch, &canonical).await?;
     }
-    let stored = domain::get_domain(&state.store, &canonical)
-        .await?
-        .ok_or_else(|| AppError::Internal(format!("domain '{canonical}' missing after create")))?;
-    let (sent, failed) = crate::server_push::fanout_domain_upsert(&state, &stored).await;
+    stage_audit(&state, &mut batch, &auth.0, "domain.create", &entry, None)?;
+    batch.commit().await?;
+    audit::flush(&state).await;
+    let (sent, failed) = crate::server_push::fanout_domain_upsert(&state, &entry).await;
     info!(
         caller = %auth.0.did,
         domain = %canonical,
@@ -206,7 +211,7 @@ pub async fn create_domain_route(
         fanout_failed = failed,
         "domain created"
     );
-    Ok((StatusCode::CREATED, Json(stored)))
+    Ok((StatusCode::CREATED, Json(entry)))
 }
 
 /// `PUT /api/domains/{name}` request body. Subset of `DomainEntry`
@@ -243,6 +248,7 @@ pub async fn update_domain_route(
     let mut entry = domain::get_domain(&state.store, &canonical)
         .await?
         .ok_or_else(|| AppError::NotFound(format!("domain '{canonical}'")))?;
+    let before = audit::digest(&entry);
     if let Some(label) = req.label {
         entry.label = Some(label);
     }
@@ -264,7 +270,11 @@ pub async fn update_domain_route(
     if let Some(wk) = req.well_known_enabled {
         entry.well_known_enabled = wk;
     }
-    domain::update_domain(&state.store, &canonical, &entry).await?;
+    let mut batch = state.store.batch();
+    domain::stage_update_domain(&state.store, &mut batch, &canonical, &entry).await?;
+    stage_audit(&state, &mut batch, &auth.0, "domain.update", &entry, before)?;
+    batch.commit().await?;
+    audit::flush(&state).await;
     let (sent, failed) = crate::server_push::fanout_domain_upsert(&state, &entry).await;
     info!(
         caller = %auth.0.did,
@@ -294,17 +304,33 @@ pub async fn disable_domain_route(
             state.config.hosting.disable_purge_grace
         ))
     })?;
-    domain::disable_domain(
+    let before = domain_digest(&state, &canonical).await?;
+    let mut batch = state.store.batch();
+    let entry = domain::stage_disable_domain(
         &state.store,
+        &mut batch,
         &canonical,
         now_epoch(),
         grace_seconds,
         &auth.0.did,
     )
     .await?;
-    let entry = domain::get_domain(&state.store, &canonical)
-        .await?
-        .ok_or_else(|| AppError::Internal(format!("domain '{canonical}' missing after disable")))?;
+    stage_audit(
+        &state,
+        &mut batch,
+        &auth.0,
+        "domain.disable",
+        &entry,
+        before,
+    )?;
+    webhooks::emit(
+        &state.store,
+        &mut batch,
+        &webhooks::WebhookEvent::for_domain(webhooks::DOMAIN_DISABLED, &canonical),
+    )?;
+    batch.commit().await?;
+    audit::flush(&state).await;
+    webhooks::wake();
     let (sent, failed) = crate::server_push::fanout_domain_upsert(&state, &entry).await;
     info!(
         caller = %auth.0.did,
@@ -325,10 +351,18 @@ pub async fn enable_domain_route(
     Path(name): Path<String>,
 ) -> Result<Json<DomainEntry>, AppError> {
     let canonical = normalize_domain_name(&name)?;
-    domain::enable_domain(&state.store, &canonical).await?;
-    let entry = domain::get_domain(&state.store, &canonical)
-        .await?
-        .ok_or_else(|| AppError::Internal(format!("domain '{canonical}' missing after enable")))?;
+    let before = domain_digest(&state, &canonical).await?;
+    let mut batch = state.store.batch();
+    let entry = domain::stage_enable_domain(&state.store, &mut batch, &canonical).await?;
+    stage_audit(&state, &mut batch, &auth.0, "domain.enable", &entry, before)?;
+    webhooks::emit(
+        &state.store,
+        &mut batch,
+        &webhooks::WebhookEvent::for_domain(webhooks::DOMAIN_ENABLED, &canonical),
+    )?;
+    batch.commit().await?;
+    audit::flush(&state).await;
+    webhooks::wake();
     let (sent, failed) = crate::server_push::fanout_domain_upsert(&state, &entry).await;
     info!(
         caller = %auth.0.did,
@@ -426,6 +460,24 @@ pub async fn delete_domain_route(
         )));
     }
 
+    // A `domain/purge` consent policy holds the delete until enough other
+    // approvers sign off; their decisions go on the audit record.
+    let approvals = consent::require(
+        &state,
+        &auth.0.did,
+        consent::Gate {
+            task: consent::DOMAIN_PURGE,
+            role: None,
+            subject: &canonical,
+            note: if opts.purge_servers {
+                format!("Delete the domain {canonical} and purge it from every server")
+            } else {
+                format!("Delete the domain {canonical}")
+            },
+        },
+    )
+    .await?;
+
     // Optional: fanout MSG_DOMAIN_PURGE to every server instance
     // serving this domain. Best-effort per instance — a failure to
     // enqueue for one server must not block the others or the local
@@ -480,12 +532,34 @@ pub async fn delete_domain_route(
         }
     }
 
-    domain::delete_domain_record(&state.store, &canonical).await?;
-    // Best-effort: any pending_purge row was either consumed by the
-    // sweep or never scheduled (legacy disable before the feature
-    // shipped). Either way, leaving a stale row would be harmless but
-    // ugly in audit logs.
-    let _ = pending_purge::cancel(&state.store, &canonical).await;
+    let mut batch = state.store.batch();
+    domain::stage_delete_domain_record(&state.store, &mut batch, &canonical).await?;
+    // Any pending_purge row was either consumed by the sweep or never
+    // scheduled (legacy disable before the feature shipped). Either way,
+    // leaving a stale row would be harmless but ugly in audit logs.
+    pending_purge::stage_cancel(&state.store, &mut batch, &canonical).await?;
+    audit::with_approvals(approvals, async {
+        audit::stage(
+            &state.store,
+            &mut batch,
+            Some(audit::Actor::from(&auth.0)),
+            audit::Entry {
+                action: "domain.delete",
+                subject: &canonical,
+                before: audit::digest(&entry),
+                after: None,
+            },
+        )
+    })
+    .await?;
+    webhooks::emit(
+        &state.store,
+        &mut batch,
+        &webhooks::WebhookEvent::for_domain(webhooks::DOMAIN_DELETED, &canonical),
+    )?;
+    batch.commit().await?;
+    audit::flush(&state).await;
+    webhooks::wake();
     info!(
         caller = %auth.0.did,
         acr = %auth.0.acr,
@@ -505,12 +579,19 @@ pub async fn set_default_domain_route(
     Path(name): Path<String>,
 ) -> Result<Json<DomainEntry>, AppError> {
     let canonical = normalize_domain_name(&name)?;
-    domain::set_default_domain(&state.store, &canonical).await?;
-    let entry = domain::get_domain(&state.store, &canonical)
-        .await?
-        .ok_or_else(|| {
-            AppError::Internal(format!("domain '{canonical}' missing after set-default"))
-        })?;
+    let before = domain_digest(&state, &canonical).await?;
+    let mut batch = state.store.batch();
+    let entry = domain::stage_set_default_domain(&state.store, &mut batch, &canonical).await?;
+    stage_audit(
+        &state,
+        &mut batch,
+        &auth.0,
+        "domain.set-default",
+        &entry,
+        before,
+    )?;
+    batch.commit().await?;
+    audit::flush(&state).await;
     let (sent, failed) = crate::server_push::fanout_domain_upsert(&state, &entry).await;
     info!(
         caller = %auth.0.did,
@@ -521,3 +602,33 @@ pub async fn set_default_domain_route(
     );
     Ok(Json(entry))
 }
+
+/// Digest of a domain's stored entry, for the audit log.
+async fn domain_digest(state: &AppState, name: &str) -> Result<Option<String>, AppError> {
+    Ok(domain::get_domain(&state.store, name)
+        .await?
+        .and_then(|e| audit::digest(&e)))
+}
+
+/// Stage the audit record for a domain mutation in `batch`, digesting
+/// the entry as the batch leaves it.
+fn stage_audit(
+    state: &AppState,
+    batch: &mut WriteBatch,
+    auth: &AuthClaims,
+    action: &str,
+    entry: &DomainEntry,
+    before: Option<String>,
+) -> Result<(), AppError> {
+    audit::stage(
+        &state.store,
+        batch,
+        Some(audit::Actor::from(auth)),
+        audit::Entry {
+            action,
+            subject: &entry.name,
+            before,
+            after: audit::digest(entry),
+        },
+    )
+}
@@ -22,6 +22,8 @@ use axum::{Json, http::StatusCode};
 use serde::Serialize;
 
 use did_hosting_common::server::auth::extractor::AdminAuth;
+use did_hosting_common::server::identity::IdentityGeneration;
+use did_hosting_common::server::store::WriteBatch;
 
 use crate::error::AppError;
 use crate::server::AppState;
@@ -107,10 +109,24 @@ pub async fn list_generations(
 /// stop using the current key, publish a new DID document; the rotation makes it
 /// superseded, and then this endpoint can retire it.
 pub async fn retire_generation(
-    _auth: AdminAuth,
+    auth: AdminAuth,
     State(state): State<AppState>,
     Path(id): Path<u64>,
 ) -> Result<StatusCode, AppError> {
-    crate::identity_rotation::retire_generation_now(&state, id).await?;
+    let actor = crate::audit::Actor::from(&auth.0);
+    let stage_audit = |batch: &mut WriteBatch, generation: &IdentityGeneration| {
+        crate::audit::stage(
+            &state.store,
+            batch,
+            Some(actor.clone()),
+            crate::audit::Entry {
+                action: "identity.retire",
+                subject: &generation.id.to_string(),
+                before: crate::audit::digest(generation),
+                after: None,
+            },
+        )
+    };
+    crate::identity_rotation::retire_generation_now(&state, id, &stage_audit).await?;
     Ok(StatusCode::NO_CONTENT)
 }
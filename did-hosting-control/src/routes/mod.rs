@@ -1,4 +1,6 @@
 mod acl;
+pub(crate) mod api_keys;
+mod audit;
 mod auth;
 // `pub(crate)` so the DIDComm dispatch table reuses the REST request types
 // and helpers verbatim — the two transports must not grow separate shapes.
@@ -11,14 +13,20 @@ mod passkey;
 mod proxy;
 mod registry;
 pub mod server_info;
+// `pub(crate)` for `revoke_on_acl_change`, which the ACL Trust Task
+// transports call through `crate::audit::AclTaskWatch`.
+pub(crate) mod sessions;
 pub(crate) mod stats_sync;
 pub mod task_consent;
 mod trust_tasks;
+mod usage;
+mod webhooks;
 
-use axum::Router;
 use axum::extract::DefaultBodyLimit;
-use axum::routing::{any, get, post, put};
+use axum::routing::{any, delete, get, post, put};
+use axum::{Extension, Router};
 use did_hosting_common::did_hosting_tasks::*;
+use did_hosting_common::server::auth::api_key::{DomainAware, HandlerChecksTask};
 use did_hosting_common::server::trust_task::TrustTaskRouter;
 
 use crate::server::AppState;
@@ -94,6 +102,24 @@ pub fn router_without_fallback() -> Router<AppState> {
             post(registry::register_service),
             (*TASK_SERVER_REGISTER_0_1).clone(),
         )
+        // Tamper-evident audit log of control-plane mutations.
+        .route_with_task_permissive("/audit", get(audit::list), (*TASK_AUDIT_LIST_1_0).clone())
+        // Scoped API keys for machine clients.
+        .route_with_task_permissive(
+            "/api-keys",
+            get(api_keys::list),
+            (*TASK_API_KEY_LIST_1_0).clone(),
+        )
+        .route_with_task_permissive(
+            "/api-keys",
+            post(api_keys::create),
+            (*TASK_API_KEY_CREATE_1_0).clone(),
+        )
+        .route_with_task_permissive(
+            "/api-keys/{id}",
+            delete(api_keys::revoke),
+            (*TASK_API_KEY_REVOKE_1_0).clone(),
+        )
         .into_router();
 
     // Upload routes with a custom body-size limit (DID log + witness).
@@ -114,11 +140,37 @@ pub fn router_without_fallback() -> Router<AppState> {
             put(did_manage::upload_witness),
             (*TASK_WEBVH_WITNESS_PUBLISH_0_1).clone(),
         )
+        // The `did.json` of a did:webs DID, checked against its KEL and
+        // published as a new version of it.
+        .route_with_task_permissive(
+            "/did-document/{*mnemonic}",
+            put(did_manage::upload_did_document),
+            (*TASK_DID_REGISTER_0_1).clone(),
+        )
         .route_with_task_permissive(
             "/dids/register",
             post(did_manage::register_did),
             (*TASK_DID_REGISTER_0_1).clone(),
         )
+        // Migrate-in of a did:webvh hosted elsewhere. Start carries the
+        // source log (or its URL); complete carries it again with the
+        // signed move entry appended.
+        .route_with_task_permissive(
+            "/import",
+            post(did_manage::start_import),
+            (*TASK_DID_IMPORT_1_0).clone(),
+        )
+        .route_with_task_permissive(
+            "/import/{*mnemonic}",
+            get(did_manage::get_import).post(did_manage::complete_import),
+            (*TASK_DID_IMPORT_COMPLETE_1_0).clone(),
+        )
+        // A move carries the whole log with the signed move entry on the end.
+        .route_with_task_permissive(
+            "/move/{*mnemonic}",
+            post(did_manage::move_did),
+            (*TASK_DID_MOVE_1_0).clone(),
+        )
         // Agent-name mutations carry the new signed did.jsonl in the body, so
         // they share the register/publish body ceiling. All four require the
         // owner (or admin) — plain `AuthClaims`, no step-up.
@@ -141,7 +193,60 @@ pub fn router_without_fallback() -> Router<AppState> {
             (*TASK_AGENT_NAME_REMOVE_0_1).clone(),
         )
         .into_router()
-        .layer(DefaultBodyLimit::max(10 * 1024 * 1024)); // 10 MB
+        .layer(DefaultBodyLimit::max(10 * 1024 * 1024)) // 10 MB
+        .layer(Extension(DomainAware));
+
+    // Per-DID routes. Every handler goes through `did_ops`, which confines
+    // a domain-restricted API key to its domains, so they are marked
+    // `DomainAware`; a restricted key is refused everywhere else.
+    let did_routes: Router<AppState> = TrustTaskRouter::new()
+        .route_with_task_permissive(
+            "/dids",
+            post(did_manage::request_uri).get(did_manage::list_dids),
+            (*TASK_DID_CHECK_NAME_0_1).clone(),
+        )
+        .route_with_task_permissive(
+            "/dids/{*mnemonic}",
+            get(did_manage::get_did).delete(did_manage::delete_did),
+            (*TASK_DID_INFO_0_1).clone(),
+        )
+        .route_with_task_permissive(
+            "/log/{*mnemonic}",
+            get(did_manage::get_did_log),
+            (*TASK_DID_LOG_1_0).clone(),
+        )
+        .route_with_task_permissive(
+            "/owner/{*mnemonic}",
+            put(did_manage::change_owner),
+            (*TASK_DID_CHANGE_OWNER_0_1).clone(),
+        )
+        .route_with_task_permissive(
+            "/disable/{*mnemonic}",
+            put(did_manage::disable_did),
+            (*TASK_DID_SET_STATE_0_1).clone(),
+        )
+        .route_with_task_permissive(
+            "/enable/{*mnemonic}",
+            put(did_manage::enable_did),
+            (*TASK_DID_SET_STATE_0_1).clone(),
+        )
+        .route_with_task_permissive(
+            "/rollback/{*mnemonic}",
+            post(did_manage::rollback_did),
+            (*TASK_DID_ROLLBACK_0_1).clone(),
+        )
+        .route_with_task_permissive(
+            "/undo-rollback/{*mnemonic}",
+            post(did_manage::undo_rollback_did),
+            (*TASK_DID_UNDO_ROLLBACK_1_0).clone(),
+        )
+        .route_with_task_permissive(
+            "/raw/{*mnemonic}",
+            get(did_manage::get_raw_log),
+            (*TASK_DID_RAW_LOG_1_0).clone(),
+        )
+        .into_router()
+        .layer(Extension(DomainAware));
 
     let api: Router<AppState> = TrustTaskRouter::new()
         // Auth (DIDComm challenge-response)
@@ -160,15 +265,33 @@ pub fn router_without_fallback() -> Router<AppState> {
             post(auth::refresh),
             (*TASK_AUTH_REFRESH_0_1).clone(),
         )
+        // Session inventory and revocation: logout, revoke-by-DID.
+        .route_with_task_permissive(
+            "/auth/sessions",
+            get(sessions::list).delete(sessions::revoke_all),
+            (*TASK_AUTH_SESSION_LIST_1_0).clone(),
+        )
+        .route_with_task_permissive(
+            "/auth/sessions/{id}",
+            delete(sessions::revoke),
+            (*TASK_AUTH_SESSION_REVOKE_1_0).clone(),
+        )
         // RP-initiated wallet consent (admin-only). Sends a
         // `task-consent/request/0.1` document to a holder DID over DIDComm
-        // and waits for the wallet's signed `task-consent/decision/0.1`.
+        // and answers 202 with the stored request's id; the wallet's signed
+        // `task-consent/decision/0.1` is recorded against it.
         // (Replaces the retired `confirm/{request,response}/0.1` flow.)
         .route_with_task_permissive(
             "/task-consent/request",
             post(task_consent::request),
             (*TASK_CONSENT_REQUEST_0_1).clone(),
         )
+        // Where a consent request (including a gated action's) stands.
+        .route_with_task_permissive(
+            "/task-consent/{id}",
+            get(task_consent::status),
+            (*TASK_CONSENT_STATUS_1_0).clone(),
+        )
         // Passkey (WebAuthn)
         .route_with_task_permissive(
             "/auth/passkey/enroll/start",
@@ -305,46 +428,6 @@ pub fn router_without_fallback() -> Router<AppState> {
             post(did_manage::resolve_agent_names),
             (*TASK_AGENT_NAME_RESOLVE_1_0).clone(),
         )
-        .route_with_task_permissive(
-            "/dids",
-            post(did_manage::request_uri).get(did_manage::list_dids),
-            (*TASK_DID_CHECK_NAME_0_1).clone(),
-        )
-        .route_with_task_permissive(
-            "/dids/{*mnemonic}",
-            get(did_manage::get_did).delete(did_manage::delete_did),
-            (*TASK_DID_INFO_0_1).clone(),
-        )
-        .route_with_task_permissive(
-            "/log/{*mnemonic}",
-            get(did_manage::get_did_log),
-            (*TASK_DID_LOG_1_0).clone(),
-        )
-        .route_with_task_permissive(
-            "/owner/{*mnemonic}",
-            put(did_manage::change_owner),
-            (*TASK_DID_CHANGE_OWNER_0_1).clone(),
-        )
-        .route_with_task_permissive(
-            "/disable/{*mnemonic}",
-            put(did_manage::disable_did),
-            (*TASK_DID_SET_STATE_0_1).clone(),
-        )
-        .route_with_task_permissive(
-            "/enable/{*mnemonic}",
-            put(did_manage::enable_did),
-            (*TASK_DID_SET_STATE_0_1).clone(),
-        )
-        .route_with_task_permissive(
-            "/rollback/{*mnemonic}",
-            post(did_manage::rollback_did),
-            (*TASK_DID_ROLLBACK_0_1).clone(),
-        )
-        .route_with_task_permissive(
-            "/raw/{*mnemonic}",
-            get(did_manage::get_raw_log),
-            (*TASK_DID_RAW_LOG_1_0).clone(),
-        )
         // Stats & time-series
         .route_with_task_permissive(
             "/stats",
@@ -376,6 +459,35 @@ pub fn router_without_fallback() -> Router<AppState> {
             get(did_manage::get_config),
             (*TASK_CONFIG_1_0).clone(),
         )
+        // Quota usage against ACL / domain limits.
+        .route_with_task_permissive(
+            "/usage",
+            get(usage::my_usage),
+            (*TASK_USAGE_OWNER_1_0).clone(),
+        )
+        .route_with_task_permissive(
+            "/usage/owners/{did}",
+            get(usage::owner_usage),
+            (*TASK_USAGE_OWNER_1_0).clone(),
+        )
+        .route_with_task_permissive(
+            "/usage/domains/{name}",
+            get(usage::domain_usage),
+            (*TASK_USAGE_DOMAIN_1_0).clone(),
+        )
+        // Outbound webhooks for DID / domain lifecycle events.
+        .route_with_task_permissive(
+            "/webhooks",
+            get(webhooks::list).post(webhooks::create),
+            (*TASK_WEBHOOK_LIST_1_0).clone(),
+        )
+        .route_with_task_permissive(
+            "/webhooks/{id}",
+            get(webhooks::get)
+                .put(webhooks::update)
+                .delete(webhooks::delete),
+            (*TASK_WEBHOOK_UPDATE_1_0).clone(),
+        )
         // Exempt: Trust Tasks transport (v0.7.0+). The envelope's
         // `type` URI is the task identifier; the legacy
         // `Trust-Task:` header isn't carried on this surface.
@@ -386,10 +498,15 @@ pub fn router_without_fallback() -> Router<AppState> {
         // gives 64 KB. Caps an authenticated-Owner DoS class where a
         // compromised credential drives parsing of multi-MB documents
         // before the handler-level Admin check rejects.
+        //
+        // One route, many tasks: the dispatcher checks an API key's task
+        // scope against the document's `type` itself.
         .route_exempt(
             "/trust-tasks",
-            post(trust_tasks::dispatch_trust_task)
-                .layer(DefaultBodyLimit::max(TRUST_TASKS_BODY_LIMIT_BYTES)),
+            post(trust_tasks::dispatch_trust_task).layer((
+                DefaultBodyLimit::max(TRUST_TASKS_BODY_LIMIT_BYTES),
+                Extension(HandlerChecksTask),
+            )),
         )
         // Exempt: DIDComm envelope (inner message type is the real
         // task identifier).
@@ -421,8 +538,14 @@ pub fn router_without_fallback() -> Router<AppState> {
             "/identity/generations/{id}/retire",
             post(identity::retire_generation),
         )
+        .merge(did_routes)
         // Merge upload routes (body-limited).
-        .merge(upload_routes);
+        .merge(upload_routes)
+        // Mutations record the `Trust-Task` header they ran under in the
+        // audit log (see `crate::audit`).
+        .layer(axum::middleware::from_fn(
+            crate::audit::trust_task_header_scope,
+        ));
 
     #[allow(unused_mut)]
     let mut router = Router::new()
@@ -440,6 +440,8 @@ async fn compute_did_sync_updates(
     };
 
     let mut updates = Vec::new();
+    // Signs rolled-back logs (see `log_archive::rollback_proof`).
+    let signer = crate::audit::signing_key(state).map(|(_, key)| key);
 
     for (_key, value) in raw {
         let record: DidRecord = match serde_json::from_slice(&value) {
@@ -486,7 +488,27 @@ async fn compute_did_sync_updates(
             _ => None,
         };
 
+        let did_document = match state
+            .dids_ks
+            .get_raw(did_ops::content_did_doc_key(&record.mnemonic))
+            .await
+        {
+            Ok(Some(bytes)) => String::from_utf8(bytes).ok(),
+            _ => None,
+        };
+
         let did_id = record.did_id.unwrap_or_default();
+        let previous_did_id =
+            crate::did_move::previous_did_id(&state.dids_ks, &record.mnemonic).await;
+        let rolled_back =
+            crate::log_archive::rolled_back(&state.dids_ks, &record.mnemonic, &log_content)
+                .await
+                .unwrap_or_default();
+        let rollback_proof = if rolled_back.is_empty() {
+            None
+        } else {
+            crate::log_archive::rollback_proof(signer.as_ref(), &did_id, &log_content, &rolled_back)
+        };
 
         updates.push(DidSyncUpdate {
             mnemonic: record.mnemonic,
@@ -494,6 +516,10 @@ async fn compute_did_sync_updates(
             log_content,
             witness_content,
             version_count: record.version_count,
+            previous_did_id,
+            rolled_back,
+            rollback_proof,
+            did_document,
         });
     }
 
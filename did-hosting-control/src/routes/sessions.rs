@@ -0,0 +1,235 @@
+//! Session inventory and revocation.
+//!
+//! - `GET /api/auth/sessions` — the caller's sessions. Admins see every
+//!   session, or one DID's with `?did=`.
+//! - `DELETE /api/auth/sessions/{id}` — revoke one session: logout when it
+//!   is the caller's own, a kill when an admin revokes someone else's.
+//! - `DELETE /api/auth/sessions` — revoke every session of `?did=`
+//!   (default: the caller, i.e. "log out everywhere"). Only admins may name
+//!   another DID.
+//!
+//! Revocation deletes the session and its refresh index and puts its access
+//! token on the denylist the auth extractor checks, so the token stops
+//! working immediately rather than at expiry. Removing an ACL entry, or
+//! changing its role to anything but Admin, revokes the subject's sessions
+//! the same way ([`revoke_on_acl_change`]).
+
+use axum::Json;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::acl::Role;
+use crate::audit;
+use crate::auth::AuthClaims;
+use crate::auth::session::{
+    self, Session, acl_change_revokes_sessions, list_sessions, revoke_sessions_for_did,
+};
+use crate::error::AppError;
+use crate::server::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct SessionQuery {
+    #[serde(default)]
+    pub did: Option<String>,
+}
+
+/// A session as listed. Never carries the refresh token.
+#[derive(Debug, Serialize)]
+pub struct SessionInfo {
+    pub session_id: String,
+    pub did: String,
+    pub created_at: u64,
+    pub last_seen: u64,
+    /// When the refresh token lapses; the session cannot outlive it.
+    pub expires_at: Option<u64>,
+    pub amr: Vec<String>,
+    pub acr: String,
+    /// Whether this is the session the request was made with.
+    pub current: bool,
+}
+
+impl SessionInfo {
+    fn new(session: Session, caller_session: &str) -> Self {
+        Self {
+            current: session.session_id == caller_session,
+            session_id: session.session_id,
+            did: session.did,
+            created_at: session.created_at,
+            last_seen: session.last_seen,
+            expires_at: session.refresh_expires_at,
+            amr: session.amr,
+            acr: session.acr,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionListResponse {
+    pub sessions: Vec<SessionInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RevokeSessionsResponse {
+    pub did: String,
+    pub revoked: usize,
+}
+
+/// The DID a request is about: `did` if given, else the caller. Only
+/// admins may name someone else.
+fn target_did(auth: &AuthClaims, did: Option<String>) -> Result<String, AppError> {
+    match did {
+        Some(did) if did != auth.did && auth.role != Role::Admin => {
+            warn!(caller = %auth.did, target = %did, "session access denied: not the caller's DID");
+            Err(AppError::Forbidden(
+                "only admins can manage another DID's sessions".into(),
+            ))
+        }
+        Some(did) => Ok(did),
+        None => Ok(auth.did.clone()),
+    }
+}
+
+/// `GET /api/auth/sessions`
+pub async fn list(
+    auth: AuthClaims,
+    State(state): State<AppState>,
+    Query(query): Query<SessionQuery>,
+) -> Result<Json<SessionListResponse>, AppError> {
+    // Admins without a filter see everyone; anyone else sees one DID.
+    let did = match query.did {
+        None if auth.role == Role::Admin => None,
+        did => Some(target_did(&auth, did)?),
+    };
+    let sessions = list_sessions(&state.sessions_ks, did.as_deref())
+        .await?
+        .into_iter()
+        .map(|s| SessionInfo::new(s, &auth.session_id))
+        .collect::<Vec<_>>();
+    info!(caller = %auth.did, returned = sessions.len(), "sessions listed");
+    Ok(Json(SessionListResponse { sessions }))
+}
+
+/// `DELETE /api/auth/sessions/{id}`
+pub async fn revoke(
+    auth: AuthClaims,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    // Someone else's session is reported as missing to a non-admin, so
+    // session ids cannot be probed.
+    let not_found = || AppError::NotFound(format!("session not found: {id}"));
+    let session = session::get_session(&state.sessions_ks, &id)
+        .await?
+        .ok_or_else(not_found)?;
+    if session.did != auth.did && auth.role != Role::Admin {
+        warn!(caller = %auth.did, session_id = %id, "session revoke denied: not the caller's session");
+        return Err(not_found());
+    }
+
+    let did = session.did.clone();
+    let before = audit::digest(&SessionInfo::new(session, &auth.session_id));
+    let mut batch = state.store.batch();
+    session::stage_revoke_session(&mut batch, &state.sessions_ks, &id).await?;
+    audit::stage(
+        &state.store,
+        &mut batch,
+        Some(audit::Actor::from(&auth)),
+        audit::Entry {
+            action: "session.revoke",
+            subject: &did,
+            before,
+            after: None,
+        },
+    )?;
+    batch.commit().await?;
+    audit::flush(&state).await;
+    info!(caller = %auth.did, did = %did, session_id = %id, "session revoked");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `DELETE /api/auth/sessions`
+pub async fn revoke_all(
+    auth: AuthClaims,
+    State(state): State<AppState>,
+    Query(query): Query<SessionQuery>,
+) -> Result<Json<RevokeSessionsResponse>, AppError> {
+    let did = target_did(&auth, query.did)?;
+    let mut batch = state.store.batch();
+    let revoked =
+        session::stage_revoke_sessions_for_did(&mut batch, &state.sessions_ks, &did).await?;
+    audit::stage(
+        &state.store,
+        &mut batch,
+        Some(audit::Actor::from(&auth)),
+        audit::Entry {
+            action: "session.revoke-all",
+            subject: &did,
+            before: None,
+            after: None,
+        },
+    )?;
+    batch.commit().await?;
+    audit::flush(&state).await;
+    info!(caller = %auth.did, did = %did, revoked, "sessions revoked");
+    Ok(Json(RevokeSessionsResponse { did, revoked }))
+}
+
+/// Revoke `did`'s sessions after its ACL entry went from `before` to
+/// `after` (`None`: deleted), if the change calls for it
+/// ([`acl_change_revokes_sessions`]).
+///
+/// Runs after the ACL change is stored, so a failure is logged rather than
+/// returned: the change stands, and the sessions lapse on their own.
+pub async fn revoke_on_acl_change(
+    state: &AppState,
+    did: &str,
+    before: &Role,
+    after: Option<&Role>,
+) {
+    if !acl_change_revokes_sessions(before, after) {
+        return;
+    }
+    match revoke_sessions_for_did(&state.sessions_ks, did).await {
+        Ok(revoked) => info!(did, revoked, "sessions revoked after ACL change"),
+        Err(e) => warn!(did, error = %e, "failed to revoke sessions after ACL change"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims(did: &str, role: Role) -> AuthClaims {
+        AuthClaims {
+            did: did.into(),
+            role,
+            session_id: "s-1".into(),
+            session_pubkey_b58btc: None,
+            amr: vec!["did".into()],
+            acr: "aal1".into(),
+            api_key: None,
+        }
+    }
+
+    #[test]
+    fn only_admins_name_another_did() {
+        let owner = claims("did:example:owner", Role::Owner);
+        assert_eq!(target_did(&owner, None).unwrap(), "did:example:owner");
+        assert_eq!(
+            target_did(&owner, Some("did:example:owner".into())).unwrap(),
+            "did:example:owner"
+        );
+        assert!(matches!(
+            target_did(&owner, Some("did:example:other".into())),
+            Err(AppError::Forbidden(_))
+        ));
+
+        let admin = claims("did:example:admin", Role::Admin);
+        assert_eq!(
+            target_did(&admin, Some("did:example:other".into())).unwrap(),
+            "did:example:other"
+        );
+    }
+}
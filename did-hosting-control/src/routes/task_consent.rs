@@ -3,11 +3,12 @@
 //! The control plane (Relying Party / executor) asks a wallet to consent
 //! to a sensitive admin action: it generates a random `challenge`, sends
 //! a `task-consent/request/0.1` Trust Task document over DIDComm to the
-//! holder DID (authcrypt + forward via the holder's mediator), and parks
-//! the REST request on a `oneshot` channel until the wallet authcrypts a
-//! signed `task-consent/decision/0.1` back. Correlation is by
-//! `challenge`; the inbound decision handler (see
-//! [`crate::messaging::handle_consent_decision`]) resolves the wait.
+//! holder DID (authcrypt + forward via the holder's mediator), stores the
+//! pending request ([`crate::approvals`]), and answers `202` with its id.
+//! The wallet authcrypts a signed `task-consent/decision/0.1` back;
+//! correlation is by `challenge`, and the inbound decision handler (see
+//! [`crate::messaging::handle_consent_decision`]) records it on whichever
+//! replica receives it. `GET /api/task-consent/{id}` reports the outcome.
 //!
 //! This replaced the retired `confirm/{request,response}/0.1` pair
 //! (registry supersededBy: `task-consent/*`): a confirm is a
@@ -15,14 +16,22 @@
 //! requester's display text carried in the explicitly-untrusted `note`
 //! field.
 //!
+//! The same round trip serves multi-party approval: [`solicit`] sends one
+//! request per approver under a shared `challenge`, and the stored approval
+//! collects decisions until `minApprovals` distinct approvers have
+//! approved, one has denied, or the window lapses. [`crate::consent`]
+//! drives it from the policy table; `POST /task-consent/request` is the
+//! single-holder case.
+//!
 //! ## Wire contract (must match the wallet implementation)
 //!
 //! - **Request** (RP → wallet): DIDComm message
 //!   `type = "https://trusttasks.org/spec/task-consent/request/0.1"`,
-//!   `to = [holder_did]`, body = a full Trust Task document whose
-//!   payload carries `challenge`, `taskType`, `payloadDigest`,
-//!   `effects: []`, `note` (the admin's action text, verbatim),
-//!   `minApprovals: 1` and `expiresAt`.
+//!   `to = [approver_did]` (one message per approver), body = a full
+//!   Trust Task document whose payload carries `challenge`, `taskType`,
+//!   `payloadDigest`, `effects: []`, `note` (the admin's action text,
+//!   verbatim), `approverSet`, `minApprovals`, `excludeRequester` and
+//!   `expiresAt`.
 //! - **Decision** (wallet → RP): inbound DIDComm message
 //!   `type = "https://trusttasks.org/spec/task-consent/decision/0.1"`,
 //!   authcrypt-sender = the holder DID, body = a Trust Task document
@@ -34,8 +43,8 @@
 //! `eddsa-jcs-2022` proof over the holder's key — the proof, not the
 //! transport session, is the authorization (per the task-consent spec).
 //! The authcrypt envelope additionally binds the sender: a decision is
-//! only honoured if its authcrypt sender equals the holder DID the
-//! request was sent to AND the proof verifies against that same DID.
+//! only honoured if its authcrypt sender is one of the DIDs the request
+//! was sent to AND the proof verifies against that same DID.
 //! The *request* leg carries the spec's REQUIRED Data Integrity proof,
 //! signed by the control plane's own DID key (`eddsa-jcs-2022`,
 //! `proofPurpose: assertionMethod`, `issuer` == the DID of
@@ -48,26 +57,51 @@ use std::time::Duration;
 
 use affinidi_messaging_didcomm::Message;
 use axum::Json;
-use axum::extract::State;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use sha2::{Digest, Sha256};
 use tracing::{info, warn};
 
-use crate::auth::AdminAuth;
+use crate::acl::Role;
+use crate::approvals::{self, Approval};
 use crate::auth::session::now_epoch;
+use crate::auth::{AdminAuth, AuthClaims};
+use crate::consent::ConsentOutcome;
 use crate::error::AppError;
-use crate::server::{AppState, PendingConfirm};
+use crate::server::AppState;
 
 /// DIDComm listener id the control plane registers (see
 /// `server::start_didcomm_service`). Outbound `send_message` calls are
 /// scoped to this listener.
 const CONTROL_LISTENER_ID: &str = "control";
 
-/// How long the REST request waits for the wallet's decision. Also the
-/// window advertised to the wallet as the request's `expiresAt`.
+/// How long the holder has to answer a `POST /task-consent/request`
+/// prompt; advertised to the wallet as the request's `expiresAt`.
 const CONSENT_TIMEOUT: Duration = Duration::from_secs(60);
 
+/// The approval rule a request advertises to its approvers.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ApprovalRule<'a> {
+    /// `approverSet`: which set the recipient was asked as a member of.
+    pub approver_set: &'a str,
+    /// `minApprovals`.
+    pub min_approvals: usize,
+    /// `excludeRequester`.
+    pub exclude_requester: bool,
+}
+
+impl ApprovalRule<'static> {
+    /// A single holder asked on their own — the `POST /task-consent/request`
+    /// shape.
+    pub(crate) const HOLDER: Self = Self {
+        approver_set: "holder",
+        min_approvals: 1,
+        exclude_requester: false,
+    };
+}
+
 /// `note` is capped at 500 characters by the `task-consent/request/0.1`
 /// payload schema. The executor MAY truncate, but a truncated consent
 /// prompt is worse than a rejected request — we validate instead.
@@ -88,9 +122,22 @@ pub struct ConsentRequest {
 }
 
 #[derive(Debug, Serialize)]
-pub struct ConsentResult {
-    /// `true` if the user approved, `false` if denied.
-    pub approved: bool,
+pub struct ConsentRequested {
+    /// Poll `GET /api/task-consent/{approval_id}` for the outcome.
+    pub approval_id: String,
+}
+
+/// `GET /api/task-consent/{id}`.
+#[derive(Debug, Serialize)]
+pub struct ConsentStatus {
+    pub approval_id: String,
+    /// `pending`, `granted`, `denied` or `expired`.
+    pub status: &'static str,
+    pub approvals: usize,
+    pub min_approvals: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub denied_by: Option<String>,
+    pub expires_at: u64,
 }
 
 /// The salted wire digest the wallet echoes back (`payloadDigest`).
@@ -177,6 +224,7 @@ pub(crate) async fn build_signed_request_document(
     signing_secret: &affinidi_tdk::secrets_resolver::secrets::Secret,
     holder_did: &str,
     requester_did: &str,
+    rule: ApprovalRule<'_>,
     action: &str,
     task_type: &str,
     challenge: &str,
@@ -210,9 +258,9 @@ pub(crate) async fn build_signed_request_document(
             ],
             "requester": requester_did,
             "note": action,
-            "approverSet": "holder",
-            "minApprovals": 1,
-            "excludeRequester": false,
+            "approverSet": rule.approver_set,
+            "minApprovals": rule.min_approvals,
+            "excludeRequester": rule.exclude_requester,
             "expiresAt": expires_at,
         },
     });
@@ -222,14 +270,14 @@ pub(crate) async fn build_signed_request_document(
 /// POST /api/task-consent/request — admin-only.
 ///
 /// Generates a random hex `challenge`, sends a signed
-/// `task-consent/request/0.1` document to `holder_did`, then waits (up
-/// to 60s) for the wallet's signed `task-consent/decision/0.1`. Returns
-/// `{ "approved": bool }`.
+/// `task-consent/request/0.1` document to `holder_did`, and answers `202`
+/// with `{ "approval_id" }`. The wallet has 60s to answer; poll
+/// [`status`] for the outcome.
 pub async fn request(
     auth: AdminAuth,
     State(state): State<AppState>,
     Json(req): Json<ConsentRequest>,
-) -> Result<Json<ConsentResult>, AppError> {
+) -> Result<(StatusCode, Json<ConsentRequested>), AppError> {
     if req.holder_did.is_empty() {
         return Err(AppError::Validation("holder_did must not be empty".into()));
     }
@@ -238,15 +286,90 @@ pub async fn request(
             "holder_did exceeds maximum length".into(),
         ));
     }
-    if req.action.is_empty() {
+    validate_action(&req.action)?;
+
+    // The pending "task" is the prose-described admin action itself —
+    // this service cannot dry-run it, so the payload is the action text.
+    let approval_id = solicit(
+        &state,
+        &auth.0.did,
+        std::slice::from_ref(&req.holder_did),
+        ApprovalRule::HOLDER,
+        &req.action,
+        &json!({ "action": req.action }),
+        None,
+        CONSENT_TIMEOUT,
+    )
+    .await?;
+    Ok((StatusCode::ACCEPTED, Json(ConsentRequested { approval_id })))
+}
+
+/// GET /api/task-consent/{id} — the requester, or an admin.
+///
+/// Where a consent request stands. Reading it spends nothing; a gated
+/// action is run by repeating it once this says `granted`.
+pub async fn status(
+    auth: AuthClaims,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<ConsentStatus>, AppError> {
+    let approval = approvals::get(&state.store, &id)
+        .await?
+        .filter(|a| a.requester == auth.did || auth.role == Role::Admin)
+        .ok_or_else(|| AppError::NotFound(format!("consent request not found: {id}")))?;
+    let (status, approvals, denied_by) =
+        match approvals::status(&state.store, &approval, now_epoch()).await? {
+            ConsentOutcome::Pending { approvals } => ("pending", approvals, None),
+            ConsentOutcome::Granted(decisions) => ("granted", decisions.len(), None),
+            ConsentOutcome::Denied { by } => ("denied", 0, Some(by)),
+            ConsentOutcome::Expired { approvals } => ("expired", approvals, None),
+        };
+    Ok(Json(ConsentStatus {
+        approval_id: approval.id,
+        status,
+        approvals,
+        min_approvals: approval.min_approvals,
+        denied_by,
+        expires_at: approval.expires_at,
+    }))
+}
+
+/// Reject an action text the request's `note` cannot carry.
+pub(crate) fn validate_action(action: &str) -> Result<(), AppError> {
+    if action.is_empty() {
         return Err(AppError::Validation("action must not be empty".into()));
     }
-    if req.action.chars().count() > MAX_ACTION_CHARS {
+    if action.chars().count() > MAX_ACTION_CHARS {
         return Err(AppError::Validation(format!(
             "action exceeds the {MAX_ACTION_CHARS}-character note limit"
         )));
     }
+    Ok(())
+}
 
+/// Ask every DID in `approvers` to consent to `action` within `timeout`,
+/// and return the id of the stored [`Approval`].
+///
+/// One signed `task-consent/request/0.1` goes to each approver, all under
+/// the same `challenge` and `payloadDigest`; the digest binds
+/// `pending_payload` under the admin-action task type. Decisions are
+/// recorded by [`crate::messaging`] against the stored approval, and
+/// [`approvals::status`] reads the outcome. `gate` ties the approval to the
+/// action [`crate::consent::require`] will run once it is granted.
+///
+/// Fails up front, leaving nothing stored, when fewer approvers could be
+/// reached than the rule needs — such a request could never be granted.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn solicit(
+    state: &AppState,
+    requester_did: &str,
+    approvers: &[String],
+    rule: ApprovalRule<'_>,
+    action: &str,
+    pending_payload: &Value,
+    gate: Option<String>,
+    timeout: Duration,
+) -> Result<String, AppError> {
     // The control DID is the authcrypt sender of the outbound request.
     let control_did = state
         .config
@@ -265,7 +388,7 @@ pub async fn request(
 
     // The control DID's assertion key — resolved before anything is
     // registered, so a missing key fails the request cleanly.
-    let signing_secret = crate::signing::control_assertion_secret(&state, &control_did)?;
+    let signing_secret = crate::signing::control_assertion_secret(state, &control_did)?;
 
     // Fresh 16-byte (128-bit) challenge, hex-encoded — the spec's
     // entropy floor, and the digest salt.
@@ -274,100 +397,94 @@ pub async fn request(
         .map(|b| format!("{b:02x}"))
         .collect::<String>();
 
-    // The pending "task" is the prose-described admin action itself —
-    // this service cannot dry-run it, so the payload is the action text
-    // and the type is the service-local admin-action identifier. The
-    // digest still binds the wallet's decision to exactly this
-    // (type, payload, challenge) triple.
+    // The type is the service-local admin-action identifier. The digest
+    // binds every approver's decision to exactly this (type, payload,
+    // challenge) triple.
     let task_type = did_hosting_common::did_hosting_tasks::TASK_ADMIN_ACTION_1_0.as_str();
-    let pending_payload = json!({ "action": req.action });
-    let digest = wire_digest(task_type, &pending_payload, &challenge)?;
+    let digest = wire_digest(task_type, pending_payload, &challenge)?;
 
     let now = chrono::Utc::now();
-    let expires_at = (now + chrono::Duration::from_std(CONSENT_TIMEOUT).expect("static"))
-        .to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let issued_at = now.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let expires_at = (now
+        + chrono::Duration::from_std(timeout)
+            .map_err(|e| AppError::Internal(format!("consent timeout out of range: {e}")))?)
+    .to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
     let request_type = did_hosting_common::did_hosting_tasks::TASK_CONSENT_REQUEST_0_1.as_str();
 
-    // Build + sign the document before registering the pending entry, so
-    // a signing failure leaves nothing behind to clean up.
-    let document = build_signed_request_document(
-        &control_did,
-        &signing_secret,
-        &req.holder_did,
-        &auth.0.did,
-        &req.action,
-        task_type,
-        &challenge,
-        &digest,
-        &now.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
-        &expires_at,
-    )
-    .await?;
+    // Build + sign every document before storing the approval, so a
+    // signing failure leaves nothing behind to clean up.
+    let mut messages = Vec::with_capacity(approvers.len());
+    for approver in approvers {
+        let document = build_signed_request_document(
+            &control_did,
+            &signing_secret,
+            approver,
+            requester_did,
+            rule,
+            action,
+            task_type,
+            &challenge,
+            &digest,
+            &issued_at,
+            &expires_at,
+        )
+        .await?;
+        let message = Message::build(
+            uuid::Uuid::new_v4().to_string(),
+            request_type.to_string(),
+            document,
+        )
+        .from(control_did.clone())
+        .to(approver.clone())
+        .created_time(now_epoch())
+        .finalize();
+        messages.push((approver, message));
+    }
 
-    // Register the pending entry *before* sending so a fast wallet
-    // decision can never arrive before the correlation slot exists.
-    let (tx, rx) = tokio::sync::oneshot::channel::<bool>();
-    {
-        let mut pending = state.pending_confirms.lock().await;
-        pending.insert(
-            challenge.clone(),
-            PendingConfirm {
-                holder_did: req.holder_did.clone(),
-                expected_digest: digest.clone(),
-                tx,
-            },
+    // Store the approval *before* sending so a fast wallet decision can
+    // never arrive before it exists.
+    let approval = Approval {
+        id: challenge.clone(),
+        gate,
+        requester: requester_did.to_string(),
+        note: action.to_string(),
+        approvers: approvers.to_vec(),
+        expected_digest: digest,
+        min_approvals: rule.min_approvals,
+        created_at: now_epoch(),
+        expires_at: now_epoch().saturating_add(timeout.as_secs()),
+    };
+    approvals::open(&state.store, &approval).await?;
+
+    let mut sent = 0;
+    let mut last_error = None;
+    for (approver, message) in messages {
+        info!(
+            approver = %approver,
+            challenge = %challenge,
+            "sending task-consent request"
         );
+        match svc
+            .send_message(CONTROL_LISTENER_ID, message, approver)
+            .await
+        {
+            Ok(_) => sent += 1,
+            Err(e) => {
+                warn!(approver = %approver, error = %e, "task-consent request not delivered");
+                last_error = Some(e.to_string());
+            }
+        }
     }
-
-    let message = Message::build(
-        uuid::Uuid::new_v4().to_string(),
-        request_type.to_string(),
-        document,
-    )
-    .from(control_did)
-    .to(req.holder_did.clone())
-    .created_time(now_epoch())
-    .finalize();
-
-    info!(
-        holder_did = %req.holder_did,
-        challenge = %challenge,
-        "sending task-consent request"
-    );
-
-    if let Err(e) = svc
-        .send_message(CONTROL_LISTENER_ID, message, &req.holder_did)
-        .await
-    {
-        // Drop the pending entry — no decision will ever resolve it.
-        state.pending_confirms.lock().await.remove(&challenge);
+    if sent < rule.min_approvals {
+        // Drop the approval — too few approvers can answer it.
+        approvals::remove(&state.store, &approval).await?;
         return Err(AppError::Internal(format!(
-            "failed to send task-consent request: {e}"
+            "failed to send task-consent request to enough approvers ({sent} of {} needed): {}",
+            rule.min_approvals,
+            last_error.unwrap_or_default()
         )));
     }
-
-    match tokio::time::timeout(CONSENT_TIMEOUT, rx).await {
-        Ok(Ok(approved)) => {
-            info!(holder_did = %req.holder_did, approved, "task-consent decision resolved");
-            Ok(Json(ConsentResult { approved }))
-        }
-        Ok(Err(_recv_err)) => {
-            // Sender dropped without sending — the pending entry was
-            // already removed by the decision handler. Treat as internal.
-            state.pending_confirms.lock().await.remove(&challenge);
-            Err(AppError::Internal(
-                "consent channel closed before a decision arrived".into(),
-            ))
-        }
-        Err(_timed_out) => {
-            // Remove the abandoned pending entry on timeout.
-            state.pending_confirms.lock().await.remove(&challenge);
-            warn!(holder_did = %req.holder_did, "task-consent request timed out");
-            Err(AppError::Internal(
-                "wallet did not respond within the consent window".into(),
-            ))
-        }
-    }
+    Ok(challenge)
 }
 
 #[cfg(test)]
@@ -470,6 +587,7 @@ mod tests {
             &signer,
             "did:web:holder.example",
             "did:web:admin.example",
+            ApprovalRule::HOLDER,
             action,
             task_type,
             challenge,
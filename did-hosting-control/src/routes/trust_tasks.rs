@@ -43,6 +43,7 @@ use did_hosting_common::server::trust_tasks::{
     DispatchOutcome, TransportBoundVerifier, TrustTaskContext, build_dispatcher, dispatch_inbound,
 };
 
+use crate::audit;
 use crate::auth::AuthClaims;
 use crate::error::AppError;
 use crate::server::AppState;
@@ -84,6 +85,24 @@ pub async fn dispatch_trust_task(
         }
     };
 
+    // ─── 2a. An API key may only send the task types it was minted
+    //         for. The route is marked `HandlerChecksTask`, so the
+    //         extractor left this check to us.
+    if let Some(scope) = auth.api_key.as_ref()
+        && !scope.allows_task(&doc.type_uri.to_string())
+    {
+        tracing::warn!(
+            key_id = %scope.id,
+            type_uri = %doc.type_uri,
+            "trust-task rejected: type outside the API key's tasks"
+        );
+        let reject = RejectReason::PermissionDenied {
+            reason: "this API key is not scoped to this task".to_string(),
+        };
+        let routed = doc.reject_with(format!("urn:uuid:{}", Uuid::new_v4()), reject);
+        return Ok(into_response(DispatchOutcome::Rejected(routed)));
+    }
+
     // ─── 3. Proof-verificationMethod binding pre-check (SECURITY).
     //
     // Two cases, depending on how the JWT was issued:
@@ -163,9 +182,18 @@ pub async fn dispatch_trust_task(
         .registered_uris()
         .contains(&type_uri.as_str())
     {
-        let value = crate::messaging::bridge_did_management(&state, &auth.did, my_vid, &doc)
-            .await
-            .map_err(|e| AppError::Internal(e.to_string()))?;
+        let value = audit::with_trust_task(
+            Some(audit::TrustTaskRef::from(&doc)),
+            crate::messaging::bridge_did_management(
+                &state,
+                &auth.did,
+                my_vid,
+                &doc,
+                auth.api_key.clone(),
+            ),
+        )
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
         return Ok((
             StatusCode::OK,
             [(axum::http::header::CONTENT_TYPE, "application/json")],
@@ -174,7 +202,37 @@ pub async fn dispatch_trust_task(
             .into_response());
     }
 
-    // ─── 4. Build the transport adapter + context.
+    // ─── 3a. Framework ops (ACL, discovery) are not per-domain, so a
+    //         domain-restricted API key is refused them outright.
+    if let Some(scope) = auth.api_key.as_ref()
+        && !scope.domains.is_empty()
+    {
+        tracing::warn!(
+            key_id = %scope.id,
+            type_uri = %doc.type_uri,
+            "trust-task rejected: framework op for a domain-restricted API key"
+        );
+        let reject = RejectReason::PermissionDenied {
+            reason: "a domain-restricted API key cannot be used for this task".to_string(),
+        };
+        let routed = doc.reject_with(format!("urn:uuid:{}", Uuid::new_v4()), reject);
+        return Ok(into_response(DispatchOutcome::Rejected(routed)));
+    }
+
+    // ─── 4. Multi-party consent. A role grant under an `acl/grant`
+    //        policy waits here for the approvers; their decisions go on
+    //        the audit record.
+    let approvals = match crate::consent::require_for_acl_task(&state, &auth.did, &doc).await {
+        Ok(approvals) => approvals,
+        Err(e) => {
+            return Ok(into_response(DispatchOutcome::Rejected(
+                crate::consent::reject(&doc, &e),
+            )));
+        }
+    };
+
+    // ─── 5. Build the transport adapter + context.
+    let actor = audit::Actor::from(&auth);
     let transport = HttpsHandler::new(my_vid.to_string(), auth.did);
     let ctx = TrustTaskContext {
         acl_ks: &state.acl_ks,
@@ -182,7 +240,7 @@ pub async fn dispatch_trust_task(
         my_vid,
     };
 
-    // ─── 5. Dispatch.
+    // ─── 6. Dispatch.
     //
     // Map the operator's `enforce_proofs` toggle to a framework
     // [`ProofPolicy`]:
@@ -205,7 +263,11 @@ pub async fn dispatch_trust_task(
         (true, Some(v)) => ProofPolicy::Verify(v),
         _ => ProofPolicy::RejectIfPresent,
     };
+    let watch = audit::AclTaskWatch::begin(&state, &doc).await;
     let outcome = dispatch_inbound::<TransportBoundVerifier>(&ctx, &transport, policy, doc).await;
+    if let Some(watch) = watch {
+        audit::with_approvals(approvals, watch.finish(&state, actor, &outcome)).await;
+    }
     Ok(into_response(outcome))
 }
 
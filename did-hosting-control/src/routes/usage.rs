@@ -0,0 +1,60 @@
+//! Quota usage: what an owner or a domain hosts against its limits.
+//!
+//! - `GET /api/usage` — the caller's own usage.
+//! - `GET /api/usage/owners/{did}` — one owner's usage. Admins may ask
+//!   about anyone; other callers only about themselves.
+//! - `GET /api/usage/domains/{name}` — Admin only.
+//!
+//! The numbers come from [`crate::quota`], the same computation the write
+//! paths enforce, so what this reports is exactly what a create or
+//! publish will be measured against.
+
+use axum::Json;
+use axum::extract::{Path, State};
+use did_hosting_common::server::domain::normalize_domain_name;
+use tracing::{info, warn};
+
+use crate::acl::Role;
+use crate::auth::{AdminAuth, AuthClaims};
+use crate::error::AppError;
+use crate::quota::{self, UsageReport};
+use crate::server::AppState;
+
+/// `GET /api/usage`
+pub async fn my_usage(
+    auth: AuthClaims,
+    State(state): State<AppState>,
+) -> Result<Json<UsageReport>, AppError> {
+    let report = quota::owner_report(&state, &auth.did).await?;
+    info!(caller = %auth.did, "caller read own quota usage");
+    Ok(Json(report))
+}
+
+/// `GET /api/usage/owners/{did}`
+pub async fn owner_usage(
+    auth: AuthClaims,
+    State(state): State<AppState>,
+    Path(did): Path<String>,
+) -> Result<Json<UsageReport>, AppError> {
+    if auth.role != Role::Admin && auth.did != did {
+        warn!(caller = %auth.did, owner = %did, "quota usage denied: not the owner");
+        return Err(AppError::Forbidden(
+            "only admins can read another owner's usage".into(),
+        ));
+    }
+    let report = quota::owner_report(&state, &did).await?;
+    info!(caller = %auth.did, owner = %did, "owner quota usage read");
+    Ok(Json(report))
+}
+
+/// `GET /api/usage/domains/{name}`
+pub async fn domain_usage(
+    auth: AdminAuth,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<UsageReport>, AppError> {
+    let name = normalize_domain_name(&name)?;
+    let report = quota::domain_report(&state, &name).await?;
+    info!(caller = %auth.0.did, domain = %name, "domain quota usage read");
+    Ok(Json(report))
+}
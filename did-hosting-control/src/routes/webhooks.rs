@@ -0,0 +1,281 @@
+//! Webhook subscriptions for DID and domain lifecycle events (see
+//! [`crate::webhooks`]).
+//!
+//! - `GET /api/webhooks` — the caller's subscriptions; admins see all.
+//! - `POST /api/webhooks` — register an endpoint. The response carries the
+//!   signing secret; it is never shown again.
+//! - `GET` / `PUT` / `DELETE /api/webhooks/{id}` — creator or admin.
+//!
+//! Owners subscribe to their own DIDs only (the `owner` scope, which is
+//! also the default); `domain` and `global` scopes are Admin only.
+//! Service accounts have no access.
+
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use did_hosting_common::server::domain::{get_domain, normalize_domain_name};
+use did_hosting_common::server::store::WriteBatch;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::acl::Role;
+use crate::audit;
+use crate::auth::AuthClaims;
+use crate::auth::session::now_epoch;
+use crate::error::AppError;
+use crate::server::AppState;
+use crate::webhooks::{self, Scope, Signing, Subscription, SubscriptionInfo};
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    /// Defaults to the caller's own DIDs.
+    #[serde(default)]
+    pub scope: Option<Scope>,
+    /// Empty (the default) subscribes to every event type.
+    #[serde(default)]
+    pub events: Vec<String>,
+    #[serde(default)]
+    pub signing: Signing,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateWebhookRequest {
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub events: Option<Vec<String>>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebhookListResponse {
+    pub webhooks: Vec<SubscriptionInfo>,
+}
+
+fn require_tenant(auth: &AuthClaims) -> Result<(), AppError> {
+    if auth.role == Role::Service {
+        return Err(AppError::Forbidden(
+            "service accounts cannot manage webhooks".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Load a subscription the caller may manage: their own, or any for an
+/// admin.
+async fn load_authorized(
+    state: &AppState,
+    auth: &AuthClaims,
+    id: &str,
+) -> Result<Subscription, AppError> {
+    require_tenant(auth)?;
+    let sub = webhooks::get(&state.store, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("webhook not found: {id}")))?;
+    if auth.role != Role::Admin && sub.created_by != auth.did {
+        warn!(caller = %auth.did, webhook = %id, "webhook access denied: not the creator");
+        return Err(AppError::Forbidden(
+            "only the creator or an admin can manage this webhook".into(),
+        ));
+    }
+    Ok(sub)
+}
+
+/// Stage the audit record for a subscription change in `batch`.
+fn stage_audit(
+    state: &AppState,
+    batch: &mut WriteBatch,
+    auth: &AuthClaims,
+    action: &'static str,
+    id: &str,
+    before: Option<String>,
+    after: Option<&Subscription>,
+) -> Result<(), AppError> {
+    audit::stage(
+        &state.store,
+        batch,
+        Some(audit::Actor::from(auth)),
+        audit::Entry {
+            action,
+            subject: id,
+            before,
+            // Digest the secret-free view: the audit log is readable by
+            // every admin, the signing secret is not.
+            after: after.and_then(|s| audit::digest(&SubscriptionInfo::from(s))),
+        },
+    )
+}
+
+/// `GET /api/webhooks`
+pub async fn list(
+    auth: AuthClaims,
+    State(state): State<AppState>,
+) -> Result<Json<WebhookListResponse>, AppError> {
+    require_tenant(&auth)?;
+    let webhooks = webhooks::list(&state.store)
+        .await?
+        .iter()
+        .filter(|s| auth.role == Role::Admin || s.created_by == auth.did)
+        .map(SubscriptionInfo::from)
+        .collect();
+    Ok(Json(WebhookListResponse { webhooks }))
+}
+
+/// `POST /api/webhooks`
+pub async fn create(
+    auth: AuthClaims,
+    State(state): State<AppState>,
+    Json(req): Json<CreateWebhookRequest>,
+) -> Result<(StatusCode, Json<SubscriptionInfo>), AppError> {
+    require_tenant(&auth)?;
+    let admin = auth.role == Role::Admin;
+
+    let scope = match req.scope.unwrap_or(Scope::Owner {
+        did: auth.did.clone(),
+    }) {
+        Scope::Owner { did } if did != auth.did && !admin => {
+            return Err(AppError::Forbidden(
+                "owners can only subscribe to their own DIDs".into(),
+            ));
+        }
+        Scope::Owner { did } => Scope::Owner { did },
+        Scope::Domain { .. } | Scope::Global if !admin => {
+            return Err(AppError::Forbidden(
+                "domain and global webhooks require the admin role".into(),
+            ));
+        }
+        Scope::Domain { name } => {
+            let name = normalize_domain_name(&name)?;
+            if get_domain(&state.store, &name).await?.is_none() {
+                return Err(AppError::NotFound(format!("domain not found: {name}")));
+            }
+            Scope::Domain { name }
+        }
+        Scope::Global => Scope::Global,
+    };
+    let url = webhooks::validate_url(&req.url, admin)?;
+    let events = webhooks::validate_events(&req.events)?;
+
+    if !admin {
+        let held = webhooks::list(&state.store)
+            .await?
+            .iter()
+            .filter(|s| s.created_by == auth.did)
+            .count();
+        if held >= webhooks::MAX_SUBSCRIPTIONS_PER_OWNER {
+            return Err(AppError::QuotaExceeded(format!(
+                "at most {} webhooks per owner",
+                webhooks::MAX_SUBSCRIPTIONS_PER_OWNER
+            )));
+        }
+    }
+
+    let now = now_epoch();
+    let sub = Subscription {
+        id: uuid::Uuid::new_v4().simple().to_string(),
+        created_by: auth.did.clone(),
+        scope,
+        url,
+        events,
+        signing: req.signing,
+        secret: webhooks::generate_secret(),
+        enabled: true,
+        created_at: now,
+        updated_at: now,
+    };
+    let mut batch = state.store.batch();
+    webhooks::stage_put(&state.store, &mut batch, &sub)?;
+    stage_audit(
+        &state,
+        &mut batch,
+        &auth,
+        "webhook.create",
+        &sub.id,
+        None,
+        Some(&sub),
+    )?;
+    batch.commit().await?;
+    audit::flush(&state).await;
+    info!(caller = %auth.did, webhook = %sub.id, url = %sub.url, "webhook created");
+
+    let mut info = SubscriptionInfo::from(&sub);
+    info.secret = Some(sub.secret);
+    Ok((StatusCode::CREATED, Json(info)))
+}
+
+/// `GET /api/webhooks/{id}`
+pub async fn get(
+    auth: AuthClaims,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<SubscriptionInfo>, AppError> {
+    let sub = load_authorized(&state, &auth, &id).await?;
+    Ok(Json(SubscriptionInfo::from(&sub)))
+}
+
+/// `PUT /api/webhooks/{id}` — change the URL or event filter, or pause /
+/// resume delivery. Scope and signing mode are fixed at creation; delete
+/// and re-create to change them. Pausing keeps queued deliveries.
+pub async fn update(
+    auth: AuthClaims,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<UpdateWebhookRequest>,
+) -> Result<Json<SubscriptionInfo>, AppError> {
+    let mut sub = load_authorized(&state, &auth, &id).await?;
+    let before = audit::digest(&SubscriptionInfo::from(&sub));
+
+    if let Some(url) = req.url {
+        sub.url = webhooks::validate_url(&url, auth.role == Role::Admin)?;
+    }
+    if let Some(events) = req.events {
+        sub.events = webhooks::validate_events(&events)?;
+    }
+    if let Some(enabled) = req.enabled {
+        sub.enabled = enabled;
+    }
+    sub.updated_at = now_epoch();
+
+    let mut batch = state.store.batch();
+    webhooks::stage_put(&state.store, &mut batch, &sub)?;
+    stage_audit(
+        &state,
+        &mut batch,
+        &auth,
+        "webhook.update",
+        &id,
+        before,
+        Some(&sub),
+    )?;
+    batch.commit().await?;
+    audit::flush(&state).await;
+    info!(caller = %auth.did, webhook = %id, enabled = sub.enabled, "webhook updated");
+    Ok(Json(SubscriptionInfo::from(&sub)))
+}
+
+/// `DELETE /api/webhooks/{id}` — also discards its undelivered events.
+pub async fn delete(
+    auth: AuthClaims,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let sub = load_authorized(&state, &auth, &id).await?;
+    let before = audit::digest(&SubscriptionInfo::from(&sub));
+    let mut batch = state.store.batch();
+    webhooks::stage_delete(&state.store, &mut batch, &id).await?;
+    stage_audit(
+        &state,
+        &mut batch,
+        &auth,
+        "webhook.delete",
+        &id,
+        before,
+        None,
+    )?;
+    batch.commit().await?;
+    audit::flush(&state).await;
+    info!(caller = %auth.did, webhook = %id, "webhook deleted");
+    Ok(StatusCode::NO_CONTENT)
+}
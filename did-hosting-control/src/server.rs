@@ -1,4 +1,3 @@
-use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -7,6 +6,7 @@ use affinidi_messaging_didcomm_service::{
     DIDCommService, DIDCommServiceConfig, ListenerConfig, Protocols, RestartPolicy, RetryConfig,
 };
 use affinidi_tdk::secrets_resolver::ThreadedSecretsResolver;
+use did_hosting_common::server::auth::api_key::ApiKeyStore;
 use did_hosting_common::server::auth::extractor::AuthState;
 use did_hosting_common::server::didcomm_profile::{
     advertised_protocols, build_tdk_profile_for_identity, reconcile_listener_protocols,
@@ -16,7 +16,7 @@ use did_hosting_common::server::identity::{self, ServiceIdentity};
 use did_hosting_common::server::init;
 use did_hosting_common::server::passkey::PasskeyState;
 use did_hosting_common::server::store::{
-    KS_ACL, KS_DIDS, KS_REGISTRY, KS_SESSIONS, KS_STATS, KS_TIMESERIES,
+    KS_ACL, KS_API_KEYS, KS_DIDS, KS_REGISTRY, KS_SESSIONS, KS_STATS, KS_TIMESERIES,
 };
 use tokio_util::sync::CancellationToken;
 use webauthn_rs::prelude::Webauthn;
@@ -34,27 +34,6 @@ use tokio::sync::{oneshot, watch};
 use tower_http::trace::{DefaultMakeSpan, DefaultOnResponse, TraceLayer};
 use tracing::{Level, debug, error, info, warn};
 
-/// An in-flight wallet consent awaiting the holder's authcrypted, signed
-/// `task-consent/decision/0.1`. Keyed by `challenge` in
-/// [`AppState::pending_confirms`]. The REST trigger endpoint parks a
-/// `oneshot::Receiver` while the inbound DIDComm decision handler fires
-/// the approve/deny on `tx`.
-pub struct PendingConfirm {
-    /// The holder DID the `task-consent/request/0.1` was addressed to.
-    /// The inbound decision is only honoured if its authcrypt sender —
-    /// and the DID its proof verifies against — equals this.
-    pub holder_did: String,
-    /// The salted `payloadDigest` sent in the request. The decision must
-    /// echo it verbatim; a mismatch means the wallet answered a
-    /// different question than the one this entry asked.
-    pub expected_digest: String,
-    /// Resolves the parked REST request with the user's decision.
-    pub tx: tokio::sync::oneshot::Sender<bool>,
-}
-
-/// Map of in-flight wallet consents, keyed by `challenge`.
-pub type PendingConfirms = Arc<tokio::sync::Mutex<HashMap<String, PendingConfirm>>>;
-
 #[derive(Clone)]
 pub struct AppState {
     pub store: Store,
@@ -153,13 +132,6 @@ pub struct AppState {
     /// global counters above. See `crate::rate_limit` for the
     /// trusted-proxy / X-Forwarded-For policy.
     pub ip_rate_limiter: Arc<crate::rate_limit::IpRateLimiter>,
-    /// In-flight RP→wallet consent requests, keyed by `challenge`.
-    /// The `POST /task-consent/request` endpoint inserts a pending entry
-    /// and parks on a `oneshot`; the inbound `task-consent/decision/0.1`
-    /// DIDComm handler looks the entry up by challenge, verifies the
-    /// decision's proof and that the authcrypt sender matches the
-    /// addressed holder DID, and resolves the wait.
-    pub pending_confirms: PendingConfirms,
     /// Wakes the [`crate::outbox`] worker when a new entry lands in
     /// the durable outbound queue. The route handlers call
     /// `outbox::enqueue_and_notify`, which writes to fjall + fires
@@ -197,6 +169,13 @@ impl AuthState for AppState {
     fn sessions_ks(&self) -> &KeyspaceHandle {
         &self.sessions_ks
     }
+
+    fn api_keys(&self) -> Result<Option<ApiKeyStore>, AppError> {
+        Ok(Some(ApiKeyStore {
+            keys: self.store.keyspace(KS_API_KEYS)?,
+            acl: self.acl_ks.clone(),
+        }))
+    }
 }
 
 impl PasskeyState for AppState {
@@ -388,11 +367,10 @@ pub async fn run(config: AppConfig, store: Store, secrets: ServerSecrets) -> Res
         acl_locks: did_hosting_common::server::path_locks::PathLocks::new(),
         pending_challenges: Arc::new(crate::pending_challenges::PendingChallengeTracker::new()),
         ip_rate_limiter: Arc::new(crate::rate_limit::IpRateLimiter::new()),
-        pending_confirms: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
         outbox_notify: Arc::new(tokio::sync::Notify::new()),
     };
 
-    backfill_service_badges(&state.store).await;
+    backfill_did_records(&state.store).await;
 
     // Seed registry from static config
     seed_registry(&state).await;
@@ -552,9 +530,9 @@ pub async fn run(config: AppConfig, store: Store, secrets: ServerSecrets) -> Res
     // sweep but only deletes DomainEntry rows for ripe `disable-grace`
     // pending purges — control has no hosted DIDs to clean up.
     let (purge_shutdown_tx, purge_shutdown_rx) = tokio::sync::watch::channel(false);
-    let purge_store = state.store.clone();
+    let purge_state = state.clone();
     let purge_handle = tokio::spawn(async move {
-        crate::purge_sweep::run_purge_sweep_loop(purge_store, purge_shutdown_rx).await;
+        crate::purge_sweep::run_purge_sweep_loop(purge_state, purge_shutdown_rx).await;
     });
 
     // 5b. Spawn the identity sweep. Expires generations whose grace period has
@@ -581,6 +559,24 @@ pub async fn run(config: AppConfig, store: Store, secrets: ServerSecrets) -> Res
         crate::outbox::run_outbox_loop(outbox_state, outbox_notify, outbox_shutdown_rx).await;
     });
 
+    // 7. Spawn the webhook worker. Same drain discipline as the outbox,
+    // per subscription instead of per target; woken by every
+    // `crate::webhooks::wake` after a staged event commits.
+    let (webhook_shutdown_tx, webhook_shutdown_rx) = tokio::sync::watch::channel(false);
+    let webhook_state = state.clone();
+    let webhook_handle = tokio::spawn(async move {
+        crate::webhooks::run_webhook_loop(webhook_state, webhook_shutdown_rx).await;
+    });
+
+    // 8. Spawn the audit worker. Requests chain their own records once
+    // their write commits; this picks up any they could not, and lays the
+    // chain's baton down again if a writer died holding it.
+    let (audit_shutdown_tx, audit_shutdown_rx) = tokio::sync::watch::channel(false);
+    let audit_state = state.clone();
+    let audit_handle = tokio::spawn(async move {
+        crate::audit::run_audit_loop(audit_state, audit_shutdown_rx).await;
+    });
+
     // Wait for shutdown signal
     init::shutdown_signal().await;
 
@@ -592,6 +588,8 @@ pub async fn run(config: AppConfig, store: Store, secrets: ServerSecrets) -> Res
     let _ = purge_shutdown_tx.send(true);
     let _ = identity_shutdown_tx.send(true);
     let _ = outbox_shutdown_tx.send(true);
+    let _ = webhook_shutdown_tx.send(true);
+    let _ = audit_shutdown_tx.send(true);
     // DIDCommService shutdown is handled by the cancellation token
 
     let _ = rest_shutdown_tx.send(true);
@@ -634,6 +632,14 @@ pub async fn run(config: AppConfig, store: Store, secrets: ServerSecrets) -> Res
         warn!("outbox worker didn't shut down cleanly: {e}");
     }
 
+    if let Err(e) = webhook_handle.await {
+        warn!("webhook worker didn't shut down cleanly: {e}");
+    }
+
+    if let Err(e) = audit_handle.await {
+        warn!("audit worker didn't shut down cleanly: {e}");
+    }
+
     if any_panic {
         return Err(AppError::Internal("one or more threads panicked".into()));
     }
@@ -753,39 +759,46 @@ pub async fn start_didcomm_service(
 }
 
 // ---------------------------------------------------------------------------
-// Service-badge backfill
+// DID record backfills
 // ---------------------------------------------------------------------------
 
-/// Populate the per-DID service-badge cache (`DidRecord.services`) for records
-/// written before that field existed.
+/// Fill in DID record fields added after the records were written: the
+/// per-DID service-badge cache (`DidRecord.services`, `M-02`) and the
+/// deactivation mark (`DidRecord.deactivated_at`, `M-03`).
 ///
-/// Runs **only** `M-02`, not the full [`migrations::registry`]. The standalone
+/// Runs **only** those two, not the full [`migrations::registry`]. The standalone
 /// control plane has never invoked the migration runner, so a store here may
 /// never have seen `M-01` either — switching the whole set on as a side effect
 /// of adding badges would fill `domain` from the system-default tier on records
 /// that have gone their entire life without it. That's a separate decision with
 /// its own blast radius. `M-02` writes nothing but `services`, a field read only
-/// by the UI, so it is safe to run unattended.
+/// by the UI, and `M-03` nothing but `deactivated_at`, read off each DID's own
+/// log, so both are safe to run unattended.
 ///
 /// Idempotent and marker-gated in the `meta` keyspace: one pass over the DID
 /// logs on the first boot after upgrade, a no-op on every boot after.
 ///
-/// Failure is non-fatal. The daemon exits when its migrations fail, but missing
-/// badges are cosmetic and must not stop the control plane from starting —
-/// `publish_did` self-heals each record on its next publish regardless.
-pub async fn backfill_service_badges(store: &Store) {
-    use did_hosting_common::server::migrations::{M02CacheDidRecordServices, MigrationRunner};
+/// Failure is non-fatal. The daemon exits when its migrations fail, but the
+/// control plane must still start — `publish_did` self-heals badges on each
+/// record's next publish, and a failed run is retried on the next boot.
+pub async fn backfill_did_records(store: &Store) {
+    use did_hosting_common::server::migrations::{
+        M02CacheDidRecordServices, M03MarkDeactivatedDidRecords, MigrationRunner,
+    };
 
-    let runner = MigrationRunner::new(vec![Arc::new(M02CacheDidRecordServices)]);
+    let runner = MigrationRunner::new(vec![
+        Arc::new(M02CacheDidRecordServices),
+        Arc::new(M03MarkDeactivatedDidRecords),
+    ]);
     match runner.run_pending(store).await {
         Ok(summary) => info!(
             applied = ?summary.applied,
             skipped = ?summary.skipped,
-            "service-badge backfill complete"
+            "DID record backfill complete"
         ),
         Err(e) => warn!(
             error = %e,
-            "service-badge backfill failed; DID badges may be missing until each DID is next published"
+            "DID record backfill failed; badges or deactivation marks may be missing until the next boot"
         ),
     }
 }
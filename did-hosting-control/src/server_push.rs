@@ -15,11 +15,13 @@
 
 use did_hosting_common::did_ops::{self, DidRecord};
 use did_hosting_common::didcomm_types::*;
+use ed25519_dalek::SigningKey;
 use serde_json::json;
 use tracing::{info, warn};
 
 use crate::registry::{self, ServiceType};
 use crate::server::AppState;
+use crate::store::{KeyspaceHandle, Store};
 
 /// Enqueue published DIDs to one server's outbox — only the ones it doesn't
 /// already have at the current version.
@@ -110,13 +112,18 @@ pub fn sync_all_dids_to_server(
                 _ => None,
             };
 
-            let body = json!({
+            let mut body = json!({
                 "mnemonic": record.mnemonic,
                 "did_id": record.did_id.unwrap_or_default(),
                 "log_content": log_content,
                 "witness_content": witness_content,
                 "version_count": record.version_count,
             });
+            if let Some(previous) =
+                crate::did_move::previous_did_id(&dids_ks, &record.mnemonic).await
+            {
+                body["previous_did_id"] = json!(previous);
+            }
 
             if !batch {
                 if let Err(e) =
@@ -193,85 +200,13 @@ pub fn notify_servers_did(state: &AppState, mnemonic: String) {
     let dids_ks = state.dids_ks.clone();
     let store = state.store.clone();
     let notify = state.outbox_notify.clone();
+    let signer = crate::audit::signing_key(state).map(|(_, key)| key);
 
     tokio::spawn(async move {
         info!(mnemonic = %mnemonic, "DID changed — queueing sync to servers");
-
-        let record = match dids_ks.get::<DidRecord>(did_ops::did_key(&mnemonic)).await {
-            Ok(Some(r)) => r,
-            Ok(None) => {
-                warn!(mnemonic = %mnemonic, "DID sync: record not found in store");
-                return;
-            }
-            Err(e) => {
-                warn!(mnemonic = %mnemonic, error = %e, "DID sync: failed to read record");
-                return;
-            }
-        };
-
-        let log_content = match dids_ks.get_raw(did_ops::content_log_key(&mnemonic)).await {
-            Ok(Some(bytes)) => match String::from_utf8(bytes) {
-                Ok(s) => s,
-                Err(_) => {
-                    warn!(mnemonic = %mnemonic, "DID sync: invalid UTF-8 in log content");
-                    return;
-                }
-            },
-            Ok(None) => {
-                warn!(mnemonic = %mnemonic, "DID sync: no log content found");
-                return;
-            }
-            Err(e) => {
-                warn!(mnemonic = %mnemonic, error = %e, "DID sync: failed to read log");
-                return;
-            }
-        };
-
-        let witness_content = match dids_ks
-            .get_raw(did_ops::content_witness_key(&mnemonic))
-            .await
-        {
-            Ok(Some(bytes)) => String::from_utf8(bytes).ok(),
-            _ => None,
-        };
-
-        let body = json!({
-            "mnemonic": mnemonic,
-            "did_id": record.did_id.unwrap_or_default(),
-            "log_content": log_content,
-            "witness_content": witness_content,
-            "version_count": record.version_count,
-        });
-
-        let servers = match get_active_servers(&registry_ks).await {
-            Some(s) => s,
-            None => {
-                warn!(mnemonic = %mnemonic, "DID sync: no active servers in registry");
-                return;
-            }
-        };
-
-        for (server_did, instance_id) in &servers {
-            if let Err(e) =
-                crate::outbox::enqueue(&store, server_did, MSG_SYNC_UPDATE, body.clone()).await
-            {
-                warn!(
-                    server_did,
-                    instance_id,
-                    mnemonic = %mnemonic,
-                    error = %e,
-                    "DID sync: outbox enqueue failed"
-                );
-            } else {
-                info!(
-                    server_did,
-                    instance_id,
-                    mnemonic = %mnemonic,
-                    "DID sync: queued for server"
-                );
-            }
+        if queue_did_update(&store, &dids_ks, &registry_ks, &mnemonic, signer.as_ref()).await > 0 {
+            notify.notify_one();
         }
-        notify.notify_one();
     });
 }
 
@@ -283,38 +218,164 @@ pub fn notify_servers_delete(state: &AppState, mnemonic: String) {
 
     tokio::spawn(async move {
         info!(mnemonic = %mnemonic, "DID deleted — queueing sync to servers");
+        if queue_did_delete(&store, &registry_ks, &mnemonic).await > 0 {
+            notify.notify_one();
+        }
+    });
+}
 
-        let servers = match get_active_servers(&registry_ks).await {
-            Some(s) => s,
-            None => {
-                warn!(mnemonic = %mnemonic, "DID delete sync: no active servers in registry");
-                return;
-            }
-        };
+/// The enqueue half of [`notify_servers_did`], for callers without an
+/// [`AppState`] (the purge sweep). Returns the number of rows queued; the
+/// outbox worker picks them up on its next tick unless notified.
+///
+/// `signer` is the control DID's assertion key; a rolled-back log is
+/// pushed with a [`did_hosting_common::RollbackProof`] made with it, and
+/// without one watchers take the shorter log for a fork.
+pub async fn queue_did_update(
+    store: &Store,
+    dids_ks: &KeyspaceHandle,
+    registry_ks: &KeyspaceHandle,
+    mnemonic: &str,
+    signer: Option<&SigningKey>,
+) -> usize {
+    let record = match dids_ks.get::<DidRecord>(did_ops::did_key(mnemonic)).await {
+        Ok(Some(r)) => r,
+        Ok(None) => {
+            warn!(mnemonic = %mnemonic, "DID sync: record not found in store");
+            return 0;
+        }
+        Err(e) => {
+            warn!(mnemonic = %mnemonic, error = %e, "DID sync: failed to read record");
+            return 0;
+        }
+    };
 
-        let body = json!({ "mnemonic": mnemonic });
-        for (server_did, instance_id) in &servers {
-            if let Err(e) =
-                crate::outbox::enqueue(&store, server_did, MSG_SYNC_DELETE, body.clone()).await
-            {
-                warn!(
-                    server_did,
-                    instance_id,
-                    mnemonic = %mnemonic,
-                    error = %e,
-                    "DID delete sync: outbox enqueue failed"
-                );
-            } else {
-                info!(
-                    server_did,
-                    instance_id,
-                    mnemonic = %mnemonic,
-                    "DID delete sync: queued for server"
-                );
+    let log_content = match dids_ks.get_raw(did_ops::content_log_key(mnemonic)).await {
+        Ok(Some(bytes)) => match String::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(_) => {
+                warn!(mnemonic = %mnemonic, "DID sync: invalid UTF-8 in log content");
+                return 0;
             }
+        },
+        Ok(None) => {
+            warn!(mnemonic = %mnemonic, "DID sync: no log content found");
+            return 0;
+        }
+        Err(e) => {
+            warn!(mnemonic = %mnemonic, error = %e, "DID sync: failed to read log");
+            return 0;
         }
-        notify.notify_one();
+    };
+
+    let witness_content = match dids_ks
+        .get_raw(did_ops::content_witness_key(mnemonic))
+        .await
+    {
+        Ok(Some(bytes)) => String::from_utf8(bytes).ok(),
+        _ => None,
+    };
+
+    let did_id = record.did_id.unwrap_or_default();
+    let mut body = json!({
+        "mnemonic": mnemonic,
+        "did_id": did_id,
+        "log_content": log_content,
+        "witness_content": witness_content,
+        "version_count": record.version_count,
     });
+    if let Ok(Some(bytes)) = dids_ks
+        .get_raw(did_ops::content_did_doc_key(mnemonic))
+        .await
+        && let Ok(doc) = String::from_utf8(bytes)
+    {
+        body["did_document"] = json!(doc);
+    }
+    if let Some(previous) = crate::did_move::previous_did_id(dids_ks, mnemonic).await {
+        body["previous_did_id"] = json!(previous);
+    }
+    // Watchers downstream would otherwise take a rollback's shorter log
+    // for a rewritten history.
+    match crate::log_archive::rolled_back(dids_ks, mnemonic, &log_content).await {
+        Ok(entries) if !entries.is_empty() => {
+            let proof = crate::log_archive::rollback_proof(signer, &did_id, &log_content, &entries);
+            if let Some(proof) = proof {
+                body["rollback_proof"] = json!(proof);
+            }
+            body["rolled_back"] = json!(entries);
+        }
+        Ok(_) => {}
+        Err(e) => {
+            warn!(mnemonic = %mnemonic, error = %e, "DID sync: failed to read rolled-back entries")
+        }
+    }
+
+    let Some(servers) = get_active_servers(registry_ks).await else {
+        warn!(mnemonic = %mnemonic, "DID sync: no active servers in registry");
+        return 0;
+    };
+
+    let mut queued = 0;
+    for (server_did, instance_id) in &servers {
+        if let Err(e) =
+            crate::outbox::enqueue(store, server_did, MSG_SYNC_UPDATE, body.clone()).await
+        {
+            warn!(
+                server_did,
+                instance_id,
+                mnemonic = %mnemonic,
+                error = %e,
+                "DID sync: outbox enqueue failed"
+            );
+        } else {
+            queued += 1;
+            info!(
+                server_did,
+                instance_id,
+                mnemonic = %mnemonic,
+                "DID sync: queued for server"
+            );
+        }
+    }
+    queued
+}
+
+/// The enqueue half of [`notify_servers_delete`]. Same contract as
+/// [`queue_did_update`].
+pub async fn queue_did_delete(
+    store: &Store,
+    registry_ks: &KeyspaceHandle,
+    mnemonic: &str,
+) -> usize {
+    let Some(servers) = get_active_servers(registry_ks).await else {
+        warn!(mnemonic = %mnemonic, "DID delete sync: no active servers in registry");
+        return 0;
+    };
+
+    let body = json!({ "mnemonic": mnemonic });
+    let mut queued = 0;
+    for (server_did, instance_id) in &servers {
+        if let Err(e) =
+            crate::outbox::enqueue(store, server_did, MSG_SYNC_DELETE, body.clone()).await
+        {
+            warn!(
+                server_did,
+                instance_id,
+                mnemonic = %mnemonic,
+                error = %e,
+                "DID delete sync: outbox enqueue failed"
+            );
+        } else {
+            queued += 1;
+            info!(
+                server_did,
+                instance_id,
+                mnemonic = %mnemonic,
+                "DID delete sync: queued for server"
+            );
+        }
+    }
+    queued
 }
 
 // ---------------------------------------------------------------------------
@@ -322,9 +383,7 @@ pub fn notify_servers_delete(state: &AppState, mnemonic: String) {
 // ---------------------------------------------------------------------------
 
 /// Get active server DIDs and instance IDs from the registry.
-async fn get_active_servers(
-    registry_ks: &crate::store::KeyspaceHandle,
-) -> Option<Vec<(String, String)>> {
+async fn get_active_servers(registry_ks: &KeyspaceHandle) -> Option<Vec<(String, String)>> {
     let instances = match registry::list_instances(registry_ks).await {
         Ok(i) => i,
         Err(e) => {
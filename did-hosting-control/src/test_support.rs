@@ -148,6 +148,7 @@ impl TestServer {
             trust_tasks: Default::default(),
             hosting: Default::default(),
             identity: Default::default(),
+            consent: Default::default(),
             config_path: PathBuf::new(),
         };
 
@@ -181,7 +182,6 @@ impl TestServer {
             acl_locks: did_hosting_common::server::path_locks::PathLocks::new(),
             pending_challenges: Arc::new(crate::pending_challenges::PendingChallengeTracker::new()),
             ip_rate_limiter: Arc::new(crate::rate_limit::IpRateLimiter::new()),
-            pending_confirms: Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
             outbox_notify: Arc::new(tokio::sync::Notify::new()),
         };
 
@@ -266,6 +266,8 @@ impl TestServer {
             domain: String::new(),
             services: None,
             agent_names: Vec::new(),
+            deactivated_at: None,
+            sync_head: None,
         };
         self.put_did(&record).await;
         // Owner index — `register_did_atomic` writes this alongside the record.
@@ -20,7 +20,9 @@
 //! everything else to the legacy bridge (deprecated over time).
 //!
 //! Migration status: **all eight ops** are implemented — check-name, info,
-//! list, delete, publish, register, change-owner, witness-publish. Publish
+//! list, delete, publish, register, change-owner, witness-publish. The
+//! later `import` / `import-complete` pair and `move` have no legacy
+//! counterpart. Publish
 //! and register carry the `did.jsonl` log as a first-class typed field
 //! (`didLog`), the fit-for-purpose motivation the upstream record-centric
 //! payloads miss. The legacy `MSG_*` path (`dispatch_did_op`) remains for
@@ -160,7 +162,11 @@ pub struct DeleteResponse {
 #[serde(rename_all = "camelCase")]
 pub struct RegisterRequest {
     pub path: String,
+    /// The log of any enabled method; the method is read off it.
     pub did_log: String,
+    /// did:webs only: the `did.json` served next to the KEL.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub did_document: Option<serde_json::Value>,
     #[serde(default)]
     pub force: bool,
 }
@@ -218,6 +224,71 @@ pub struct WitnessPublishResponse {
     pub witness_url: String,
 }
 
+// --- Import ----------------------------------------------------------------
+//
+// Migrate a did:webvh in from another host (see `crate::did_import`). Two
+// round-trips, because the move entry has to be signed by the DID's own
+// update keys between them.
+
+/// `did-hosting/did/import/1.0` — verify a source log and reserve the slot
+/// it will move to. Exactly one of `sourceUrl` / `didLog`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub did_log: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub force: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+}
+impl trust_tasks_rs::Payload for ImportRequest {
+    const TYPE_URI: &'static str = "https://trusttasks.org/spec/did-hosting/did/import/1.0";
+}
+
+/// `did-hosting/did/import-complete/1.0` — the source log with the signed
+/// move entry appended.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportCompleteRequest {
+    pub mnemonic: String,
+    pub did_log: String,
+}
+impl trust_tasks_rs::Payload for ImportCompleteRequest {
+    const TYPE_URI: &'static str =
+        "https://trusttasks.org/spec/did-hosting/did/import-complete/1.0";
+}
+
+/// `did-hosting/did/import-complete/1.0#response`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportCompleteResponse {
+    pub mnemonic: String,
+    pub did_id: Option<String>,
+    pub version_count: u64,
+}
+
+// --- Move ------------------------------------------------------------------
+//
+// Move a hosted did:webvh to another hosted domain or path (see
+// `crate::did_move`). The slot it lands in follows from the new identifier.
+
+/// `did-hosting/did/move/1.0` — the current log with the tenant-signed move
+/// entry appended.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveRequest {
+    pub mnemonic: String,
+    pub did_log: String,
+}
+impl trust_tasks_rs::Payload for MoveRequest {
+    const TYPE_URI: &'static str = "https://trusttasks.org/spec/did-hosting/did/move/1.0";
+}
+
 // --- Agent names -----------------------------------------------------------
 //
 // Bind a human-memorable name (`example.com/@alice`) to a hosted DID. Both
@@ -302,6 +373,9 @@ enum DidHostingInbound {
     WitnessPublish(TrustTask<WitnessPublishRequest>),
     UpdateAgentName(TrustTask<UpdateAgentNameRequest>),
     RemoveAgentName(TrustTask<RemoveAgentNameRequest>),
+    Import(TrustTask<ImportRequest>),
+    ImportComplete(TrustTask<ImportCompleteRequest>),
+    Move(TrustTask<MoveRequest>),
 }
 
 fn build_dispatcher() -> Dispatcher<DidHostingInbound> {
@@ -315,6 +389,9 @@ fn build_dispatcher() -> Dispatcher<DidHostingInbound> {
         .on::<WitnessPublishRequest, _>(DidHostingInbound::WitnessPublish)
         .on::<UpdateAgentNameRequest, _>(DidHostingInbound::UpdateAgentName)
         .on::<RemoveAgentNameRequest, _>(DidHostingInbound::RemoveAgentName)
+        .on::<ImportRequest, _>(DidHostingInbound::Import)
+        .on::<ImportCompleteRequest, _>(DidHostingInbound::ImportComplete)
+        .on::<MoveRequest, _>(DidHostingInbound::Move)
 }
 
 /// Does the typed `did-hosting/*/1.0` protocol own this Type URI? The
@@ -355,6 +432,11 @@ where
         Ok(DidHostingInbound::RemoveAgentName(d)) => {
             handle_remove_agent_name(state, transport, policy, d).await
         }
+        Ok(DidHostingInbound::Import(d)) => handle_import(state, transport, policy, d).await,
+        Ok(DidHostingInbound::ImportComplete(d)) => {
+            handle_import_complete(state, transport, policy, d).await
+        }
+        Ok(DidHostingInbound::Move(d)) => handle_move(state, transport, policy, d).await,
         Err(err) => DispatchOutcome::Rejected(err),
     }
 }
@@ -418,6 +500,7 @@ where
                 session_pubkey_b58btc: None,
                 amr: vec!["did".to_string()],
                 acr: "aal1".to_string(),
+                api_key: None,
             };
             let req = &doc.payload;
 
@@ -670,10 +753,17 @@ where
                         .with_message("register requires a non-empty `path`"),
                 ));
             }
-            let result =
-                did_ops::register_did_atomic(&auth, &state, &req.path, &req.did_log, req.force)
-                    .await
-                    .map_err(|e| reject_apperror(&doc, e))?;
+            let did_doc = req.did_document.as_ref().map(|d| d.to_string());
+            let result = did_ops::register_did_atomic(
+                &auth,
+                &state,
+                &req.path,
+                &req.did_log,
+                did_doc.as_deref(),
+                req.force,
+            )
+            .await
+            .map_err(|e| reject_apperror(&doc, e))?;
             crate::server_push::notify_servers_did(&state, result.mnemonic.clone());
             let server_did = state.config.server_did.clone().unwrap_or_default();
             Ok(doc.respond_with(
@@ -886,6 +976,141 @@ where
     .await
 }
 
+async fn handle_import<V>(
+    state: &AppState,
+    transport: &(impl TransportHandler + Sync),
+    policy: ProofPolicy<'_, V>,
+    doc: TrustTask<ImportRequest>,
+) -> DispatchOutcome
+where
+    V: ProofVerifier + ?Sized,
+{
+    let (my_vid, state) = match resolve_state(state, &doc) {
+        Ok(v) => v,
+        Err(o) => return *o,
+    };
+    run_pipeline(
+        transport,
+        policy,
+        doc,
+        &my_vid,
+        move |doc, parties| async move {
+            let auth = authorize(&state, &doc, &parties).await?;
+            let req = &doc.payload;
+            let source = crate::did_import::ImportSource::from_parts(
+                req.source_url.as_deref(),
+                req.did_log.as_deref(),
+            )
+            .map_err(|e| reject_apperror(&doc, e))?;
+            // Same domain chain as a reservation: explicit → ACL default →
+            // system default; the hosting URL's host when none resolves.
+            let acl_scope =
+                match did_hosting_common::server::acl::get_acl_entry(&state.acl_ks, &auth.did)
+                    .await
+                    .map_err(|e| reject_apperror(&doc, e))?
+                {
+                    Some(e) => e.domains,
+                    None => DomainScope::All,
+                };
+            let system_default = get_default_domain(&state.store).await.ok().flatten();
+            let resolved_domain = resolve_request_domain(
+                req.domain.as_deref(),
+                &acl_scope,
+                system_default.as_deref(),
+            )
+            .ok();
+            let offer = did_ops::start_import(
+                &auth,
+                &state,
+                source,
+                req.path.as_deref(),
+                req.force,
+                resolved_domain.as_deref(),
+            )
+            .await
+            .map_err(|e| reject_apperror(&doc, e))?;
+            Ok(doc.respond_with(new_id(), offer))
+        },
+    )
+    .await
+}
+
+async fn handle_import_complete<V>(
+    state: &AppState,
+    transport: &(impl TransportHandler + Sync),
+    policy: ProofPolicy<'_, V>,
+    doc: TrustTask<ImportCompleteRequest>,
+) -> DispatchOutcome
+where
+    V: ProofVerifier + ?Sized,
+{
+    let (my_vid, state) = match resolve_state(state, &doc) {
+        Ok(v) => v,
+        Err(o) => return *o,
+    };
+    run_pipeline(
+        transport,
+        policy,
+        doc,
+        &my_vid,
+        move |doc, parties| async move {
+            let auth = authorize(&state, &doc, &parties).await?;
+            let (record, _) = did_ops::complete_import(
+                &auth,
+                &state,
+                &doc.payload.mnemonic,
+                &doc.payload.did_log,
+            )
+            .await
+            .map_err(|e| reject_apperror(&doc, e))?;
+            crate::server_push::notify_servers_did(&state, record.mnemonic.clone());
+            Ok(doc.respond_with(
+                new_id(),
+                ImportCompleteResponse {
+                    mnemonic: record.mnemonic,
+                    did_id: record.did_id,
+                    version_count: record.version_count,
+                },
+            ))
+        },
+    )
+    .await
+}
+
+async fn handle_move<V>(
+    state: &AppState,
+    transport: &(impl TransportHandler + Sync),
+    policy: ProofPolicy<'_, V>,
+    doc: TrustTask<MoveRequest>,
+) -> DispatchOutcome
+where
+    V: ProofVerifier + ?Sized,
+{
+    let (my_vid, state) = match resolve_state(state, &doc) {
+        Ok(v) => v,
+        Err(o) => return *o,
+    };
+    run_pipeline(
+        transport,
+        policy,
+        doc,
+        &my_vid,
+        move |doc, parties| async move {
+            let auth = authorize(&state, &doc, &parties).await?;
+            let moved =
+                did_ops::move_did(&auth, &state, &doc.payload.mnemonic, &doc.payload.did_log)
+                    .await
+                    .map_err(|e| reject_apperror(&doc, e))?;
+            crate::server_push::notify_servers_did(&state, moved.mnemonic.clone());
+            if moved.mnemonic != moved.previous_mnemonic {
+                crate::server_push::notify_servers_did(&state, moved.previous_mnemonic.clone());
+            }
+            Ok(doc.respond_with(new_id(), moved))
+        },
+    )
+    .await
+}
+
 // ---------------------------------------------------------------------------
 // Helpers
 // ---------------------------------------------------------------------------
@@ -941,6 +1166,7 @@ async fn authorize<P>(
         session_pubkey_b58btc: None,
         amr: vec!["did".to_string()],
         acr: "aal1".to_string(),
+        api_key: None,
     })
 }
 
@@ -948,9 +1174,25 @@ fn new_id() -> String {
     format!("urn:uuid:{}", uuid::Uuid::new_v4())
 }
 
+/// Extended error code for a write refused by [`crate::quota`]. The
+/// framework has no standard code for it, and folding it into
+/// `task_failed` would hide the one failure a client should act on by
+/// upgrading rather than retrying.
+const ERR_QUOTA_EXCEEDED: &str = "quota_exceeded";
+
 /// Map an [`AppError`] to a framework-routed error document, preserving
 /// the request's `issuer`/`recipient` so it addresses the caller.
-fn reject_apperror<P>(doc: &TrustTask<P>, e: AppError) -> trust_tasks_rs::ErrorResponse {
+fn reject_apperror<P: trust_tasks_rs::Payload>(
+    doc: &TrustTask<P>,
+    e: AppError,
+) -> trust_tasks_rs::ErrorResponse {
+    if matches!(e, AppError::QuotaExceeded(_)) {
+        return doc.reject_with(
+            new_id(),
+            ErrorPayload::new(P::extended_code(ERR_QUOTA_EXCEEDED.to_string()))
+                .with_message(e.user_message()),
+        );
+    }
     // StandardCode has no NotFound/Conflict; map the closest framework code.
     let code = match &e {
         AppError::Validation(_) => StandardCode::MalformedRequest,
@@ -1033,6 +1275,7 @@ mod tests {
             trust_tasks: Default::default(),
             hosting: Default::default(),
             identity: Default::default(),
+            consent: Default::default(),
             config_path: PathBuf::new(),
         };
         let state = AppState {
@@ -1059,7 +1302,6 @@ mod tests {
             acl_locks: did_hosting_common::server::path_locks::PathLocks::new(),
             pending_challenges: Arc::new(crate::pending_challenges::PendingChallengeTracker::new()),
             ip_rate_limiter: Arc::new(crate::rate_limit::IpRateLimiter::new()),
-            pending_confirms: Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
             outbox_notify: Arc::new(tokio::sync::Notify::new()),
         };
         (state, dir)
@@ -1302,6 +1544,9 @@ mod tests {
             WitnessPublishRequest::TYPE_URI,
             UpdateAgentNameRequest::TYPE_URI,
             RemoveAgentNameRequest::TYPE_URI,
+            ImportRequest::TYPE_URI,
+            ImportCompleteRequest::TYPE_URI,
+            MoveRequest::TYPE_URI,
         ] {
             assert!(owns(uri), "dispatcher should own {uri}");
         }
@@ -1330,6 +1575,59 @@ mod tests {
         );
     }
 
+    /// Import routes to `did_ops::start_import`; a source log that fails
+    /// verification is rejected before any slot is reserved.
+    #[tokio::test]
+    async fn import_unverifiable_source_rejected_over_typed() {
+        let (state, _dir) = test_state().await;
+        seed_admin(&state).await;
+        let outcome = dispatch::<TransportBoundVerifier>(
+            &state,
+            &transport(),
+            ProofPolicy::AcceptUnverified,
+            op_doc(
+                ImportRequest::TYPE_URI,
+                json!({ "path": "imported", "didLog": "garbage" }),
+            ),
+        )
+        .await;
+        assert!(
+            matches!(outcome, DispatchOutcome::Rejected(_)),
+            "unverifiable source log is rejected"
+        );
+        assert!(
+            state
+                .dids_ks
+                .get_raw(did_key("imported"))
+                .await
+                .unwrap()
+                .is_none(),
+            "no slot is reserved for a rejected import"
+        );
+    }
+
+    /// Move routes to `did_ops::move_did`; a log with no resolvable new
+    /// identifier is rejected before either slot is touched.
+    #[tokio::test]
+    async fn move_unpublished_slot_rejected_over_typed() {
+        let (state, _dir) = test_state().await;
+        seed_admin(&state).await;
+        let outcome = dispatch::<TransportBoundVerifier>(
+            &state,
+            &transport(),
+            ProofPolicy::AcceptUnverified,
+            op_doc(
+                MoveRequest::TYPE_URI,
+                json!({ "mnemonic": "nowhere", "didLog": "garbage" }),
+            ),
+        )
+        .await;
+        assert!(
+            matches!(outcome, DispatchOutcome::Rejected(_)),
+            "a move without a new identifier is rejected"
+        );
+    }
+
     /// Change-owner routes to `did_ops::change_did_owner`; transferring to
     /// an unknown owner is rejected there.
     #[tokio::test]
@@ -153,6 +153,7 @@ mod tests {
             trust_tasks: Default::default(),
             hosting: Default::default(),
             identity: Default::default(),
+            consent: Default::default(),
             config_path: PathBuf::new(),
         };
         let state = AppState {
@@ -179,7 +180,6 @@ mod tests {
             acl_locks: did_hosting_common::server::path_locks::PathLocks::new(),
             pending_challenges: Arc::new(crate::pending_challenges::PendingChallengeTracker::new()),
             ip_rate_limiter: Arc::new(crate::rate_limit::IpRateLimiter::new()),
-            pending_confirms: Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
             outbox_notify: Arc::new(tokio::sync::Notify::new()),
         };
         (state, dir)
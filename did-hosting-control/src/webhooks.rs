@@ -0,0 +1,1003 @@
+//! Outbound webhooks for DID lifecycle events.
+//!
+//! Tenants and admins register HTTPS endpoints that are POSTed a JSON
+//! [`WebhookEvent`] whenever something they care about changes: a DID is
+//! registered, updated, deactivated, disabled, deleted, moved or handed to
+//! a new owner, or a domain changes state. Downstream consumers (credential
+//! issuers watching for key rotations, mostly) no longer have to poll
+//! `/api/dids`.
+//!
+//! ## Scopes
+//!
+//! - **Owner** — events for DIDs the named owner holds. An owner may only
+//!   subscribe for themselves; `did.owner-changed` reaches both the old
+//!   and the new owner.
+//! - **Domain** — events for DIDs hosted on the domain, plus the domain's
+//!   own lifecycle events. Admin only.
+//! - **Global** — everything. Admin only.
+//!
+//! ## Delivery
+//!
+//! [`emit`] stages an event in the same write batch as the change it
+//! reports, so a committed change always has its event and a failed one
+//! never does. The worker first fans staged events out to one queue row
+//! per matching subscription ([`fan_out_staged`]), then
+//! [`run_webhook_loop`] drains the queue with the same semantics as
+//! [`crate::outbox`]:
+//! at-least-once, per-subscription FIFO with head-of-line blocking,
+//! exponential backoff, and poison entries dropped after
+//! [`outbox::MAX_ATTEMPTS`] attempts or [`outbox::MAX_AGE_SECS`]. A 2xx
+//! response is success; anything else (redirects included — the shared
+//! HTTP client does not follow them) is retried. The event `id` is stable
+//! across retries so receivers can de-duplicate. Endpoints registered by
+//! non-admins are reached through [`outbound::client`], so a hostname that
+//! resolves to an internal address fails at connect time.
+//!
+//! ## Signatures
+//!
+//! Headers follow the Standard Webhooks layout: `webhook-id`,
+//! `webhook-timestamp` and `webhook-signature`, the signature covering
+//! `{webhook-id}.{webhook-timestamp}.{body}`.
+//!
+//! - **`hmac`** (default) — `v1,<base64 HMAC-SHA256>` keyed with the
+//!   subscription's `whsec_…` secret, which is returned once, on create.
+//! - **`did`** — `v1a,<base64 Ed25519>` with the control DID's current
+//!   assertion key, named in `webhook-key-id`. Receivers verify against
+//!   the control DID document, so there is no shared secret to leak.
+//!
+//! ## Failure policy
+//!
+//! As with the audit log, a staged event is stored with the mutation, so
+//! a fan-out failure only delays it: the worker retries on its next tick.
+//!
+//! ## Key layout (`KS_WEBHOOKS`)
+//!
+//! - `sub:{id}` → [`Subscription`]
+//! - `queue:{sub_id}:{enqueue_micros:020}:{uuid_short}` → [`Delivery`]
+
+use std::time::Duration;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use did_hosting_common::did_ops::DidRecord;
+use did_hosting_common::server::acl::{Role, get_acl_entry};
+use did_hosting_common::server::store::{KS_WEBHOOKS, KeyspaceHandle, Store, WriteBatch};
+use ed25519_dalek::Signer;
+use hmac::{Hmac, KeyInit, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use tokio::sync::{Notify, watch};
+use tracing::{debug, info, warn};
+
+use crate::auth::session::now_epoch;
+use crate::outbound;
+use crate::outbox::{self, TickReport};
+use crate::server::AppState;
+use did_hosting_common::server::error::AppError;
+
+pub const DID_REGISTERED: &str = "did.registered";
+pub const DID_UPDATED: &str = "did.updated";
+pub const DID_DEACTIVATED: &str = "did.deactivated";
+pub const DID_DISABLED: &str = "did.disabled";
+pub const DID_ENABLED: &str = "did.enabled";
+pub const DID_DELETED: &str = "did.deleted";
+pub const DID_OWNER_CHANGED: &str = "did.owner-changed";
+pub const DID_MOVED: &str = "did.moved";
+pub const DOMAIN_DISABLED: &str = "domain.disabled";
+pub const DOMAIN_ENABLED: &str = "domain.enabled";
+pub const DOMAIN_DELETED: &str = "domain.deleted";
+
+/// Every event type a subscription may filter on.
+pub const EVENT_TYPES: &[&str] = &[
+    DID_REGISTERED,
+    DID_UPDATED,
+    DID_DEACTIVATED,
+    DID_DISABLED,
+    DID_ENABLED,
+    DID_DELETED,
+    DID_OWNER_CHANGED,
+    DID_MOVED,
+    DOMAIN_DISABLED,
+    DOMAIN_ENABLED,
+    DOMAIN_DELETED,
+];
+
+/// Worker tick when nothing's notified — same cadence as the outbox.
+pub const DEFAULT_WEBHOOK_TICK: Duration = Duration::from_secs(30);
+
+/// Per-request budget for one delivery. A receiver slower than this is
+/// treated as a failure and retried; it must not stall the worker.
+pub const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Subscriptions a non-admin caller may hold.
+pub const MAX_SUBSCRIPTIONS_PER_OWNER: usize = 20;
+
+/// Wakes the worker after a staged event commits. A module-level `Notify`
+/// rather than an `AppState` field: webhooks have exactly one producer
+/// path ([`emit`], then [`wake`]) and one consumer, so there is nothing to
+/// thread.
+static WAKE: Notify = Notify::const_new();
+
+/// Who a subscription hears about.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Scope {
+    Owner { did: String },
+    Domain { name: String },
+    Global,
+}
+
+/// How deliveries are signed.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Signing {
+    #[default]
+    Hmac,
+    Did,
+}
+
+/// A registered endpoint, as stored.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Subscription {
+    pub id: String,
+    /// DID of the caller that created it; owners see and manage only
+    /// their own subscriptions.
+    pub created_by: String,
+    pub scope: Scope,
+    pub url: String,
+    /// Event types to deliver. Empty means all of [`EVENT_TYPES`].
+    #[serde(default)]
+    pub events: Vec<String>,
+    #[serde(default)]
+    pub signing: Signing,
+    /// `whsec_` + base64 of 32 random bytes. Never returned after create.
+    pub secret: String,
+    pub enabled: bool,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+/// The wire shape of a [`Subscription`]: everything but the secret,
+/// which is only present in the response to the create call.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SubscriptionInfo {
+    pub id: String,
+    pub created_by: String,
+    pub scope: Scope,
+    pub url: String,
+    pub events: Vec<String>,
+    pub signing: Signing,
+    pub enabled: bool,
+    pub created_at: u64,
+    pub updated_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+}
+
+impl From<&Subscription> for SubscriptionInfo {
+    fn from(sub: &Subscription) -> Self {
+        Self {
+            id: sub.id.clone(),
+            created_by: sub.created_by.clone(),
+            scope: sub.scope.clone(),
+            url: sub.url.clone(),
+            events: sub.events.clone(),
+            signing: sub.signing,
+            enabled: sub.enabled,
+            created_at: sub.created_at,
+            updated_at: sub.updated_at,
+            secret: None,
+        }
+    }
+}
+
+/// The JSON body POSTed to a subscriber.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WebhookEvent {
+    /// Stable across retries — the receiver's de-duplication key, and
+    /// the `webhook-id` header.
+    pub id: String,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub occurred_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub did: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mnemonic: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+    /// Event-specific detail (`previous_owner`, `version_id`, …).
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub data: Value,
+}
+
+impl WebhookEvent {
+    fn new(event_type: &str) -> Self {
+        Self {
+            id: format!("evt_{}", uuid::Uuid::new_v4().simple()),
+            event_type: event_type.to_string(),
+            occurred_at: now_epoch(),
+            did: None,
+            mnemonic: None,
+            owner: None,
+            domain: None,
+            data: Value::Null,
+        }
+    }
+
+    /// An event about one DID, populated from its (post-mutation) record.
+    /// Records that predate domain tagging fall back to the DID's host.
+    pub fn for_did(event_type: &str, record: &DidRecord, data: Value) -> Self {
+        let domain = if record.domain.is_empty() {
+            record
+                .did_id
+                .as_deref()
+                .and_then(|d| did_hosting_common::server::domain::extract_did_host(d).ok())
+        } else {
+            Some(record.domain.clone())
+        };
+        Self {
+            did: record.did_id.clone(),
+            mnemonic: Some(record.mnemonic.clone()),
+            owner: Some(record.owner.clone()),
+            domain,
+            data,
+            ..Self::new(event_type)
+        }
+    }
+
+    /// An event about a domain itself.
+    pub fn for_domain(event_type: &str, domain: &str) -> Self {
+        Self {
+            domain: Some(domain.to_string()),
+            ..Self::new(event_type)
+        }
+    }
+}
+
+impl Subscription {
+    /// Whether this subscription should receive `event`.
+    pub fn matches(&self, event: &WebhookEvent) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        if !self.events.is_empty() && !self.events.contains(&event.event_type) {
+            return false;
+        }
+        match &self.scope {
+            Scope::Global => true,
+            Scope::Domain { name } => event.domain.as_deref() == Some(name.as_str()),
+            Scope::Owner { did } => {
+                event.owner.as_deref() == Some(did.as_str())
+                    || event.data.get("previous_owner").and_then(Value::as_str)
+                        == Some(did.as_str())
+            }
+        }
+    }
+}
+
+/// One pending POST of an event to a subscription.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Delivery {
+    pub subscription_id: String,
+    pub event: WebhookEvent,
+    pub enqueued_at: u64,
+    pub attempts: u32,
+    pub next_attempt_at: u64,
+    pub last_error: Option<String>,
+}
+
+fn webhooks_ks(store: &Store) -> Result<KeyspaceHandle, AppError> {
+    store.keyspace(KS_WEBHOOKS)
+}
+
+fn sub_key(id: &str) -> Vec<u8> {
+    format!("sub:{id}").into_bytes()
+}
+
+fn queue_prefix(sub_id: &str) -> Vec<u8> {
+    format!("queue:{sub_id}:").into_bytes()
+}
+
+fn queue_key(sub_id: &str, enqueue_micros: u128, uuid_short: &str) -> Vec<u8> {
+    format!("queue:{sub_id}:{enqueue_micros:020}:{uuid_short}").into_bytes()
+}
+
+fn staged_key(enqueue_micros: u128, uuid_short: &str) -> Vec<u8> {
+    format!("event:{enqueue_micros:020}:{uuid_short}").into_bytes()
+}
+
+// ---------------------------------------------------------------------------
+// Subscriptions
+// ---------------------------------------------------------------------------
+
+/// A fresh `whsec_` signing secret.
+pub fn generate_secret() -> String {
+    format!("whsec_{}", BASE64.encode(rand::random::<[u8; 32]>()))
+}
+
+/// Check an endpoint URL and return it normalised.
+///
+/// See [`outbound::validate_url`]: HTTPS only, no embedded credentials,
+/// and no internal targets for non-admin callers. Deliveries for those
+/// callers are also checked at connect time.
+pub fn validate_url(raw: &str, admin: bool) -> Result<String, AppError> {
+    outbound::validate_url(raw, admin, "webhook URL").map(String::from)
+}
+
+/// Check an event filter: every entry must be a known type; duplicates
+/// are dropped.
+pub fn validate_events(events: &[String]) -> Result<Vec<String>, AppError> {
+    let mut out: Vec<String> = Vec::with_capacity(events.len());
+    for e in events {
+        if !EVENT_TYPES.contains(&e.as_str()) {
+            return Err(AppError::Validation(format!(
+                "unknown webhook event type '{e}' (expected one of: {})",
+                EVENT_TYPES.join(", ")
+            )));
+        }
+        if !out.contains(e) {
+            out.push(e.clone());
+        }
+    }
+    Ok(out)
+}
+
+pub async fn get(store: &Store, id: &str) -> Result<Option<Subscription>, AppError> {
+    webhooks_ks(store)?.get(sub_key(id)).await
+}
+
+/// Every subscription, oldest key first.
+pub async fn list(store: &Store) -> Result<Vec<Subscription>, AppError> {
+    let raw = webhooks_ks(store)?
+        .prefix_iter_raw(b"sub:".to_vec())
+        .await?;
+    let mut out = Vec::with_capacity(raw.len());
+    for (k, v) in raw {
+        match serde_json::from_slice::<Subscription>(&v) {
+            Ok(sub) => out.push(sub),
+            Err(e) => warn!(
+                key = %String::from_utf8_lossy(&k),
+                error = %e,
+                "webhooks: skipping malformed subscription"
+            ),
+        }
+    }
+    Ok(out)
+}
+
+pub async fn put(store: &Store, sub: &Subscription) -> Result<(), AppError> {
+    let mut batch = store.batch();
+    stage_put(store, &mut batch, sub)?;
+    batch.commit().await
+}
+
+/// [`put`] as part of `batch`.
+pub fn stage_put(
+    store: &Store,
+    batch: &mut WriteBatch,
+    sub: &Subscription,
+) -> Result<(), AppError> {
+    batch.insert(&webhooks_ks(store)?, sub_key(&sub.id), sub)
+}
+
+/// Remove a subscription and every delivery still queued for it.
+pub async fn delete(store: &Store, id: &str) -> Result<(), AppError> {
+    let mut batch = store.batch();
+    stage_delete(store, &mut batch, id).await?;
+    batch.commit().await
+}
+
+/// [`delete`] as part of `batch`.
+pub async fn stage_delete(store: &Store, batch: &mut WriteBatch, id: &str) -> Result<(), AppError> {
+    let ks = webhooks_ks(store)?;
+    let queued = ks.prefix_iter_raw(queue_prefix(id)).await?;
+    batch.remove(&ks, sub_key(id));
+    for (k, _) in queued {
+        batch.remove(&ks, k);
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Queue
+// ---------------------------------------------------------------------------
+
+/// Queue `event` for every subscription it matches. Returns how many
+/// deliveries were persisted.
+pub async fn enqueue_matching(store: &Store, event: &WebhookEvent) -> Result<usize, AppError> {
+    let mut batch = store.batch();
+    let queued = queue_deliveries(store, &mut batch, event).await?;
+    batch.commit().await?;
+    Ok(queued)
+}
+
+/// Add one delivery row per subscription `event` matches to `batch`.
+async fn queue_deliveries(
+    store: &Store,
+    batch: &mut WriteBatch,
+    event: &WebhookEvent,
+) -> Result<usize, AppError> {
+    let targets: Vec<Subscription> = list(store)
+        .await?
+        .into_iter()
+        .filter(|s| s.matches(event))
+        .collect();
+    let ks = webhooks_ks(store)?;
+    let now = now_epoch();
+    for sub in &targets {
+        let delivery = Delivery {
+            subscription_id: sub.id.clone(),
+            event: event.clone(),
+            enqueued_at: now,
+            attempts: 0,
+            next_attempt_at: now,
+            last_error: None,
+        };
+        let uuid_short = uuid::Uuid::new_v4().simple().to_string();
+        let key = queue_key(&sub.id, outbox::now_micros(), &uuid_short[..12]);
+        batch.insert(&ks, key, &delivery)?;
+    }
+    Ok(targets.len())
+}
+
+/// Fan every staged event out to the subscriptions it matches, oldest
+/// first. An event's deliveries and the removal of its staged row commit
+/// together, so a crash part-way through neither loses nor doubles it.
+/// Returns how many deliveries were queued.
+pub async fn fan_out_staged(store: &Store) -> Result<usize, AppError> {
+    let ks = webhooks_ks(store)?;
+    let mut staged = ks.prefix_iter_raw("event:").await?;
+    staged.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let mut queued = 0;
+    for (key, value) in staged {
+        let mut batch = store.batch();
+        match serde_json::from_slice::<WebhookEvent>(&value) {
+            Ok(event) => queued += queue_deliveries(store, &mut batch, &event).await?,
+            Err(e) => warn!(error = %e, "webhooks: dropping malformed staged event"),
+        }
+        batch.remove(&ks, key);
+        batch.commit().await?;
+    }
+    Ok(queued)
+}
+
+/// Pending deliveries for one subscription, in enqueue order.
+pub async fn list_pending(
+    store: &Store,
+    sub_id: &str,
+) -> Result<Vec<(Vec<u8>, Delivery)>, AppError> {
+    let ks = webhooks_ks(store)?;
+    let raw = ks.prefix_iter_raw(queue_prefix(sub_id)).await?;
+    let mut out = Vec::with_capacity(raw.len());
+    for (k, v) in raw {
+        match serde_json::from_slice::<Delivery>(&v) {
+            Ok(d) => out.push((k, d)),
+            Err(e) => {
+                warn!(subscription = sub_id, error = %e, "webhooks: dropping malformed delivery");
+                let _ = ks.remove(k).await;
+            }
+        }
+    }
+    out.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(out)
+}
+
+/// Remove a delivered (or dropped) queue row.
+pub async fn remove(store: &Store, key: Vec<u8>) -> Result<(), AppError> {
+    webhooks_ks(store)?.remove(key).await
+}
+
+/// Bump attempts + backoff timer + last_error on a queued delivery.
+pub async fn record_failure(
+    store: &Store,
+    key: Vec<u8>,
+    delivery: &Delivery,
+    err: &str,
+) -> Result<(), AppError> {
+    let next = Delivery {
+        attempts: delivery.attempts.saturating_add(1),
+        last_error: Some(outbox::truncated(err)),
+        next_attempt_at: now_epoch().saturating_add(outbox::compute_backoff(delivery.attempts + 1)),
+        ..delivery.clone()
+    };
+    webhooks_ks(store)?.insert(key, &next).await
+}
+
+// ---------------------------------------------------------------------------
+// Emitting
+// ---------------------------------------------------------------------------
+
+/// Stage `event` in `batch`, the write that makes the change it reports,
+/// so the event commits — or fails — with it. The worker fans staged
+/// events out to subscriptions; call [`wake`] once the batch has
+/// committed.
+pub fn emit(store: &Store, batch: &mut WriteBatch, event: &WebhookEvent) -> Result<(), AppError> {
+    let uuid_short = uuid::Uuid::new_v4().simple().to_string();
+    let key = staged_key(outbox::now_micros(), &uuid_short[..12]);
+    batch.insert(&webhooks_ks(store)?, key, event)?;
+    debug!(event = %event.event_type, id = %event.id, "webhook event staged");
+    Ok(())
+}
+
+/// Wake the worker to fan out events staged by a committed batch.
+pub fn wake() {
+    WAKE.notify_one();
+}
+
+/// [`emit`] an event about one DID.
+pub fn emit_did(
+    store: &Store,
+    batch: &mut WriteBatch,
+    event_type: &str,
+    record: &DidRecord,
+    data: Value,
+) -> Result<(), AppError> {
+    emit(
+        store,
+        batch,
+        &WebhookEvent::for_did(event_type, record, data),
+    )
+}
+
+/// [`emit`] the event for a newly committed log: `did.registered` on the
+/// first version, `did.deactivated` when the latest entry deactivates the
+/// DID (the verifier rejects anything after that, so it fires once), and
+/// `did.updated` otherwise.
+pub fn emit_did_log(
+    store: &Store,
+    batch: &mut WriteBatch,
+    record: &DidRecord,
+    did_log: &str,
+) -> Result<(), AppError> {
+    let meta = did_hosting_common::did_ops::extract_log_metadata(did_log);
+    let event_type = if record.version_count <= 1 {
+        DID_REGISTERED
+    } else if record.is_deactivated() {
+        DID_DEACTIVATED
+    } else {
+        DID_UPDATED
+    };
+    let data = serde_json::json!({
+        "version_id": meta.latest_version_id,
+        "version_time": meta.latest_version_time,
+    });
+    emit_did(store, batch, event_type, record, data)
+}
+
+/// Stage an event about a domain on its own and wake the worker. Domain
+/// lifecycle changes are not made in a batch the event could join, so
+/// this runs right after the change and its error is the caller's.
+pub async fn emit_domain(store: &Store, event_type: &str, domain: &str) -> Result<(), AppError> {
+    let mut batch = store.batch();
+    emit(
+        store,
+        &mut batch,
+        &WebhookEvent::for_domain(event_type, domain),
+    )?;
+    batch.commit().await?;
+    wake();
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Signing
+// ---------------------------------------------------------------------------
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac =
+        <Hmac<Sha256> as KeyInit>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// The bytes a signature covers: `{id}.{timestamp}.{body}`.
+fn signed_content(id: &str, timestamp: u64, body: &[u8]) -> Vec<u8> {
+    let mut out = format!("{id}.{timestamp}.").into_bytes();
+    out.extend_from_slice(body);
+    out
+}
+
+/// `webhook-signature` value for an HMAC subscription.
+pub fn hmac_signature(
+    secret: &str,
+    id: &str,
+    timestamp: u64,
+    body: &[u8],
+) -> Result<String, AppError> {
+    let key = BASE64
+        .decode(secret.strip_prefix("whsec_").unwrap_or(secret))
+        .map_err(|e| AppError::Internal(format!("webhook secret is not base64: {e}")))?;
+    let mac = hmac_sha256(&key, &signed_content(id, timestamp, body));
+    Ok(format!("v1,{}", BASE64.encode(mac)))
+}
+
+/// `webhook-signature` value for a DID-signed subscription.
+pub fn did_signature(
+    key: &ed25519_dalek::SigningKey,
+    id: &str,
+    timestamp: u64,
+    body: &[u8],
+) -> String {
+    let sig = key.sign(&signed_content(id, timestamp, body));
+    format!("v1a,{}", BASE64.encode(sig.to_bytes()))
+}
+
+// ---------------------------------------------------------------------------
+// Worker
+// ---------------------------------------------------------------------------
+
+async fn deliver(
+    state: &AppState,
+    sub: &Subscription,
+    delivery: &Delivery,
+    did_key: Option<&(String, ed25519_dalek::SigningKey)>,
+) -> Result<(), String> {
+    let body = serde_json::to_vec(&delivery.event).map_err(|e| e.to_string())?;
+    let id = &delivery.event.id;
+    let timestamp = now_epoch();
+
+    let admin = get_acl_entry(&state.acl_ks, &sub.created_by)
+        .await
+        .map_err(|e| e.to_string())?
+        .is_some_and(|e| e.role == Role::Admin);
+    let mut request = outbound::client(state, admin)
+        .post(&sub.url)
+        .timeout(DELIVERY_TIMEOUT)
+        .header("content-type", "application/json")
+        .header("webhook-id", id)
+        .header("webhook-timestamp", timestamp.to_string());
+    request = match sub.signing {
+        Signing::Hmac => {
+            let sig =
+                hmac_signature(&sub.secret, id, timestamp, &body).map_err(|e| e.to_string())?;
+            request.header("webhook-signature", sig)
+        }
+        Signing::Did => {
+            let (kid, key) =
+                did_key.ok_or("no service identity loaded to sign the delivery with")?;
+            request
+                .header(
+                    "webhook-signature",
+                    did_signature(key, id, timestamp, &body),
+                )
+                .header("webhook-key-id", kid)
+        }
+    };
+
+    let response = request.body(body).send().await.map_err(|e| e.to_string())?;
+    let status = response.status();
+    if status.is_success() {
+        Ok(())
+    } else {
+        Err(format!("endpoint answered HTTP {status}"))
+    }
+}
+
+/// Fan out staged events, then process every subscription's queue once.
+pub async fn run_tick(state: &AppState) -> TickReport {
+    if let Err(e) = fan_out_staged(&state.store).await {
+        // The staged rows stay put; the next tick retries them.
+        warn!(error = %e, "webhook tick: fanning out staged events failed");
+    }
+    let subs = match list(&state.store).await {
+        Ok(s) => s,
+        Err(e) => {
+            warn!(error = %e, "webhook tick: listing subscriptions failed");
+            return TickReport::default();
+        }
+    };
+    // Resolved once per tick; `None` only parks DID-signed subscriptions.
+    let did_key = crate::audit::signing_key(state);
+
+    let mut report = TickReport::default();
+    let now = now_epoch();
+    for sub in subs.iter().filter(|s| s.enabled) {
+        let pending = match list_pending(&state.store, &sub.id).await {
+            Ok(p) => p,
+            Err(e) => {
+                warn!(subscription = %sub.id, error = %e, "webhook tick: list_pending failed");
+                continue;
+            }
+        };
+        for (key, delivery) in pending {
+            if delivery.attempts >= outbox::MAX_ATTEMPTS
+                || now.saturating_sub(delivery.enqueued_at) >= outbox::MAX_AGE_SECS
+            {
+                warn!(
+                    subscription = %sub.id,
+                    event = %delivery.event.event_type,
+                    attempts = delivery.attempts,
+                    age_secs = now.saturating_sub(delivery.enqueued_at),
+                    last_error = delivery.last_error.as_deref().unwrap_or(""),
+                    "webhooks: dropping delivery past retry budget"
+                );
+                let _ = remove(&state.store, key).await;
+                report.dropped += 1;
+                continue;
+            }
+            if delivery.next_attempt_at > now {
+                // Head-of-line still backing off: keep this endpoint's
+                // events in order.
+                report.deferred += 1;
+                break;
+            }
+            match deliver(state, sub, &delivery, did_key.as_ref()).await {
+                Ok(()) => {
+                    info!(
+                        subscription = %sub.id,
+                        event = %delivery.event.event_type,
+                        attempts = delivery.attempts + 1,
+                        "webhook delivered"
+                    );
+                    let _ = remove(&state.store, key).await;
+                    report.delivered += 1;
+                }
+                Err(e) => {
+                    warn!(
+                        subscription = %sub.id,
+                        event = %delivery.event.event_type,
+                        attempts = delivery.attempts + 1,
+                        error = %e,
+                        "webhook delivery failed; will retry after backoff"
+                    );
+                    let _ = record_failure(&state.store, key, &delivery, &e).await;
+                    report.deferred += 1;
+                    break;
+                }
+            }
+        }
+    }
+    report
+}
+
+/// Long-running worker. Wakes on every [`wake`] after a staged event;
+/// falls back to [`DEFAULT_WEBHOOK_TICK`] to retry backed-off deliveries.
+pub async fn run_webhook_loop(state: AppState, mut shutdown: watch::Receiver<bool>) {
+    let mut ticker = tokio::time::interval(DEFAULT_WEBHOOK_TICK);
+    ticker.tick().await; // skip the immediate first tick
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                let report = run_tick(&state).await;
+                if report.delivered > 0 || report.dropped > 0 {
+                    info!(?report, "webhook tick");
+                }
+            }
+            _ = WAKE.notified() => {
+                let report = run_tick(&state).await;
+                if report.delivered > 0 || report.dropped > 0 {
+                    info!(?report, "webhook tick (notified)");
+                }
+            }
+            _ = shutdown.changed() => {
+                info!("webhook worker shutting down");
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use did_hosting_common::server::config::StoreConfig;
+    use ed25519_dalek::{Signature, SigningKey, Verifier};
+    use serde_json::json;
+
+    async fn fjall_store() -> Store {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let cfg = StoreConfig {
+            data_dir: dir.path().to_path_buf(),
+            ..StoreConfig::default()
+        };
+        std::mem::forget(dir);
+        Store::open(&cfg).await.expect("open fjall")
+    }
+
+    fn subscription(id: &str, scope: Scope, events: &[&str]) -> Subscription {
+        Subscription {
+            id: id.to_string(),
+            created_by: "did:example:admin".into(),
+            scope,
+            url: "https://hooks.example.com/in".into(),
+            events: events.iter().map(|e| e.to_string()).collect(),
+            signing: Signing::Hmac,
+            secret: generate_secret(),
+            enabled: true,
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    fn did_event(event_type: &str, owner: &str, domain: &str) -> WebhookEvent {
+        WebhookEvent {
+            owner: Some(owner.into()),
+            domain: Some(domain.into()),
+            mnemonic: Some("alpha".into()),
+            ..WebhookEvent::new(event_type)
+        }
+    }
+
+    #[test]
+    fn hmac_matches_rfc4231_case_2() {
+        let mac = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        let hex: String = mac.iter().map(|b| format!("{b:02x}")).collect();
+        assert_eq!(
+            hex,
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn signatures_cover_id_timestamp_and_body() {
+        let secret = format!("whsec_{}", BASE64.encode(b"Jefe"));
+        let sig = hmac_signature(&secret, "evt_1", 1_700_000_000, b"{}").unwrap();
+        let expected = hmac_sha256(b"Jefe", b"evt_1.1700000000.{}");
+        assert_eq!(sig, format!("v1,{}", BASE64.encode(expected)));
+        assert_ne!(
+            sig,
+            hmac_signature(&secret, "evt_1", 1_700_000_001, b"{}").unwrap(),
+            "timestamp is signed"
+        );
+
+        let key = SigningKey::from_bytes(&[3u8; 32]);
+        let header = did_signature(&key, "evt_1", 1_700_000_000, b"{}");
+        let raw = BASE64.decode(header.strip_prefix("v1a,").unwrap()).unwrap();
+        let sig = Signature::from_slice(&raw).unwrap();
+        key.verifying_key()
+            .verify(b"evt_1.1700000000.{}", &sig)
+            .expect("verifies over the signed content");
+    }
+
+    #[test]
+    fn urls_must_be_https_and_public_for_tenants() {
+        assert!(validate_url("https://hooks.example.com/x", false).is_ok());
+        assert!(validate_url("http://hooks.example.com/x", false).is_err());
+        assert!(validate_url("https://user:pw@hooks.example.com/x", false).is_err());
+        for internal in [
+            "https://localhost/x",
+            "https://127.0.0.1/x",
+            "https://10.1.2.3/x",
+            "https://169.254.169.254/latest",
+            "https://[::1]/x",
+            "https://[fd00::1]/x",
+            "https://[::ffff:192.168.0.1]/x",
+            "https://100.64.0.1/x",
+            "https://198.18.0.1/x",
+        ] {
+            assert!(validate_url(internal, false).is_err(), "{internal}");
+            assert!(
+                validate_url(internal, true).is_ok(),
+                "admins may: {internal}"
+            );
+        }
+    }
+
+    #[test]
+    fn event_filter_rejects_unknown_types_and_dedups() {
+        assert!(validate_events(&["did.exploded".into()]).is_err());
+        let events = validate_events(&[DID_UPDATED.into(), DID_UPDATED.into()]).unwrap();
+        assert_eq!(events, vec![DID_UPDATED.to_string()]);
+    }
+
+    #[test]
+    fn scope_and_filter_matching() {
+        let owner = subscription(
+            "a",
+            Scope::Owner {
+                did: "did:example:alice".into(),
+            },
+            &[],
+        );
+        let domain = subscription(
+            "b",
+            Scope::Domain {
+                name: "example.com".into(),
+            },
+            &[DID_DELETED],
+        );
+        let global = subscription("c", Scope::Global, &[]);
+
+        let updated = did_event(DID_UPDATED, "did:example:alice", "example.com");
+        assert!(owner.matches(&updated));
+        assert!(!domain.matches(&updated), "filtered by event type");
+        assert!(global.matches(&updated));
+
+        let other = did_event(DID_DELETED, "did:example:bob", "example.com");
+        assert!(!owner.matches(&other));
+        assert!(domain.matches(&other));
+
+        // The previous owner hears about the hand-over too.
+        let handed_over = WebhookEvent {
+            data: json!({ "previous_owner": "did:example:alice" }),
+            ..did_event(DID_OWNER_CHANGED, "did:example:bob", "example.com")
+        };
+        assert!(owner.matches(&handed_over));
+
+        let disabled = Subscription {
+            enabled: false,
+            ..global.clone()
+        };
+        assert!(!disabled.matches(&updated));
+    }
+
+    #[tokio::test]
+    async fn events_queue_per_subscription_in_order() {
+        let store = fjall_store().await;
+        let alice = subscription(
+            "alice",
+            Scope::Owner {
+                did: "did:example:alice".into(),
+            },
+            &[],
+        );
+        put(&store, &alice).await.unwrap();
+        put(&store, &subscription("all", Scope::Global, &[]))
+            .await
+            .unwrap();
+
+        let first = did_event(DID_REGISTERED, "did:example:alice", "example.com");
+        let second = did_event(DID_UPDATED, "did:example:alice", "example.com");
+        let bob = did_event(DID_UPDATED, "did:example:bob", "example.com");
+        assert_eq!(enqueue_matching(&store, &first).await.unwrap(), 2);
+        assert_eq!(enqueue_matching(&store, &second).await.unwrap(), 2);
+        assert_eq!(enqueue_matching(&store, &bob).await.unwrap(), 1);
+
+        let pending = list_pending(&store, "alice").await.unwrap();
+        let types: Vec<_> = pending
+            .iter()
+            .map(|(_, d)| d.event.event_type.as_str())
+            .collect();
+        assert_eq!(types, [DID_REGISTERED, DID_UPDATED]);
+        assert_eq!(list_pending(&store, "all").await.unwrap().len(), 3);
+
+        let (key, head) = pending[0].clone();
+        record_failure(&store, key, &head, "endpoint answered HTTP 503")
+            .await
+            .unwrap();
+        let after = list_pending(&store, "alice").await.unwrap();
+        assert_eq!(after[0].1.attempts, 1);
+        assert_eq!(
+            after[0].1.event.id, head.event.id,
+            "id is stable across retries"
+        );
+        assert!(after[0].1.next_attempt_at > head.next_attempt_at);
+
+        delete(&store, "alice").await.unwrap();
+        assert!(get(&store, "alice").await.unwrap().is_none());
+        assert!(list_pending(&store, "alice").await.unwrap().is_empty());
+        assert_eq!(list_pending(&store, "all").await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn staged_events_are_queued_only_once_their_batch_commits() {
+        let store = fjall_store().await;
+        put(&store, &subscription("all", Scope::Global, &[]))
+            .await
+            .unwrap();
+        let event = did_event(DID_UPDATED, "did:example:alice", "example.com");
+
+        let mut dropped = store.batch();
+        emit(&store, &mut dropped, &event).unwrap();
+        drop(dropped);
+        assert_eq!(fan_out_staged(&store).await.unwrap(), 0);
+
+        let mut batch = store.batch();
+        emit(&store, &mut batch, &event).unwrap();
+        batch.commit().await.unwrap();
+        assert_eq!(fan_out_staged(&store).await.unwrap(), 1);
+        assert_eq!(
+            fan_out_staged(&store).await.unwrap(),
+            0,
+            "staged row consumed"
+        );
+        let pending = list_pending(&store, "all").await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].1.event.id, event.id);
+    }
+}
@@ -46,6 +46,8 @@ async fn bind_name(h: &TestServer, mnemonic: &str, did_id: &str, name: &str, ena
             enabled,
             created_at: 0,
         }],
+        deactivated_at: None,
+        sync_head: None,
     };
     h.state
         .dids_ks
@@ -334,6 +334,8 @@ async fn seed_server_own_names(h: &TestServer, did_id: &str, names: &[(&str, boo
                 created_at: 0,
             })
             .collect(),
+        deactivated_at: None,
+        sync_head: None,
     };
     h.put_did(&record).await;
 }
@@ -448,6 +450,8 @@ async fn seed_named_did(h: &TestServer, mnemonic: &str, did_id: &str, names: &[(
                 created_at: 0,
             })
             .collect(),
+        deactivated_at: None,
+        sync_head: None,
     };
     h.put_did(&record).await;
 }
@@ -77,6 +77,7 @@ async fn make_state() -> (AppState, tempfile::TempDir) {
         trust_tasks: Default::default(),
         hosting: Default::default(),
         identity: Default::default(),
+        consent: Default::default(),
         config_path: PathBuf::new(),
     };
 
@@ -106,7 +107,6 @@ async fn make_state() -> (AppState, tempfile::TempDir) {
             did_hosting_control::pending_challenges::PendingChallengeTracker::new(),
         ),
         ip_rate_limiter: Arc::new(did_hosting_control::rate_limit::IpRateLimiter::new()),
-        pending_confirms: Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
         outbox_notify: Arc::new(tokio::sync::Notify::new()),
     };
 
@@ -140,6 +140,7 @@ fn auth_for(did: &str, role: Role) -> AuthClaims {
         session_id: String::new(),
         amr: vec!["did".to_string()],
         acr: "aal1".to_string(),
+        api_key: None,
     }
 }
 
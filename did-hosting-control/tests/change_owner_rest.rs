@@ -81,6 +81,7 @@ async fn make_harness() -> Harness {
         trust_tasks: Default::default(),
         hosting: Default::default(),
         identity: Default::default(),
+        consent: Default::default(),
         config_path: PathBuf::new(),
     };
 
@@ -112,7 +113,6 @@ async fn make_harness() -> Harness {
             did_hosting_control::pending_challenges::PendingChallengeTracker::new(),
         ),
         ip_rate_limiter: Arc::new(did_hosting_control::rate_limit::IpRateLimiter::new()),
-        pending_confirms: Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
         outbox_notify: Arc::new(tokio::sync::Notify::new()),
     };
 
@@ -178,6 +178,8 @@ async fn seed_did(state: &AppState, owner_did: &str, mnemonic: &str) {
         domain: String::new(),
         services: None,
         agent_names: Vec::new(),
+        deactivated_at: None,
+        sync_head: None,
     };
     let mut batch = state.store.batch();
     batch
@@ -61,6 +61,7 @@ async fn make_state() -> (AppState, tempfile::TempDir) {
         trust_tasks: Default::default(),
         hosting: Default::default(),
         identity: Default::default(),
+        consent: Default::default(),
         config_path: PathBuf::new(),
     };
 
@@ -90,7 +91,6 @@ async fn make_state() -> (AppState, tempfile::TempDir) {
             did_hosting_control::pending_challenges::PendingChallengeTracker::new(),
         ),
         ip_rate_limiter: Arc::new(did_hosting_control::rate_limit::IpRateLimiter::new()),
-        pending_confirms: Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
         outbox_notify: Arc::new(tokio::sync::Notify::new()),
     };
 
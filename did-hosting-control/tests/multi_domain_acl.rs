@@ -68,6 +68,7 @@ async fn make_state() -> (AppState, tempfile::TempDir) {
         trust_tasks: Default::default(),
         hosting: Default::default(),
         identity: Default::default(),
+        consent: Default::default(),
         config_path: PathBuf::new(),
     };
 
@@ -97,7 +98,6 @@ async fn make_state() -> (AppState, tempfile::TempDir) {
             did_hosting_control::pending_challenges::PendingChallengeTracker::new(),
         ),
         ip_rate_limiter: Arc::new(did_hosting_control::rate_limit::IpRateLimiter::new()),
-        pending_confirms: Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
         outbox_notify: Arc::new(tokio::sync::Notify::new()),
     };
 
@@ -157,6 +157,7 @@ fn auth(did: &str) -> AuthClaims {
         session_id: String::new(),
         amr: vec!["did".to_string()],
         acr: "aal1".to_string(),
+        api_key: None,
     }
 }
 
@@ -174,6 +175,7 @@ fn req(path: &str, domain_field: Option<&str>) -> DidRegisterRequest {
         method: None,
         did_data: None,
         domain: domain_field.map(|s| s.into()),
+        did_document: None,
         did_log: Some(
             r#"{"versionId":"1-x","state":{"id":"did:webvh:Q1:a.example:alpha"}}"#.into(),
         ),
@@ -211,6 +213,7 @@ async fn allowed_owner_on_authorised_domain_passes_resolve_gate() {
         &state,
         &request.path,
         &request.did_log.clone().unwrap(),
+        None,
         false,
     )
     .await
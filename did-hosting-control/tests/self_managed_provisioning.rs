@@ -66,6 +66,7 @@ async fn tenant_provisioning_succeeds_with_self_managed_config() {
         trust_tasks: Default::default(),
         hosting: Default::default(),
         identity: Default::default(),
+        consent: Default::default(),
         config_path: PathBuf::new(),
     };
 
@@ -97,7 +98,6 @@ async fn tenant_provisioning_succeeds_with_self_managed_config() {
             did_hosting_control::pending_challenges::PendingChallengeTracker::new(),
         ),
         ip_rate_limiter: Arc::new(did_hosting_control::rate_limit::IpRateLimiter::new()),
-        pending_confirms: Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
         outbox_notify: Arc::new(tokio::sync::Notify::new()),
     };
 
@@ -126,6 +126,7 @@ async fn tenant_provisioning_succeeds_with_self_managed_config() {
         session_id: String::new(),
         amr: vec!["did".to_string()],
         acr: "aal1".to_string(),
+        api_key: None,
     };
 
     // 5. Phase 1 — tenant requests a DID slot (analog to MSG_DID_REQUEST).
@@ -1,5 +1,5 @@
 //! Boot-time backfill of the per-DID service-badge cache on a standalone
-//! control plane (`server::backfill_service_badges`).
+//! control plane (`server::backfill_did_records`, which also runs `M-03`).
 //!
 //! The control plane has never invoked the migration runner. Adding badges
 //! must not become a back door for running every other migration against
@@ -18,7 +18,7 @@ use std::path::PathBuf;
 use did_hosting_common::did_ops::{DidRecord, content_log_key, did_key};
 use did_hosting_common::server::config::StoreConfig;
 use did_hosting_common::server::store::{KS_DIDS, Store};
-use did_hosting_control::server::backfill_service_badges;
+use did_hosting_control::server::backfill_did_records;
 
 async fn temp_store() -> (Store, tempfile::TempDir) {
     let dir = tempfile::tempdir().expect("temp dir");
@@ -47,6 +47,8 @@ fn legacy_record(mnemonic: &str) -> DidRecord {
         domain: String::new(),
         services: None,
         agent_names: Vec::new(),
+        deactivated_at: None,
+        sync_head: None,
     }
 }
 
@@ -83,7 +85,7 @@ async fn backfill_populates_services_and_leaves_domain_untouched() {
     let (store, _dir) = temp_store().await;
     seed(&store, "legacy").await;
 
-    backfill_service_badges(&store).await;
+    backfill_did_records(&store).await;
 
     let rec = load(&store, "legacy").await;
     assert_eq!(
@@ -110,10 +112,10 @@ async fn backfill_is_idempotent_across_boots() {
     let (store, _dir) = temp_store().await;
     seed(&store, "legacy").await;
 
-    backfill_service_badges(&store).await;
+    backfill_did_records(&store).await;
     let after_first = load(&store, "legacy").await.services;
 
-    backfill_service_badges(&store).await;
+    backfill_did_records(&store).await;
     let after_second = load(&store, "legacy").await.services;
 
     assert_eq!(after_first, after_second);
@@ -125,5 +127,5 @@ async fn backfill_is_idempotent_across_boots() {
 #[tokio::test]
 async fn backfill_on_empty_store_is_a_noop() {
     let (store, _dir) = temp_store().await;
-    backfill_service_badges(&store).await;
+    backfill_did_records(&store).await;
 }
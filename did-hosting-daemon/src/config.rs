@@ -36,6 +36,8 @@ pub struct DaemonConfig {
     #[serde(default)]
     pub limits: did_hosting_server::config::LimitsConfig,
     #[serde(default)]
+    pub cache: did_hosting_server::config::CacheConfig,
+    #[serde(default)]
     pub watchers: Vec<did_hosting_server::config::WatcherEndpoint>,
 
     // Witness-specific
@@ -49,6 +51,9 @@ pub struct DaemonConfig {
     // Control-specific
     #[serde(default)]
     pub registry: did_hosting_control::config::RegistryConfig,
+    /// Multi-party approval policies for sensitive admin actions.
+    #[serde(default)]
+    pub consent: did_hosting_control::config::ConsentConfig,
 
     /// Feature flags (didcomm, rest_api).
     #[serde(default)]
@@ -188,6 +193,8 @@ impl DaemonConfig {
             *url = url.trim_end_matches('/').to_string();
         }
 
+        config.consent.validate()?;
+
         Ok(config)
     }
 
@@ -210,6 +217,7 @@ impl DaemonConfig {
             control_did: None,
             vta: self.vta.clone(),
             stats: did_hosting_server::config::StatsConfig::default(),
+            cache: self.cache.clone(),
             // Carried through for completeness. The embedded server runs no
             // DIDComm listener of its own, so its rotation path is inert — the
             // daemon's control plane owns the identity.
@@ -265,6 +273,7 @@ impl DaemonConfig {
             vta: self.vta.clone(),
             registry: self.registry.clone(),
             trust_tasks: did_hosting_control::config::TrustTasksConfig::default(),
+            consent: self.consent.clone(),
             hosting: self.hosting.clone(),
             // The daemon's control plane owns the only DIDComm listener, so it
             // is the one that rotates — it needs the daemon's grace period, not
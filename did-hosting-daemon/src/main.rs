@@ -16,6 +16,7 @@ use tokio_util::sync::CancellationToken;
 use tower_http::trace::{DefaultMakeSpan, DefaultOnResponse, TraceLayer};
 use tracing::{Level, debug, error, info, warn};
 
+use did_hosting_common::server::backup::{BackupRequest, KeyspaceSelection, RestoreRequest};
 use did_hosting_common::server::config::init_tracing;
 use did_hosting_common::server::error::AppError;
 use did_hosting_common::server::identity::ServiceIdentity;
@@ -23,10 +24,12 @@ use did_hosting_common::server::init;
 use did_hosting_common::server::secret_store::ServerSecrets;
 use did_hosting_common::server::stats_collector::StatsCollector;
 use did_hosting_common::server::store::{KeyspaceHandle, Store};
+use did_hosting_common::server::store_migrate::MigrateRequest;
 
 use config::DaemonConfig;
 use did_hosting_common::server::store::{
-    KS_ACL, KS_DIDS, KS_REGISTRY, KS_SESSIONS, KS_STATS, KS_TIMESERIES, KS_WITNESSES,
+    KS_ACL, KS_DIDS, KS_REGISTRY, KS_SESSIONS, KS_STATS, KS_TIMESERIES, KS_WATCHER_EVENTS,
+    KS_WITNESSES,
 };
 
 #[derive(Parser)]
@@ -271,14 +274,77 @@ enum Command {
     /// Export server data to a backup file
     Backup {
         /// Output file path (use "-" for stdout)
-        #[arg(short, long, default_value = "webvh-backup.json")]
+        #[arg(short, long, default_value = "webvh-backup.jsonl")]
         output: String,
+        /// Back up only these keyspaces (repeatable or comma-separated).
+        #[arg(long = "keyspace", value_delimiter = ',')]
+        keyspaces: Vec<String>,
+        /// Leave these keyspaces out.
+        #[arg(long = "exclude-keyspace", value_delimiter = ',')]
+        exclude_keyspaces: Vec<String>,
+        /// Encrypt to this age X25519 recipient (`age1…`; repeatable). The
+        /// backup can then only be read with the matching identity file.
+        #[arg(long = "recipient")]
+        recipients: Vec<String>,
+        /// Incremental: write only what changed or was deleted since this
+        /// earlier backup (full or incremental).
+        #[arg(long)]
+        incremental_from: Option<PathBuf>,
+        /// age identity file, to read an encrypted `--incremental-from`.
+        #[arg(long = "identity")]
+        identities: Vec<PathBuf>,
     },
     /// Restore server data from a backup file
     Restore {
         /// Input backup file path
         #[arg(short, long)]
         input: String,
+        /// Restore only these keyspaces (repeatable or comma-separated).
+        #[arg(long = "keyspace", value_delimiter = ',')]
+        keyspaces: Vec<String>,
+        /// Leave these keyspaces out.
+        #[arg(long = "exclude-keyspace", value_delimiter = ',')]
+        exclude_keyspaces: Vec<String>,
+        /// age identity file, for an encrypted backup (repeatable).
+        #[arg(long = "identity")]
+        identities: Vec<PathBuf>,
+        /// Report what would be added, overwritten and kept, per keyspace,
+        /// without writing the config or the store.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Check a backup file's digest and entries without restoring it
+    VerifyBackup {
+        /// Input backup file path
+        #[arg(short, long)]
+        input: String,
+        /// age identity file, for an encrypted backup (repeatable).
+        #[arg(long = "identity")]
+        identities: Vec<PathBuf>,
+    },
+    /// Copy every keyspace into another storage backend.
+    ///
+    /// Stop the service first. Both backends' `store-*` features must be
+    /// compiled into this binary.
+    StoreMigrate {
+        /// Config file whose [store] is the source
+        #[arg(long)]
+        from: PathBuf,
+        /// Config file whose [store] is the target
+        #[arg(long)]
+        to: PathBuf,
+        /// Progress file; an interrupted run resumes from it
+        #[arg(long, default_value = "store-migrate.checkpoint")]
+        checkpoint: PathBuf,
+        /// Migrate only these keyspaces (repeatable or comma-separated).
+        #[arg(long = "keyspace", value_delimiter = ',')]
+        keyspaces: Vec<String>,
+        /// Leave these keyspaces out.
+        #[arg(long = "exclude-keyspace", value_delimiter = ',')]
+        exclude_keyspaces: Vec<String>,
+        /// Compare the two stores without copying anything
+        #[arg(long)]
+        verify_only: bool,
     },
     /// Migrate a legacy `webvh-*` config file to the new `did-hosting-*`
     /// shape (env-var renames, repo-rename pointer updates).
@@ -499,18 +565,79 @@ async fn main() {
                 std::process::exit(1);
             }
         }
-        Some(Command::Backup { output }) => {
-            if let Err(e) = did_hosting_server::backup::run_backup(cli.config, output).await {
+        Some(Command::Backup {
+            output,
+            keyspaces,
+            exclude_keyspaces,
+            recipients,
+            incremental_from,
+            identities,
+        }) => {
+            let req = BackupRequest {
+                output,
+                selection: KeyspaceSelection {
+                    only: keyspaces,
+                    exclude: exclude_keyspaces,
+                },
+                recipients,
+                incremental_from,
+                identities,
+            };
+            if let Err(e) = did_hosting_server::backup::run_backup(cli.config, req).await {
                 eprintln!("Backup error: {e}");
                 std::process::exit(1);
             }
         }
-        Some(Command::Restore { input }) => {
-            if let Err(e) = did_hosting_server::backup::run_restore(cli.config, input).await {
+        Some(Command::Restore {
+            input,
+            keyspaces,
+            exclude_keyspaces,
+            identities,
+            dry_run,
+        }) => {
+            let req = RestoreRequest {
+                input,
+                selection: KeyspaceSelection {
+                    only: keyspaces,
+                    exclude: exclude_keyspaces,
+                },
+                identities,
+                dry_run,
+            };
+            if let Err(e) = did_hosting_server::backup::run_restore(cli.config, req).await {
                 eprintln!("Restore error: {e}");
                 std::process::exit(1);
             }
         }
+        Some(Command::VerifyBackup { input, identities }) => {
+            if let Err(e) = did_hosting_server::backup::run_verify_backup(input, identities) {
+                eprintln!("Backup verification failed: {e}");
+                std::process::exit(1);
+            }
+        }
+        Some(Command::StoreMigrate {
+            from,
+            to,
+            checkpoint,
+            keyspaces,
+            exclude_keyspaces,
+            verify_only,
+        }) => {
+            let req = MigrateRequest {
+                from,
+                to,
+                checkpoint,
+                selection: KeyspaceSelection {
+                    only: keyspaces,
+                    exclude: exclude_keyspaces,
+                },
+                verify_only,
+            };
+            if let Err(e) = run_store_migrate(req).await {
+                eprintln!("Store migration error: {e}");
+                std::process::exit(1);
+            }
+        }
         Some(Command::MigrateFromWebvhConfig {
             input,
             output,
@@ -525,6 +652,40 @@ async fn main() {
     }
 }
 
+/// `store-migrate` for the daemon. `[store]` holds every service's
+/// keyspaces; the witness keeps its own in `[witness_store]`. Each store is
+/// migrated with its own checkpoint (`<checkpoint>` and
+/// `<checkpoint>.witness.checkpoint`), and the keyspace selection applies
+/// to both.
+async fn run_store_migrate(req: MigrateRequest) -> Result<(), AppError> {
+    use did_hosting_common::server::store::ALL_KEYSPACES;
+    use did_hosting_common::server::store_migrate::run_store_migrate;
+
+    run_store_migrate(&req, "store", ALL_KEYSPACES, None).await?;
+
+    let witness_keyspaces = webvh_witness::backup::KEYSPACES;
+    let narrow = |names: &[String]| -> Vec<String> {
+        names
+            .iter()
+            .filter(|n| witness_keyspaces.contains(&n.as_str()))
+            .cloned()
+            .collect()
+    };
+    let only = narrow(&req.selection.only);
+    if !req.selection.only.is_empty() && only.is_empty() {
+        return Ok(());
+    }
+    let witness_req = MigrateRequest {
+        checkpoint: req.checkpoint.with_extension("witness.checkpoint"),
+        selection: KeyspaceSelection {
+            only,
+            exclude: narrow(&req.selection.exclude),
+        },
+        ..req
+    };
+    run_store_migrate(&witness_req, "witness_store", witness_keyspaces, None).await
+}
+
 /// Skeleton for the legacy-config migration subcommand. Verifies the
 /// input file exists and prints a clear "not yet implemented in v0.7.0"
 /// message pointing to the rollout plan. The full rewriter lands in the
@@ -1059,6 +1220,17 @@ async fn run_daemon(config_path: Option<PathBuf>) {
         None
     };
 
+    // 4d. Webhook worker. Delivers DID / domain lifecycle events to
+    // subscribed HTTPS endpoints; independent of DIDComm.
+    let (webhook_shutdown_tx, webhook_shutdown_rx) = watch::channel(false);
+    let webhook_handle = control_state.as_ref().map(|state| {
+        let webhook_state = state.clone();
+        tokio::spawn(async move {
+            did_hosting_control::webhooks::run_webhook_loop(webhook_state, webhook_shutdown_rx)
+                .await;
+        })
+    });
+
     // Wait for HTTP server to complete (shutdown signal received)
     let _ = http_handle.await;
 
@@ -1076,6 +1248,14 @@ async fn run_daemon(config_path: Option<PathBuf>) {
         }
     }
 
+    let _ = webhook_shutdown_tx.send(true);
+    if let Some(handle) = webhook_handle {
+        match handle.await {
+            Ok(()) => info!("webhook worker stopped"),
+            Err(e) => warn!("webhook worker didn't shut down cleanly: {e}"),
+        }
+    }
+
     // 5b. Stop storage task (includes final flush + persist main_store)
     let _ = storage_shutdown_tx.send(true);
     match storage_handle.await {
@@ -1287,8 +1467,8 @@ async fn build_server(
         signing_key_bytes,
         http_client: http_client.clone(),
         stats_collector: Some(stats_collector.clone()),
-        did_cache: Arc::new(did_hosting_server::cache::ContentCache::new(
-            Duration::from_secs(300),
+        did_cache: Arc::new(did_hosting_server::cache::ContentCache::from_config(
+            &config.cache,
         )),
         trusted_proxy_cidrs: Arc::new(parsed_cidrs),
     };
@@ -1347,10 +1527,12 @@ async fn build_watcher(config: &DaemonConfig, store: &Store) -> ServiceResult {
 
     let watcher_config = config.watcher_config();
     let dids_ks = store.keyspace(KS_DIDS)?;
+    let events_ks = store.keyspace(KS_WATCHER_EVENTS)?;
 
     let state = AppState {
         store: store.clone(),
         dids_ks,
+        events_ks,
         config: Arc::new(watcher_config),
     };
 
@@ -1441,7 +1623,6 @@ async fn build_control(
         pending_challenges: Arc::new(
             did_hosting_control::pending_challenges::PendingChallengeTracker::new(),
         ),
-        pending_confirms: Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
         outbox_notify: Arc::new(tokio::sync::Notify::new()),
         ip_rate_limiter: Arc::new(did_hosting_control::rate_limit::IpRateLimiter::new()),
     };
@@ -1706,32 +1887,14 @@ async fn run_bootstrap_did(
             {
                 eprintln!("  Warning: witness authentication failed: {e}");
             } else {
-                let version_id = result
-                    .jsonl
-                    .lines()
-                    .last()
-                    .and_then(|line| serde_json::from_str::<serde_json::Value>(line).ok())
-                    .and_then(|v| {
-                        v.get("versionId")
-                            .and_then(|id| id.as_str())
-                            .map(String::from)
-                    });
-
-                if let Some(vid) = version_id {
-                    match witness_client.request_proof(&w_id, &vid).await {
-                        Ok(proof) => {
-                            let proof_json = serde_json::to_string(&proof)?;
-                            dids_ks
-                                .insert_raw(
-                                    did_hosting_server::did_ops::content_witness_key(&mnemonic),
-                                    proof_json.into_bytes(),
-                                )
-                                .await?;
-                            eprintln!("  Witness proof stored.");
-                        }
-                        Err(e) => {
-                            eprintln!("  Warning: witness proof request failed: {e}");
-                        }
+                match witness_client.request_proof(&w_id, &result.jsonl).await {
+                    Ok(proof) => {
+                        let proof_json = serde_json::to_string(&proof)?;
+                        bootstrap::store_witness(&store, &dids_ks, &mnemonic, &proof_json).await?;
+                        eprintln!("  Witness proof stored.");
+                    }
+                    Err(e) => {
+                        eprintln!("  Warning: witness proof request failed: {e}");
                     }
                 }
             }
@@ -1792,6 +1955,7 @@ async fn run_recreate_did(
         .get::<did_hosting_common::did_ops::DidRecord>(did_key.clone())
         .await?
     {
+        did_hosting_server::precompressed::remove_did(&dids_ks, &mnemonic).await?;
         dids_ks.remove(did_key).await?;
         dids_ks
             .remove(did_hosting_server::did_ops::content_log_key(&mnemonic))
@@ -2273,6 +2437,7 @@ async fn run_remove_did(
     eprintln!("  Owner:  {}", record.owner);
 
     let mut batch = store.batch();
+    did_hosting_server::precompressed::stage_did_removal(&mut batch, &dids_ks, &path).await?;
     batch.remove(&dids_ks, did_key(&path));
     batch.remove(&dids_ks, content_log_key(&path));
     batch.remove(&dids_ks, content_witness_key(&path));
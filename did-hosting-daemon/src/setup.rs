@@ -213,6 +213,7 @@ pub async fn run_wizard(
             ..StoreConfig::default()
         },
         limits: did_hosting_server::config::LimitsConfig::default(),
+        cache: did_hosting_server::config::CacheConfig::default(),
         watchers: Vec::new(),
         vta: VtaConfig {
             url: outcome.vta_url.clone(),
@@ -221,6 +222,7 @@ pub async fn run_wizard(
         },
         watcher_sync: webvh_watcher::config::SyncConfig::default(),
         registry: did_hosting_control::config::RegistryConfig::default(),
+        consent: Default::default(),
         features,
         identity: IdentityConfig::default(),
         enable,
@@ -1039,10 +1041,12 @@ async fn run_self_managed_setup(
             ..StoreConfig::default()
         },
         limits: did_hosting_server::config::LimitsConfig::default(),
+        cache: did_hosting_server::config::CacheConfig::default(),
         watchers: Vec::new(),
         vta: VtaConfig::default(),
         watcher_sync: webvh_watcher::config::SyncConfig::default(),
         registry: did_hosting_control::config::RegistryConfig::default(),
+        consent: Default::default(),
         features,
         identity: IdentityConfig {
             mode: IdentityMode::SelfManaged,
@@ -1406,6 +1410,7 @@ pub async fn run_setup_offline_complete(
             ..StoreConfig::default()
         },
         limits: did_hosting_server::config::LimitsConfig::default(),
+        cache: did_hosting_server::config::CacheConfig::default(),
         watchers: Vec::new(),
         vta: VtaConfig {
             url: result.vta_url.clone(),
@@ -1414,6 +1419,7 @@ pub async fn run_setup_offline_complete(
         },
         watcher_sync: webvh_watcher::config::SyncConfig::default(),
         registry: did_hosting_control::config::RegistryConfig::default(),
+        consent: Default::default(),
         features: state.features.clone(),
         identity: IdentityConfig::default(),
         hosting: did_hosting_common::server::config::HostingConfig::default(),
@@ -250,6 +250,7 @@ pub async fn apply_recipe(
             ..StoreConfig::default()
         },
         limits: did_hosting_server::config::LimitsConfig::default(),
+        cache: did_hosting_server::config::CacheConfig::default(),
         watchers: Vec::new(),
         vta: VtaConfig {
             url: vta_url,
@@ -258,6 +259,7 @@ pub async fn apply_recipe(
         },
         watcher_sync: webvh_watcher::config::SyncConfig::default(),
         registry: did_hosting_control::config::RegistryConfig::default(),
+        consent: Default::default(),
         features,
         identity: IdentityConfig {
             mode: identity_mode,
@@ -7,6 +7,7 @@ use affinidi_tdk::secrets_resolver::secrets::Secret;
 use did_hosting_common::did::{
     DidDocumentOptions, build_did_document, create_log_entry, encode_host,
 };
+use did_hosting_common::sync_head;
 use tracing::info;
 
 use crate::auth::session::now_epoch;
@@ -15,6 +16,7 @@ use crate::did_ops::{
     extract_service_types, owner_key, validate_did_jsonl,
 };
 use crate::error::AppError;
+use crate::precompressed::Variants;
 use crate::store::{KeyspaceHandle, Store};
 
 /// Result of bootstrapping the root DID.
@@ -145,8 +147,11 @@ pub async fn bootstrap_did(
         // what `build_did_document` just wrote into the doc.
         services: extract_service_types(&jsonl),
         agent_names: Vec::new(),
+        deactivated_at: None,
+        sync_head: Some(sync_head(jsonl.as_bytes(), None)),
     };
 
+    let variants = Variants::build(dids_ks, &content_log_key(&mnemonic), jsonl.as_bytes()).await?;
     let mut batch = store.batch();
     batch.insert(dids_ks, did_key(&mnemonic), &record)?;
     batch.insert_raw(
@@ -154,6 +159,7 @@ pub async fn bootstrap_did(
         content_log_key(&mnemonic),
         jsonl.as_bytes().to_vec(),
     );
+    variants.stage(&mut batch, dids_ks);
     batch.insert_raw(
         dids_ks,
         owner_key("system", &mnemonic),
@@ -191,6 +197,29 @@ pub async fn import_root_did(
     import_did_at_path(store, dids_ks, ".well-known", jsonl, witness_content).await
 }
 
+/// Store a witness file for a DID created by [`bootstrap_did`] or
+/// [`import_did_at_path`], keeping the record's sync head in step.
+pub async fn store_witness(
+    store: &Store,
+    dids_ks: &KeyspaceHandle,
+    mnemonic: &str,
+    witness: &str,
+) -> Result<(), AppError> {
+    let witness_key = content_witness_key(mnemonic);
+    let variants = Variants::build(dids_ks, &witness_key, witness.as_bytes()).await?;
+    let mut batch = store.batch();
+    if let Some(mut record) = dids_ks.get::<DidRecord>(did_key(mnemonic)).await? {
+        match dids_ks.get_raw(content_log_key(mnemonic)).await? {
+            Some(log) => record.set_sync_head(&log, Some(witness.as_bytes())),
+            None => record.sync_head = None,
+        }
+        batch.insert(dids_ks, did_key(mnemonic), &record)?;
+    }
+    batch.insert_raw(dids_ks, witness_key, witness.as_bytes().to_vec());
+    variants.stage(&mut batch, dids_ks);
+    batch.commit().await
+}
+
 /// Import an existing DID at an arbitrary path (mnemonic).
 ///
 /// Generalisation of `import_root_did` — works for any mnemonic, not just
@@ -252,8 +281,15 @@ pub async fn import_did_at_path(
         services: extract_service_types(jsonl),
 
         agent_names: Vec::new(),
+        deactivated_at: None,
+        sync_head: Some(sync_head(
+            jsonl.as_bytes(),
+            witness_content.map(str::as_bytes),
+        )),
     };
 
+    let variants =
+        Variants::build(dids_ks, &content_log_key(&mnemonic_str), jsonl.as_bytes()).await?;
     let mut batch = store.batch();
     batch.insert(dids_ks, did_key(&mnemonic_str), &record)?;
     batch.insert_raw(
@@ -261,17 +297,17 @@ pub async fn import_did_at_path(
         content_log_key(&mnemonic_str),
         jsonl.as_bytes().to_vec(),
     );
+    variants.stage(&mut batch, dids_ks);
     batch.insert_raw(
         dids_ks,
         owner_key("system", &mnemonic_str),
         mnemonic_str.as_bytes().to_vec(),
     );
     if let Some(witness) = witness_content {
-        batch.insert_raw(
-            dids_ks,
-            content_witness_key(&mnemonic_str),
-            witness.as_bytes().to_vec(),
-        );
+        let witness_key = content_witness_key(&mnemonic_str);
+        let variants = Variants::build(dids_ks, &witness_key, witness.as_bytes()).await?;
+        batch.insert_raw(dids_ks, witness_key, witness.as_bytes().to_vec());
+        variants.stage(&mut batch, dids_ks);
     }
     batch.commit().await?;
 
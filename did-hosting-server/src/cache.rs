@@ -1,76 +1,545 @@
-//! Simple in-memory content cache with TTL-based eviction.
+//! Bounded in-memory content cache.
 //!
-//! Used to cache DID document content (`did.jsonl`) to reduce store lookups
-//! on the hot DID resolution path. Entries are evicted on read after TTL expires.
-//! Uses `Arc<Vec<u8>>` to avoid cloning large documents on cache hits.
+//! Used to cache DID content (`did.jsonl`, witness files, materialised
+//! versions) to reduce store lookups on the hot DID resolution path.
+//!
+//! The cache is split into shards by key hash, each behind its own mutex,
+//! so concurrent resolves of different DIDs rarely contend. The byte budget
+//! and entry cap (`[cache]`, see [`CacheConfig`]) are divided evenly across
+//! shards. Within a shard the least recently used entry goes first, but a
+//! new key only displaces it if the key has been asked for more often
+//! recently (a TinyLFU-style frequency sketch): a crawler walking every
+//! mnemonic once cannot flush the DIDs that are actually being resolved.
+//!
+//! A store read that found nothing can be remembered too, for the shorter
+//! `negative_ttl_secs`, so repeated 404s don't each reach the store.
+//!
+//! Data is stored behind `Arc` so hits return a cheap reference-counted
+//! pointer instead of cloning the document.
 
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, RandomState};
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-struct CacheEntry {
-    data: Arc<Vec<u8>>,
-    inserted_at: Instant,
+use crate::config::CacheConfig;
+
+/// Estimated bookkeeping cost of one entry on top of its key and value:
+/// the map slot, the recency-order node and the `Arc`/`Vec` headers.
+const ENTRY_OVERHEAD: usize = 128;
+
+/// What a lookup found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cached {
+    /// The stored bytes.
+    Hit(Arc<Vec<u8>>),
+    /// The store was read recently and had nothing under this key.
+    Missing,
+}
+
+/// Counters since the cache was created, plus its current size.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub entries: u64,
+    /// Estimated, including keys and per-entry overhead.
+    pub bytes: u64,
+    pub hits: u64,
+    pub negative_hits: u64,
+    pub misses: u64,
+    /// Entries pushed out to make room.
+    pub evictions: u64,
+    /// Inserts refused: the key was colder than what it would displace,
+    /// or larger than a shard's budget.
+    pub rejections: u64,
 }
 
-/// Thread-safe content cache with TTL-based eviction.
-///
-/// Uses `RwLock<HashMap>` — reads only acquire a read lock (no contention
-/// between concurrent resolves). Writes acquire a write lock (infrequent:
-/// only on cache miss or invalidation).
-///
-/// Data is stored behind `Arc` so cache hits return a cheap reference-counted
-/// pointer instead of cloning the entire document.
+/// Thread-safe, sharded LRU cache with a byte budget and an entry cap.
 pub struct ContentCache {
-    entries: RwLock<HashMap<String, CacheEntry>>,
+    shards: Vec<Mutex<Shard>>,
+    hasher: RandomState,
     ttl: Duration,
+    negative_ttl: Duration,
+    shard_max_bytes: usize,
+    shard_max_entries: usize,
+    entries: AtomicI64,
+    bytes: AtomicI64,
+    hits: AtomicU64,
+    negative_hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    rejections: AtomicU64,
 }
 
 impl ContentCache {
-    /// Create a new cache with the given TTL.
+    /// Create a cache with the given TTL and the default limits.
     pub fn new(ttl: Duration) -> Self {
+        Self::from_config(&CacheConfig {
+            ttl_secs: ttl.as_secs(),
+            ..CacheConfig::default()
+        })
+    }
+
+    /// Create a cache sized by `[cache]`.
+    pub fn from_config(config: &CacheConfig) -> Self {
+        let shards = config.shards.clamp(1, config.max_entries.max(1));
+        let shard_max_bytes = config.max_bytes / shards;
+        let shard_max_entries = config.max_entries / shards;
+        // One counter per entry the shard can actually hold.
+        let sketch_width = shard_max_entries.min(shard_max_bytes / ENTRY_OVERHEAD);
         Self {
-            entries: RwLock::new(HashMap::new()),
-            ttl,
+            shards: (0..shards)
+                .map(|_| Mutex::new(Shard::new(sketch_width)))
+                .collect(),
+            hasher: RandomState::new(),
+            ttl: Duration::from_secs(config.ttl_secs),
+            negative_ttl: Duration::from_secs(config.negative_ttl_secs),
+            shard_max_bytes,
+            shard_max_entries,
+            entries: AtomicI64::new(0),
+            bytes: AtomicI64::new(0),
+            hits: AtomicU64::new(0),
+            negative_hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            rejections: AtomicU64::new(0),
+        }
+    }
+
+    /// Look a key up, counting the access towards its admission frequency.
+    /// `None` means the store has to be read.
+    pub fn lookup(&self, key: &str) -> Option<Cached> {
+        let hash = self.hasher.hash_one(key);
+        let found = self
+            .with_shard(hash, |shard| {
+                shard.sketch.increment(hash);
+                match shard.entries.get(key) {
+                    Some(entry) if entry.expires_at > Instant::now() => {
+                        let data = entry.data.clone();
+                        shard.promote(key);
+                        Some(data)
+                    }
+                    Some(_) => {
+                        shard.remove(key);
+                        None
+                    }
+                    None => None,
+                }
+            })
+            .flatten();
+
+        match found {
+            Some(Some(data)) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                #[cfg(feature = "metrics")]
+                did_hosting_common::server::metrics::inc_cache_hit();
+                Some(Cached::Hit(data))
+            }
+            Some(None) => {
+                self.negative_hits.fetch_add(1, Ordering::Relaxed);
+                #[cfg(feature = "metrics")]
+                did_hosting_common::server::metrics::inc_cache_negative_hit();
+                Some(Cached::Missing)
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                #[cfg(feature = "metrics")]
+                did_hosting_common::server::metrics::inc_cache_miss();
+                None
+            }
         }
     }
 
-    /// Get cached content if it exists and hasn't expired.
-    /// Returns an `Arc<Vec<u8>>` — cheap clone (reference count bump only).
+    /// Get cached content if it exists and hasn't expired. A cached
+    /// "not found" reads as `None` here; use [`lookup`](Self::lookup) to
+    /// tell the two apart.
     pub fn get(&self, key: &str) -> Option<Arc<Vec<u8>>> {
-        let entries = self.entries.read().ok()?;
-        let entry = entries.get(key)?;
-        if entry.inserted_at.elapsed() < self.ttl {
-            Some(entry.data.clone()) // Arc clone = atomic ref count increment
-        } else {
-            None // Expired — caller should fetch from store and re-insert
+        match self.lookup(key)? {
+            Cached::Hit(data) => Some(data),
+            Cached::Missing => None,
         }
     }
 
-    /// Insert or update a cache entry.
-    pub fn insert(&self, key: String, data: Vec<u8>) {
-        if let Ok(mut entries) = self.entries.write() {
-            entries.insert(
-                key,
-                CacheEntry {
-                    data: Arc::new(data),
-                    inserted_at: Instant::now(),
-                },
-            );
+    /// Insert or update a cache entry. Returns the shared data whether or
+    /// not the cache kept it.
+    pub fn insert(&self, key: String, data: Vec<u8>) -> Arc<Vec<u8>> {
+        let data = Arc::new(data);
+        self.put(key, Some(data.clone()), self.ttl);
+        data
+    }
+
+    /// Remember that the store has nothing under `key`. No-op when
+    /// `negative_ttl_secs` is 0.
+    pub fn insert_missing(&self, key: String) {
+        if !self.negative_ttl.is_zero() {
+            self.put(key, None, self.negative_ttl);
         }
     }
 
     /// Invalidate a cache entry (call on publish/delete).
     pub fn invalidate(&self, key: &str) {
-        if let Ok(mut entries) = self.entries.write() {
-            entries.remove(key);
-        }
+        let hash = self.hasher.hash_one(key);
+        self.with_shard(hash, |shard| shard.remove(key));
     }
 
     /// Remove all expired entries (call periodically from cleanup thread).
     pub fn evict_expired(&self) {
-        if let Ok(mut entries) = self.entries.write() {
-            entries.retain(|_, entry| entry.inserted_at.elapsed() < self.ttl);
+        let now = Instant::now();
+        for idx in 0..self.shards.len() {
+            self.with_shard_at(idx, |shard| {
+                let expired: Vec<String> = shard
+                    .entries
+                    .iter()
+                    .filter(|(_, entry)| entry.expires_at <= now)
+                    .map(|(key, _)| key.clone())
+                    .collect();
+                for key in expired {
+                    shard.remove(&key);
+                }
+            });
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.entries.load(Ordering::Relaxed).max(0) as u64,
+            bytes: self.bytes.load(Ordering::Relaxed).max(0) as u64,
+            hits: self.hits.load(Ordering::Relaxed),
+            negative_hits: self.negative_hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            rejections: self.rejections.load(Ordering::Relaxed),
+        }
+    }
+
+    fn put(&self, key: String, data: Option<Arc<Vec<u8>>>, ttl: Duration) {
+        let size = entry_size(&key, data.as_ref().map_or(0, |d| d.len()));
+        let hash = self.hasher.hash_one(key.as_str());
+        let outcome = self.with_shard(hash, |shard| {
+            // Replacing a key it already holds is always allowed.
+            let replacing = shard.remove(&key).is_some();
+            if size > self.shard_max_bytes || self.shard_max_entries == 0 {
+                return Admission::Rejected;
+            }
+            let full = |shard: &Shard| {
+                shard.bytes + size > self.shard_max_bytes
+                    || shard.entries.len() >= self.shard_max_entries
+            };
+            if !replacing
+                && full(shard)
+                && let Some(victim) = shard.oldest()
+                && shard.sketch.estimate(hash)
+                    <= shard.sketch.estimate(self.hasher.hash_one(victim))
+            {
+                return Admission::Rejected;
+            }
+            let mut evicted = 0;
+            while full(shard) && shard.pop_oldest() {
+                evicted += 1;
+            }
+            shard.push(key, data, Instant::now() + ttl, size);
+            Admission::Admitted { evicted }
+        });
+
+        match outcome {
+            Some(Admission::Admitted { evicted }) if evicted > 0 => {
+                self.evictions.fetch_add(evicted, Ordering::Relaxed);
+                #[cfg(feature = "metrics")]
+                did_hosting_common::server::metrics::inc_cache_evictions(evicted);
+            }
+            Some(Admission::Rejected) => {
+                self.rejections.fetch_add(1, Ordering::Relaxed);
+                #[cfg(feature = "metrics")]
+                did_hosting_common::server::metrics::inc_cache_rejection();
+            }
+            _ => {}
+        }
+    }
+
+    fn with_shard<R>(&self, hash: u64, f: impl FnOnce(&mut Shard) -> R) -> Option<R> {
+        self.with_shard_at(hash as usize % self.shards.len(), f)
+    }
+
+    /// Run `f` under one shard's lock and fold its size change into the
+    /// cache-wide totals. A poisoned shard is skipped, as a miss.
+    fn with_shard_at<R>(&self, idx: usize, f: impl FnOnce(&mut Shard) -> R) -> Option<R> {
+        let mut shard = self.shards[idx].lock().ok()?;
+        let (bytes, entries) = (shard.bytes, shard.entries.len());
+        let result = f(&mut shard);
+        let bytes = shard.bytes as i64 - bytes as i64;
+        let entries = shard.entries.len() as i64 - entries as i64;
+        drop(shard);
+
+        if bytes != 0 || entries != 0 {
+            let _total_bytes = self.bytes.fetch_add(bytes, Ordering::Relaxed) + bytes;
+            let _total_entries = self.entries.fetch_add(entries, Ordering::Relaxed) + entries;
+            #[cfg(feature = "metrics")]
+            did_hosting_common::server::metrics::set_cache_usage(_total_bytes, _total_entries);
+        }
+        Some(result)
+    }
+}
+
+enum Admission {
+    Admitted { evicted: u64 },
+    Rejected,
+}
+
+fn entry_size(key: &str, data_len: usize) -> usize {
+    // The key is held twice: in the map and in the recency order.
+    2 * key.len() + data_len + ENTRY_OVERHEAD
+}
+
+struct Entry {
+    /// `None` for a cached "not found".
+    data: Option<Arc<Vec<u8>>>,
+    expires_at: Instant,
+    /// Position in the shard's recency order.
+    tick: u64,
+    size: usize,
+}
+
+struct Shard {
+    entries: HashMap<String, Entry>,
+    /// Recency order, least recently used first.
+    order: BTreeMap<u64, String>,
+    next_tick: u64,
+    bytes: usize,
+    sketch: FrequencySketch,
+}
+
+impl Shard {
+    fn new(sketch_width: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            next_tick: 0,
+            bytes: 0,
+            sketch: FrequencySketch::new(sketch_width),
+        }
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.next_tick += 1;
+        self.next_tick
+    }
+
+    fn push(&mut self, key: String, data: Option<Arc<Vec<u8>>>, expires_at: Instant, size: usize) {
+        let tick = self.tick();
+        self.order.insert(tick, key.clone());
+        self.entries.insert(
+            key,
+            Entry {
+                data,
+                expires_at,
+                tick,
+                size,
+            },
+        );
+        self.bytes += size;
+    }
+
+    fn promote(&mut self, key: &str) {
+        let tick = self.tick();
+        if let Some(entry) = self.entries.get_mut(key)
+            && let Some(key) = self.order.remove(&entry.tick)
+        {
+            entry.tick = tick;
+            self.order.insert(tick, key);
         }
     }
+
+    fn remove(&mut self, key: &str) -> Option<Entry> {
+        let entry = self.entries.remove(key)?;
+        self.order.remove(&entry.tick);
+        self.bytes -= entry.size;
+        Some(entry)
+    }
+
+    fn oldest(&self) -> Option<&str> {
+        self.order.first_key_value().map(|(_, key)| key.as_str())
+    }
+
+    /// Drop the least recently used entry. `false` when empty.
+    fn pop_oldest(&mut self) -> bool {
+        let Some((_, key)) = self.order.pop_first() else {
+            return false;
+        };
+        if let Some(entry) = self.entries.remove(&key) {
+            self.bytes -= entry.size;
+        }
+        true
+    }
+}
+
+/// Approximate recent access counts per key: a count-min sketch of four
+/// rows of small saturating counters. All counters are halved every
+/// `10 × width` increments, so popularity fades and a key that was hot an
+/// hour ago does not keep its slot forever.
+struct FrequencySketch {
+    counters: Vec<u8>,
+    mask: usize,
+    additions: usize,
+    reset_at: usize,
+}
+
+const SKETCH_ROWS: usize = 4;
+const SKETCH_MAX: u8 = 15;
+const SKETCH_SEEDS: [u64; SKETCH_ROWS] = [
+    0x9E37_79B9_7F4A_7C15,
+    0xC2B2_AE3D_27D4_EB4F,
+    0x1656_67B1_9E37_79F9,
+    0x85EB_CA77_C2B2_AE63,
+];
+
+impl FrequencySketch {
+    fn new(width: usize) -> Self {
+        let width = width.max(16).next_power_of_two();
+        Self {
+            counters: vec![0; width * SKETCH_ROWS],
+            mask: width - 1,
+            additions: 0,
+            reset_at: width * 10,
+        }
+    }
+
+    fn index(&self, hash: u64, row: usize) -> usize {
+        let h = hash.wrapping_mul(SKETCH_SEEDS[row]);
+        row * (self.mask + 1) + ((h ^ (h >> 32)) as usize & self.mask)
+    }
+
+    fn increment(&mut self, hash: u64) {
+        let mut added = false;
+        for row in 0..SKETCH_ROWS {
+            let idx = self.index(hash, row);
+            if self.counters[idx] < SKETCH_MAX {
+                self.counters[idx] += 1;
+                added = true;
+            }
+        }
+        if added {
+            self.additions += 1;
+            if self.additions >= self.reset_at {
+                self.counters.iter_mut().for_each(|c| *c >>= 1);
+                self.additions /= 2;
+            }
+        }
+    }
+
+    fn estimate(&self, hash: u64) -> u8 {
+        (0..SKETCH_ROWS)
+            .map(|row| self.counters[self.index(hash, row)])
+            .min()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(max_entries: usize, max_bytes: usize) -> ContentCache {
+        ContentCache::from_config(&CacheConfig {
+            max_entries,
+            max_bytes,
+            shards: 1,
+            ..CacheConfig::default()
+        })
+    }
+
+    /// Look `key` up `n` times, as resolves would before inserting it.
+    fn ask(cache: &ContentCache, key: &str, n: usize) {
+        for _ in 0..n {
+            cache.lookup(key);
+        }
+    }
+
+    #[test]
+    fn evicts_least_recently_used_first() {
+        let cache = cache(2, 1 << 20);
+        for key in ["a", "b"] {
+            ask(&cache, key, 2);
+            cache.insert(key.into(), vec![0; 10]);
+        }
+        // Touch `a`, so `b` is the older of the two.
+        assert!(cache.get("a").is_some());
+        ask(&cache, "c", 5);
+        cache.insert("c".into(), vec![0; 10]);
+
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("c").is_some());
+        let stats = cache.stats();
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.evictions, 1);
+    }
+
+    #[test]
+    fn one_off_keys_do_not_displace_hot_ones() {
+        let cache = cache(2, 1 << 20);
+        // Saturate the hot keys' counters: the sketch is seeded per run,
+        // and in a 16-wide sketch a one-off key can share every row with a
+        // hot one. Nothing can then estimate above them.
+        for key in ["a", "b"] {
+            ask(&cache, key, SKETCH_MAX as usize);
+            cache.insert(key.into(), vec![0; 10]);
+        }
+        for n in 0..50 {
+            let key = format!("crawl-{n}");
+            ask(&cache, &key, 1);
+            cache.insert(key, vec![0; 10]);
+        }
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("b").is_some());
+        assert_eq!(cache.stats().rejections, 50);
+    }
+
+    #[test]
+    fn stays_within_the_byte_budget() {
+        let size = entry_size("k0", 100);
+        let cache = cache(100, 2 * size);
+        for n in 0..5 {
+            let key = format!("k{n}");
+            ask(&cache, &key, n + 1);
+            cache.insert(key, vec![0; 100]);
+        }
+        let stats = cache.stats();
+        assert_eq!(stats.entries, 2);
+        assert!(stats.bytes <= 2 * size as u64);
+        assert!(cache.get("k4").is_some());
+
+        // Larger than the whole budget: never cached.
+        cache.insert("big".into(), vec![0; 4 * size]);
+        assert!(cache.get("big").is_none());
+    }
+
+    #[test]
+    fn remembers_misses_until_invalidated() {
+        let cache = ContentCache::new(Duration::from_secs(60));
+        assert_eq!(cache.lookup("content:x:log"), None);
+        cache.insert_missing("content:x:log".into());
+        assert_eq!(cache.lookup("content:x:log"), Some(Cached::Missing));
+        assert_eq!(cache.get("content:x:log"), None);
+
+        cache.invalidate("content:x:log");
+        assert_eq!(cache.lookup("content:x:log"), None);
+        assert_eq!(cache.stats().negative_hits, 2);
+
+        let off = ContentCache::from_config(&CacheConfig {
+            negative_ttl_secs: 0,
+            ..CacheConfig::default()
+        });
+        off.insert_missing("content:x:log".into());
+        assert_eq!(off.lookup("content:x:log"), None);
+    }
+
+    #[test]
+    fn expired_entries_are_dropped() {
+        let cache = ContentCache::new(Duration::ZERO);
+        cache.insert("a".into(), vec![1]);
+        assert_eq!(cache.stats().entries, 1);
+        cache.evict_expired();
+        assert_eq!(cache.stats().entries, 0);
+        assert_eq!(cache.stats().bytes, 0);
+    }
 }
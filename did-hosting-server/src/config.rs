@@ -37,6 +37,8 @@ pub struct AppConfig {
     #[serde(default)]
     pub stats: StatsConfig,
     #[serde(default)]
+    pub cache: CacheConfig,
+    #[serde(default)]
     pub watchers: Vec<WatcherEndpoint>,
     /// URL of the control plane for service registration.
     pub control_url: Option<String>,
@@ -135,6 +137,61 @@ impl Default for StatsConfig {
     }
 }
 
+/// In-memory DID content cache (see [`crate::cache`]).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CacheConfig {
+    /// How long (seconds) a cached document is served before the store is
+    /// read again. Default: 300.
+    #[serde(default = "default_cache_ttl")]
+    pub ttl_secs: u64,
+    /// How long (seconds) a "not found" is remembered. Set to 0 to disable
+    /// negative caching. Default: 30.
+    #[serde(default = "default_cache_negative_ttl")]
+    pub negative_ttl_secs: u64,
+    /// Memory budget (bytes) for cached content, counting keys and a fixed
+    /// per-entry overhead. Set to 0 to disable the cache. Default: 64MB.
+    #[serde(default = "default_cache_max_bytes")]
+    pub max_bytes: usize,
+    /// Maximum number of cached entries. Default: 100000.
+    #[serde(default = "default_cache_max_entries")]
+    pub max_entries: usize,
+    /// Number of independently locked shards. Default: 16.
+    #[serde(default = "default_cache_shards")]
+    pub shards: usize,
+}
+
+fn default_cache_ttl() -> u64 {
+    300
+}
+
+fn default_cache_negative_ttl() -> u64 {
+    30
+}
+
+fn default_cache_max_bytes() -> usize {
+    64 * 1024 * 1024
+}
+
+fn default_cache_max_entries() -> usize {
+    100_000
+}
+
+fn default_cache_shards() -> usize {
+    16
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl_secs: default_cache_ttl(),
+            negative_ttl_secs: default_cache_negative_ttl(),
+            max_bytes: default_cache_max_bytes(),
+            max_entries: default_cache_max_entries(),
+            shards: default_cache_shards(),
+        }
+    }
+}
+
 impl AppConfig {
     /// Return the public-facing base URL for this server.
     pub fn public_base_url(&self) -> String {
@@ -229,6 +286,16 @@ impl AppConfig {
             config.stats.sync_interval_secs
         );
 
+        // Cache
+        env_parse!("DID_HOSTING_CACHE_TTL_SECS", config.cache.ttl_secs);
+        env_parse!(
+            "DID_HOSTING_CACHE_NEGATIVE_TTL_SECS",
+            config.cache.negative_ttl_secs
+        );
+        env_parse!("DID_HOSTING_CACHE_MAX_BYTES", config.cache.max_bytes);
+        env_parse!("DID_HOSTING_CACHE_MAX_ENTRIES", config.cache.max_entries);
+        env_parse!("DID_HOSTING_CACHE_SHARDS", config.cache.shards);
+
         // Validate configuration
         config.auth.validate()?;
         if let Some(ref did) = config.server_did
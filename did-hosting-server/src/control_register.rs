@@ -15,8 +15,10 @@ use affinidi_messaging_didcomm::Message;
 use affinidi_messaging_didcomm_service::DIDCommService;
 use did_hosting_common::DidSyncUpdate;
 use did_hosting_common::did_ops::{
-    AgentNameEntry, DidRecord, agent_name_key, content_log_key, content_witness_key, did_key,
-    extract_agent_names, extract_service_types, owner_key, validate_did_jsonl,
+    AgentNameEntry, DidRecord, agent_name_key, content_did_doc_key, content_log_key,
+    content_witness_key, detect_log_method, did_key, extract_agent_names, extract_log_metadata,
+    extract_service_types, inspect_did_log, owner_key, previous_did_key, rollback_proof_key,
+    rolled_back_key, validate_did_jsonl,
 };
 use did_hosting_common::didcomm_types::MSG_SERVER_REGISTER;
 use did_hosting_common::server::acl::{AclEntry, Role, get_acl_entry, store_acl_entry};
@@ -32,6 +34,7 @@ use did_hosting_common::server::trust_tasks::send::{
 use serde_json::json;
 use tracing::{info, warn};
 
+use crate::precompressed::{self, Variants};
 use crate::server::AppState;
 use crate::store::{KeyspaceHandle, Store};
 
@@ -306,7 +309,12 @@ pub async fn apply_single_update(
 ) -> Result<(), crate::error::AppError> {
     use crate::auth::session::now_epoch;
 
-    validate_did_jsonl(&update.log_content).map_err(crate::error::AppError::Validation)?;
+    let method = detect_log_method(&update.log_content).unwrap_or("webvh");
+    if method == "webvh" && update.did_document.is_some() {
+        return Err(crate::error::AppError::Validation(
+            "a separate DID document is only accepted for did:webs".into(),
+        ));
+    }
 
     let now = now_epoch();
 
@@ -318,7 +326,27 @@ pub async fn apply_single_update(
     // serving names for anyway.
     let did_host = extract_did_host(&update.did_id).unwrap_or_default();
 
-    let record = DidRecord {
+    // Services, names and deactivation are read off the log (or, for
+    // did:webs, the document checked against the KEL). The control plane
+    // verified a webvh log's proofs on publish; its shape is rechecked here.
+    let (services, claims, deactivated) = if method == "webvh" {
+        validate_did_jsonl(&update.log_content).map_err(crate::error::AppError::Validation)?;
+        (
+            extract_service_types(&update.log_content),
+            extract_agent_names(&update.log_content, &did_host),
+            extract_log_metadata(&update.log_content).deactivated,
+        )
+    } else {
+        let summary = inspect_did_log(&update.log_content, update.did_document.as_deref())
+            .map_err(crate::error::AppError::Validation)?;
+        (
+            summary.services(),
+            summary.agent_names(&did_host),
+            summary.deactivated,
+        )
+    };
+
+    let mut record = DidRecord {
         owner: "system".to_string(),
         mnemonic: update.mnemonic.clone(),
         created_at: now,
@@ -329,14 +357,14 @@ pub async fn apply_single_update(
         disabled: false,
         deleted_at: None,
 
-        // T12: legacy construction site; T13 migration fills `domain`.
-        method: "webvh".to_string(),
+        method: method.to_string(),
+        // T13 migration fills `domain`.
         domain: String::new(),
 
         // Derive from the synced log rather than trusting the control
         // plane to send a services list — the log is the authority, and
         // this keeps the edge node's badges consistent with what it serves.
-        services: extract_service_types(&update.log_content),
+        services,
 
         // Same argument, and here it carries security weight: agent names
         // come from the signed document's `alsoKnownAs`, never from the
@@ -356,7 +384,7 @@ pub async fn apply_single_update(
         // single most valuable name to get wrong, so it does not rely on the
         // push being honest. `validate_agent_name_binding` also drops reserved
         // names for the same reason.
-        agent_names: extract_agent_names(&update.log_content, &did_host)
+        agent_names: claims
             .into_iter()
             .filter(|name| validate_agent_name_binding(name, &update.mnemonic).is_ok())
             .map(|name| AgentNameEntry {
@@ -365,6 +393,8 @@ pub async fn apply_single_update(
                 created_at: now,
             })
             .collect(),
+        deactivated_at: None,
+        sync_head: None,
     };
 
     // Read the record we are replacing so stale name-index entries can be
@@ -374,13 +404,45 @@ pub async fn apply_single_update(
     // precisely the state Layer-1 exists to prevent.
     let previous: Option<DidRecord> = dids_ks.get(did_key(&update.mnemonic)).await.ok().flatten();
 
+    // Deactivation is read off the log, like everything else here; keep the
+    // time it was first seen rather than moving it on every resync.
+    if deactivated {
+        record.deactivated_at = previous
+            .as_ref()
+            .and_then(|p| p.deactivated_at)
+            .or(Some(now));
+    }
+    // An update without a witness file leaves the stored one in place.
+    let witness = match update.witness_content {
+        Some(ref witness) => Some(witness.as_bytes().to_vec()),
+        None => {
+            dids_ks
+                .get_raw(content_witness_key(&update.mnemonic))
+                .await?
+        }
+    };
+    record.set_sync_head(update.log_content.as_bytes(), witness.as_deref());
+
+    let log_variants = Variants::build(
+        dids_ks,
+        &content_log_key(&update.mnemonic),
+        update.log_content.as_bytes(),
+    )
+    .await?;
     let mut batch = store.batch();
     batch.insert(dids_ks, did_key(&update.mnemonic), &record)?;
 
     if let Some(prev) = previous.as_ref() {
+        // A domain move changes the host the old names were indexed under,
+        // so every one of them is stale there.
+        let prev_host = prev
+            .did_id
+            .as_deref()
+            .and_then(|d| extract_did_host(d).ok())
+            .unwrap_or_else(|| did_host.clone());
         for old in &prev.agent_names {
-            if !record.agent_names.iter().any(|n| n.name == old.name) {
-                batch.remove(dids_ks, agent_name_key(&did_host, &old.name));
+            if prev_host != did_host || !record.agent_names.iter().any(|n| n.name == old.name) {
+                batch.remove(dids_ks, agent_name_key(&prev_host, &old.name));
             }
         }
     }
@@ -396,21 +458,62 @@ pub async fn apply_single_update(
         content_log_key(&update.mnemonic),
         update.log_content.as_bytes().to_vec(),
     );
+    log_variants.stage(&mut batch, dids_ks);
     batch.insert_raw(
         dids_ks,
         owner_key("system", &update.mnemonic),
         update.mnemonic.as_bytes().to_vec(),
     );
+    let doc_key = content_did_doc_key(&update.mnemonic);
+    match update.did_document {
+        Some(ref doc) => {
+            let variants = Variants::build(dids_ks, &doc_key, doc.as_bytes()).await?;
+            batch.insert_raw(dids_ks, doc_key, doc.as_bytes().to_vec());
+            variants.stage(&mut batch, dids_ks);
+        }
+        None => {
+            precompressed::stage_removal(&mut batch, dids_ks, &doc_key).await?;
+            batch.remove(dids_ks, doc_key);
+        }
+    }
     if let Some(ref witness) = update.witness_content {
+        let witness_key = content_witness_key(&update.mnemonic);
+        let variants = Variants::build(dids_ks, &witness_key, witness.as_bytes()).await?;
+        batch.insert_raw(dids_ks, witness_key, witness.as_bytes().to_vec());
+        variants.stage(&mut batch, dids_ks);
+    }
+    // After an in-place domain move the old host keeps resolving this slot
+    // for as long as the control plane keeps sending the old identifier.
+    match update.previous_did_id {
+        Some(ref previous_did) => batch.insert_raw(
+            dids_ks,
+            previous_did_key(&update.mnemonic),
+            previous_did.as_bytes().to_vec(),
+        ),
+        None => batch.remove(dids_ks, previous_did_key(&update.mnemonic)),
+    }
+    // Kept for the watcher push, which tells watchers the shorter log is a
+    // rollback rather than a fork.
+    if update.rolled_back.is_empty() {
+        batch.remove(dids_ks, rolled_back_key(&update.mnemonic));
+    } else {
         batch.insert_raw(
             dids_ks,
-            content_witness_key(&update.mnemonic),
-            witness.as_bytes().to_vec(),
+            rolled_back_key(&update.mnemonic),
+            serde_json::to_vec(&update.rolled_back)?,
         );
     }
+    match update.rollback_proof {
+        Some(ref proof) if !update.rolled_back.is_empty() => {
+            batch.insert(dids_ks, rollback_proof_key(&update.mnemonic), proof)?
+        }
+        _ => batch.remove(dids_ks, rollback_proof_key(&update.mnemonic)),
+    }
     batch.commit().await?;
 
     did_cache.invalidate(&content_log_key(&update.mnemonic));
+    did_cache.invalidate(&content_witness_key(&update.mnemonic));
+    did_cache.invalidate(&content_did_doc_key(&update.mnemonic));
 
     info!(
         mnemonic = %update.mnemonic,
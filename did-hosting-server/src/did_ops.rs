@@ -10,22 +10,27 @@ use crate::auth::AuthClaims;
 use crate::auth::session::now_epoch;
 use crate::config::AppConfig;
 use crate::error::AppError;
-use crate::mnemonic::{
-    generate_unique_mnemonic, is_path_available, validate_custom_path, validate_mnemonic,
-};
+use crate::mnemonic::{generate_unique_mnemonic, is_path_available, validate_mnemonic};
+use crate::precompressed::{self, Variants};
 use crate::server::AppState;
 
 use crate::store::KeyspaceHandle;
 use did_hosting_common::DidListEntry;
+use did_hosting_common::server::deactivation::{
+    deactivation_retention_secs, ensure_not_deactivated,
+};
+use did_hosting_common::server::rollback_proof;
+use ed25519_dalek::SigningKey;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
 
 // Re-export shared types and helpers from did-hosting-common so existing code
 // that imports from `crate::did_ops::*` continues to work.
 pub use did_hosting_common::did_ops::{
-    DidRecord, LogEntryInfo, LogMetadata, content_log_key, content_witness_key, did_key,
-    extract_did_id, extract_did_web_document, extract_log_metadata, extract_service_types,
-    owner_key, parse_log_entries, watcher_sync_key,
+    DidRecord, LogEntryInfo, LogMetadata, content_did_doc_key, content_log_key,
+    content_witness_key, did_key, extract_did_id, extract_did_web_document, extract_log_metadata,
+    extract_service_types, owner_key, parse_log_entries, previous_did_key, rollback_proof_key,
+    rolled_back_key, watcher_sync_key,
 };
 
 // ---------------------------------------------------------------------------
@@ -242,7 +247,8 @@ pub async fn create_did(
             custom_path.to_string()
         }
         Some(custom_path) => {
-            validate_custom_path(custom_path)?;
+            // A custom path, or a webs/webplus method-specific identifier.
+            validate_mnemonic(custom_path)?;
             if !is_path_available(&state.dids_ks, custom_path).await? {
                 return Err(AppError::Conflict(format!(
                     "path '{custom_path}' is already taken"
@@ -265,13 +271,16 @@ pub async fn create_did(
         disabled: false,
         deleted_at: None,
 
-        // T12: legacy construction site; T13 migration fills `domain`.
+        // A placeholder until the first publish, which sets the method
+        // read off the log. T13 migration fills `domain`.
         method: "webvh".to_string(),
         domain: String::new(),
 
         // Empty slot — no log yet, so no document to read services from.
         services: None,
         agent_names: Vec::new(),
+        deactivated_at: None,
+        sync_head: None,
     };
 
     let mut batch = state.store.batch();
@@ -305,6 +314,10 @@ pub struct PublishDidResult {
 }
 
 /// Publish (upload) a did.jsonl log for an existing DID slot.
+///
+/// A log ending in a `deactivated: true` entry marks the record
+/// deactivated: no further publish is accepted, and the DID leaves its
+/// owner's quota.
 pub async fn publish_did(
     auth: &AuthClaims,
     state: &AppState,
@@ -313,11 +326,48 @@ pub async fn publish_did(
 ) -> Result<PublishDidResult, AppError> {
     validate_mnemonic(mnemonic)?;
     let mut record = get_authorized_record(&state.dids_ks, mnemonic, auth).await?;
-
-    validate_did_jsonl(did_log)?;
+    ensure_not_deactivated(&record)?;
+
+    // The method is read off the log; webvh keeps its shape check and
+    // the others go through their method's own verification (a did:webs
+    // KEL against the `did.json` already stored for the slot).
+    let method = did_hosting_common::did_ops::detect_log_method(did_log).unwrap_or("webvh");
+    let (deactivating, did_id, services) = if method == "webvh" {
+        validate_did_jsonl(did_log)?;
+        (
+            extract_log_metadata(did_log).deactivated,
+            extract_did_id(did_log),
+            extract_service_types(did_log),
+        )
+    } else {
+        let did_doc = match method {
+            "webs" => state
+                .dids_ks
+                .get_raw(content_did_doc_key(mnemonic))
+                .await?
+                .and_then(|b| String::from_utf8(b).ok()),
+            _ => None,
+        };
+        let summary = did_hosting_common::did_ops::inspect_did_log(did_log, did_doc.as_deref())
+            .map_err(AppError::Validation)?;
+        (
+            summary.deactivated,
+            Some(summary.did_id.clone()),
+            summary.services(),
+        )
+    };
+    did_hosting_common::server::mnemonic::validate_mnemonic_for(method, mnemonic)?;
+    if record.version_count > 0 && record.method != method {
+        return Err(AppError::Validation(format!(
+            "{mnemonic} hosts a did:{} DID; a did:{method} log cannot replace it",
+            record.method
+        )));
+    }
 
     let new_size = did_log.len() as u64;
     let old_size = record.content_size;
+    // A deactivating entry is charged like any other; the DID leaves the
+    // owner's quota only once it has landed.
     check_total_size_limit(
         auth,
         &state.dids_ks,
@@ -328,8 +378,6 @@ pub async fn publish_did(
     )
     .await?;
 
-    let did_id = extract_did_id(did_log);
-
     let version_id = did_log
         .lines()
         .last()
@@ -344,9 +392,21 @@ pub async fn publish_did(
     record.version_count += 1;
     record.did_id = did_id.clone();
     record.content_size = new_size;
+    record.method = method.to_string();
     // Recompute, don't fill-if-empty: an upload can drop a service as
     // well as add one. Also backfills legacy `None` records on next write.
-    record.services = extract_service_types(did_log);
+    record.services = services;
+    if deactivating {
+        record.deactivated_at = Some(record.updated_at);
+    }
+    let witness = state.dids_ks.get_raw(content_witness_key(mnemonic)).await?;
+    record.set_sync_head(did_log.as_bytes(), witness.as_deref());
+    let variants = Variants::build(
+        &state.dids_ks,
+        &content_log_key(mnemonic),
+        did_log.as_bytes(),
+    )
+    .await?;
 
     let mut batch = state.store.batch();
     batch.insert_raw(
@@ -354,11 +414,20 @@ pub async fn publish_did(
         content_log_key(mnemonic),
         did_log.as_bytes().to_vec(),
     );
+    variants.stage(&mut batch, &state.dids_ks);
     batch.insert(&state.dids_ks, did_key(mnemonic), &record)?;
+    // A new entry replaces whatever was rolled back; watchers must not
+    // accept the old entries as a rollback any more.
+    batch.remove(&state.dids_ks, rolled_back_key(mnemonic));
+    batch.remove(&state.dids_ks, rollback_proof_key(mnemonic));
     batch.commit().await?;
 
-    // Update quota index for size change
-    quota_on_size_change(&state.dids_ks, &record.owner, old_size, new_size).await?;
+    // Update quota index for size change; a deactivated DID stops counting.
+    if deactivating {
+        quota_on_delete(&state.dids_ks, &record.owner, old_size).await?;
+    } else {
+        quota_on_size_change(&state.dids_ks, &record.owner, old_size, new_size).await?;
+    }
 
     // Invalidate cache for this DID
     state.did_cache.invalidate(&content_log_key(mnemonic));
@@ -367,12 +436,17 @@ pub async fn publish_did(
         collector.record_update(mnemonic);
     }
 
-    let did_url = format!("{}/{mnemonic}/did.jsonl", state.config.public_base_url());
+    let did_url = format!(
+        "{}/{mnemonic}/{}",
+        state.config.public_base_url(),
+        did_hosting_common::did_ops::resolution_file(method)
+    );
 
     info!(
         did = %auth.did,
         role = %auth.role,
         mnemonic = %mnemonic,
+        method,
         size = new_size,
         version = record.version_count,
         "did.jsonl published"
@@ -409,7 +483,7 @@ pub async fn upload_witness(
     witness_content: &str,
 ) -> Result<WitnessUploadResult, AppError> {
     validate_mnemonic(mnemonic)?;
-    get_authorized_record(&state.dids_ks, mnemonic, auth).await?;
+    let mut record = get_authorized_record(&state.dids_ks, mnemonic, auth).await?;
 
     if witness_content.is_empty() {
         return Err(AppError::Validation(
@@ -423,13 +497,26 @@ pub async fn upload_witness(
 
     let size = witness_content.len();
 
-    state
-        .dids_ks
-        .insert_raw(
-            content_witness_key(mnemonic),
-            witness_content.as_bytes().to_vec(),
-        )
-        .await?;
+    match state.dids_ks.get_raw(content_log_key(mnemonic)).await? {
+        Some(log) => record.set_sync_head(&log, Some(witness_content.as_bytes())),
+        None => record.sync_head = None,
+    }
+    let variants = Variants::build(
+        &state.dids_ks,
+        &content_witness_key(mnemonic),
+        witness_content.as_bytes(),
+    )
+    .await?;
+    let mut batch = state.store.batch();
+    batch.insert_raw(
+        &state.dids_ks,
+        content_witness_key(mnemonic),
+        witness_content.as_bytes().to_vec(),
+    );
+    variants.stage(&mut batch, &state.dids_ks);
+    batch.insert(&state.dids_ks, did_key(mnemonic), &record)?;
+    batch.commit().await?;
+    state.did_cache.invalidate(&content_witness_key(mnemonic));
 
     let witness_url = format!(
         "{}/{mnemonic}/did-witness.json",
@@ -441,6 +528,55 @@ pub async fn upload_witness(
     Ok(WitnessUploadResult { witness_url })
 }
 
+/// Replace the `did.json` served next to a did:webs KEL, after checking it
+/// against the stored KEL. A key rotation that changes both goes through
+/// the control plane's register, which writes them together.
+pub async fn upload_did_document(
+    auth: &AuthClaims,
+    state: &AppState,
+    mnemonic: &str,
+    did_doc: &str,
+) -> Result<(), AppError> {
+    validate_mnemonic(mnemonic)?;
+    let mut record = get_authorized_record(&state.dids_ks, mnemonic, auth).await?;
+    ensure_not_deactivated(&record)?;
+    if record.method != "webs" {
+        return Err(AppError::Validation(format!(
+            "a separate DID document is only accepted for did:webs, not did:{}",
+            record.method
+        )));
+    }
+    let kel = state
+        .dids_ks
+        .get_raw(content_log_key(mnemonic))
+        .await?
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| AppError::NotFound(format!("no KEL published for {mnemonic}")))?;
+    let summary = did_hosting_common::did_ops::inspect_did_log(&kel, Some(did_doc))
+        .map_err(AppError::Validation)?;
+    if record.did_id.as_deref() != Some(summary.did_id.as_str()) {
+        return Err(AppError::Validation(format!(
+            "did.json names {}, not the hosted DID",
+            summary.did_id
+        )));
+    }
+
+    record.updated_at = now_epoch();
+    record.services = summary.services();
+    let mut batch = state.store.batch();
+    batch.insert_raw(
+        &state.dids_ks,
+        content_did_doc_key(mnemonic),
+        did_doc.as_bytes().to_vec(),
+    );
+    batch.insert(&state.dids_ks, did_key(mnemonic), &record)?;
+    batch.commit().await?;
+    state.did_cache.invalidate(&content_did_doc_key(mnemonic));
+
+    info!(did = %auth.did, role = %auth.role, mnemonic = %mnemonic, size = did_doc.len(), "did.json uploaded");
+    Ok(())
+}
+
 /// Result of retrieving DID info.
 pub struct DidInfoResult {
     pub record: DidRecord,
@@ -547,6 +683,7 @@ pub async fn list_dids(
                 domain: (!record.domain.is_empty()).then(|| record.domain.clone()),
                 agent_names,
                 services: record.services,
+                deactivated_at: record.deactivated_at,
             });
         }
     }
@@ -587,6 +724,7 @@ async fn list_all_dids(auth: &AuthClaims, state: &AppState) -> Result<Vec<DidLis
             domain: (!record.domain.is_empty()).then(|| record.domain.clone()),
             agent_names,
             services: record.services,
+            deactivated_at: record.deactivated_at,
         });
     }
 
@@ -613,12 +751,25 @@ pub async fn delete_did(
 
     let did_id = record.did_id.clone();
 
+    // A deactivated DID's log is the evidence verifiers rely on; keep it
+    // for the retention window.
+    if let Some(at) = record.deletable_at(deactivation_retention_secs(&state.config.hosting)?)
+        && at > now_epoch()
+    {
+        return Err(AppError::Conflict(format!(
+            "DID is deactivated and cannot be deleted before {at} (unix seconds)"
+        )));
+    }
+
     // Mark as deleted instead of removing
     record.deleted_at = Some(now_epoch());
     state.dids_ks.insert(did_key(mnemonic), &record).await?;
 
-    // Update quota index (content is still stored but quota is freed)
-    quota_on_delete(&state.dids_ks, &record.owner, record.content_size).await?;
+    // Update quota index (content is still stored but quota is freed).
+    // A deactivated DID already left the quota when it was deactivated.
+    if !record.is_deactivated() {
+        quota_on_delete(&state.dids_ks, &record.owner, record.content_size).await?;
+    }
 
     state.did_cache.invalidate(&content_log_key(mnemonic));
 
@@ -650,8 +801,10 @@ pub async fn recover_did(state: &AppState, mnemonic: &str) -> Result<(), AppErro
     record.deleted_at = None;
     state.dids_ks.insert(did_key(mnemonic), &record).await?;
 
-    // Restore quota
-    quota_on_create(&state.dids_ks, &record.owner).await?;
+    // Restore quota, unless the DID is deactivated and never counted.
+    if !record.is_deactivated() {
+        quota_on_create(&state.dids_ks, &record.owner).await?;
+    }
 
     state.did_cache.invalidate(&content_log_key(mnemonic));
 
@@ -683,6 +836,7 @@ pub async fn rollback_did(
 ) -> Result<RollbackDidResult, AppError> {
     validate_mnemonic(mnemonic)?;
     let mut record = get_authorized_record(&state.dids_ks, mnemonic, auth).await?;
+    ensure_not_deactivated(&record)?;
 
     let bytes = state
         .dids_ks
@@ -703,6 +857,17 @@ pub async fn rollback_did(
     let truncated_lines = &lines[..lines.len() - 1];
     let truncated = truncated_lines.join("\n");
 
+    // Watchers holding the dropped entry accept the shorter log only if
+    // the push names it, ahead of anything rolled back before.
+    let mut rolled_back = vec![lines[lines.len() - 1].to_string()];
+    rolled_back.extend(
+        state
+            .dids_ks
+            .get::<Vec<String>>(rolled_back_key(mnemonic))
+            .await?
+            .unwrap_or_default(),
+    );
+
     let new_did_id = extract_did_id(&truncated);
     let new_size = truncated.len() as u64;
 
@@ -712,18 +877,46 @@ pub async fn rollback_did(
     record.updated_at = now_epoch();
     // Rolling back can retract a service the dropped entry introduced.
     record.services = extract_service_types(&truncated);
+    record.set_sync_head(truncated.as_bytes(), None);
 
+    let variants = Variants::build(
+        &state.dids_ks,
+        &content_log_key(mnemonic),
+        truncated.as_bytes(),
+    )
+    .await?;
     let mut batch = state.store.batch();
     batch.insert_raw(
         &state.dids_ks,
         content_log_key(mnemonic),
         truncated.as_bytes().to_vec(),
     );
+    variants.stage(&mut batch, &state.dids_ks);
     batch.insert(&state.dids_ks, did_key(mnemonic), &record)?;
     batch.remove(&state.dids_ks, content_witness_key(mnemonic));
+    precompressed::stage_removal(&mut batch, &state.dids_ks, &content_witness_key(mnemonic))
+        .await?;
+    batch.insert(&state.dids_ks, rolled_back_key(mnemonic), &rolled_back)?;
+    // Watchers accept the shorter log only under our signature.
+    let proof = state.signing_key_bytes.and_then(|seed| {
+        rollback_proof::sign(
+            record.did_id.as_deref().unwrap_or_default(),
+            &truncated,
+            &rolled_back,
+            &SigningKey::from_bytes(&seed),
+        )
+    });
+    match proof {
+        Some(ref proof) => batch.insert(&state.dids_ks, rollback_proof_key(mnemonic), proof)?,
+        None => {
+            warn!(mnemonic = %mnemonic, "no signing key; watchers will treat this rollback as a fork");
+            batch.remove(&state.dids_ks, rollback_proof_key(mnemonic));
+        }
+    }
     batch.commit().await?;
 
     state.did_cache.invalidate(&content_log_key(mnemonic));
+    state.did_cache.invalidate(&content_witness_key(mnemonic));
 
     let log_metadata = Some(extract_log_metadata(&truncated));
     let did_url = format!("{}/{mnemonic}/did.jsonl", state.config.public_base_url());
@@ -789,11 +982,17 @@ pub async fn cleanup_empty_dids(
             || record.deleted_at.is_some_and(|d| now.saturating_sub(d) > SOFT_DELETE_RETENTION);
 
         if should_remove {
+            precompressed::remove_did(dids_ks, &record.mnemonic).await?;
             dids_ks.remove(did_key(&record.mnemonic)).await?;
             dids_ks.remove(content_log_key(&record.mnemonic)).await?;
             dids_ks
                 .remove(content_witness_key(&record.mnemonic))
                 .await?;
+            dids_ks
+                .remove(content_did_doc_key(&record.mnemonic))
+                .await?;
+            dids_ks.remove(rolled_back_key(&record.mnemonic)).await?;
+            dids_ks.remove(rollback_proof_key(&record.mnemonic)).await?;
             dids_ks
                 .remove(owner_key(&record.owner, &record.mnemonic))
                 .await?;
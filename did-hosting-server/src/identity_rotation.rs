@@ -90,6 +90,7 @@ pub async fn reload_now(state: &AppState) -> Result<(), AppError> {
             tsp: state.config.features.tsp,
         },
         state.config.identity.rotation_grace_secs(),
+        None,
     )
     .await?;
 
@@ -385,8 +386,14 @@ pub async fn retire_generation_now(state: &AppState, generation_id: u64) -> Resu
     };
     let secret_store = create_secret_store(&state.config)?;
 
-    identity::retire_generation_now(identity, &state.store, secret_store.as_ref(), generation_id)
-        .await?;
+    identity::retire_generation_now(
+        identity,
+        &state.store,
+        secret_store.as_ref(),
+        generation_id,
+        None,
+    )
+    .await?;
 
     rebuild_listener(state).await?;
 
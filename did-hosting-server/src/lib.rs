@@ -26,7 +26,9 @@ pub mod messaging;
 pub mod mnemonic;
 #[cfg(feature = "openapi")]
 pub mod openapi;
+pub mod precompressed;
 pub mod purge_sweep;
+pub mod resolution;
 pub mod routes;
 pub mod secret_store;
 pub mod server;
@@ -1,5 +1,7 @@
 use clap::{Parser, Subcommand};
+use did_hosting_common::server::backup::{BackupRequest, KeyspaceSelection, RestoreRequest};
 use did_hosting_common::server::store::KS_DIDS;
+use did_hosting_common::server::store_migrate::{self, MigrateRequest};
 use did_hosting_server::config::AppConfig;
 use did_hosting_server::{
     backup, bootstrap, health, secret_store, server, setup, setup_recipe, store,
@@ -140,14 +142,77 @@ enum Command {
     /// Export server data to a backup file
     Backup {
         /// Output file path (use "-" for stdout)
-        #[arg(short, long, default_value = "webvh-backup.json")]
+        #[arg(short, long, default_value = "webvh-backup.jsonl")]
         output: String,
+        /// Back up only these keyspaces (repeatable or comma-separated).
+        #[arg(long = "keyspace", value_delimiter = ',')]
+        keyspaces: Vec<String>,
+        /// Leave these keyspaces out.
+        #[arg(long = "exclude-keyspace", value_delimiter = ',')]
+        exclude_keyspaces: Vec<String>,
+        /// Encrypt to this age X25519 recipient (`age1…`; repeatable). The
+        /// backup can then only be read with the matching identity file.
+        #[arg(long = "recipient")]
+        recipients: Vec<String>,
+        /// Incremental: write only what changed or was deleted since this
+        /// earlier backup (full or incremental).
+        #[arg(long)]
+        incremental_from: Option<PathBuf>,
+        /// age identity file, to read an encrypted `--incremental-from`.
+        #[arg(long = "identity")]
+        identities: Vec<PathBuf>,
     },
     /// Restore server data from a backup file
     Restore {
         /// Input backup file path
         #[arg(short, long)]
         input: String,
+        /// Restore only these keyspaces (repeatable or comma-separated).
+        #[arg(long = "keyspace", value_delimiter = ',')]
+        keyspaces: Vec<String>,
+        /// Leave these keyspaces out.
+        #[arg(long = "exclude-keyspace", value_delimiter = ',')]
+        exclude_keyspaces: Vec<String>,
+        /// age identity file, for an encrypted backup (repeatable).
+        #[arg(long = "identity")]
+        identities: Vec<PathBuf>,
+        /// Report what would be added, overwritten and kept, per keyspace,
+        /// without writing the config or the store.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Check a backup file's digest and entries without restoring it
+    VerifyBackup {
+        /// Input backup file path
+        #[arg(short, long)]
+        input: String,
+        /// age identity file, for an encrypted backup (repeatable).
+        #[arg(long = "identity")]
+        identities: Vec<PathBuf>,
+    },
+    /// Copy every keyspace into another storage backend.
+    ///
+    /// Stop the service first. Both backends' `store-*` features must be
+    /// compiled into this binary.
+    StoreMigrate {
+        /// Config file whose [store] is the source
+        #[arg(long)]
+        from: PathBuf,
+        /// Config file whose [store] is the target
+        #[arg(long)]
+        to: PathBuf,
+        /// Progress file; an interrupted run resumes from it
+        #[arg(long, default_value = "store-migrate.checkpoint")]
+        checkpoint: PathBuf,
+        /// Migrate only these keyspaces (repeatable or comma-separated).
+        #[arg(long = "keyspace", value_delimiter = ',')]
+        keyspaces: Vec<String>,
+        /// Leave these keyspaces out.
+        #[arg(long = "exclude-keyspace", value_delimiter = ',')]
+        exclude_keyspaces: Vec<String>,
+        /// Compare the two stores without copying anything
+        #[arg(long)]
+        verify_only: bool,
     },
     /// Load a DID at an arbitrary path (e.g., "services/control")
     LoadDid {
@@ -492,18 +557,82 @@ async fn main() {
                 std::process::exit(1);
             }
         }
-        Some(Command::Backup { output }) => {
-            if let Err(e) = backup::run_backup(cli.config, output).await {
+        Some(Command::Backup {
+            output,
+            keyspaces,
+            exclude_keyspaces,
+            recipients,
+            incremental_from,
+            identities,
+        }) => {
+            let req = BackupRequest {
+                output,
+                selection: KeyspaceSelection {
+                    only: keyspaces,
+                    exclude: exclude_keyspaces,
+                },
+                recipients,
+                incremental_from,
+                identities,
+            };
+            if let Err(e) = backup::run_backup(cli.config, req).await {
                 eprintln!("Backup error: {e}");
                 std::process::exit(1);
             }
         }
-        Some(Command::Restore { input }) => {
-            if let Err(e) = backup::run_restore(cli.config, input).await {
+        Some(Command::Restore {
+            input,
+            keyspaces,
+            exclude_keyspaces,
+            identities,
+            dry_run,
+        }) => {
+            let req = RestoreRequest {
+                input,
+                selection: KeyspaceSelection {
+                    only: keyspaces,
+                    exclude: exclude_keyspaces,
+                },
+                identities,
+                dry_run,
+            };
+            if let Err(e) = backup::run_restore(cli.config, req).await {
                 eprintln!("Restore error: {e}");
                 std::process::exit(1);
             }
         }
+        Some(Command::VerifyBackup { input, identities }) => {
+            if let Err(e) = backup::run_verify_backup(input, identities) {
+                eprintln!("Backup verification failed: {e}");
+                std::process::exit(1);
+            }
+        }
+        Some(Command::StoreMigrate {
+            from,
+            to,
+            checkpoint,
+            keyspaces,
+            exclude_keyspaces,
+            verify_only,
+        }) => {
+            let req = MigrateRequest {
+                from,
+                to,
+                checkpoint,
+                selection: KeyspaceSelection {
+                    only: keyspaces,
+                    exclude: exclude_keyspaces,
+                },
+                verify_only,
+            };
+            if let Err(e) =
+                store_migrate::run_store_migrate(&req, "store", backup::KEYSPACES, Some("WEBVH"))
+                    .await
+            {
+                eprintln!("Store migration error: {e}");
+                std::process::exit(1);
+            }
+        }
         Some(Command::LoadDid {
             path,
             did_log,
@@ -837,6 +966,7 @@ async fn run_recreate_did(
     let did_key = did_hosting_server::did_ops::did_key(&mnemonic);
     if dids_ks.contains_key(did_key.clone()).await? {
         // Remove the DID record and its content
+        did_hosting_server::precompressed::remove_did(&dids_ks, &mnemonic).await?;
         dids_ks.remove(did_key).await?;
         dids_ks
             .remove(did_hosting_server::did_ops::content_log_key(&mnemonic))
@@ -844,6 +974,12 @@ async fn run_recreate_did(
         dids_ks
             .remove(did_hosting_server::did_ops::content_witness_key(&mnemonic))
             .await?;
+        dids_ks
+            .remove(did_hosting_server::did_ops::rolled_back_key(&mnemonic))
+            .await?;
+        dids_ks
+            .remove(did_hosting_server::did_ops::rollback_proof_key(&mnemonic))
+            .await?;
         // Remove owner index entry (owner is "system" for bootstrapped DIDs)
         dids_ks
             .remove(did_hosting_server::did_ops::owner_key("system", &mnemonic))
@@ -1018,35 +1154,15 @@ async fn run_bootstrap_did(
                 eprintln!("  Warning: witness authentication failed: {e}");
                 eprintln!("  The DID was created but has no witness proof.");
             } else {
-                let version_id = result
-                    .jsonl
-                    .lines()
-                    .last()
-                    .and_then(|line| serde_json::from_str::<serde_json::Value>(line).ok())
-                    .and_then(|v| {
-                        v.get("versionId")
-                            .and_then(|id| id.as_str())
-                            .map(String::from)
-                    });
-
-                if let Some(vid) = version_id {
-                    match witness_client.request_proof(&w_id, &vid).await {
-                        Ok(proof) => {
-                            let proof_json = serde_json::to_string(&proof)?;
-                            dids_ks
-                                .insert_raw(
-                                    did_hosting_server::did_ops::content_witness_key(&mnemonic),
-                                    proof_json.into_bytes(),
-                                )
-                                .await?;
-                            eprintln!("  Witness proof stored.");
-                        }
-                        Err(e) => {
-                            eprintln!("  Warning: witness proof request failed: {e}");
-                        }
+                match witness_client.request_proof(&w_id, &result.jsonl).await {
+                    Ok(proof) => {
+                        let proof_json = serde_json::to_string(&proof)?;
+                        bootstrap::store_witness(&store, &dids_ks, &mnemonic, &proof_json).await?;
+                        eprintln!("  Witness proof stored.");
+                    }
+                    Err(e) => {
+                        eprintln!("  Warning: witness proof request failed: {e}");
                     }
-                } else {
-                    eprintln!("  Warning: could not extract versionId for witness proof.");
                 }
             }
         }
@@ -1168,7 +1284,8 @@ async fn run_remove_did(
     path: String,
 ) -> Result<(), Box<dyn std::error::Error>> {
     use did_hosting_common::did_ops::{
-        DidRecord, content_log_key, content_witness_key, did_key, owner_key,
+        DidRecord, content_did_doc_key, content_log_key, content_witness_key, did_key, owner_key,
+        rollback_proof_key, rolled_back_key,
     };
 
     let config = AppConfig::load(config_path)?;
@@ -1190,9 +1307,13 @@ async fn run_remove_did(
     eprintln!("  Owner:  {}", record.owner);
 
     let mut batch = store_handle.batch();
+    did_hosting_server::precompressed::stage_did_removal(&mut batch, &dids_ks, &path).await?;
     batch.remove(&dids_ks, did_key(&path));
     batch.remove(&dids_ks, content_log_key(&path));
     batch.remove(&dids_ks, content_witness_key(&path));
+    batch.remove(&dids_ks, content_did_doc_key(&path));
+    batch.remove(&dids_ks, rolled_back_key(&path));
+    batch.remove(&dids_ks, rollback_proof_key(&path));
     batch.remove(&dids_ks, owner_key(&record.owner, &path));
     batch.commit().await?;
 
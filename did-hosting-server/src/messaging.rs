@@ -359,7 +359,7 @@ async fn do_sync_batch(
 
 /// Apply one sync-update body — the shape `MSG_SYNC_UPDATE` carries and each
 /// element of a `MSG_SYNC_BATCH`. Returns the mnemonic on success. Runs the
-/// own-DID rotation check, so a batched update to the server's own DID is
+/// own-DID rotation check and pushes to watchers, so a batched update is
 /// treated exactly like a single one.
 async fn apply_sync_update_body(state: &AppState, body: &Value) -> Result<String, String> {
     use crate::control_register::apply_single_update;
@@ -385,6 +385,27 @@ async fn apply_sync_update_body(state: &AppState, body: &Value) -> Result<String
         .get("version_count")
         .and_then(|v| v.as_u64())
         .ok_or("missing 'version_count' in sync-update")?;
+    let previous_did_id = body
+        .get("previous_did_id")
+        .and_then(|v| v.as_str())
+        .map(String::from);
+    let rolled_back = body
+        .get("rolled_back")
+        .and_then(|v| v.as_array())
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(|e| e.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default();
+    let rollback_proof = body
+        .get("rollback_proof")
+        .and_then(|v| serde_json::from_value(v.clone()).ok());
+    let did_document = body
+        .get("did_document")
+        .and_then(|v| v.as_str())
+        .map(String::from);
 
     let update = DidSyncUpdate {
         mnemonic: mnemonic.to_string(),
@@ -392,6 +413,10 @@ async fn apply_sync_update_body(state: &AppState, body: &Value) -> Result<String
         log_content: log_content.to_string(),
         witness_content,
         version_count,
+        previous_did_id,
+        rolled_back,
+        rollback_proof,
+        did_document,
     };
 
     apply_single_update(&state.dids_ks, &state.store, &update, &state.did_cache)
@@ -411,6 +436,15 @@ async fn apply_sync_update_body(state: &AppState, body: &Value) -> Result<String
     // new log entry for it. Same rotation check as the direct publish path.
     crate::identity_rotation::on_did_published(state, mnemonic).await;
 
+    // Watchers hear about control-plane changes the same way they hear about
+    // a direct publish: from the server that applied them.
+    crate::watcher_push::notify_watchers_did(
+        &state.config,
+        &state.http_client,
+        &state.dids_ks,
+        mnemonic.to_string(),
+    );
+
     Ok(mnemonic.to_string())
 }
 
@@ -439,14 +473,27 @@ async fn do_sync_delete(
 
     if let Some(record) = record {
         let mut batch = state.store.batch();
+        crate::precompressed::stage_did_removal(&mut batch, &state.dids_ks, mnemonic)
+            .await
+            .map_err(|e| e.to_string())?;
         batch.remove(&state.dids_ks, did_ops::did_key(mnemonic));
         batch.remove(&state.dids_ks, did_ops::content_log_key(mnemonic));
         batch.remove(&state.dids_ks, did_ops::content_witness_key(mnemonic));
+        batch.remove(&state.dids_ks, did_ops::content_did_doc_key(mnemonic));
         batch.remove(&state.dids_ks, did_ops::owner_key(&record.owner, mnemonic));
         batch.remove(&state.dids_ks, did_ops::watcher_sync_key(mnemonic));
+        batch.remove(&state.dids_ks, did_ops::previous_did_key(mnemonic));
+        batch.remove(&state.dids_ks, did_ops::rolled_back_key(mnemonic));
+        batch.remove(&state.dids_ks, did_ops::rollback_proof_key(mnemonic));
         batch.commit().await.map_err(|e| e.to_string())?;
 
         info!(did = sender, mnemonic = %mnemonic, "deleted DID via sync from control plane");
+        crate::watcher_push::notify_watchers_delete(
+            &state.config,
+            &state.http_client,
+            &state.dids_ks,
+            mnemonic.to_string(),
+        );
     } else {
         info!(mnemonic = %mnemonic, "sync delete: DID not found locally");
     }
@@ -174,4 +174,29 @@ mod tests {
         assert_eq!(path.len(), 255);
         assert!(validate_custom_path(&path).is_ok());
     }
+
+    #[cfg(feature = "method-webs")]
+    #[test]
+    fn webs_mnemonic_accepts_aid_final_segment() {
+        use did_hosting_common::server::mnemonic::validate_webs_mnemonic;
+        let aid = format!("E{}", "A".repeat(43));
+        assert!(validate_webs_mnemonic(&aid).is_ok());
+        assert!(validate_webs_mnemonic(&format!("people/{aid}")).is_ok());
+        // The prefix still follows the normal path grammar.
+        assert!(validate_webs_mnemonic(&format!("People/{aid}")).is_err());
+        // And the final segment must be an AID.
+        assert!(validate_webs_mnemonic("people/alice").is_err());
+    }
+
+    #[cfg(feature = "method-webplus")]
+    #[test]
+    fn webplus_mnemonic_accepts_root_self_hash_final_segment() {
+        use did_hosting_common::method::webplus::SelfHashAlgorithm;
+        use did_hosting_common::server::mnemonic::validate_webplus_mnemonic;
+        let root = SelfHashAlgorithm::Blake3.placeholder();
+        assert!(validate_webplus_mnemonic(&root).is_ok());
+        assert!(validate_webplus_mnemonic(&format!("people/{root}")).is_ok());
+        assert!(validate_webplus_mnemonic(&format!("People/{root}")).is_err());
+        assert!(validate_webplus_mnemonic("people/alice").is_err());
+    }
 }
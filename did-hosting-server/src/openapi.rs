@@ -49,6 +49,7 @@ impl utoipa::Modify for SecurityAddon {
     paths(
         did_manage::upload_did,
         did_manage::upload_witness,
+        did_manage::upload_did_document,
         did_manage::get_did,
         did_manage::get_did_log,
         did_manage::list_dids,
@@ -0,0 +1,146 @@
+//! Compressed variants of served content, built when the content is written.
+//!
+//! The write paths — publish, witness upload, rollback, control-plane sync
+//! and bootstrap — run [`encoding::precompress`] over the bytes they store
+//! and stage the variants in the same batch, under
+//! `{key}:{encoding}:{tag}`, where `tag` is a SHA-256 of the exact bytes the
+//! variant was made from. A resolve looks a variant up by the hash of what it
+//! is about to serve, so a variant left over from an older version, or
+//! content written by a path that builds none, is never served for the
+//! wrong bytes: the resolve then compresses on demand, as before (see
+//! `routes::resolve_shared::encoded_response`).
+//!
+//! `{key}:variants` holds the tag the stored variants were built for, so
+//! the next write removes them.
+
+use did_hosting_common::did_ops::{content_did_doc_key, content_log_key, content_witness_key};
+use did_hosting_common::server::conditional::content_etag;
+use did_hosting_common::server::encoding::{self, Encoding};
+use tracing::warn;
+
+use crate::error::AppError;
+use crate::store::{KeyspaceHandle, WriteBatch};
+
+/// Where the `encoding` variant of the content under `key` is kept, for
+/// the bytes hashing to `tag`.
+pub fn variant_key(key: &str, encoding: Encoding, tag: &str) -> String {
+    format!("{key}:{}:{tag}", encoding.token())
+}
+
+/// The tag variants of `bytes` are stored under: its content ETag,
+/// unquoted (`sha256-…`).
+pub fn tag_of(bytes: &[u8]) -> String {
+    content_etag(bytes).trim_matches('"').to_string()
+}
+
+fn tag_key(key: &str) -> String {
+    format!("{key}:variants")
+}
+
+/// Compressed variants of content about to be written, ready to stage next
+/// to it.
+pub struct Variants {
+    key: String,
+    tag: String,
+    previous: Option<String>,
+    variants: Vec<(Encoding, Vec<u8>)>,
+}
+
+impl Variants {
+    /// Compress `bytes` on the blocking pool. A failed compression only
+    /// costs the variants: resolves then compress on demand.
+    pub async fn build(ks: &KeyspaceHandle, key: &str, bytes: &[u8]) -> Result<Self, AppError> {
+        let previous = previous_tag(ks, key).await?;
+        let source = bytes.to_vec();
+        let log_key = key.to_string();
+        let (tag, variants) = tokio::task::spawn_blocking(move || {
+            let variants = encoding::precompress(&source).unwrap_or_else(|e| {
+                warn!(key = %log_key, error = %e, "failed to precompress content");
+                Vec::new()
+            });
+            (tag_of(&source), variants)
+        })
+        .await
+        .map_err(|e| AppError::Internal(format!("compression task failed: {e}")))?;
+        Ok(Self {
+            key: key.to_string(),
+            tag,
+            previous,
+            variants,
+        })
+    }
+
+    /// Write the variants and drop the previous version's.
+    pub fn stage(self, batch: &mut WriteBatch, ks: &KeyspaceHandle) {
+        if let Some(previous) = self.previous.filter(|p| *p != self.tag) {
+            for encoding in Encoding::COMPRESSED {
+                batch.remove(ks, variant_key(&self.key, encoding, &previous));
+            }
+        }
+        for encoding in Encoding::COMPRESSED {
+            let key = variant_key(&self.key, encoding, &self.tag);
+            match self.variants.iter().find(|(e, _)| *e == encoding) {
+                Some((_, bytes)) => batch.insert_raw(ks, key, bytes.clone()),
+                None => batch.remove(ks, key),
+            }
+        }
+        batch.insert_raw(ks, tag_key(&self.key), self.tag.into_bytes());
+    }
+}
+
+/// Stage removal of the variants kept for the content under `key`, for a
+/// write path that removes the content itself.
+pub async fn stage_removal(
+    batch: &mut WriteBatch,
+    ks: &KeyspaceHandle,
+    key: &str,
+) -> Result<(), AppError> {
+    if let Some(previous) = previous_tag(ks, key).await? {
+        for encoding in Encoding::COMPRESSED {
+            batch.remove(ks, variant_key(key, encoding, &previous));
+        }
+    }
+    batch.remove(ks, tag_key(key));
+    Ok(())
+}
+
+/// [`stage_removal`] for every artifact a DID slot serves.
+pub async fn stage_did_removal(
+    batch: &mut WriteBatch,
+    ks: &KeyspaceHandle,
+    mnemonic: &str,
+) -> Result<(), AppError> {
+    for key in served_keys(mnemonic) {
+        stage_removal(batch, ks, &key).await?;
+    }
+    Ok(())
+}
+
+/// Remove the variants of every artifact a DID slot serves, for a path
+/// that removes the slot key by key rather than in a batch.
+pub async fn remove_did(ks: &KeyspaceHandle, mnemonic: &str) -> Result<(), AppError> {
+    for key in served_keys(mnemonic) {
+        if let Some(previous) = previous_tag(ks, &key).await? {
+            for encoding in Encoding::COMPRESSED {
+                ks.remove(variant_key(&key, encoding, &previous)).await?;
+            }
+        }
+        ks.remove(tag_key(&key)).await?;
+    }
+    Ok(())
+}
+
+fn served_keys(mnemonic: &str) -> [String; 3] {
+    [
+        content_log_key(mnemonic),
+        content_witness_key(mnemonic),
+        content_did_doc_key(mnemonic),
+    ]
+}
+
+async fn previous_tag(ks: &KeyspaceHandle, key: &str) -> Result<Option<String>, AppError> {
+    Ok(ks
+        .get_raw(tag_key(key))
+        .await?
+        .map(|tag| String::from_utf8_lossy(&tag).into_owned()))
+}
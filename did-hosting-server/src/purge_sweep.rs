@@ -184,6 +184,8 @@ mod tests {
             domain: domain.into(),
             services: None,
             agent_names: Vec::new(),
+            deactivated_at: None,
+            sync_head: None,
         }
     }
 
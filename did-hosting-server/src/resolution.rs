@@ -0,0 +1,396 @@
+//! DID resolution from the local store.
+//!
+//! Turns a hosted DID plus W3C DID Resolution options (`versionId`,
+//! `versionNumber`, `versionTime`) into a DID document and its
+//! `didDocumentMetadata`, working from the stored did:webvh log. Used by
+//! the Universal-Resolver-compatible `GET /1.0/identifiers/{did}` route;
+//! the raw-artifact routes (`did.jsonl`, `did.json`) do not go through
+//! here.
+//!
+//! Materialised versions are cached in the server's [`ContentCache`] by
+//! [`resolve_cached`], keyed on the log's head `versionId` so a publish
+//! can never serve a stale answer.
+//!
+//! did:web DIDs are the bridge view of a webvh log (see
+//! [`crate::routes::resolve_web`]): the same version is selected, then the
+//! document is rewritten to the did:web identifier.
+
+use chrono::{DateTime, FixedOffset};
+use did_hosting_common::did_ops::LogEntryInfo;
+use did_hosting_common::did_ops::parse_log_entries;
+use did_hosting_common::method::{ParsedDid, method_by_name, parse_did_method};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::cache::ContentCache;
+
+/// Resolution options accepted as query parameters.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolutionOptions {
+    /// Exact `versionId` of the entry to resolve.
+    pub version_id: Option<String>,
+    /// 1-based entry number (the `N` of a `N-hash` versionId).
+    pub version_number: Option<u64>,
+    /// RFC 3339 instant; resolves the entry in force at that time.
+    pub version_time: Option<String>,
+}
+
+/// `didDocumentMetadata` of a DID Resolution Result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_number: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_version_id: Option<String>,
+    pub deactivated: bool,
+}
+
+/// A resolved version of a DID.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedVersion {
+    pub document: Value,
+    pub metadata: DocumentMetadata,
+}
+
+/// Why resolution failed, in DID Resolution `error` terms.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolutionError {
+    /// `invalidDid` — not a DID this server can parse.
+    InvalidDid(String),
+    /// `notFound` — no such DID, or no such version of it.
+    NotFound(String),
+    /// `invalidOptions` — malformed or conflicting resolution options.
+    InvalidOptions(String),
+}
+
+impl ResolutionError {
+    /// The DID Resolution `error` code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ResolutionError::InvalidDid(_) => "invalidDid",
+            ResolutionError::NotFound(_) => "notFound",
+            ResolutionError::InvalidOptions(_) => "invalidOptions",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            ResolutionError::InvalidDid(m)
+            | ResolutionError::NotFound(m)
+            | ResolutionError::InvalidOptions(m) => m,
+        }
+    }
+}
+
+/// Parse a DID and derive the mnemonic it is stored under.
+///
+/// The DID path (`:`-joined) becomes the `/`-joined mnemonic; a pathless
+/// DID is the root DID, stored under `.well-known`.
+pub fn locate(did: &str) -> Result<(ParsedDid, String), ResolutionError> {
+    let invalid = |e: String| ResolutionError::InvalidDid(format!("{did}: {e}"));
+    let method_name = parse_did_method(did).map_err(|e| invalid(e.to_string()))?;
+    let method = method_by_name(method_name)
+        .filter(|m| matches!(m.name(), "webvh" | "web"))
+        .ok_or_else(|| invalid(format!("method '{method_name}' is not resolvable here")))?;
+    let parsed = method
+        .parse_identifier(did)
+        .map_err(|e| invalid(e.to_string()))?;
+    let mnemonic = if parsed.path.is_empty() {
+        ".well-known".to_string()
+    } else {
+        parsed.path.replace(':', "/")
+    };
+    Ok((parsed, mnemonic))
+}
+
+/// Pick the log entry `options` ask for: the latest when none are given.
+/// At most one option may be set.
+pub fn select_version(
+    entries: &[LogEntryInfo],
+    options: &ResolutionOptions,
+) -> Result<usize, ResolutionError> {
+    if entries.is_empty() {
+        return Err(ResolutionError::NotFound("log has no entries".into()));
+    }
+    let set = [
+        options.version_id.is_some(),
+        options.version_number.is_some(),
+        options.version_time.is_some(),
+    ];
+    if set.iter().filter(|s| **s).count() > 1 {
+        return Err(ResolutionError::InvalidOptions(
+            "versionId, versionNumber and versionTime are mutually exclusive".into(),
+        ));
+    }
+
+    if let Some(version_id) = &options.version_id {
+        return entries
+            .iter()
+            .position(|e| e.version_id.as_deref() == Some(version_id.as_str()))
+            .ok_or_else(|| ResolutionError::NotFound(format!("no version {version_id}")));
+    }
+    if let Some(number) = options.version_number {
+        return entries
+            .iter()
+            .position(|e| version_number(e.version_id.as_deref()) == Some(number))
+            .ok_or_else(|| ResolutionError::NotFound(format!("no version number {number}")));
+    }
+    if let Some(version_time) = &options.version_time {
+        let at = parse_time(version_time).ok_or_else(|| {
+            ResolutionError::InvalidOptions(format!(
+                "versionTime is not an RFC 3339 timestamp: {version_time}"
+            ))
+        })?;
+        // Entries are in time order, so the one in force is the last one
+        // not after `at`.
+        return entries
+            .iter()
+            .rposition(|e| {
+                e.version_time
+                    .as_deref()
+                    .and_then(parse_time)
+                    .is_some_and(|t| t <= at)
+            })
+            .ok_or_else(|| {
+                ResolutionError::NotFound(format!("DID did not exist at {version_time}"))
+            });
+    }
+    Ok(entries.len() - 1)
+}
+
+/// Resolve `options` against a stored log, through `cache`.
+///
+/// Cache keys are `version:{mnemonic}:{head}:{selector}`, where `head` is
+/// the log's last `versionId`: a publish changes the head, so entries for
+/// the old log are simply never read again and age out with the TTL.
+/// Only the bounded selectors are cached — latest, a `versionId` or a
+/// `versionNumber` that exists — so arbitrary `versionTime` values cannot
+/// grow the cache; a `versionTime` lookup is stored under the `versionId`
+/// it landed on instead.
+pub fn resolve_cached(
+    cache: &ContentCache,
+    mnemonic: &str,
+    log: &[u8],
+    options: &ResolutionOptions,
+) -> Result<ResolvedVersion, ResolutionError> {
+    let log = String::from_utf8_lossy(log);
+    let head = log
+        .lines()
+        .rfind(|l| !l.trim().is_empty())
+        .and_then(|l| serde_json::from_str::<Value>(l).ok())
+        .and_then(|v| v.get("versionId")?.as_str().map(str::to_string))
+        .ok_or_else(|| ResolutionError::NotFound("log has no entries".into()))?;
+    let key = |selector: &str| format!("version:{mnemonic}:{head}:{selector}");
+
+    let selector = match (
+        &options.version_id,
+        options.version_number,
+        &options.version_time,
+    ) {
+        (None, None, None) => Some("latest".to_string()),
+        (Some(id), None, None) => Some(format!("id={id}")),
+        (None, Some(n), None) => Some(format!("n={n}")),
+        _ => None,
+    };
+    if let Some(selector) = &selector
+        && let Some(hit) = cache.get(&key(selector))
+        && let Ok(resolved) = serde_json::from_slice::<ResolvedVersion>(&hit)
+    {
+        return Ok(resolved);
+    }
+
+    let entries = parse_log_entries(&log);
+    let idx = select_version(&entries, options)?;
+    let resolved = materialise(&entries, idx)
+        .ok_or_else(|| ResolutionError::NotFound("entry has no document".into()))?;
+
+    if let Ok(bytes) = serde_json::to_vec(&resolved) {
+        if let Some(selector) = &selector {
+            cache.insert(key(selector), bytes.clone());
+        }
+        if let Some(id) = &resolved.metadata.version_id {
+            cache.insert(key(&format!("id={id}")), bytes);
+        }
+    }
+    Ok(resolved)
+}
+
+/// The `N` of an `N-hash` versionId.
+fn version_number(version_id: Option<&str>) -> Option<u64> {
+    version_id?.split_once('-')?.0.parse().ok()
+}
+
+/// Materialise entry `idx` as a document plus metadata.
+///
+/// `deactivated` reflects the parameters in force at `idx`: webvh
+/// parameters are deltas, so the last entry up to `idx` that mentions
+/// `deactivated` decides.
+pub fn materialise(entries: &[LogEntryInfo], idx: usize) -> Option<ResolvedVersion> {
+    let entry = entries.get(idx)?;
+    let document = entry.state.clone()?;
+    let deactivated = entries[..=idx]
+        .iter()
+        .rev()
+        .find_map(|e| e.parameters.as_ref()?.get("deactivated")?.as_bool())
+        .unwrap_or(false);
+    Some(ResolvedVersion {
+        document,
+        metadata: DocumentMetadata {
+            created: entries[0].version_time.clone(),
+            updated: entry.version_time.clone(),
+            version_id: entry.version_id.clone(),
+            version_number: version_number(entry.version_id.as_deref()),
+            next_version_id: entries.get(idx + 1).and_then(|e| e.version_id.clone()),
+            deactivated,
+        },
+    })
+}
+
+fn parse_time(s: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(s).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(version_id: &str, version_time: &str, deactivated: Option<bool>) -> LogEntryInfo {
+        LogEntryInfo {
+            version_id: Some(version_id.into()),
+            version_time: Some(version_time.into()),
+            state: Some(serde_json::json!({ "id": "did:webvh:scid:example.com:alice" })),
+            parameters: Some(match deactivated {
+                Some(d) => serde_json::json!({ "deactivated": d }),
+                None => serde_json::json!({}),
+            }),
+        }
+    }
+
+    fn log() -> Vec<LogEntryInfo> {
+        vec![
+            entry("1-a", "2025-01-01T00:00:00Z", None),
+            entry("2-b", "2025-02-01T00:00:00Z", None),
+            entry("3-c", "2025-03-01T00:00:00Z", Some(true)),
+        ]
+    }
+
+    fn opts(version_id: Option<&str>, version_time: Option<&str>) -> ResolutionOptions {
+        ResolutionOptions {
+            version_id: version_id.map(Into::into),
+            version_number: None,
+            version_time: version_time.map(Into::into),
+        }
+    }
+
+    #[test]
+    fn selects_latest_by_default() {
+        assert_eq!(select_version(&log(), &opts(None, None)), Ok(2));
+    }
+
+    #[test]
+    fn selects_by_version_id_and_time() {
+        assert_eq!(select_version(&log(), &opts(Some("2-b"), None)), Ok(1));
+        assert_eq!(
+            select_version(&log(), &opts(None, Some("2025-02-15T00:00:00Z"))),
+            Ok(1)
+        );
+        assert_eq!(
+            select_version(&log(), &opts(None, Some("2025-02-01T00:00:00Z"))),
+            Ok(1)
+        );
+    }
+
+    #[test]
+    fn reports_missing_and_conflicting_options() {
+        assert_eq!(
+            select_version(&log(), &opts(Some("9-z"), None))
+                .unwrap_err()
+                .code(),
+            "notFound"
+        );
+        assert_eq!(
+            select_version(&log(), &opts(None, Some("2024-12-31T00:00:00Z")))
+                .unwrap_err()
+                .code(),
+            "notFound"
+        );
+        assert_eq!(
+            select_version(&log(), &opts(Some("1-a"), Some("2025-01-01T00:00:00Z")))
+                .unwrap_err()
+                .code(),
+            "invalidOptions"
+        );
+    }
+
+    #[test]
+    fn selects_by_version_number() {
+        let by_number = |n| ResolutionOptions {
+            version_number: Some(n),
+            ..Default::default()
+        };
+        assert_eq!(select_version(&log(), &by_number(2)), Ok(1));
+        assert_eq!(
+            select_version(&log(), &by_number(4)).unwrap_err().code(),
+            "notFound"
+        );
+    }
+
+    #[test]
+    fn cached_resolution_is_keyed_on_log_head() {
+        let cache = ContentCache::new(std::time::Duration::from_secs(60));
+        let line = |id: &str, controller: &str| {
+            serde_json::json!({
+                "versionId": id,
+                "versionTime": "2025-01-01T00:00:00Z",
+                "state": { "controller": controller },
+            })
+            .to_string()
+        };
+        let v1 = format!("{}\n", line("1-a", "one"));
+        let v2 = format!("{v1}{}\n", line("2-b", "two"));
+        let latest = ResolutionOptions::default();
+
+        let r = resolve_cached(&cache, "m", v1.as_bytes(), &latest).unwrap();
+        assert_eq!(r.document["controller"], "one");
+        assert_eq!(r.metadata.next_version_id, None);
+
+        // After a publish the head moves, so the cached latest is not reused.
+        let r = resolve_cached(&cache, "m", v2.as_bytes(), &latest).unwrap();
+        assert_eq!(r.document["controller"], "two");
+        let r = resolve_cached(&cache, "m", v2.as_bytes(), &opts(Some("1-a"), None)).unwrap();
+        assert_eq!(r.metadata.next_version_id.as_deref(), Some("2-b"));
+        assert_eq!(r.metadata.version_number, Some(1));
+    }
+
+    #[test]
+    fn metadata_links_versions_and_tracks_deactivation() {
+        let entries = log();
+        let first = materialise(&entries, 0).unwrap().metadata;
+        assert_eq!(first.created.as_deref(), Some("2025-01-01T00:00:00Z"));
+        assert_eq!(first.next_version_id.as_deref(), Some("2-b"));
+        assert!(!first.deactivated);
+
+        let last = materialise(&entries, 2).unwrap().metadata;
+        assert_eq!(last.updated.as_deref(), Some("2025-03-01T00:00:00Z"));
+        assert_eq!(last.next_version_id, None);
+        assert!(last.deactivated);
+    }
+
+    #[cfg(feature = "method-web")]
+    #[test]
+    fn locates_root_and_path_dids() {
+        let (_, m) = locate("did:web:example.com").unwrap();
+        assert_eq!(m, ".well-known");
+        let (_, m) = locate("did:web:example.com:people:alice").unwrap();
+        assert_eq!(m, "people/alice");
+        assert_eq!(locate("not-a-did").unwrap_err().code(), "invalidDid");
+    }
+}
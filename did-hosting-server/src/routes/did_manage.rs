@@ -32,6 +32,9 @@ pub struct DidDetailResponse {
     pub method: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub domain: Option<String>,
+    /// Set once the log ends in a `deactivated: true` entry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deactivated_at: Option<u64>,
 }
 
 impl DidDetailResponse {
@@ -54,6 +57,7 @@ impl DidDetailResponse {
             watcher_sync,
             method,
             domain,
+            deactivated_at: record.deactivated_at,
         }
     }
 }
@@ -190,6 +194,38 @@ pub async fn upload_witness(
     Ok(StatusCode::NO_CONTENT)
 }
 
+// ---------- PUT /did-document/{mnemonic} ----------
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    put,
+    path = "/api/did-document/{mnemonic}",
+    tag = "dids",
+    params(("mnemonic" = String, Path, description = "Slot path (mnemonic); may contain '/'")),
+    request_body(
+        content = String,
+        description = "did:webs DID document (did.json), checked against the slot's KEL",
+        content_type = "application/did+json",
+    ),
+    responses(
+        (status = 204, description = "DID document stored"),
+        (status = 400, description = "Not a did:webs slot, or the document does not match the KEL"),
+        (status = 401, description = "Missing/invalid bearer token"),
+        (status = 403, description = "Caller not authorized for this slot"),
+        (status = 404, description = "No KEL published for this slot"),
+    ),
+    security(("bearer" = [])),
+))]
+pub async fn upload_did_document(
+    auth: AuthClaims,
+    State(state): State<AppState>,
+    Path(mnemonic): Path<String>,
+    body: String,
+) -> Result<StatusCode, AppError> {
+    let mnemonic = clean_mnemonic(&mnemonic);
+    did_ops::upload_did_document(&auth, &state, mnemonic, &body).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 // ---------- DELETE /dids/{mnemonic} ----------
 
 #[cfg_attr(feature = "openapi", utoipa::path(
@@ -13,7 +13,15 @@
 //! 1. **did:webvh** (when `method-webvh` is on). Webvh's URLs end in
 //!    `/did.jsonl` or `/did-witness.json`; both are method-exclusive,
 //!    so registering webvh first doesn't shadow other methods.
-//! 2. **did:web** (when `method-web` is on). Web's URL ends in
+//! 2. **did:webs** (when `method-webs` is on). `/keri.cesr` is
+//!    webs-exclusive; `/did.json` is shared with did:web, so the webs
+//!    dispatcher only claims it for slots whose record is tagged
+//!    `webs`. It must run before did:web for that check to matter.
+//! 3. **did:webplus** (when `method-webplus` is on). Its suffixes
+//!    (`/did-documents.jsonl`, `/did/selfHash/{h}.json`,
+//!    `/did/versionId/{n}.json`) are method-exclusive, so placement
+//!    only needs to be ahead of the catch-all 404.
+//! 4. **did:web** (when `method-web` is on). Web's URL ends in
 //!    `/did.json`. Lower priority than webvh purely as a convention —
 //!    webvh's bridge handler at `resolve_web` shares the same suffix,
 //!    so once T26 wires up record-method-aware dispatch, the order
@@ -36,6 +44,10 @@ use axum::response::{IntoResponse, Response};
 
 #[cfg(feature = "method-web")]
 use super::resolve_web;
+#[cfg(feature = "method-webplus")]
+use super::resolve_webplus;
+#[cfg(feature = "method-webs")]
+use super::resolve_webs;
 #[cfg(feature = "method-webvh")]
 use super::resolve_webvh;
 use crate::server::AppState;
@@ -58,9 +70,10 @@ use crate::server::AppState;
 pub async fn serve_public(State(state): State<AppState>, request: Request) -> Response {
     let (parts, _) = request.into_parts();
 
-    // Order matters when methods share suffixes; today they don't, but
-    // the iteration is encoded explicitly anyway so the priority is
-    // visible at the call site rather than buried in router config.
+    // Order matters when methods share suffixes — webs and web both
+    // serve `/did.json` — so the iteration is encoded explicitly and the
+    // priority is visible at the call site rather than buried in router
+    // config.
 
     #[cfg(feature = "method-webvh")]
     {
@@ -69,6 +82,20 @@ pub async fn serve_public(State(state): State<AppState>, request: Request) -> Re
         }
     }
 
+    #[cfg(feature = "method-webs")]
+    {
+        if let Some(response) = resolve_webs::dispatch(&state, &parts).await {
+            return response;
+        }
+    }
+
+    #[cfg(feature = "method-webplus")]
+    {
+        if let Some(response) = resolve_webplus::dispatch(&state, &parts).await {
+            return response;
+        }
+    }
+
     #[cfg(feature = "method-web")]
     {
         if let Some(response) = resolve_web::dispatch(&state, &parts).await {
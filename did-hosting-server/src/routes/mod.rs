@@ -6,11 +6,18 @@ pub mod did_public;
 pub(crate) mod health;
 pub mod resolve_agent_name;
 mod resolve_shared;
+#[cfg(feature = "method-webvh")]
+pub mod resolve_universal;
 #[cfg(feature = "method-web")]
 pub mod resolve_web;
+#[cfg(feature = "method-webplus")]
+pub mod resolve_webplus;
+#[cfg(feature = "method-webs")]
+pub mod resolve_webs;
 #[cfg(feature = "method-webvh")]
 pub mod resolve_webvh;
 mod stats;
+mod sync_export;
 
 use axum::Router;
 use axum::extract::DefaultBodyLimit;
@@ -52,6 +59,10 @@ pub fn router_without_fallback(upload_body_limit: usize) -> Router<AppState> {
     let upload_routes = Router::new()
         .route("/dids/{*mnemonic}", put(did_manage::upload_did))
         .route("/witness/{*mnemonic}", put(did_manage::upload_witness))
+        .route(
+            "/did-document/{*mnemonic}",
+            put(did_manage::upload_did_document),
+        )
         .layer(DefaultBodyLimit::max(upload_body_limit));
 
     // API routes live under /api/ so they never collide with DID serving paths.
@@ -82,6 +93,9 @@ pub fn router_without_fallback(upload_body_limit: usize) -> Router<AppState> {
         .route("/stats/{*mnemonic}", get(stats::get_did_stats))
         // Server config (admin only)
         .route("/config", get(config::get_config))
+        // Watcher pull sync (bearer token from `watchers[].token`)
+        .route("/sync/dids", get(sync_export::list_dids))
+        .route("/sync/dids/{*mnemonic}", get(sync_export::get_did))
         // ACL management (admin only)
         .route("/acl", get(acl::list_acl).post(acl::create_acl))
         .route("/acl/{did}", put(acl::update_acl).delete(acl::delete_acl))
@@ -111,7 +125,9 @@ pub fn router_without_fallback(upload_body_limit: usize) -> Router<AppState> {
             .route(
                 "/.well-known/did-witness.json",
                 get(resolve_webvh::serve_root_witness),
-            );
+            )
+            // Universal Resolver driver surface (public, like the above).
+            .route("/1.0/identifiers/{did}", get(resolve_universal::resolve));
     }
     #[cfg(feature = "method-web")]
     {
@@ -141,7 +157,9 @@ pub fn router_public_only() -> Router<AppState> {
             .route(
                 "/.well-known/did-witness.json",
                 get(resolve_webvh::serve_root_witness),
-            );
+            )
+            // Universal Resolver driver surface (public, like the above).
+            .route("/1.0/identifiers/{did}", get(resolve_universal::resolve));
     }
     #[cfg(feature = "method-web")]
     {
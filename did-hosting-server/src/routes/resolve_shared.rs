@@ -1,17 +1,82 @@
 //! Shared helpers for per-method resolve handlers (T25).
 //!
-//! Today only the trusted-CIDR-gated request-host extractor (T19) is
-//! shared between methods. The cached-content reader (`serve_content`)
-//! is webvh-only — did:web has its own custom did.json extraction —
-//! so it lives in `resolve_webvh` directly. Adding a method that
-//! reuses the cached jsonl/witness shape (e.g. did:webs) would lift
-//! `serve_content` back up here.
+//! - [`extract_request_host`] — the trusted-CIDR-gated request-host
+//!   extractor (T19), used by every method.
+//! - [`serve_content`] — the cached raw-artifact reader shared by the
+//!   methods that serve stored bytes verbatim: webvh (`did.jsonl`,
+//!   `did-witness.json`), webs (`keri.cesr`, `did.json`) and webplus
+//!   (`did-documents.jsonl`). did:web has its own did.json extraction
+//!   and doesn't use it.
+//! - [`read_content`] / [`encoded_response`] — the two halves of
+//!   [`serve_content`]. did:web uses the second for its extracted
+//!   document; webplus per-version documents, cut from the stored
+//!   ledger, use the first and answer without negotiation.
+//!
+//! Which ETag an artifact gets is part of its [`Artifact`]: a did:webvh
+//! log is tagged by its last `versionId`, everything else by its bytes.
+//!
+//! Every response carries `ETag` / `Last-Modified` and honours
+//! `If-None-Match` / `If-Modified-Since` with a `304`, and `Vary:
+//! Accept-Encoding`. Bodies of [`encoding::MIN_COMPRESS_SIZE`] or more are
+//! sent gzip / brotli / zstd compressed when the client accepts it, with a
+//! per-encoding ETag.
+//!
+//! [`encoding::MIN_COMPRESS_SIZE`]: did_hosting_common::server::encoding::MIN_COMPRESS_SIZE
 
 use std::net::SocketAddr;
 
 use axum::extract::ConnectInfo;
 use axum::http::request::Parts;
+#[cfg(any(
+    feature = "method-webvh",
+    feature = "method-webs",
+    feature = "method-webplus"
+))]
+use axum::{http::HeaderMap, response::Response};
+#[cfg(any(
+    feature = "method-webvh",
+    feature = "method-webs",
+    feature = "method-webplus"
+))]
+use did_hosting_common::server::conditional::Validators;
+#[cfg(any(
+    feature = "method-webvh",
+    feature = "method-webs",
+    feature = "method-webplus"
+))]
+use did_hosting_common::server::domain::assert_resolution_allowed;
 use did_hosting_common::server::domain::{HostHeaders, resolve_request_host};
+#[cfg(any(
+    feature = "method-webvh",
+    feature = "method-webs",
+    feature = "method-webplus"
+))]
+use tracing::debug;
+
+#[cfg(any(
+    feature = "method-webvh",
+    feature = "method-webs",
+    feature = "method-webplus"
+))]
+use crate::cache::Cached;
+#[cfg(any(
+    feature = "method-webvh",
+    feature = "method-webs",
+    feature = "method-webplus"
+))]
+use crate::did_ops::{self, DidRecord};
+#[cfg(any(
+    feature = "method-webvh",
+    feature = "method-webs",
+    feature = "method-webplus"
+))]
+use crate::error::AppError;
+#[cfg(any(
+    feature = "method-webvh",
+    feature = "method-webs",
+    feature = "method-webplus"
+))]
+use crate::server::AppState;
 
 /// Extract the intended request host using the trusted-CIDR-gated
 /// resolver. Reads everything off [`Parts`] so the helper works for
@@ -46,3 +111,273 @@ pub(super) fn extract_request_host(
         .map(|ci| ci.0.ip());
     resolve_request_host(&h, peer_ip, trusted_cidrs).map(|s| s.to_string())
 }
+
+/// A kind of stored artifact: its content type and where its ETag comes
+/// from.
+#[cfg(any(
+    feature = "method-webvh",
+    feature = "method-webs",
+    feature = "method-webplus"
+))]
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Artifact {
+    pub content_type: &'static str,
+    /// The log's last `versionId` rather than a content hash
+    /// ([`Validators::for_log`]); only meaningful for a did:webvh log.
+    pub log_version_etag: bool,
+}
+
+#[cfg(any(
+    feature = "method-webvh",
+    feature = "method-webs",
+    feature = "method-webplus"
+))]
+impl Artifact {
+    /// A did:webvh log, tagged by its last entry's `versionId`.
+    pub const WEBVH_LOG: Self = Self {
+        content_type: "application/jsonl+json",
+        log_version_etag: true,
+    };
+
+    /// Any other artifact, tagged by a hash of its bytes.
+    pub const fn content(content_type: &'static str) -> Self {
+        Self {
+            content_type,
+            log_version_etag: false,
+        }
+    }
+
+    fn validators(&self, bytes: &[u8], updated_at: Option<u64>) -> Validators {
+        if self.log_version_etag {
+            Validators::for_log(bytes, updated_at)
+        } else {
+            Validators::for_content(bytes, updated_at)
+        }
+    }
+}
+
+/// Serve stored content for a mnemonic, optionally incrementing
+/// resolve stats. Runs the disabled/deleted check and the T21
+/// resolve-side safety check before returning bytes, and answers
+/// `304 Not Modified` when the request's validators still match.
+#[cfg(any(
+    feature = "method-webvh",
+    feature = "method-webs",
+    feature = "method-webplus"
+))]
+pub(super) async fn serve_content(
+    state: &AppState,
+    mnemonic: &str,
+    key: &str,
+    artifact: Artifact,
+    track_stats: bool,
+    request_host: Option<&str>,
+    request_headers: &HeaderMap,
+) -> Result<Response, AppError> {
+    let content = read_content(state, mnemonic, key, track_stats, request_host).await?;
+    debug!(
+        mnemonic = %mnemonic,
+        size = content.bytes.len(),
+        content_type = artifact.content_type,
+        "content resolved"
+    );
+    let validators = artifact.validators(&content.bytes, content.updated_at);
+    Ok(encoded_response(
+        state,
+        key,
+        request_headers,
+        &validators,
+        artifact.content_type,
+        &content.bytes,
+    )
+    .await)
+}
+
+/// Stored bytes plus the owning record's `updated_at`, for
+/// `Last-Modified`. `updated_at` is `None` for content with no record.
+#[cfg(any(
+    feature = "method-webvh",
+    feature = "method-webs",
+    feature = "method-webplus"
+))]
+pub(super) struct StoredContent {
+    pub bytes: std::sync::Arc<Vec<u8>>,
+    pub updated_at: Option<u64>,
+}
+
+/// The read half of [`serve_content`]: gate checks, cache-through read,
+/// and stats.
+#[cfg(any(
+    feature = "method-webvh",
+    feature = "method-webs",
+    feature = "method-webplus"
+))]
+pub(super) async fn read_content(
+    state: &AppState,
+    mnemonic: &str,
+    key: &str,
+    track_stats: bool,
+    request_host: Option<&str>,
+) -> Result<StoredContent, AppError> {
+    let mut updated_at = None;
+    if let Some(record) = state
+        .dids_ks
+        .get::<DidRecord>(did_ops::did_key(mnemonic))
+        .await?
+    {
+        if record.disabled || record.deleted_at.is_some() {
+            return Err(AppError::NotFound(format!("content not found: {mnemonic}")));
+        }
+        if let Some(host) = request_host
+            && let Some(ref did_id) = record.did_id
+            && let Err(e) = assert_resolution_allowed(&state.store, host, did_id).await
+        {
+            // A DID moved in place to a new domain keeps answering on its
+            // old host until the control plane drops `previous_did_id`.
+            let previous = state
+                .dids_ks
+                .get_raw(did_ops::previous_did_key(mnemonic))
+                .await?;
+            match previous {
+                Some(previous) => {
+                    let previous = String::from_utf8_lossy(&previous);
+                    assert_resolution_allowed(&state.store, host, &previous).await?
+                }
+                None => return Err(e),
+            }
+        }
+        updated_at = Some(record.updated_at);
+    }
+
+    let not_found = || AppError::NotFound(format!("content not found: {mnemonic}"));
+    let content = match state.did_cache.lookup(key) {
+        Some(Cached::Hit(cached)) => cached,
+        Some(Cached::Missing) => return Err(not_found()),
+        None => {
+            let Some(data) = state.dids_ks.get_raw(key).await? else {
+                state.did_cache.insert_missing(key.to_string());
+                return Err(not_found());
+            };
+            state.did_cache.insert(key.to_string(), data)
+        }
+    };
+
+    if track_stats && let Some(ref collector) = state.stats_collector {
+        collector.record_resolve(mnemonic);
+        #[cfg(feature = "metrics")]
+        did_hosting_common::server::metrics::inc_resolve();
+    }
+
+    Ok(StoredContent {
+        bytes: content,
+        updated_at,
+    })
+}
+
+/// The response half of [`serve_content`], compressed to what the
+/// client's `Accept-Encoding` prefers (see
+/// [`did_hosting_common::server::encoding`]).
+///
+/// DID logs are content-addressed (the SCID prevents content drift) and
+/// safe to cache aggressively, so the response carries an explicit
+/// public `Cache-Control` (overriding the global `no-store` security
+/// middleware) plus `ETag` / `Last-Modified`; see
+/// [`did_hosting_common::server::conditional`].
+///
+/// Compressed variants live in the content cache under
+/// `{key}:{encoding}:{etag}`. The ETag changes with the content, so a
+/// publish never serves an old variant; superseded ones age out. On a
+/// cache miss the variant the write path stored next to the content (see
+/// [`crate::precompressed`]) is used; only content without one — did:web's
+/// extracted document, or content a path wrote without variants — is
+/// compressed here, on the blocking pool. A variant that would not be
+/// smaller is cached empty, so the body is not compressed again on every
+/// request. A `304` is answered before anything is read or compressed.
+#[cfg(any(
+    feature = "method-webvh",
+    feature = "method-web",
+    feature = "method-webs",
+    feature = "method-webplus"
+))]
+pub(super) async fn encoded_response(
+    state: &crate::server::AppState,
+    key: &str,
+    request_headers: &axum::http::HeaderMap,
+    validators: &did_hosting_common::server::conditional::Validators,
+    content_type: &str,
+    body: &std::sync::Arc<Vec<u8>>,
+) -> axum::response::Response {
+    use did_hosting_common::server::conditional::{self, shared_body};
+    use did_hosting_common::server::encoding::{self, Encoding};
+
+    let identity = || {
+        conditional::respond_encoded(
+            request_headers,
+            validators,
+            content_type,
+            Encoding::Identity,
+            || shared_body(body),
+        )
+    };
+    if !encoding::worth_compressing(body) {
+        return identity();
+    }
+    let chosen = encoding::negotiate(request_headers, &Encoding::COMPRESSED);
+    if chosen == Encoding::Identity {
+        return identity();
+    }
+    if conditional::is_not_modified(request_headers, &validators.for_encoding(chosen)) {
+        return conditional::respond_encoded(
+            request_headers,
+            validators,
+            content_type,
+            chosen,
+            Default::default,
+        );
+    }
+
+    let variant_key = format!("{key}:{}:{}", chosen.token(), validators.etag);
+    let stored = || async {
+        let stored_key =
+            crate::precompressed::variant_key(key, chosen, &crate::precompressed::tag_of(body));
+        match state.dids_ks.get_raw(stored_key).await {
+            Ok(stored) => stored,
+            Err(e) => {
+                tracing::warn!(key, encoding = chosen.token(), error = %e, "reading stored variant failed");
+                None
+            }
+        }
+    };
+    let variant = match state.did_cache.get(&variant_key) {
+        Some(variant) => variant,
+        None => match stored().await {
+            Some(stored) => state.did_cache.insert(variant_key, stored),
+            None => {
+                let source = body.clone();
+                let compressed =
+                    tokio::task::spawn_blocking(move || encoding::compress(chosen, &source)).await;
+                match compressed {
+                    Ok(Ok(compressed)) if compressed.len() < body.len() => {
+                        state.did_cache.insert(variant_key, compressed)
+                    }
+                    Ok(Ok(_)) => state.did_cache.insert(variant_key, Vec::new()),
+                    Ok(Err(e)) => {
+                        tracing::warn!(key, encoding = chosen.token(), error = %e, "compression failed");
+                        return identity();
+                    }
+                    Err(e) => {
+                        tracing::warn!(key, encoding = chosen.token(), error = %e, "compression task failed");
+                        return identity();
+                    }
+                }
+            }
+        },
+    };
+    // An empty variant marks a body that does not shrink.
+    if variant.is_empty() {
+        return identity();
+    }
+    conditional::respond_encoded(request_headers, validators, content_type, chosen, || {
+        shared_body(&variant)
+    })
+}
@@ -0,0 +1,170 @@
+//! Universal-Resolver-compatible resolution (gated by `method-webvh`).
+//!
+//! `GET /1.0/identifiers/{did}` resolves a DID hosted on this server and
+//! returns a W3C DID Resolution Result, the shape a Universal Resolver
+//! driver proxies verbatim. Accepts `?versionId=`, `?versionNumber=` or
+//! `?versionTime=` to resolve a historical version — verifiers checking an
+//! old credential need the keys that were valid when it was signed.
+//!
+//! Covers `did:webvh` DIDs and their `did:web` bridge view — both are
+//! served from the stored webvh log, so the route needs `method-webvh`.
+//! Everything else comes back as `invalidDid` / `notFound` in
+//! `didResolutionMetadata`, with the matching HTTP status.
+
+use axum::extract::{OriginalUri, Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use did_hosting_common::did_ops::extract_did_web_document;
+use did_hosting_common::server::domain::{assert_resolution_allowed, extract_did_host};
+use serde_json::{Value, json};
+use tracing::debug;
+
+use super::resolve_shared::read_content;
+use crate::did_ops::{self, DidRecord};
+use crate::error::AppError;
+use crate::resolution::{self, ResolutionError, ResolutionOptions};
+use crate::server::AppState;
+
+const ROUTE_PREFIX: &str = "/1.0/identifiers/";
+const RESULT_CONTENT_TYPE: &str = "application/ld+json;profile=\"https://w3id.org/did-resolution\"";
+const RESOLUTION_CONTEXT: &str = "https://w3id.org/did-resolution/v1";
+
+/// `GET /1.0/identifiers/{did}`
+pub async fn resolve(
+    State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
+    Path(decoded): Path<String>,
+    Query(options): Query<ResolutionOptions>,
+) -> Result<Response, AppError> {
+    // Prefer the raw path: a DID may carry a literal `%3A` (ported host)
+    // that the router's decoding would turn into a path-changing `:`.
+    // Fall back to the decoded form for clients that encode the whole DID.
+    let did = uri
+        .path()
+        .strip_prefix(ROUTE_PREFIX)
+        .filter(|raw| raw.starts_with("did:"))
+        .map(str::to_string)
+        .unwrap_or(decoded);
+
+    match resolve_did(&state, &did, &options).await? {
+        Ok((document, metadata)) => {
+            let status = if metadata.deactivated {
+                StatusCode::GONE
+            } else {
+                StatusCode::OK
+            };
+            debug!(did = %did, version_id = ?metadata.version_id, "DID resolved");
+            Ok(result_response(
+                status,
+                json!({
+                    "@context": RESOLUTION_CONTEXT,
+                    "didDocument": document,
+                    "didResolutionMetadata": { "contentType": "application/did+json" },
+                    "didDocumentMetadata": metadata,
+                }),
+            ))
+        }
+        Err(e) => {
+            let status = match e {
+                ResolutionError::NotFound(_) => StatusCode::NOT_FOUND,
+                ResolutionError::InvalidDid(_) | ResolutionError::InvalidOptions(_) => {
+                    StatusCode::BAD_REQUEST
+                }
+            };
+            debug!(did = %did, error = e.code(), "DID resolution failed");
+            Ok(result_response(
+                status,
+                json!({
+                    "@context": RESOLUTION_CONTEXT,
+                    "didDocument": Value::Null,
+                    "didResolutionMetadata": {
+                        "error": e.code(),
+                        "errorMessage": e.message(),
+                    },
+                    "didDocumentMetadata": {},
+                }),
+            ))
+        }
+    }
+}
+
+/// Resolve `did` against the store. The outer `Result` carries store
+/// failures; the inner one, resolution outcomes the client should see.
+async fn resolve_did(
+    state: &AppState,
+    did: &str,
+    options: &ResolutionOptions,
+) -> Result<Result<(Value, resolution::DocumentMetadata), ResolutionError>, AppError> {
+    let not_found = || ResolutionError::NotFound(format!("{did} is not hosted here"));
+
+    let (parsed, mnemonic) = match resolution::locate(did) {
+        Ok(v) => v,
+        Err(e) => return Ok(Err(e)),
+    };
+    let record = match state
+        .dids_ks
+        .get::<DidRecord>(did_ops::did_key(&mnemonic))
+        .await?
+    {
+        Some(r) if r.method == "webvh" && !r.disabled && r.deleted_at.is_none() => r,
+        _ => return Ok(Err(not_found())),
+    };
+    if parsed.method == "webvh" && record.did_id.as_deref() != Some(did) {
+        return Ok(Err(not_found()));
+    }
+
+    // Same host / disabled-domain gate as the artifact routes, with the
+    // DID's own host standing in for the request's.
+    let Ok(host) = extract_did_host(did) else {
+        return Ok(Err(not_found()));
+    };
+    match assert_resolution_allowed(&state.store, &host, did).await {
+        Ok(()) => {}
+        Err(AppError::NotFound(_)) => return Ok(Err(not_found())),
+        Err(e) => return Err(e),
+    }
+
+    let content = match read_content(
+        state,
+        &mnemonic,
+        &did_ops::content_log_key(&mnemonic),
+        true,
+        None,
+    )
+    .await
+    {
+        Ok(c) => c,
+        Err(AppError::NotFound(_)) => return Ok(Err(not_found())),
+        Err(e) => return Err(e),
+    };
+    let resolved =
+        match resolution::resolve_cached(&state.did_cache, &mnemonic, &content.bytes, options) {
+            Ok(r) => r,
+            Err(e) => return Ok(Err(e)),
+        };
+
+    let document = if parsed.method == "web" {
+        // Reuse the did.json bridge on the selected entry: it checks
+        // `alsoKnownAs` and rewrites the identifier.
+        let line = json!({ "state": resolved.document }).to_string();
+        match extract_did_web_document(&line, did)
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        {
+            Some(doc) => doc,
+            None => return Ok(Err(not_found())),
+        }
+    } else {
+        resolved.document
+    };
+
+    Ok(Ok((document, resolved.metadata)))
+}
+
+fn result_response(status: StatusCode, body: Value) -> Response {
+    (
+        status,
+        [("content-type", RESULT_CONTENT_TYPE)],
+        body.to_string(),
+    )
+        .into_response()
+}
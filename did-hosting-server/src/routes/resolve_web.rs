@@ -20,13 +20,14 @@
 //! `data` directly".
 
 use axum::extract::{Request, State};
-use axum::http::{StatusCode, request::Parts};
+use axum::http::{HeaderMap, request::Parts};
 use axum::response::{IntoResponse, Response};
 use did_hosting_common::did::build_did_web_id;
+use did_hosting_common::server::conditional::Validators;
 use did_hosting_common::server::domain::assert_resolution_allowed;
 use tracing::debug;
 
-use super::resolve_shared::extract_request_host;
+use super::resolve_shared::{encoded_response, extract_request_host};
 use crate::did_ops::{self, DidRecord};
 use crate::error::AppError;
 use crate::mnemonic::validate_mnemonic;
@@ -38,7 +39,9 @@ async fn serve_did_web(
     state: &AppState,
     mnemonic: &str,
     request_host: Option<&str>,
+    request_headers: &HeaderMap,
 ) -> Result<Response, AppError> {
+    let mut updated_at = None;
     if let Some(record) = state
         .dids_ks
         .get::<DidRecord>(did_ops::did_key(mnemonic))
@@ -52,6 +55,7 @@ async fn serve_did_web(
         {
             assert_resolution_allowed(&state.store, host, did_id).await?;
         }
+        updated_at = Some(record.updated_at);
     }
 
     let content_bytes = state
@@ -76,12 +80,17 @@ async fn serve_did_web(
 
     debug!(mnemonic = %mnemonic, size = doc_bytes.len(), "did:web document resolved");
 
-    Ok((
-        StatusCode::OK,
-        [("content-type", "application/did+json")],
-        doc_bytes,
+    // The document is a view over the log, so hash what is actually served.
+    let validators = Validators::for_content(&doc_bytes, updated_at);
+    Ok(encoded_response(
+        state,
+        &format!("did_web:{mnemonic}"),
+        request_headers,
+        &validators,
+        "application/did+json",
+        &std::sync::Arc::new(doc_bytes),
     )
-        .into_response())
+    .await)
 }
 
 /// `GET /.well-known/did.json` — root-DID did:web document.
@@ -91,7 +100,7 @@ pub async fn serve_root_did_web(
 ) -> Result<Response, AppError> {
     let (parts, _) = request.into_parts();
     let host = extract_request_host(&parts, &state.trusted_proxy_cidrs);
-    serve_did_web(&state, ".well-known", host.as_deref()).await
+    serve_did_web(&state, ".well-known", host.as_deref(), &parts.headers).await
 }
 
 /// Catch-all dispatcher for did:web artifacts.
@@ -111,7 +120,7 @@ pub async fn dispatch(state: &AppState, parts: &Parts) -> Option<Response> {
             return Some(e.into_response());
         }
         return Some(
-            serve_did_web(state, mnemonic, host)
+            serve_did_web(state, mnemonic, host, &parts.headers)
                 .await
                 .unwrap_or_else(|e| e.into_response()),
         );
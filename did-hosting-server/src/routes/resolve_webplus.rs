@@ -0,0 +1,144 @@
+//! did:webplus resolution routes (gated by `method-webplus`).
+//!
+//! Serves the microledger and its per-version documents:
+//!
+//! - `GET /{*mnemonic}/did-documents.jsonl` — the whole ledger, stored at
+//!   [`did_ops::content_log_key`].
+//! - `GET /{*mnemonic}/did/selfHash/{selfHash}.json` and
+//!   `GET /{*mnemonic}/did/versionId/{versionId}.json` — one document,
+//!   cut from the same stored ledger (there is no per-version sidecar).
+//!
+//! The mnemonic always ends in the root self-hash (see
+//! `did_hosting_common::method::webplus`), so mnemonics are checked with
+//! [`validate_webplus_mnemonic`]. Every suffix here is webplus-exclusive,
+//! so matching requests are always claimed; they only serve slots whose
+//! [`DidRecord`] is tagged `method = "webplus"`.
+
+use axum::http::HeaderMap;
+use axum::http::request::Parts;
+use axum::response::{IntoResponse, Response};
+use did_hosting_common::method::webplus::{DID_DOCUMENTS_FILE, parse_microledger};
+use did_hosting_common::server::conditional::Validators;
+use did_hosting_common::server::mnemonic::validate_webplus_mnemonic;
+use tracing::warn;
+
+use super::resolve_shared::{Artifact, extract_request_host, read_content, serve_content};
+use crate::did_ops::{self, DidRecord};
+use crate::error::AppError;
+use crate::server::AppState;
+
+/// A single-document request: which document of the ledger to serve.
+enum Version<'a> {
+    SelfHash(&'a str),
+    VersionId(u64),
+}
+
+/// Catch-all dispatcher for did:webplus artifacts.
+///
+/// Returns:
+/// - `Some(response)` for `/did-documents.jsonl` and the per-version
+///   paths (404 unless the slot is webplus-tagged). Terminal.
+/// - `None` otherwise — the caller tries the next method.
+pub async fn dispatch(state: &AppState, parts: &Parts) -> Option<Response> {
+    let path = parts.uri.path().trim_start_matches('/');
+    let host = extract_request_host(parts, &state.trusted_proxy_cidrs);
+    let host = host.as_deref();
+
+    if let Some(mnemonic) = path
+        .strip_suffix(DID_DOCUMENTS_FILE)
+        .and_then(|p| p.strip_suffix('/'))
+        && !mnemonic.is_empty()
+    {
+        if let Err(e) = check_slot(state, mnemonic).await {
+            return Some(e.into_response());
+        }
+        let key = did_ops::content_log_key(mnemonic);
+        return Some(
+            serve_content(
+                state,
+                mnemonic,
+                &key,
+                Artifact::content("application/jsonl"),
+                true,
+                host,
+                &parts.headers,
+            )
+            .await
+            .unwrap_or_else(|e| e.into_response()),
+        );
+    }
+
+    let (mnemonic, version) = parse_version_path(path)?;
+    Some(
+        serve_version(state, mnemonic, version, host, &parts.headers)
+            .await
+            .unwrap_or_else(|e| e.into_response()),
+    )
+}
+
+/// Split `{mnemonic}/did/selfHash/{h}.json` or
+/// `{mnemonic}/did/versionId/{n}.json`. A malformed version id still
+/// matches (as an id that can't exist) so the request gets a 404 here
+/// rather than falling through to another method.
+fn parse_version_path(path: &str) -> Option<(&str, Version<'_>)> {
+    let path = path.strip_suffix(".json")?;
+    if let Some((mnemonic, hash)) = path.rsplit_once("/did/selfHash/")
+        && !mnemonic.is_empty()
+        && !hash.is_empty()
+    {
+        return Some((mnemonic, Version::SelfHash(hash)));
+    }
+    if let Some((mnemonic, id)) = path.rsplit_once("/did/versionId/")
+        && !mnemonic.is_empty()
+    {
+        return Some((mnemonic, Version::VersionId(id.parse().unwrap_or(u64::MAX))));
+    }
+    None
+}
+
+async fn serve_version(
+    state: &AppState,
+    mnemonic: &str,
+    version: Version<'_>,
+    host: Option<&str>,
+    request_headers: &HeaderMap,
+) -> Result<Response, AppError> {
+    check_slot(state, mnemonic).await?;
+    let key = did_ops::content_log_key(mnemonic);
+    let content = read_content(state, mnemonic, &key, true, host).await?;
+    let ledger = parse_microledger(&content.bytes).map_err(|e| {
+        // Only validated ledgers are stored, so this is corruption.
+        warn!(mnemonic = %mnemonic, error = %e, "stored did:webplus ledger failed to parse");
+        AppError::Internal(format!("stored ledger for {mnemonic} is invalid"))
+    })?;
+    let document = match version {
+        Version::SelfHash(hash) => ledger.by_self_hash(hash),
+        Version::VersionId(id) => ledger.by_version_id(id),
+    }
+    .ok_or_else(|| AppError::NotFound(format!("document version not found: {mnemonic}")))?;
+    // Each version's document is immutable once published, so its own
+    // bytes are the validator.
+    let body = document.document.clone().into_bytes();
+    let validators = Validators::for_content(&body, content.updated_at);
+    Ok(did_hosting_common::server::conditional::respond(
+        request_headers,
+        &validators,
+        "application/did+json",
+        body,
+    ))
+}
+
+/// Validate the mnemonic and require a webplus-tagged record — the log
+/// key holds whatever the slot's method stores, and only a webplus
+/// slot's log is a microledger.
+async fn check_slot(state: &AppState, mnemonic: &str) -> Result<(), AppError> {
+    validate_webplus_mnemonic(mnemonic)?;
+    match state
+        .dids_ks
+        .get::<DidRecord>(did_ops::did_key(mnemonic))
+        .await?
+    {
+        Some(record) if record.method == "webplus" => Ok(()),
+        _ => Err(AppError::NotFound(format!("content not found: {mnemonic}"))),
+    }
+}
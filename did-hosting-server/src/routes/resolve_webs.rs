@@ -0,0 +1,109 @@
+//! did:webs resolution routes (gated by `method-webs`).
+//!
+//! Serves the two artifacts a did:webs DID publishes at its HTTPS
+//! origin:
+//!
+//! - `GET /{*mnemonic}/did.json` — the rendered DID document, stored at
+//!   [`did_ops::content_did_doc_key`].
+//! - `GET /{*mnemonic}/keri.cesr` — the KEL stream, stored at
+//!   [`did_ops::content_log_key`].
+//!
+//! The mnemonic always ends in the AID (see
+//! `did_hosting_common::method::webs`), so there is no `.well-known`
+//! root variant, and mnemonics are checked with
+//! [`validate_webs_mnemonic`] — the AID's mixed-case CESR alphabet
+//! would fail the plain lowercase path grammar.
+//!
+//! ## Sharing `/did.json` with did:web
+//!
+//! did:web serves the same suffix. Rather than own the suffix, this
+//! dispatcher claims a `/did.json` request only when the slot's
+//! [`DidRecord`] is tagged `method = "webs"` and returns `None`
+//! otherwise, so [`super::resolve_web::dispatch`] (registered after it
+//! in `did_public`) still sees every did:web request. `/keri.cesr` is
+//! webs-exclusive, so it is always claimed, but it too only serves
+//! webs-tagged slots.
+
+use axum::http::request::Parts;
+use axum::response::{IntoResponse, Response};
+use did_hosting_common::method::webs::KERI_CESR_CONTENT_TYPE;
+use did_hosting_common::server::mnemonic::validate_webs_mnemonic;
+
+use super::resolve_shared::{Artifact, extract_request_host, serve_content};
+use crate::did_ops::{self, DidRecord};
+use crate::error::AppError;
+use crate::server::AppState;
+
+/// Catch-all dispatcher for did:webs artifacts.
+///
+/// Returns:
+/// - `Some(response)` for `/keri.cesr` (404 unless the slot is
+///   webs-tagged), and for `/did.json` on a webs-tagged slot. Terminal.
+/// - `None` otherwise — the caller tries the next method.
+pub async fn dispatch(state: &AppState, parts: &Parts) -> Option<Response> {
+    let path = parts.uri.path().trim_start_matches('/');
+    let host = extract_request_host(parts, &state.trusted_proxy_cidrs);
+    let host = host.as_deref();
+
+    if let Some(mnemonic) = path.strip_suffix("/keri.cesr")
+        && !mnemonic.is_empty()
+    {
+        if let Err(e) = validate_webs_mnemonic(mnemonic) {
+            return Some(e.into_response());
+        }
+        // The log key holds whatever the slot's method stores; only a
+        // webs slot's log is a KEL.
+        if !is_webs_slot(state, mnemonic).await {
+            return Some(
+                AppError::NotFound(format!("content not found: {mnemonic}")).into_response(),
+            );
+        }
+        let key = did_ops::content_log_key(mnemonic);
+        return Some(
+            serve_content(
+                state,
+                mnemonic,
+                &key,
+                Artifact::content(KERI_CESR_CONTENT_TYPE),
+                false,
+                host,
+                &parts.headers,
+            )
+            .await
+            .unwrap_or_else(|e| e.into_response()),
+        );
+    }
+
+    if let Some(mnemonic) = path.strip_suffix("/did.json")
+        && !mnemonic.is_empty()
+        && validate_webs_mnemonic(mnemonic).is_ok()
+        && is_webs_slot(state, mnemonic).await
+    {
+        let key = did_ops::content_did_doc_key(mnemonic);
+        return Some(
+            serve_content(
+                state,
+                mnemonic,
+                &key,
+                Artifact::content("application/did+json"),
+                true,
+                host,
+                &parts.headers,
+            )
+            .await
+            .unwrap_or_else(|e| e.into_response()),
+        );
+    }
+
+    None
+}
+
+/// Whether `mnemonic` holds a did:webs record. A store error reads as
+/// "no": `/did.json` then falls through to did:web, whose own lookup will
+/// surface the error.
+async fn is_webs_slot(state: &AppState, mnemonic: &str) -> bool {
+    matches!(
+        state.dids_ks.get::<DidRecord>(did_ops::did_key(mnemonic)).await,
+        Ok(Some(record)) if record.method == "webs"
+    )
+}
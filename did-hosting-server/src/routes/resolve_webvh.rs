@@ -19,82 +19,14 @@
 //! corresponding route is never registered.
 
 use axum::extract::{Request, State};
-use axum::http::{StatusCode, request::Parts};
+use axum::http::request::Parts;
 use axum::response::{IntoResponse, Response};
-use did_hosting_common::server::domain::assert_resolution_allowed;
-use tracing::debug;
 
-use super::resolve_shared::extract_request_host;
-use crate::did_ops::{self, DidRecord};
+use super::resolve_shared::{Artifact, extract_request_host, serve_content};
 use crate::error::AppError;
 use crate::mnemonic::validate_mnemonic;
 use crate::server::AppState;
 
-/// Serve stored content for a mnemonic, optionally incrementing
-/// resolve stats. Runs the disabled/deleted check and the T21
-/// resolve-side safety check before returning bytes.
-async fn serve_content(
-    state: &AppState,
-    mnemonic: &str,
-    key: &str,
-    content_type: &str,
-    track_stats: bool,
-    request_host: Option<&str>,
-) -> Result<Response, AppError> {
-    if let Some(record) = state
-        .dids_ks
-        .get::<DidRecord>(did_ops::did_key(mnemonic))
-        .await?
-    {
-        if record.disabled || record.deleted_at.is_some() {
-            return Err(AppError::NotFound(format!("content not found: {mnemonic}")));
-        }
-        if let Some(host) = request_host
-            && let Some(ref did_id) = record.did_id
-        {
-            assert_resolution_allowed(&state.store, host, did_id).await?;
-        }
-    }
-
-    let content = if let Some(cached) = state.did_cache.get(key) {
-        #[cfg(feature = "metrics")]
-        did_hosting_common::server::metrics::inc_cache_hit();
-        cached
-    } else {
-        #[cfg(feature = "metrics")]
-        did_hosting_common::server::metrics::inc_cache_miss();
-        let data = state
-            .dids_ks
-            .get_raw(key)
-            .await?
-            .ok_or_else(|| AppError::NotFound(format!("content not found: {mnemonic}")))?;
-        state.did_cache.insert(key.to_string(), data.clone());
-        std::sync::Arc::new(data)
-    };
-
-    if track_stats && let Some(ref collector) = state.stats_collector {
-        collector.record_resolve(mnemonic);
-        #[cfg(feature = "metrics")]
-        did_hosting_common::server::metrics::inc_resolve();
-    }
-
-    debug!(mnemonic = %mnemonic, size = content.len(), content_type, "content resolved");
-
-    // DID logs are content-addressed (the SCID prevents content drift) and
-    // safe to cache aggressively. Setting an explicit `Cache-Control` here
-    // overrides the global `no-store` security middleware so CDNs and
-    // browsers can serve hot DIDs without round-tripping the origin.
-    Ok((
-        StatusCode::OK,
-        [
-            ("content-type", content_type),
-            ("cache-control", "public, max-age=300"),
-        ],
-        (*content).clone(),
-    )
-        .into_response())
-}
-
 /// `GET /.well-known/did.jsonl` — root-DID webvh log.
 #[cfg_attr(feature = "openapi", utoipa::path(
     get,
@@ -115,9 +47,10 @@ pub async fn serve_root_did_log(
         &state,
         ".well-known",
         "content:.well-known:log",
-        "application/jsonl+json",
+        Artifact::WEBVH_LOG,
         true,
         host.as_deref(),
+        &parts.headers,
     )
     .await
 }
@@ -133,9 +66,10 @@ pub async fn serve_root_witness(
         &state,
         ".well-known",
         "content:.well-known:witness",
-        "application/json",
+        Artifact::content("application/json"),
         false,
         host.as_deref(),
+        &parts.headers,
     )
     .await
 }
@@ -161,9 +95,17 @@ pub async fn dispatch(state: &AppState, parts: &Parts) -> Option<Response> {
         }
         let key = format!("content:{mnemonic}:log");
         return Some(
-            serve_content(state, mnemonic, &key, "application/jsonl+json", true, host)
-                .await
-                .unwrap_or_else(|e| e.into_response()),
+            serve_content(
+                state,
+                mnemonic,
+                &key,
+                Artifact::WEBVH_LOG,
+                true,
+                host,
+                &parts.headers,
+            )
+            .await
+            .unwrap_or_else(|e| e.into_response()),
         );
     }
 
@@ -175,9 +117,17 @@ pub async fn dispatch(state: &AppState, parts: &Parts) -> Option<Response> {
         }
         let key = format!("content:{mnemonic}:witness");
         return Some(
-            serve_content(state, mnemonic, &key, "application/json", false, host)
-                .await
-                .unwrap_or_else(|e| e.into_response()),
+            serve_content(
+                state,
+                mnemonic,
+                &key,
+                Artifact::content("application/json"),
+                false,
+                host,
+                &parts.headers,
+            )
+            .await
+            .unwrap_or_else(|e| e.into_response()),
         );
     }
 
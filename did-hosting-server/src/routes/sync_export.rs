@@ -0,0 +1,147 @@
+//! Pull side of watcher sync.
+//!
+//! `watcher_push` pushes every change to the configured watchers, but a
+//! watcher that was down during a push never hears about it. These routes
+//! let a watcher reconcile on its own schedule:
+//!
+//! - `GET /api/sync/dids` — every live did:webvh DID with the fields a
+//!   watcher needs to decide whether it is behind, including the
+//!   [`sync_head`] of its content recorded on [`DidRecord::sync_head`].
+//! - `GET /api/sync/dids/{*mnemonic}` — one DID as a [`SyncDidRequest`],
+//!   the same body a push carries.
+//!
+//! Authentication is the same shared secret the push uses, in the other
+//! direction: the bearer token must match one of `config.watchers[].token`.
+
+use axum::Json;
+use axum::extract::{FromRequestParts, Path, State};
+use axum::http::request::Parts;
+use did_hosting_common::server::auth::constant_time_eq;
+use did_hosting_common::server::mnemonic::validate_mnemonic;
+use did_hosting_common::{SyncDidListResponse, SyncDidRequest, SyncDidSummary, sync_head};
+use tracing::debug;
+
+use crate::did_ops::DidRecord;
+use crate::error::AppError;
+use crate::server::AppState;
+use crate::watcher_push;
+
+// ---------------------------------------------------------------------------
+// WatcherAuth extractor — validates bearer token against configured watchers
+// ---------------------------------------------------------------------------
+
+pub struct WatcherAuth;
+
+impl FromRequestParts<AppState> for WatcherAuth {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .ok_or(AppError::Authentication("missing sync token".into()))?;
+
+        if state
+            .config
+            .watchers
+            .iter()
+            .filter_map(|w| w.token.as_deref())
+            .any(|t| constant_time_eq(t.as_bytes(), token.as_bytes()))
+        {
+            Ok(WatcherAuth)
+        } else {
+            Err(AppError::Authentication("invalid sync token".into()))
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// GET /api/sync/dids — list DIDs a watcher should mirror
+// ---------------------------------------------------------------------------
+
+pub async fn list_dids(
+    State(state): State<AppState>,
+    _auth: WatcherAuth,
+) -> Result<Json<SyncDidListResponse>, AppError> {
+    let raw = state.dids_ks.prefix_iter_raw("did:").await?;
+
+    let mut dids = Vec::with_capacity(raw.len());
+    for (_key, value) in raw {
+        let mut record: DidRecord = match serde_json::from_slice(&value) {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+        // Watchers mirror did:webvh logs only, and a soft-deleted DID is
+        // exactly what reconciliation should remove.
+        if record.method != "webvh" || record.deleted_at.is_some() {
+            continue;
+        }
+        // Written with the content, so the watcher can tell a same-second
+        // update from no update at all without this scan reading every log.
+        // A record that predates the field is hashed here instead.
+        let head = match record.sync_head.take() {
+            Some(head) => head,
+            None => {
+                let Some(log) = state
+                    .dids_ks
+                    .get_raw(crate::did_ops::content_log_key(&record.mnemonic))
+                    .await?
+                else {
+                    continue;
+                };
+                let witness = state
+                    .dids_ks
+                    .get_raw(crate::did_ops::content_witness_key(&record.mnemonic))
+                    .await?;
+                sync_head(&log, witness.as_deref())
+            }
+        };
+        dids.push(SyncDidSummary {
+            head: Some(head),
+            mnemonic: record.mnemonic,
+            did_id: record.did_id,
+            updated_at: record.updated_at,
+            disabled: record.disabled,
+        });
+    }
+
+    debug!(count = dids.len(), "sync listing served to watcher");
+
+    Ok(Json(SyncDidListResponse {
+        source_url: state.config.public_base_url(),
+        dids,
+    }))
+}
+
+// ---------------------------------------------------------------------------
+// GET /api/sync/dids/{*mnemonic} — fetch one DID's sync payload
+// ---------------------------------------------------------------------------
+
+pub async fn get_did(
+    State(state): State<AppState>,
+    _auth: WatcherAuth,
+    Path(mnemonic): Path<String>,
+) -> Result<Json<SyncDidRequest>, AppError> {
+    let mnemonic = mnemonic.trim_start_matches('/');
+    validate_mnemonic(mnemonic)?;
+
+    let not_found = || AppError::NotFound(format!("DID not found: {mnemonic}"));
+    let record = state
+        .dids_ks
+        .get::<DidRecord>(crate::did_ops::did_key(mnemonic))
+        .await?
+        .ok_or_else(not_found)?;
+    if record.method != "webvh" || record.deleted_at.is_some() {
+        return Err(not_found());
+    }
+
+    watcher_push::build_sync_payload(&state.config, &state.dids_ks, mnemonic)
+        .await?
+        .map(Json)
+        .ok_or_else(not_found)
+}
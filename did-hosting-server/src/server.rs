@@ -62,7 +62,7 @@ pub struct AppState {
     pub signing_key_bytes: Option<[u8; 32]>,
     pub http_client: reqwest::Client,
     pub stats_collector: Option<Arc<stats::StatsCollector>>,
-    /// In-memory cache for DID content (did.jsonl). TTL-based eviction on read.
+    /// In-memory cache for DID content (did.jsonl), bounded by `[cache]`.
     pub did_cache: Arc<crate::cache::ContentCache>,
     /// Parsed `server.trusted_proxy_cidrs` — peers inside this set
     /// have their `Forwarded` / `X-Forwarded-Host` headers honoured
@@ -207,6 +207,7 @@ pub async fn run(config: AppConfig, store: Store, secrets: ServerSecrets) -> Res
         );
     }
 
+    let did_cache = Arc::new(crate::cache::ContentCache::from_config(&config.cache));
     let state = AppState {
         store: store.clone(),
         sessions_ks,
@@ -226,7 +227,7 @@ pub async fn run(config: AppConfig, store: Store, secrets: ServerSecrets) -> Res
             .build()
             .expect("failed to build HTTP client"),
         stats_collector: Some(stats_collector.clone()),
-        did_cache: Arc::new(crate::cache::ContentCache::new(Duration::from_secs(300))),
+        did_cache,
         trusted_proxy_cidrs: Arc::new(parsed_cidrs),
     };
 
@@ -286,6 +287,7 @@ pub async fn run(config: AppConfig, store: Store, secrets: ServerSecrets) -> Res
     let storage_control_url = state.config.control_url.clone();
     let storage_server_did = state.config.server_did.clone();
     let storage_stats_config = stats_config;
+    let storage_did_cache = state.did_cache.clone();
     let storage_handle = std::thread::Builder::new()
         .name("webvh-storage".into())
         .spawn(move || {
@@ -301,6 +303,7 @@ pub async fn run(config: AppConfig, store: Store, secrets: ServerSecrets) -> Res
                     http: storage_http,
                     control_url: storage_control_url,
                     server_did: storage_server_did,
+                    did_cache: storage_did_cache,
                 },
                 &mut storage_shutdown,
             )
@@ -655,6 +658,7 @@ struct StorageThreadParams {
     http: reqwest::Client,
     control_url: Option<String>,
     server_did: Option<String>,
+    did_cache: Arc<crate::cache::ContentCache>,
 }
 
 fn run_storage_thread(params: StorageThreadParams, shutdown_rx: &mut watch::Receiver<bool>) {
@@ -669,6 +673,7 @@ fn run_storage_thread(params: StorageThreadParams, shutdown_rx: &mut watch::Rece
         http,
         control_url,
         server_did,
+        did_cache,
     } = params;
     let rt = tokio::runtime::Builder::new_current_thread()
         .enable_all()
@@ -704,6 +709,7 @@ fn run_storage_thread(params: StorageThreadParams, shutdown_rx: &mut watch::Rece
                     }
                 }
                 _ = did_timer.tick() => {
+                    did_cache.evict_expired();
                     match cleanup_empty_dids(&dids_ks, did_ttl_seconds).await {
                         Ok(0) => {}
                         Ok(n) => {
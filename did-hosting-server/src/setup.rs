@@ -245,6 +245,7 @@ pub async fn run_wizard(
             context_id: None,
         },
         stats: crate::config::StatsConfig::default(),
+        cache: crate::config::CacheConfig::default(),
         identity: Default::default(),
         config_path: output_path.clone(),
     };
@@ -835,6 +836,7 @@ pub async fn run_setup_offline_complete(
             context_id: None,
         },
         stats: crate::config::StatsConfig::default(),
+        cache: crate::config::CacheConfig::default(),
         identity: Default::default(),
         config_path: state.config_output.clone(),
     };
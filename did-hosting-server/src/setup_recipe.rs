@@ -22,8 +22,8 @@ use vta_sdk::provision_client::{EphemeralSetupKey, OperatorMessages};
 use crate::acl::{AclEntry, Role, store_acl_entry};
 use crate::auth::session::now_epoch;
 use crate::config::{
-    AppConfig, AuthConfig, FeaturesConfig, LimitsConfig, LogConfig, LogFormat, ServerConfig,
-    StatsConfig, StoreConfig, VtaConfig,
+    AppConfig, AuthConfig, CacheConfig, FeaturesConfig, LimitsConfig, LogConfig, LogFormat,
+    ServerConfig, StatsConfig, StoreConfig, VtaConfig,
 };
 use crate::error::AppError;
 use crate::secret_store::{ServerSecrets, create_secret_store};
@@ -259,6 +259,7 @@ pub async fn apply_recipe(
             context_id: None,
         },
         stats: StatsConfig::default(),
+        cache: CacheConfig::default(),
         identity: Default::default(),
         config_path: recipe.output.config_path.clone(),
     };
@@ -7,8 +7,9 @@
 use crate::auth::session::now_epoch;
 use crate::config::AppConfig;
 use crate::did_ops;
+use crate::error::AppError;
 use crate::store::KeyspaceHandle;
-use did_hosting_common::{SyncDeleteRequest, SyncDidRequest};
+use did_hosting_common::{RollbackProof, SyncDeleteRequest, SyncDidRequest};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tracing::warn;
@@ -41,6 +42,62 @@ fn normalize_url(url: &str) -> String {
 // Push logic
 // ---------------------------------------------------------------------------
 
+/// Build the sync payload for a DID: its record, log and witness content.
+///
+/// Shared by the push path below and the pull path
+/// (`routes::sync_export`), so a watcher sees the same body either way.
+/// Returns `None` when the record or its log is missing, or the log is not
+/// UTF-8.
+pub async fn build_sync_payload(
+    config: &AppConfig,
+    dids_ks: &KeyspaceHandle,
+    mnemonic: &str,
+) -> Result<Option<SyncDidRequest>, AppError> {
+    let Some(record) = dids_ks
+        .get::<did_ops::DidRecord>(did_ops::did_key(mnemonic))
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let Some(log_content) = dids_ks
+        .get_raw(did_ops::content_log_key(mnemonic))
+        .await?
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+    else {
+        return Ok(None);
+    };
+
+    let witness_content = dids_ks
+        .get_raw(did_ops::content_witness_key(mnemonic))
+        .await?
+        .and_then(|bytes| String::from_utf8(bytes).ok());
+
+    let rolled_back = dids_ks
+        .get::<Vec<String>>(did_ops::rolled_back_key(mnemonic))
+        .await?
+        .unwrap_or_default();
+    let rollback_proof = if rolled_back.is_empty() {
+        None
+    } else {
+        dids_ks
+            .get::<RollbackProof>(did_ops::rollback_proof_key(mnemonic))
+            .await?
+    };
+
+    Ok(Some(SyncDidRequest {
+        mnemonic: mnemonic.to_string(),
+        did_id: record.did_id,
+        log_content,
+        witness_content,
+        source_url: config.public_base_url(),
+        updated_at: record.updated_at,
+        disabled: record.disabled,
+        rolled_back,
+        rollback_proof,
+    }))
+}
+
 /// Push the current state of a DID to watchers that the DID declares.
 ///
 /// The DID's log parameters may contain a `watchers` array of URLs.  Only
@@ -61,59 +118,23 @@ pub fn notify_watchers_did(
     let dids_ks = dids_ks.clone();
 
     tokio::spawn(async move {
-        let record = match dids_ks
-            .get::<did_ops::DidRecord>(did_ops::did_key(&mnemonic))
-            .await
-        {
-            Ok(Some(r)) => r,
+        let payload = match build_sync_payload(&config, &dids_ks, &mnemonic).await {
+            Ok(Some(payload)) => payload,
             Ok(None) => return,
             Err(e) => {
-                warn!(mnemonic = %mnemonic, error = %e, "watcher push: failed to read record");
+                warn!(mnemonic = %mnemonic, error = %e, "watcher push: failed to read DID");
                 return;
             }
         };
 
-        let log_content = match dids_ks.get_raw(did_ops::content_log_key(&mnemonic)).await {
-            Ok(Some(bytes)) => match String::from_utf8(bytes) {
-                Ok(s) => s,
-                Err(_) => {
-                    warn!(mnemonic = %mnemonic, "watcher push: invalid UTF-8 in log content");
-                    return;
-                }
-            },
-            Ok(None) => return,
-            Err(e) => {
-                warn!(mnemonic = %mnemonic, error = %e, "watcher push: failed to read log");
-                return;
-            }
-        };
-
-        let witness_content = match dids_ks
-            .get_raw(did_ops::content_witness_key(&mnemonic))
-            .await
-        {
-            Ok(Some(bytes)) => String::from_utf8(bytes).ok(),
-            _ => None,
-        };
-
         // Extract metadata to get the DID's declared watcher URLs and version.
-        let meta = did_ops::extract_log_metadata(&log_content);
+        let meta = did_ops::extract_log_metadata(&payload.log_content);
 
         // If the DID declares no watchers and no watchers are configured, nothing to do.
         if meta.watcher_urls.is_empty() && config.watchers.is_empty() {
             return;
         }
 
-        let payload = SyncDidRequest {
-            mnemonic: mnemonic.clone(),
-            did_id: record.did_id,
-            log_content,
-            witness_content,
-            source_url: config.public_base_url(),
-            updated_at: record.updated_at,
-            disabled: record.disabled,
-        };
-
         let did_watcher_urls: Vec<String> =
             meta.watcher_urls.iter().map(|u| normalize_url(u)).collect();
 
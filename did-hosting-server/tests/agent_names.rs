@@ -19,7 +19,7 @@ use did_hosting_common::server::config::{
 use did_hosting_common::server::store::Store;
 use did_hosting_common::server::store::{KS_ACL, KS_DIDS, KS_SESSIONS};
 use did_hosting_server::cache::ContentCache;
-use did_hosting_server::config::{AppConfig, LimitsConfig, StatsConfig};
+use did_hosting_server::config::{AppConfig, CacheConfig, LimitsConfig, StatsConfig};
 use did_hosting_server::server::AppState;
 use tower::ServiceExt;
 
@@ -53,6 +53,7 @@ async fn make_state(agent_names_enabled: bool) -> (AppState, tempfile::TempDir)
         secrets: SecretsConfig::default(),
         limits: LimitsConfig::default(),
         stats: StatsConfig::default(),
+        cache: CacheConfig::default(),
         watchers: Vec::new(),
         control_url: None,
         control_did: None,
@@ -102,6 +103,8 @@ async fn seed(state: &AppState, name: &str, enabled: bool, disabled_did: bool) {
             enabled,
             created_at: 0,
         }],
+        deactivated_at: None,
+        sync_head: None,
     };
     // A blank log so the DID record is coherent.
     state
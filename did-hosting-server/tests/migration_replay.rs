@@ -93,6 +93,8 @@ fn pre_t12_record(mnemonic: &str, did_id: Option<&str>) -> DidRecord {
         // Absent services — what M-02 fills in.
         services: None,
         agent_names: Vec::new(),
+        deactivated_at: None,
+        sync_head: None,
     }
 }
 
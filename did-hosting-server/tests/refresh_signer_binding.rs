@@ -26,7 +26,7 @@ use did_hosting_common::server::config::{
 };
 use did_hosting_common::server::store::{KS_ACL, KS_DIDS, KS_SESSIONS, Store};
 use did_hosting_server::cache::ContentCache;
-use did_hosting_server::config::{AppConfig, LimitsConfig, StatsConfig};
+use did_hosting_server::config::{AppConfig, CacheConfig, LimitsConfig, StatsConfig};
 use did_hosting_server::error::AppError;
 use did_hosting_server::server::AppState;
 use vti_common::auth::RefreshInput;
@@ -58,6 +58,7 @@ async fn make_state() -> (AppState, tempfile::TempDir) {
         secrets: SecretsConfig::default(),
         limits: LimitsConfig::default(),
         stats: StatsConfig::default(),
+        cache: CacheConfig::default(),
         watchers: Vec::new(),
         control_url: None,
         control_did: None,
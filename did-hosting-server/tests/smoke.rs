@@ -10,6 +10,11 @@
 //! 3. `GET /unknown-mnemonic/did.jsonl` returns 404 (proves the
 //!    fallback handler runs and the error mapper produces a clean
 //!    response shape).
+//! 4. `ETag` / `Last-Modified` are set and a matching conditional request
+//!    gets `304 Not Modified`.
+//! 5. A large log is sent compressed when `Accept-Encoding` allows, with
+//!    its own ETag; one that does not shrink is sent as is, and content
+//!    synced with stored variants is served from them.
 //!
 //! This is the smallest end-to-end smoke test that covers the daemon's
 //! public DID surface in-process. End-to-end DIDComm flows need a fake
@@ -30,7 +35,7 @@ use did_hosting_common::server::domain::{
 use did_hosting_common::server::store::Store;
 use did_hosting_common::server::store::{KS_ACL, KS_DIDS, KS_SESSIONS};
 use did_hosting_server::cache::ContentCache;
-use did_hosting_server::config::{AppConfig, LimitsConfig, StatsConfig};
+use did_hosting_server::config::{AppConfig, CacheConfig, LimitsConfig, StatsConfig};
 use did_hosting_server::server::AppState;
 use std::time::Duration;
 use tower::ServiceExt; // for `oneshot`
@@ -59,6 +64,7 @@ async fn make_state() -> (AppState, tempfile::TempDir) {
         secrets: SecretsConfig::default(),
         limits: LimitsConfig::default(),
         stats: StatsConfig::default(),
+        cache: CacheConfig::default(),
         watchers: Vec::new(),
         control_url: None,
         control_did: None,
@@ -174,6 +180,282 @@ async fn public_did_resolution_round_trip() {
 /// checks (`/did.jsonl`, `/did.json`) only trigger on actual artifact
 /// URLs; anything else falls through to the eventual 404 (or the
 /// daemon's SPA fallback).
+#[tokio::test]
+async fn public_did_resolution_honours_conditional_get() {
+    let (state, _dir) = make_state().await;
+    let mnemonic = "carol";
+    let log = "{\"versionId\":\"1-abc\",\"state\":{}}\n{\"versionId\":\"2-def\",\"state\":{}}\n";
+    let record = DidRecord {
+        owner: "did:example:owner".into(),
+        mnemonic: mnemonic.into(),
+        created_at: 1_700_000_000,
+        updated_at: 1_700_000_000,
+        version_count: 2,
+        did_id: None,
+        content_size: log.len() as u64,
+        disabled: false,
+        deleted_at: None,
+        method: "webvh".into(),
+        domain: String::new(),
+        services: None,
+        agent_names: Vec::new(),
+        deactivated_at: None,
+        sync_head: None,
+    };
+    state
+        .dids_ks
+        .insert(did_key(mnemonic), &record)
+        .await
+        .expect("seed DidRecord");
+    state
+        .dids_ks
+        .insert_raw(content_log_key(mnemonic), log.as_bytes().to_vec())
+        .await
+        .expect("seed did log");
+
+    let app = did_hosting_server::routes::router(1024 * 1024).with_state(state.clone());
+    let get = |header: Option<(&'static str, String)>| {
+        let app = app.clone();
+        async move {
+            let mut req = Request::builder().uri(format!("/{mnemonic}/did.jsonl"));
+            if let Some((name, value)) = header {
+                req = req.header(name, value);
+            }
+            app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap()
+        }
+    };
+
+    // Unconditional: 200 with validators derived from the last versionId
+    // and the record's updated_at.
+    let response = get(None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["etag"], "\"2-def\"");
+    // Too small to compress, but a cache must still key on the encoding.
+    assert_eq!(response.headers()["vary"], "Accept-Encoding");
+    let last_modified = response.headers()["last-modified"]
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert_eq!(last_modified, "Tue, 14 Nov 2023 22:13:20 GMT");
+
+    // Matching If-None-Match / If-Modified-Since: 304, empty body.
+    for header in [
+        ("if-none-match", "\"2-def\"".to_string()),
+        ("if-modified-since", last_modified.clone()),
+    ] {
+        let response = get(Some(header)).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()["etag"], "\"2-def\"");
+        let bytes = axum::body::to_bytes(response.into_body(), 1 << 20)
+            .await
+            .unwrap();
+        assert!(bytes.is_empty());
+    }
+
+    // A stale tag gets the full body.
+    let response = get(Some(("if-none-match", "\"1-abc\"".into()))).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn public_did_resolution_negotiates_encoding() {
+    use did_hosting_common::server::encoding::{Encoding, compress};
+
+    let (state, _dir) = make_state().await;
+    let mnemonic = "dave";
+    let log: String = (1..=40)
+        .map(|n| {
+            format!("{{\"versionId\":\"{n}-abc\",\"state\":{{\"id\":\"did:example:dave\"}}}}\n")
+        })
+        .collect();
+    state
+        .dids_ks
+        .insert_raw(content_log_key(mnemonic), log.as_bytes().to_vec())
+        .await
+        .expect("seed did log");
+
+    let app = did_hosting_server::routes::router(1024 * 1024).with_state(state.clone());
+    let get = |headers: Vec<(&'static str, &'static str)>| {
+        let app = app.clone();
+        async move {
+            let mut req = Request::builder().uri(format!("/{mnemonic}/did.jsonl"));
+            for (name, value) in headers {
+                req = req.header(name, value);
+            }
+            app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap()
+        }
+    };
+
+    // Without Accept-Encoding: the raw log, but the response still varies.
+    let response = get(vec![]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get("content-encoding").is_none());
+    assert_eq!(response.headers()["vary"], "Accept-Encoding");
+    assert_eq!(response.headers()["etag"], "\"40-abc\"");
+
+    // Twice, so the second is served from the cached variant.
+    let gzipped = compress(Encoding::Gzip, log.as_bytes()).unwrap();
+    for _ in 0..2 {
+        let response = get(vec![("accept-encoding", "gzip, br;q=0.5")]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-encoding"], "gzip");
+        assert_eq!(response.headers()["etag"], "\"40-abc-gzip\"");
+        let bytes = axum::body::to_bytes(response.into_body(), 1 << 20)
+            .await
+            .unwrap();
+        assert_eq!(bytes.as_ref(), gzipped.as_slice());
+    }
+
+    let response = get(vec![
+        ("accept-encoding", "gzip"),
+        ("if-none-match", "\"40-abc-gzip\""),
+    ])
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    // A body that does not shrink is sent as is, and the outcome is cached
+    // so it is not compressed again.
+    let noise: Vec<u8> = (0..4096).map(|_| rand::random::<u8>()).collect();
+    state
+        .dids_ks
+        .insert_raw(content_log_key("erin"), noise.clone())
+        .await
+        .expect("seed incompressible log");
+    let etag = did_hosting_common::server::conditional::content_etag(&noise);
+    for _ in 0..2 {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/erin/did.jsonl")
+                    .header("accept-encoding", "br")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get("content-encoding").is_none());
+        assert_eq!(response.headers()["vary"], "Accept-Encoding");
+        let bytes = axum::body::to_bytes(response.into_body(), 1 << 20)
+            .await
+            .unwrap();
+        assert_eq!(bytes.as_ref(), noise.as_slice());
+    }
+    let marker = state
+        .did_cache
+        .get(&format!("{}:br:{etag}", content_log_key("erin")))
+        .expect("outcome cached");
+    assert!(marker.is_empty());
+}
+
+#[tokio::test]
+async fn universal_resolver_endpoint_resolves_versions() {
+    let (state, _dir) = make_state().await;
+    let mnemonic = "people/dave";
+    let did = "did:webvh:QmScid:server.example.com:people:dave";
+    let log = format!(
+        "{}\n{}\n",
+        serde_json::json!({
+            "versionId": "1-a",
+            "versionTime": "2025-01-01T00:00:00Z",
+            "parameters": {},
+            "state": { "id": did, "controller": "v1" },
+        }),
+        serde_json::json!({
+            "versionId": "2-b",
+            "versionTime": "2025-06-01T00:00:00Z",
+            "parameters": {},
+            "state": { "id": did, "controller": "v2" },
+        }),
+    );
+    let record = DidRecord {
+        owner: "did:example:owner".into(),
+        mnemonic: mnemonic.into(),
+        created_at: 0,
+        updated_at: 0,
+        version_count: 2,
+        did_id: Some(did.into()),
+        content_size: log.len() as u64,
+        disabled: false,
+        deleted_at: None,
+        method: "webvh".into(),
+        domain: String::new(),
+        services: None,
+        agent_names: Vec::new(),
+        deactivated_at: None,
+        sync_head: None,
+    };
+    state
+        .dids_ks
+        .insert(did_key(mnemonic), &record)
+        .await
+        .expect("seed DidRecord");
+    state
+        .dids_ks
+        .insert_raw(content_log_key(mnemonic), log.into_bytes())
+        .await
+        .expect("seed did log");
+
+    let app = did_hosting_server::routes::router(1024 * 1024).with_state(state.clone());
+    let get = |uri: String| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let status = response.status();
+            let bytes = axum::body::to_bytes(response.into_body(), 1 << 20)
+                .await
+                .unwrap();
+            (
+                status,
+                serde_json::from_slice::<serde_json::Value>(&bytes).unwrap(),
+            )
+        }
+    };
+
+    let (status, body) = get(format!("/1.0/identifiers/{did}")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["didDocument"]["controller"], "v2");
+    assert_eq!(body["didDocumentMetadata"]["versionId"], "2-b");
+    assert_eq!(
+        body["didDocumentMetadata"]["created"],
+        "2025-01-01T00:00:00Z"
+    );
+    assert_eq!(body["didDocumentMetadata"]["deactivated"], false);
+
+    let (status, body) = get(format!(
+        "/1.0/identifiers/{did}?versionTime=2025-03-01T00:00:00Z"
+    ))
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["didDocument"]["controller"], "v1");
+    assert_eq!(body["didDocumentMetadata"]["nextVersionId"], "2-b");
+
+    let (status, body) = get(format!("/1.0/identifiers/{did}?versionNumber=1")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["didDocument"]["controller"], "v1");
+    assert_eq!(body["didDocumentMetadata"]["versionNumber"], 1);
+
+    let (status, body) = get(format!(
+        "/1.0/identifiers/{did}?versionNumber=1&versionId=1-a"
+    ))
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["didResolutionMetadata"]["error"], "invalidOptions");
+
+    let (status, body) = get(format!("/1.0/identifiers/{did}?versionId=9-z")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["didResolutionMetadata"]["error"], "notFound");
+
+    let (status, body) =
+        get("/1.0/identifiers/did:webvh:QmOther:server.example.com:people:dave".into()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["didResolutionMetadata"]["error"], "notFound");
+}
+
 #[tokio::test]
 async fn route_ordering_specific_routes_beat_method_dispatchers() {
     let (state, _dir) = make_state().await;
@@ -294,6 +576,8 @@ async fn resolve_side_safety_blocks_cross_domain_and_disabled_domain() {
         domain: "domain-a.example".into(),
         services: None,
         agent_names: Vec::new(),
+        deactivated_at: None,
+        sync_head: None,
     };
     state
         .dids_ks
@@ -534,6 +818,8 @@ async fn first_boot_init_seeds_domain_and_backfills_legacy_records() {
         domain: String::new(), // legacy state
         services: None,        // legacy state
         agent_names: Vec::new(),
+        deactivated_at: None,
+        sync_head: None,
     };
     state
         .dids_ks
@@ -584,3 +870,471 @@ async fn first_boot_init_seeds_domain_and_backfills_legacy_records() {
         .expect("list domains after replay");
     assert_eq!(domains.len(), 1, "second run must be a no-op");
 }
+
+/// did:webs slots serve `keri.cesr` and claim `/did.json` from the
+/// did:web dispatcher; a non-webs slot's `/did.json` still falls
+/// through to did:web.
+#[cfg(feature = "method-webs")]
+#[tokio::test]
+async fn webs_slot_serves_kel_and_did_document() {
+    use did_hosting_common::did_ops::content_did_doc_key;
+
+    let (state, _dir) = make_state().await;
+    let mnemonic = "people/EAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA";
+    let kel = "{\"v\":\"KERI10JSON000000_\",\"t\":\"icp\"}-AAB";
+    let doc = "{\"id\":\"did:webs:server.example.com:people:EAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA\"}";
+
+    let record = DidRecord {
+        owner: "did:example:owner".into(),
+        mnemonic: mnemonic.into(),
+        created_at: 0,
+        updated_at: 0,
+        version_count: 1,
+        did_id: None,
+        content_size: kel.len() as u64,
+        disabled: false,
+        deleted_at: None,
+        method: "webs".into(),
+        domain: String::new(),
+        services: None,
+        agent_names: Vec::new(),
+        deactivated_at: None,
+        sync_head: None,
+    };
+    state
+        .dids_ks
+        .insert(did_key(mnemonic), &record)
+        .await
+        .expect("seed DidRecord");
+    state
+        .dids_ks
+        .insert_raw(content_log_key(mnemonic), kel.as_bytes().to_vec())
+        .await
+        .expect("seed keri.cesr");
+    state
+        .dids_ks
+        .insert_raw(content_did_doc_key(mnemonic), doc.as_bytes().to_vec())
+        .await
+        .expect("seed did.json");
+
+    let app = did_hosting_server::routes::router(1024 * 1024).with_state(state.clone());
+    let get = |uri: String| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let status = response.status();
+            let content_type = response
+                .headers()
+                .get("content-type")
+                .map(|v| v.to_str().unwrap().to_string());
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            (status, content_type, body)
+        }
+    };
+
+    let (status, ct, body) = get(format!("/{mnemonic}/keri.cesr")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(ct.as_deref(), Some("application/cesr"));
+    assert_eq!(&body[..], kel.as_bytes());
+
+    let (status, ct, body) = get(format!("/{mnemonic}/did.json")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(ct.as_deref(), Some("application/did+json"));
+    assert_eq!(&body[..], doc.as_bytes());
+
+    // A slot not tagged `webs` is left to did:web, which rejects the
+    // AID-shaped mnemonic — either way the sidecar must not be served.
+    let mut other = record.clone();
+    other.method = "webvh".into();
+    state
+        .dids_ks
+        .insert(did_key(mnemonic), &other)
+        .await
+        .expect("retag DidRecord");
+    let (status, _, body) = get(format!("/{mnemonic}/did.json")).await;
+    assert_ne!(status, StatusCode::OK);
+    assert_ne!(&body[..], doc.as_bytes());
+    let (status, _, _) = get(format!("/{mnemonic}/keri.cesr")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+/// A control-plane sync that replaces a did:webs document drops the
+/// cached copy, so the next `/did.json` serves the new one.
+#[cfg(feature = "method-webs")]
+#[tokio::test]
+async fn synced_webs_document_replaces_the_cached_one() {
+    use did_hosting_common::DidSyncUpdate;
+    use did_hosting_server::control_register::apply_single_update;
+
+    let (state, _dir) = make_state().await;
+    let aid = "EAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA";
+    let mnemonic = format!("people/{aid}");
+    let did = format!("did:webs:server.example.com:people:{aid}");
+    // A single inception event with a correct version string; the
+    // signature is not verified on sync.
+    let body = format!(r#"{{"v":"KERI10JSON000000_","d":"{aid}","i":"{aid}","s":"0","t":"icp"}}"#);
+    let body = body.replacen("000000", &format!("{:06x}", body.len()), 1);
+    let kel = format!("{body}-AAB0B{}", "A".repeat(86));
+
+    let sync = |doc: String| DidSyncUpdate {
+        mnemonic: mnemonic.clone(),
+        did_id: did.clone(),
+        log_content: kel.clone(),
+        witness_content: None,
+        version_count: 1,
+        previous_did_id: None,
+        rolled_back: Vec::new(),
+        rollback_proof: None,
+        did_document: Some(doc),
+    };
+    let resolve = || {
+        let app = did_hosting_server::routes::router(1024 * 1024).with_state(state.clone());
+        let uri = format!("/{mnemonic}/did.json");
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap()
+        }
+    };
+
+    let first = serde_json::json!({ "id": did }).to_string();
+    apply_single_update(
+        &state.dids_ks,
+        &state.store,
+        &sync(first.clone()),
+        &state.did_cache,
+    )
+    .await
+    .expect("first sync");
+    assert_eq!(&resolve().await[..], first.as_bytes());
+
+    let second = serde_json::json!({ "id": did, "alsoKnownAs": [] }).to_string();
+    apply_single_update(
+        &state.dids_ks,
+        &state.store,
+        &sync(second.clone()),
+        &state.did_cache,
+    )
+    .await
+    .expect("second sync");
+    assert_eq!(&resolve().await[..], second.as_bytes());
+}
+
+/// The watcher sync listing serves the head recorded with the content
+/// instead of reading every log.
+#[tokio::test]
+async fn sync_listing_serves_the_recorded_head() {
+    use did_hosting_common::did::{build_did_document, create_log_entry, encode_host};
+    use did_hosting_common::{DidSyncUpdate, sync_head};
+    use did_hosting_server::config::WatcherEndpoint;
+    use did_hosting_server::control_register::apply_single_update;
+
+    let (mut state, _dir) = make_state().await;
+    let mut config = (*state.config).clone();
+    config.watchers = vec![WatcherEndpoint {
+        url: "http://watcher.example".into(),
+        token: Some("sync-token".into()),
+    }];
+    state.config = Arc::new(config);
+
+    let secret = affinidi_tdk::secrets_resolver::secrets::Secret::generate_ed25519(None, None);
+    let host = encode_host("http://server.example.com").unwrap();
+    let doc = build_did_document(
+        &host,
+        "carol",
+        &secret.get_public_keymultibase().unwrap(),
+        &Default::default(),
+    );
+    let (_scid, log) = create_log_entry(&doc, &secret).await.unwrap();
+    let witness = r#"[{"versionId":"1-x","proof":[]}]"#;
+    apply_single_update(
+        &state.dids_ks,
+        &state.store,
+        &DidSyncUpdate {
+            mnemonic: "carol".into(),
+            did_id: did_hosting_common::did_ops::extract_did_id(&log).unwrap(),
+            log_content: log.clone(),
+            witness_content: Some(witness.into()),
+            version_count: 1,
+            previous_did_id: None,
+            rolled_back: Vec::new(),
+            rollback_proof: None,
+            did_document: None,
+        },
+        &state.did_cache,
+    )
+    .await
+    .expect("sync");
+
+    let mut record: DidRecord = state.dids_ks.get(did_key("carol")).await.unwrap().unwrap();
+    let expected = sync_head(log.as_bytes(), Some(witness.as_bytes()));
+    assert_eq!(record.sync_head.as_deref(), Some(expected.as_str()));
+
+    let listed_head = || {
+        let app = did_hosting_server::routes::router(1024 * 1024).with_state(state.clone());
+        async move {
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .uri("/api/sync/dids")
+                        .header("authorization", "Bearer sync-token")
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let listing: did_hosting_common::SyncDidListResponse =
+                serde_json::from_slice(&body).unwrap();
+            listing.dids[0].head.clone()
+        }
+    };
+    assert_eq!(listed_head().await, Some(expected));
+
+    // The listing takes the record's word for it rather than hashing the
+    // content again.
+    record.sync_head = Some("recorded".into());
+    state
+        .dids_ks
+        .insert(did_key("carol"), &record)
+        .await
+        .unwrap();
+    assert_eq!(listed_head().await.as_deref(), Some("recorded"));
+}
+
+/// A sync stores compressed variants next to the content, and resolves
+/// serve them instead of compressing on demand.
+#[tokio::test]
+async fn synced_content_is_served_from_stored_variants() {
+    use did_hosting_common::DidSyncUpdate;
+    use did_hosting_common::did::{build_did_document, create_log_entry, encode_host};
+    use did_hosting_common::did_ops::content_witness_key;
+    use did_hosting_common::server::encoding::Encoding;
+    use did_hosting_server::control_register::apply_single_update;
+    use did_hosting_server::precompressed::{tag_of, variant_key};
+
+    let (state, _dir) = make_state().await;
+    let secret = affinidi_tdk::secrets_resolver::secrets::Secret::generate_ed25519(None, None);
+    let host = encode_host("http://server.example.com").unwrap();
+    let doc = build_did_document(
+        &host,
+        "frank",
+        &secret.get_public_keymultibase().unwrap(),
+        &Default::default(),
+    );
+    let (_scid, log) = create_log_entry(&doc, &secret).await.unwrap();
+    let witness = |versions: usize| {
+        let proofs: Vec<_> = (1..=versions)
+            .map(|n| serde_json::json!({ "versionId": format!("{n}-x"), "proof": [] }))
+            .collect();
+        serde_json::Value::Array(proofs).to_string()
+    };
+    let sync = |witness: &str| DidSyncUpdate {
+        mnemonic: "frank".into(),
+        did_id: did_hosting_common::did_ops::extract_did_id(&log).unwrap(),
+        log_content: log.clone(),
+        witness_content: Some(witness.into()),
+        version_count: 1,
+        previous_did_id: None,
+        rolled_back: Vec::new(),
+        rollback_proof: None,
+        did_document: None,
+    };
+
+    let first = witness(60);
+    apply_single_update(
+        &state.dids_ks,
+        &state.store,
+        &sync(&first),
+        &state.did_cache,
+    )
+    .await
+    .expect("first sync");
+    let key = content_witness_key("frank");
+    let first_variant = variant_key(&key, Encoding::Brotli, &tag_of(first.as_bytes()));
+    assert!(
+        state
+            .dids_ks
+            .get_raw(first_variant.clone())
+            .await
+            .unwrap()
+            .is_some()
+    );
+
+    // Swap in a marker: it only comes back if the stored variant is served.
+    state
+        .dids_ks
+        .insert_raw(first_variant.clone(), b"stored".to_vec())
+        .await
+        .unwrap();
+    let app = did_hosting_server::routes::router(1024 * 1024).with_state(state.clone());
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/frank/did-witness.json")
+                .header("accept-encoding", "br")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-encoding"], "br");
+    let bytes = axum::body::to_bytes(response.into_body(), 1 << 20)
+        .await
+        .unwrap();
+    assert_eq!(bytes.as_ref(), b"stored");
+
+    // The next write replaces the variants rather than piling them up.
+    let second = witness(80);
+    apply_single_update(
+        &state.dids_ks,
+        &state.store,
+        &sync(&second),
+        &state.did_cache,
+    )
+    .await
+    .expect("second sync");
+    assert!(
+        state
+            .dids_ks
+            .get_raw(first_variant)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    let second_variant = variant_key(&key, Encoding::Gzip, &tag_of(second.as_bytes()));
+    assert!(
+        state
+            .dids_ks
+            .get_raw(second_variant)
+            .await
+            .unwrap()
+            .is_some()
+    );
+}
+
+/// did:webplus slots serve the whole microledger and each version by
+/// `selfHash` and `versionId`, all from the one stored ledger.
+#[cfg(feature = "method-webplus")]
+#[tokio::test]
+async fn webplus_slot_serves_ledger_and_versions() {
+    use did_hosting_common::method::webplus::{SelfHashAlgorithm, compute_self_hash};
+
+    let alg = SelfHashAlgorithm::Blake3;
+    let seal = |doc: serde_json::Value| -> serde_json::Value {
+        let hash = compute_self_hash(&doc, alg).unwrap();
+        serde_json::from_str(&doc.to_string().replace(&alg.placeholder(), &hash)).unwrap()
+    };
+    let v0 = seal(serde_json::json!({
+        "id": format!("did:webplus:server.example.com:people:{}", alg.placeholder()),
+        "selfHash": alg.placeholder(),
+        "updateRules": {"key": "u7QEJX5oaWFJUFJ5eYlGnT7nMx2TF8ArSZqdX4Fy9cvbWJA"},
+        "validFrom": "2026-01-01T00:00:00Z",
+        "versionId": 0,
+    }));
+    let mut v1 = v0.clone();
+    v1["prevDIDDocumentSelfHash"] = v0["selfHash"].clone();
+    v1["selfHash"] = serde_json::json!(alg.placeholder());
+    v1["versionId"] = serde_json::json!(1);
+    v1["validFrom"] = serde_json::json!("2026-02-01T00:00:00Z");
+    let v1 = seal(v1);
+    let ledger = format!("{v0}\n{v1}\n");
+    let root = v0["selfHash"].as_str().unwrap();
+    let mnemonic = format!("people/{root}");
+
+    let record = DidRecord {
+        owner: "did:example:owner".into(),
+        mnemonic: mnemonic.clone(),
+        created_at: 0,
+        updated_at: 0,
+        version_count: 2,
+        did_id: None,
+        content_size: ledger.len() as u64,
+        disabled: false,
+        deleted_at: None,
+        method: "webplus".into(),
+        domain: String::new(),
+        services: None,
+        agent_names: Vec::new(),
+        deactivated_at: None,
+        sync_head: None,
+    };
+    let (state, _dir) = make_state().await;
+    state
+        .dids_ks
+        .insert(did_key(&mnemonic), &record)
+        .await
+        .expect("seed DidRecord");
+    state
+        .dids_ks
+        .insert_raw(content_log_key(&mnemonic), ledger.as_bytes().to_vec())
+        .await
+        .expect("seed did-documents.jsonl");
+
+    let app = did_hosting_server::routes::router(1024 * 1024).with_state(state.clone());
+    let get = |uri: String| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let status = response.status();
+            let content_type = response
+                .headers()
+                .get("content-type")
+                .map(|v| v.to_str().unwrap().to_string());
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            (status, content_type, body)
+        }
+    };
+
+    let (status, ct, body) = get(format!("/{mnemonic}/did-documents.jsonl")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(ct.as_deref(), Some("application/jsonl"));
+    assert_eq!(&body[..], ledger.as_bytes());
+
+    let v1_hash = v1["selfHash"].as_str().unwrap();
+    let (status, ct, body) = get(format!("/{mnemonic}/did/selfHash/{v1_hash}.json")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(ct.as_deref(), Some("application/did+json"));
+    assert_eq!(&body[..], v1.to_string().as_bytes());
+
+    let (status, _, body) = get(format!("/{mnemonic}/did/versionId/0.json")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(&body[..], v0.to_string().as_bytes());
+
+    let (status, _, _) = get(format!("/{mnemonic}/did/versionId/2.json")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _, _) = get(format!("/{mnemonic}/did/versionId/latest.json")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // The log key of a slot tagged for another method is not a ledger.
+    let mut other = record.clone();
+    other.method = "webvh".into();
+    state
+        .dids_ks
+        .insert(did_key(&mnemonic), &other)
+        .await
+        .expect("retag DidRecord");
+    let (status, _, _) = get(format!("/{mnemonic}/did-documents.jsonl")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _, _) = get(format!("/{mnemonic}/did/versionId/0.json")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -892,6 +892,20 @@ export default function DidDetail() {
           </View>
         )}
 
+        {/* Deactivation is terminal: the log is final and stays served as
+            evidence, so say so before anything offers to change it. */}
+        {didDetail?.deactivatedAt != null && (
+          <View style={styles.card}>
+            <Text style={[styles.hint, { color: colors.error }]}>
+              Deactivated {formatDate(didDetail.deactivatedAt)}. This DID
+              accepts no further updates; its log stays published so
+              verifiers can see the deactivation.
+              {didDetail.deletableAt != null &&
+                ` It can be deleted from ${formatDate(didDetail.deletableAt)}.`}
+            </Text>
+          </View>
+        )}
+
         {/* Stats */}
         <View style={styles.card}>
           <Text style={styles.sectionTitle}>Statistics</Text>
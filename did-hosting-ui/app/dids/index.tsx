@@ -384,6 +384,13 @@ export default function DidList() {
                         </Text>
                       </View>
                     )}
+                    {item.deactivatedAt != null && (
+                      <View style={styles.deactivatedBadge}>
+                        <Text style={styles.deactivatedBadgeText}>
+                          Deactivated
+                        </Text>
+                      </View>
+                    )}
                     {showOwnerInfo && isOwn && (
                       <View style={styles.youBadge}>
                         <Text style={styles.youBadgeText}>You</Text>
@@ -564,6 +571,17 @@ const styles = StyleSheet.create({
     gap: spacing.sm,
     marginBottom: spacing.sm,
   },
+  deactivatedBadge: {
+    backgroundColor: colors.errorBg,
+    borderRadius: radii.sm,
+    paddingVertical: 2,
+    paddingHorizontal: spacing.sm,
+  },
+  deactivatedBadgeText: {
+    fontSize: 11,
+    fontFamily: fonts.semibold,
+    color: colors.error,
+  },
   youBadge: {
     backgroundColor: colors.tealMuted,
     borderRadius: radii.sm,
@@ -39,6 +39,10 @@ export interface DidRecord {
    *  `servedNames` before showing them as resolvable. Absent when the DID has
    *  none. */
   agentNames?: AgentNameEntry[];
+  /** Unix seconds the log was published ending in a `deactivated: true`
+   *  entry. Absent for live DIDs. A deactivated DID takes no further
+   *  publishes and does not count against its owner's quota. */
+  deactivatedAt?: number;
 }
 
 // ---------------------------------------------------------------------------
@@ -224,6 +228,10 @@ export interface DidDetailResponse {
    *  `enabled` flag. Absent/empty when the DID has none. Parked names appear
    *  here only — they are deliberately not in the document. */
   agentNames?: AgentNameEntry[];
+  /** Set once the DID is deactivated; its log is final. */
+  deactivatedAt?: number;
+  /** Unix seconds from which a deactivated DID may be deleted. */
+  deletableAt?: number;
 }
 
 export interface LogEntryInfo {
@@ -233,6 +241,46 @@ export interface LogEntryInfo {
   parameters: Record<string, any> | null;
 }
 
+/** A log entry set aside by rollback; `id` is what undo-rollback takes. */
+export interface ArchivedLogEntryInfo extends Partial<LogEntryInfo> {
+  id: string;
+  archivedAt: number;
+  expiresAt: number;
+  archivedBy: string;
+  hasWitness: boolean;
+}
+
+export interface ImportOffer {
+  mnemonic: string;
+  didUrl: string;
+  sourceDid: string;
+  /** The `state.id` the move entry must carry. */
+  targetDid: string;
+  sourceVersionId: string | null;
+  sourceVersionCount: number;
+  /** Must appear in the move entry's `alsoKnownAs`. */
+  alsoKnownAs: string;
+  expiresAt: number;
+}
+
+export interface MovedDid {
+  mnemonic: string;
+  didId: string;
+  previousMnemonic: string;
+  previousDidId: string;
+  versionCount: number;
+  /** Unix seconds until which the old location keeps resolving. */
+  stubExpiresAt: number;
+}
+
+export interface StartImportRequest {
+  sourceUrl?: string;
+  didLog?: string;
+  path?: string;
+  domain?: string;
+  force?: boolean;
+}
+
 export interface CreateDidResponse {
   mnemonic: string;
   didUrl: string;
@@ -724,6 +772,9 @@ export const api = {
   getDidLog: (mnemonic: string) =>
     request<LogEntryInfo[]>(`/api/log/${mnemonic}`),
 
+  getArchivedLogEntries: (mnemonic: string) =>
+    request<ArchivedLogEntryInfo[]>(`/api/log/${mnemonic}?archived=true`),
+
   createDid: (
     path?: string,
     force?: boolean,
@@ -741,6 +792,30 @@ export const api = {
         : {}),
     }),
 
+  startImport: (req: StartImportRequest) =>
+    request<ImportOffer>("/api/import", {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify(req),
+    }),
+
+  getImport: (mnemonic: string) =>
+    request<ImportOffer>(`/api/import/${mnemonic}`),
+
+  completeImport: (mnemonic: string, didLog: string) =>
+    request<DidDetailResponse>(`/api/import/${mnemonic}`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({ didLog }),
+    }),
+
+  moveDid: (mnemonic: string, didLog: string) =>
+    request<MovedDid>(`/api/move/${mnemonic}`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({ didLog }),
+    }),
+
   changeOwner: (mnemonic: string, newOwner: string) =>
     request<ChangeOwnerResponse>(`/api/owner/${mnemonic}`, {
       method: "PUT",
@@ -800,6 +875,13 @@ export const api = {
   rollbackDid: (mnemonic: string) =>
     request<DidDetailResponse>(`/api/rollback/${mnemonic}`, { method: "POST" }),
 
+  undoRollback: (mnemonic: string, id?: string) => {
+    const params = id ? `?id=${encodeURIComponent(id)}` : "";
+    return request<DidDetailResponse>(`/api/undo-rollback/${mnemonic}${params}`, {
+      method: "POST",
+    });
+  },
+
   getRawLog: (mnemonic: string) => requestText(`/api/raw/${mnemonic}`),
 
   getServices: () => request<ServicesResponse>("/api/services"),
@@ -1,4 +1,6 @@
 use crate::error::AppError;
+use did_hosting_common::server::init::decode_public_key;
+use ed25519_dalek::VerifyingKey;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -23,12 +25,30 @@ pub struct SyncConfig {
     /// Shared secret tokens that source servers must present when pushing.
     #[serde(default)]
     pub push_tokens: Vec<String>,
-    /// Source servers to pull from on startup (reconciliation).
+    /// Source servers to reconcile against (see [`crate::reconcile`]).
+    /// Pulled once on startup, then every `reconcile_interval` seconds.
     #[serde(default)]
     pub sources: Vec<SourceConfig>,
-    /// Reconciliation interval in seconds (0 = disabled).
+    /// Reconciliation interval in seconds (0 = startup pass only).
     #[serde(default)]
     pub reconcile_interval: u64,
+    /// Ed25519 multikeys (`z6Mk…`) trusted to sign rollbacks: the control
+    /// plane's assertion key, or a standalone server's signing key. A
+    /// shorter log is a fork unless one of them signed it (see
+    /// [`crate::watcher_ops::is_authenticated_rollback`]).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rollback_keys: Vec<String>,
+}
+
+impl SyncConfig {
+    /// `rollback_keys`, decoded. [`AppConfig::load`] has already refused
+    /// malformed ones.
+    pub fn rollback_verifying_keys(&self) -> Vec<VerifyingKey> {
+        self.rollback_keys
+            .iter()
+            .filter_map(|k| decode_public_key(k))
+            .collect()
+    }
 }
 
 // `push_tokens` are the shared bearer secrets that gate the /sync push
@@ -43,13 +63,16 @@ impl std::fmt::Debug for SyncConfig {
             )
             .field("sources", &self.sources)
             .field("reconcile_interval", &self.reconcile_interval)
+            .field("rollback_keys", &self.rollback_keys)
             .finish()
     }
 }
 
 #[derive(Clone, Deserialize, Serialize)]
 pub struct SourceConfig {
+    /// Base URL of a did-hosting-server.
     pub url: String,
+    /// Must match one of that server's `watchers[].token`.
     pub token: Option<String>,
 }
 
@@ -116,6 +139,17 @@ impl AppConfig {
             config.log.level = v;
         }
 
+        if let Some(key) = config
+            .sync
+            .rollback_keys
+            .iter()
+            .find(|k| decode_public_key(k).is_none())
+        {
+            return Err(AppError::Config(format!(
+                "sync.rollback_keys: not an Ed25519 multikey: {key}"
+            )));
+        }
+
         Ok(config)
     }
 }
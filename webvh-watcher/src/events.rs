@@ -0,0 +1,433 @@
+//! Durable record of tampering the watcher has observed.
+//!
+//! Every sync body goes through [`crate::watcher_ops::apply_sync`], which
+//! compares it with what the watcher already holds. When a source offers
+//! something a well-behaved host never would — rewritten or dropped log
+//! entries, a changed witness proof, a deactivated DID coming back — an
+//! [`WatcherEvent`] is written to [`KS_WATCHER_EVENTS`]. A rollback the
+//! source declares and signs (see
+//! [`crate::watcher_ops::is_authenticated_rollback`]) is accepted, not
+//! tampering, but is recorded too so the removed entries stay on record.
+//! Events are never updated or removed, including when the DID itself is
+//! deleted, so a relying party can always ask "has this DID ever been
+//! tampered with?".
+//!
+//! Keys are `event:{id}`, where `id` is a zero-padded sequence number handed
+//! out in detection order; [`HEAD_KEY`] holds the last one. Listing walks
+//! back from the head by point reads, so a page costs at most [`MAX_SCAN`]
+//! reads however many events are stored, on every backend.
+//!
+//! [`KS_WATCHER_EVENTS`]: did_hosting_common::server::store::KS_WATCHER_EVENTS
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::store::KeyspaceHandle;
+
+/// What kind of divergence was observed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// A held log entry was replaced by a different one.
+    HistoryRewritten,
+    /// The offered log is a strict prefix of the held one.
+    EntriesRemoved,
+    /// A witness's proof for a held version was dropped, or replaced by
+    /// another witness key's. Re-signing is not reported.
+    WitnessProofsChanged,
+    /// The held log ends deactivated; the offered one does not.
+    DeactivationReversed,
+    /// Held entries were removed by a rollback the source declared.
+    /// Accepted; informational only.
+    RolledBack,
+}
+
+impl EventKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EventKind::HistoryRewritten => "history_rewritten",
+            EventKind::EntriesRemoved => "entries_removed",
+            EventKind::WitnessProofsChanged => "witness_proofs_changed",
+            EventKind::DeactivationReversed => "deactivation_reversed",
+            EventKind::RolledBack => "rolled_back",
+        }
+    }
+
+    /// Whether the event is evidence of tampering rather than a change the
+    /// source declared.
+    pub fn is_tamper(self) -> bool {
+        !matches!(self, EventKind::RolledBack)
+    }
+}
+
+/// One observed divergence incident.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatcherEvent {
+    pub id: String,
+    pub kind: EventKind,
+    pub mnemonic: String,
+    pub did_id: Option<String>,
+    /// `source_url` of the sync body that carried the divergent content.
+    pub source_url: String,
+    /// Epoch seconds.
+    pub detected_at: u64,
+    /// Human-readable specifics (which entry, which version).
+    pub detail: String,
+}
+
+/// Query filter for [`list_events`]. All fields but `limit` are optional.
+#[derive(Debug, Default, Clone)]
+pub struct EventFilter {
+    pub mnemonic: Option<String>,
+    pub kind: Option<EventKind>,
+    /// Inclusive lower bound on `detected_at`.
+    pub since: Option<u64>,
+    /// Only events with a lower id — the paging cursor.
+    pub before: Option<u64>,
+    /// Maximum number of events returned (newest first).
+    pub limit: usize,
+}
+
+/// A page of events, newest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventPage {
+    pub events: Vec<WatcherEvent>,
+    /// Set when the page stopped before the oldest event: pass it back as
+    /// `before` for the rest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_before: Option<u64>,
+}
+
+/// Key holding the id of the newest event.
+pub const HEAD_KEY: &str = "head";
+
+/// Most events one [`list_events`] call reads, matching or not.
+pub const MAX_SCAN: u64 = 10_000;
+
+/// One writer per process; [`record_event`]'s insert-if-absent keeps ids
+/// distinct across replicas.
+static APPEND_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+pub fn event_key(id: u64) -> String {
+    format!("event:{id:020}")
+}
+
+/// The newest event id, or `None` before the first event. The head can
+/// lag an event another replica wrote, so probe past it.
+async fn tip(ks: &KeyspaceHandle) -> Result<Option<u64>, AppError> {
+    let Some(mut tip) = ks.get::<u64>(HEAD_KEY).await? else {
+        return Ok(None);
+    };
+    while ks.contains_key(event_key(tip + 1)).await? {
+        tip += 1;
+    }
+    Ok(Some(tip))
+}
+
+/// Persist one event.
+pub async fn record_event(
+    ks: &KeyspaceHandle,
+    kind: EventKind,
+    mnemonic: &str,
+    did_id: Option<&str>,
+    source_url: &str,
+    detail: String,
+) -> Result<WatcherEvent, AppError> {
+    let _guard = APPEND_LOCK.lock().await;
+    let mut id = tip(ks).await?.map_or(0, |tip| tip + 1);
+    let mut event = WatcherEvent {
+        id: String::new(),
+        kind,
+        mnemonic: mnemonic.to_string(),
+        did_id: did_id.map(str::to_string),
+        source_url: source_url.to_string(),
+        detected_at: did_hosting_common::server::auth::session::now_epoch(),
+        detail,
+    };
+    loop {
+        event.id = format!("{id:020}");
+        if ks.insert_if_absent(event_key(id), &event).await? {
+            break;
+        }
+        id += 1;
+    }
+    ks.insert(HEAD_KEY, &id).await?;
+    if event.kind.is_tamper() {
+        warn!(
+            mnemonic = %event.mnemonic,
+            source = %event.source_url,
+            kind = event.kind.as_str(),
+            detail = %event.detail,
+            "tamper event recorded"
+        );
+    } else {
+        info!(
+            mnemonic = %event.mnemonic,
+            source = %event.source_url,
+            kind = event.kind.as_str(),
+            detail = %event.detail,
+            "watcher event recorded"
+        );
+    }
+    Ok(event)
+}
+
+/// Events matching `filter`, newest first.
+///
+/// Reads events by id from the cursor towards the oldest, stopping at
+/// `limit` matches, after [`MAX_SCAN`] events, or at the first event
+/// detected before `since`; `next_before` then says where to resume
+/// unless the walk reached either end.
+pub async fn list_events(ks: &KeyspaceHandle, filter: &EventFilter) -> Result<EventPage, AppError> {
+    let mut page = EventPage {
+        events: Vec::new(),
+        next_before: None,
+    };
+    let Some(tip) = tip(ks).await? else {
+        return Ok(page);
+    };
+    let start = match filter.before {
+        Some(0) => return Ok(page),
+        Some(before) => (before - 1).min(tip),
+        None => tip,
+    };
+    for (scanned, id) in (0..=start).rev().enumerate() {
+        if page.events.len() == filter.limit || scanned as u64 == MAX_SCAN {
+            page.next_before = Some(id + 1);
+            break;
+        }
+        let Some(event) = ks.get::<WatcherEvent>(event_key(id)).await? else {
+            continue;
+        };
+        if filter.since.is_some_and(|s| event.detected_at < s) {
+            break;
+        }
+        if filter
+            .mnemonic
+            .as_deref()
+            .is_none_or(|m| event.mnemonic == m)
+            && filter.kind.is_none_or(|k| event.kind == k)
+        {
+            page.events.push(event);
+        }
+    }
+    Ok(page)
+}
+
+// ---------------------------------------------------------------------------
+// Content inspection
+// ---------------------------------------------------------------------------
+
+/// Whether the last entry of a log deactivates the DID.
+pub fn log_is_deactivated(log: &str) -> bool {
+    log.lines()
+        .map(str::trim)
+        .rfind(|l| !l.is_empty())
+        .and_then(|l| serde_json::from_str::<Value>(l).ok())
+        .and_then(|v| {
+            v.pointer("/parameters/deactivated")
+                .and_then(Value::as_bool)
+        })
+        .unwrap_or(false)
+}
+
+/// A witness attestation the offered witness file no longer carries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LostWitnessProof {
+    /// Highest version the witness had signed in the held file.
+    pub version_id: String,
+    /// The witness's verification method.
+    pub witness: String,
+    /// Other witness keys the offered file signs that version with instead.
+    pub replaced_by: Vec<String>,
+}
+
+impl std::fmt::Display for LostWitnessProof {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.version_id, self.witness)?;
+        if self.replaced_by.is_empty() {
+            write!(f, " dropped")
+        } else {
+            write!(f, " replaced by {}", self.replaced_by.join(", "))
+        }
+    }
+}
+
+/// Witness attestations held but no longer offered.
+///
+/// A witness's proof for version N attests every version up to N, so each
+/// witness is judged by the highest version it signs. Re-signing (a new
+/// `proofValue` for the same version and key) and pruning proofs a later
+/// one from the same witness covers are both legitimate and not reported.
+/// Only a witness whose highest signed version is no longer covered — its
+/// proofs dropped, or swapped for another key's — counts. Coverage is
+/// capped at `log_entries`, the offered log's length: proofs for versions
+/// an accepted rollback removed are expected to go.
+pub fn lost_witness_proofs(held: &str, offered: &str, log_entries: u64) -> Vec<LostWitnessProof> {
+    let held = witness_proofs(held);
+    let offered = witness_proofs(offered);
+    let mut lost: Vec<LostWitnessProof> = held
+        .iter()
+        .filter(|(witness, (number, _))| {
+            let needed = (*number).min(log_entries);
+            needed > 0
+                && offered
+                    .get(*witness)
+                    .is_none_or(|(offered_number, _)| *offered_number < needed)
+        })
+        .map(|(witness, (_, version_id))| LostWitnessProof {
+            version_id: version_id.clone(),
+            witness: witness.clone(),
+            replaced_by: offered
+                .iter()
+                .filter(|(other, (_, v))| v == version_id && !held.contains_key(*other))
+                .map(|(other, _)| other.clone())
+                .collect(),
+        })
+        .collect();
+    for l in &mut lost {
+        l.replaced_by.sort();
+    }
+    lost.sort_by(|a, b| (&a.version_id, &a.witness).cmp(&(&b.version_id, &b.witness)));
+    lost
+}
+
+/// Per witness key, the highest version it signs: `(number, versionId)`.
+fn witness_proofs(content: &str) -> std::collections::HashMap<String, (u64, String)> {
+    let mut out: std::collections::HashMap<String, (u64, String)> =
+        std::collections::HashMap::new();
+    let Ok(Value::Array(entries)) = serde_json::from_str::<Value>(content) else {
+        return out;
+    };
+    for entry in &entries {
+        let Some(version_id) = entry.get("versionId").and_then(Value::as_str) else {
+            continue;
+        };
+        let Some(number) = version_id
+            .split_once('-')
+            .and_then(|(n, _)| n.parse::<u64>().ok())
+        else {
+            continue;
+        };
+        let Some(proofs) = entry.get("proof").and_then(Value::as_array) else {
+            continue;
+        };
+        for proof in proofs {
+            let Some(vm) = proof.get("verificationMethod").and_then(Value::as_str) else {
+                continue;
+            };
+            if out.get(vm).is_none_or(|(held, _)| *held < number) {
+                out.insert(vm.to_string(), (number, version_id.to_string()));
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn pages_newest_first_from_the_cursor() {
+        use did_hosting_common::server::config::StoreConfig;
+        use did_hosting_common::server::store::KS_WATCHER_EVENTS;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = crate::store::Store::open(&StoreConfig {
+            data_dir: dir.path().to_path_buf(),
+            ..StoreConfig::default()
+        })
+        .await
+        .unwrap();
+        let ks = store.keyspace(KS_WATCHER_EVENTS).unwrap();
+        for n in 0..5 {
+            let mnemonic = if n % 2 == 0 { "even" } else { "odd" };
+            let event = record_event(
+                &ks,
+                EventKind::HistoryRewritten,
+                mnemonic,
+                None,
+                "https://source.example",
+                format!("event {n}"),
+            )
+            .await
+            .unwrap();
+            assert_eq!(event.id, format!("{n:020}"));
+        }
+
+        let mut filter = EventFilter {
+            limit: 2,
+            ..EventFilter::default()
+        };
+        let mut seen = Vec::new();
+        loop {
+            let page = list_events(&ks, &filter).await.unwrap();
+            seen.extend(page.events.into_iter().map(|e| e.detail));
+            match page.next_before {
+                Some(before) => filter.before = Some(before),
+                None => break,
+            }
+        }
+        assert_eq!(
+            seen,
+            ["event 4", "event 3", "event 2", "event 1", "event 0"]
+        );
+
+        let odd = list_events(
+            &ks,
+            &EventFilter {
+                mnemonic: Some("odd".into()),
+                before: Some(3),
+                limit: 10,
+                ..EventFilter::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(odd.events.len(), 1);
+        assert_eq!(odd.events[0].detail, "event 1");
+        assert_eq!(odd.next_before, None);
+    }
+
+    #[test]
+    fn detects_deactivated_tail() {
+        let log = "{\"parameters\":{}}\n{\"parameters\":{\"deactivated\":true}}\n";
+        assert!(log_is_deactivated(log));
+        assert!(!log_is_deactivated("{\"parameters\":{}}\n"));
+    }
+
+    #[test]
+    fn reports_dropped_and_replaced_but_not_resigned_or_pruned_proofs() {
+        let held = r#"[
+            {"versionId":"1-a","proof":[{"verificationMethod":"w1","proofValue":"x"}]},
+            {"versionId":"2-b","proof":[{"verificationMethod":"w1","proofValue":"y"}]}
+        ]"#;
+        let pruned = r#"[
+            {"versionId":"2-b","proof":[{"verificationMethod":"w1","proofValue":"y"}]}
+        ]"#;
+        let resigned = r#"[
+            {"versionId":"2-b","proof":[{"verificationMethod":"w1","proofValue":"z"}]}
+        ]"#;
+        let rolled_back = r#"[
+            {"versionId":"1-a","proof":[{"verificationMethod":"w1","proofValue":"x"}]}
+        ]"#;
+        let replaced = r#"[
+            {"versionId":"2-b","proof":[{"verificationMethod":"w2","proofValue":"y"}]}
+        ]"#;
+        assert!(lost_witness_proofs(held, pruned, 2).is_empty());
+        assert!(lost_witness_proofs(held, resigned, 2).is_empty());
+        // The log was rolled back too: version 2 is gone from it.
+        assert!(lost_witness_proofs(held, rolled_back, 1).is_empty());
+
+        let dropped = lost_witness_proofs(held, rolled_back, 2);
+        assert_eq!(dropped.len(), 1);
+        assert_eq!(dropped[0].to_string(), "2-b (w1) dropped");
+
+        let swapped = lost_witness_proofs(held, replaced, 2);
+        assert_eq!(swapped.len(), 1);
+        assert_eq!(swapped[0].to_string(), "2-b (w1) replaced by w2");
+    }
+}
@@ -23,6 +23,8 @@ pub async fn run_health(config_path: Option<PathBuf>) -> Result<(), Box<dyn Erro
                     "Reconcile interval: {}s",
                     c.sync.reconcile_interval
                 ));
+            } else if !c.sync.sources.is_empty() {
+                health::info_msg("Reconcile interval: startup pass only");
             } else {
                 health::info_msg("Reconcile interval: disabled");
             }
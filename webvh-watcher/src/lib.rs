@@ -10,7 +10,9 @@
 
 pub mod config;
 pub mod error;
+pub mod events;
 pub mod health;
+pub mod reconcile;
 pub mod routes;
 pub mod server;
 pub mod setup;
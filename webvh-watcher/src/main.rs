@@ -1,4 +1,6 @@
 use clap::{Parser, Subcommand};
+use did_hosting_common::server::backup::KeyspaceSelection;
+use did_hosting_common::server::store_migrate::{self, MigrateRequest};
 use std::path::PathBuf;
 use webvh_watcher::config::AppConfig;
 use webvh_watcher::{health, server, setup, store};
@@ -36,6 +38,30 @@ enum Command {
     },
     /// Run health check diagnostics
     Health,
+    /// Copy every keyspace into another storage backend.
+    ///
+    /// Stop the service first. Both backends' `store-*` features must be
+    /// compiled into this binary.
+    StoreMigrate {
+        /// Config file whose [store] is the source
+        #[arg(long)]
+        from: PathBuf,
+        /// Config file whose [store] is the target
+        #[arg(long)]
+        to: PathBuf,
+        /// Progress file; an interrupted run resumes from it
+        #[arg(long, default_value = "store-migrate.checkpoint")]
+        checkpoint: PathBuf,
+        /// Migrate only these keyspaces (repeatable or comma-separated).
+        #[arg(long = "keyspace", value_delimiter = ',')]
+        keyspaces: Vec<String>,
+        /// Leave these keyspaces out.
+        #[arg(long = "exclude-keyspace", value_delimiter = ',')]
+        exclude_keyspaces: Vec<String>,
+        /// Compare the two stores without copying anything
+        #[arg(long)]
+        verify_only: bool,
+    },
 }
 
 #[tokio::main]
@@ -65,6 +91,31 @@ async fn main() {
                 std::process::exit(1);
             }
         }
+        Some(Command::StoreMigrate {
+            from,
+            to,
+            checkpoint,
+            keyspaces,
+            exclude_keyspaces,
+            verify_only,
+        }) => {
+            let req = MigrateRequest {
+                from,
+                to,
+                checkpoint,
+                selection: KeyspaceSelection {
+                    only: keyspaces,
+                    exclude: exclude_keyspaces,
+                },
+                verify_only,
+            };
+            if let Err(e) =
+                store_migrate::run_store_migrate(&req, "store", store::KEYSPACES, None).await
+            {
+                eprintln!("Store migration error: {e}");
+                std::process::exit(1);
+            }
+        }
         None => run_watcher(cli.config).await,
     }
 }
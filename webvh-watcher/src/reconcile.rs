@@ -0,0 +1,371 @@
+//! Pull-based reconciliation against the configured source servers.
+//!
+//! Pushes (`POST /api/sync/did`) are fire-and-forget on the server side, so
+//! a watcher that is down or unreachable during a push silently falls
+//! behind. Reconciliation closes that gap: for each entry in
+//! `sync.sources` it lists the server's DIDs (`GET /api/sync/dids`), fetches
+//! every DID the watcher is missing or holds an older copy of, and removes
+//! the DIDs that source no longer lists.
+//!
+//! A pass runs once at startup whenever `sources` is non-empty, then every
+//! `reconcile_interval` seconds if that is non-zero. A source that cannot be
+//! listed is skipped for the pass — nothing is removed on a failed listing.
+//! Shutdown stops a pass between DIDs and abandons any request in flight;
+//! a DID already being applied is finished first, so nothing is left half
+//! written.
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use did_hosting_common::{SyncDidListResponse, SyncDidRequest};
+use ed25519_dalek::VerifyingKey;
+use serde::de::DeserializeOwned;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use crate::config::SourceConfig;
+use crate::error::AppError;
+use crate::server::AppState;
+use crate::store::{KeyspaceHandle, Store};
+use crate::watcher_ops::{self, WatcherRecord};
+
+/// Upper bound on any single request to a source server.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Outcome of reconciling one source.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReconcileSummary {
+    /// DIDs fetched because they were missing or stale.
+    pub fetched: usize,
+    /// DIDs removed because the source no longer lists them.
+    pub removed: usize,
+    /// DIDs whose fetch or store failed; retried on the next pass.
+    pub failed: usize,
+    /// Shutdown stopped the pass before it was done.
+    pub cancelled: bool,
+}
+
+/// Spawn the reconcile loop on the current runtime. Returns `None` when no
+/// sources are configured.
+pub fn spawn(
+    state: AppState,
+    mut shutdown: watch::Receiver<bool>,
+) -> Option<tokio::task::JoinHandle<()>> {
+    if state.config.sync.sources.is_empty() {
+        return None;
+    }
+
+    Some(tokio::spawn(async move {
+        let http = match reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build() {
+            Ok(c) => c,
+            Err(e) => {
+                warn!(error = %e, "reconcile: failed to build HTTP client; reconciliation disabled");
+                return;
+            }
+        };
+        let interval = state.config.sync.reconcile_interval;
+
+        loop {
+            run_pass(&state, &http, &shutdown).await;
+            if *shutdown.borrow() {
+                return;
+            }
+
+            if interval == 0 {
+                info!("reconcile: startup pass complete (no interval configured)");
+                return;
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(interval)) => {}
+                _ = shutdown.changed() => return,
+            }
+        }
+    }))
+}
+
+/// Reconcile every configured source once, or until shutdown.
+async fn run_pass(state: &AppState, http: &reqwest::Client, shutdown: &watch::Receiver<bool>) {
+    let rollback_keys = state.config.sync.rollback_verifying_keys();
+    for source in &state.config.sync.sources {
+        match reconcile_source(
+            &state.store,
+            &state.dids_ks,
+            &state.events_ks,
+            &rollback_keys,
+            http,
+            source,
+            shutdown,
+        )
+        .await
+        {
+            Ok(summary) if summary.cancelled => {
+                info!(
+                    source = %source.url,
+                    fetched = summary.fetched,
+                    removed = summary.removed,
+                    "reconcile: pass cancelled by shutdown"
+                );
+                return;
+            }
+            Ok(summary) => info!(
+                source = %source.url,
+                fetched = summary.fetched,
+                removed = summary.removed,
+                failed = summary.failed,
+                "reconcile: source reconciled"
+            ),
+            Err(e) => warn!(source = %source.url, error = %e, "reconcile: source skipped"),
+        }
+    }
+}
+
+/// Resolves once shutdown is signalled (or its sender is gone).
+async fn cancelled(shutdown: &watch::Receiver<bool>) {
+    let mut shutdown = shutdown.clone();
+    let _ = shutdown.wait_for(|stop| *stop).await;
+}
+
+/// Bring the local mirror in line with one source server.
+///
+/// Stops early, with [`ReconcileSummary::cancelled`] set, once `shutdown`
+/// fires.
+pub async fn reconcile_source(
+    store: &Store,
+    ks: &KeyspaceHandle,
+    events_ks: &KeyspaceHandle,
+    rollback_keys: &[VerifyingKey],
+    http: &reqwest::Client,
+    source: &SourceConfig,
+    shutdown: &watch::Receiver<bool>,
+) -> Result<ReconcileSummary, AppError> {
+    let mut summary = ReconcileSummary::default();
+    let base = source.url.trim_end_matches('/');
+    let url = format!("{base}/api/sync/dids");
+    let listing: SyncDidListResponse = tokio::select! {
+        listing = get_json(http, source, &url) => listing?,
+        _ = cancelled(shutdown) => {
+            summary.cancelled = true;
+            return Ok(summary);
+        }
+    };
+
+    let local = watcher_ops::list_records(ks).await?;
+    let plan = plan(&local, &listing);
+
+    for mnemonic in plan.fetch {
+        let url = format!("{base}/api/sync/dids/{mnemonic}");
+        let fetched = tokio::select! {
+            fetched = get_json::<SyncDidRequest>(http, source, &url) => fetched,
+            _ = cancelled(shutdown) => {
+                summary.cancelled = true;
+                return Ok(summary);
+            }
+        };
+        let result = match fetched {
+            Ok(req) => watcher_ops::apply_sync(store, ks, events_ks, rollback_keys, req).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => summary.fetched += 1,
+            Err(e) => {
+                warn!(source = %source.url, mnemonic = %mnemonic, error = %e, "reconcile: fetch failed");
+                summary.failed += 1;
+            }
+        }
+    }
+    for mnemonic in plan.remove {
+        if *shutdown.borrow() {
+            summary.cancelled = true;
+            return Ok(summary);
+        }
+        match watcher_ops::delete_record(store, ks, &mnemonic, &source.url).await {
+            Ok(()) => summary.removed += 1,
+            Err(e) => {
+                warn!(source = %source.url, mnemonic = %mnemonic, error = %e, "reconcile: remove failed");
+                summary.failed += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// What one reconcile pass will do for a source.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Plan {
+    fetch: Vec<String>,
+    remove: Vec<String>,
+}
+
+/// Diff the local mirror against a source's listing.
+///
+/// A DID is fetched when the watcher has no copy, content whose head differs
+/// from the listed one, or a different `disabled` flag. Heads catch what
+/// one-second `updated_at` stamps cannot: a second write within the same
+/// second. Only a source that lists no head is compared by `updated_at`.
+/// A local DID is removed only when it came from this source (its
+/// `source_url` matches the listing's) and the listing no longer has it, so
+/// one source never deletes another's DIDs.
+fn plan(local: &[WatcherRecord], listing: &SyncDidListResponse) -> Plan {
+    let by_mnemonic: HashMap<&str, &WatcherRecord> =
+        local.iter().map(|r| (r.mnemonic.as_str(), r)).collect();
+    let listed: HashSet<&str> = listing.dids.iter().map(|d| d.mnemonic.as_str()).collect();
+
+    let fetch = listing
+        .dids
+        .iter()
+        .filter(|remote| match by_mnemonic.get(remote.mnemonic.as_str()) {
+            None => true,
+            Some(r) => {
+                let stale = match &remote.head {
+                    Some(head) => r.head.as_ref() != Some(head),
+                    None => r.updated_at < remote.updated_at,
+                };
+                stale || r.disabled != remote.disabled
+            }
+        })
+        .map(|d| d.mnemonic.clone())
+        .collect();
+    let remove = local
+        .iter()
+        .filter(|r| r.source_url == listing.source_url && !listed.contains(r.mnemonic.as_str()))
+        .map(|r| r.mnemonic.clone())
+        .collect();
+
+    Plan { fetch, remove }
+}
+
+async fn get_json<T: DeserializeOwned>(
+    http: &reqwest::Client,
+    source: &SourceConfig,
+    url: &str,
+) -> Result<T, AppError> {
+    let mut req = http.get(url);
+    if let Some(token) = &source.token {
+        req = req.bearer_auth(token);
+    }
+    let resp = req
+        .send()
+        .await
+        .map_err(|e| AppError::Internal(format!("GET {url} failed: {e}")))?;
+    if !resp.status().is_success() {
+        return Err(AppError::Internal(format!(
+            "GET {url} returned HTTP {}",
+            resp.status()
+        )));
+    }
+    resp.json()
+        .await
+        .map_err(|e| AppError::Internal(format!("GET {url}: invalid response body: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use did_hosting_common::SyncDidSummary;
+
+    const SOURCE: &str = "https://a.example.com";
+
+    fn local(mnemonic: &str, source_url: &str, updated_at: u64) -> WatcherRecord {
+        WatcherRecord {
+            mnemonic: mnemonic.into(),
+            did_id: None,
+            source_url: source_url.into(),
+            updated_at,
+            disabled: false,
+            head: None,
+            fork: None,
+        }
+    }
+
+    fn local_at(mnemonic: &str, updated_at: u64, head: Option<&str>) -> WatcherRecord {
+        WatcherRecord {
+            head: head.map(Into::into),
+            ..local(mnemonic, SOURCE, updated_at)
+        }
+    }
+
+    fn listing(dids: &[(&str, u64, bool)]) -> SyncDidListResponse {
+        SyncDidListResponse {
+            source_url: SOURCE.into(),
+            dids: dids
+                .iter()
+                .map(|(m, updated_at, disabled)| SyncDidSummary {
+                    mnemonic: (*m).into(),
+                    did_id: None,
+                    updated_at: *updated_at,
+                    disabled: *disabled,
+                    head: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn listing_with_heads(dids: &[(&str, u64, &str)]) -> SyncDidListResponse {
+        let mut out = listing(
+            &dids
+                .iter()
+                .map(|(m, updated_at, _)| (*m, *updated_at, false))
+                .collect::<Vec<_>>(),
+        );
+        for (summary, (_, _, head)) in out.dids.iter_mut().zip(dids) {
+            summary.head = Some((*head).into());
+        }
+        out
+    }
+
+    #[test]
+    fn fetches_missing_stale_and_flag_changed_dids() {
+        let local = vec![
+            local("current", SOURCE, 10),
+            local("stale", SOURCE, 5),
+            local("toggled", SOURCE, 10),
+        ];
+        let listing = listing(&[
+            ("current", 10, false),
+            ("stale", 6, false),
+            ("toggled", 10, true),
+            ("missing", 1, false),
+        ]);
+        let plan = plan(&local, &listing);
+        assert_eq!(plan.fetch, vec!["stale", "toggled", "missing"]);
+        assert!(plan.remove.is_empty());
+    }
+
+    #[test]
+    fn removes_only_this_sources_unlisted_dids() {
+        let local = vec![
+            local("gone", SOURCE, 1),
+            local("other-source", "https://b.example.com", 1),
+        ];
+        let plan = plan(&local, &listing(&[]));
+        assert!(plan.fetch.is_empty());
+        assert_eq!(plan.remove, vec!["gone"]);
+    }
+
+    #[test]
+    fn newer_local_copy_is_not_refetched() {
+        let plan = plan(
+            &[local("ahead", SOURCE, 20)],
+            &listing(&[("ahead", 10, false)]),
+        );
+        assert_eq!(plan, Plan::default());
+    }
+
+    #[test]
+    fn heads_decide_when_the_source_lists_them() {
+        let local = vec![
+            local_at("same-second", 10, Some("h1")),
+            local_at("unchanged", 10, Some("h1")),
+            local_at("rolled-back", 20, Some("h2")),
+            local_at("legacy", 10, None),
+        ];
+        let listing = listing_with_heads(&[
+            ("same-second", 10, "h2"),
+            ("unchanged", 10, "h1"),
+            ("rolled-back", 20, "h1"),
+            ("legacy", 10, "h1"),
+        ]);
+        let plan = plan(&local, &listing);
+        assert_eq!(plan.fetch, vec!["same-second", "rolled-back", "legacy"]);
+    }
+}
@@ -1,7 +1,9 @@
 use axum::extract::State;
-use axum::http::{StatusCode, Uri};
+use axum::http::{HeaderMap, StatusCode, Uri};
 use axum::response::{IntoResponse, Response};
 
+use did_hosting_common::server::conditional::{self, Validators};
+use did_hosting_common::server::encoding::{self, Encoding};
 use did_hosting_common::server::mnemonic::validate_mnemonic;
 use tracing::debug;
 
@@ -9,20 +11,28 @@ use crate::error::AppError;
 use crate::server::AppState;
 use crate::watcher_ops::{self, WatcherRecord};
 
-/// Serve stored content for a mnemonic.
+/// Content type of a did:webvh log; its ETag is the last `versionId`.
+const LOG_CONTENT_TYPE: &str = "application/jsonl+json";
+
+/// Serve stored content for a mnemonic, answering `304 Not Modified` when
+/// the request's `If-None-Match` / `If-Modified-Since` still match.
+///
+/// Compressed variants were stored next to the content at sync time
+/// ([`watcher_ops::encoded_key`]); the one the client prefers is served
+/// when it exists, the raw bytes otherwise.
 async fn serve_content(
     state: &AppState,
     mnemonic: &str,
     key: &str,
     content_type: &str,
+    request_headers: &HeaderMap,
 ) -> Result<Response, AppError> {
     // Check if the DID is disabled — return 404 to avoid leaking state.
-    if let Some(record) = state
+    let record = state
         .dids_ks
         .get::<WatcherRecord>(watcher_ops::did_key(mnemonic))
-        .await?
-        && record.disabled
-    {
+        .await?;
+    if record.as_ref().is_some_and(|r| r.disabled) {
         return Err(AppError::NotFound(format!("content not found: {mnemonic}")));
     }
 
@@ -35,45 +45,82 @@ async fn serve_content(
     debug!(mnemonic = %mnemonic, size = content.len(), content_type, "content resolved");
 
     // Public DID resolution is cacheable (content-addressed via the SCID).
-    // Setting Cache-Control here overrides the global `no-store` security
-    // middleware so CDNs / browsers can serve mirrored DIDs without
-    // hitting the watcher origin every time.
-    Ok((
-        StatusCode::OK,
-        [
-            ("content-type", content_type),
-            ("cache-control", "public, max-age=300"),
-        ],
-        content,
-    )
-        .into_response())
+    // `respond` sets an explicit public Cache-Control, which overrides the
+    // global `no-store` security middleware so CDNs / browsers can serve
+    // mirrored DIDs without hitting the watcher origin every time.
+    let updated_at = record.map(|r| r.updated_at);
+    let validators = if content_type == LOG_CONTENT_TYPE {
+        Validators::for_log(&content, updated_at)
+    } else {
+        Validators::for_content(&content, updated_at)
+    };
+    let chosen = encoding::negotiate(request_headers, &Encoding::COMPRESSED);
+    if chosen != Encoding::Identity {
+        if conditional::is_not_modified(request_headers, &validators.for_encoding(chosen)) {
+            return Ok(conditional::respond_encoded(
+                request_headers,
+                &validators,
+                content_type,
+                chosen,
+                Default::default,
+            ));
+        }
+        if let Some(variant) = state
+            .dids_ks
+            .get_raw(watcher_ops::encoded_key(key, chosen))
+            .await?
+        {
+            return Ok(conditional::respond_encoded(
+                request_headers,
+                &validators,
+                content_type,
+                chosen,
+                || variant.into(),
+            ));
+        }
+    }
+    Ok(conditional::respond_encoded(
+        request_headers,
+        &validators,
+        content_type,
+        Encoding::Identity,
+        || content.into(),
+    ))
 }
 
 /// GET /.well-known/did.jsonl — serve the root DID log
-pub async fn serve_root_did_log(State(state): State<AppState>) -> Result<Response, AppError> {
+pub async fn serve_root_did_log(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
     serve_content(
         &state,
         ".well-known",
         "content:.well-known:log",
-        "application/jsonl+json",
+        LOG_CONTENT_TYPE,
+        &headers,
     )
     .await
 }
 
 /// GET /.well-known/did-witness.json — serve the root witness
-pub async fn serve_root_witness(State(state): State<AppState>) -> Result<Response, AppError> {
+pub async fn serve_root_witness(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
     serve_content(
         &state,
         ".well-known",
         "content:.well-known:witness",
         "application/json",
+        &headers,
     )
     .await
 }
 
 /// Combined fallback handler: serves DID documents for any path ending
 /// in `/did.jsonl` or `/did-witness.json`.
-pub async fn serve_public(State(state): State<AppState>, uri: Uri) -> Response {
+pub async fn serve_public(State(state): State<AppState>, uri: Uri, headers: HeaderMap) -> Response {
     let path = uri.path().trim_start_matches('/');
 
     // Check for DID log: <mnemonic>/did.jsonl
@@ -84,7 +131,7 @@ pub async fn serve_public(State(state): State<AppState>, uri: Uri) -> Response {
             return e.into_response();
         }
         let key = format!("content:{mnemonic}:log");
-        return match serve_content(&state, mnemonic, &key, "application/jsonl+json").await {
+        return match serve_content(&state, mnemonic, &key, LOG_CONTENT_TYPE, &headers).await {
             Ok(resp) => resp,
             Err(e) => e.into_response(),
         };
@@ -98,7 +145,7 @@ pub async fn serve_public(State(state): State<AppState>, uri: Uri) -> Response {
             return e.into_response();
         }
         let key = format!("content:{mnemonic}:witness");
-        return match serve_content(&state, mnemonic, &key, "application/json").await {
+        return match serve_content(&state, mnemonic, &key, "application/json", &headers).await {
             Ok(resp) => resp,
             Err(e) => e.into_response(),
         };
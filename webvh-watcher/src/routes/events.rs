@@ -0,0 +1,169 @@
+//! Tamper event queries and feeds.
+//!
+//! - `GET /api/events` — JSON list, filterable by `mnemonic`, `kind`,
+//!   `since` and `limit` (default 100, max 1000), paged with `before`: pass
+//!   back the `next_before` of the previous page.
+//! - `GET /api/events/feed.json` — the same events as a JSON Feed 1.1.
+//! - `GET /api/events/feed.atom` — the same events as an Atom feed.
+//!
+//! All three are public: they describe misbehaviour by a source, which is
+//! exactly what relying parties and external monitors need to see. Events
+//! for disabled or deleted DIDs stay visible.
+
+use axum::Json;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::error::AppError;
+use crate::events::{self, EventFilter, EventKind, EventPage, WatcherEvent};
+use crate::server::AppState;
+
+/// Default and maximum page size.
+const DEFAULT_LIMIT: usize = 100;
+const MAX_LIMIT: usize = 1000;
+
+const FEED_ID: &str = "urn:webvh-watcher:events";
+const FEED_TITLE: &str = "webvh-watcher tamper events";
+
+#[derive(Debug, Deserialize)]
+pub struct EventsQuery {
+    pub mnemonic: Option<String>,
+    pub kind: Option<EventKind>,
+    /// Inclusive lower bound on `detected_at` (Unix seconds).
+    pub since: Option<u64>,
+    /// Only events with a lower id — the paging cursor.
+    pub before: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+async fn query_events(state: &AppState, query: EventsQuery) -> Result<EventPage, AppError> {
+    events::list_events(
+        &state.events_ks,
+        &EventFilter {
+            mnemonic: query.mnemonic,
+            kind: query.kind,
+            since: query.since,
+            before: query.before,
+            limit: query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT),
+        },
+    )
+    .await
+}
+
+/// GET /api/events
+pub async fn list_events(
+    State(state): State<AppState>,
+    Query(query): Query<EventsQuery>,
+) -> Result<Json<EventPage>, AppError> {
+    Ok(Json(query_events(&state, query).await?))
+}
+
+/// GET /api/events/feed.json
+pub async fn json_feed(
+    State(state): State<AppState>,
+    Query(query): Query<EventsQuery>,
+) -> Result<Response, AppError> {
+    let events = query_events(&state, query).await?.events;
+    let items: Vec<_> = events
+        .iter()
+        .map(|e| {
+            json!({
+                "id": entry_id(e),
+                "title": entry_title(e),
+                "content_text": e.detail,
+                "date_published": rfc3339(e.detected_at),
+                "tags": [e.kind.as_str()],
+                "_watcher_event": e,
+            })
+        })
+        .collect();
+    let feed = json!({
+        "version": "https://jsonfeed.org/version/1.1",
+        "title": FEED_TITLE,
+        "items": items,
+    });
+    Ok((
+        StatusCode::OK,
+        [("content-type", "application/feed+json")],
+        feed.to_string(),
+    )
+        .into_response())
+}
+
+/// GET /api/events/feed.atom
+pub async fn atom_feed(
+    State(state): State<AppState>,
+    Query(query): Query<EventsQuery>,
+) -> Result<Response, AppError> {
+    let events = query_events(&state, query).await?.events;
+    let updated = events
+        .first()
+        .map_or_else(|| rfc3339(0), |e| rfc3339(e.detected_at));
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str(&format!("  <id>{FEED_ID}</id>\n"));
+    xml.push_str(&format!("  <title>{FEED_TITLE}</title>\n"));
+    xml.push_str(&format!("  <updated>{updated}</updated>\n"));
+    xml.push_str("  <author><name>webvh-watcher</name></author>\n");
+    for e in &events {
+        xml.push_str("  <entry>\n");
+        xml.push_str(&format!("    <id>{}</id>\n", escape_xml(&entry_id(e))));
+        xml.push_str(&format!(
+            "    <title>{}</title>\n",
+            escape_xml(&entry_title(e))
+        ));
+        xml.push_str(&format!(
+            "    <updated>{}</updated>\n",
+            rfc3339(e.detected_at)
+        ));
+        xml.push_str(&format!("    <category term=\"{}\"/>\n", e.kind.as_str()));
+        xml.push_str(&format!(
+            "    <summary>{}</summary>\n",
+            escape_xml(&format!("{} (source: {})", e.detail, e.source_url))
+        ));
+        xml.push_str("  </entry>\n");
+    }
+    xml.push_str("</feed>\n");
+
+    Ok((
+        StatusCode::OK,
+        [("content-type", "application/atom+xml")],
+        xml,
+    )
+        .into_response())
+}
+
+fn entry_id(e: &WatcherEvent) -> String {
+    format!("{FEED_ID}:{}", e.id)
+}
+
+fn entry_title(e: &WatcherEvent) -> String {
+    let subject = e.did_id.as_deref().unwrap_or(&e.mnemonic);
+    format!("{}: {subject}", e.kind.as_str())
+}
+
+fn rfc3339(epoch: u64) -> String {
+    DateTime::<Utc>::from_timestamp(epoch as i64, 0)
+        .unwrap_or_default()
+        .to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
@@ -1,5 +1,7 @@
 mod did_public;
+mod events;
 pub mod health;
+mod status;
 mod sync;
 
 use axum::Router;
@@ -15,7 +17,12 @@ pub fn router() -> Router<AppState> {
         .route("/delete", post(sync::receive_delete))
         .layer(DefaultBodyLimit::max(256 * 1024)); // 256 KB
 
-    let api = Router::new().nest("/sync", sync_routes);
+    let api = Router::new()
+        .nest("/sync", sync_routes)
+        .route("/status/{*mnemonic}", get(status::get_status))
+        .route("/events", get(events::list_events))
+        .route("/events/feed.json", get(events::json_feed))
+        .route("/events/feed.atom", get(events::atom_feed));
 
     Router::new()
         .nest("/api", api)
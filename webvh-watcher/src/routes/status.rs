@@ -0,0 +1,49 @@
+//! Per-DID mirror status.
+//!
+//! `GET /api/status/{*mnemonic}` reports what the watcher holds for a DID
+//! and whether a source has ever offered a log that rewrites its history
+//! (see [`watcher_ops::apply_sync`]). Public, like the mirrored content
+//! itself; disabled DIDs are 404 here too so state is not leaked.
+
+use axum::Json;
+use axum::extract::{Path, State};
+use did_hosting_common::server::mnemonic::validate_mnemonic;
+use serde::Serialize;
+
+use crate::error::AppError;
+use crate::server::AppState;
+use crate::watcher_ops::{self, ForkInfo};
+
+#[derive(Debug, Serialize)]
+pub struct DidStatusResponse {
+    pub mnemonic: String,
+    pub did_id: Option<String>,
+    pub source_url: String,
+    pub updated_at: u64,
+    /// `true` once any source has offered a divergent log.
+    pub fork_detected: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fork: Option<ForkInfo>,
+}
+
+pub async fn get_status(
+    State(state): State<AppState>,
+    Path(mnemonic): Path<String>,
+) -> Result<Json<DidStatusResponse>, AppError> {
+    let mnemonic = mnemonic.trim_start_matches('/');
+    validate_mnemonic(mnemonic)?;
+
+    let record = watcher_ops::get_record(&state.dids_ks, mnemonic)
+        .await?
+        .filter(|r| !r.disabled)
+        .ok_or_else(|| AppError::NotFound(format!("DID not found: {mnemonic}")))?;
+
+    Ok(Json(DidStatusResponse {
+        mnemonic: record.mnemonic,
+        did_id: record.did_id,
+        source_url: record.source_url,
+        updated_at: record.updated_at,
+        fork_detected: record.fork.is_some(),
+        fork: record.fork,
+    }))
+}
@@ -4,10 +4,11 @@ use did_hosting_common::server::init;
 
 use crate::config::AppConfig;
 use crate::error::AppError;
+use crate::reconcile;
 use crate::routes;
 use crate::store::{KeyspaceHandle, Store};
 use axum::routing::get;
-use did_hosting_common::server::store::KS_DIDS;
+use did_hosting_common::server::store::{KS_DIDS, KS_WATCHER_EVENTS};
 use tokio::sync::watch;
 use tower_http::trace::{DefaultMakeSpan, DefaultOnResponse, TraceLayer};
 use tracing::{Level, error, info};
@@ -16,11 +17,13 @@ use tracing::{Level, error, info};
 pub struct AppState {
     pub store: Store,
     pub dids_ks: KeyspaceHandle,
+    pub events_ks: KeyspaceHandle,
     pub config: Arc<AppConfig>,
 }
 
 pub async fn run(config: AppConfig, store: Store) -> Result<(), AppError> {
     let dids_ks = store.keyspace(KS_DIDS)?;
+    let events_ks = store.keyspace(KS_WATCHER_EVENTS)?;
 
     let std_listener = {
         let addr = format!("{}:{}", config.server.host, config.server.port);
@@ -33,11 +36,15 @@ pub async fn run(config: AppConfig, store: Store) -> Result<(), AppError> {
     let state = AppState {
         store: store.clone(),
         dids_ks,
+        events_ks,
         config: Arc::new(config),
     };
 
     let (shutdown_tx, shutdown_rx) = watch::channel(false);
 
+    // Pull-based reconciliation against `sync.sources` (no-op without any).
+    let reconcile_handle = reconcile::spawn(state.clone(), shutdown_rx.clone());
+
     // REST thread
     let rest_state = state.clone();
     let mut rest_shutdown = shutdown_rx.clone();
@@ -121,6 +128,13 @@ pub async fn run(config: AppConfig, store: Store) -> Result<(), AppError> {
 
     let mut any_panic = false;
 
+    if let Some(handle) = reconcile_handle
+        && let Err(e) = handle.await
+    {
+        error!("reconcile task failed: {e}");
+        any_panic = true;
+    }
+
     match tokio::task::spawn_blocking(move || rest_handle.join()).await {
         Ok(Ok(())) => info!("REST thread stopped"),
         Ok(Err(_)) => {
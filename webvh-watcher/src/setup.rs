@@ -160,6 +160,7 @@ pub async fn run_wizard(config_path: Option<PathBuf>) -> Result<(), Box<dyn std:
             push_tokens,
             sources,
             reconcile_interval,
+            rollback_keys: Vec::new(),
         },
         config_path: output_path.clone(),
     };
@@ -270,6 +271,7 @@ pub async fn apply_recipe(
             push_tokens: recipe.watcher.push_tokens.clone(),
             sources,
             reconcile_interval: recipe.watcher.reconcile_interval,
+            rollback_keys: Vec::new(),
         },
         config_path: output_path.clone(),
     };
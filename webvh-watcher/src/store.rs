@@ -1 +1,5 @@
-pub use did_hosting_common::server::store::{KeyspaceHandle, Store};
+use did_hosting_common::server::store::{KS_DIDS, KS_WATCHER_EVENTS};
+pub use did_hosting_common::server::store::{KeyspaceHandle, Store, WriteBatch};
+
+/// Every keyspace the watcher owns: mirrored DIDs and tamper events.
+pub const KEYSPACES: &[&str] = &[KS_DIDS, KS_WATCHER_EVENTS];
@@ -1,7 +1,15 @@
+use did_hosting_common::SyncDidRequest;
+use did_hosting_common::server::auth::session::now_epoch;
+use did_hosting_common::server::encoding::{self, Encoding};
+use did_hosting_common::server::mnemonic::validate_mnemonic;
+use did_hosting_common::server::rollback_proof;
+use ed25519_dalek::VerifyingKey;
 use serde::{Deserialize, Serialize};
+use tracing::warn;
 
 use crate::error::AppError;
-use crate::store::KeyspaceHandle;
+use crate::events::{self, EventKind};
+use crate::store::{KeyspaceHandle, Store, WriteBatch};
 
 /// A mirrored DID record on the watcher.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,6 +19,61 @@ pub struct WatcherRecord {
     pub source_url: String,
     pub updated_at: u64,
     pub disabled: bool,
+    /// [`did_hosting_common::sync_head`] of the content held. Reconcile
+    /// compares it with the source's listing. `None` on records written
+    /// before it existed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head: Option<String>,
+    /// Set once a source offers a log that does not extend the one held.
+    /// Sticky: later valid extensions are still accepted, but the flag
+    /// stays so the divergence is not lost.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fork: Option<ForkInfo>,
+}
+
+/// A log the watcher refused because it rewrites history it already holds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForkInfo {
+    /// When the most recent divergent log was received (epoch seconds).
+    pub detected_at: u64,
+    /// `source_url` of the sync body that carried it.
+    pub source_url: String,
+    /// 1-based index of the first entry that differs from the held log.
+    pub diverges_at: usize,
+    /// Entries in the held (served) log.
+    pub held_entries: usize,
+    /// Entries in the divergent log, kept under [`content_fork_key`].
+    pub offered_entries: usize,
+}
+
+/// How an offered log relates to the one already held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogRelation {
+    /// Byte-identical entries.
+    Same,
+    /// Every held entry is present, in order, followed by new ones.
+    Extends,
+    /// Entry `at` (1-based) differs, or held entries are missing.
+    Diverges { at: usize },
+}
+
+/// What the watcher keeps of a DID after its source deleted it, so the
+/// mnemonic cannot be re-pushed with a history that does not extend the
+/// one it served.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tombstone {
+    /// Epoch seconds.
+    pub deleted_at: u64,
+    /// `source_url` of the delete.
+    pub source_url: String,
+    pub did_id: Option<String>,
+    /// Last entry of the log held when the DID was deleted.
+    pub head: String,
+    /// Entries in that log.
+    pub entries: usize,
+    /// Carried over from the record, and set by forks offered since.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fork: Option<ForkInfo>,
 }
 
 // ---------------------------------------------------------------------------
@@ -29,6 +92,149 @@ pub fn content_witness_key(mnemonic: &str) -> String {
     format!("content:{mnemonic}:witness")
 }
 
+pub fn content_fork_key(mnemonic: &str) -> String {
+    format!("content:{mnemonic}:fork")
+}
+
+/// Where [`Tombstone`] is kept. Outside `did:`, so deleted DIDs are not
+/// listed as held.
+pub fn tombstone_key(mnemonic: &str) -> String {
+    format!("tombstone:{mnemonic}")
+}
+
+/// Where the `encoding` variant of the content under `key` is kept
+/// (`content:{mnemonic}:log:br`).
+pub fn encoded_key(key: &str, encoding: Encoding) -> String {
+    format!("{key}:{}", encoding.token())
+}
+
+/// Served content with its compressed variants, ready to stage in a
+/// batch so public resolution never compresses per request.
+struct Content {
+    key: String,
+    bytes: Vec<u8>,
+    variants: Vec<(Encoding, Vec<u8>)>,
+}
+
+impl Content {
+    /// Compress `bytes` on the blocking pool. A failed compression only
+    /// costs the variants: the content is then served uncompressed.
+    async fn compress(key: String, bytes: Vec<u8>) -> Result<Self, AppError> {
+        tokio::task::spawn_blocking(move || {
+            let variants = encoding::precompress(&bytes).unwrap_or_else(|e| {
+                warn!(key = %key, error = %e, "failed to precompress content; serving it uncompressed");
+                Vec::new()
+            });
+            Self {
+                key,
+                bytes,
+                variants,
+            }
+        })
+        .await
+        .map_err(|e| AppError::Internal(format!("compression task failed: {e}")))
+    }
+
+    /// Write the content and its variants. Variants that are not worth
+    /// keeping (small bodies, no saving) are removed rather than left stale.
+    fn stage(self, batch: &mut WriteBatch, ks: &KeyspaceHandle) {
+        for encoding in Encoding::COMPRESSED {
+            let variant_key = encoded_key(&self.key, encoding);
+            match self.variants.iter().find(|(e, _)| *e == encoding) {
+                Some((_, bytes)) => batch.insert_raw(ks, variant_key, bytes.clone()),
+                None => batch.remove(ks, variant_key),
+            }
+        }
+        batch.insert_raw(ks, self.key, self.bytes);
+    }
+}
+
+/// Remove served content and its compressed variants.
+fn stage_content_removal(batch: &mut WriteBatch, ks: &KeyspaceHandle, key: String) {
+    for encoding in Encoding::COMPRESSED {
+        batch.remove(ks, encoded_key(&key, encoding));
+    }
+    batch.remove(ks, key);
+}
+
+fn log_entries(log: &str) -> Vec<&str> {
+    log.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect()
+}
+
+/// Compare an offered log against the held one, entry by entry.
+pub fn compare_logs(held: &str, offered: &str) -> LogRelation {
+    let held = log_entries(held);
+    let offered = log_entries(offered);
+    if let Some(idx) = held.iter().zip(&offered).position(|(h, o)| h != o) {
+        return LogRelation::Diverges { at: idx + 1 };
+    }
+    match offered.len().cmp(&held.len()) {
+        std::cmp::Ordering::Equal => LogRelation::Same,
+        std::cmp::Ordering::Greater => LogRelation::Extends,
+        std::cmp::Ordering::Less => LogRelation::Diverges {
+            at: offered.len() + 1,
+        },
+    }
+}
+
+/// Compare an offered log against a held one of which only the last entry
+/// and the entry count are kept. Each entry's `versionId` hashes the one
+/// before it, so a verified log carrying `head` at the same position
+/// carries the whole held history.
+pub fn compare_with_head(entries: usize, head: &str, offered: &str) -> LogRelation {
+    let offered = log_entries(offered);
+    match offered.get(entries.saturating_sub(1)) {
+        Some(entry) if *entry == head.trim() => {
+            if offered.len() == entries {
+                LogRelation::Same
+            } else {
+                LogRelation::Extends
+            }
+        }
+        Some(_) => LogRelation::Diverges { at: entries },
+        None => LogRelation::Diverges {
+            at: offered.len() + 1,
+        },
+    }
+}
+
+/// Whether `offered` is `held` with exactly the `rolled_back` entries taken
+/// off the end — a rollback the source declared rather than a rewrite.
+pub fn is_declared_rollback(held: &str, offered: &str, rolled_back: &[String]) -> bool {
+    if rolled_back.is_empty() {
+        return false;
+    }
+    let held = log_entries(held);
+    let offered = log_entries(offered);
+    held.len() == offered.len() + rolled_back.len()
+        && held[..offered.len()] == offered[..]
+        && held[offered.len()..]
+            .iter()
+            .zip(rolled_back)
+            .all(|(h, r)| *h == r.trim())
+}
+
+/// Whether the rollback `req` declares carries a proof signed by one of the
+/// `trusted` keys (see [`rollback_proof`]). Anyone with a push token can
+/// fill in `rolled_back`; only the proof shows the source meant it.
+pub fn is_authenticated_rollback(req: &SyncDidRequest, trusted: &[VerifyingKey]) -> bool {
+    let Some(proof) = &req.rollback_proof else {
+        warn!(mnemonic = %req.mnemonic, "declared rollback carries no proof");
+        return false;
+    };
+    let did = did_hosting_common::did_ops::extract_did_id(&req.log_content).unwrap_or_default();
+    match rollback_proof::verify(proof, &did, &req.log_content, &req.rolled_back, trusted) {
+        Ok(()) => true,
+        Err(e) => {
+            warn!(mnemonic = %req.mnemonic, error = %e, "rollback proof rejected");
+            false
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // CRUD operations
 // ---------------------------------------------------------------------------
@@ -37,6 +243,248 @@ pub async fn store_record(ks: &KeyspaceHandle, record: &WatcherRecord) -> Result
     ks.insert(did_key(&record.mnemonic), record).await
 }
 
+/// Validate a DID sync body and store it.
+///
+/// The one ingest path for mirrored content: `POST /api/sync/did` pushes
+/// and [`crate::reconcile`] pulls both land here.
+///
+/// The log must pass full proof verification and be a strict extension of
+/// any log already held, or of the [`Tombstone`] of one deleted since. A
+/// log that rewrites or truncates held history, or brings a deactivated DID
+/// back, is never served: it is kept under [`content_fork_key`] for
+/// inspection, the record (or tombstone) is flagged with [`ForkInfo`], and
+/// the call fails with `Conflict`. Each distinct divergence is also written
+/// to `events_ks` (see [`crate::events`]); a replaced witness proof is
+/// recorded there too, but does not block the sync.
+///
+/// The one truncation accepted is a rollback the source declares in
+/// `rolled_back` ([`is_declared_rollback`]) and signs with one of the
+/// `rollback_keys` ([`is_authenticated_rollback`]); it is recorded as
+/// [`EventKind::RolledBack`]. Without a valid proof it is a fork like any
+/// other, and rolling back a deactivation is a fork either way.
+pub async fn apply_sync(
+    store: &Store,
+    ks: &KeyspaceHandle,
+    events_ks: &KeyspaceHandle,
+    rollback_keys: &[VerifyingKey],
+    req: SyncDidRequest,
+) -> Result<(), AppError> {
+    // Validate mnemonic format to prevent store key injection
+    validate_mnemonic(&req.mnemonic)?;
+
+    // Verify the log end to end — structure, SCID, entry hashes and
+    // update-key proofs. A leaked push token must not let attackers
+    // republish bogus DID documents on the watcher's hostname.
+    if req.log_content.is_empty() {
+        return Err(AppError::Validation("log_content cannot be empty".into()));
+    }
+    did_hosting_common::did_ops::verify_did_log_proofs(&req.log_content).map_err(|e| {
+        warn!(mnemonic = %req.mnemonic, error = %e, "unverifiable WebVH log in sync");
+        AppError::Validation(format!("invalid WebVH log content: {e}"))
+    })?;
+
+    let existing = get_record(ks, &req.mnemonic).await?;
+    let held_log = ks
+        .get_raw(content_log_key(&req.mnemonic))
+        .await?
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned());
+    let tombstone = match held_log {
+        Some(_) => None,
+        None => get_tombstone(ks, &req.mnemonic).await?,
+    };
+
+    // (relation, held entries, held log deactivated, authenticated rollback)
+    let baseline = match (&held_log, &tombstone) {
+        (Some(held), _) => Some((
+            compare_logs(held, &req.log_content),
+            log_entries(held).len(),
+            events::log_is_deactivated(held),
+            is_declared_rollback(held, &req.log_content, &req.rolled_back)
+                && is_authenticated_rollback(&req, rollback_keys),
+        )),
+        (None, Some(t)) => Some((
+            compare_with_head(t.entries, &t.head, &req.log_content),
+            t.entries,
+            events::log_is_deactivated(&t.head),
+            false,
+        )),
+        (None, None) => None,
+    };
+
+    if let Some((relation, held_entries, held_deactivated, declared)) = baseline {
+        let offered_entries = log_entries(&req.log_content).len();
+        let reversal = held_deactivated && !events::log_is_deactivated(&req.log_content);
+
+        let divergence = match relation {
+            LogRelation::Same | LogRelation::Extends => None,
+            LogRelation::Diverges { at } if at > offered_entries && declared && !reversal => {
+                events::record_event(
+                    events_ks,
+                    EventKind::RolledBack,
+                    &req.mnemonic,
+                    req.did_id.as_deref(),
+                    &req.source_url,
+                    format!("source rolled back {held_entries} entries to {offered_entries}"),
+                )
+                .await?;
+                None
+            }
+            LogRelation::Diverges { at } if at > offered_entries => Some((
+                at,
+                EventKind::EntriesRemoved,
+                format!("held log has {held_entries} entries, offered log only {offered_entries}"),
+            )),
+            LogRelation::Diverges { at } => Some((
+                at,
+                EventKind::HistoryRewritten,
+                format!("entry {at} differs from the held log"),
+            )),
+        };
+
+        if divergence.is_some() || reversal {
+            let at = divergence
+                .as_ref()
+                .map_or(held_entries + 1, |(at, _, _)| *at);
+            warn!(
+                mnemonic = %req.mnemonic,
+                source = %req.source_url,
+                diverges_at = at,
+                deleted = tombstone.is_some(),
+                "fork detected: sync log does not extend the held log"
+            );
+
+            // The same divergent log offered again (e.g. on every reconcile
+            // pass) is one incident, not many.
+            let fork_key = content_fork_key(&req.mnemonic);
+            let repeat =
+                ks.get_raw(fork_key.clone()).await?.as_deref() == Some(req.log_content.as_bytes());
+            if !repeat {
+                let did_id = req.did_id.as_deref();
+                if let Some((_, kind, detail)) = divergence {
+                    events::record_event(
+                        events_ks,
+                        kind,
+                        &req.mnemonic,
+                        did_id,
+                        &req.source_url,
+                        detail,
+                    )
+                    .await?;
+                }
+                if reversal {
+                    events::record_event(
+                        events_ks,
+                        EventKind::DeactivationReversed,
+                        &req.mnemonic,
+                        did_id,
+                        &req.source_url,
+                        "held log is deactivated; offered log is not".into(),
+                    )
+                    .await?;
+                }
+                ks.insert_raw(fork_key, req.log_content.into_bytes())
+                    .await?;
+            }
+
+            let fork = ForkInfo {
+                detected_at: now_epoch(),
+                source_url: req.source_url.clone(),
+                diverges_at: at,
+                held_entries,
+                offered_entries,
+            };
+            // A deleted DID stays deleted: the fork goes on its tombstone.
+            if let Some(mut tombstone) = tombstone {
+                if !repeat || tombstone.fork.is_none() {
+                    tombstone.fork = Some(fork);
+                    ks.insert(tombstone_key(&req.mnemonic), &tombstone).await?;
+                }
+            } else {
+                let mut record = existing.unwrap_or_else(|| WatcherRecord {
+                    mnemonic: req.mnemonic.clone(),
+                    did_id: req.did_id.clone(),
+                    source_url: req.source_url.clone(),
+                    updated_at: req.updated_at,
+                    disabled: req.disabled,
+                    head: None,
+                    fork: None,
+                });
+                if !repeat || record.fork.is_none() {
+                    record.fork = Some(fork);
+                    store_record(ks, &record).await?;
+                }
+            }
+
+            return Err(AppError::Conflict(format!(
+                "log for {} diverges from the held log at entry {at}",
+                req.mnemonic
+            )));
+        }
+    }
+
+    if let Some(witness) = &req.witness_content
+        && let Some(held) = ks.get_raw(content_witness_key(&req.mnemonic)).await?
+    {
+        let lost = events::lost_witness_proofs(
+            &String::from_utf8_lossy(&held),
+            witness,
+            log_entries(&req.log_content).len() as u64,
+        );
+        if !lost.is_empty() {
+            let detail = lost
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            events::record_event(
+                events_ks,
+                EventKind::WitnessProofsChanged,
+                &req.mnemonic,
+                req.did_id.as_deref(),
+                &req.source_url,
+                format!("witness proofs lost: {detail}"),
+            )
+            .await?;
+        }
+    }
+
+    let record = WatcherRecord {
+        head: Some(did_hosting_common::sync_head(
+            req.log_content.as_bytes(),
+            req.witness_content.as_deref().map(str::as_bytes),
+        )),
+        mnemonic: req.mnemonic.clone(),
+        did_id: req.did_id,
+        source_url: req.source_url,
+        updated_at: req.updated_at,
+        disabled: req.disabled,
+        fork: existing
+            .and_then(|r| r.fork)
+            .or_else(|| tombstone.and_then(|t| t.fork)),
+    };
+
+    let log =
+        Content::compress(content_log_key(&req.mnemonic), req.log_content.into_bytes()).await?;
+    let witness = match req.witness_content {
+        Some(witness) => {
+            Some(Content::compress(content_witness_key(&req.mnemonic), witness.into_bytes()).await?)
+        }
+        None => None,
+    };
+
+    // Record, content and variants land together, so a reader never sees
+    // a record without its log or a variant of an older log.
+    let mut batch = store.batch();
+    batch.insert(ks, did_key(&record.mnemonic), &record)?;
+    log.stage(&mut batch, ks);
+    if let Some(witness) = witness {
+        witness.stage(&mut batch, ks);
+    }
+    // Served again: the held log is the baseline from here on.
+    batch.remove(ks, tombstone_key(&req.mnemonic));
+    batch.commit().await
+}
+
 pub async fn get_record(
     ks: &KeyspaceHandle,
     mnemonic: &str,
@@ -44,11 +492,43 @@ pub async fn get_record(
     ks.get(did_key(mnemonic)).await
 }
 
-pub async fn delete_record(ks: &KeyspaceHandle, mnemonic: &str) -> Result<(), AppError> {
-    ks.remove(did_key(mnemonic)).await?;
-    ks.remove(content_log_key(mnemonic)).await?;
-    ks.remove(content_witness_key(mnemonic)).await?;
-    Ok(())
+pub async fn get_tombstone(
+    ks: &KeyspaceHandle,
+    mnemonic: &str,
+) -> Result<Option<Tombstone>, AppError> {
+    ks.get(tombstone_key(mnemonic)).await
+}
+
+/// Stop serving a DID. Leaves a [`Tombstone`] of the held log, and keeps
+/// any divergent log under [`content_fork_key`], so a later push under the
+/// same mnemonic is still checked against the history that was served.
+pub async fn delete_record(
+    store: &Store,
+    ks: &KeyspaceHandle,
+    mnemonic: &str,
+    source_url: &str,
+) -> Result<(), AppError> {
+    let mut batch = store.batch();
+    if let Some(held) = ks.get_raw(content_log_key(mnemonic)).await? {
+        let held = String::from_utf8_lossy(&held);
+        let entries = log_entries(&held);
+        if let Some(head) = entries.last() {
+            let record = get_record(ks, mnemonic).await?;
+            let tombstone = Tombstone {
+                deleted_at: now_epoch(),
+                source_url: source_url.to_string(),
+                did_id: record.as_ref().and_then(|r| r.did_id.clone()),
+                head: head.to_string(),
+                entries: entries.len(),
+                fork: record.and_then(|r| r.fork),
+            };
+            batch.insert(ks, tombstone_key(mnemonic), &tombstone)?;
+        }
+    }
+    batch.remove(ks, did_key(mnemonic));
+    stage_content_removal(&mut batch, ks, content_log_key(mnemonic));
+    stage_content_removal(&mut batch, ks, content_witness_key(mnemonic));
+    batch.commit().await
 }
 
 pub async fn list_records(ks: &KeyspaceHandle) -> Result<Vec<WatcherRecord>, AppError> {
@@ -61,3 +541,180 @@ pub async fn list_records(ks: &KeyspaceHandle) -> Result<Vec<WatcherRecord>, App
     }
     Ok(records)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_logs_are_same() {
+        assert_eq!(compare_logs("a\nb\n", "a\nb"), LogRelation::Same);
+    }
+
+    #[test]
+    fn appended_entries_extend() {
+        assert_eq!(compare_logs("a\nb\n", "a\nb\nc\n"), LogRelation::Extends);
+    }
+
+    #[test]
+    fn rewritten_entry_diverges() {
+        assert_eq!(
+            compare_logs("a\nb\nc\n", "a\nx\nc\nd\n"),
+            LogRelation::Diverges { at: 2 }
+        );
+    }
+
+    #[test]
+    fn head_comparison_matches_full_comparison() {
+        assert_eq!(compare_with_head(2, "b", "a\nb\n"), LogRelation::Same);
+        assert_eq!(compare_with_head(2, "b", "a\nb\nc\n"), LogRelation::Extends);
+        assert_eq!(
+            compare_with_head(2, "b", "a\nx\nc\n"),
+            LogRelation::Diverges { at: 2 }
+        );
+        assert_eq!(
+            compare_with_head(3, "c", "a\n"),
+            LogRelation::Diverges { at: 2 }
+        );
+    }
+
+    #[test]
+    fn only_the_declared_entries_may_be_rolled_back() {
+        let held = "a\nb\nc\n";
+        let declared = ["b".to_string(), "c".to_string()];
+        assert!(is_declared_rollback(held, "a\n", &declared));
+        assert!(!is_declared_rollback(held, "a\n", &declared[1..]));
+        assert!(!is_declared_rollback(held, "a\nb\n", &declared));
+        assert!(!is_declared_rollback(held, "x\n", &declared));
+        assert!(!is_declared_rollback(held, "a\nb\n", &[]));
+    }
+
+    #[test]
+    fn truncated_log_diverges() {
+        assert_eq!(
+            compare_logs("a\nb\nc\n", "a\n"),
+            LogRelation::Diverges { at: 2 }
+        );
+    }
+    /// A genesis log and the same log with a second, signed entry.
+    async fn two_version_log() -> (String, String) {
+        use affinidi_tdk::secrets_resolver::secrets::Secret;
+        use did_hosting_common::did::{DidDocumentOptions, build_did_document};
+        use didwebvh_rs::DIDWebVHState;
+        use didwebvh_rs::parameters::Parameters;
+        use std::sync::Arc;
+
+        let mut signing = Secret::generate_ed25519(None, None);
+        let public_key = signing.get_public_keymultibase().unwrap();
+        signing.id = format!("did:key:{public_key}#{public_key}");
+        let doc = build_did_document(
+            "example.com",
+            "people/alice",
+            &public_key,
+            &DidDocumentOptions::default(),
+        );
+        let params = Parameters {
+            update_keys: Some(Arc::new(vec![public_key.clone().into()])),
+            ..Default::default()
+        };
+        let mut webvh = DIDWebVHState::default();
+        webvh
+            .create_log_entry(
+                Some((chrono::Utc::now() - chrono::Duration::minutes(1)).fixed_offset()),
+                &doc,
+                &params,
+                &signing,
+            )
+            .await
+            .unwrap();
+        let jsonl = |webvh: &DIDWebVHState| {
+            webvh
+                .log_entries()
+                .iter()
+                .map(|e| serde_json::to_string(&e.log_entry).unwrap())
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        let genesis = jsonl(&webvh);
+
+        let last = webvh.log_entries().last().unwrap();
+        let mut updated = last.get_state().clone();
+        updated["alsoKnownAs"] = serde_json::json!(["https://example.com/alias"]);
+        let mut params = last.validated_parameters.clone();
+        params.update_keys = Some(Arc::new(vec![public_key.into()]));
+        webvh
+            .create_log_entry(None, &updated, &params, &signing)
+            .await
+            .unwrap();
+        (genesis, jsonl(&webvh))
+    }
+
+    fn sync_request(log: &str) -> SyncDidRequest {
+        SyncDidRequest {
+            mnemonic: "alice".into(),
+            did_id: did_hosting_common::did_ops::extract_did_id(log),
+            log_content: log.into(),
+            witness_content: None,
+            source_url: "https://source.example".into(),
+            updated_at: now_epoch(),
+            disabled: false,
+            rolled_back: Vec::new(),
+            rollback_proof: None,
+        }
+    }
+
+    /// `rolled_back` alone does not make a truncation a rollback: without a
+    /// proof from a trusted key it is a fork, and the held log stays served.
+    #[tokio::test]
+    async fn rollback_without_a_trusted_proof_is_a_fork() {
+        use did_hosting_common::server::config::StoreConfig;
+        use did_hosting_common::server::store::{KS_DIDS, KS_WATCHER_EVENTS};
+        use ed25519_dalek::SigningKey;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = Store::open(&StoreConfig {
+            data_dir: dir.path().to_path_buf(),
+            ..StoreConfig::default()
+        })
+        .await
+        .unwrap();
+        let ks = store.keyspace(KS_DIDS).unwrap();
+        let events_ks = store.keyspace(KS_WATCHER_EVENTS).unwrap();
+        let source = SigningKey::from_bytes(&[5u8; 32]);
+        let trusted = [source.verifying_key()];
+
+        let (genesis, updated) = two_version_log().await;
+        apply_sync(&store, &ks, &events_ks, &trusted, sync_request(&updated))
+            .await
+            .unwrap();
+
+        let dropped = vec![log_entries(&updated)[1].to_string()];
+        let did = did_hosting_common::did_ops::extract_did_id(&genesis).unwrap();
+        let stranger = SigningKey::from_bytes(&[6u8; 32]);
+        for proof in [
+            None,
+            rollback_proof::sign(&did, &genesis, &dropped, &stranger),
+        ] {
+            let mut req = sync_request(&genesis);
+            req.rolled_back = dropped.clone();
+            req.rollback_proof = proof;
+            let result = apply_sync(&store, &ks, &events_ks, &trusted, req).await;
+            assert!(matches!(result, Err(AppError::Conflict(_))));
+        }
+
+        let record = get_record(&ks, "alice").await.unwrap().unwrap();
+        let fork = record.fork.expect("unauthenticated rollback flags a fork");
+        assert_eq!((fork.held_entries, fork.offered_entries), (2, 1));
+        let served = ks.get_raw(content_log_key("alice")).await.unwrap().unwrap();
+        assert_eq!(served, updated.as_bytes());
+
+        let mut req = sync_request(&genesis);
+        req.rolled_back = dropped.clone();
+        req.rollback_proof = rollback_proof::sign(&did, &genesis, &dropped, &source);
+        apply_sync(&store, &ks, &events_ks, &trusted, req)
+            .await
+            .unwrap();
+        let served = ks.get_raw(content_log_key("alice")).await.unwrap().unwrap();
+        assert_eq!(served, genesis.as_bytes());
+    }
+}
@@ -58,6 +58,7 @@ pub async fn reload_now(state: &AppState) -> Result<(), AppError> {
             tsp: false,
         },
         state.config.identity.rotation_grace_secs(),
+        None,
     )
     .await?;
 
@@ -15,6 +15,7 @@
 
 pub mod acl;
 pub mod auth;
+pub mod backup;
 pub mod config;
 pub mod error;
 pub mod health;
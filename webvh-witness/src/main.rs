@@ -1,8 +1,12 @@
 use clap::{Parser, Subcommand};
+use did_hosting_common::server::backup::{BackupRequest, KeyspaceSelection, RestoreRequest};
 use did_hosting_common::server::store::KS_WITNESSES;
+use did_hosting_common::server::store_migrate::{self, MigrateRequest};
 use std::path::PathBuf;
 use webvh_witness::config::AppConfig;
-use webvh_witness::{health, secret_store, server, setup, setup_recipe, store, witness_ops};
+use webvh_witness::{
+    backup, health, secret_store, server, setup, setup_recipe, store, witness_ops,
+};
 
 #[derive(Parser)]
 #[command(name = "webvh-witness", about = "WebVH Witness Node", version)]
@@ -88,6 +92,85 @@ enum Command {
         #[arg(long)]
         id: String,
     },
+    /// Export witness data to a backup file.
+    ///
+    /// The service must be stopped (the store is exclusively locked).
+    Backup {
+        /// Output file path (use "-" for stdout)
+        #[arg(short, long, default_value = "witness-backup.jsonl")]
+        output: String,
+        /// Back up only these keyspaces (repeatable or comma-separated).
+        #[arg(long = "keyspace", value_delimiter = ',')]
+        keyspaces: Vec<String>,
+        /// Leave these keyspaces out.
+        #[arg(long = "exclude-keyspace", value_delimiter = ',')]
+        exclude_keyspaces: Vec<String>,
+        /// Encrypt to this age X25519 recipient (`age1…`; repeatable). The
+        /// backup can then only be read with the matching identity file.
+        #[arg(long = "recipient")]
+        recipients: Vec<String>,
+        /// Incremental: write only what changed or was deleted since this
+        /// earlier backup (full or incremental).
+        #[arg(long)]
+        incremental_from: Option<PathBuf>,
+        /// age identity file, to read an encrypted `--incremental-from`.
+        #[arg(long = "identity")]
+        identities: Vec<PathBuf>,
+    },
+    /// Restore witness data and config.toml from a backup file.
+    ///
+    /// The service must be stopped (the store is exclusively locked).
+    Restore {
+        /// Input backup file path
+        #[arg(short, long)]
+        input: String,
+        /// Restore only these keyspaces (repeatable or comma-separated).
+        #[arg(long = "keyspace", value_delimiter = ',')]
+        keyspaces: Vec<String>,
+        /// Leave these keyspaces out.
+        #[arg(long = "exclude-keyspace", value_delimiter = ',')]
+        exclude_keyspaces: Vec<String>,
+        /// age identity file, for an encrypted backup (repeatable).
+        #[arg(long = "identity")]
+        identities: Vec<PathBuf>,
+        /// Report what would be added, overwritten and kept, per keyspace,
+        /// without writing the config or the store.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Check a backup file's digest and entries without restoring it
+    VerifyBackup {
+        /// Input backup file path
+        #[arg(short, long)]
+        input: String,
+        /// age identity file, for an encrypted backup (repeatable).
+        #[arg(long = "identity")]
+        identities: Vec<PathBuf>,
+    },
+    /// Copy every keyspace into another storage backend.
+    ///
+    /// Stop the service first. Both backends' `store-*` features must be
+    /// compiled into this binary.
+    StoreMigrate {
+        /// Config file whose [store] is the source
+        #[arg(long)]
+        from: PathBuf,
+        /// Config file whose [store] is the target
+        #[arg(long)]
+        to: PathBuf,
+        /// Progress file; an interrupted run resumes from it
+        #[arg(long, default_value = "store-migrate.checkpoint")]
+        checkpoint: PathBuf,
+        /// Migrate only these keyspaces (repeatable or comma-separated).
+        #[arg(long = "keyspace", value_delimiter = ',')]
+        keyspaces: Vec<String>,
+        /// Leave these keyspaces out.
+        #[arg(long = "exclude-keyspace", value_delimiter = ',')]
+        exclude_keyspaces: Vec<String>,
+        /// Compare the two stores without copying anything
+        #[arg(long)]
+        verify_only: bool,
+    },
     /// Step 1/2 of the offline (air-gapped VTA) setup wizard.
     ///
     /// Runs the interactive prompts, writes the bootstrap-request.json +
@@ -310,6 +393,82 @@ async fn main() {
                 std::process::exit(1);
             }
         }
+        Some(Command::Backup {
+            output,
+            keyspaces,
+            exclude_keyspaces,
+            recipients,
+            incremental_from,
+            identities,
+        }) => {
+            let req = BackupRequest {
+                output,
+                selection: KeyspaceSelection {
+                    only: keyspaces,
+                    exclude: exclude_keyspaces,
+                },
+                recipients,
+                incremental_from,
+                identities,
+            };
+            if let Err(e) = backup::run_backup(cli.config, req).await {
+                eprintln!("Backup error: {e}");
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Restore {
+            input,
+            keyspaces,
+            exclude_keyspaces,
+            identities,
+            dry_run,
+        }) => {
+            let req = RestoreRequest {
+                input,
+                selection: KeyspaceSelection {
+                    only: keyspaces,
+                    exclude: exclude_keyspaces,
+                },
+                identities,
+                dry_run,
+            };
+            if let Err(e) = backup::run_restore(cli.config, req).await {
+                eprintln!("Restore error: {e}");
+                std::process::exit(1);
+            }
+        }
+        Some(Command::VerifyBackup { input, identities }) => {
+            if let Err(e) = backup::run_verify_backup(input, identities) {
+                eprintln!("Backup verification failed: {e}");
+                std::process::exit(1);
+            }
+        }
+        Some(Command::StoreMigrate {
+            from,
+            to,
+            checkpoint,
+            keyspaces,
+            exclude_keyspaces,
+            verify_only,
+        }) => {
+            let req = MigrateRequest {
+                from,
+                to,
+                checkpoint,
+                selection: KeyspaceSelection {
+                    only: keyspaces,
+                    exclude: exclude_keyspaces,
+                },
+                verify_only,
+            };
+            if let Err(e) =
+                store_migrate::run_store_migrate(&req, "store", backup::KEYSPACES, Some("WITNESS"))
+                    .await
+            {
+                eprintln!("Store migration error: {e}");
+                std::process::exit(1);
+            }
+        }
         Some(Command::SetupOfflinePrepare { request, state }) => {
             if let Err(e) = setup::run_setup_offline_prepare(cli.config, request, state).await {
                 eprintln!("Setup error: {e}");
@@ -75,7 +75,7 @@ async fn handle_proof_request(
     let sender = require_sender(&ctx)?;
 
     let (response_type, response_body) = match check_acl(&state.acl_ks, sender).await {
-        Ok(_) => match do_proof_request(&state, &message).await {
+        Ok(_) => match do_proof_request(&state, &message, sender).await {
             Ok(r) => r,
             Err(e) => error_response(&e),
         },
@@ -175,24 +175,34 @@ async fn do_authenticate(state: &AppState, sender_base: &str) -> Result<(String,
     ))
 }
 
-async fn do_proof_request(state: &AppState, msg: &Message) -> Result<(String, Value), AppError> {
+async fn do_proof_request(
+    state: &AppState,
+    msg: &Message,
+    sender: &str,
+) -> Result<(String, Value), AppError> {
     let witness_id = msg
         .body
         .get("witness_id")
         .and_then(|v| v.as_str())
         .ok_or_else(|| AppError::Validation("missing witness_id".into()))?;
 
-    let version_id = msg
+    let log_entry = msg
         .body
-        .get("version_id")
+        .get("log_entry")
         .and_then(|v| v.as_str())
-        .ok_or_else(|| AppError::Validation("missing version_id".into()))?;
+        .ok_or_else(|| AppError::Validation("missing log_entry".into()))?;
+
+    let previous_log = msg.body.get("previous_log").and_then(|v| v.as_str());
+    let expected_version_id = msg.body.get("version_id").and_then(|v| v.as_str());
 
     let (version_id, proof) = crate::witness_ops::sign_witness_proof(
         &state.witnesses_ks,
         state.signer.as_ref(),
         witness_id,
-        version_id,
+        log_entry,
+        previous_log,
+        expected_version_id,
+        sender,
     )
     .await?;
 
@@ -5,7 +5,7 @@ use tracing::{info, warn};
 
 use crate::acl::{AclEntry, delete_acl_entry, get_acl_entry, list_acl_entries, store_acl_entry};
 use crate::auth::AdminAuth;
-use crate::auth::session::now_epoch;
+use crate::auth::session::{acl_change_revokes_sessions, now_epoch, revoke_sessions_for_did};
 use crate::error::AppError;
 use crate::server::AppState;
 use did_hosting_common::server::acl::{
@@ -65,6 +65,7 @@ pub async fn update_acl(
     let mut entry = get_acl_entry(&state.acl_ks, &did)
         .await?
         .ok_or_else(|| AppError::NotFound(format!("ACL entry not found: {did}")))?;
+    let before_role = entry.role.clone();
 
     if let Some(role) = req.role {
         entry.role = role;
@@ -80,6 +81,9 @@ pub async fn update_acl(
     }
 
     store_acl_entry(&state.acl_ks, &entry).await?;
+    if acl_change_revokes_sessions(&before_role, Some(&entry.role)) {
+        revoke_sessions_for_did(&state.sessions_ks, &entry.did).await?;
+    }
 
     info!(caller = %auth.0.did, did = %entry.did, role = %entry.role, "ACL entry updated");
     Ok(Json(AclEntryResponse::from(entry)))
@@ -104,6 +108,8 @@ pub async fn delete_acl(
         .ok_or_else(|| AppError::NotFound(format!("ACL entry not found: {did}")))?;
 
     delete_acl_entry(&state.acl_ks, &did).await?;
+    // Issued tokens would otherwise stay valid until they expire.
+    revoke_sessions_for_did(&state.sessions_ks, &did).await?;
 
     info!(caller = %auth.0.did, did = %did, "ACL entry deleted");
     Ok(StatusCode::NO_CONTENT)
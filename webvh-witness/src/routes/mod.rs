@@ -20,6 +20,7 @@ pub fn router() -> Router<AppState> {
         .route("/witnesses", get(witness::list_witnesses))
         .route("/witnesses/{witness_id}", get(witness::get_witness))
         .route("/witnesses/{witness_id}", delete(witness::delete_witness))
+        .route("/witnesses/{witness_id}/proofs", get(witness::list_proofs))
         // Proof signing (any authenticated user)
         .route("/proof/{witness_id}", post(witness::sign_proof))
         // ACL (admin)
@@ -1,5 +1,5 @@
 use axum::Json;
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
 use serde::{Deserialize, Serialize};
 
@@ -39,9 +39,22 @@ pub struct WitnessListResponse {
     pub witnesses: Vec<WitnessResponse>,
 }
 
+/// Body of `POST /proof/{witness_id}`.
+///
+/// Field names are snake_case; the camelCase spellings the
+/// `did-hosting-common` client sends are accepted as aliases.
 #[derive(Deserialize)]
 pub struct SignProofRequest {
-    pub version_id: String,
+    /// The log entry to witness — one did.jsonl line.
+    #[serde(alias = "logEntry")]
+    pub log_entry: String,
+    /// Every log entry before `log_entry`, from genesis. Omit for a
+    /// genesis entry.
+    #[serde(default, alias = "previousLog")]
+    pub previous_log: Option<String>,
+    /// Optional cross-check: must equal `log_entry`'s `versionId`.
+    #[serde(default, alias = "versionId")]
+    pub version_id: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -50,6 +63,20 @@ pub struct SignProofResponse {
     pub proof: serde_json::Value,
 }
 
+/// Query string of `GET /witnesses/{witness_id}/proofs`. `since` / `until`
+/// are inclusive Unix-second bounds on `signed_at`.
+#[derive(Deserialize)]
+pub struct ListProofsQuery {
+    pub did: Option<String>,
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct ProofListResponse {
+    pub proofs: Vec<witness_ops::ProofRecord>,
+}
+
 pub async fn create_witness(
     _auth: AdminAuth,
     State(state): State<AppState>,
@@ -111,7 +138,10 @@ pub async fn sign_proof(
         &state.witnesses_ks,
         state.signer.as_ref(),
         &witness_id,
-        &req.version_id,
+        &req.log_entry,
+        req.previous_log.as_deref(),
+        req.version_id.as_deref(),
+        &auth.0.did,
     )
     .await?;
 
@@ -131,3 +161,32 @@ pub async fn sign_proof(
         proof: proof_json,
     }))
 }
+
+pub async fn list_proofs(
+    auth: AdminAuth,
+    State(state): State<AppState>,
+    Path(witness_id): Path<String>,
+    Query(query): Query<ListProofsQuery>,
+) -> Result<Json<ProofListResponse>, AppError> {
+    // Deleted witnesses keep their ledger, so an unknown witness_id is not
+    // an error here — it just lists whatever the ledger still holds.
+    let proofs = witness_ops::list_proofs(
+        &state.witnesses_ks,
+        &witness_id,
+        &witness_ops::ProofFilter {
+            did: query.did.as_deref(),
+            since: query.since,
+            until: query.until,
+        },
+    )
+    .await?;
+
+    tracing::info!(
+        admin_did = %auth.0.did,
+        witness_id,
+        count = proofs.len(),
+        "witness proofs listed",
+    );
+
+    Ok(Json(ProofListResponse { proofs }))
+}
@@ -1,5 +1,7 @@
 use affinidi_data_integrity::DataIntegrityProof;
 use affinidi_tdk::secrets_resolver::secrets::Secret;
+use did_hosting_common::did_ops::{extract_did_id, verify_did_log_proofs};
+use did_hosting_common::server::error::ValidationKind;
 use serde::{Deserialize, Serialize};
 
 use crate::error::AppError;
@@ -119,17 +121,98 @@ pub async fn delete_witness(
     Ok(())
 }
 
-/// Sign a witness proof for a given version ID.
+/// One proof the witness has issued — the signed-proof ledger entry.
+///
+/// Stored at `proof:{witness_id}:{scid}:{version_number}` in the witnesses
+/// keyspace, so a `(scid, version number)` pair has at most one entry per
+/// witness; that is what [`sign_witness_proof`]'s equivocation check relies
+/// on. Keying on the SCID rather than the DID keeps one slot per log when
+/// the DID moves to another domain. Ledger entries outlive their witness:
+/// [`delete_witness`] leaves them in place for audit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofRecord {
+    pub witness_id: String,
+    /// The DID whose log entry was witnessed (`state.id` of the entry).
+    pub did: String,
+    /// The log's SCID, shared by every DID the log has moved through.
+    pub scid: String,
+    pub version_id: String,
+    /// The entry hash half of `version_id`.
+    pub entry_hash: String,
+    /// Unix timestamp of the first signature over this entry.
+    pub signed_at: u64,
+    /// DID of the caller that requested the proof.
+    pub requester: String,
+    /// The `DataIntegrityProof` as issued.
+    pub proof: serde_json::Value,
+}
+
+/// A log entry that passed [`verify_candidate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedEntry {
+    pub did: String,
+    pub scid: String,
+    pub version_id: String,
+    pub version_number: u64,
+    pub entry_hash: String,
+}
+
+/// Filters for [`list_proofs`]. Time bounds are inclusive Unix seconds.
+#[derive(Debug, Default, Clone)]
+pub struct ProofFilter<'a> {
+    pub did: Option<&'a str>,
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+}
+
+/// Serialises the equivocation check with the ledger write: two
+/// concurrent requests for the same `(scid, version)` must not both see an
+/// empty slot. Process-local, like the fjall backend's own locking — the
+/// witness runs as a single replica.
+static SIGN_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+fn proof_key(witness_id: &str, scid: &str, version_number: u64) -> String {
+    format!("proof:{witness_id}:{scid}:{version_number}")
+}
+
+/// The SCID of a `did:webvh:{scid}:…` identifier.
+fn scid_of(did: &str) -> Option<&str> {
+    did.strip_prefix("did:webvh:")?
+        .split(':')
+        .next()
+        .filter(|scid| !scid.is_empty())
+}
+
+/// Sign a witness proof for a candidate log entry.
+///
+/// The witness never signs a bare `versionId`: `log_entry` (the entry being
+/// witnessed, one JSONL line) is appended to `previous_log` (every entry
+/// before it, from genesis; `None` or empty for a genesis entry) and the
+/// combined log runs through [`verify_candidate`] first. Only a candidate
+/// that chains correctly gets a proof, and the proof covers the candidate's
+/// own `versionId`. `expected_version_id`, when the caller supplies one, must
+/// match it.
+///
+/// Every proof is recorded as a [`ProofRecord`]. A witness that has already
+/// signed a *different* entry hash for the same SCID and version number —
+/// under this DID or one the log had on another domain — refuses with
+/// [`AppError::Conflict`]; signing both would be equivocation.
+/// Re-requesting the same entry is allowed; the ledger keeps the first
+/// record.
 pub async fn sign_witness_proof(
     witnesses_ks: &KeyspaceHandle,
     signer: &dyn WitnessSigner,
     witness_id: &str,
-    version_id: &str,
+    log_entry: &str,
+    previous_log: Option<&str>,
+    expected_version_id: Option<&str>,
+    requester: &str,
 ) -> Result<(String, DataIntegrityProof), AppError> {
-    // Validate version_id format: <number>-<hash>
-    if !is_valid_version_id(version_id) {
+    if let Some(expected) = expected_version_id
+        && !is_valid_version_id(expected)
+    {
         return Err(AppError::Validation(format!(
-            "invalid version_id format: '{version_id}' (expected '<number>-<hash>')"
+            "invalid version_id format: '{expected}' (expected '<number>-<hash>')"
         )));
     }
 
@@ -137,14 +220,137 @@ pub async fn sign_witness_proof(
         .await?
         .ok_or_else(|| AppError::NotFound(format!("witness not found: {witness_id}")))?;
 
-    let proof = signer.sign_proof(&witness, version_id).await?;
+    let entry = verify_candidate(log_entry, previous_log)?;
+    if let Some(expected) = expected_version_id
+        && expected != entry.version_id
+    {
+        return Err(AppError::Validation(format!(
+            "version_id '{expected}' does not match the log entry's versionId '{}'",
+            entry.version_id
+        )));
+    }
+
+    let _guard = SIGN_LOCK.lock().await;
+
+    let key = proof_key(witness_id, &entry.scid, entry.version_number);
+    let existing = witnesses_ks.get::<ProofRecord>(key.clone()).await?;
+    if let Some(existing) = &existing
+        && existing.entry_hash != entry.entry_hash
+    {
+        return Err(AppError::Conflict(format!(
+            "witness {witness_id} already signed version {} of {} with entry hash {}; \
+             refusing to sign {}",
+            entry.version_number, existing.did, existing.entry_hash, entry.entry_hash
+        )));
+    }
+
+    let proof = signer.sign_proof(&witness, &entry.version_id).await?;
+
+    if existing.is_none() {
+        let record = ProofRecord {
+            witness_id: witness_id.to_string(),
+            did: entry.did.clone(),
+            scid: entry.scid.clone(),
+            version_id: entry.version_id.clone(),
+            entry_hash: entry.entry_hash.clone(),
+            signed_at: crate::auth::session::now_epoch(),
+            requester: requester.to_string(),
+            proof: serde_json::to_value(&proof)?,
+        };
+        witnesses_ks.insert(key, &record).await?;
+    }
 
     // Increment proof counter
     let mut updated = witness;
     updated.proofs_signed += 1;
     store_witness(witnesses_ks, &updated).await?;
 
-    Ok((version_id.to_string(), proof))
+    Ok((entry.version_id, proof))
+}
+
+/// List the proofs `witness_id` has issued, oldest first.
+pub async fn list_proofs(
+    witnesses_ks: &KeyspaceHandle,
+    witness_id: &str,
+    filter: &ProofFilter<'_>,
+) -> Result<Vec<ProofRecord>, AppError> {
+    // A DID narrows the scan to its log; the equality check below still
+    // matters because the log may have carried other DIDs.
+    let prefix = match filter.did.and_then(scid_of) {
+        Some(scid) => format!("proof:{witness_id}:{scid}:"),
+        None => format!("proof:{witness_id}:"),
+    };
+    let mut records = Vec::new();
+    for (_key, value) in witnesses_ks.prefix_iter_raw(prefix).await? {
+        let record: ProofRecord = serde_json::from_slice(&value)?;
+        if filter.did.is_some_and(|did| did != record.did)
+            || filter.since.is_some_and(|since| record.signed_at < since)
+            || filter.until.is_some_and(|until| record.signed_at > until)
+        {
+            continue;
+        }
+        records.push(record);
+    }
+    records.sort_by_key(|r| r.signed_at);
+    Ok(records)
+}
+
+/// Verify that `log_entry` is a valid next entry of `previous_log`.
+///
+/// Runs the full didwebvh-rs chain verification over the combined log
+/// ([`verify_did_log_proofs`]): SCID derivation, entry hashes, update-key
+/// authorisation and pre-rotation commitments. `previous_log` must
+/// therefore start at the genesis entry — a tail alone can't prove the
+/// SCID.
+pub fn verify_candidate(
+    log_entry: &str,
+    previous_log: Option<&str>,
+) -> Result<VerifiedEntry, AppError> {
+    let log_entry = log_entry.trim();
+    if log_entry.is_empty() {
+        return Err(AppError::Validation("log_entry cannot be empty".into()));
+    }
+    if log_entry.contains('\n') {
+        return Err(AppError::Validation(
+            "log_entry must be a single JSONL line".into(),
+        ));
+    }
+
+    let mut log = previous_log.unwrap_or_default().trim_end().to_string();
+    if !log.is_empty() {
+        log.push('\n');
+    }
+    log.push_str(log_entry);
+
+    verify_did_log_proofs(&log).map_err(|e| {
+        AppError::validation(
+            ValidationKind::InvalidLog,
+            format!("refusing to witness log entry: {e}"),
+        )
+    })?;
+
+    let did = extract_did_id(log_entry)
+        .ok_or_else(|| AppError::Validation("log entry has no state.id".into()))?;
+    let scid = scid_of(&did)
+        .ok_or_else(|| AppError::Validation(format!("'{did}' is not a did:webvh identifier")))?
+        .to_string();
+    let version_id = serde_json::from_str::<serde_json::Value>(log_entry)?
+        .get("versionId")
+        .and_then(|v| v.as_str())
+        .map(String::from)
+        .ok_or_else(|| AppError::Validation("log entry has no versionId".into()))?;
+    let (version_number, entry_hash) = version_id
+        .split_once('-')
+        .and_then(|(n, hash)| Some((n.parse().ok()?, hash.to_string())))
+        .ok_or_else(|| AppError::Validation(format!("malformed versionId '{version_id}'")))?;
+
+    Ok(VerifiedEntry {
+        did,
+        scid,
+        version_id,
+        version_number,
+        entry_hash,
+    })
 }
 
 /// Validate that a version_id matches the expected format: `<number>-<hash>`.
@@ -179,4 +385,225 @@ mod tests {
         assert!(!is_valid_version_id("-hash"));
         assert!(!is_valid_version_id("nohyphen"));
     }
+
+    /// A single-entry (genesis) did:webvh log signed by a fresh key.
+    async fn genesis_log() -> String {
+        use did_hosting_common::did::{DidDocumentOptions, build_did_document, create_log_entry};
+
+        let signing = Secret::generate_ed25519(None, None);
+        let public_key = signing.get_public_keymultibase().unwrap();
+        let doc = build_did_document(
+            "example.com",
+            "people/alice",
+            &public_key,
+            &DidDocumentOptions::default(),
+        );
+        create_log_entry(&doc, &signing).await.unwrap().1
+    }
+
+    #[tokio::test]
+    async fn verify_candidate_accepts_valid_genesis_entry() {
+        let log = genesis_log().await;
+        let entry = verify_candidate(&log, None).expect("genesis entry verifies");
+        assert_eq!(entry.version_number, 1);
+        assert_eq!(entry.version_id, format!("1-{}", entry.entry_hash));
+        assert!(entry.did.ends_with(":example.com:people:alice"));
+        assert_eq!(
+            entry.did,
+            format!("did:webvh:{}:example.com:people:alice", entry.scid)
+        );
+        // An empty previous log is the same as none.
+        assert_eq!(verify_candidate(&log, Some("")).unwrap(), entry);
+    }
+
+    #[tokio::test]
+    async fn verify_candidate_rejects_tampered_entry() {
+        let log = genesis_log().await;
+        let tampered = log.replace("people:alice", "people:mallory");
+        assert!(matches!(
+            verify_candidate(&tampered, None),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn verify_candidate_rejects_entry_that_does_not_chain() {
+        // Re-submitting the genesis entry on top of itself is not a valid
+        // second entry.
+        let log = genesis_log().await;
+        assert!(matches!(
+            verify_candidate(&log, Some(&log)),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    async fn test_witnesses_ks() -> (KeyspaceHandle, tempfile::TempDir) {
+        use did_hosting_common::server::config::StoreConfig;
+        use did_hosting_common::server::store::{KS_WITNESSES, Store};
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = Store::open(&StoreConfig {
+            data_dir: dir.path().to_path_buf(),
+            ..StoreConfig::default()
+        })
+        .await
+        .unwrap();
+        (store.keyspace(KS_WITNESSES).unwrap(), dir)
+    }
+
+    #[tokio::test]
+    async fn signing_records_proof_in_ledger() {
+        let (ks, _dir) = test_witnesses_ks().await;
+        let witness = create_witness(&ks, None).await.unwrap();
+        let log = genesis_log().await;
+        let entry = verify_candidate(&log, None).unwrap();
+
+        let signer = crate::signing::LocalSigner;
+        for _ in 0..2 {
+            sign_witness_proof(
+                &ks,
+                &signer,
+                &witness.witness_id,
+                &log,
+                None,
+                None,
+                "did:example:a",
+            )
+            .await
+            .expect("same entry may be re-signed");
+        }
+
+        let all = list_proofs(&ks, &witness.witness_id, &ProofFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 1, "re-signing keeps the first ledger record");
+        assert_eq!(all[0].did, entry.did);
+        assert_eq!(all[0].version_id, entry.version_id);
+        assert_eq!(all[0].entry_hash, entry.entry_hash);
+        assert_eq!(all[0].requester, "did:example:a");
+
+        let other_did = ProofFilter {
+            did: Some("did:webvh:other:example.com"),
+            ..ProofFilter::default()
+        };
+        assert!(
+            list_proofs(&ks, &witness.witness_id, &other_did)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let future = ProofFilter {
+            since: Some(all[0].signed_at + 1),
+            ..ProofFilter::default()
+        };
+        assert!(
+            list_proofs(&ks, &witness.witness_id, &future)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn refuses_to_equivocate() {
+        let (ks, _dir) = test_witnesses_ks().await;
+        let witness = create_witness(&ks, None).await.unwrap();
+        let log = genesis_log().await;
+        let entry = verify_candidate(&log, None).unwrap();
+
+        // The witness already attested a different entry at this version.
+        let prior = ProofRecord {
+            witness_id: witness.witness_id.clone(),
+            did: entry.did.clone(),
+            scid: entry.scid.clone(),
+            version_id: "1-QmOther".into(),
+            entry_hash: "QmOther".into(),
+            signed_at: 0,
+            requester: "did:example:a".into(),
+            proof: serde_json::Value::Null,
+        };
+        ks.insert(proof_key(&witness.witness_id, &entry.scid, 1), &prior)
+            .await
+            .unwrap();
+
+        let err = sign_witness_proof(
+            &ks,
+            &crate::signing::LocalSigner,
+            &witness.witness_id,
+            &log,
+            None,
+            None,
+            "did:example:b",
+        )
+        .await
+        .expect_err("second entry hash for the same version must be refused");
+        assert!(matches!(err, AppError::Conflict(_)));
+    }
+
+    #[tokio::test]
+    async fn refuses_to_equivocate_across_domains() {
+        let (ks, _dir) = test_witnesses_ks().await;
+        let witness = create_witness(&ks, None).await.unwrap();
+        let log = genesis_log().await;
+        let entry = verify_candidate(&log, None).unwrap();
+
+        // The same log, moved to another domain, had a different entry
+        // attested at this version.
+        let moved = entry.did.replace(":example.com:", ":other.example:");
+        assert_ne!(moved, entry.did);
+        let prior = ProofRecord {
+            witness_id: witness.witness_id.clone(),
+            did: moved.clone(),
+            scid: entry.scid.clone(),
+            version_id: "1-QmOther".into(),
+            entry_hash: "QmOther".into(),
+            signed_at: 0,
+            requester: "did:example:a".into(),
+            proof: serde_json::Value::Null,
+        };
+        ks.insert(proof_key(&witness.witness_id, &entry.scid, 1), &prior)
+            .await
+            .unwrap();
+
+        let err = sign_witness_proof(
+            &ks,
+            &crate::signing::LocalSigner,
+            &witness.witness_id,
+            &log,
+            None,
+            None,
+            "did:example:b",
+        )
+        .await
+        .expect_err("a different entry for the same SCID and version must be refused");
+        assert!(matches!(err, AppError::Conflict(_)));
+        assert!(err.to_string().contains(&moved));
+
+        // A DID filter still lists only that DID's records.
+        let by_moved = ProofFilter {
+            did: Some(&moved),
+            ..ProofFilter::default()
+        };
+        let listed = list_proofs(&ks, &witness.witness_id, &by_moved)
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+        let by_original = ProofFilter {
+            did: Some(&entry.did),
+            ..ProofFilter::default()
+        };
+        assert!(
+            list_proofs(&ks, &witness.witness_id, &by_original)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn verify_candidate_rejects_garbage_and_multi_line_input() {
+        assert!(verify_candidate("", None).is_err());
+        assert!(verify_candidate("not json", None).is_err());
+        assert!(verify_candidate("{}\n{}", None).is_err());
+    }
 }